@@ -0,0 +1,135 @@
+use mongodb::{
+    bson::{doc, Document},
+    options::{FindOneOptions, UpdateOptions},
+};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, BlockTag, FieldElement},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+
+use crate::{models::AppState, rpc_queue::Priority, utils::to_hex};
+
+const ACTIVITY_COLLECTION: &str = "address_activity";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Activity {
+    Active,
+    Dormant,
+}
+
+/// Last nonce seen for an address, the nonce only tells how many transactions were sent so
+/// recent activity is detected by its changes between two checks
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ActivitySnapshot {
+    pub address: String,
+    pub nonce: String,
+    // unknown until the nonce changes after the first check
+    pub nonce_changed_at: Option<i64>,
+    pub last_renewal: Option<i64>,
+    pub checked_at: i64,
+}
+
+/// Active when the address sent a transaction or renewed a domain within the window
+pub fn get_activity(snapshot: &ActivitySnapshot, now: i64, window_secs: i64) -> Activity {
+    let recent = |timestamp: Option<i64>| matches!(timestamp, Some(t) if now - t <= window_secs);
+    if recent(snapshot.nonce_changed_at) || recent(snapshot.last_renewal) {
+        Activity::Active
+    } else {
+        Activity::Dormant
+    }
+}
+
+/// Next snapshot from the previous one and the values just fetched
+pub fn update_snapshot(
+    previous: Option<ActivitySnapshot>,
+    address: &FieldElement,
+    nonce: &FieldElement,
+    last_renewal: Option<i64>,
+    now: i64,
+) -> ActivitySnapshot {
+    let nonce = to_hex(nonce);
+    let nonce_changed_at = match previous {
+        Some(previous) if previous.nonce == nonce => previous.nonce_changed_at,
+        Some(_) => Some(now),
+        None => None,
+    };
+    ActivitySnapshot {
+        address: to_hex(address),
+        nonce,
+        nonce_changed_at,
+        last_renewal,
+        checked_at: now,
+    }
+}
+
+async fn get_last_renewal(state: &AppState, domain: &str) -> Option<i64> {
+    state
+        .starknetid_db
+        .collection::<Document>("renewals")
+        .find_one(
+            doc! { "domain": domain, "_cursor.to": null },
+            FindOneOptions::builder()
+                .sort(doc! { "timestamp": -1 })
+                .build(),
+        )
+        .await
+        .ok()??
+        .get_i64("timestamp")
+        .ok()
+}
+
+/// Activity of the owner of an identity, refreshed from the rpc once the cached snapshot
+/// is too old
+pub async fn get_address_activity(
+    state: &AppState,
+    address: &FieldElement,
+    domain: Option<&str>,
+) -> Option<Activity> {
+    let conf = &state.conf.activity;
+    let now = chrono::Utc::now().timestamp();
+    let collection = state
+        .starknetid_db
+        .collection::<ActivitySnapshot>(ACTIVITY_COLLECTION);
+    let previous = collection
+        .find_one(doc! { "address": to_hex(address) }, None)
+        .await
+        .ok()?;
+    if let Some(snapshot) = &previous {
+        if now - snapshot.checked_at < conf.cache_secs {
+            return Some(get_activity(snapshot, now, conf.window_secs));
+        }
+    }
+
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).ok()?,
+    ));
+    let nonce = state
+        .rpc_queue
+        .run(
+            Priority::Interactive,
+            "starknet_getNonce",
+            provider.get_nonce(BlockId::Tag(BlockTag::Latest), *address),
+        )
+        .await
+        .ok()?;
+    let last_renewal = match domain {
+        Some(domain) => get_last_renewal(state, domain).await,
+        None => None,
+    };
+    let snapshot = update_snapshot(previous, address, &nonce, last_renewal, now);
+    if let Ok(update) = mongodb::bson::to_document(&snapshot) {
+        let _ = state
+            .starknetid_db
+            .collection::<Document>(ACTIVITY_COLLECTION)
+            .update_one(
+                doc! { "address": &snapshot.address },
+                doc! { "$set": update },
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await;
+    }
+    Some(get_activity(&snapshot, now, conf.window_secs))
+}
@@ -0,0 +1,50 @@
+use std::{collections::HashMap, fs};
+
+use serde::{Deserialize, Serialize};
+use starknet::core::types::FieldElement;
+
+use crate::logger::Logger;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AddressLabel {
+    #[serde(skip_serializing)]
+    pub address: FieldElement,
+    pub label: String,
+    // exchange, bridge, protocol, token...
+    pub category: String,
+}
+
+/// Public tags of well known addresses, used when an address has no domain
+#[derive(Debug, Clone, Default)]
+pub struct AddressLabels {
+    labels: HashMap<FieldElement, AddressLabel>,
+}
+
+impl AddressLabels {
+    pub fn new(labels: Vec<AddressLabel>) -> Self {
+        AddressLabels {
+            labels: labels
+                .into_iter()
+                .map(|label| (label.address, label))
+                .collect(),
+        }
+    }
+
+    pub fn get(&self, address: &FieldElement) -> Option<&AddressLabel> {
+        self.labels.get(address)
+    }
+}
+
+/// Merges the datasets in order, a later dataset overrides the labels of the previous ones
+pub fn load_address_labels(datasets: &[String], logger: &Logger) -> AddressLabels {
+    let mut labels = vec![];
+    for path in datasets {
+        match fs::read_to_string(path).map(|data| serde_json::from_str::<Vec<AddressLabel>>(&data))
+        {
+            Ok(Ok(dataset)) => labels.extend(dataset),
+            Ok(Err(e)) => logger.warning(format!("Unable to parse address labels {}: {}", path, e)),
+            Err(e) => logger.warning(format!("Unable to load address labels {}: {}", path, e)),
+        }
+    }
+    AddressLabels::new(labels)
+}
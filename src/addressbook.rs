@@ -0,0 +1,81 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use mongodb::bson::doc;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use starknet::core::types::FieldElement;
+
+use crate::{
+    preferences::PREFERENCES_COLLECTION,
+    storage::{FindSpec, Storage},
+};
+
+// hashes accepted in one sync request
+pub const MAX_CONTACTS: usize = 1000;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ContactMatch {
+    pub hash: String,
+    pub address: String,
+    pub domain: String,
+}
+
+/// Identifier wallets hash their contacts with, the sha256 of the 32 bytes big endian
+/// address, so the server never receives the addresses which have no match
+pub fn get_contact_hash(address: &FieldElement) -> String {
+    hex::encode(Sha256::digest(address.to_bytes_be()))
+}
+
+/// Domains of the submitted contacts which opted in to the address book sync
+pub async fn resolve_contacts(
+    storage: &dyn Storage,
+    hashes: &[String],
+) -> Result<Vec<ContactMatch>> {
+    let hashes: Vec<String> = hashes.iter().map(|hash| hash.to_lowercase()).collect();
+    let opted_in = storage
+        .find(
+            PREFERENCES_COLLECTION,
+            doc! { "addressbook_discoverable": true, "contact_hash": { "$in": hashes } },
+            FindSpec::default(),
+        )
+        .await?;
+    let addresses: HashMap<String, String> = opted_in
+        .iter()
+        .filter_map(|doc| {
+            Some((
+                doc.get_str("address").ok()?.to_string(),
+                doc.get_str("contact_hash").ok()?.to_string(),
+            ))
+        })
+        .collect();
+    if addresses.is_empty() {
+        return Ok(vec![]);
+    }
+
+    // the reverse resolution of each address gives the name it chose to be displayed with
+    let domains = storage
+        .find(
+            "domains",
+            doc! {
+                "rev_address": { "$in": addresses.keys().cloned().collect::<Vec<_>>() },
+                "_cursor.to": null,
+            },
+            FindSpec::default(),
+        )
+        .await?;
+    let mut matches: Vec<ContactMatch> = domains
+        .iter()
+        .filter_map(|doc| {
+            let address = doc.get_str("rev_address").ok()?;
+            Some(ContactMatch {
+                hash: addresses.get(address)?.clone(),
+                address: address.to_string(),
+                domain: doc.get_str("domain").ok()?.to_string(),
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| a.hash.cmp(&b.hash));
+    matches.dedup_by(|a, b| a.hash == b.hash);
+    Ok(matches)
+}
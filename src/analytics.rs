@@ -0,0 +1,330 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::Result;
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, from_bson, from_slice, to_bson, to_vec, Bson, Document},
+    change_stream::event::{OperationType, ResumeToken},
+    options::{ChangeStreamOptions, FullDocumentType},
+};
+use tokio::time::sleep;
+use tokio_postgres::{types::ToSql, Client, NoTls};
+
+use crate::{config::Analytics, logger::Logger, metrics::labeled, models::AppState};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnType {
+    Text,
+    BigInt,
+}
+
+/// Mongo collection copied to a postgres table of the same name, `mongo_id` is the primary
+/// key and `cursor_to` is null for the live documents like `_cursor.to` in mongo
+pub struct MirroredTable {
+    pub name: &'static str,
+    // document field (dotted path) and column name
+    pub columns: &'static [(&'static str, &'static str, ColumnType)],
+}
+
+pub static MIRRORED_TABLES: [MirroredTable; 3] = [
+    MirroredTable {
+        name: "domains",
+        columns: &[
+            ("domain", "domain", ColumnType::Text),
+            ("id", "id", ColumnType::Text),
+            ("legacy_address", "legacy_address", ColumnType::Text),
+            ("creation_date", "creation_date", ColumnType::BigInt),
+            ("expiry", "expiry", ColumnType::BigInt),
+            ("_cursor.to", "cursor_to", ColumnType::BigInt),
+        ],
+    },
+    MirroredTable {
+        name: "id_owners",
+        columns: &[
+            ("id", "id", ColumnType::Text),
+            ("owner", "owner", ColumnType::Text),
+            ("_cursor.to", "cursor_to", ColumnType::BigInt),
+        ],
+    },
+    MirroredTable {
+        name: "renewals",
+        columns: &[
+            ("domain", "domain", ColumnType::Text),
+            ("timestamp", "timestamp", ColumnType::BigInt),
+            ("_cursor.to", "cursor_to", ColumnType::BigInt),
+        ],
+    },
+];
+
+// delay before watching a collection again after the change stream failed
+const RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Postgres copy of the indexed collections, only read by the stats endpoints
+pub struct AnalyticsMirror {
+    client: Client,
+}
+
+fn get_path<'a>(doc: &'a Document, path: &str) -> Option<&'a Bson> {
+    let mut parts = path.split('.');
+    let mut value = doc.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_document()?.get(part)?;
+    }
+    Some(value)
+}
+
+fn get_mongo_id(doc: &Document) -> Option<String> {
+    match doc.get("_id")? {
+        Bson::ObjectId(id) => Some(id.to_hex()),
+        id => Some(id.to_string()),
+    }
+}
+
+/// Column value of a field, missing or mistyped fields are stored as null
+pub fn get_column_value(
+    doc: &Document,
+    path: &str,
+    column_type: ColumnType,
+) -> Box<dyn ToSql + Sync + Send> {
+    let value = get_path(doc, path);
+    match column_type {
+        ColumnType::Text => Box::new(value.and_then(|value| value.as_str().map(String::from))),
+        ColumnType::BigInt => Box::new(match value {
+            Some(Bson::Int32(value)) => Some(*value as i64),
+            Some(Bson::Int64(value)) => Some(*value),
+            Some(Bson::Double(value)) => Some(*value as i64),
+            _ => None,
+        }),
+    }
+}
+
+pub fn get_create_statement(table: &MirroredTable) -> String {
+    let columns: Vec<String> = table
+        .columns
+        .iter()
+        .map(|(_, column, column_type)| {
+            let sql_type = match column_type {
+                ColumnType::Text => "TEXT",
+                ColumnType::BigInt => "BIGINT",
+            };
+            format!("{} {}", column, sql_type)
+        })
+        .collect();
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} (mongo_id TEXT PRIMARY KEY, {})",
+        table.name,
+        columns.join(", ")
+    )
+}
+
+pub fn get_upsert_statement(table: &MirroredTable) -> String {
+    let columns: Vec<&str> = table.columns.iter().map(|(_, column, _)| *column).collect();
+    let placeholders: Vec<String> = (1..=columns.len() + 1).map(|i| format!("${}", i)).collect();
+    let updates: Vec<String> = columns
+        .iter()
+        .map(|column| format!("{} = EXCLUDED.{}", column, column))
+        .collect();
+    format!(
+        "INSERT INTO {} (mongo_id, {}) VALUES ({}) ON CONFLICT (mongo_id) DO UPDATE SET {}",
+        table.name,
+        columns.join(", "),
+        placeholders.join(", "),
+        updates.join(", ")
+    )
+}
+
+impl AnalyticsMirror {
+    pub async fn connect(conf: &Analytics, logger: &Logger) -> Result<Option<Self>> {
+        if conf.postgres_url.is_empty() {
+            return Ok(None);
+        }
+        let (client, connection) = tokio_postgres::connect(&conf.postgres_url, NoTls).await?;
+        let logger = logger.clone();
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                logger.severe(format!("analytics: postgres connection closed: {}", e));
+            }
+        });
+        client
+            .batch_execute(
+                "CREATE TABLE IF NOT EXISTS mirror_state (collection TEXT PRIMARY KEY, resume_token BYTEA NOT NULL)",
+            )
+            .await?;
+        for table in &MIRRORED_TABLES {
+            client.batch_execute(&get_create_statement(table)).await?;
+        }
+        client
+            .batch_execute(
+                "CREATE INDEX IF NOT EXISTS domains_creation_date ON domains (creation_date) WHERE cursor_to IS NULL;
+                CREATE INDEX IF NOT EXISTS renewals_timestamp ON renewals (timestamp) WHERE cursor_to IS NULL",
+            )
+            .await?;
+        Ok(Some(AnalyticsMirror { client }))
+    }
+
+    async fn upsert(&self, table: &MirroredTable, doc: &Document) -> Result<()> {
+        let mongo_id = match get_mongo_id(doc) {
+            Some(mongo_id) => mongo_id,
+            None => return Ok(()),
+        };
+        let mut values: Vec<Box<dyn ToSql + Sync + Send>> = vec![Box::new(mongo_id)];
+        values.extend(
+            table
+                .columns
+                .iter()
+                .map(|(path, _, column_type)| get_column_value(doc, path, *column_type)),
+        );
+        let params: Vec<&(dyn ToSql + Sync)> = values
+            .iter()
+            .map(|value| value.as_ref() as &(dyn ToSql + Sync))
+            .collect();
+        self.client
+            .execute(&get_upsert_statement(table), &params)
+            .await?;
+        Ok(())
+    }
+
+    async fn delete(&self, table: &MirroredTable, key: &Document) -> Result<()> {
+        if let Some(mongo_id) = get_mongo_id(key) {
+            self.client
+                .execute(
+                    &format!("DELETE FROM {} WHERE mongo_id = $1", table.name),
+                    &[&mongo_id],
+                )
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn get_resume_token(&self, table: &MirroredTable) -> Result<Option<ResumeToken>> {
+        let row = self
+            .client
+            .query_opt(
+                "SELECT resume_token FROM mirror_state WHERE collection = $1",
+                &[&table.name],
+            )
+            .await?;
+        Ok(match row {
+            Some(row) => {
+                let doc: Document = from_slice(row.get::<_, &[u8]>(0))?;
+                Some(from_bson(doc.get("token").cloned().unwrap_or(Bson::Null))?)
+            }
+            None => None,
+        })
+    }
+
+    async fn save_resume_token(&self, table: &MirroredTable, token: &ResumeToken) -> Result<()> {
+        self.client
+            .execute(
+                "INSERT INTO mirror_state (collection, resume_token) VALUES ($1, $2)
+                ON CONFLICT (collection) DO UPDATE SET resume_token = EXCLUDED.resume_token",
+                &[&table.name, &to_vec(&doc! { "token": to_bson(token)? })?],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Live documents created in [begin, end] grouped in segments of `delta` seconds
+    pub async fn count_segments(
+        &self,
+        table: &str,
+        time_column: &str,
+        begin: i64,
+        end: i64,
+        delta: i64,
+    ) -> Result<Vec<(i64, i64)>> {
+        let query = format!(
+            "SELECT $1 + (({column} - $1) / $3) * $3 AS segment, COUNT(*) FROM {table}
+            WHERE cursor_to IS NULL AND {column} BETWEEN $1 AND $2
+            GROUP BY segment ORDER BY segment",
+            column = time_column,
+            table = table,
+        );
+        let rows = self.client.query(&query, &[&begin, &end, &delta]).await?;
+        Ok(rows
+            .iter()
+            .map(|row| (row.get::<_, i64>(0), row.get::<_, i64>(1)))
+            .collect())
+    }
+
+    /// Live domains created since `since` and not expired at `now`
+    pub async fn count_domains(&self, since: i64, now: i64) -> Result<u64> {
+        let row = self
+            .client
+            .query_one(
+                "SELECT COUNT(*) FROM domains
+                WHERE cursor_to IS NULL AND expiry >= $1 AND creation_date >= $2",
+                &[&now, &since],
+            )
+            .await?;
+        Ok(row.get::<_, i64>(0) as u64)
+    }
+}
+
+// copies the whole collection, then applies its changes from the time the copy started
+async fn mirror_collection(
+    state: &AppState,
+    mirror: &AnalyticsMirror,
+    table: &MirroredTable,
+) -> Result<()> {
+    let collection = state.starknetid_db.collection::<Document>(table.name);
+    let resume_token = mirror.get_resume_token(table).await?;
+    let options = ChangeStreamOptions::builder()
+        .full_document(Some(FullDocumentType::UpdateLookup))
+        .resume_after(resume_token.clone())
+        .build();
+    let mut stream = collection.watch(None, options).await?;
+
+    if resume_token.is_none() {
+        let mut cursor = collection.find(doc! {}, None).await?;
+        while let Some(doc) = cursor.next().await {
+            mirror.upsert(table, &doc?).await?;
+        }
+        if let Some(token) = stream.resume_token() {
+            mirror.save_resume_token(table, &token).await?;
+        }
+    }
+
+    let events_metric = labeled("analytics_mirror_events_total", "collection", table.name);
+    while let Some(event) = stream.next().await {
+        let event = event?;
+        match event.operation_type {
+            OperationType::Insert | OperationType::Update | OperationType::Replace => {
+                if let Some(doc) = &event.full_document {
+                    mirror.upsert(table, doc).await?;
+                }
+            }
+            OperationType::Delete => {
+                if let Some(key) = &event.document_key {
+                    mirror.delete(table, key).await?;
+                }
+            }
+            _ => {}
+        }
+        mirror.save_resume_token(table, &event.id).await?;
+        state.metrics.add(&events_metric, 1);
+    }
+    Ok(())
+}
+
+/// Keeps the mirror of each collection up to date, until the process exits
+pub fn start(state: &Arc<AppState>) {
+    if state.analytics.is_none() {
+        return;
+    }
+    for table in &MIRRORED_TABLES {
+        let state = state.clone();
+        tokio::spawn(async move {
+            let mirror = state.analytics.as_ref().unwrap();
+            loop {
+                if let Err(e) = mirror_collection(&state, mirror, table).await {
+                    state.logger.warning(format!(
+                        "analytics: mirror of {} interrupted: {}",
+                        table.name, e
+                    ));
+                }
+                sleep(RETRY_DELAY).await;
+            }
+        });
+    }
+}
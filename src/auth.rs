@@ -0,0 +1,131 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use axum::http::HeaderMap;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, BlockTag, FieldElement, FunctionCall},
+    macros::{selector, short_string},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+
+use crate::{models::AppState, rpc_queue::Priority};
+
+// a disabled key keeps working for this long on the instances which cached it
+const API_KEY_TTL_SECS: i64 = 30;
+// expired lookups are dropped past this many cached keys
+const MAX_CACHED_KEYS: usize = 10_000;
+
+/// Partner API key, stored in the `api_keys` collection and sent in the `x-api-key` header
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ApiKey {
+    // the rate limits count the requests by id, a name could be reused by a later key
+    #[serde(rename = "_id")]
+    pub id: ObjectId,
+    pub key: String,
+    pub name: String,
+    // root domains or TLDs this partner is allowed to customize, eg: "braavos.stark"
+    pub namespaces: Vec<String>,
+    pub enabled: bool,
+    // picks the burst credits of the key in `rate_limits.burst_tiers`
+    #[serde(default)]
+    pub tier: Option<String>,
+}
+
+impl ApiKey {
+    pub fn owns_namespace(&self, namespace: &str) -> bool {
+        self.namespaces.iter().any(|owned| owned == namespace)
+    }
+}
+
+pub fn is_admin(state: &AppState, headers: &HeaderMap) -> bool {
+    let expected = &state.conf.admin.api_key;
+    // an empty key disables the admin endpoints entirely
+    !expected.is_empty()
+        && headers
+            .get("x-admin-key")
+            .and_then(|value| value.to_str().ok())
+            .map_or(false, |value| value == expected)
+}
+
+/// Recent lookups of the x-api-key headers, None for the unknown or disabled keys so that
+/// the invalid ones don't each cost a query
+#[derive(Default)]
+pub struct ApiKeyCache {
+    entries: Mutex<HashMap<String, (i64, Option<ApiKey>)>>,
+}
+
+impl ApiKeyCache {
+    pub fn get(&self, key: &str, now: i64) -> Option<Option<ApiKey>> {
+        match self.entries.lock().unwrap().get(key) {
+            Some((expires_at, api_key)) if *expires_at > now => Some(api_key.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&self, key: &str, api_key: Option<ApiKey>, now: i64) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_CACHED_KEYS {
+            entries.retain(|_, (expires_at, _)| *expires_at > now);
+        }
+        // a flood of random keys doesn't grow the cache past its size
+        if entries.len() >= MAX_CACHED_KEYS {
+            entries.clear();
+        }
+        entries.insert(key.to_string(), (now + API_KEY_TTL_SECS, api_key));
+    }
+}
+
+pub async fn get_api_key(state: &AppState, headers: &HeaderMap) -> Option<ApiKey> {
+    let key = headers.get("x-api-key")?.to_str().ok()?;
+    let now = chrono::Utc::now().timestamp();
+    if let Some(api_key) = state.api_key_cache.get(key, now) {
+        return api_key;
+    }
+    // the database errors aren't cached, the next request tries again
+    let doc = state
+        .starknetid_db
+        .collection::<Document>("api_keys")
+        .find_one(doc! { "key": key, "enabled": true }, None)
+        .await
+        .ok()?;
+    let api_key = doc.and_then(|doc| mongodb::bson::from_document::<ApiKey>(doc).ok());
+    state.api_key_cache.insert(key, api_key.clone(), now);
+    api_key
+}
+
+/// Checks a signature of `hash` against the account contract deployed at `addr`,
+/// which lets any account type (argent, braavos, multisig...) authenticate
+pub async fn verify_account_signature(
+    state: &AppState,
+    addr: FieldElement,
+    hash: FieldElement,
+    signature: &[FieldElement],
+) -> bool {
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).unwrap(),
+    ));
+    let mut calldata = vec![hash, FieldElement::from(signature.len())];
+    calldata.extend_from_slice(signature);
+
+    let call = provider.call(
+        FunctionCall {
+            contract_address: addr,
+            entry_point_selector: selector!("is_valid_signature"),
+            calldata,
+        },
+        BlockId::Tag(BlockTag::Latest),
+    );
+    match state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_call", call)
+        .await
+    {
+        // cairo 0 accounts return 1 while cairo 1 accounts return 'VALID'
+        Ok(result) => result.first().map_or(false, |value| {
+            *value == FieldElement::ONE || *value == short_string!("VALID")
+        }),
+        Err(_) => false,
+    }
+}
@@ -0,0 +1,240 @@
+use anyhow::{anyhow, Result};
+use mongodb::bson::doc;
+use reqwest::Url;
+use serde::Serialize;
+use starknet::{
+    core::types::{BlockId, BlockTag, FieldElement, FunctionCall},
+    macros::selector,
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+
+use crate::{
+    models::AppState,
+    pricing::{get_label_length, get_price_per_year},
+    rpc_queue::Priority,
+    storage::{FindSpec, Storage},
+    utils::to_hex,
+};
+
+const DOMAINS: &str = "domains";
+const OWNERS: &str = "id_owners";
+
+const ETH_FLOWS: &str = "auto_renew_flows";
+const ALTCOIN_FLOWS: &str = "auto_renew_flows_altcoins";
+
+/// Enabled subscription of the owner of a domain, as indexed
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flow {
+    pub renewer: FieldElement,
+    // the autorenewal contract spending the token
+    pub contract: FieldElement,
+    pub token: FieldElement,
+    // altcoins are priced by a quote at renewal time
+    pub yearly_price: Option<u128>,
+}
+
+/// Funds the autorenewal contract can take for a subscription, read on chain
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SubscriptionStatus {
+    pub contract: String,
+    pub token: String,
+    pub allowance: String,
+    pub balance: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub yearly_price: Option<String>,
+    // renewals the allowance and balance both cover, unknown for the altcoins
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renewals_covered: Option<u64>,
+}
+
+/// Subscriptions of a domain combined with what the autorenewal contracts can take for them
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RenewalStatus {
+    pub domain: String,
+    pub id: String,
+    pub expiry: i64,
+    pub owner: Option<String>,
+    // the owner has an enabled subscription
+    pub enabled: bool,
+    // most renewals one of the eth subscriptions covers
+    pub renewals_covered: Option<u64>,
+    pub subscriptions: Vec<SubscriptionStatus>,
+}
+
+/// Renewals of a year the token amounts cover, the smallest of the allowance and balance
+pub fn get_renewals_covered(allowance: u128, balance: u128, yearly_price: u128) -> u64 {
+    match yearly_price {
+        0 => 0,
+        price => (allowance.min(balance) / price).min(u64::MAX as u128) as u64,
+    }
+}
+
+/// Subscriptions of the current owner, an old owner's one doesn't renew the domain
+pub async fn get_flows(
+    storage: &dyn Storage,
+    domain: &str,
+    owner: &str,
+    eth_contract: &FieldElement,
+    eth_token: &FieldElement,
+    altcoins: impl Fn(&FieldElement) -> Option<FieldElement>,
+) -> Result<Vec<Flow>> {
+    let root = domain.strip_suffix(".stark").unwrap_or(domain);
+    let mut flows = vec![];
+    for collection in [ETH_FLOWS, ALTCOIN_FLOWS] {
+        let docs = storage
+            .find(
+                collection,
+                doc! {
+                    "domain": domain,
+                    "renewer_address": owner,
+                    "enabled": true,
+                    "_cursor.to": null,
+                },
+                FindSpec::default(),
+            )
+            .await?;
+        for doc in docs {
+            let contract = doc
+                .get_str("auto_renew_contract")
+                .ok()
+                .and_then(|contract| FieldElement::from_hex_be(contract).ok());
+            let (contract, token, yearly_price) = match (collection, contract) {
+                (ETH_FLOWS, contract) => (
+                    contract.unwrap_or(*eth_contract),
+                    *eth_token,
+                    get_price_per_year(get_label_length(root)),
+                ),
+                (_, Some(contract)) => match altcoins(&contract) {
+                    Some(token) => (contract, token, None),
+                    None => continue,
+                },
+                _ => continue,
+            };
+            flows.push(Flow {
+                renewer: FieldElement::from_hex_be(owner)?,
+                contract,
+                token,
+                yearly_price,
+            });
+        }
+    }
+    Ok(flows)
+}
+
+// amount of a u256 result, the unlimited approvals saturate
+fn to_amount(result: &[FieldElement]) -> Result<u128> {
+    let (low, high) = match result {
+        [low, high, ..] => (*low, *high),
+        [low] => (*low, FieldElement::ZERO),
+        [] => return Err(anyhow!("Empty amount")),
+    };
+    if high != FieldElement::ZERO {
+        return Ok(u128::MAX);
+    }
+    u128::try_from(low).map_err(|_| anyhow!("Invalid amount"))
+}
+
+async fn call_token(
+    state: &AppState,
+    provider: &JsonRpcClient<HttpTransport>,
+    token: FieldElement,
+    entry_point_selector: FieldElement,
+    calldata: Vec<FieldElement>,
+) -> Result<u128> {
+    let call = provider.call(
+        FunctionCall {
+            contract_address: token,
+            entry_point_selector,
+            calldata,
+        },
+        BlockId::Tag(BlockTag::Latest),
+    );
+    let result = state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_call", call)
+        .await?;
+    to_amount(&result)
+}
+
+/// Allowance of the autorenewal contract and balance of the renewer for a subscription
+pub async fn get_subscription_status(state: &AppState, flow: &Flow) -> Result<SubscriptionStatus> {
+    let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(
+        &state.conf.variables.rpc_url,
+    )?));
+    let (allowance, balance) = tokio::try_join!(
+        call_token(
+            state,
+            &provider,
+            flow.token,
+            selector!("allowance"),
+            vec![flow.renewer, flow.contract],
+        ),
+        call_token(
+            state,
+            &provider,
+            flow.token,
+            selector!("balanceOf"),
+            vec![flow.renewer],
+        ),
+    )?;
+    Ok(SubscriptionStatus {
+        contract: to_hex(&flow.contract),
+        token: to_hex(&flow.token),
+        allowance: allowance.to_string(),
+        balance: balance.to_string(),
+        yearly_price: flow.yearly_price.map(|price| price.to_string()),
+        renewals_covered: flow
+            .yearly_price
+            .map(|price| get_renewals_covered(allowance, balance, price)),
+    })
+}
+
+/// Status of a domain, None when it isn't indexed
+pub async fn get_renewal_status(state: &AppState, domain: &str) -> Result<Option<RenewalStatus>> {
+    let storage = state.storage.as_ref();
+    let doc = match storage
+        .find_one(DOMAINS, doc! { "domain": domain, "_cursor.to": null })
+        .await?
+    {
+        Some(doc) => doc,
+        None => return Ok(None),
+    };
+    let id = doc.get_str("id").unwrap_or_default().to_string();
+    let owner = storage
+        .find_one(OWNERS, doc! { "id": &id, "_cursor.to": null })
+        .await?
+        .and_then(|doc| doc.get_str("owner").ok().map(String::from));
+
+    let mut subscriptions = vec![];
+    if let Some(owner) = &owner {
+        let conf = &state.conf;
+        let flows = get_flows(
+            storage,
+            domain,
+            owner,
+            &conf.auto_renewal.eth_contract,
+            &conf.auto_renewal.eth_token,
+            |contract| {
+                conf.subscription_to_altcoin
+                    .get(contract)
+                    .and_then(|token| FieldElement::from_hex_be(token).ok())
+            },
+        )
+        .await?;
+        for flow in &flows {
+            subscriptions.push(get_subscription_status(state, flow).await?);
+        }
+    }
+    Ok(Some(RenewalStatus {
+        domain: domain.to_string(),
+        id,
+        expiry: doc.get_i64("expiry").unwrap_or_default(),
+        owner,
+        enabled: !subscriptions.is_empty(),
+        renewals_covered: subscriptions
+            .iter()
+            .filter_map(|subscription| subscription.renewals_covered)
+            .max(),
+        subscriptions,
+    }))
+}
@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Result};
+use ethers::{
+    abi::{decode, encode, ParamType, Token},
+    types::{H160, U256},
+};
+use serde_json::{json, Value};
+
+// selectors of ownerOf(uint256), tokenURI(uint256), balanceOf(address,uint256) and uri(uint256)
+const OWNER_OF: [u8; 4] = [0x63, 0x52, 0x21, 0x1e];
+const TOKEN_URI: [u8; 4] = [0xc8, 0x7b, 0x56, 0xdd];
+const BALANCE_OF: [u8; 4] = [0x00, 0xfd, 0xd5, 0x8e];
+const URI: [u8; 4] = [0x0e, 0x89, 0x34, 0x1c];
+
+async fn eth_call(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    to: &H160,
+    data: Vec<u8>,
+) -> Result<Vec<u8>> {
+    let body = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_call",
+        "params": [{ "to": format!("{:?}", to), "data": format!("0x{}", hex::encode(data)) }, "latest"],
+    });
+    let response = client
+        .post(rpc_url)
+        .json(&body)
+        .send()
+        .await?
+        .json::<Value>()
+        .await?;
+    if let Some(error) = response.get("error") {
+        return Err(anyhow!("eth_call failed: {}", error));
+    }
+    let result = response["result"]
+        .as_str()
+        .ok_or_else(|| anyhow!("eth_call returned no result"))?;
+    Ok(hex::decode(result.trim_start_matches("0x"))?)
+}
+
+fn call_data(selector: [u8; 4], args: &[Token]) -> Vec<u8> {
+    let mut data = selector.to_vec();
+    data.extend(encode(args));
+    data
+}
+
+async fn call_string(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    contract: &H160,
+    selector: [u8; 4],
+    token_id: U256,
+) -> Result<String> {
+    let result = eth_call(
+        client,
+        rpc_url,
+        contract,
+        call_data(selector, &[Token::Uint(token_id)]),
+    )
+    .await?;
+    match decode(&[ParamType::String], &result)?.pop() {
+        Some(Token::String(uri)) => Ok(uri),
+        _ => Err(anyhow!("Invalid token uri")),
+    }
+}
+
+/// Metadata uri of an erc721 token
+pub async fn get_erc721_uri(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    contract: &H160,
+    token_id: U256,
+) -> Result<String> {
+    call_string(client, rpc_url, contract, TOKEN_URI, token_id).await
+}
+
+/// Metadata uri of an erc1155 token, with its `{id}` placeholder replaced
+pub async fn get_erc1155_uri(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    contract: &H160,
+    token_id: U256,
+) -> Result<String> {
+    let uri = call_string(client, rpc_url, contract, URI, token_id).await?;
+    Ok(expand_erc1155_id(&uri, token_id))
+}
+
+/// The `{id}` of an erc1155 uri is the lowercase hex token id padded to 64 characters
+pub fn expand_erc1155_id(uri: &str, token_id: U256) -> String {
+    let mut bytes = [0_u8; 32];
+    token_id.to_big_endian(&mut bytes);
+    uri.replace("{id}", &hex::encode(bytes))
+}
+
+pub async fn is_erc721_owner(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    contract: &H160,
+    token_id: U256,
+    owner: &H160,
+) -> Result<bool> {
+    let result = eth_call(
+        client,
+        rpc_url,
+        contract,
+        call_data(OWNER_OF, &[Token::Uint(token_id)]),
+    )
+    .await?;
+    match decode(&[ParamType::Address], &result)?.pop() {
+        Some(Token::Address(address)) => Ok(address == *owner),
+        _ => Err(anyhow!("Invalid token owner")),
+    }
+}
+
+pub async fn is_erc1155_owner(
+    client: &reqwest::Client,
+    rpc_url: &str,
+    contract: &H160,
+    token_id: U256,
+    owner: &H160,
+) -> Result<bool> {
+    let result = eth_call(
+        client,
+        rpc_url,
+        contract,
+        call_data(BALANCE_OF, &[Token::Address(*owner), Token::Uint(token_id)]),
+    )
+    .await?;
+    match decode(&[ParamType::Uint(256)], &result)?.pop() {
+        Some(Token::Uint(balance)) => Ok(!balance.is_zero()),
+        _ => Err(anyhow!("Invalid token balance")),
+    }
+}
@@ -0,0 +1,314 @@
+mod eip155;
+
+use std::{str::FromStr, time::Duration};
+
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ethers::types::{H160, U256};
+use mongodb::bson::doc;
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::Value;
+use starknet::{
+    core::{
+        types::{BlockId, BlockTag, FieldElement, FunctionCall},
+        utils::{cairo_short_string_to_felt, parse_cairo_short_string},
+    },
+    macros::selector,
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+
+use crate::{
+    config::Config,
+    images::{check_fetchable, read_body},
+    models::AppState,
+    records::{get_record, get_unbounded_user_data, Chain},
+    rpc_queue::Priority,
+    utils::{parse_image_url, resolve_image_url, to_hex},
+};
+
+pub use eip155::expand_erc1155_id;
+
+// fields of the token metadata holding its image, in order of preference
+const IMAGE_FIELDS: [&str; 3] = ["image", "image_url", "image_data"];
+// token metadata larger than this is refused
+const MAX_METADATA_SIZE: usize = 1 << 20;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NftStandard {
+    Erc721,
+    Erc1155,
+}
+
+/// Avatar set by an identity, either an image or a token whose image is shown
+#[derive(Debug, Clone, PartialEq)]
+pub enum AvatarUri {
+    // https, ipfs or data uri used as is
+    Url(String),
+    // starknet erc721 token, nft:<contract>/<token id>
+    Starknet {
+        contract: FieldElement,
+        token_id: U256,
+    },
+    // ENSIP-12 token, eip155:<chain id>/<erc721|erc1155>:<contract>/<token id>
+    Eip155 {
+        chain_id: u64,
+        standard: NftStandard,
+        contract: H160,
+        token_id: U256,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Avatar {
+    // avatar field of the identity
+    pub uri: String,
+    pub image: String,
+}
+
+fn parse_token_id(token_id: &str) -> Result<U256> {
+    match token_id.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_dec_str(token_id).ok(),
+    }
+    .ok_or_else(|| anyhow!("Invalid token id"))
+}
+
+impl AvatarUri {
+    pub fn parse(uri: &str) -> Result<Self> {
+        if let Some(token) = uri.strip_prefix("nft:") {
+            let (contract, token_id) = token
+                .split_once('/')
+                .ok_or_else(|| anyhow!("An nft avatar is nft:<contract>/<token id>"))?;
+            return Ok(AvatarUri::Starknet {
+                contract: FieldElement::from_hex_be(contract)
+                    .map_err(|_| anyhow!("Invalid nft contract"))?,
+                token_id: parse_token_id(token_id)?,
+            });
+        }
+        if let Some(token) = uri.strip_prefix("eip155:") {
+            let parts: Vec<&str> = token.split('/').collect();
+            let (chain_id, asset, token_id) = match parts[..] {
+                [chain_id, asset, token_id] => (chain_id, asset, token_id),
+                _ => {
+                    return Err(anyhow!(
+                        "An eip155 avatar is eip155:<chain id>/<standard>:<contract>/<token id>"
+                    ))
+                }
+            };
+            let (standard, contract) = asset
+                .split_once(':')
+                .ok_or_else(|| anyhow!("Invalid eip155 asset"))?;
+            let standard = match standard.to_lowercase().as_str() {
+                "erc721" => NftStandard::Erc721,
+                "erc1155" => NftStandard::Erc1155,
+                _ => return Err(anyhow!("Unsupported token standard {}", standard)),
+            };
+            return Ok(AvatarUri::Eip155 {
+                chain_id: chain_id.parse().map_err(|_| anyhow!("Invalid chain id"))?,
+                standard,
+                contract: H160::from_str(contract).map_err(|_| anyhow!("Invalid contract"))?,
+                token_id: parse_token_id(token_id)?,
+            });
+        }
+        if ["https://", "http://", "ipfs://", "data:image/"]
+            .iter()
+            .any(|scheme| uri.starts_with(scheme))
+        {
+            return Ok(AvatarUri::Url(uri.to_string()));
+        }
+        Err(anyhow!("Unsupported avatar uri"))
+    }
+}
+
+/// Json of a `data:application/json` uri, base64 encoded or not
+pub fn decode_data_json(uri: &str) -> Result<Value> {
+    let (header, data) = uri
+        .split_once(',')
+        .ok_or_else(|| anyhow!("Invalid data uri"))?;
+    let data = if header.ends_with(";base64") {
+        STANDARD.decode(data)?
+    } else {
+        data.as_bytes().to_vec()
+    };
+    Ok(serde_json::from_slice(&data)?)
+}
+
+/// Image of a token metadata, with its ipfs uri resolved through the gateway
+pub fn get_image(config: &Config, metadata: &Value) -> Option<String> {
+    IMAGE_FIELDS
+        .iter()
+        .find_map(|field| metadata[field].as_str().filter(|image| !image.is_empty()))
+        .map(|image| parse_image_url(config, image))
+}
+
+async fn get_metadata_image(
+    client: &reqwest::Client,
+    config: &Config,
+    uri: &str,
+) -> Result<String> {
+    let metadata = if uri.starts_with("data:application/json") {
+        decode_data_json(uri)?
+    } else {
+        let url = resolve_image_url(config, uri).await;
+        check_fetchable(config, &url).await?;
+        let response = client.get(url).send().await?.error_for_status()?;
+        serde_json::from_slice(&read_body(response, MAX_METADATA_SIZE).await?)?
+    };
+    get_image(config, &metadata).ok_or_else(|| anyhow!("The token metadata has no image"))
+}
+
+/// Low and high felts of an u256
+pub fn split_u256(value: U256) -> (FieldElement, FieldElement) {
+    let mut bytes = [0_u8; 32];
+    value.to_big_endian(&mut bytes);
+    // 16 bytes always fit in a felt
+    let felt = |bytes: &[u8]| FieldElement::from_byte_slice_be(bytes).unwrap();
+    (felt(&bytes[16..]), felt(&bytes[..16]))
+}
+
+async fn starknet_call(
+    state: &AppState,
+    contract: FieldElement,
+    selector: FieldElement,
+    calldata: Vec<FieldElement>,
+) -> Result<Vec<FieldElement>> {
+    let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(
+        &state.conf.variables.rpc_url,
+    )?));
+    let call = provider.call(
+        FunctionCall {
+            contract_address: contract,
+            entry_point_selector: selector,
+            calldata,
+        },
+        BlockId::Tag(BlockTag::Latest),
+    );
+    Ok(state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_call", call)
+        .await?)
+}
+
+async fn get_starknet_image(
+    state: &AppState,
+    client: &reqwest::Client,
+    id: FieldElement,
+    contract: FieldElement,
+    token_id: U256,
+) -> Result<String> {
+    let owner = state
+        .storage
+        .find_one("id_owners", doc! { "id": to_hex(&id), "_cursor.to": null })
+        .await?
+        .and_then(|doc| FieldElement::from_hex_be(doc.get_str("owner").ok()?).ok())
+        .ok_or_else(|| anyhow!("Identity not found"))?;
+    let (low, high) = split_u256(token_id);
+    let nft_owner = starknet_call(state, contract, selector!("ownerOf"), vec![low, high]).await?;
+    if nft_owner.first() != Some(&owner) {
+        return Err(anyhow!("The avatar nft isn't owned by the identity owner"));
+    }
+    // the uri is an array of short strings starting with its length
+    let uri = starknet_call(state, contract, selector!("tokenURI"), vec![low, high])
+        .await?
+        .iter()
+        .skip(1)
+        .map(parse_cairo_short_string)
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    get_metadata_image(client, &state.conf, &uri).await
+}
+
+async fn get_eip155_image(
+    state: &AppState,
+    client: &reqwest::Client,
+    id: FieldElement,
+    chain_id: u64,
+    standard: NftStandard,
+    contract: H160,
+    token_id: U256,
+) -> Result<String> {
+    let rpc_url = state
+        .conf
+        .avatars
+        .evm_rpc_urls
+        .get(&chain_id.to_string())
+        .ok_or_else(|| anyhow!("Avatars of chain {} can't be verified", chain_id))?;
+    // the token must be held by the evm address record of the identity
+    let owner = get_record(state, id, Chain::Evm)
+        .await?
+        .ok_or_else(|| anyhow!("The identity has no evm address holding the avatar nft"))?;
+    let owner = H160::from_str(&owner.address)?;
+    let (owned, uri) = match standard {
+        NftStandard::Erc721 => (
+            eip155::is_erc721_owner(client, rpc_url, &contract, token_id, &owner).await?,
+            eip155::get_erc721_uri(client, rpc_url, &contract, token_id).await?,
+        ),
+        NftStandard::Erc1155 => (
+            eip155::is_erc1155_owner(client, rpc_url, &contract, token_id, &owner).await?,
+            eip155::get_erc1155_uri(client, rpc_url, &contract, token_id).await?,
+        ),
+    };
+    if !owned {
+        return Err(anyhow!("The avatar nft isn't owned by the identity"));
+    }
+    get_metadata_image(client, &state.conf, &uri).await
+}
+
+/// Identity of a domain, None when it isn't registered or the grace policy hides it
+pub async fn get_domain_id(state: &AppState, domain: &str) -> Result<Option<FieldElement>> {
+    let domain_doc = match state
+        .storage
+        .find_one("domains", doc! { "domain": domain, "_cursor.to": null })
+        .await?
+    {
+        Some(domain_doc) => domain_doc,
+        None => return Ok(None),
+    };
+    let display = state.conf.grace.get_display(
+        domain_doc.get_i64("expiry").ok(),
+        chrono::Utc::now().timestamp(),
+    );
+    Ok(domain_doc
+        .get_str("id")
+        .ok()
+        .and_then(|id| FieldElement::from_hex_be(id).ok())
+        .filter(|_| display.resolves))
+}
+
+pub fn get_client(config: &Config) -> Result<reqwest::Client> {
+    // redirects are refused, they could point back to the internal network
+    Ok(reqwest::Client::builder()
+        .timeout(Duration::from_millis(config.avatars.fetch_timeout_ms))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?)
+}
+
+/// Image of the avatar of an identity, the tokens are dereferenced once their ownership
+/// is verified. None when no avatar is set
+pub async fn resolve_avatar(state: &AppState, id: FieldElement) -> Result<Option<Avatar>> {
+    let felts = get_unbounded_user_data(state, id, cairo_short_string_to_felt("avatar")?).await?;
+    if felts.is_empty() {
+        return Ok(None);
+    }
+    let uri = felts
+        .iter()
+        .map(parse_cairo_short_string)
+        .collect::<Result<Vec<_>, _>>()?
+        .concat();
+    let client = get_client(&state.conf)?;
+    let image = match AvatarUri::parse(&uri)? {
+        AvatarUri::Url(url) => resolve_image_url(&state.conf, &url).await,
+        AvatarUri::Starknet { contract, token_id } => {
+            get_starknet_image(state, &client, id, contract, token_id).await?
+        }
+        AvatarUri::Eip155 {
+            chain_id,
+            standard,
+            contract,
+            token_id,
+        } => get_eip155_image(state, &client, id, chain_id, standard, contract, token_id).await?,
+    };
+    Ok(Some(Avatar { uri, image }))
+}
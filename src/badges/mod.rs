@@ -0,0 +1,173 @@
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize};
+use starknet::core::{types::FieldElement, utils::cairo_short_string_to_felt};
+use std::fs;
+
+use crate::{
+    logger::Logger,
+    models::{AppState, IdentityData},
+};
+
+const SECONDS_PER_YEAR: u64 = 365 * 24 * 3600;
+
+#[derive(Deserialize, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BadgeCondition {
+    // the domain was created before this timestamp
+    RegisteredBefore {
+        timestamp: u64,
+    },
+    // the domain is registered for at least this many years (creation to expiry)
+    RegistrationYears {
+        years: u64,
+    },
+    // every listed field has been verified by one of the configured verifiers
+    Verified {
+        fields: Vec<String>,
+    },
+    // the domain name matches this regex, compiled when the rules are loaded
+    DomainMatches {
+        #[serde(deserialize_with = "deserialize_regex")]
+        pattern: Regex,
+    },
+    // the domain was claimed by the contract it resolves to
+    ContractVerified,
+}
+
+fn deserialize_regex<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Regex, D::Error> {
+    let pattern = String::deserialize(deserializer)?;
+    Regex::new(&pattern).map_err(serde::de::Error::custom)
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct BadgeRule {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub condition: BadgeCondition,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct BadgeRules {
+    pub badges: Vec<BadgeRule>,
+}
+
+// rules parsed one by one so that an invalid one doesn't drop the others
+#[derive(Deserialize)]
+struct RawBadgeRules {
+    badges: Vec<serde_json::Value>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Badge {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+}
+
+/// Indexed facts about an identity that badge conditions are evaluated against
+#[derive(Default)]
+pub struct BadgeSubject {
+    pub domain: Option<String>,
+    pub creation_date: Option<u64>,
+    pub expiry: Option<u64>,
+    pub verified_fields: Vec<FieldElement>,
+    pub contract_verified: bool,
+}
+
+impl BadgeCondition {
+    pub fn is_met(&self, subject: &BadgeSubject) -> bool {
+        match self {
+            BadgeCondition::RegisteredBefore { timestamp } => subject
+                .creation_date
+                .map_or(false, |date| date < *timestamp),
+            BadgeCondition::RegistrationYears { years } => {
+                match (subject.creation_date, subject.expiry) {
+                    (Some(creation), Some(expiry)) if expiry > creation => {
+                        expiry - creation >= years * SECONDS_PER_YEAR
+                    }
+                    _ => false,
+                }
+            }
+            BadgeCondition::Verified { fields } => {
+                !fields.is_empty()
+                    && fields.iter().all(|field| {
+                        cairo_short_string_to_felt(field)
+                            .map_or(false, |felt| subject.verified_fields.contains(&felt))
+                    })
+            }
+            BadgeCondition::DomainMatches { pattern } => subject
+                .domain
+                .as_ref()
+                .map_or(false, |domain| pattern.is_match(domain)),
+            BadgeCondition::ContractVerified => subject.contract_verified,
+        }
+    }
+}
+
+impl BadgeRules {
+    pub fn compute(&self, subject: &BadgeSubject) -> Vec<Badge> {
+        self.badges
+            .iter()
+            .filter(|rule| rule.condition.is_met(subject))
+            .map(|rule| Badge {
+                id: rule.id.clone(),
+                name: rule.name.clone(),
+                description: rule.description.clone(),
+            })
+            .collect()
+    }
+}
+
+impl BadgeSubject {
+    pub fn from_identity(state: &AppState, identity: &IdentityData) -> Self {
+        BadgeSubject {
+            domain: identity.domain.as_ref().map(|d| d.domain.clone()),
+            creation_date: identity.domain.as_ref().map(|d| d.creation_date),
+            expiry: identity.domain.as_ref().and_then(|d| d.expiry),
+            verified_fields: identity
+                .verifier_data
+                .iter()
+                .filter(|data| state.conf.contracts.verifiers.contains(&data.verifier))
+                .map(|data| data.field)
+                .collect(),
+            contract_verified: false,
+        }
+    }
+}
+
+pub fn load_badges(logger: &Logger) -> BadgeRules {
+    let rules = match fs::read_to_string("./src/badges/badges.json") {
+        Ok(data) => match serde_json::from_str::<RawBadgeRules>(&data) {
+            Ok(rules) => rules,
+            Err(e) => {
+                logger.warning(format!("Unable to parse badges file: {}", e));
+                return BadgeRules { badges: vec![] };
+            }
+        },
+        Err(e) => {
+            logger.warning(format!("Unable to load badges file: {}", e));
+            return BadgeRules { badges: vec![] };
+        }
+    };
+
+    // drop the invalid rules, e.g. with a bad regex, instead of silently never awarding them
+    let badges = rules
+        .badges
+        .into_iter()
+        .filter_map(
+            |rule| match serde_json::from_value::<BadgeRule>(rule.clone()) {
+                Ok(rule) => Some(rule),
+                Err(e) => {
+                    logger.warning(format!(
+                        "Invalid badge {}: {}",
+                        rule.get("id").and_then(|id| id.as_str()).unwrap_or("?"),
+                        e
+                    ));
+                    None
+                }
+            },
+        )
+        .collect();
+    BadgeRules { badges }
+}
@@ -0,0 +1,218 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::{boxed, Body, Bytes, Full},
+    extract::{MatchedPath, State},
+    http::{HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use hyper::body::HttpBody;
+use starknet::core::types::FieldElement;
+use tokio::{
+    sync::broadcast::error::RecvError,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    models::AppState,
+    utils::{normalize_domain, to_hex},
+    ws::feed::ChangeEvent,
+};
+
+// responses larger than this are not kept
+const MAX_BODY_SIZE: u64 = 256 * 1024;
+// the responses depend on the encoding and language negotiated with these headers
+const VARY_HEADERS: [&str; 2] = ["accept", "accept-language"];
+
+#[derive(Clone)]
+pub struct CachedResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Bytes,
+    pub expires_at: Instant,
+    // the response is dropped when one of these is written to, see `get_event_tags`
+    pub tags: Vec<String>,
+}
+
+/// Responses of the hot read routes, kept until their ttl or an invalidating write
+#[derive(Default)]
+pub struct ResponseCache {
+    entries: Mutex<HashMap<String, CachedResponse>>,
+}
+
+impl ResponseCache {
+    pub fn get(&self, key: &str, now: Instant) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock().unwrap();
+        match entries.get(key) {
+            Some(entry) if entry.expires_at > now => Some(entry.clone()),
+            Some(_) => {
+                entries.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: String, entry: CachedResponse, max_entries: usize, now: Instant) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= max_entries {
+            entries.retain(|_, entry| entry.expires_at > now);
+        }
+        // still full of live entries, they expire soon enough
+        if entries.len() < max_entries {
+            entries.insert(key, entry);
+        }
+    }
+
+    /// Drops the responses tagged with one of `tags`
+    pub fn invalidate(&self, tags: &[String]) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|_, entry| !entry.tags.iter().any(|tag| tags.contains(tag)));
+        before - entries.len()
+    }
+
+    pub fn clear(&self) {
+        self.entries.lock().unwrap().clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.lock().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+fn parse_felt(value: &str) -> Option<FieldElement> {
+    if value.starts_with("0x") {
+        FieldElement::from_hex_be(value).ok()
+    } else {
+        FieldElement::from_dec_str(value).ok()
+    }
+}
+
+/// Domains, addresses and identities a response was computed from, read from its query
+pub fn get_query_tags(query: &str) -> Vec<String> {
+    let params: Vec<(String, String)> = serde_urlencoded::from_str(query).unwrap_or_default();
+    params
+        .iter()
+        .filter_map(|(key, value)| match key.as_str() {
+            "domain" => Some(format!("domain:{}", normalize_domain(value))),
+            "addr" | "address" => parse_felt(value).map(|addr| format!("addr:{}", to_hex(&addr))),
+            "id" => parse_felt(value).map(|id| format!("id:{}", to_hex(&id))),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Tags of the responses an indexed write makes outdated, the writes which move a domain
+/// away from an address aren't seen from that address so its responses wait for their ttl
+pub fn get_event_tags(event: &ChangeEvent) -> Vec<String> {
+    let mut tags: Vec<String> = event
+        .addresses
+        .iter()
+        .map(|address| format!("addr:{}", address))
+        .collect();
+    if let Some(domain) = &event.domain {
+        tags.push(format!("domain:{}", domain));
+    }
+    if let Some(id) = &event.id {
+        tags.push(format!("id:{}", id));
+    }
+    tags
+}
+
+/// Bumped when `get_cache_key` changes, instances of another version don't share a pool
+pub const CACHE_KEY_VERSION: u32 = 1;
+
+pub fn get_cache_key(path: &str, query: Option<&str>, headers: &HeaderMap) -> String {
+    let mut key = format!("{}?{}", path, query.unwrap_or_default());
+    for name in VARY_HEADERS {
+        let value = headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or_default();
+        key.push_str(&format!("|{}={}", name, value));
+    }
+    key
+}
+
+/// Serves the configured GET routes from the cache, the successful responses are stored
+pub async fn cache_responses(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let conf = &state.conf.response_cache;
+    let ttl = match req.extensions().get::<MatchedPath>() {
+        Some(path) if conf.enabled && req.method() == axum::http::Method::GET => {
+            conf.ttls.get(path.as_str()).copied()
+        }
+        _ => None,
+    };
+    let ttl = match ttl {
+        Some(ttl) => Duration::from_secs(ttl),
+        None => return next.run(req).await,
+    };
+
+    let key = get_cache_key(req.uri().path(), req.uri().query(), req.headers());
+    let now = Instant::now();
+    if let Some(entry) = state.response_cache.get(&key, now) {
+        let mut response = Response::new(boxed(Full::from(entry.body)));
+        *response.status_mut() = entry.status;
+        *response.headers_mut() = entry.headers;
+        response
+            .headers_mut()
+            .insert("x-cache", HeaderValue::from_static("hit"));
+        return response;
+    }
+
+    let tags = get_query_tags(req.uri().query().unwrap_or_default());
+    let response = next.run(req).await;
+    let size = response.body().size_hint().upper();
+    if response.status() != StatusCode::OK || !matches!(size, Some(size) if size <= MAX_BODY_SIZE) {
+        return response;
+    }
+    let (mut parts, body) = response.into_parts();
+    let body = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(_) => return Response::from_parts(parts, boxed(Full::default())),
+    };
+    state.response_cache.insert(
+        key,
+        CachedResponse {
+            status: parts.status,
+            headers: parts.headers.clone(),
+            body: body.clone(),
+            expires_at: now + ttl,
+            tags,
+        },
+        conf.max_entries,
+        now,
+    );
+    parts
+        .headers
+        .insert("x-cache", HeaderValue::from_static("miss"));
+    Response::from_parts(parts, boxed(Full::from(body)))
+}
+
+/// Drops the cached responses outdated by the writes published on the change feed
+pub async fn run_invalidation(state: &AppState) {
+    let mut events = state.change_feed.subscribe();
+    loop {
+        match events.recv().await {
+            Ok(event) => {
+                state.response_cache.invalidate(&get_event_tags(&event));
+            }
+            // the missed writes are unknown
+            Err(RecvError::Lagged(_)) => state.response_cache.clear(),
+            Err(RecvError::Closed) => return,
+        }
+    }
+}
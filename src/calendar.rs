@@ -0,0 +1,129 @@
+use anyhow::Result;
+use chrono::DateTime;
+use mongodb::bson::{doc, Bson};
+use starknet::core::types::FieldElement;
+
+use crate::{
+    storage::{FindSpec, Storage},
+    utils::to_hex,
+};
+
+// lines longer than this are folded (RFC 5545, 3.1)
+const MAX_LINE_LEN: usize = 75;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExpiryEvent {
+    pub domain: String,
+    pub expiry: i64,
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// continuation lines start with a space, splits happen on char boundaries
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > MAX_LINE_LEN {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded.push_str("\r\n");
+    folded
+}
+
+fn format_time(timestamp: i64) -> String {
+    DateTime::from_timestamp(timestamp, 0)
+        .unwrap_or_default()
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// iCalendar feed with an event at each expiry and an alarm for each reminder
+pub fn render_calendar(events: &[ExpiryEvent], reminder_days: &[u32], now: i64) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//starknet.id//domain expiries//EN".to_string(),
+        "CALSCALE:GREGORIAN".to_string(),
+        "X-WR-CALNAME:Starknet ID expiries".to_string(),
+    ];
+    for event in events {
+        let summary = escape_text(&format!("{} expires", event.domain));
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            // stable across refreshes while the expiry doesn't change
+            format!("UID:{}-{}@starknet.id", event.domain, event.expiry),
+            format!("DTSTAMP:{}", format_time(now)),
+            format!("DTSTART:{}", format_time(event.expiry)),
+            format!("DTEND:{}", format_time(event.expiry + 3600)),
+            format!("SUMMARY:{}", summary),
+            format!(
+                "DESCRIPTION:{}",
+                escape_text(&format!(
+                    "Renew {} before it expires to keep it.",
+                    event.domain
+                ))
+            ),
+        ]);
+        for days in reminder_days {
+            lines.extend([
+                "BEGIN:VALARM".to_string(),
+                "ACTION:DISPLAY".to_string(),
+                format!("DESCRIPTION:{}", summary),
+                format!("TRIGGER:-P{}D", days),
+                "END:VALARM".to_string(),
+            ]);
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+    lines.iter().map(|line| fold_line(line)).collect()
+}
+
+/// Live root domains of the identities owned by `address`, soonest expiry first
+pub async fn get_expiry_events(
+    storage: &dyn Storage,
+    address: &FieldElement,
+) -> Result<Vec<ExpiryEvent>> {
+    let ids: Vec<Bson> = storage
+        .find(
+            "id_owners",
+            doc! { "owner": to_hex(address), "_cursor.to": null },
+            FindSpec::default(),
+        )
+        .await?
+        .iter()
+        .filter_map(|doc| doc.get("id").cloned())
+        .collect();
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let domains = storage
+        .find(
+            "domains",
+            doc! { "id": { "$in": ids }, "expiry": { "$ne": null }, "_cursor.to": null },
+            FindSpec {
+                sort: Some(doc! { "expiry": 1 }),
+                ..Default::default()
+            },
+        )
+        .await?;
+    Ok(domains
+        .iter()
+        .filter_map(|doc| {
+            Some(ExpiryEvent {
+                domain: doc.get_str("domain").ok()?.to_string(),
+                expiry: doc.get_i64("expiry").ok()?,
+            })
+        })
+        .collect())
+}
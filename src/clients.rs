@@ -0,0 +1,194 @@
+use std::{collections::HashMap, sync::Arc, sync::Mutex};
+
+use axum::{
+    extract::{MatchedPath, State},
+    http::{header::USER_AGENT, Request},
+    middleware::Next,
+    response::Response,
+};
+use serde::Serialize;
+
+use crate::{metrics::labeled, models::AppState};
+
+/// Kind of integration sending a request, guessed from its `User-Agent`
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum ClientClass {
+    Wallet,
+    Explorer,
+    Sdk,
+    Bot,
+    Browser,
+    Unknown,
+}
+
+// lowercase substrings of the user agents, the first class matching wins so the
+// integrations are found before the generic crawlers and browsers
+const PATTERNS: [(ClientClass, &[&str]); 4] = [
+    (
+        ClientClass::Explorer,
+        &["starkscan", "voyager", "viewblock", "oklink", "blockscout"],
+    ),
+    (
+        ClientClass::Wallet,
+        &[
+            "argent",
+            "braavos",
+            "okxwallet",
+            "metamask",
+            "bitget",
+            "keplr",
+        ],
+    ),
+    (
+        ClientClass::Sdk,
+        &[
+            "starknetid",
+            "starknet.js",
+            "starknet-rs",
+            "starknet-py",
+            "starknet_py",
+            "starknetkit",
+            "axios",
+            "node-fetch",
+            "undici",
+            "okhttp",
+        ],
+    ),
+    (
+        ClientClass::Bot,
+        &[
+            "bot",
+            "crawler",
+            "spider",
+            "curl/",
+            "wget/",
+            "python-requests",
+            "go-http-client",
+            "headless",
+            "monitor",
+        ],
+    ),
+];
+
+impl ClientClass {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ClientClass::Wallet => "wallet",
+            ClientClass::Explorer => "explorer",
+            ClientClass::Sdk => "sdk",
+            ClientClass::Bot => "bot",
+            ClientClass::Browser => "browser",
+            ClientClass::Unknown => "unknown",
+        }
+    }
+}
+
+pub fn classify(user_agent: Option<&str>) -> ClientClass {
+    let user_agent = match user_agent {
+        Some(user_agent) if !user_agent.trim().is_empty() => user_agent.to_lowercase(),
+        _ => return ClientClass::Unknown,
+    };
+    PATTERNS
+        .iter()
+        .find(|(_, patterns)| patterns.iter().any(|pattern| user_agent.contains(pattern)))
+        .map(|(class, _)| *class)
+        .unwrap_or(if user_agent.starts_with("mozilla/") {
+            ClientClass::Browser
+        } else {
+            ClientClass::Unknown
+        })
+}
+
+struct EndpointStats {
+    requests: u64,
+    last_seen: i64,
+}
+
+/// Requests of each client class per route since the server started
+#[derive(Default)]
+pub struct ClientUsage {
+    endpoints: Mutex<HashMap<(ClientClass, String), EndpointStats>>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ClientEndpoint {
+    pub endpoint: String,
+    pub requests: u64,
+    pub last_seen: i64,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ClassUsage {
+    pub class: ClientClass,
+    pub requests: u64,
+    // most requested first
+    pub endpoints: Vec<ClientEndpoint>,
+}
+
+impl ClientUsage {
+    pub fn record(&self, class: ClientClass, endpoint: &str, now: i64) {
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let stats = endpoints
+            .entry((class, endpoint.to_string()))
+            .or_insert(EndpointStats {
+                requests: 0,
+                last_seen: now,
+            });
+        stats.requests += 1;
+        stats.last_seen = now;
+    }
+
+    /// Usage of the classes seen, the busiest first
+    pub fn report(&self) -> Vec<ClassUsage> {
+        let mut classes: HashMap<ClientClass, ClassUsage> = HashMap::new();
+        for ((class, endpoint), stats) in self.endpoints.lock().unwrap().iter() {
+            let usage = classes.entry(*class).or_insert_with(|| ClassUsage {
+                class: *class,
+                requests: 0,
+                endpoints: vec![],
+            });
+            usage.requests += stats.requests;
+            usage.endpoints.push(ClientEndpoint {
+                endpoint: endpoint.clone(),
+                requests: stats.requests,
+                last_seen: stats.last_seen,
+            });
+        }
+        let mut classes: Vec<ClassUsage> = classes.into_values().collect();
+        for usage in &mut classes {
+            usage.endpoints.sort_by(|a, b| {
+                b.requests
+                    .cmp(&a.requests)
+                    .then(a.endpoint.cmp(&b.endpoint))
+            });
+        }
+        classes.sort_by(|a, b| b.requests.cmp(&a.requests).then(a.class.cmp(&b.class)));
+        classes
+    }
+}
+
+// counts the routed requests per client class
+pub async fn track_clients<B>(
+    State(state): State<Arc<AppState>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let endpoint = match req.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_string(),
+        None => return next.run(req).await,
+    };
+    let class = classify(
+        req.headers()
+            .get(USER_AGENT)
+            .and_then(|value| value.to_str().ok()),
+    );
+    state.metrics.add(
+        &labeled("requests_by_client_total", "class", class.as_str()),
+        1,
+    );
+    state
+        .client_usage
+        .record(class, &endpoint, chrono::Utc::now().timestamp());
+    next.run(req).await
+}
@@ -0,0 +1,30 @@
+use std::collections::HashMap;
+
+/// Most specific club of a domain, in the order used by `/stats/count_club_domains`
+pub fn get_club(domain: &str) -> Option<&'static str> {
+    let label = domain.strip_suffix(".stark")?;
+    if label.contains('.') {
+        return [("vip", "og"), ("everai", "everai"), ("onsheet", "onsheet")]
+            .iter()
+            .find(|(parent, _)| label.ends_with(&format!(".{}", parent)))
+            .map(|(_, club)| *club);
+    }
+    let length = label.chars().count();
+    let digits = label.chars().all(|c| c.is_ascii_digit());
+    match (length, digits) {
+        (1, _) => Some("single_letter"),
+        (2, true) => Some("99"),
+        (2, false) => Some("two_letters"),
+        (3, true) => Some("999"),
+        (3, false) => Some("three_letters"),
+        (4, true) => Some("10k"),
+        (4, false) => Some("four_letters"),
+        _ => None,
+    }
+}
+
+/// Estimated value in usd, the floor price of the club of the domain
+pub fn appraise(club_floors: &HashMap<String, f64>, default_floor: f64, club: Option<&str>) -> f64 {
+    club.and_then(|club| club_floors.get(club).copied())
+        .unwrap_or(default_floor)
+}
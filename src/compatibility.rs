@@ -0,0 +1,156 @@
+use anyhow::Result;
+use mongodb::bson::{doc, from_document, to_document};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache::CACHE_KEY_VERSION, config::CONFIG_SCHEMA_VERSION, models::AppState,
+    routes::RESPONSE_SHAPE_VERSION, storage::Storage,
+};
+
+pub const PEERS_COLLECTION: &str = "deployment_peers";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaVersions {
+    pub config: u32,
+    pub cache_key: u32,
+    pub response_shape: u32,
+}
+
+/// Versions of this build
+pub fn get_running_versions() -> SchemaVersions {
+    SchemaVersions {
+        config: CONFIG_SCHEMA_VERSION,
+        cache_key: CACHE_KEY_VERSION,
+        response_shape: RESPONSE_SHAPE_VERSION,
+    }
+}
+
+/// Instance of the pool, as recorded by its last heartbeat
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Peer {
+    pub instance_id: String,
+    pub server_version: String,
+    pub versions: SchemaVersions,
+    pub heartbeat_at: i64,
+}
+
+/// Version of a peer which can't serve next to this instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct Incompatibility {
+    pub instance_id: String,
+    pub component: &'static str,
+    pub peer: u32,
+    pub running: u32,
+}
+
+pub fn new_instance_id() -> String {
+    let host = std::env::var("HOSTNAME").unwrap_or_else(|_| "instance".to_string());
+    format!("{}-{:08x}", host, rand::random::<u32>())
+}
+
+/// Peers whose cache keys or response shapes differ, a different config schema only
+/// needs the config to be migrated so it is reported apart
+pub fn get_incompatibilities(running: &SchemaVersions, peers: &[Peer]) -> Vec<Incompatibility> {
+    let mut incompatibilities = vec![];
+    for peer in peers {
+        for (component, version, running) in [
+            ("cache_key", peer.versions.cache_key, running.cache_key),
+            (
+                "response_shape",
+                peer.versions.response_shape,
+                running.response_shape,
+            ),
+        ] {
+            if version != running {
+                incompatibilities.push(Incompatibility {
+                    instance_id: peer.instance_id.clone(),
+                    component,
+                    peer: version,
+                    running,
+                });
+            }
+        }
+    }
+    incompatibilities
+}
+
+/// Other instances which sent a heartbeat in the last `ttl_secs`
+pub async fn get_live_peers(
+    storage: &dyn Storage,
+    instance_id: &str,
+    now: i64,
+    ttl_secs: u64,
+) -> Result<Vec<Peer>> {
+    let documents = storage
+        .find(
+            PEERS_COLLECTION,
+            doc! {
+                "instance_id": { "$ne": instance_id },
+                "heartbeat_at": { "$gte": now - ttl_secs as i64 },
+            },
+            Default::default(),
+        )
+        .await?;
+    Ok(documents
+        .into_iter()
+        .filter_map(|document| from_document(document).ok())
+        .collect())
+}
+
+pub async fn send_heartbeat(storage: &dyn Storage, instance_id: &str, now: i64) -> Result<()> {
+    let peer = Peer {
+        instance_id: instance_id.to_string(),
+        server_version: env!("CARGO_PKG_VERSION").to_string(),
+        versions: get_running_versions(),
+        heartbeat_at: now,
+    };
+    storage
+        .replace_one(
+            PEERS_COLLECTION,
+            doc! { "instance_id": instance_id },
+            to_document(&peer)?,
+        )
+        .await
+}
+
+/// Records the instance in the pool unless a live peer runs incompatible versions,
+/// false when the instance must not serve
+pub async fn join_pool(state: &AppState, instance_id: &str) -> Result<bool> {
+    let conf = &state.conf.compatibility;
+    let now = chrono::Utc::now().timestamp();
+    let running = get_running_versions();
+    // the instances which left the pool never remove their record
+    state
+        .storage
+        .delete_many(
+            PEERS_COLLECTION,
+            doc! { "heartbeat_at": { "$lt": now - conf.peer_ttl_secs as i64 } },
+        )
+        .await?;
+    let peers =
+        get_live_peers(state.storage.as_ref(), instance_id, now, conf.peer_ttl_secs).await?;
+    for peer in peers
+        .iter()
+        .filter(|peer| peer.versions.config != running.config)
+    {
+        state.logger.warning(format!(
+            "compatibility: peer {} runs config schema {}, this instance {}",
+            peer.instance_id, peer.versions.config, running.config
+        ));
+    }
+    let incompatibilities = get_incompatibilities(&running, &peers);
+    for incompatibility in &incompatibilities {
+        state.logger.severe(format!(
+            "compatibility: peer {} runs {} version {}, this instance {}",
+            incompatibility.instance_id,
+            incompatibility.component,
+            incompatibility.peer,
+            incompatibility.running
+        ));
+    }
+    if !incompatibilities.is_empty() && !conf.force {
+        return Ok(false);
+    }
+    send_heartbeat(state.storage.as_ref(), instance_id, now).await?;
+    Ok(true)
+}
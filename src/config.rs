@@ -6,24 +6,50 @@ use std::collections::HashMap;
 use std::env;
 use std::fs;
 
+use crate::display_address::AddressFormat;
 use crate::endpoints::crosschain::ethereum::text_records::HandlerType;
+use crate::governance::PowerRule;
+use crate::listener::ListenerKind;
+use crate::networks::get_route_collisions;
+use crate::reminders::email::EmailProvider;
+use crate::routes::{RouteGroup, ROUTES};
 use crate::utils::to_hex;
 
+/// Bumped when a config section is renamed or changes meaning, peers of another schema are
+/// reported on startup
+pub const CONFIG_SCHEMA_VERSION: u32 = 1;
+
 macro_rules! pub_struct {
-    ($($derive:path),*; $name:ident {$($field:ident: $t:ty),* $(,)?}) => {
+    ($($derive:path),*; $name:ident {$($(#[$meta:meta])* $field:ident: $t:ty),* $(,)?}) => {
         #[derive($($derive),*)]
         pub struct $name {
-            $(pub $field: $t),*
+            $($(#[$meta])* pub $field: $t),*
         }
     }
 }
 
-pub_struct!(Clone, Deserialize; Server { port: u16 });
+pub_struct!(Clone, Deserialize; Server {
+    port: u16,
+    #[serde(default)]
+    listener: ListenerKind,
+    // path of the socket when listening on a unix socket
+    #[serde(default = "default_unix_socket")]
+    unix_socket: String,
+    // redirect requests whose domain parameter isn't canonical instead of normalizing it
+    #[serde(default)]
+    canonical_redirect: bool,
+});
+
+fn default_unix_socket() -> String {
+    "/run/starknetid/server.sock".to_string()
+}
 
 pub_struct!(Clone, Deserialize; Databases {
     starknetid: Database,
     sales: Database,
     free_domains: Database,
+    #[serde(default)]
+    pool: MongoPool,
 });
 
 pub_struct!(Clone, Deserialize; Database {
@@ -31,6 +57,25 @@ pub_struct!(Clone, Deserialize; Database {
     connection_string: String,
 });
 
+pub_struct!(Clone, Deserialize; MongoPool {
+    min_connections: u32,
+    max_connections: u32,
+    // connections idle for longer are closed, down to min_connections
+    max_idle_secs: u64,
+    wait_queue_timeout_ms: u64,
+});
+
+impl Default for MongoPool {
+    fn default() -> Self {
+        MongoPool {
+            min_connections: 2,
+            max_connections: 50,
+            max_idle_secs: 60,
+            wait_queue_timeout_ms: 5000,
+        }
+    }
+}
+
 pub_struct!(Clone, Deserialize; Contracts {
     starknetid: FieldElement,
     naming: FieldElement,
@@ -64,12 +109,18 @@ pub_struct!(Clone, Debug, Deserialize; AltcoinData {
     decimals: u32,
     max_quote_validity: i64,
     auto_renew_contract: Option<FieldElement>,
+    // coingecko id of the token, to cross-check the avnu price
+    secondary_id: Option<String>,
 });
 
 #[derive(Debug, Deserialize)]
 struct TempAltcoins {
     avnu_api: String,
     private_key: FieldElement,
+    #[serde(default)]
+    secondary_api: String,
+    #[serde(default)]
+    max_deviation: f64,
     #[serde(flatten)]
     data: HashMap<String, AltcoinData>,
 }
@@ -77,13 +128,24 @@ struct TempAltcoins {
 pub_struct!(Clone, Debug; Altcoins {
     avnu_api: String,
     private_key: FieldElement,
+    // coingecko compatible api checked against avnu, empty disables the check
+    secondary_api: String,
+    // largest relative difference allowed between the two prices
+    max_deviation: f64,
     data: HashMap<FieldElement, AltcoinData>,
 });
 
 pub_struct!(Clone, Debug, Deserialize; Variables {
     rpc_url: String,
     refresh_delay: f64,
-    ipfs_gateway: String,
+    // tried in this order, a gateway failing repeatedly is skipped for a while
+    // the single ipfs_gateway of the configs written before the failover is its only gateway
+    #[serde(
+        default = "default_ipfs_gateways",
+        alias = "ipfs_gateway",
+        deserialize_with = "deserialize_gateways"
+    )]
+    ipfs_gateways: Vec<String>,
     discord_token: String,
     discord_api_url: String,
     twitter_api_key: String,
@@ -120,6 +182,444 @@ pub_struct!(Clone, Debug, Deserialize; FreeDomains {
     priv_key: FieldElement,
 });
 
+pub_struct!(Clone, Debug, Deserialize; Admin {
+    api_key: String,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Events {
+    max_limit: i64,
+    // a stream resumed from an older Last-Event-ID restarts this many blocks back
+    max_backfill_blocks: i64,
+    // contract name used in queries -> collection where the indexer stores its raw events
+    collections: HashMap<String, String>,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Reorgs {
+    // rolls back the indexed data on the reorgs reported by the indexer, to run on a single
+    // instance and only when the indexer doesn't roll back itself. The indexer stops, reports
+    // the last block it ingested with the reorg and resumes once the reorg is processed
+    enabled: bool,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Tls {
+    enabled: bool,
+    domains: Vec<String>,
+    contact_email: String,
+    // where the certificate and its key are stored between restarts
+    cache_dir: String,
+    // use the Let's Encrypt staging environment
+    staging: bool,
+    renew_after_days: u64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; DebugSampling {
+    // share of the requests recorded, between 0 and 1
+    rate: f64,
+    // requests or responses with a larger body aren't recorded
+    max_body_size: u64,
+    // size in bytes of the capped collection storing the samples
+    collection_size: u64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Profanity {
+    // word lists loaded from src/profanity/words/<locale>.txt
+    locales: Vec<String>,
+});
+
+pub_struct!(Clone, Debug, Deserialize; AddressLabels {
+    // json files listing public address tags
+    datasets: Vec<String>,
+});
+
+pub_struct!(Clone, Debug, Deserialize; RpcLimits {
+    max_concurrent: usize,
+    // background calls waiting longer than this go before interactive ones
+    max_background_wait_ms: u64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Exports {
+    // s3 compatible bucket receiving the export artifacts, empty disables exports
+    bucket: String,
+    region: String,
+    endpoint: String,
+    access_key: String,
+    secret_key: String,
+    // lifetime of the pre-signed download urls
+    url_expiry_secs: u32,
+    // where artifacts are written before being uploaded
+    tmp_dir: String,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Transparency {
+    // signs the tree heads of the transparency log
+    private_key: FieldElement,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Retention {
+    interval_secs: u64,
+    // renewals made this long after the end of the first year still count
+    grace_secs: i64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Notifications {
+    // delay between two scans for notifications to send
+    interval_secs: u64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; MarketplaceApi {
+    name: String,
+    // e.g. https://api.opensea.io/api/v2/chain/starknet/contract/{contract}/nfts/{token_id}/refresh
+    url: String,
+    api_key_header: String,
+    // no header is sent when empty
+    api_key: String,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Marketplaces {
+    // delay between two scans for identity cards to refresh
+    interval_secs: u64,
+    apis: Vec<MarketplaceApi>,
+});
+
+pub_struct!(Clone, Debug, Deserialize; RateLimits {
+    enabled: bool,
+    window_secs: i64,
+    // requests per window of each route rate class
+    light: u64,
+    heavy: u64,
+    write: u64,
+    // callers with a partner api key get this many times the limits
+    api_key_multiplier: u64,
+    // counts the clients by the rightmost x-forwarded-for entry, appended by the reverse proxy,
+    // instead of the peer address
+    trust_forwarded_for: bool,
+    // burst credits by api key tier, spent once the window limit is reached
+    burst_tiers: HashMap<String, BurstTier>,
+    // limits of the api keys by name, replacing the multiplied default ones
+    api_key_quotas: HashMap<String, ClassLimits>,
+});
+
+pub_struct!(Clone, Debug, Deserialize; ClassLimits {
+    light: u64,
+    heavy: u64,
+    write: u64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; BurstTier {
+    capacity: f64,
+    refill_per_sec: f64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; ResponseCache {
+    // invalidated by the change streams of the ws feed, which need a replica set
+    enabled: bool,
+    max_entries: usize,
+    // seconds a response is kept, by route path
+    ttls: HashMap<String, u64>,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Ui {
+    // only served by the builds with the embedded-ui feature
+    enabled: bool,
+    title: String,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Fanout {
+    // deadline of each concurrent lookup of the uri and profile endpoints
+    branch_timeout_ms: u64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Availability {
+    // root labels which can't be registered, e.g. "starknet"
+    reserved: Vec<String>,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Avatars {
+    // chain id -> json-rpc url of the evm chains the eip155 avatars are verified on
+    evm_rpc_urls: HashMap<String, String>,
+    // token metadata and proxied image requests
+    fetch_timeout_ms: u64,
+    // proxied images larger than this are refused
+    max_image_size: usize,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Images {
+    // where /img caches the images, unless `use_bucket` stores them in the exports bucket
+    cache_dir: String,
+    use_bucket: bool,
+    cache_ttl_secs: u64,
+    // the widths ?w= accepts, so the cache can't be filled with every size
+    widths: Vec<u32>,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Portfolio {
+    // identities and domains moved by one multicall
+    max_items: usize,
+    // targets refused, e.g. exchange deposit addresses which can't transfer nfts back
+    denied_addresses: Vec<FieldElement>,
+    denied_class_hashes: Vec<FieldElement>,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Fallback {
+    // rpc called for the domains missing from the database, empty disables the fallback
+    rpc_url: String,
+    cache_secs: i64,
+    miss_cache_secs: i64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Reminders {
+    enabled: bool,
+    interval_secs: u64,
+    // a reminder is sent when the expiry gets this close, e.g. [30, 7, 1]
+    days_before: Vec<i64>,
+    // writes the addresses in the email field of the identities
+    email_verifier: FieldElement,
+    provider: EmailProvider,
+    from: String,
+    // `{domain}`, `{days}` and `{expiry}` are replaced
+    subject: String,
+    template: String,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Cards {
+    // the nft metadata points to the cards rendered by /identity_card instead of identicons
+    enabled: bool,
+    // public url of the api the card urls start with
+    base_url: String,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Tracing {
+    // filter of the json request logs, e.g. "info" or "starknetid_server=debug"
+    level: String,
+    // collector the spans are exported to over grpc, none when empty
+    otlp_endpoint: String,
+    service_name: String,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Cors {
+    // the public deployments are called from any website
+    allow_any_origin: bool,
+    // e.g. "https://app.example.com" or "https://*.example.com" for its subdomains
+    allowed_origins: Vec<String>,
+    // how long the browsers keep a preflight response
+    max_age_secs: u64,
+    // strict-transport-security, for the deployments only served over https
+    hsts: bool,
+});
+
+pub_struct!(Clone, Debug, Deserialize; AutoRenewal {
+    // autorenewal contract of the eth subscriptions indexed without one
+    eth_contract: FieldElement,
+    eth_token: FieldElement,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Examples {
+    // replays the public reads at startup for the responses of /docs/examples
+    enabled: bool,
+    // identity indexed in the database the examples are requested for
+    domain: String,
+    address: String,
+    id: String,
+});
+
+pub_struct!(Clone, Deserialize; Status {
+    // /status answers 503 past this many seconds between the indexed block and the head
+    max_lag_secs: i64,
+});
+
+pub_struct!(Clone, Deserialize; Governance {
+    // signs the attestations, its public key is returned with them
+    private_key: FieldElement,
+    attestation_validity_secs: i64,
+    rules: Vec<PowerRule>,
+});
+
+pub_struct!(Clone, Deserialize; Network {
+    starknetid: Database,
+    sales: Database,
+    free_domains: Database,
+    rpc_url: String,
+    contracts: Contracts,
+});
+
+/// Networks served by the instance, the top level sections configure the default one
+#[derive(Clone, Deserialize)]
+pub struct Networks {
+    // e.g. mainnet, the requests naming no network are served on it
+    pub default: String,
+    #[serde(flatten)]
+    pub others: HashMap<String, Network>,
+}
+
+pub_struct!(Clone, Debug, Deserialize; Webhooks {
+    // the dispatcher and the delivery worker, to run on a single instance
+    enabled: bool,
+    interval_secs: u64,
+    // writes read and deliveries attempted per run
+    batch_size: i64,
+    timeout_ms: u64,
+    // a failing delivery is retried after retry_base_secs, doubling up to retry_max_secs
+    max_attempts: u32,
+    retry_base_secs: i64,
+    retry_max_secs: i64,
+    max_per_key: usize,
+    // finished deliveries are kept this long for their owner to inspect
+    retention_secs: i64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Ipfs {
+    // cid the gateways are probed with, e.g. the empty directory
+    health_check_cid: String,
+    check_interval_secs: u64,
+    timeout_ms: u64,
+    // a gateway failing this many times in a row is skipped for `cooldown_secs`
+    max_failures: u32,
+    cooldown_secs: i64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Compatibility {
+    // peers which haven't sent a heartbeat for this long have left the pool
+    peer_ttl_secs: u64,
+    // joins the pool despite incompatible peers, for the cutover of a breaking rollout
+    force: bool,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Suggestions {
+    // locale -> synonym groups file, one group of comma separated words per line
+    dictionaries: HashMap<String, String>,
+    // free alternatives returned with a taken domain
+    max_suggestions: usize,
+});
+
+pub_struct!(Clone, Debug, Deserialize; GracePolicy {
+    // seconds after the expiry during which only the owner can renew
+    grace_secs: i64,
+    resolve_in_grace: bool,
+    resolve_expired: bool,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Pagination {
+    // page size of the address lists, also used when no limit is given
+    max_limit: i64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Ws {
+    // the change streams need mongodb to run as a replica set
+    enabled: bool,
+    max_subscriptions: usize,
+    // writes replayed to a resuming client, older or further ones are left out
+    max_backfill_blocks: i64,
+    max_backfill_events: i64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; VerifierFreshness {
+    // verifications written longer ago are flagged with is_stale
+    stale_after_secs: i64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; AddressDisplay {
+    format: AddressFormat,
+});
+
+pub_struct!(Clone, Debug, Deserialize; UserTokens {
+    // tokens issued by /me/token expire after this delay
+    ttl_secs: i64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Calendar {
+    // alarms of the expiry events, in days before the expiry
+    reminder_days: Vec<u32>,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Partner {
+    // sponsor_addr of the referral revenues attributed to the partner
+    sponsors: Vec<String>,
+    // part of the referred sales owed to the partner, e.g. 0.1
+    share: f64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; TxBuilder {
+    // returns the registration price of a domain length for a number of days
+    pricing: FieldElement,
+    // runs the built multicalls through starknet_simulateTransactions
+    simulate: bool,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Distribution {
+    // delay between two computations of /stats/distribution
+    interval_secs: u64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Appraisal {
+    // floor price of each club in usd, e.g. three_letters = 100
+    club_floors: HashMap<String, f64>,
+    default_floor: f64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Analytics {
+    // postgres mirror serving the stats endpoints, empty disables it
+    postgres_url: String,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Activity {
+    // a transaction or renewal within this window makes an address active
+    window_secs: i64,
+    // delay before the nonce of an address is fetched again
+    cache_secs: i64,
+});
+
+pub_struct!(Clone, Debug, Deserialize; Maintenance {
+    interval_secs: u64,
+    // documents older than this, by their created_at, are deleted if no ttl index did it
+    retention_secs: HashMap<String, u64>,
+    // collections trimmed to their most recent documents, then compacted
+    max_documents: HashMap<String, u64>,
+    // in memory caches with more keys are reported as oversized
+    max_cache_keys: usize,
+});
+
+pub_struct!(Clone, Debug, Deserialize; RpcBudget {
+    // credits allowed per calendar month by the rpc provider, 0 disables the alarm
+    monthly_credits: u64,
+    // alarm once the projected monthly usage exceeds this share of the budget
+    alarm_ratio: f64,
+    check_interval_secs: u64,
+    default_credits: u64,
+    // credits charged for each rpc method, e.g. starknet_call = 1
+    method_credits: HashMap<String, u64>,
+});
+
+impl RpcBudget {
+    pub fn credits(&self, method: &str) -> u64 {
+        self.method_credits
+            .get(method)
+            .copied()
+            .unwrap_or(self.default_credits)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Tier {
+    Public,
+    Partner,
+    Internal,
+}
+
+pub_struct!(Clone, Debug, Deserialize; Deployment {
+    tier: Tier,
+    // route groups which are not served on each tier
+    disabled_groups: HashMap<Tier, Vec<RouteGroup>>,
+});
+
+impl Deployment {
+    pub fn is_enabled(&self, group: RouteGroup) -> bool {
+        self.disabled_groups
+            .get(&self.tier)
+            .map_or(true, |disabled| !disabled.contains(&group))
+    }
+}
+
 #[derive(Deserialize)]
 struct RawConfig {
     server: Server,
@@ -137,6 +637,125 @@ struct RawConfig {
     evm_records_verifiers: HashMap<String, EvmRecordVerifier>,
     free_domains: FreeDomains,
     watchtower: Watchtower,
+    #[serde(skip)]
+    optional: OptionalSections,
+}
+
+// sections added after the first releases, a config.toml written before them loads with the
+// features they configure turned off
+#[derive(Deserialize)]
+#[serde(default)]
+struct OptionalSections {
+    admin: Admin,
+    events: Events,
+    deployment: Deployment,
+    rpc_limits: RpcLimits,
+    rpc_budget: RpcBudget,
+    tls: Tls,
+    debug_sampling: DebugSampling,
+    profanity: Profanity,
+    address_labels: AddressLabels,
+    exports: Exports,
+    transparency: Transparency,
+    maintenance: Maintenance,
+    activity: Activity,
+    analytics: Analytics,
+    appraisal: Appraisal,
+    distribution: Distribution,
+    retention: Retention,
+    tx_builder: TxBuilder,
+    partners: HashMap<String, Partner>,
+    user_tokens: UserTokens,
+    calendar: Calendar,
+    address_display: AddressDisplay,
+    verifier_freshness: VerifierFreshness,
+    notifications: Notifications,
+    marketplaces: Marketplaces,
+    rate_limits: RateLimits,
+    ws: Ws,
+    response_cache: ResponseCache,
+    ui: Ui,
+    pagination: Pagination,
+    fanout: Fanout,
+    grace: GracePolicy,
+    availability: Availability,
+    suggestions: Suggestions,
+    avatars: Avatars,
+    images: Images,
+    compatibility: Compatibility,
+    ipfs: Ipfs,
+    webhooks: Webhooks,
+    portfolio: Portfolio,
+    networks: Networks,
+    governance: Governance,
+    fallback: Fallback,
+    status: Status,
+    examples: Examples,
+    reminders: Reminders,
+    auto_renewal: AutoRenewal,
+    cards: Cards,
+    tracing: Tracing,
+    cors: Cors,
+    reorgs: Reorgs,
+}
+
+impl Default for OptionalSections {
+    fn default() -> Self {
+        let conf = Config::default();
+        OptionalSections {
+            admin: conf.admin,
+            events: conf.events,
+            deployment: conf.deployment,
+            rpc_limits: conf.rpc_limits,
+            rpc_budget: conf.rpc_budget,
+            tls: conf.tls,
+            debug_sampling: conf.debug_sampling,
+            profanity: conf.profanity,
+            address_labels: conf.address_labels,
+            exports: conf.exports,
+            transparency: conf.transparency,
+            maintenance: conf.maintenance,
+            activity: conf.activity,
+            analytics: conf.analytics,
+            appraisal: conf.appraisal,
+            distribution: conf.distribution,
+            retention: conf.retention,
+            tx_builder: conf.tx_builder,
+            partners: conf.partners,
+            user_tokens: conf.user_tokens,
+            calendar: conf.calendar,
+            address_display: conf.address_display,
+            verifier_freshness: conf.verifier_freshness,
+            notifications: conf.notifications,
+            marketplaces: conf.marketplaces,
+            rate_limits: conf.rate_limits,
+            ws: conf.ws,
+            response_cache: conf.response_cache,
+            ui: conf.ui,
+            pagination: conf.pagination,
+            fanout: conf.fanout,
+            grace: conf.grace,
+            availability: conf.availability,
+            suggestions: conf.suggestions,
+            avatars: conf.avatars,
+            images: conf.images,
+            compatibility: conf.compatibility,
+            ipfs: conf.ipfs,
+            webhooks: conf.webhooks,
+            portfolio: conf.portfolio,
+            networks: conf.networks,
+            governance: conf.governance,
+            fallback: conf.fallback,
+            status: conf.status,
+            examples: conf.examples,
+            reminders: conf.reminders,
+            auto_renewal: conf.auto_renewal,
+            cards: conf.cards,
+            tracing: conf.tracing,
+            cors: conf.cors,
+            reorgs: conf.reorgs,
+        }
+    }
 }
 
 pub_struct!(Clone, Deserialize; Config {
@@ -157,6 +776,57 @@ pub_struct!(Clone, Deserialize; Config {
     subscription_to_altcoin: HashMap<FieldElement, String>,
     free_domains: FreeDomains,
     watchtower: Watchtower,
+    admin: Admin,
+    events: Events,
+    deployment: Deployment,
+    rpc_limits: RpcLimits,
+    rpc_budget: RpcBudget,
+    tls: Tls,
+    debug_sampling: DebugSampling,
+    profanity: Profanity,
+    address_labels: AddressLabels,
+    exports: Exports,
+    transparency: Transparency,
+    maintenance: Maintenance,
+    activity: Activity,
+    analytics: Analytics,
+    appraisal: Appraisal,
+    distribution: Distribution,
+    retention: Retention,
+    tx_builder: TxBuilder,
+    partners: HashMap<String, Partner>,
+    user_tokens: UserTokens,
+    calendar: Calendar,
+    address_display: AddressDisplay,
+    verifier_freshness: VerifierFreshness,
+    notifications: Notifications,
+    marketplaces: Marketplaces,
+    rate_limits: RateLimits,
+    ws: Ws,
+    response_cache: ResponseCache,
+    ui: Ui,
+    pagination: Pagination,
+    fanout: Fanout,
+    grace: GracePolicy,
+    availability: Availability,
+    suggestions: Suggestions,
+    avatars: Avatars,
+    images: Images,
+    compatibility: Compatibility,
+    ipfs: Ipfs,
+    webhooks: Webhooks,
+    portfolio: Portfolio,
+    networks: Networks,
+    governance: Governance,
+    fallback: Fallback,
+    status: Status,
+    examples: Examples,
+    reminders: Reminders,
+    auto_renewal: AutoRenewal,
+    cards: Cards,
+    tracing: Tracing,
+    cors: Cors,
+    reorgs: Reorgs,
 });
 
 pub_struct!(Clone, Deserialize; Watchtower {
@@ -186,6 +856,7 @@ impl Altcoins {
                     decimals: val.decimals,
                     max_quote_validity: val.max_quote_validity,
                     auto_renew_contract: val.auto_renew_contract,
+                    secondary_id: val.secondary_id,
                 };
                 (val.address, altcoin_data)
             })
@@ -194,6 +865,8 @@ impl Altcoins {
         Altcoins {
             avnu_api: temp.avnu_api,
             private_key: temp.private_key,
+            secondary_api: temp.secondary_api,
+            max_deviation: temp.max_deviation,
             data,
         }
     }
@@ -291,6 +964,57 @@ impl From<RawConfig> for Config {
             subscription_to_altcoin,
             free_domains: raw.free_domains,
             watchtower: raw.watchtower,
+            admin: raw.optional.admin,
+            events: raw.optional.events,
+            deployment: raw.optional.deployment,
+            rpc_limits: raw.optional.rpc_limits,
+            rpc_budget: raw.optional.rpc_budget,
+            tls: raw.optional.tls,
+            debug_sampling: raw.optional.debug_sampling,
+            profanity: raw.optional.profanity,
+            address_labels: raw.optional.address_labels,
+            exports: raw.optional.exports,
+            transparency: raw.optional.transparency,
+            maintenance: raw.optional.maintenance,
+            activity: raw.optional.activity,
+            analytics: raw.optional.analytics,
+            appraisal: raw.optional.appraisal,
+            distribution: raw.optional.distribution,
+            retention: raw.optional.retention,
+            tx_builder: raw.optional.tx_builder,
+            partners: raw.optional.partners,
+            user_tokens: raw.optional.user_tokens,
+            calendar: raw.optional.calendar,
+            address_display: raw.optional.address_display,
+            verifier_freshness: raw.optional.verifier_freshness,
+            notifications: raw.optional.notifications,
+            marketplaces: raw.optional.marketplaces,
+            rate_limits: raw.optional.rate_limits,
+            ws: raw.optional.ws,
+            response_cache: raw.optional.response_cache,
+            ui: raw.optional.ui,
+            pagination: raw.optional.pagination,
+            fanout: raw.optional.fanout,
+            grace: raw.optional.grace,
+            availability: raw.optional.availability,
+            suggestions: raw.optional.suggestions,
+            avatars: raw.optional.avatars,
+            images: raw.optional.images,
+            compatibility: raw.optional.compatibility,
+            ipfs: raw.optional.ipfs,
+            webhooks: raw.optional.webhooks,
+            portfolio: raw.optional.portfolio,
+            networks: raw.optional.networks,
+            governance: raw.optional.governance,
+            fallback: raw.optional.fallback,
+            status: raw.optional.status,
+            examples: raw.optional.examples,
+            reminders: raw.optional.reminders,
+            auto_renewal: raw.optional.auto_renewal,
+            cards: raw.optional.cards,
+            tracing: raw.optional.tracing,
+            cors: raw.optional.cors,
+            reorgs: raw.optional.reorgs,
         }
     }
 }
@@ -307,18 +1031,60 @@ pub fn load() -> Config {
         panic!("error: unable to read file with path \"{}\"", config_path);
     }
 
-    let raw_config: RawConfig = match toml::from_str(&file_contents.unwrap()) {
+    let file_contents = file_contents.unwrap();
+    let mut raw_config: RawConfig = match toml::from_str(&file_contents) {
         Ok(loaded) => loaded,
         Err(err) => panic!("error: unable to deserialize config. {}", err),
     };
+    raw_config.optional = match toml::from_str(&file_contents) {
+        Ok(loaded) => loaded,
+        Err(err) => panic!("error: unable to deserialize config. {}", err),
+    };
+
+    let config: Config = raw_config.into();
+    let names = std::iter::once(config.networks.default.as_str())
+        .chain(config.networks.others.keys().map(String::as_str));
+    let collisions = get_route_collisions(names, ROUTES);
+    if !collisions.is_empty() {
+        panic!(
+            "error: networks named like the first segment of a route: {}",
+            collisions.join(", ")
+        );
+    }
+    // a zero key signs what anyone can forge, only a section left out disables its signatures
+    let sections: toml::Table = toml::from_str(&file_contents).unwrap_or_default();
+    let written: Vec<String> = get_unset_keys(&config)
+        .into_iter()
+        .filter(|section| sections.contains_key(*section))
+        .map(|section| format!("{}.private_key", section))
+        .collect();
+    if !written.is_empty() {
+        panic!("error: zero signing keys: {}", written.join(", "));
+    }
+    config
+}
 
-    raw_config.into()
+/// Sections whose signing key is left to zero, the signatures they would make are refused
+pub fn get_unset_keys(config: &Config) -> Vec<&'static str> {
+    [
+        ("governance", config.governance.private_key),
+        ("transparency", config.transparency.private_key),
+    ]
+    .into_iter()
+    .filter(|(_, key)| *key == FieldElement::ZERO)
+    .map(|(section, _)| section)
+    .collect()
 }
 
 impl Default for Config {
     fn default() -> Self {
         Config {
-            server: Server { port: 8080 }, // Default port 8080
+            server: Server {
+                port: 8080, // Default port 8080
+                listener: ListenerKind::Tcp,
+                unix_socket: default_unix_socket(),
+                canonical_redirect: false,
+            },
             databases: Databases {
                 starknetid: Database {
                     name: "starknet_id".to_string(),
@@ -332,11 +1098,12 @@ impl Default for Config {
                     name: "free_domains".to_string(),
                     connection_string: "localhost:5432".to_string(),
                 },
+                pool: MongoPool::default(),
             },
             variables: Variables {
                 rpc_url: "http://localhost:8545".to_string(),
                 refresh_delay: 60.0, // Default refresh delay
-                ipfs_gateway: "https://ipfs.io/ipfs/".to_string(),
+                ipfs_gateways: default_ipfs_gateways(),
                 discord_token: "default_token".to_string(),
                 discord_api_url: "https://discord.com/api".to_string(),
                 twitter_api_key: "default_api_key".to_string(),
@@ -370,6 +1137,8 @@ impl Default for Config {
             altcoins: Altcoins {
                 avnu_api: "https://api.example.com".to_string(),
                 private_key: FieldElement::default(),
+                secondary_api: String::new(),
+                max_deviation: 0.05,
                 data: HashMap::new(),
             },
             offchain_resolvers: OffchainResolvers(HashMap::new()),
@@ -393,16 +1162,296 @@ impl Default for Config {
                     severe: "severe".to_string(),
                 },
             },
+            admin: Admin {
+                api_key: String::new(),
+            },
+            events: Events {
+                max_limit: 100,
+                max_backfill_blocks: 1000,
+                collections: HashMap::new(),
+            },
+            deployment: Deployment {
+                tier: Tier::Internal,
+                disabled_groups: HashMap::new(),
+            },
+            rpc_limits: RpcLimits {
+                max_concurrent: 16,
+                max_background_wait_ms: 2000,
+            },
+            rpc_budget: RpcBudget {
+                monthly_credits: 0,
+                alarm_ratio: 1.0,
+                check_interval_secs: 3600,
+                default_credits: 1,
+                method_credits: HashMap::new(),
+            },
+            tls: Tls {
+                enabled: false,
+                domains: vec![],
+                contact_email: String::new(),
+                cache_dir: "./tls".to_string(),
+                staging: true,
+                renew_after_days: 60,
+            },
+            debug_sampling: DebugSampling {
+                rate: 0.0,
+                max_body_size: 16384,
+                collection_size: 50_000_000,
+            },
+            profanity: Profanity {
+                locales: vec!["en".to_string()],
+            },
+            address_labels: AddressLabels {
+                datasets: vec!["./src/address_labels/starknet.json".to_string()],
+            },
+            exports: Exports {
+                bucket: String::new(),
+                region: "us-east-1".to_string(),
+                endpoint: String::new(),
+                access_key: String::new(),
+                secret_key: String::new(),
+                url_expiry_secs: 3600,
+                tmp_dir: "./exports".to_string(),
+            },
+            transparency: Transparency {
+                private_key: FieldElement::default(),
+            },
+            maintenance: Maintenance {
+                interval_secs: 86400,
+                retention_secs: HashMap::new(),
+                max_documents: HashMap::new(),
+                max_cache_keys: 10000,
+            },
+            activity: Activity {
+                window_secs: 7776000,
+                cache_secs: 86400,
+            },
+            analytics: Analytics {
+                postgres_url: String::new(),
+            },
+            appraisal: Appraisal {
+                club_floors: HashMap::new(),
+                default_floor: 0.0,
+            },
+            distribution: Distribution {
+                interval_secs: 21600,
+            },
+            retention: Retention {
+                interval_secs: 86400,
+                grace_secs: 2592000,
+            },
+            tx_builder: TxBuilder {
+                pricing: FieldElement::default(),
+                simulate: false,
+            },
+            partners: HashMap::new(),
+            user_tokens: UserTokens { ttl_secs: 31536000 },
+            calendar: Calendar {
+                reminder_days: vec![30, 7, 1],
+            },
+            address_display: AddressDisplay {
+                format: AddressFormat::Checksum,
+            },
+            verifier_freshness: VerifierFreshness {
+                stale_after_secs: 31536000,
+            },
+            notifications: Notifications {
+                interval_secs: 86400,
+            },
+            marketplaces: Marketplaces {
+                interval_secs: 60,
+                apis: vec![],
+            },
+            rate_limits: RateLimits {
+                enabled: false,
+                window_secs: 60,
+                light: 600,
+                heavy: 60,
+                write: 30,
+                api_key_multiplier: 10,
+                trust_forwarded_for: false,
+                burst_tiers: HashMap::new(),
+                api_key_quotas: HashMap::new(),
+            },
+            ws: Ws {
+                enabled: false,
+                max_subscriptions: 100,
+                max_backfill_blocks: 1000,
+                max_backfill_events: 1000,
+            },
+            response_cache: ResponseCache {
+                enabled: false,
+                max_entries: 100_000,
+                ttls: HashMap::new(),
+            },
+            ui: Ui {
+                enabled: false,
+                title: "Starknet ID".to_string(),
+            },
+            pagination: Pagination { max_limit: 1000 },
+            fanout: Fanout {
+                branch_timeout_ms: 2000,
+            },
+            grace: GracePolicy {
+                grace_secs: 2592000,
+                resolve_in_grace: true,
+                resolve_expired: false,
+            },
+            availability: Availability { reserved: vec![] },
+            suggestions: Suggestions {
+                dictionaries: HashMap::new(),
+                max_suggestions: 5,
+            },
+            avatars: Avatars {
+                evm_rpc_urls: HashMap::new(),
+                fetch_timeout_ms: 5000,
+                max_image_size: 5_242_880,
+            },
+            images: Images {
+                cache_dir: "./images".to_string(),
+                use_bucket: false,
+                cache_ttl_secs: 86400,
+                widths: vec![32, 64, 128, 256, 512],
+            },
+            compatibility: Compatibility {
+                peer_ttl_secs: 60,
+                force: false,
+            },
+            ipfs: Ipfs {
+                health_check_cid: "QmUNLLsPACCz1vLxQVkXqqLX5R1X345qqfHbsf67hvA3Nn".to_string(),
+                check_interval_secs: 60,
+                timeout_ms: 3000,
+                max_failures: 3,
+                cooldown_secs: 300,
+            },
+            webhooks: Webhooks {
+                enabled: false,
+                interval_secs: 5,
+                batch_size: 500,
+                timeout_ms: 5000,
+                max_attempts: 8,
+                retry_base_secs: 30,
+                retry_max_secs: 6 * 3600,
+                max_per_key: 10,
+                retention_secs: 7 * 24 * 3600,
+            },
+            portfolio: Portfolio {
+                max_items: 50,
+                denied_addresses: vec![],
+                denied_class_hashes: vec![],
+            },
+            networks: Networks {
+                default: "mainnet".to_string(),
+                others: HashMap::new(),
+            },
+            governance: Governance {
+                private_key: FieldElement::default(),
+                attestation_validity_secs: 3600,
+                rules: vec![
+                    PowerRule::DomainHeld {
+                        min_days: 365,
+                        power: 1,
+                    },
+                    PowerRule::ProofOfPersonhood { power: 1 },
+                    PowerRule::Tenure {
+                        per_year: 0.25,
+                        max_multiplier: 2.0,
+                    },
+                ],
+            },
+            fallback: Fallback {
+                rpc_url: String::new(),
+                cache_secs: 60,
+                miss_cache_secs: 10,
+            },
+            status: Status { max_lag_secs: 300 },
+            examples: Examples {
+                enabled: false,
+                domain: "ben.stark".to_string(),
+                address: "0x1".to_string(),
+                id: "1".to_string(),
+            },
+            reminders: Reminders {
+                enabled: false,
+                interval_secs: 3600,
+                days_before: vec![30, 7, 1],
+                email_verifier: FieldElement::ZERO,
+                provider: EmailProvider::Sendgrid {
+                    api_key: String::new(),
+                },
+                from: String::new(),
+                subject: "{domain} expires in {days} days".to_string(),
+                template: "{domain} expires on {expiry}, renew it to keep it.".to_string(),
+            },
+            auto_renewal: AutoRenewal {
+                eth_contract: FieldElement::ZERO,
+                eth_token: FieldElement::ZERO,
+            },
+            cards: Cards {
+                enabled: false,
+                base_url: "https://api.starknet.id".to_string(),
+            },
+            tracing: Tracing {
+                level: "info".to_string(),
+                otlp_endpoint: String::new(),
+                service_name: "starknetid_server".to_string(),
+            },
+            cors: Cors {
+                allow_any_origin: true,
+                allowed_origins: vec![],
+                max_age_secs: 3600,
+                hsts: false,
+            },
+            reorgs: Reorgs { enabled: false },
         }
     }
 }
 
+impl Config {
+    /// Config of another network: its databases, rpc and contracts replace the top level ones
+    /// and it becomes the default network
+    pub fn for_network(&self, name: &str) -> Option<Config> {
+        let network = self.networks.others.get(name)?;
+        let mut conf = self.clone();
+        conf.databases.starknetid = network.starknetid.clone();
+        conf.databases.sales = network.sales.clone();
+        conf.databases.free_domains = network.free_domains.clone();
+        conf.variables.rpc_url = network.rpc_url.clone();
+        conf.contracts = network.contracts.clone();
+        conf.networks = Networks {
+            default: name.to_string(),
+            others: HashMap::new(),
+        };
+        Some(conf)
+    }
+}
+
+fn default_ipfs_gateways() -> Vec<String> {
+    vec!["https://ipfs.io/ipfs/".to_string()]
+}
+
+fn deserialize_gateways<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Gateways {
+        One(String),
+        List(Vec<String>),
+    }
+    Ok(match Gateways::deserialize(deserializer)? {
+        Gateways::One(gateway) => vec![gateway],
+        Gateways::List(gateways) => gateways,
+    })
+}
+
 impl Default for Variables {
     fn default() -> Self {
         Variables {
             rpc_url: "http://localhost:8545".to_string(),
             refresh_delay: 60.0, // Default refresh delay
-            ipfs_gateway: "https://ipfs.io/ipfs/".to_string(),
+            ipfs_gateways: default_ipfs_gateways(),
             discord_token: "default_token".to_string(),
             discord_api_url: "https://discord.com/api".to_string(),
             twitter_api_key: "default_api_key".to_string(),
@@ -0,0 +1,121 @@
+use mongodb::bson::{doc, Document};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::{
+        crypto::pedersen_hash,
+        types::{BlockId, BlockTag, FieldElement, FunctionCall},
+        utils::starknet_keccak,
+    },
+    macros::{selector, short_string},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+
+use crate::{models::AppState, rpc_queue::Priority, utils::to_hex};
+use utoipa::ToSchema;
+
+// means "starknet"
+const STARKNET_FIELD: &str = "0x000000000000000000000000000000000000000000000000737461726b6e6574";
+
+/// How a protocol proves it controls the contract
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClaimMethod {
+    // the account returned by the contract `owner()` signs the claim
+    Owner,
+    // the contract validates the signature itself through `is_valid_signature`
+    Signer,
+}
+
+/// Message signed to claim `domain` for `contract`, the timestamp bounds its replay
+pub fn get_claim_hash(contract: &FieldElement, domain: &str, timestamp: i64) -> FieldElement {
+    let hash = pedersen_hash(&short_string!("contract claim"), contract);
+    let hash = pedersen_hash(&hash, &starknet_keccak(domain.as_bytes()));
+    pedersen_hash(&hash, &FieldElement::from(timestamp as u64))
+}
+
+pub async fn get_contract_owner(state: &AppState, contract: FieldElement) -> Option<FieldElement> {
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).unwrap(),
+    ));
+    // openzeppelin ownable uses owner() while older contracts expose get_owner()
+    for entry_point_selector in [selector!("owner"), selector!("get_owner")] {
+        let call = provider.call(
+            FunctionCall {
+                contract_address: contract,
+                entry_point_selector,
+                calldata: vec![],
+            },
+            BlockId::Tag(BlockTag::Latest),
+        );
+        if let Ok(result) = state
+            .rpc_queue
+            .run(Priority::Interactive, "starknet_call", call)
+            .await
+        {
+            if let Some(owner) = result.first().filter(|owner| **owner != FieldElement::ZERO) {
+                return Some(*owner);
+            }
+        }
+    }
+    None
+}
+
+/// Checks the domain currently resolves to the contract
+pub async fn domain_points_to(
+    state: &AppState,
+    domain: &str,
+    contract: &FieldElement,
+) -> mongodb::error::Result<bool> {
+    let contract = to_hex(contract);
+    let domain_doc = state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .find_one(doc! { "domain": domain, "_cursor.to": null }, None)
+        .await?;
+    let domain_doc = match domain_doc {
+        Some(doc) => doc,
+        None => return Ok(false),
+    };
+    if domain_doc
+        .get_str("legacy_address")
+        .map_or(false, |addr| addr == contract)
+    {
+        return Ok(true);
+    }
+    let id = match domain_doc.get_str("id") {
+        Ok(id) => id,
+        Err(_) => return Ok(false),
+    };
+    let user_data = state
+        .starknetid_db
+        .collection::<Document>("id_user_data")
+        .find_one(
+            doc! { "id": id, "field": STARKNET_FIELD, "_cursor.to": null, "data": &contract },
+            None,
+        )
+        .await?;
+    Ok(user_data.is_some())
+}
+
+/// A claim only holds while the domain still resolves to the claimed contract
+pub async fn is_contract_verified(state: &AppState, domain: &str) -> bool {
+    let claim = state
+        .starknetid_db
+        .collection::<Document>("contract_claims")
+        .find_one(doc! { "domain": domain }, None)
+        .await;
+    let contract = match claim {
+        Ok(Some(claim)) => claim
+            .get_str("contract")
+            .ok()
+            .and_then(|contract| FieldElement::from_hex_be(contract).ok()),
+        _ => None,
+    };
+    match contract {
+        Some(contract) => domain_points_to(state, domain, &contract)
+            .await
+            .unwrap_or(false),
+        None => false,
+    }
+}
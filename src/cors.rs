@@ -0,0 +1,79 @@
+use std::{sync::Arc, time::Duration};
+
+use axum::{
+    extract::State,
+    http::{
+        header::{
+            REFERRER_POLICY, STRICT_TRANSPORT_SECURITY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS,
+        },
+        HeaderName, HeaderValue, Method, Request,
+    },
+    middleware::Next,
+    response::Response,
+};
+use tower_http::cors::{AllowOrigin, Any, CorsLayer};
+
+use crate::{config::Cors, models::AppState, request_tracing::REQUEST_ID_HEADER};
+
+// a year, long enough for the browsers to stop trying plain http
+const HSTS: &str = "max-age=31536000; includeSubDomains";
+
+/// Origin allowed by one of the patterns, `https://*.example.com` allows the subdomains of
+/// example.com but not example.com itself
+pub fn is_allowed_origin(patterns: &[String], origin: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| match pattern.split_once("*.") {
+            Some((scheme, domain)) => origin
+                .strip_prefix(scheme)
+                .and_then(|host| host.strip_suffix(domain))
+                .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+            None => pattern == origin,
+        })
+}
+
+/// Cors of the responses, any origin for the public deployments or else the configured ones
+pub fn get_cors_layer(conf: &Cors) -> CorsLayer {
+    let origin = match conf.allow_any_origin {
+        true => AllowOrigin::any(),
+        false => {
+            let patterns = conf.allowed_origins.clone();
+            AllowOrigin::predicate(move |origin: &HeaderValue, _| {
+                origin
+                    .to_str()
+                    .is_ok_and(|origin| is_allowed_origin(&patterns, origin))
+            })
+        }
+    };
+    CorsLayer::new()
+        .allow_origin(origin)
+        .allow_methods([Method::GET, Method::POST])
+        .allow_headers(Any)
+        .expose_headers([HeaderName::from_static(REQUEST_ID_HEADER)])
+        .max_age(Duration::from_secs(conf.max_age_secs))
+}
+
+/// Headers keeping the browsers from sniffing, framing or leaking the urls of the responses,
+/// the ones a handler set itself are kept
+pub async fn set_security_headers<B>(
+    State(state): State<Arc<AppState>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+    let mut security = vec![
+        (X_CONTENT_TYPE_OPTIONS, "nosniff"),
+        (X_FRAME_OPTIONS, "DENY"),
+        (REFERRER_POLICY, "no-referrer"),
+    ];
+    if state.conf.cors.hsts {
+        security.push((STRICT_TRANSPORT_SECURITY, HSTS));
+    }
+    for (name, value) in security {
+        if !headers.contains_key(&name) {
+            headers.insert(name, HeaderValue::from_static(value));
+        }
+    }
+    response
+}
@@ -0,0 +1,80 @@
+use std::{sync::Arc, time::Duration};
+
+use mongodb::{
+    event::cmap::{
+        CmapEventHandler, ConnectionCheckedInEvent, ConnectionCheckedOutEvent,
+        ConnectionCheckoutFailedEvent, ConnectionCheckoutStartedEvent, ConnectionClosedEvent,
+        ConnectionCreatedEvent,
+    },
+    options::ClientOptions,
+};
+
+use crate::{
+    config::{Database, MongoPool},
+    metrics::{labeled, Metrics},
+    request_tracing::QueryCounter,
+};
+
+/// Mirrors the state of a connection pool in the metrics
+struct PoolMetrics {
+    db: String,
+    metrics: Arc<Metrics>,
+}
+
+impl PoolMetrics {
+    fn add(&self, name: &str, delta: i64) {
+        self.metrics.add(&labeled(name, "db", &self.db), delta);
+    }
+}
+
+impl CmapEventHandler for PoolMetrics {
+    fn handle_connection_created_event(&self, _event: ConnectionCreatedEvent) {
+        self.add("mongo_pool_connections", 1);
+    }
+
+    // idle connections are closed after max_idle_time, which shrinks the pool
+    fn handle_connection_closed_event(&self, _event: ConnectionClosedEvent) {
+        self.add("mongo_pool_connections", -1);
+    }
+
+    fn handle_connection_checkout_started_event(&self, _event: ConnectionCheckoutStartedEvent) {
+        self.add("mongo_pool_wait_queue", 1);
+    }
+
+    fn handle_connection_checkout_failed_event(&self, _event: ConnectionCheckoutFailedEvent) {
+        self.add("mongo_pool_wait_queue", -1);
+        self.add("mongo_pool_checkout_failures", 1);
+    }
+
+    fn handle_connection_checked_out_event(&self, _event: ConnectionCheckedOutEvent) {
+        self.add("mongo_pool_wait_queue", -1);
+        self.add("mongo_pool_in_use", 1);
+    }
+
+    fn handle_connection_checked_in_event(&self, _event: ConnectionCheckedInEvent) {
+        self.add("mongo_pool_in_use", -1);
+    }
+}
+
+pub async fn get_client_options(
+    database: &Database,
+    pool: &MongoPool,
+    metrics: &Arc<Metrics>,
+) -> mongodb::error::Result<ClientOptions> {
+    let mut options = ClientOptions::parse(&database.connection_string).await?;
+    options.min_pool_size = Some(pool.min_connections);
+    options.max_pool_size = Some(pool.max_connections);
+    options.max_idle_time = Some(Duration::from_secs(pool.max_idle_secs));
+    // the driver bounds the wait for a free connection by the server selection timeout
+    options.server_selection_timeout = Some(Duration::from_millis(pool.wait_queue_timeout_ms));
+    metrics.set(
+        &labeled("mongo_pool_max_connections", "db", &database.name),
+        pool.max_connections as i64,
+    );
+    options.cmap_event_handler = Some(Arc::new(PoolMetrics {
+        db: database.name.clone(),
+        metrics: metrics.clone(),
+    }));
+    options.command_event_handler = Some(Arc::new(QueryCounter));
+    Ok(options)
+}
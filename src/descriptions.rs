@@ -0,0 +1,69 @@
+use anyhow::Result;
+use mongodb::bson::doc;
+use starknet::{
+    core::{crypto::pedersen_hash, types::FieldElement, utils::starknet_keccak},
+    macros::short_string,
+};
+
+use crate::{profanity::ProfanityFilter, storage::Storage, utils::to_hex};
+
+pub const DESCRIPTIONS_COLLECTION: &str = "id_descriptions";
+
+pub const MAX_DESCRIPTION_LEN: usize = 280;
+// signed descriptions can't be replayed after this delay
+pub const MAX_SIGNATURE_AGE: i64 = 600;
+
+/// Message signed by the identity owner to set its description
+pub fn get_description_hash(id: &FieldElement, description: &str, timestamp: i64) -> FieldElement {
+    let hash = pedersen_hash(&short_string!("set description"), id);
+    let hash = pedersen_hash(&hash, &starknet_keccak(description.as_bytes()));
+    pedersen_hash(&hash, &FieldElement::from(timestamp as u64))
+}
+
+/// Moderation screening of a description before it is stored
+pub fn validate_description(filter: &ProfanityFilter, description: &str) -> Result<(), String> {
+    if description.chars().count() > MAX_DESCRIPTION_LEN {
+        return Err(format!(
+            "Description must be at most {} characters",
+            MAX_DESCRIPTION_LEN
+        ));
+    }
+    if description.chars().any(|c| c.is_control() && c != '\n') {
+        return Err("Description contains control characters".to_string());
+    }
+    if filter.find_text_match(description).is_some() {
+        return Err("Description contains a blocked term".to_string());
+    }
+    Ok(())
+}
+
+pub async fn get_id_owner(
+    storage: &dyn Storage,
+    id: &FieldElement,
+) -> Result<Option<FieldElement>> {
+    Ok(storage
+        .find_one("id_owners", doc! { "id": to_hex(id), "_cursor.to": null })
+        .await?
+        .and_then(|doc| {
+            doc.get_str("owner")
+                .ok()
+                .and_then(|owner| FieldElement::from_hex_be(owner).ok())
+        }))
+}
+
+/// Description set by the current owner, it is dropped when the identity is transferred
+pub async fn get_description(
+    storage: &dyn Storage,
+    id: &FieldElement,
+    owner: &FieldElement,
+) -> Option<String> {
+    storage
+        .find_one(
+            DESCRIPTIONS_COLLECTION,
+            doc! { "id": to_hex(id), "owner": to_hex(owner) },
+        )
+        .await
+        .ok()
+        .flatten()
+        .and_then(|doc| doc.get_str("description").ok().map(String::from))
+}
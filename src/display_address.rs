@@ -0,0 +1,57 @@
+use ethers::utils::keccak256;
+use serde::Deserialize;
+use starknet::core::types::FieldElement;
+
+use crate::utils::to_hex;
+
+/// How `display_address` fields are formatted, the raw hex fields are never changed
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFormat {
+    // mixed case checksum of starknet.js `getChecksumAddress`
+    Checksum,
+    Lowercase,
+}
+
+/// EIP-55 style checksum: the letters whose nibble in the keccak256 of the address is 8 or
+/// more are uppercased, the address is zero padded to 64 characters
+pub fn get_checksum_address(address: &FieldElement) -> String {
+    let bytes = address.to_bytes_be();
+    // the hash is computed on the address without its leading zero bytes
+    let start = bytes
+        .iter()
+        .position(|byte| *byte != 0)
+        .unwrap_or(bytes.len() - 1);
+    let hash = keccak256(&bytes[start..]);
+    let chars: String = to_hex(address)[2..]
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = if i % 2 == 0 {
+                hash[i / 2] >> 4
+            } else {
+                hash[i / 2] & 0x0f
+            };
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{}", chars)
+}
+
+pub fn format_address(address: &FieldElement, format: AddressFormat) -> String {
+    match format {
+        AddressFormat::Checksum => get_checksum_address(address),
+        AddressFormat::Lowercase => to_hex(address),
+    }
+}
+
+/// Display form of an address stored as hex, None if it isn't a valid felt
+pub fn format_hex_address(address: &str, format: AddressFormat) -> Option<String> {
+    FieldElement::from_hex_be(address)
+        .ok()
+        .map(|address| format_address(&address, format))
+}
@@ -0,0 +1,147 @@
+use std::{collections::HashMap, sync::RwLock};
+
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::FindOptions,
+};
+use serde::Serialize;
+
+use crate::models::AppState;
+
+// lower bounds of the buckets, the last one is open ended
+const HOLDINGS_BOUNDS: [i64; 7] = [1, 2, 3, 5, 10, 50, 100];
+const AGE_BOUNDS_DAYS: [i64; 6] = [0, 30, 90, 180, 365, 730];
+const LENGTH_BOUNDS: [i64; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Bucket {
+    // inclusive
+    pub from: i64,
+    // exclusive, none for the last bucket
+    pub to: Option<i64>,
+    pub count: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DistributionReport {
+    pub computed_at: i64,
+    pub holders: u64,
+    pub domains: u64,
+    // share of the domains held by the 100 largest holders
+    pub top_100_share: f64,
+    pub domains_per_holder: Vec<Bucket>,
+    pub registration_age_days: Vec<Bucket>,
+    pub length: Vec<Bucket>,
+}
+
+/// Last distribution computed by the scheduled job
+#[derive(Default)]
+pub struct DistributionStats {
+    report: RwLock<Option<DistributionReport>>,
+}
+
+impl DistributionStats {
+    pub fn report(&self) -> Option<DistributionReport> {
+        self.report.read().unwrap().clone()
+    }
+}
+
+/// Counts the values falling in each bucket, values under the first bound are ignored
+pub fn get_histogram(values: impl IntoIterator<Item = i64>, bounds: &[i64]) -> Vec<Bucket> {
+    let mut buckets: Vec<Bucket> = bounds
+        .iter()
+        .enumerate()
+        .map(|(i, from)| Bucket {
+            from: *from,
+            to: bounds.get(i + 1).copied(),
+            count: 0,
+        })
+        .collect();
+    for value in values {
+        if let Some(bucket) = buckets.iter_mut().rev().find(|bucket| value >= bucket.from) {
+            bucket.count += 1;
+        }
+    }
+    buckets
+}
+
+pub fn get_top_share(mut holdings: Vec<u64>, top: usize) -> f64 {
+    let total: u64 = holdings.iter().sum();
+    if total == 0 {
+        return 0.0;
+    }
+    holdings.sort_unstable_by(|a, b| b.cmp(a));
+    holdings.iter().take(top).sum::<u64>() as f64 / total as f64
+}
+
+async fn get_owners(state: &AppState) -> mongodb::error::Result<HashMap<String, String>> {
+    let options = FindOptions::builder()
+        .projection(doc! { "_id": 0, "id": 1, "owner": 1 })
+        .build();
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("id_owners")
+        .find(doc! { "_cursor.to": null }, options)
+        .await?;
+    let mut owners = HashMap::new();
+    while let Some(doc) = cursor.next().await {
+        let doc = doc?;
+        if let (Ok(id), Ok(owner)) = (doc.get_str("id"), doc.get_str("owner")) {
+            owners.insert(id.to_string(), owner.to_string());
+        }
+    }
+    Ok(owners)
+}
+
+/// Recomputes the histograms from the live domains, root domains only
+pub async fn compute(state: &AppState) -> mongodb::error::Result<DistributionReport> {
+    let now = chrono::Utc::now().timestamp();
+    let owners = get_owners(state).await?;
+    let options = FindOptions::builder()
+        .projection(doc! { "_id": 0, "domain": 1, "id": 1, "creation_date": 1 })
+        .build();
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .find(
+            doc! { "_cursor.to": null, "expiry": { "$gte": now } },
+            options,
+        )
+        .await?;
+
+    let mut holdings: HashMap<&str, u64> = HashMap::new();
+    let mut ages = vec![];
+    let mut lengths = vec![];
+    while let Some(doc) = cursor.next().await {
+        let doc = doc?;
+        let label = match doc
+            .get_str("domain")
+            .ok()
+            .and_then(|domain| domain.strip_suffix(".stark"))
+        {
+            Some(label) if !label.contains('.') => label,
+            _ => continue,
+        };
+        lengths.push(label.chars().count() as i64);
+        if let Ok(creation_date) = doc.get_i64("creation_date") {
+            ages.push((now - creation_date) / 86400);
+        }
+        if let Some(owner) = doc.get_str("id").ok().and_then(|id| owners.get(id)) {
+            *holdings.entry(owner.as_str()).or_default() += 1;
+        }
+    }
+
+    let holdings: Vec<u64> = holdings.into_values().collect();
+    let report = DistributionReport {
+        computed_at: now,
+        holders: holdings.len() as u64,
+        domains: lengths.len() as u64,
+        top_100_share: get_top_share(holdings.clone(), 100),
+        domains_per_holder: get_histogram(holdings.iter().map(|h| *h as i64), &HOLDINGS_BOUNDS),
+        registration_age_days: get_histogram(ages, &AGE_BOUNDS_DAYS),
+        length: get_histogram(lengths, &LENGTH_BOUNDS),
+    };
+    *state.distribution.report.write().unwrap() = Some(report.clone());
+    Ok(report)
+}
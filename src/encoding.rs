@@ -0,0 +1,96 @@
+use axum::{
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+
+/// Body formats a client can ask for with the `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Encoding {
+    Json,
+    Cbor,
+    MessagePack,
+}
+
+impl Encoding {
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type {
+            "application/json" => Some(Encoding::Json),
+            "application/cbor" => Some(Encoding::Cbor),
+            "application/msgpack" | "application/x-msgpack" | "application/vnd.msgpack" => {
+                Some(Encoding::MessagePack)
+            }
+            _ => None,
+        }
+    }
+
+    /// First supported media type of `Accept`, json by default
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        headers
+            .get(header::ACCEPT)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|accept| {
+                accept.split(',').find_map(|entry| {
+                    let mut parts = entry.split(';');
+                    let encoding = Encoding::from_media_type(parts.next()?.trim())?;
+                    let refused = parts.any(|param| {
+                        param
+                            .trim()
+                            .strip_prefix("q=")
+                            .and_then(|q| q.parse::<f32>().ok())
+                            .map_or(false, |q| q <= 0.0)
+                    });
+                    (!refused).then_some(encoding)
+                })
+            })
+            .unwrap_or(Encoding::Json)
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Encoding::Json => "application/json",
+            Encoding::Cbor => "application/cbor",
+            Encoding::MessagePack => "application/msgpack",
+        }
+    }
+
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, String> {
+        match self {
+            Encoding::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+            Encoding::Cbor => {
+                let mut bytes = Vec::new();
+                ciborium::ser::into_writer(value, &mut bytes).map_err(|e| e.to_string())?;
+                Ok(bytes)
+            }
+            // maps keep the field names so the payload matches the json one
+            Encoding::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+/// Like `Json` but serialized in the format negotiated with the client
+pub struct Encoded<T>(pub Encoding, pub T);
+
+impl<T: Serialize> IntoResponse for Encoded<T> {
+    fn into_response(self) -> Response {
+        let Encoded(encoding, value) = self;
+        let mut response = match encoding {
+            Encoding::Json => Json(value).into_response(),
+            _ => match encoding.encode(&value) {
+                Ok(bytes) => (
+                    [(
+                        header::CONTENT_TYPE,
+                        HeaderValue::from_static(encoding.content_type()),
+                    )],
+                    bytes,
+                )
+                    .into_response(),
+                Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, e).into_response(),
+            },
+        };
+        response
+            .headers_mut()
+            .insert(header::VARY, HeaderValue::from_static("Accept"));
+        response
+    }
+}
@@ -1,6 +1,8 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
-    utils::{get_error, to_hex},
+    types::starknet_addr::StarknetAddr,
+    utils::to_hex,
 };
 use axum::{
     extract::{Query, State},
@@ -11,17 +13,18 @@ use axum::{
 use axum_auto_routes::route;
 use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct AddrToDomainData {
     has_rev: bool,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct AddrHasRevQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
 }
 
 #[route(get, "/addr_has_rev", crate::endpoints::addr_has_rev)]
@@ -51,6 +54,10 @@ pub async fn handler(
             }),
         )
             .into_response(),
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(_) => ApiError::new(
+            ErrorCode::DatabaseError,
+            "Error while fetching from database",
+        )
+        .into_response(),
     }
 }
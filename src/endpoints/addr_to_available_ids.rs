@@ -1,7 +1,9 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
     resolving::get_custom_resolver,
-    utils::{get_error, to_hex},
+    types::starknet_addr::StarknetAddr,
+    utils::to_hex,
 };
 use axum::{
     extract::{Query, State},
@@ -15,15 +17,17 @@ use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct AvailableIds {
     ids: Vec<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct AddrQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
 }
 
 #[route(get, "/addr_to_available_ids", crate::endpoints::addr_to_available_ids)]
@@ -72,7 +76,10 @@ pub async fn handler(
                         if let Some(doc) = doc_opt {
                             let domain_rs = doc.get_str("domain");
                             if let Ok(domain) = domain_rs {
-                                if get_custom_resolver(&domains, domain ,&state).await.is_none() {
+                                if get_custom_resolver(&domains, domain, &state)
+                                    .await
+                                    .is_none()
+                                {
                                     continue;
                                 }
                             }
@@ -83,6 +90,10 @@ pub async fn handler(
             }
             (StatusCode::OK, Json(AvailableIds { ids })).into_response()
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(_) => ApiError::new(
+            ErrorCode::DatabaseError,
+            "Error while fetching from database",
+        )
+        .into_response(),
     }
 }
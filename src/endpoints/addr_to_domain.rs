@@ -1,13 +1,18 @@
 use crate::{
+    address_labels::AddressLabel,
+    encoding::{Encoded, Encoding},
+    errors::{ApiError, ErrorCode},
+    finality::Finality,
+    grace::ExpiryStatus,
     models::AppState,
-    utils::{get_error, to_hex},
+    types::starknet_addr::StarknetAddr,
+    utils::to_hex,
 };
 use anyhow::{bail, Result};
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
-    Json,
 };
 use axum_auto_routes::route;
 use futures::StreamExt;
@@ -17,28 +22,47 @@ use mongodb::{
     Cursor,
 };
 use serde::{Deserialize, Serialize};
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct AddrToDomainData {
-    domain: String,
+    domain: Option<String>,
     domain_expiry: Option<i64>,
+    #[serde(skip_serializing_if = "ExpiryStatus::is_active")]
+    expiry_status: ExpiryStatus,
+    finality: Option<Finality>,
+    // public tag of the address when it has no domain
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<AddressLabel>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct AddrToDomainQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
 }
 
-async fn read_cursor(mut cursor: Cursor<Document>) -> Result<AddrToDomainData> {
+async fn read_cursor(state: &AppState, mut cursor: Cursor<Document>) -> Result<AddrToDomainData> {
     if let Some(result) = cursor.next().await {
         let doc = result?;
         let domain = doc.get_str("domain").unwrap_or_default().to_owned();
         let domain_expiry = doc.get_i64("domain_expiry").ok();
+        let display = state
+            .conf
+            .grace
+            .get_display(domain_expiry, chrono::Utc::now().timestamp());
+        // the next pipelines may still find a domain which resolves
+        if !display.resolves {
+            bail!("The domain of the address is expired")
+        }
+        let finality = doc.get_i64("block").ok().map(|block| state.finality(block));
         Ok(AddrToDomainData {
-            domain,
+            domain: Some(domain),
             domain_expiry,
+            expiry_status: display.status,
+            finality,
+            label: None,
         })
     } else {
         bail!("No document found for the given address")
@@ -46,20 +70,23 @@ async fn read_cursor(mut cursor: Cursor<Document>) -> Result<AddrToDomainData> {
 }
 
 async fn aggregate_data(
+    state: &AppState,
     collection: mongodb::Collection<Document>,
     pipeline: Vec<Document>,
 ) -> Result<AddrToDomainData> {
     let cursor = collection
         .aggregate(pipeline, AggregateOptions::default())
         .await?;
-    read_cursor(cursor).await
+    read_cursor(state, cursor).await
 }
 
 #[route(get, "/addr_to_domain", crate::endpoints::addr_to_domain)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Query(query): Query<AddrToDomainQuery>,
 ) -> impl IntoResponse {
+    let encoding = Encoding::from_headers(&headers);
     let hex_addr = to_hex(&query.addr);
     let domains_collection = state.starknetid_db.collection::<Document>("domains");
     let id_owners_collection = state.starknetid_db.collection::<Document>("id_owners");
@@ -69,19 +96,36 @@ pub async fn handler(
     let main_id_pipeline = create_main_id_pipeline(&hex_addr);
 
     let results = [
-        aggregate_data(domains_collection.clone(), legacy_pipeline),
-        aggregate_data(domains_collection.clone(), normal_pipeline),
-        aggregate_data(id_owners_collection, main_id_pipeline),
+        aggregate_data(&state, domains_collection.clone(), legacy_pipeline),
+        aggregate_data(&state, domains_collection.clone(), normal_pipeline),
+        aggregate_data(&state, id_owners_collection, main_id_pipeline),
     ];
 
     for result in results {
         match result.await {
-            Ok(data) => return (StatusCode::OK, Json(data)).into_response(),
+            Ok(data) => return (StatusCode::OK, Encoded(encoding, data)).into_response(),
             Err(_) => continue,
         }
     }
 
-    get_error("No data found for the given address".to_string())
+    match state.address_labels.get(&query.addr) {
+        Some(label) => (
+            StatusCode::OK,
+            Encoded(
+                encoding,
+                AddrToDomainData {
+                    domain: None,
+                    domain_expiry: None,
+                    expiry_status: ExpiryStatus::Active,
+                    finality: None,
+                    label: Some(label.clone()),
+                },
+            ),
+        )
+            .into_response(),
+        None => ApiError::new(ErrorCode::NotFound, "No data found for the given address")
+            .into_response(),
+    }
 }
 
 fn create_legacy_pipeline(address: &String) -> Vec<Document> {
@@ -91,7 +135,8 @@ fn create_legacy_pipeline(address: &String) -> Vec<Document> {
         } } },
         doc! { "$project": {
             "domain": 1,
-            "domain_expiry" : "$expiry"
+            "domain_expiry" : "$expiry",
+            "block": "$_cursor.from"
         }},
     ]
 }
@@ -309,7 +354,8 @@ fn create_normal_pipeline(address: &String) -> Vec<Document> {
         doc! {
             "$project": doc! {
                 "domain": 1,
-                "domain_expiry": "$expiry"
+                "domain_expiry": "$expiry",
+                "block": "$_cursor.from"
             }
         },
     ]
@@ -332,7 +378,9 @@ fn create_main_id_pipeline(address: &String) -> Vec<Document> {
         doc! { "$unwind": "$domain_data" },
         doc! { "$project": {
             "domain": "$domain_data.domain",
-            "domain_expiry" : "$domain_data.expiry"
+            "domain_expiry" : "$domain_data.expiry",
+            // the main id is only final once both documents are
+            "block": { "$max": ["$_cursor.from", "$domain_data._cursor.from"] }
         }},
     ]
 }
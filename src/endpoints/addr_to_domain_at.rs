@@ -0,0 +1,65 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    history::{addr_to_domain_at, get_lookup_block, ResolutionAt},
+    models::AppState,
+    types::starknet_addr::StarknetAddr,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Serialize)]
+pub struct AddrToDomainAtData {
+    #[serde(flatten)]
+    resolution: ResolutionAt,
+    block: i64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct AddrToDomainAtQuery {
+    #[param(value_type = String)]
+    addr: StarknetAddr,
+    block: Option<u64>,
+    timestamp: Option<u64>,
+}
+
+#[route(get, "/addr_to_domain_at", crate::endpoints::addr_to_domain_at)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AddrToDomainAtQuery>,
+) -> impl IntoResponse {
+    let block = match get_lookup_block(&state, query.block, query.timestamp).await {
+        Ok(block) => block,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to find the block: {}", e),
+            )
+            .into_response()
+        }
+    };
+    match addr_to_domain_at(state.storage.as_ref(), &query.addr, block).await {
+        Ok(Some(resolution)) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=3600"));
+            let data = AddrToDomainAtData { resolution, block };
+            (StatusCode::OK, headers, Json(data)).into_response()
+        }
+        Ok(None) => ApiError::new(
+            ErrorCode::DomainNotFound,
+            "No domain found for the address at this block",
+        )
+        .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -1,5 +1,8 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
+    pagination::{get_limit, get_sort, PageCursor, BLOCK_ORDER},
+    types::starknet_addr::StarknetAddr,
     utils::{get_error, to_hex},
 };
 use axum::{
@@ -9,7 +12,7 @@ use axum::{
 };
 use axum_auto_routes::route;
 use futures::StreamExt;
-use mongodb::bson::doc;
+use mongodb::{bson::doc, options::FindOptions};
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::sync::Arc; // for stream handling
@@ -17,11 +20,15 @@ use std::sync::Arc; // for stream handling
 #[derive(Serialize)]
 pub struct DomainData {
     domains: Vec<String>,
+    next_cursor: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct DomainQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
+    limit: Option<i64>,
+    cursor: Option<String>,
 }
 
 #[route(
@@ -36,25 +43,36 @@ pub async fn handler(
     let subdomains = state
         .starknetid_db
         .collection::<mongodb::bson::Document>("custom_resolutions");
+    use utoipa::IntoParams;
     let addr = &query.addr;
     let mut domains_list = Vec::new();
 
-    let cursor = subdomains
-        .find(
-            doc! {
-                "field" : "0x000000000000000000000000000000000000000000000000737461726b6e6574", // starknet encoded
-                "value": to_hex(addr),
-                "_cursor.to": null,
-            },
-            None,
-        )
-        .await;
+    let mut filter = doc! {
+        "field" : "0x000000000000000000000000000000000000000000000000737461726b6e6574", // starknet encoded
+        "value": to_hex(addr),
+        "_cursor.to": null,
+    };
+    if let Some(cursor) = &query.cursor {
+        match PageCursor::decode(cursor) {
+            Some(cursor) => filter.extend(cursor.after("_cursor.from")),
+            None => return get_error("Invalid cursor".to_string()),
+        }
+    }
+    let limit = get_limit(query.limit, state.conf.pagination.max_limit);
+    let options = FindOptions::builder()
+        .sort(get_sort(BLOCK_ORDER))
+        .limit(limit)
+        .build();
+    let cursor = subdomains.find(filter, options).await;
 
     match cursor {
         Ok(mut cursor) => {
+            let (mut count, mut last_cursor) = (0, None);
             while let Some(result) = cursor.next().await {
                 match result {
                     Ok(doc) => {
+                        count += 1;
+                        last_cursor = PageCursor::from_document(&doc, "_cursor.from");
                         let domain_slice =
                             doc.get_str("domain_slice").unwrap_or_default().to_owned();
                         let resolver =
@@ -71,7 +89,13 @@ pub async fn handler(
                             None => {}
                         }
                     }
-                    Err(_) => return get_error("Error while fetching from database".to_string()),
+                    Err(_) => {
+                        return ApiError::new(
+                            ErrorCode::DatabaseError,
+                            "Error while fetching from database",
+                        )
+                        .into_response()
+                    }
                 }
             }
 
@@ -79,11 +103,22 @@ pub async fn handler(
             let mut headers = HeaderMap::new();
             headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
 
+            // pages are counted in resolutions, a full one might be followed by others
+            let next_cursor = if count == limit {
+                last_cursor.map(|cursor| cursor.encode())
+            } else {
+                None
+            };
             let response = axum::response::Json(DomainData {
                 domains: domains_list,
+                next_cursor,
             });
             (StatusCode::OK, headers, response).into_response()
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(_) => ApiError::new(
+            ErrorCode::DatabaseError,
+            "Error while fetching from database",
+        )
+        .into_response(),
     }
 }
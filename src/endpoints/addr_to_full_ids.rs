@@ -1,23 +1,30 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
+    pagination::{get_limit, get_sort, PageCursor, BLOCK_ORDER},
+    streaming::{stream_json_response, Envelope},
+    types::starknet_addr::StarknetAddr,
     utils::{fetch_img_url, get_error, to_hex, to_u256},
 };
 use axum::{
     extract::{Query, State},
-    http::StatusCode,
+    http::HeaderMap,
     response::IntoResponse,
-    Json,
 };
 use axum_auto_routes::route;
-use futures::future::join_all;
-use futures::stream::StreamExt;
+use futures::stream::{self, StreamExt};
 use mongodb::{
     bson::{doc, Bson},
     options::AggregateOptions,
 };
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Map};
 use starknet::core::types::FieldElement;
-use std::sync::Arc;
+use std::{convert::Infallible, sync::Arc};
+use utoipa::IntoParams;
+
+// lookups of the profile pictures running at once
+const MAX_PP_FETCHES: usize = 16;
 
 #[derive(Serialize, Deserialize)]
 pub struct FullId {
@@ -43,14 +50,12 @@ struct NFTPP {
     id: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct AddrQuery {
-    addr: FieldElement,
-}
-
-#[derive(Serialize)]
-pub struct FullIdResponse {
-    full_ids: Vec<FullId>,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
+    limit: Option<i64>,
+    cursor: Option<String>,
 }
 
 #[route(get, "/addr_to_full_ids", crate::endpoints::addr_to_full_ids)]
@@ -62,16 +67,25 @@ pub async fn handler(
         .starknetid_db
         .collection::<mongodb::bson::Document>("id_owners");
 
+    let mut filter = doc! {
+        "owner": to_hex(&query.addr),
+        "id" : {
+            "$ne" : null
+          },
+        "_cursor.to": Bson::Null
+    };
+    if let Some(cursor) = &query.cursor {
+        match PageCursor::decode(cursor) {
+            Some(cursor) => filter.extend(cursor.after("_cursor.from")),
+            None => return get_error("Invalid cursor".to_string()),
+        }
+    }
+    let limit = get_limit(query.limit, state.conf.pagination.max_limit);
+
     let pipeline = [
-        doc! {
-            "$match": doc! {
-                "owner": to_hex(&query.addr),
-                "id" : {
-                    "$ne" : null
-                  },
-                "_cursor.to": Bson::Null
-            }
-        },
+        doc! { "$match": filter },
+        doc! { "$sort": get_sort(BLOCK_ORDER) },
+        doc! { "$limit": limit },
         doc! {
             "$lookup": doc! {
                 "from": "domains",
@@ -141,7 +155,8 @@ pub async fn handler(
         },
         doc! {
             "$project": doc! {
-                "_id": 0,
+                "_id": 1,
+                "block": "$_cursor.from",
                 "id": 1,
                 "domain": "$domainData.domain",
                 "domain_expiry": "$domainData.expiry",
@@ -156,8 +171,10 @@ pub async fn handler(
     match cursor {
         Ok(mut cursor) => {
             let mut temp_full_ids = Vec::new();
+            let mut last_cursor = None;
             while let Some(doc) = cursor.next().await {
                 if let Ok(doc) = doc {
+                    last_cursor = PageCursor::from_document(&doc, "block");
                     let id = FieldElement::from_hex_be(
                         &doc.get_str("id").unwrap_or_default().to_owned(),
                     )
@@ -194,10 +211,10 @@ pub async fn handler(
                                 })
                                 .collect();
 
-                            let id = to_u256(data_id.get(0).unwrap(), data_id.get(1).unwrap())
-                                .to_string();
-
-                            pp_url_info = Some((contract_str, id));
+                            if let (Some(low), Some(high)) = (data_id.first(), data_id.get(1)) {
+                                pp_url_info =
+                                    to_u256(low, high).map(|id| (contract_str, id.to_string()));
+                            }
                         }
                     }
                     temp_full_ids.push(TempsFullId {
@@ -208,11 +225,17 @@ pub async fn handler(
                     });
                 }
             }
+            // a full page means there might be more identities after it
+            let next_cursor = if temp_full_ids.len() as i64 == limit {
+                last_cursor.map(|cursor| cursor.encode())
+            } else {
+                None
+            };
             let api_url = state.conf.starkscan.api_url.clone();
             let api_key = state.conf.starkscan.api_key.clone();
-            let full_ids_futures: Vec<_> = temp_full_ids
-                .iter()
-                .map(|id| {
+            // identities are written in order as soon as their picture is known
+            let full_ids = stream::iter(temp_full_ids)
+                .map(move |id| {
                     let api_url_clone = api_url.clone();
                     let api_key_clone = api_key.clone();
                     async move {
@@ -229,21 +252,26 @@ pub async fn handler(
                             None => None,
                         };
 
-                        FullId {
-                            id: id.id.clone(),
-                            domain: id.domain.clone(),
+                        Ok::<_, Infallible>(FullId {
+                            id: id.id,
+                            domain: id.domain,
                             domain_expiry: id.domain_expiry,
                             pp_url: pp_url,
-                        }
+                        })
                     }
                 })
-                .collect();
-
-            let full_ids: Vec<_> = join_all(full_ids_futures).await;
+                .buffered(MAX_PP_FETCHES);
 
-            let response = FullIdResponse { full_ids: full_ids };
-            (StatusCode::OK, Json(response)).into_response()
+            let envelope = Envelope::Object {
+                field: "full_ids",
+                extra: Map::from_iter([("next_cursor".to_string(), json!(next_cursor))]),
+            };
+            stream_json_response(HeaderMap::new(), envelope, full_ids)
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(_) => ApiError::new(
+            ErrorCode::DatabaseError,
+            "Error while fetching from database",
+        )
+        .into_response(),
     }
 }
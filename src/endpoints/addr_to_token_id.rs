@@ -1,6 +1,8 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
-    utils::{get_error, to_hex},
+    types::starknet_addr::StarknetAddr,
+    utils::to_hex,
 };
 use axum::{
     extract::{Query, State},
@@ -12,15 +14,17 @@ use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct TokenIdData {
     token_id: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct TokenIdQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
 }
 
 #[route(get, "/addr_to_token_id", crate::endpoints::addr_to_token_id)]
@@ -56,9 +60,17 @@ pub async fn handler(
                 let data = TokenIdData { token_id: id };
                 (StatusCode::OK, headers, Json(data)).into_response()
             } else {
-                get_error("no main domain found for this address".to_string())
+                ApiError::new(
+                    ErrorCode::DomainNotFound,
+                    "no main domain found for this address",
+                )
+                .into_response()
             }
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(_) => ApiError::new(
+            ErrorCode::DatabaseError,
+            "Error while fetching from database",
+        )
+        .into_response(),
     }
 }
@@ -0,0 +1,44 @@
+use crate::{
+    addressbook::{resolve_contacts, MAX_CONTACTS},
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    utils::get_error,
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct ResolveSyncQuery {
+    // sha256 of the contact addresses, see get_contact_hash
+    hashes: Vec<String>,
+}
+
+#[route(
+    post,
+    "/addressbook/resolve_sync",
+    crate::endpoints::addressbook::resolve_sync
+)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<ResolveSyncQuery>,
+) -> impl IntoResponse {
+    if query.hashes.len() > MAX_CONTACTS {
+        return get_error(format!("At most {} contacts can be synced", MAX_CONTACTS));
+    }
+    match resolve_contacts(state.storage.as_ref(), &query.hashes).await {
+        Ok(matches) => (StatusCode::OK, Json(json!({ "matches": matches }))).into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -1,8 +1,16 @@
-use crate::{models::AppState, utils::to_hex};
+use crate::{
+    config::GracePolicy,
+    encoding::{Encoded, Encoding},
+    errors::{ApiError, ErrorCode},
+    grace::ExpiryStatus,
+    models::AppState,
+    types::starknet_addr::StarknetAddr,
+    utils::{get_error, to_hex},
+};
 use anyhow::{Context, Result};
 use axum::{
     extract::{Json, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::IntoResponse,
 };
 use axum_auto_routes::route;
@@ -13,30 +21,45 @@ use mongodb::{
     Cursor,
 };
 use serde::{Deserialize, Serialize};
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::ToSchema;
+
+// bounds the $in lists of the pipelines
+const MAX_ADDRESSES: usize = 500;
 
 #[derive(Serialize, Debug)]
 struct AddrToDomainData {
     domain: Option<String>,
     address: String,
+    #[serde(skip_serializing_if = "ExpiryStatus::is_active")]
+    expiry_status: ExpiryStatus,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct AddrToDomainsQuery {
-    addresses: Vec<FieldElement>,
+    #[schema(value_type = Vec<String>)]
+    addresses: Vec<StarknetAddr>,
 }
 
 async fn process_cursor(
     mut cursor: Cursor<Document>,
     results: &mut Vec<AddrToDomainData>,
+    grace: &GracePolicy,
 ) -> Result<()> {
+    let now = chrono::Utc::now().timestamp();
     while let Some(result) = cursor.next().await {
         let doc = result.context("Failed to retrieve document from cursor")?;
+        let display = grace.get_display(doc.get_i64("expiry").ok(), now);
+        // left for the next pipelines
+        if !display.resolves {
+            continue;
+        }
         if let (Ok(domain), Ok(address)) = (doc.get_str("domain"), doc.get_str("address")) {
-            if let Some(data) = results.iter_mut().find(|d| d.address == address) {
-                if data.domain == None {
+            // an address can be listed several times
+            for data in results.iter_mut().filter(|d| d.address == address) {
+                if data.domain.is_none() {
                     data.domain = Some(domain.to_string());
+                    data.expiry_status = display.status;
                 }
             }
         }
@@ -45,6 +68,7 @@ async fn process_cursor(
 }
 
 async fn run_aggregation_pipeline(
+    state: &AppState,
     collection: mongodb::Collection<Document>,
     pipeline: Vec<Document>,
     results: &mut Vec<AddrToDomainData>,
@@ -54,14 +78,22 @@ async fn run_aggregation_pipeline(
         .await
         .context("Failed to execute aggregation pipeline")?;
 
-    process_cursor(cursor, results).await
+    process_cursor(cursor, results, &state.conf.grace).await
 }
 
 #[route(post, "/addrs_to_domains", crate::endpoints::addrs_to_domains)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
     Json(query): Json<AddrToDomainsQuery>,
 ) -> impl IntoResponse {
+    let encoding = Encoding::from_headers(&headers);
+    if query.addresses.len() > MAX_ADDRESSES {
+        return get_error(format!(
+            "At most {} addresses can be resolved",
+            MAX_ADDRESSES
+        ));
+    }
     let domains_collection = state
         .starknetid_db
         .collection::<mongodb::bson::Document>("domains");
@@ -69,28 +101,39 @@ pub async fn handler(
         .starknetid_db
         .collection::<mongodb::bson::Document>("id_owners");
 
-    let addresses: Vec<String> = query.addresses.iter().map(to_hex).collect();
+    let addresses: Vec<String> = query.addresses.iter().map(|addr| addr.to_hex()).collect();
 
     let mut results = addresses
         .iter()
         .map(|addr| AddrToDomainData {
             domain: None,
             address: addr.clone(),
+            expiry_status: ExpiryStatus::Active,
         })
         .collect::<Vec<_>>();
 
     let legacy_pipeline = create_legacy_pipeline(&addresses);
-    if let Err(e) =
-        run_aggregation_pipeline(domains_collection.clone(), legacy_pipeline, &mut results).await
+    if let Err(e) = run_aggregation_pipeline(
+        &state,
+        domains_collection.clone(),
+        legacy_pipeline,
+        &mut results,
+    )
+    .await
     {
-        return (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response();
+        return ApiError::new(ErrorCode::DatabaseError, e.to_string()).into_response();
     }
 
     let normal_pipeline = create_normal_pipeline(&addresses);
-    if let Err(e) =
-        run_aggregation_pipeline(domains_collection.clone(), normal_pipeline, &mut results).await
+    if let Err(e) = run_aggregation_pipeline(
+        &state,
+        domains_collection.clone(),
+        normal_pipeline,
+        &mut results,
+    )
+    .await
     {
-        return (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response();
+        return ApiError::new(ErrorCode::DatabaseError, e.to_string()).into_response();
     }
 
     let fallback_addresses = results
@@ -98,14 +141,21 @@ pub async fn handler(
         .filter_map(|data| data.domain.is_none().then(|| data.address.clone()))
         .collect::<Vec<_>>();
 
-    let fallback_pipeline = create_fallback_pipeline(&fallback_addresses);
-    if let Err(e) =
-        run_aggregation_pipeline(id_owners_collection, fallback_pipeline, &mut results).await
-    {
-        return (StatusCode::INTERNAL_SERVER_ERROR, Json(e.to_string())).into_response();
+    if !fallback_addresses.is_empty() {
+        let fallback_pipeline = create_fallback_pipeline(&fallback_addresses);
+        if let Err(e) = run_aggregation_pipeline(
+            &state,
+            id_owners_collection,
+            fallback_pipeline,
+            &mut results,
+        )
+        .await
+        {
+            return ApiError::new(ErrorCode::DatabaseError, e.to_string()).into_response();
+        }
     }
 
-    (StatusCode::OK, Json(results)).into_response()
+    (StatusCode::OK, Encoded(encoding, results)).into_response()
 }
 
 fn create_legacy_pipeline(addresses: &[String]) -> Vec<Document> {
@@ -122,6 +172,7 @@ fn create_legacy_pipeline(addresses: &[String]) -> Vec<Document> {
                 "_id": 0,
                 "domain": 1,
                 "address": "$legacy_address",
+                "expiry": 1,
             },
         },
     ]
@@ -311,6 +362,7 @@ fn create_normal_pipeline(addresses: &[String]) -> Vec<Document> {
         doc! { "$project": {
             "domain": 1,
             "address" : "$rev_address",
+            "expiry": 1,
         }},
     ]
 }
@@ -343,6 +395,7 @@ fn create_fallback_pipeline(fallback_addresses: &[String]) -> Vec<Document> {
                 "_id": 0,
                 "domain": "$domain_data.domain",
                 "address": "$owner",
+                "expiry": "$domain_data.expiry",
             }
         },
     ]
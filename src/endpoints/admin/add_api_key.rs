@@ -0,0 +1,84 @@
+use crate::{
+    auth::{is_admin, ApiKey},
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+};
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::{
+    bson::{doc, oid::ObjectId, to_document, Document},
+    options::UpdateOptions,
+};
+use rand::{distributions::Alphanumeric, Rng};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct AddApiKeyQuery {
+    name: String,
+    namespaces: Vec<String>,
+    tier: Option<String>,
+}
+
+#[route(post, "/admin/add_api_key", crate::endpoints::admin::add_api_key)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(query): Json<AddApiKeyQuery>,
+) -> impl IntoResponse {
+    if !is_admin(&state, &headers) {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid admin key").into_response();
+    }
+
+    let api_key = ApiKey {
+        id: ObjectId::new(),
+        key: rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(40)
+            .map(char::from)
+            .collect(),
+        name: query.name,
+        namespaces: query.namespaces,
+        enabled: true,
+        tier: query.tier,
+    };
+    let document = match to_document(&api_key) {
+        Ok(document) => document,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::InternalError,
+                format!("Unable to serialize api key: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let api_keys = state.starknetid_db.collection::<Document>("api_keys");
+    // the names own the webhooks, exports and quotas of the keys, a taken one is refused
+    match api_keys
+        .update_one(
+            doc! { "name": &api_key.name },
+            doc! { "$setOnInsert": document },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await
+    {
+        Ok(result) if result.upserted_id.is_some() => {
+            (StatusCode::OK, Json(api_key)).into_response()
+        }
+        Ok(_) => ApiError::new(
+            ErrorCode::Conflict,
+            format!("An api key named {} already exists", api_key.name),
+        )
+        .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while updating database: {}", e),
+        )
+        .into_response(),
+    }
+}
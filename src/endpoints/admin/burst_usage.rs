@@ -0,0 +1,26 @@
+use crate::{
+    auth::is_admin,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde_json::json;
+use std::sync::Arc;
+
+#[route(get, "/admin/usage/burst", crate::endpoints::admin::burst_usage)]
+pub async fn handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> impl IntoResponse {
+    if !is_admin(&state, &headers) {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid admin key").into_response();
+    }
+
+    let now = chrono::Utc::now().timestamp_millis() as f64 / 1000.0;
+    let usage = state
+        .rate_limiter
+        .get_burst_usage(&state.conf.rate_limits, now);
+    (StatusCode::OK, Json(json!({ "api_keys": usage }))).into_response()
+}
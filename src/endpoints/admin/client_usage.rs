@@ -0,0 +1,26 @@
+use crate::{
+    auth::is_admin,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde_json::json;
+use std::sync::Arc;
+
+#[route(get, "/admin/usage/clients", crate::endpoints::admin::client_usage)]
+pub async fn handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> impl IntoResponse {
+    if !is_admin(&state, &headers) {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid admin key").into_response();
+    }
+
+    (
+        StatusCode::OK,
+        Json(json!({ "classes": state.client_usage.report() })),
+    )
+        .into_response()
+}
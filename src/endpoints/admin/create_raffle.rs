@@ -0,0 +1,98 @@
+use crate::{
+    auth::is_admin,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    raffle::{get_commitment, EligibilityRule, Raffle, RaffleAudit},
+    utils::get_error,
+};
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::bson::{doc, to_document, Document};
+use rand::Rng;
+use serde::Deserialize;
+use starknet::core::utils::cairo_short_string_to_felt;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct CreateRaffleQuery {
+    id: String,
+    name: String,
+    winners_count: i64,
+    entry_deadline: i64,
+    #[schema(inline)]
+    rules: Vec<EligibilityRule>,
+}
+
+#[route(post, "/admin/create_raffle", crate::endpoints::admin::create_raffle)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(query): Json<CreateRaffleQuery>,
+) -> impl IntoResponse {
+    if !is_admin(&state, &headers) {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid admin key").into_response();
+    }
+    // the id is signed by entrants as a short string
+    if query.id.is_empty() || cairo_short_string_to_felt(&query.id).is_err() {
+        return get_error("Raffle id must be a short string of 31 characters max".to_string());
+    }
+    if query.winners_count <= 0 {
+        return get_error("winners_count must be positive".to_string());
+    }
+    if query.entry_deadline <= chrono::Utc::now().timestamp() {
+        return get_error("entry_deadline must be in the future".to_string());
+    }
+
+    let raffles = state.free_domains_db.collection::<Document>("raffles");
+    match raffles.find_one(doc! { "id": &query.id }, None).await {
+        Ok(None) => {}
+        Ok(Some(_)) => {
+            return ApiError::new(ErrorCode::Conflict, "A raffle with this id already exists")
+                .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    }
+
+    let mut seed = [0u8; 32];
+    rand::thread_rng().fill(&mut seed);
+    let raffle = Raffle {
+        id: query.id,
+        name: query.name,
+        winners_count: query.winners_count,
+        entry_deadline: query.entry_deadline,
+        rules: query.rules,
+        commitment: get_commitment(&seed),
+        seed: format!("0x{}", hex::encode(seed)),
+        closing: None,
+        draw: None,
+    };
+    let document = match to_document(&raffle) {
+        Ok(document) => document,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::InternalError,
+                format!("Unable to serialize raffle: {}", e),
+            )
+            .into_response()
+        }
+    };
+    match raffles.insert_one(document, None).await {
+        Ok(_) => (StatusCode::OK, Json(RaffleAudit::from(raffle))).into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while updating database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,49 @@
+use crate::{
+    auth::is_admin,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+};
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::bson::{doc, Document};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct DeleteThemeQuery {
+    namespace: String,
+}
+
+#[route(post, "/admin/delete_theme", crate::endpoints::admin::delete_theme)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(query): Json<DeleteThemeQuery>,
+) -> impl IntoResponse {
+    if !is_admin(&state, &headers) {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid admin key").into_response();
+    }
+
+    let themes = state.starknetid_db.collection::<Document>("themes");
+    match themes
+        .delete_one(doc! { "namespace": &query.namespace }, None)
+        .await
+    {
+        Ok(result) if result.deleted_count > 0 => {
+            (StatusCode::OK, Json("Theme deleted".to_string())).into_response()
+        }
+        Ok(_) => {
+            ApiError::new(ErrorCode::NotFound, "No theme found for this namespace").into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while updating database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,191 @@
+use crate::{
+    auth::is_admin,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    raffle::{
+        close_raffle, draw_winners, get_entrants, get_entrants_hash, Raffle, RaffleAudit,
+        RaffleDraw, CLOSE_DELAY_SECS,
+    },
+    utils::{get_block_at_timestamp, to_hex},
+};
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::bson::{doc, from_document, to_bson, Document};
+use reqwest::Url;
+use serde::Deserialize;
+use starknet::{
+    core::types::{BlockId, MaybePendingBlockWithTxHashes},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct DrawRaffleQuery {
+    id: String,
+}
+
+#[route(post, "/admin/draw_raffle", crate::endpoints::admin::draw_raffle)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(query): Json<DrawRaffleQuery>,
+) -> impl IntoResponse {
+    if !is_admin(&state, &headers) {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid admin key").into_response();
+    }
+
+    let raffles = state.free_domains_db.collection::<Document>("raffles");
+    let mut raffle = match raffles.find_one(doc! { "id": &query.id }, None).await {
+        Ok(Some(doc)) => match from_document::<Raffle>(doc) {
+            Ok(raffle) => raffle,
+            Err(e) => {
+                return ApiError::new(ErrorCode::InternalError, format!("Malformed raffle: {}", e))
+                    .into_response()
+            }
+        },
+        Ok(None) => return ApiError::new(ErrorCode::NotFound, "Raffle not found").into_response(),
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    if raffle.draw.is_some() {
+        return ApiError::new(ErrorCode::Conflict, "Raffle already drawn").into_response();
+    }
+    let closing = match &raffle.closing {
+        Some(closing) => closing.clone(),
+        None if chrono::Utc::now().timestamp() <= raffle.entry_deadline + CLOSE_DELAY_SECS => {
+            return ApiError::new(ErrorCode::Conflict, "Raffle entries are still open")
+                .into_response()
+        }
+        None => match close_raffle(&state, &raffle).await {
+            Ok(closing) => closing,
+            Err(e) => {
+                return ApiError::new(
+                    ErrorCode::DatabaseError,
+                    format!("Unable to close raffle: {}", e),
+                )
+                .into_response()
+            }
+        },
+    };
+
+    // the first block after the closing provides entropy the API couldn't know when committing
+    // to the seed nor when publishing the entrants hash
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).unwrap(),
+    ));
+    let block_number = match get_block_at_timestamp(&provider, closing.closed_at as u64).await {
+        Ok(block_number) => block_number + 1,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to find closing block: {}", e),
+            )
+            .into_response()
+        }
+    };
+    match provider.block_number().await {
+        Ok(latest) if latest >= block_number => {}
+        Ok(_) => {
+            return ApiError::new(ErrorCode::Conflict, "Seeding block not produced yet")
+                .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to fetch latest block: {}", e),
+            )
+            .into_response()
+        }
+    }
+    let block_hash = match provider
+        .get_block_with_tx_hashes(BlockId::Number(block_number))
+        .await
+    {
+        Ok(MaybePendingBlockWithTxHashes::Block(block)) => block.block_hash,
+        Ok(MaybePendingBlockWithTxHashes::PendingBlock(_)) => {
+            return ApiError::new(ErrorCode::Conflict, "Seeding block is still pending")
+                .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to fetch seeding block: {}", e),
+            )
+            .into_response()
+        }
+    };
+
+    let entrants = match get_entrants(&state, &raffle).await {
+        Ok(entrants) => entrants,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while reading entries: {}", e),
+            )
+            .into_response()
+        }
+    };
+    // the winners are drawn among the entrants published at the closing only
+    if get_entrants_hash(&entrants) != closing.entrants_hash {
+        return ApiError::new(
+            ErrorCode::Conflict,
+            "Entrants changed since the entries closed",
+        )
+        .into_response();
+    }
+
+    let seed = match hex::decode(raffle.seed.trim_start_matches("0x")) {
+        Ok(seed) => seed,
+        Err(_) => {
+            return ApiError::new(ErrorCode::InternalError, "Malformed raffle seed").into_response()
+        }
+    };
+    let draw = RaffleDraw {
+        block_number: block_number as i64,
+        block_hash: to_hex(&block_hash),
+        entrants_hash: closing.entrants_hash.clone(),
+        winners: draw_winners(&seed, &block_hash, &entrants, raffle.winners_count as usize),
+        drawn_at: chrono::Utc::now().timestamp(),
+    };
+    let draw_bson = match to_bson(&draw) {
+        Ok(draw_bson) => draw_bson,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::InternalError,
+                format!("Unable to serialize draw: {}", e),
+            )
+            .into_response()
+        }
+    };
+    // only the first draw is ever stored
+    match raffles
+        .update_one(
+            doc! { "id": &raffle.id, "draw": null },
+            doc! { "$set": { "draw": draw_bson } },
+            None,
+        )
+        .await
+    {
+        Ok(result) if result.modified_count == 1 => {
+            raffle.closing = Some(closing);
+            raffle.draw = Some(draw);
+            (StatusCode::OK, Json(RaffleAudit::from(raffle))).into_response()
+        }
+        Ok(_) => ApiError::new(ErrorCode::Conflict, "Raffle already drawn").into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while updating database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,69 @@
+use crate::{
+    auth::is_admin,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    pagination::get_limit,
+    sampling::SAMPLES_COLLECTION,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::FindOptions,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+const MAX_SAMPLES: i64 = 100;
+
+#[derive(Deserialize, IntoParams)]
+pub struct GetSamplesQuery {
+    path: Option<String>,
+    limit: Option<i64>,
+}
+
+#[route(get, "/admin/get_samples", crate::endpoints::admin::get_samples)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<GetSamplesQuery>,
+) -> impl IntoResponse {
+    if !is_admin(&state, &headers) {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid admin key").into_response();
+    }
+
+    let filter = match query.path {
+        Some(path) => doc! { "path": path },
+        None => doc! {},
+    };
+    // most recent samples first
+    let options = FindOptions::builder()
+        .sort(doc! { "$natural": -1 })
+        .limit(get_limit(query.limit, MAX_SAMPLES))
+        .projection(doc! { "_id": 0 })
+        .build();
+    let samples = state
+        .starknetid_db
+        .collection::<Document>(SAMPLES_COLLECTION);
+    match samples.find(filter, options).await {
+        Ok(cursor) => match cursor.try_collect::<Vec<Document>>().await {
+            Ok(samples) => (StatusCode::OK, Json(samples)).into_response(),
+            Err(e) => ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while reading samples: {}", e),
+            )
+            .into_response(),
+        },
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{
+    auth::is_admin,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    rendering::theme::Theme,
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use futures::StreamExt;
+use mongodb::bson::{doc, Document};
+use std::sync::Arc;
+
+#[route(get, "/admin/get_themes", crate::endpoints::admin::get_themes)]
+pub async fn handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> impl IntoResponse {
+    if !is_admin(&state, &headers) {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid admin key").into_response();
+    }
+
+    let themes = state.starknetid_db.collection::<Document>("themes");
+    match themes.find(doc! {}, None).await {
+        Ok(mut cursor) => {
+            let mut results: Vec<Theme> = Vec::new();
+            while let Some(result) = cursor.next().await {
+                match result.map(mongodb::bson::from_document::<Theme>) {
+                    Ok(Ok(theme)) => results.push(theme),
+                    _ => {
+                        return ApiError::new(
+                            ErrorCode::InternalError,
+                            "Error while parsing themes",
+                        )
+                        .into_response()
+                    }
+                }
+            }
+            (StatusCode::OK, Json(results)).into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
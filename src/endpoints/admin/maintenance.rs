@@ -0,0 +1,43 @@
+use crate::{
+    auth::is_admin,
+    errors::{ApiError, ErrorCode},
+    maintenance,
+    models::AppState,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct MaintenanceQuery {
+    // runs the maintenance now instead of returning the last report
+    #[serde(default)]
+    run: bool,
+}
+
+#[route(get, "/admin/maintenance", crate::endpoints::admin::maintenance)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<MaintenanceQuery>,
+) -> impl IntoResponse {
+    if !is_admin(&state, &headers) {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid admin key").into_response();
+    }
+
+    let report = if query.run {
+        Some(maintenance::run(&state).await)
+    } else {
+        state.maintenance.last_report()
+    };
+    match report {
+        Some(report) => (StatusCode::OK, Json(report)).into_response(),
+        None => ApiError::new(ErrorCode::NotFound, "Maintenance has not run yet").into_response(),
+    }
+}
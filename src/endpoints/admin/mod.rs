@@ -0,0 +1,11 @@
+pub mod add_api_key;
+pub mod burst_usage;
+pub mod client_usage;
+pub mod create_raffle;
+pub mod delete_theme;
+pub mod draw_raffle;
+pub mod get_samples;
+pub mod get_themes;
+pub mod maintenance;
+pub mod metrics;
+pub mod rpc_usage;
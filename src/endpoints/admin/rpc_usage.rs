@@ -0,0 +1,25 @@
+use crate::{
+    auth::is_admin,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use std::sync::Arc;
+
+#[route(get, "/admin/usage/rpc", crate::endpoints::admin::rpc_usage)]
+pub async fn handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> impl IntoResponse {
+    if !is_admin(&state, &headers) {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid admin key").into_response();
+    }
+
+    (
+        StatusCode::OK,
+        Json(state.rpc_queue.usage.report(&state.conf.rpc_budget)),
+    )
+        .into_response()
+}
@@ -1,4 +1,11 @@
-use crate::{ecdsa_sign::non_determinist_ecdsa_sign, models::AppState, utils::get_error};
+use crate::{
+    ecdsa_sign::non_determinist_ecdsa_sign,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    transparency::append_entry,
+    types::starknet_addr::StarknetAddr,
+    utils::get_error,
+};
 use axum::{
     extract::{Query, State},
     response::{IntoResponse, Json},
@@ -13,10 +20,12 @@ use starknet_crypto::pedersen_hash;
 use std::sync::Arc;
 
 use crate::utils::to_hex;
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct FreeDomainQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
     code: String,
     domain: String,
 }
@@ -42,6 +51,9 @@ pub async fn handler(
         return get_error("Domain must be a root domain".to_string());
     }
     let domain_len = domain_parts[0].len();
+    if !state.profanity.is_allowed(&query.domain) {
+        return get_error("This domain name is not allowed".to_string());
+    }
 
     let free_domains = state
         .free_domains_db
@@ -72,15 +84,25 @@ pub async fn handler(
                             )
                                 .into_response();
                         } else {
-                            return get_error(format!("Coupon code already used by {}\nIf you own this account, this means you have already used this coupon code with the other account. Please switch to it.", spent_by));
+                            return ApiError::new(ErrorCode::Conflict, format!("Coupon code already used by {}\nIf you own this account, this means you have already used this coupon code with the other account. Please switch to it.", spent_by)).into_response();
                         }
                     } else {
-                        return get_error("Coupon code already used by someone else".to_string());
+                        return ApiError::new(
+                            ErrorCode::Conflict,
+                            "Coupon code already used by someone else",
+                        )
+                        .into_response();
                     }
                 }
             } else {
-                logger.warning(format!("Error while verifying coupon code spent status and user address"));
-                return get_error("Error while verifying coupon code availability".to_string());
+                logger.warning(format!(
+                    "Error while verifying coupon code spent status and user address"
+                ));
+                return ApiError::new(
+                    ErrorCode::DatabaseError,
+                    "Error while verifying coupon code availability",
+                )
+                .into_response();
             }
 
             // Check domain length matches the coupon type
@@ -94,15 +116,22 @@ pub async fn handler(
                             ));
                         }
                     } else {
-                        return get_error(
-                            "Failed to parse the numeric part of the coupon type".to_string(),
-                        );
+                        return ApiError::new(
+                            ErrorCode::InternalError,
+                            "Failed to parse the numeric part of the coupon type",
+                        )
+                        .into_response();
                     }
                 } else {
-                    return get_error("Invalid coupon type format".to_string());
+                    return ApiError::new(ErrorCode::InternalError, "Invalid coupon type format")
+                        .into_response();
                 }
             } else {
-                return get_error("Error while verifying coupon code type".to_string());
+                return ApiError::new(
+                    ErrorCode::DatabaseError,
+                    "Error while verifying coupon code type",
+                )
+                .into_response();
             }
 
             // generate the signature
@@ -112,6 +141,22 @@ pub async fn handler(
                 &message_hash,
             ) {
                 Ok(signature) => {
+                    if let Err(e) = append_entry(
+                        &state,
+                        "free_domain",
+                        query.domain.clone(),
+                        &query.addr,
+                        &message_hash,
+                        &signature,
+                    )
+                    .await
+                    {
+                        return ApiError::new(
+                            ErrorCode::InternalError,
+                            format!("Unable to log the issuance: {}", e),
+                        )
+                        .into_response();
+                    }
                     // we blacklist the coupon code
                     match free_domains
                         .update_one(
@@ -174,22 +219,35 @@ pub async fn handler(
                                     })),
                                 )
                                     .into_response(),
-                                Ok(response) => get_error(format!(
-                                    "Paymaster API request failed with status: {}",
-                                    response.status()
-                                )),
-                                Err(e) => get_error(format!(
-                                    "Error while requesting Paymaster API: {}",
-                                    e
-                                )),
+                                Ok(response) => ApiError::new(
+                                    ErrorCode::UpstreamError,
+                                    format!(
+                                        "Paymaster API request failed with status: {}",
+                                        response.status()
+                                    ),
+                                )
+                                .into_response(),
+                                Err(e) => ApiError::new(
+                                    ErrorCode::UpstreamError,
+                                    format!("Error while requesting Paymaster API: {}", e),
+                                )
+                                .into_response(),
                             }
                         }
-                        Err(e) => get_error(format!("Error while updating coupon code: {}", e)),
+                        Err(e) => ApiError::new(
+                            ErrorCode::DatabaseError,
+                            format!("Error while updating coupon code: {}", e),
+                        )
+                        .into_response(),
                     }
                 }
-                Err(e) => get_error(format!("Error while generating signature: {}", e)),
+                Err(e) => ApiError::new(
+                    ErrorCode::InternalError,
+                    format!("Error while generating signature: {}", e),
+                )
+                .into_response(),
             }
         }
-        _ => get_error("Coupon code not found".to_string()),
+        _ => ApiError::new(ErrorCode::NotFound, "Coupon code not found").into_response(),
     }
 }
@@ -0,0 +1,145 @@
+use crate::{
+    clubs::{appraise, get_club},
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    utils::{get_error, normalize_domain},
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::FindOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+const MAX_DOMAINS: usize = 10;
+const MAX_SALES: i64 = 5;
+
+#[derive(Deserialize, IntoParams)]
+pub struct CompareQuery {
+    // comma separated, e.g. a.stark,b.stark
+    domains: String,
+}
+
+#[derive(Serialize)]
+pub struct Sale {
+    timestamp: i64,
+    payer: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct DomainComparison {
+    domain: String,
+    registered: bool,
+    length: usize,
+    creation_date: Option<i64>,
+    // days since the registration
+    age: Option<i64>,
+    expiry: Option<i64>,
+    club: Option<&'static str>,
+    sales_count: u64,
+    last_sales: Vec<Sale>,
+    appraisal: f64,
+}
+
+async fn compare_domain(
+    state: &AppState,
+    domain: String,
+) -> mongodb::error::Result<DomainComparison> {
+    let now = chrono::Utc::now().timestamp();
+    let domain_doc = state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .find_one(doc! { "domain": &domain, "_cursor.to": null }, None)
+        .await?;
+    let creation_date = domain_doc
+        .as_ref()
+        .and_then(|doc| doc.get_i64("creation_date").ok());
+    let expiry = domain_doc
+        .as_ref()
+        .and_then(|doc| doc.get_i64("expiry").ok());
+
+    let sales = state.sales_db.collection::<Document>("sales");
+    let filter = doc! { "domain": &domain, "_cursor.to": null };
+    let sales_count = sales.count_documents(filter.clone(), None).await?;
+    let options = FindOptions::builder()
+        .sort(doc! { "timestamp": -1 })
+        .limit(MAX_SALES)
+        .build();
+    let last_sales = sales
+        .find(filter, options)
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?
+        .into_iter()
+        .map(|doc| Sale {
+            timestamp: doc.get_i64("timestamp").unwrap_or_default(),
+            payer: doc.get_str("payer").ok().map(String::from),
+        })
+        .collect();
+
+    let club = get_club(&domain);
+    Ok(DomainComparison {
+        length: domain
+            .split('.')
+            .next()
+            .map_or(0, |label| label.chars().count()),
+        registered: domain_doc.is_some(),
+        creation_date,
+        age: creation_date.map(|creation_date| (now - creation_date) / 86400),
+        expiry,
+        club,
+        sales_count,
+        last_sales,
+        appraisal: appraise(
+            &state.conf.appraisal.club_floors,
+            state.conf.appraisal.default_floor,
+            club,
+        ),
+        domain,
+    })
+}
+
+#[route(get, "/compare", crate::endpoints::compare)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CompareQuery>,
+) -> impl IntoResponse {
+    let mut domains: Vec<String> = query
+        .domains
+        .split(',')
+        .map(normalize_domain)
+        .filter(|domain| !domain.is_empty())
+        .collect();
+    domains.dedup();
+    if domains.is_empty() {
+        return get_error("No domain to compare".to_string());
+    }
+    if domains.len() > MAX_DOMAINS {
+        return get_error(format!("At most {} domains can be compared", MAX_DOMAINS));
+    }
+
+    let mut comparisons = Vec::with_capacity(domains.len());
+    for domain in domains {
+        match compare_domain(&state, domain).await {
+            Ok(comparison) => comparisons.push(comparison),
+            Err(e) => {
+                return ApiError::new(
+                    ErrorCode::DatabaseError,
+                    format!("Error while fetching from database: {}", e),
+                )
+                .into_response()
+            }
+        }
+    }
+    let mut headers = HeaderMap::new();
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=60"));
+    (StatusCode::OK, headers, Json(comparisons)).into_response()
+}
@@ -0,0 +1,108 @@
+use crate::{
+    auth::verify_account_signature,
+    contract_claims::{domain_points_to, get_claim_hash, get_contract_owner, ClaimMethod},
+    descriptions::MAX_SIGNATURE_AGE,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    utils::{deserialize_domain, to_hex},
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::{bson::doc, bson::Document, options::UpdateOptions};
+use serde::Deserialize;
+use serde_json::json;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct ClaimQuery {
+    #[schema(value_type = String)]
+    contract: FieldElement,
+    #[serde(deserialize_with = "deserialize_domain")]
+    domain: String,
+    #[schema(inline)]
+    method: ClaimMethod,
+    timestamp: i64,
+    // signature of pedersen(pedersen(pedersen('contract claim', contract), keccak(domain)),
+    // timestamp)
+    #[schema(value_type = Vec<String>)]
+    signature: Vec<FieldElement>,
+}
+
+#[route(post, "/contracts/claim", crate::endpoints::contracts::claim)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<ClaimQuery>,
+) -> impl IntoResponse {
+    if (chrono::Utc::now().timestamp() - query.timestamp).abs() > MAX_SIGNATURE_AGE {
+        return ApiError::new(ErrorCode::SignatureExpired, "Signature expired").into_response();
+    }
+
+    match domain_points_to(&state, &query.domain, &query.contract).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return ApiError::new(
+                ErrorCode::ContractNotResolved,
+                "Domain doesn't resolve to this contract",
+            )
+            .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    }
+
+    let signer = match query.method {
+        ClaimMethod::Owner => match get_contract_owner(&state, query.contract).await {
+            Some(owner) => owner,
+            None => {
+                return ApiError::new(
+                    ErrorCode::UpstreamError,
+                    "Unable to read the contract owner",
+                )
+                .into_response()
+            }
+        },
+        ClaimMethod::Signer => query.contract,
+    };
+    let message_hash = get_claim_hash(&query.contract, &query.domain, query.timestamp);
+    if !verify_account_signature(&state, signer, message_hash, &query.signature).await {
+        return ApiError::new(ErrorCode::InvalidSignature, "Invalid signature").into_response();
+    }
+
+    let claims = state
+        .starknetid_db
+        .collection::<Document>("contract_claims");
+    match claims
+        .update_one(
+            doc! { "domain": &query.domain },
+            doc! {
+                "$set": {
+                    "domain": &query.domain,
+                    "contract": to_hex(&query.contract),
+                    "method": mongodb::bson::to_bson(&query.method).unwrap(),
+                    "signer": to_hex(&signer),
+                    "verified_at": chrono::Utc::now().timestamp(),
+                }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await
+    {
+        Ok(_) => (StatusCode::OK, Json(json!({ "contract_verified": true }))).into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while saving claim: {}", e),
+        )
+        .into_response(),
+    }
+}
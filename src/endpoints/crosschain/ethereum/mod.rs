@@ -1,4 +1,5 @@
 pub mod lookup;
 pub mod resolve;
+pub mod resolve_get;
 pub mod text_records;
 pub mod utils;
@@ -8,7 +8,9 @@ use crate::{
             get_user_data_multicall, sign_message, to_eth_hex,
         },
     },
+    errors::{ApiError, ErrorCode},
     models::AppState,
+    rpc_queue::Priority,
     utils::{get_error, to_hex},
 };
 use anyhow::Result;
@@ -109,6 +111,12 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
         Query::Json(data) => (data.data, data.sender.to_lowercase()),
         Query::Form(data) => (data.data, data.sender.to_lowercase()),
     };
+    resolve(&state, encoded_data, sender).await
+}
+
+/// Signed EIP-3668 response of the `resolve(bytes,bytes)` call encoded in `encoded_data`,
+/// for the offchain resolver contract `sender`
+pub async fn resolve(state: &Arc<AppState>, encoded_data: String, sender: String) -> Response {
     let logger = &state.logger;
 
     match decode_data(&encoded_data) {
@@ -130,20 +138,26 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
             ));
             let mut calldata: Vec<FieldElement> = vec![FieldElement::from(encoded_domain.len())];
             calldata.extend(encoded_domain.clone());
-            let call_result = provider
-                .call(
-                    FunctionCall {
-                        contract_address: state.conf.contracts.naming,
-                        entry_point_selector: selector!("domain_to_id"),
-                        calldata,
-                    },
-                    BlockId::Tag(BlockTag::Latest),
-                )
+            let call = provider.call(
+                FunctionCall {
+                    contract_address: state.conf.contracts.naming,
+                    entry_point_selector: selector!("domain_to_id"),
+                    calldata,
+                },
+                BlockId::Tag(BlockTag::Latest),
+            );
+            let call_result = state
+                .rpc_queue
+                .run(Priority::Interactive, "starknet_call", call)
                 .await;
             match call_result {
                 Ok(result) => {
                     if result[0] == FieldElement::ZERO {
-                        return get_error(format!("No identity found for : {}", name));
+                        return ApiError::new(
+                            ErrorCode::IdentityNotFound,
+                            format!("No identity found for : {}", name),
+                        )
+                        .into_response();
                     }
                     let id: FieldElement = result[0];
 
@@ -165,10 +179,12 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
                                     {
                                         Some(pfp) => vec![Token::String(pfp)],
                                         None => {
-                                            return get_error(
+                                            return ApiError::new(
+                                                ErrorCode::NotFound,
                                                 "No profile picture specified for this domain"
                                                     .to_string(),
                                             )
+                                            .into_response()
                                         }
                                     }
                                 }
@@ -189,10 +205,14 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
                                                     vec![Token::String(record_data)]
                                                 }
                                                 None => {
-                                                    return get_error(format!(
-                                                        "No data found for record: {}",
-                                                        record
-                                                    ));
+                                                    return ApiError::new(
+                                                        ErrorCode::NotFound,
+                                                        format!(
+                                                            "No data found for record: {}",
+                                                            record
+                                                        ),
+                                                    )
+                                                    .into_response();
                                                 }
                                             }
                                         }
@@ -206,10 +226,14 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
                                             {
                                                 Some(data) => vec![Token::String(data)],
                                                 None => {
-                                                    return get_error(format!(
-                                                        "No data found for record: {}",
-                                                        record
-                                                    ));
+                                                    return ApiError::new(
+                                                        ErrorCode::NotFound,
+                                                        format!(
+                                                            "No data found for record: {}",
+                                                            record
+                                                        ),
+                                                    )
+                                                    .into_response();
                                                 }
                                             }
                                         }
@@ -240,19 +264,21 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
                                             let bytes =
                                                 ethers::utils::hex::decode(trimmed_hex_addr)
                                                     .map_err(|err| {
-                                                        get_error(format!(
-                                                            "Invalid Structure: {}",
-                                                            err
-                                                        ))
+                                                        ApiError::new(
+                                                            ErrorCode::InternalError,
+                                                            format!("Invalid Structure: {}", err),
+                                                        )
                                                     })
                                                     .unwrap();
                                             vec![Token::Bytes(bytes)]
                                         }
                                         None => {
-                                            return get_error(
+                                            return ApiError::new(
+                                                ErrorCode::NotFound,
                                                 "No starknet address specified for this domain"
                                                     .to_string(),
-                                            );
+                                            )
+                                            .into_response();
                                         }
                                     }
                                 } else {
@@ -275,19 +301,21 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
                                                         trimmed_hex_addr,
                                                     )
                                                     .map_err(|err| {
-                                                        get_error(format!(
-                                                            "Invalid Structure: {}",
-                                                            err
-                                                        ))
+                                                        ApiError::new(
+                                                            ErrorCode::InternalError,
+                                                            format!("Invalid Structure: {}", err),
+                                                        )
                                                     })
                                                     .unwrap();
                                                     vec![Token::Bytes(bytes)]
                                                 }
                                                 None => {
-                                                    return get_error(
+                                                    return ApiError::new(
+                                                        ErrorCode::NotFound,
                                                         "No evm address specified for this domain"
                                                             .to_string(),
-                                                    );
+                                                    )
+                                                    .into_response();
                                                 }
                                             }
                                         }
@@ -310,19 +338,21 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
                                                         trimmed_hex_addr,
                                                     )
                                                     .map_err(|err| {
-                                                        get_error(format!(
-                                                            "Invalid Structure: {}",
-                                                            err
-                                                        ))
+                                                        ApiError::new(
+                                                            ErrorCode::InternalError,
+                                                            format!("Invalid Structure: {}", err),
+                                                        )
                                                     })
                                                     .unwrap();
                                                     vec![Token::Bytes(bytes)]
                                                 }
                                                 None => {
-                                                    return get_error(
+                                                    return ApiError::new(
+                                                        ErrorCode::NotFound,
                                                         "No evm address specified for this domain"
                                                             .to_string(),
-                                                    );
+                                                    )
+                                                    .into_response();
                                                 }
                                             }
                                         }
@@ -350,9 +380,11 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
                                     vec![Token::Address(eth_addr)]
                                 }
                                 None => {
-                                    return get_error(
+                                    return ApiError::new(
+                                        ErrorCode::NotFound,
                                         "No evm address specified for this domain".to_string(),
-                                    );
+                                    )
+                                    .into_response();
                                 }
                             }
                         }
@@ -382,12 +414,24 @@ pub async fn handler(State(state): State<Arc<AppState>>, query: Query) -> impl I
                             })),
                         )
                             .into_response(),
-                        Err(e) => get_error(format!("Error signing message : {}", e)),
+                        Err(e) => ApiError::new(
+                            ErrorCode::InternalError,
+                            format!("Error signing message : {}", e),
+                        )
+                        .into_response(),
                     }
                 }
-                Err(e) => get_error(format!("Error fetching identity : {}", e)),
+                Err(e) => ApiError::new(
+                    ErrorCode::UpstreamError,
+                    format!("Error fetching identity : {}", e),
+                )
+                .into_response(),
             }
         }
-        Err(e) => get_error(format!("Error decoding data: {:?}", e)),
+        Err(e) => ApiError::new(
+            ErrorCode::InternalError,
+            format!("Error decoding data: {:?}", e),
+        )
+        .into_response(),
     }
 }
@@ -0,0 +1,23 @@
+use std::sync::Arc;
+
+use crate::{endpoints::crosschain::ethereum::resolve::resolve, models::AppState};
+use axum::{
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+
+// EIP-3668 GET form of the gateway, for the resolvers set with a
+// `https://api.starknet.id/crosschain/ethereum/resolve/{sender}/{data}.json` url
+#[route(
+    get,
+    "/crosschain/ethereum/resolve/:sender/:data",
+    crate::endpoints::crosschain::ethereum::resolve_get
+)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path((sender, data)): Path<(String, String)>,
+) -> impl IntoResponse {
+    let data = data.strip_suffix(".json").unwrap_or(&data).to_string();
+    resolve(&state, data, sender.to_lowercase()).await
+}
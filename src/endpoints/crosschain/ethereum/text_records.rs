@@ -11,7 +11,12 @@ use starknet::{
     providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
 };
 
-use crate::{models::AppState ,Arc,config::{Config, EvmRecordVerifier}};
+use crate::{
+    config::{Config, EvmRecordVerifier},
+    models::AppState,
+    rpc_queue::Priority,
+    Arc,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub enum HandlerType {
@@ -140,14 +145,21 @@ pub async fn get_verifier_data(
         calls.push(FieldElement::ZERO)
     }
 
-    let call_result = provider
-        .call(
-            FunctionCall {
-                contract_address: config.contracts.argent_multicall,
-                entry_point_selector: selector!("aggregate"),
-                calldata: calls,
-            },
-            BlockId::Tag(BlockTag::Latest),
+    let call = provider.call(
+        FunctionCall {
+            contract_address: config.contracts.argent_multicall,
+            entry_point_selector: selector!("aggregate"),
+            calldata: calls,
+        },
+        BlockId::Tag(BlockTag::Latest),
+    );
+    let call_result = state
+        .rpc_queue
+        .run_batch(
+            Priority::Interactive,
+            "starknet_call",
+            record_config.verifier_contracts.len() as u64,
+            call,
         )
         .await;
 
@@ -196,19 +208,21 @@ pub async fn get_unbounded_user_data(
     let logger = &state.logger;
     let config = &state.conf;
 
-    let call_result = provider
-        .call(
-            FunctionCall {
-                contract_address: config.contracts.starknetid,
-                entry_point_selector: selector!("get_unbounded_user_data"),
-                calldata: vec![
-                    id,
-                    cairo_short_string_to_felt(field).unwrap(),
-                    FieldElement::ZERO,
-                ],
-            },
-            BlockId::Tag(BlockTag::Latest),
-        )
+    let call = provider.call(
+        FunctionCall {
+            contract_address: config.contracts.starknetid,
+            entry_point_selector: selector!("get_unbounded_user_data"),
+            calldata: vec![
+                id,
+                cairo_short_string_to_felt(field).unwrap(),
+                FieldElement::ZERO,
+            ],
+        },
+        BlockId::Tag(BlockTag::Latest),
+    );
+    let call_result = state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_call", call)
         .await;
     match call_result {
         Ok(result) => {
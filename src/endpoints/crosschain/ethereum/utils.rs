@@ -26,6 +26,7 @@ use crate::{
     config::Config,
     endpoints::uri::VerifierData,
     models::AppState,
+    rpc_queue::Priority,
     utils::{fetch_image_url, parse_base64_image, to_hex},
     Arc,
 };
@@ -156,20 +157,22 @@ pub async fn get_user_data(
     state: &Arc<AppState>,
 ) -> Option<FieldElement> {
     let logger = &state.logger;
-    let call_result = provider
-        .call(
-            FunctionCall {
-                contract_address: contract,
-                entry_point_selector: selector!("get_user_data"),
-                calldata: vec![
-                    id,
-                    // cairo_short_string_to_felt(field).unwrap(),
-                    field,
-                    FieldElement::ZERO,
-                ],
-            },
-            BlockId::Tag(BlockTag::Latest),
-        )
+    let call = provider.call(
+        FunctionCall {
+            contract_address: contract,
+            entry_point_selector: selector!("get_user_data"),
+            calldata: vec![
+                id,
+                // cairo_short_string_to_felt(field).unwrap(),
+                field,
+                FieldElement::ZERO,
+            ],
+        },
+        BlockId::Tag(BlockTag::Latest),
+    );
+    let call_result = state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_call", call)
         .await;
 
     match call_result {
@@ -198,6 +201,7 @@ pub async fn get_user_data_multicall(
 ) -> Option<FieldElement> {
     let logger = &state.logger;
     let config = &state.conf;
+    let batch_size = fields.len() as u64;
     let mut calls: Vec<FieldElement> = vec![FieldElement::from(fields.len())];
     for field in fields {
         calls.push(config.contracts.starknetid);
@@ -207,15 +211,17 @@ pub async fn get_user_data_multicall(
         calls.push(field);
         calls.push(FieldElement::ZERO)
     }
-    let call_result = provider
-        .call(
-            FunctionCall {
-                contract_address: config.contracts.argent_multicall,
-                entry_point_selector: selector!("aggregate"),
-                calldata: calls,
-            },
-            BlockId::Tag(BlockTag::Latest),
-        )
+    let call = provider.call(
+        FunctionCall {
+            contract_address: config.contracts.argent_multicall,
+            entry_point_selector: selector!("aggregate"),
+            calldata: calls,
+        },
+        BlockId::Tag(BlockTag::Latest),
+    );
+    let call_result = state
+        .rpc_queue
+        .run_batch(Priority::Interactive, "starknet_call", batch_size, call)
         .await;
 
     match call_result {
@@ -245,15 +251,17 @@ pub async fn domain_to_address(
     let mut calldata: Vec<FieldElement> = vec![FieldElement::from(encoded_domain.len())];
     calldata.extend(encoded_domain);
     calldata.push(FieldElement::ZERO);
-    let call_result = provider
-        .call(
-            FunctionCall {
-                contract_address: naming_contract,
-                entry_point_selector: selector!("domain_to_address"),
-                calldata,
-            },
-            BlockId::Tag(BlockTag::Latest),
-        )
+    let call = provider.call(
+        FunctionCall {
+            contract_address: naming_contract,
+            entry_point_selector: selector!("domain_to_address"),
+            calldata,
+        },
+        BlockId::Tag(BlockTag::Latest),
+    );
+    let call_result = state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_call", call)
         .await;
 
     match call_result {
@@ -319,18 +327,20 @@ pub async fn get_profile_picture(
             }
 
             // we fetch the tokenURI from the contract
-            let call_result = provider
-                .call(
-                    FunctionCall {
-                        contract_address: FieldElement::from_hex_be(&contract_addr).unwrap(),
-                        entry_point_selector: selector!("tokenURI"),
-                        calldata: vec![
-                            FieldElement::from_hex_be(&token_id.0).unwrap(),
-                            FieldElement::from_hex_be(&token_id.1).unwrap(),
-                        ],
-                    },
-                    BlockId::Tag(BlockTag::Latest),
-                )
+            let call = provider.call(
+                FunctionCall {
+                    contract_address: FieldElement::from_hex_be(&contract_addr).unwrap(),
+                    entry_point_selector: selector!("tokenURI"),
+                    calldata: vec![
+                        FieldElement::from_hex_be(&token_id.0).unwrap(),
+                        FieldElement::from_hex_be(&token_id.1).unwrap(),
+                    ],
+                },
+                BlockId::Tag(BlockTag::Latest),
+            );
+            let call_result = state
+                .rpc_queue
+                .run(Priority::Interactive, "starknet_call", call)
                 .await;
             match call_result {
                 Ok(result) => {
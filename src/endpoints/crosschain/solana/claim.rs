@@ -4,8 +4,11 @@ use std::{
 };
 
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
-    utils::{get_error, to_hex},
+    transparency::append_entry,
+    types::starknet_addr::StarknetAddr,
+    utils::to_hex,
 };
 use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
 use axum_auto_routes::route;
@@ -19,11 +22,13 @@ use starknet::core::{
     types::FieldElement,
 };
 use starknet_id::encode;
+use utoipa::ToSchema;
 
-#[derive(Deserialize, Debug, Clone)]
+#[derive(Deserialize, Debug, Clone, ToSchema)]
 pub struct SigQuery {
     source_domain: String,
-    target_address: FieldElement,
+    #[schema(value_type = String)]
+    target_address: StarknetAddr,
     source_signature: Vec<u8>,
     max_validity: u64,
 }
@@ -80,13 +85,14 @@ pub async fn handler(
     let source_signature_array: [u8; 64] = match query.source_signature.clone().try_into() {
         Ok(arr) => arr,
         Err(_) => {
-            return get_error("Invalid signature length".to_string());
+            return ApiError::new(ErrorCode::InvalidSignature, "Invalid signature length")
+                .into_response();
         }
     };
 
     // verify max_validity is not expired
     if !is_valid_timestamp(max_validity) {
-        return get_error("Signature expired".to_string());
+        return ApiError::new(ErrorCode::SignatureExpired, "Signature expired").into_response();
     }
 
     // get owner of SNS domain
@@ -144,28 +150,57 @@ pub async fn handler(
                             );
 
                             match ecdsa_sign(&state.conf.solana.private_key.clone(), &hash) {
-                                Ok(signature) => (
-                                    StatusCode::OK,
-                                    Json(json!({
-                                        "r": signature.r,
-                                        "s": signature.s,
-                                        "max_validity": stark_max_validity_sec
-                                    })),
+                                Ok(signature) => match append_entry(
+                                    &state,
+                                    "sol_subdomain",
+                                    source_domain.clone(),
+                                    &target_address,
+                                    &hash,
+                                    &signature,
                                 )
+                                .await
+                                {
+                                    Ok(_) => (
+                                        StatusCode::OK,
+                                        Json(json!({
+                                            "r": signature.r,
+                                            "s": signature.s,
+                                            "max_validity": stark_max_validity_sec
+                                        })),
+                                    )
+                                        .into_response(),
+                                    Err(e) => ApiError::new(
+                                        ErrorCode::InternalError,
+                                        format!("Unable to log the issuance: {}", e),
+                                    )
                                     .into_response(),
-                                Err(e) => get_error(format!(
-                                    "Error while generating Starknet signature: {}",
-                                    e
-                                )),
+                                },
+                                Err(e) => ApiError::new(
+                                    ErrorCode::InternalError,
+                                    format!("Error while generating Starknet signature: {}", e),
+                                )
+                                .into_response(),
                             }
                         }
-                        Err(e) => get_error(format!("Signature verification failed: {}", e)),
+                        Err(e) => ApiError::new(
+                            ErrorCode::InvalidSignature,
+                            format!("Signature verification failed: {}", e),
+                        )
+                        .into_response(),
                     }
                 }
-                Err(e) => get_error(format!("Error parsing response from SNS RPC: {}", e)),
+                Err(e) => ApiError::new(
+                    ErrorCode::UpstreamError,
+                    format!("Error parsing response from SNS RPC: {}", e),
+                )
+                .into_response(),
             }
         }
-        Err(e) => get_error(format!("Error sending request: SNS RPC: {}", e)),
+        Err(e) => ApiError::new(
+            ErrorCode::UpstreamError,
+            format!("Error sending request: SNS RPC: {}", e),
+        )
+        .into_response(),
     }
 }
 
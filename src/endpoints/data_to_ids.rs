@@ -1,6 +1,7 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
-    utils::{get_error, to_hex},
+    utils::to_hex,
 };
 use axum::{
     extract::{Query, State},
@@ -12,16 +13,20 @@ use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct StarknetIdData {
     starknet_id: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct StarknetIdQuery {
+    #[param(value_type = String)]
     verifier: FieldElement,
+    #[param(value_type = String)]
     field: FieldElement,
+    #[param(value_type = String)]
     data: FieldElement,
 }
 
@@ -55,9 +60,17 @@ pub async fn handler(
                 let data = StarknetIdData { starknet_id };
                 (StatusCode::OK, headers, Json(data)).into_response()
             } else {
-                get_error("no tokenid associated to this data was found".to_string())
+                ApiError::new(
+                    ErrorCode::NotFound,
+                    "no tokenid associated to this data was found",
+                )
+                .into_response()
             }
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(_) => ApiError::new(
+            ErrorCode::DatabaseError,
+            "Error while fetching from database",
+        )
+        .into_response(),
     }
 }
@@ -0,0 +1,69 @@
+use crate::{models::AppState, naming_actions::decode_labels, utils::get_error};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use starknet::core::{types::FieldElement, utils::parse_cairo_short_string};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+// felts decoded by a single request
+const MAX_FELTS: usize = 5000;
+// deepest subdomain accepted
+const MAX_LABELS: usize = 10;
+
+#[derive(Deserialize, ToSchema)]
+pub struct DecodeBatchQuery {
+    // encoded labels of each domain, e.g. the span of a naming event
+    #[serde(default)]
+    #[schema(value_type = Vec<Vec<String>>)]
+    domains: Vec<Vec<FieldElement>>,
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    short_strings: Vec<FieldElement>,
+}
+
+#[route(post, "/decode/batch", crate::endpoints::decode::batch)]
+pub async fn handler(
+    State(_state): State<Arc<AppState>>,
+    Json(query): Json<DecodeBatchQuery>,
+) -> impl IntoResponse {
+    let felts = query.domains.iter().map(Vec::len).sum::<usize>() + query.short_strings.len();
+    if felts > MAX_FELTS {
+        return get_error(format!(
+            "At most {} felts can be decoded at once",
+            MAX_FELTS
+        ));
+    }
+    if query
+        .domains
+        .iter()
+        .any(|labels| labels.is_empty() || labels.len() > MAX_LABELS)
+    {
+        return get_error(format!(
+            "Domains must have between 1 and {} labels",
+            MAX_LABELS
+        ));
+    }
+
+    let domains: Vec<String> = query
+        .domains
+        .iter()
+        .map(|labels| decode_labels(labels))
+        .collect();
+    // null for the felts which aren't ascii short strings
+    let short_strings: Vec<Option<String>> = query
+        .short_strings
+        .iter()
+        .map(|felt| parse_cairo_short_string(felt).ok())
+        .collect();
+    (
+        StatusCode::OK,
+        Json(json!({ "domains": domains, "short_strings": short_strings })),
+    )
+        .into_response()
+}
@@ -0,0 +1,15 @@
+use crate::{models::AppState, test_vectors::get_test_vectors};
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use std::sync::Arc;
+
+#[route(get, "/dev/test_vectors", crate::endpoints::dev::test_vectors)]
+pub async fn handler(State(_state): State<Arc<AppState>>) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=3600"));
+    (StatusCode::OK, headers, Json(get_test_vectors())).into_response()
+}
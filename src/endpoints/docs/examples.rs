@@ -0,0 +1,35 @@
+use crate::{examples::get_examples, models::AppState, routes::ROUTES};
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde_json::json;
+use std::sync::Arc;
+
+#[route(get, "/docs/examples", crate::endpoints::docs::examples)]
+pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let conf = &state.conf.examples;
+    // the requests don't depend on the data, the responses are added once replayed
+    let (rendered, examples) = match state.examples.get() {
+        Some(examples) => (true, examples),
+        None => (false, get_examples(ROUTES, conf)),
+    };
+    let examples: Vec<_> = examples
+        .into_iter()
+        .filter(|example| state.conf.deployment.is_enabled(example.group))
+        .collect();
+    let mut headers = HeaderMap::new();
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=60"));
+    (
+        StatusCode::OK,
+        headers,
+        Json(json!({
+            "fixture": { "domain": conf.domain, "address": conf.address, "id": conf.id },
+            "rendered": rendered,
+            "examples": examples,
+        })),
+    )
+        .into_response()
+}
@@ -0,0 +1,17 @@
+use crate::{models::AppState, openapi::DOCS_HTML};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse},
+};
+use axum_auto_routes::route;
+use std::sync::Arc;
+
+#[route(get, "/docs", crate::endpoints::docs::ui)]
+pub async fn handler(State(_state): State<Arc<AppState>>) -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        [(header::CACHE_CONTROL, "max-age=3600")],
+        Html(DOCS_HTML),
+    )
+}
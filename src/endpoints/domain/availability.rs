@@ -0,0 +1,165 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    fanout::with_timeout,
+    grace::{DomainDisplay, ExpiryStatus},
+    locale::get_locale,
+    models::AppState,
+    pricing::{get_label_length, get_price_per_day, get_price_per_year},
+    suggestions::get_suggestions,
+    utils::{deserialize_domain, get_error},
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use mongodb::bson::doc;
+use serde::{Deserialize, Serialize};
+use starknet_id::encode;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Availability {
+    Free,
+    Reserved,
+    Taken,
+}
+
+#[derive(Serialize)]
+pub struct Price {
+    length: usize,
+    // wei amounts, as decimal strings
+    per_day: String,
+    per_year: String,
+}
+
+#[derive(Serialize)]
+pub struct AvailabilityData {
+    domain: String,
+    status: Availability,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiry: Option<i64>,
+    #[serde(skip_serializing_if = "ExpiryStatus::is_active")]
+    expiry_status: ExpiryStatus,
+    price: Price,
+    // free alternatives of a taken or reserved domain
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    suggestions: Vec<String>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct AvailabilityQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
+    domain: String,
+    locale: Option<String>,
+}
+
+/// A registered domain stays taken while the grace policy resolves it
+pub fn get_availability(reserved: bool, registration: Option<DomainDisplay>) -> Availability {
+    match registration {
+        Some(display) if display.resolves => Availability::Taken,
+        _ if reserved => Availability::Reserved,
+        _ => Availability::Free,
+    }
+}
+
+#[route(get, "/domain/availability", crate::endpoints::domain::availability)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    request_headers: HeaderMap,
+    Query(query): Query<AvailabilityQuery>,
+) -> impl IntoResponse {
+    let label = query.domain.strip_suffix(".stark").unwrap_or(&query.domain);
+    if label.is_empty() || label.contains('.') {
+        return get_error("Only root domains can be registered".to_string());
+    }
+    if encode(label).is_err() {
+        return get_error("Invalid domain".to_string());
+    }
+    let length = get_label_length(label);
+    let (per_day, per_year) = match (get_price_per_day(length), get_price_per_year(length)) {
+        (Some(per_day), Some(per_year)) => (per_day, per_year),
+        _ => return get_error("Invalid domain".to_string()),
+    };
+
+    let domain_doc = match state
+        .storage
+        .find_one(
+            "domains",
+            doc! { "domain": &query.domain, "_cursor.to": null },
+        )
+        .await
+    {
+        Ok(domain_doc) => domain_doc,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let expiry = domain_doc
+        .as_ref()
+        .and_then(|doc| doc.get_i64("expiry").ok());
+    let registration = domain_doc.map(|_| {
+        state
+            .conf
+            .grace
+            .get_display(expiry, chrono::Utc::now().timestamp())
+    });
+    let reserved = state
+        .conf
+        .availability
+        .reserved
+        .iter()
+        .any(|reserved| reserved == label);
+    let status = get_availability(reserved, registration);
+    let suggestions = if status == Availability::Free {
+        vec![]
+    } else {
+        let locale = get_locale(query.locale.as_deref(), &request_headers);
+        match with_timeout(
+            &state,
+            "suggestions",
+            get_suggestions(&state, label, locale),
+        )
+        .await
+        {
+            Some(Ok(suggestions)) => suggestions,
+            Some(Err(e)) => {
+                state
+                    .logger
+                    .warning(format!("suggestions: unable to check candidates: {}", e));
+                vec![]
+            }
+            None => vec![],
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+    headers.insert("Vary", HeaderValue::from_static("Accept-Language"));
+    (
+        StatusCode::OK,
+        headers,
+        Json(AvailabilityData {
+            status,
+            expiry,
+            expiry_status: registration
+                .map(|display| display.status)
+                .unwrap_or(ExpiryStatus::Active),
+            price: Price {
+                length,
+                per_day: per_day.to_string(),
+                per_year: per_year.to_string(),
+            },
+            suggestions,
+            domain: query.domain,
+        }),
+    )
+        .into_response()
+}
@@ -0,0 +1,92 @@
+use crate::{
+    avatars::{get_domain_id, resolve_avatar},
+    errors::{ApiError, ErrorCode},
+    images::{fetch_image, set_image_headers},
+    models::AppState,
+    utils::deserialize_domain,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct AvatarQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
+    domain: String,
+    // serves the image itself instead of its url
+    #[serde(default)]
+    proxy: bool,
+}
+
+#[derive(Serialize)]
+pub struct AvatarData {
+    domain: String,
+    uri: String,
+    image: String,
+}
+
+#[route(get, "/domain/avatar", crate::endpoints::domain::avatar)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AvatarQuery>,
+) -> impl IntoResponse {
+    let id = match get_domain_id(&state, &query.domain).await {
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            return ApiError::new(ErrorCode::DomainNotFound, "Domain not found").into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let avatar = match resolve_avatar(&state, id).await {
+        Ok(Some(avatar)) => avatar,
+        Ok(None) => {
+            return ApiError::new(ErrorCode::NotFound, "No avatar set for this domain")
+                .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to resolve the avatar: {}", e),
+            )
+            .into_response()
+        }
+    };
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=300"));
+    if query.proxy {
+        return match fetch_image(&state.conf, &avatar.image).await {
+            Ok(image) => {
+                set_image_headers(&mut headers, &image);
+                (StatusCode::OK, headers, image.bytes).into_response()
+            }
+            Err(e) => ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to fetch the image: {}", e),
+            )
+            .into_response(),
+        };
+    }
+    (
+        StatusCode::OK,
+        headers,
+        Json(AvatarData {
+            domain: query.domain,
+            uri: avatar.uri,
+            image: avatar.image,
+        }),
+    )
+        .into_response()
+}
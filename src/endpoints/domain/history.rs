@@ -0,0 +1,49 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    history::get_domain_history,
+    models::AppState,
+    utils::deserialize_domain,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct HistoryQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
+    domain: String,
+}
+
+#[route(get, "/domain/history", crate::endpoints::domain::history)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<HistoryQuery>,
+) -> impl IntoResponse {
+    match get_domain_history(state.storage.as_ref(), &query.domain).await {
+        Ok(history) if history.is_empty() => {
+            ApiError::new(ErrorCode::DomainNotFound, "Domain was never registered").into_response()
+        }
+        Ok(history) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=60"));
+            (
+                StatusCode::OK,
+                headers,
+                Json(json!({ "domain": query.domain, "history": history })),
+            )
+                .into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
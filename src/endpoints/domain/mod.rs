@@ -0,0 +1,4 @@
+pub mod availability;
+pub mod avatar;
+pub mod history;
+pub mod owner_at;
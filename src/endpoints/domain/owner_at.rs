@@ -0,0 +1,118 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    utils::{cursor_at_block, get_block_at_timestamp, get_error, normalize_domain},
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use mongodb::bson::{doc, Document};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use starknet::providers::{jsonrpc::HttpTransport, JsonRpcClient};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Serialize)]
+pub struct OwnerAtData {
+    domain: String,
+    timestamp: u64,
+    block: u64,
+    id: String,
+    owner: String,
+    domain_expiry: Option<i64>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct OwnerAtQuery {
+    timestamp: u64,
+}
+
+#[route(get, "/domain/:domain/owner_at", crate::endpoints::domain::owner_at)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path(domain): Path<String>,
+    Query(query): Query<OwnerAtQuery>,
+) -> impl IntoResponse {
+    let domain = normalize_domain(&domain);
+    if query.timestamp > chrono::Utc::now().timestamp() as u64 {
+        return get_error("Timestamp is in the future".to_string());
+    }
+
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).unwrap(),
+    ));
+    let block = match get_block_at_timestamp(&provider, query.timestamp).await {
+        Ok(block) => block,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to find block for timestamp: {}", e),
+            )
+            .into_response()
+        }
+    };
+
+    // the identity holding the domain can change over time, so we first find which
+    // identity the domain pointed to at that block and then who owned this identity
+    let domains = state.starknetid_db.collection::<Document>("domains");
+    let mut domain_filter = cursor_at_block(block as i64);
+    domain_filter.insert("domain", &domain);
+    let domain_doc = match domains.find_one(domain_filter, None).await {
+        Ok(Some(doc)) => doc,
+        Ok(None) => {
+            return ApiError::new(
+                ErrorCode::DomainNotFound,
+                "Domain was not registered at this time",
+            )
+            .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let id = match domain_doc.get_str("id") {
+        Ok(id) => id.to_string(),
+        Err(_) => {
+            return ApiError::new(ErrorCode::NotFound, "Domain was not linked to an identity")
+                .into_response()
+        }
+    };
+
+    let id_owners = state.starknetid_db.collection::<Document>("id_owners");
+    let mut owner_filter = cursor_at_block(block as i64);
+    owner_filter.insert("id", &id);
+    match id_owners.find_one(owner_filter, None).await {
+        Ok(Some(doc)) => {
+            // past ownership never changes, it can be cached for a long time
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=3600"));
+            let data = OwnerAtData {
+                domain,
+                timestamp: query.timestamp,
+                block,
+                id,
+                owner: doc.get_str("owner").unwrap_or_default().to_owned(),
+                domain_expiry: domain_doc.get_i64("expiry").ok(),
+            };
+            (StatusCode::OK, headers, Json(data)).into_response()
+        }
+        Ok(None) => ApiError::new(
+            ErrorCode::NotFound,
+            "No owner found for this identity at this time",
+        )
+        .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
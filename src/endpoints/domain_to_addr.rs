@@ -1,12 +1,21 @@
 use crate::{
+    contract_claims::is_contract_verified,
+    display_address::{format_address, format_hex_address},
+    encoding::{Encoded, Encoding},
+    errors::{ApiError, ErrorCode},
+    fallback::resolve_domain,
+    finality::Finality,
+    freezes::{get_freeze, Freeze},
+    grace::ExpiryStatus,
     models::{AppState, OffchainResolverHint},
     resolving::get_offchain_resolver,
-    utils::{extract_prefix_and_root, get_error, to_hex},
+    rpc_queue::Priority,
+    utils::{deserialize_domain, extract_prefix_and_root, to_hex},
 };
 use axum::{
     extract::{Query, State},
     http::{HeaderMap, HeaderValue, StatusCode},
-    response::{IntoResponse, Json},
+    response::IntoResponse,
 };
 use axum_auto_routes::route;
 use futures::StreamExt;
@@ -20,23 +29,42 @@ use starknet::{
 };
 use starknet_id::encode;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct DomainToAddrData {
     addr: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_address: Option<String>,
     domain_expiry: Option<i64>,
+    #[serde(skip_serializing_if = "ExpiryStatus::is_active")]
+    expiry_status: ExpiryStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    finality: Option<Finality>,
+    // the domain was claimed by the contract it resolves to
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    contract_verified: bool,
+    // read from the naming contract, the indexer didn't catch up with the domain yet
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    from_rpc: bool,
+    // the identity was reported compromised by its owner
+    #[serde(skip_serializing_if = "Option::is_none")]
+    compromised: Option<Freeze>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct DomainQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
     domain: String,
 }
 
 #[route(get, "/domain_to_addr", crate::endpoints::domain_to_addr)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
+    request_headers: HeaderMap,
     Query(query): Query<DomainQuery>,
 ) -> impl IntoResponse {
+    let encoding = Encoding::from_headers(&request_headers);
     let mut headers: HeaderMap = HeaderMap::new();
     headers.insert("Cache-Control", HeaderValue::from_static("max-age=60"));
     let (prefix, root_domain) = extract_prefix_and_root(query.domain.clone());
@@ -63,11 +91,24 @@ pub async fn handler(
                 Ok(Some(doc)) => {
                     let data = DomainToAddrData {
                         addr: doc.get_str("value").unwrap().to_string(),
+                        display_address: format_hex_address(
+                            doc.get_str("value").unwrap(),
+                            state.conf.address_display.format,
+                        ),
                         domain_expiry: None,
+                        expiry_status: ExpiryStatus::Active,
+                        finality: doc
+                            .get_document("_cursor")
+                            .and_then(|c| c.get_i64("from"))
+                            .ok()
+                            .map(|block| state.finality(block)),
+                        contract_verified: false,
+                        from_rpc: false,
+                        compromised: None,
                     };
-                    (StatusCode::OK, headers, Json(data)).into_response()
+                    (StatusCode::OK, headers, Encoded(encoding, data)).into_response()
                 }
-                _ => get_error("no target found".to_string()),
+                _ => ApiError::new(ErrorCode::NotFound, "no target found").into_response(),
             }
         }
 
@@ -108,36 +149,41 @@ pub async fn handler(
                                     calldata.push(hints.s);
                                     calldata.push(FieldElement::from(hints.max_validity));
 
-                                    let call_result = provider
-                                        .call(
-                                            FunctionCall {
-                                                contract_address: state.conf.contracts.naming,
-                                                entry_point_selector: selector!("domain_to_address"),
-                                                calldata,
-                                            },
-                                            BlockId::Tag(BlockTag::Latest),
-                                        )
-                                        .await;
+                                    let call = provider.call(
+                                        FunctionCall {
+                                            contract_address: state.conf.contracts.naming,
+                                            entry_point_selector: selector!("domain_to_address"),
+                                            calldata,
+                                        },
+                                        BlockId::Tag(BlockTag::Latest),
+                                    );
+                                    let call_result = state.rpc_queue.run(Priority::Interactive, "starknet_call", call).await;
 
                                     match call_result {
                                         Ok(result) => {
                                             // if call is successful we return the address
-                                            (StatusCode::OK, Json(DomainToAddrData {
+                                            (StatusCode::OK, Encoded(encoding, DomainToAddrData {
                                                 addr: to_hex(&result[0]),
-                                                domain_expiry: None
+                                                display_address: Some(format_address(&result[0], state.conf.address_display.format)),
+                                                domain_expiry: None,
+                                                expiry_status: ExpiryStatus::Active,
+                                                finality: None,
+                                                contract_verified: false,
+                                                from_rpc: false,
+                                                compromised: None,
                                             })).into_response()
                                         }
-                                        Err(e) => get_error(format!("{}", e)),
+                                        Err(e) => ApiError::new(ErrorCode::UpstreamError, e.to_string()).into_response(),
                                     }
                                 },
-                                Err(_) => get_error(text.to_string()),
+                                Err(_) => ApiError::new(ErrorCode::UpstreamError, text).into_response(),
                             },
-                            Err(e) => get_error(format!(
+                            Err(e) => ApiError::new(ErrorCode::UpstreamError, format!(
                                 "Failed to get JSON response while fetching offchain resolver api: {}",
                                 e
-                            )),
+                            )).into_response(),
                         },
-                        Err(e) => get_error(format!("Failed to fetch offchain resolver api: {}", e)),
+                        Err(e) => ApiError::new(ErrorCode::UpstreamError, format!("Failed to fetch offchain resolver api: {}", e)).into_response(),
                     }
                 }
                 None => {
@@ -257,7 +303,9 @@ pub async fn handler(
                                         }
                                     }
                                 },
-                                "domain_expiry": "$expiry"
+                                "domain_expiry": "$expiry",
+                                "block": "$_cursor.from",
+                                "id": "$id"
                             }
                         },
                     ];
@@ -273,16 +321,81 @@ pub async fn handler(
                             Some(Ok(doc)) => {
                                 let addr = doc.get_str("addr").unwrap_or_default().to_owned();
                                 let domain_expiry = doc.get_i64("domain_expiry").ok();
+                                let display = state
+                                    .conf
+                                    .grace
+                                    .get_display(domain_expiry, chrono::Utc::now().timestamp());
+                                if !display.resolves {
+                                    return ApiError::new(
+                                        ErrorCode::DomainNotFound,
+                                        "No document found for the given domain".to_string(),
+                                    )
+                                    .into_response();
+                                }
+                                let finality =
+                                    doc.get_i64("block").ok().map(|block| state.finality(block));
+                                let contract_verified =
+                                    is_contract_verified(&state, &query.domain).await;
+                                let compromised = match doc
+                                    .get_str("id")
+                                    .ok()
+                                    .and_then(|id| FieldElement::from_hex_be(id).ok())
+                                {
+                                    Some(id) => {
+                                        get_freeze(state.storage.as_ref(), &id).await.ok().flatten()
+                                    }
+                                    None => None,
+                                };
                                 let data = DomainToAddrData {
+                                    display_address: format_hex_address(
+                                        &addr,
+                                        state.conf.address_display.format,
+                                    ),
                                     addr,
                                     domain_expiry,
+                                    expiry_status: display.status,
+                                    finality,
+                                    contract_verified,
+                                    from_rpc: false,
+                                    compromised,
                                 };
-                                (StatusCode::OK, Json(data)).into_response()
+                                (StatusCode::OK, Encoded(encoding, data)).into_response()
                             }
-                            Some(Err(e)) => get_error(format!("Error calling the db: {}", e)),
-                            None => get_error("No document found for the given domain".to_string()),
+                            Some(Err(e)) => ApiError::new(
+                                ErrorCode::DatabaseError,
+                                format!("Error calling the db: {}", e),
+                            )
+                            .into_response(),
+                            None => match resolve_domain(&state, &query.domain).await {
+                                Ok(Some(target)) => {
+                                    let addr = to_hex(&target);
+                                    let data = DomainToAddrData {
+                                        display_address: format_hex_address(
+                                            &addr,
+                                            state.conf.address_display.format,
+                                        ),
+                                        addr,
+                                        domain_expiry: None,
+                                        expiry_status: ExpiryStatus::Active,
+                                        finality: None,
+                                        contract_verified: false,
+                                        from_rpc: true,
+                                        compromised: None,
+                                    };
+                                    (StatusCode::OK, Encoded(encoding, data)).into_response()
+                                }
+                                _ => ApiError::new(
+                                    ErrorCode::DomainNotFound,
+                                    "No document found for the given domain",
+                                )
+                                .into_response(),
+                            },
                         },
-                        Err(e) => get_error(format!("Error accessing the database: {}", e)),
+                        Err(e) => ApiError::new(
+                            ErrorCode::DatabaseError,
+                            format!("Error accessing the database: {}", e),
+                        )
+                        .into_response(),
                     }
                 }
             }
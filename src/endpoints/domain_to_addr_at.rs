@@ -0,0 +1,66 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    history::{domain_to_addr_at, get_lookup_block, ResolutionAt},
+    models::AppState,
+    utils::deserialize_domain,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Serialize)]
+pub struct DomainToAddrAtData {
+    #[serde(flatten)]
+    resolution: ResolutionAt,
+    block: i64,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct DomainToAddrAtQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
+    domain: String,
+    block: Option<u64>,
+    timestamp: Option<u64>,
+}
+
+#[route(get, "/domain_to_addr_at", crate::endpoints::domain_to_addr_at)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<DomainToAddrAtQuery>,
+) -> impl IntoResponse {
+    let block = match get_lookup_block(&state, query.block, query.timestamp).await {
+        Ok(block) => block,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to find the block: {}", e),
+            )
+            .into_response()
+        }
+    };
+    match domain_to_addr_at(state.storage.as_ref(), &query.domain, block).await {
+        Ok(Some(resolution)) => {
+            // past resolutions never change, they can be cached for a long time
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=3600"));
+            let data = DomainToAddrAtData { resolution, block };
+            (StatusCode::OK, headers, Json(data)).into_response()
+        }
+        Ok(None) => ApiError::new(
+            ErrorCode::DomainNotFound,
+            "Domain was not registered at this block",
+        )
+        .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -1,6 +1,10 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
+    fanout::{complete_identity, with_timeout},
+    locale::get_locale,
     models::{AppState, IdentityData},
-    utils::get_error,
+    records::get_records,
+    utils::deserialize_domain,
 };
 use axum::{
     extract::{Query, State},
@@ -12,19 +16,25 @@ use futures::StreamExt;
 use mongodb::bson::{doc, from_bson, Bson, Document};
 use serde::Deserialize;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct DomainQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
     domain: String,
+    locale: Option<String>,
 }
 
 #[route(get, "/domain_to_data", crate::endpoints::domain_to_data)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
+    request_headers: HeaderMap,
     Query(query): Query<DomainQuery>,
 ) -> impl IntoResponse {
+    let locale = get_locale(query.locale.as_deref(), &request_headers);
     let mut headers = HeaderMap::new();
     headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+    headers.insert("Vary", HeaderValue::from_static("Accept-Language"));
 
     let collection = state.starknetid_db.collection::<Document>("domains");
 
@@ -32,9 +42,8 @@ pub async fn handler(
         Ok(cursor) => cursor,
         Err(_) => {
             return (
-                StatusCode::INTERNAL_SERVER_ERROR,
                 headers,
-                "Failed to retrieve data".to_string(),
+                ApiError::new(ErrorCode::DatabaseError, "Failed to retrieve data"),
             )
                 .into_response();
         }
@@ -43,16 +52,27 @@ pub async fn handler(
     // The aggregation returns a single document
     return if let Some(result) = cursor.next().await {
         match result {
-            Ok(doc) => (
-                StatusCode::OK,
-                headers,
-                Json(from_bson::<IdentityData>(Bson::Document(doc)).expect("Malformed document")),
+            Ok(doc) => {
+                let mut identity =
+                    from_bson::<IdentityData>(Bson::Document(doc)).expect("Malformed document");
+                complete_identity(&state, &mut identity).await;
+                identity.records =
+                    with_timeout(&state, "records", get_records(&state, identity.id))
+                        .await
+                        .unwrap_or_default();
+                if let Some(locale) = locale {
+                    identity.set_display_fields(locale);
+                }
+                (StatusCode::OK, headers, Json(identity)).into_response()
+            }
+            Err(err) => ApiError::new(
+                ErrorCode::InternalError,
+                format!("Unexpected error: {}", err),
             )
-                .into_response(),
-            Err(err) => get_error(format!("Unexpected error: {}", err)),
+            .into_response(),
         }
     } else {
-        get_error("Identity not found".to_string())
+        ApiError::new(ErrorCode::IdentityNotFound, "Identity not found").into_response()
     };
 }
 
@@ -134,7 +154,8 @@ fn get_pipeline(domain: String) -> Vec<Document> {
                             "_id": 0,
                             "field": 1,
                             "data": 1,
-                            "verifier": 1
+                            "verifier": 1,
+                            "block": "$_cursor.from"
                         }
                     }
                 ],
@@ -163,7 +184,8 @@ fn get_pipeline(domain: String) -> Vec<Document> {
                             "_id": 0,
                             "field": 1,
                             "extended_data": 1,
-                            "verifier": 1
+                            "verifier": 1,
+                            "block": "$_cursor.from"
                         }
                     }
                 ],
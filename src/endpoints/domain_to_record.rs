@@ -0,0 +1,95 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    records::{get_record, Chain},
+    utils::{deserialize_domain, get_error},
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use mongodb::bson::doc;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct RecordQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
+    domain: String,
+    #[param(inline)]
+    field: Chain,
+}
+
+#[derive(Serialize)]
+pub struct RecordData {
+    domain: String,
+    chain: Chain,
+    address: String,
+}
+
+#[route(get, "/domain_to_record", crate::endpoints::domain_to_record)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RecordQuery>,
+) -> impl IntoResponse {
+    let domain_doc = match state
+        .storage
+        .find_one(
+            "domains",
+            doc! { "domain": &query.domain, "_cursor.to": null },
+        )
+        .await
+    {
+        Ok(Some(doc)) => doc,
+        Ok(None) => {
+            return ApiError::new(ErrorCode::DomainNotFound, "Domain not found").into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let display = state.conf.grace.get_display(
+        domain_doc.get_i64("expiry").ok(),
+        chrono::Utc::now().timestamp(),
+    );
+    let id = domain_doc
+        .get_str("id")
+        .ok()
+        .and_then(|id| FieldElement::from_hex_be(id).ok());
+    let id = match id {
+        Some(id) if display.resolves => id,
+        _ => return ApiError::new(ErrorCode::DomainNotFound, "Domain not found").into_response(),
+    };
+
+    match get_record(&state, id, query.field).await {
+        Ok(Some(record)) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=60"));
+            let data = RecordData {
+                domain: query.domain,
+                chain: record.chain,
+                address: record.address,
+            };
+            (StatusCode::OK, headers, Json(data)).into_response()
+        }
+        Ok(None) => ApiError::new(
+            ErrorCode::NotFound,
+            format!("No {} address set for this domain", query.field.get_field()),
+        )
+        .into_response(),
+        Err(e) if e.downcast_ref::<mongodb::error::Error>().is_some() => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+        Err(e) => get_error(format!("Invalid record: {}", e)),
+    }
+}
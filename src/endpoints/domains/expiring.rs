@@ -0,0 +1,82 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    expiring::{get_expiring_domains, ExpiringDomain},
+    models::AppState,
+    pagination::{get_limit, PageCursor},
+    utils::get_error,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+const DAY_SECS: i64 = 24 * 3600;
+const MAX_WITHIN_DAYS: i64 = 365;
+
+#[derive(Deserialize, IntoParams)]
+pub struct ExpiringQuery {
+    within_days: Option<i64>,
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ExpiringData {
+    domains: Vec<ExpiringDomain>,
+    next_cursor: Option<String>,
+}
+
+#[route(get, "/domains/expiring", crate::endpoints::domains::expiring)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExpiringQuery>,
+) -> impl IntoResponse {
+    let within_days = query.within_days.unwrap_or(30);
+    if !(1..=MAX_WITHIN_DAYS).contains(&within_days) {
+        return get_error(format!(
+            "within_days must be between 1 and {}",
+            MAX_WITHIN_DAYS
+        ));
+    }
+    let cursor = match query.cursor.as_deref().map(PageCursor::decode) {
+        Some(Some(cursor)) => Some(cursor),
+        Some(None) => return get_error("Invalid cursor".to_string()),
+        None => None,
+    };
+    let limit = get_limit(query.limit, state.conf.pagination.max_limit);
+    let now = chrono::Utc::now().timestamp();
+
+    match get_expiring_domains(
+        state.storage.as_ref(),
+        now,
+        now + within_days * DAY_SECS,
+        cursor.as_ref(),
+        limit,
+    )
+    .await
+    {
+        Ok((domains, next_cursor)) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=60"));
+            (
+                StatusCode::OK,
+                headers,
+                Json(ExpiringData {
+                    domains,
+                    next_cursor: next_cursor.map(|cursor| cursor.encode()),
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
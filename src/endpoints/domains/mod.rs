@@ -0,0 +1,2 @@
+pub mod expiring;
+pub mod search;
@@ -0,0 +1,85 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    pagination::get_limit,
+    search::{search_domains, DomainMatch},
+    utils::get_error,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+// autocomplete only shows the first few matches
+const DEFAULT_LIMIT: i64 = 20;
+const MAX_PREFIX_LEN: usize = 64;
+
+#[derive(Deserialize, IntoParams)]
+pub struct SearchQuery {
+    q: String,
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SearchData {
+    domains: Vec<DomainMatch>,
+    next_cursor: Option<String>,
+}
+
+#[route(get, "/domains/search", crate::endpoints::domains::search)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SearchQuery>,
+) -> impl IntoResponse {
+    let prefix = query.q.trim().to_lowercase();
+    if prefix.is_empty() {
+        return get_error("Empty search".to_string());
+    }
+    if prefix.chars().count() > MAX_PREFIX_LEN {
+        return get_error(format!("Search longer than {} characters", MAX_PREFIX_LEN));
+    }
+    let limit = get_limit(
+        Some(query.limit.unwrap_or(DEFAULT_LIMIT)),
+        state.conf.pagination.max_limit,
+    );
+
+    match search_domains(
+        state.storage.as_ref(),
+        &prefix,
+        query.cursor.as_deref(),
+        limit,
+    )
+    .await
+    {
+        Ok(domains) => {
+            // the cursor is the last domain of a full page
+            let next_cursor = if domains.len() as i64 == limit {
+                domains.last().map(|domain| domain.domain.clone())
+            } else {
+                None
+            };
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+            (
+                StatusCode::OK,
+                headers,
+                Json(SearchData {
+                    domains,
+                    next_cursor,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while searching domains: {}", e),
+        )
+        .into_response(),
+    }
+}
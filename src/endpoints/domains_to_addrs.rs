@@ -0,0 +1,175 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    grace::ExpiryStatus,
+    models::AppState,
+    streaming::{stream_json_response, Envelope},
+    utils::{extract_prefix_and_root, get_error, normalize_domain},
+};
+use anyhow::Result;
+use axum::{
+    extract::{Json, State},
+    http::HeaderMap,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use futures::{stream, StreamExt};
+use mongodb::bson::{doc, Document};
+use serde::{Deserialize, Serialize};
+use std::{collections::HashMap, convert::Infallible, sync::Arc};
+use utoipa::ToSchema;
+
+const MAX_DOMAINS: usize = 500;
+// the "starknet" user data field
+const STARKNET_FIELD: &str = "0x000000000000000000000000000000000000000000000000737461726b6e6574";
+
+#[derive(Deserialize, ToSchema)]
+pub struct DomainsToAddrsQuery {
+    domains: Vec<String>,
+}
+
+#[derive(Serialize)]
+pub struct DomainToAddrData {
+    domain: String,
+    // None when the domain isn't registered, uses a custom or offchain resolver, or is
+    // expired and the grace policy doesn't resolve it
+    addr: Option<String>,
+    domain_expiry: Option<i64>,
+    #[serde(skip_serializing_if = "ExpiryStatus::is_active")]
+    expiry_status: ExpiryStatus,
+}
+
+// same resolution as the native resolver of /domain_to_addr: the legacy address, else the
+// starknet field of the identity, else its owner
+fn create_pipeline(domains: Vec<String>) -> Vec<Document> {
+    vec![
+        doc! { "$match": {
+            "_cursor.to": null,
+            "resolver": null,
+            "domain": { "$in": domains },
+        }},
+        doc! { "$lookup": {
+            "from": "id_user_data",
+            "let": { "userId": "$id" },
+            "pipeline": [
+                { "$match": {
+                    "_cursor.to": { "$exists": false },
+                    "field": STARKNET_FIELD,
+                    "$expr": { "$eq": ["$id", "$$userId"] },
+                }},
+            ],
+            "as": "userData",
+        }},
+        doc! { "$unwind": { "path": "$userData", "preserveNullAndEmptyArrays": true } },
+        doc! { "$lookup": {
+            "from": "id_owners",
+            "let": { "userId": "$id" },
+            "pipeline": [
+                { "$match": {
+                    "$or": [
+                        { "_cursor.to": { "$exists": false } },
+                        { "_cursor.to": null },
+                    ],
+                    "$expr": { "$eq": ["$id", "$$userId"] },
+                }},
+            ],
+            "as": "ownerData",
+        }},
+        doc! { "$unwind": { "path": "$ownerData", "preserveNullAndEmptyArrays": true } },
+        doc! { "$project": {
+            "_id": 0,
+            "domain": 1,
+            "addr": {
+                "$cond": {
+                    "if": { "$and": [
+                        { "$ifNull": ["$legacy_address", false] },
+                        { "$ne": [
+                            "$legacy_address",
+                            "0x0000000000000000000000000000000000000000000000000000000000000000",
+                        ]},
+                    ]},
+                    "then": "$legacy_address",
+                    "else": {
+                        "$cond": {
+                            "if": { "$ifNull": ["$userData.data", false] },
+                            "then": "$userData.data",
+                            "else": "$ownerData.owner",
+                        }
+                    },
+                }
+            },
+            "domain_expiry": "$expiry",
+        }},
+    ]
+}
+
+async fn resolve_domains(
+    state: &AppState,
+    domains: Vec<String>,
+) -> Result<HashMap<String, (Option<String>, Option<i64>)>> {
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .aggregate(create_pipeline(domains), None)
+        .await?;
+    let mut resolved = HashMap::new();
+    while let Some(doc) = cursor.next().await {
+        let doc = doc?;
+        if let Ok(domain) = doc.get_str("domain") {
+            resolved.insert(
+                domain.to_string(),
+                (
+                    doc.get_str("addr").ok().map(String::from),
+                    doc.get_i64("domain_expiry").ok(),
+                ),
+            );
+        }
+    }
+    Ok(resolved)
+}
+
+#[route(post, "/domains_to_addrs", crate::endpoints::domains_to_addrs)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<DomainsToAddrsQuery>,
+) -> impl IntoResponse {
+    if query.domains.len() > MAX_DOMAINS {
+        return get_error(format!("At most {} domains can be resolved", MAX_DOMAINS));
+    }
+    let domains: Vec<String> = query
+        .domains
+        .iter()
+        .map(|domain| normalize_domain(domain))
+        .collect();
+    let native: Vec<String> = domains
+        .iter()
+        .filter(|domain| {
+            let (_, root) = extract_prefix_and_root(domain.to_string());
+            !state.conf.reversed_resolvers.contains_key(&root)
+        })
+        .cloned()
+        .collect();
+
+    let resolved = match resolve_domains(&state, native).await {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    // in the order of the request
+    let now = chrono::Utc::now().timestamp();
+    let results = stream::iter(domains).map(move |domain| {
+        let (addr, domain_expiry) = resolved.get(&domain).cloned().unwrap_or_default();
+        let display = state.conf.grace.get_display(domain_expiry, now);
+        Ok::<_, Infallible>(DomainToAddrData {
+            domain,
+            addr: addr.filter(|_| display.resolves),
+            domain_expiry,
+            expiry_status: display.status,
+        })
+    });
+    stream_json_response(HeaderMap::new(), Envelope::Array, results)
+}
@@ -0,0 +1,154 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    pagination::{get_limit, get_sort, PageCursor, BLOCK_ORDER},
+    utils::get_error,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use futures::StreamExt;
+use lazy_static::lazy_static;
+use mongodb::{
+    bson::{doc, to_bson, Document},
+    options::FindOptions,
+};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+lazy_static! {
+    static ref FIELD_REGEX: Regex = Regex::new(r"^[a-zA-Z0-9_]+(\.[a-zA-Z0-9_]+)*$").unwrap();
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct EventsQuery {
+    contract: String,
+    #[serde(rename = "type")]
+    event_type: Option<String>,
+    from_block: Option<i64>,
+    to_block: Option<i64>,
+    // comma separated list of fields to return
+    fields: Option<String>,
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct EventsData {
+    events: Vec<Document>,
+    next_cursor: Option<String>,
+}
+
+/// Builds the projection for a comma separated fields list, the block and `_id`
+/// are always returned as they are needed to build the next cursor
+pub fn get_projection(fields: &str) -> Result<Document, String> {
+    let mut projection = doc! { "_id": 1, "_cursor": 1, "event": 1 };
+    for field in fields.split(',').map(str::trim).filter(|f| !f.is_empty()) {
+        if !FIELD_REGEX.is_match(field) {
+            return Err(format!("Invalid field: {}", field));
+        }
+        projection.insert(field, 1);
+    }
+    Ok(projection)
+}
+
+#[route(get, "/events", crate::endpoints::events::get_events)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<EventsQuery>,
+) -> impl IntoResponse {
+    let collection_name = match state.conf.events.collections.get(&query.contract) {
+        Some(name) => name,
+        None => return get_error(format!("Unknown contract: {}", query.contract)),
+    };
+
+    let mut filters = vec![];
+    if let Some(event_type) = &query.event_type {
+        filters.push(doc! { "event": event_type });
+    }
+    if let Some(from_block) = query.from_block {
+        filters.push(doc! { "_cursor.from": { "$gte": from_block } });
+    }
+    if let Some(to_block) = query.to_block {
+        filters.push(doc! { "_cursor.from": { "$lte": to_block } });
+    }
+    if let Some(cursor) = &query.cursor {
+        match PageCursor::decode(cursor) {
+            Some(cursor) => filters.push(cursor.after("_cursor.from")),
+            None => return get_error("Invalid cursor".to_string()),
+        }
+    }
+    let filter = if filters.is_empty() {
+        doc! {}
+    } else {
+        doc! { "$and": filters }
+    };
+
+    let projection = match query.fields.as_deref().map(get_projection) {
+        Some(Ok(projection)) => Some(projection),
+        Some(Err(e)) => return get_error(e),
+        None => None,
+    };
+    let limit = get_limit(query.limit, state.conf.events.max_limit);
+    let options = FindOptions::builder()
+        .sort(get_sort(BLOCK_ORDER))
+        .limit(limit)
+        .projection(projection)
+        .build();
+
+    let collection = state.starknetid_db.collection::<Document>(collection_name);
+    match collection.find(filter, options).await {
+        Ok(mut cursor) => {
+            let mut events = Vec::new();
+            let mut last_cursor = None;
+            while let Some(result) = cursor.next().await {
+                match result {
+                    Ok(mut doc) => {
+                        last_cursor = PageCursor::from_document(&doc, "_cursor.from");
+                        doc.remove("_id");
+                        if let Some(cursor) = &last_cursor {
+                            let finality = to_bson(&state.finality(cursor.key)).unwrap_or_default();
+                            doc.insert("finality", finality);
+                        }
+                        events.push(doc);
+                    }
+                    Err(e) => {
+                        return ApiError::new(
+                            ErrorCode::DatabaseError,
+                            format!("Error while reading events: {}", e),
+                        )
+                        .into_response()
+                    }
+                }
+            }
+            // a full page means there might be more events after it
+            let next_cursor = if events.len() as i64 == limit {
+                last_cursor.map(|cursor| cursor.encode())
+            } else {
+                None
+            };
+
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+            (
+                StatusCode::OK,
+                headers,
+                Json(EventsData {
+                    events,
+                    next_cursor,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
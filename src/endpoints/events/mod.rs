@@ -0,0 +1,2 @@
+pub mod get_events;
+pub mod stream;
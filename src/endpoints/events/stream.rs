@@ -0,0 +1,258 @@
+use crate::{filters::Filter, models::AppState, pagination::PageCursor, utils::get_error};
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse,
+    },
+};
+use axum_auto_routes::route;
+use futures::{stream, TryStreamExt};
+use mongodb::{
+    bson::{doc, Document},
+    options::{FindOneOptions, FindOptions},
+};
+use serde::Deserialize;
+use serde_json::json;
+use std::{
+    collections::{HashMap, VecDeque},
+    convert::Infallible,
+    sync::Arc,
+};
+use tokio::time::{sleep, Duration};
+use utoipa::IntoParams;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Deserialize, IntoParams)]
+pub struct StreamQuery {
+    // comma separated list of `contract` or `contract:type`, all contracts when empty
+    filter: Option<String>,
+    // expression the events must match, e.g. `event == "domain_mint" && length <= 5`
+    #[serde(rename = "where")]
+    condition: Option<String>,
+}
+
+/// Event types to stream for each collection, `None` streams all of them
+pub type StreamFilters = HashMap<String, Option<Vec<String>>>;
+
+pub fn parse_filters(
+    filter: &str,
+    collections: &HashMap<String, String>,
+) -> Result<StreamFilters, String> {
+    let mut filters: StreamFilters = HashMap::new();
+    for part in filter.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        let (contract, event_type) = match part.split_once(':') {
+            Some((contract, event_type)) => (contract, Some(event_type.to_string())),
+            None => (part, None),
+        };
+        let collection = collections
+            .get(contract)
+            .ok_or_else(|| format!("Unknown contract: {}", contract))?;
+        let entry = filters
+            .entry(collection.clone())
+            .or_insert_with(|| Some(vec![]));
+        match event_type {
+            Some(event_type) => {
+                if let Some(types) = entry {
+                    types.push(event_type);
+                }
+            }
+            // a bare contract streams every event type
+            None => *entry = None,
+        }
+    }
+    if filters.is_empty() {
+        for collection in collections.values() {
+            filters.insert(collection.clone(), None);
+        }
+    }
+    Ok(filters)
+}
+
+fn contract_of(state: &AppState, collection: &str) -> Option<String> {
+    state
+        .conf
+        .events
+        .collections
+        .iter()
+        .find(|(_, name)| name.as_str() == collection)
+        .map(|(contract, _)| contract.clone())
+}
+
+// where a new client starts: right after the latest indexed event
+async fn get_latest_cursor(state: &AppState, filters: &StreamFilters) -> Option<PageCursor> {
+    let options = FindOneOptions::builder()
+        .sort(doc! { "_cursor.from": -1, "_id": -1 })
+        .build();
+    let mut latest: Option<PageCursor> = None;
+    for collection in filters.keys() {
+        if let Ok(Some(doc)) = state
+            .starknetid_db
+            .collection::<Document>(collection)
+            .find_one(None, options.clone())
+            .await
+        {
+            if let Some(cursor) = PageCursor::from_document(&doc, "_cursor.from") {
+                if latest
+                    .as_ref()
+                    .map_or(true, |l| (cursor.key, cursor.id) > (l.key, l.id))
+                {
+                    latest = Some(cursor);
+                }
+            }
+        }
+    }
+    latest
+}
+
+async fn fetch_events(
+    state: &AppState,
+    filters: &StreamFilters,
+    after: &Option<PageCursor>,
+) -> mongodb::error::Result<Vec<(PageCursor, Document)>> {
+    let limit = state.conf.events.max_limit;
+    let mut events = vec![];
+    for (collection, types) in filters {
+        let mut conditions = vec![];
+        if let Some(after) = after {
+            conditions.push(after.after("_cursor.from"));
+        }
+        if let Some(types) = types {
+            conditions.push(doc! { "event": { "$in": types } });
+        }
+        let filter = if conditions.is_empty() {
+            doc! {}
+        } else {
+            doc! { "$and": conditions }
+        };
+        let options = FindOptions::builder()
+            .sort(doc! { "_cursor.from": 1, "_id": 1 })
+            .limit(limit)
+            .build();
+        let docs: Vec<Document> = state
+            .starknetid_db
+            .collection::<Document>(collection)
+            .find(filter, options)
+            .await?
+            .try_collect()
+            .await?;
+        let contract = contract_of(state, collection);
+        for mut doc in docs {
+            if let Some(cursor) = PageCursor::from_document(&doc, "_cursor.from") {
+                doc.remove("_id");
+                doc.insert("contract", contract.clone());
+                events.push((cursor, doc));
+            }
+        }
+    }
+    // merge collections in order, the events after the limit are fetched on the next poll
+    events.sort_by(|(a, _), (b, _)| (a.key, a.id).cmp(&(b.key, b.id)));
+    events.truncate(limit as usize);
+    Ok(events)
+}
+
+struct StreamState {
+    state: Arc<AppState>,
+    filters: StreamFilters,
+    condition: Option<Filter>,
+    // block a resumed stream restarted from, the events before were too old to be replayed
+    truncated_at: Option<i64>,
+    cursor: Option<PageCursor>,
+    pending: VecDeque<(PageCursor, Document)>,
+}
+
+#[route(get, "/events/stream", crate::endpoints::events::stream)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<StreamQuery>,
+) -> impl IntoResponse {
+    let filters = match parse_filters(
+        query.filter.as_deref().unwrap_or_default(),
+        &state.conf.events.collections,
+    ) {
+        Ok(filters) => filters,
+        Err(e) => return get_error(e),
+    };
+    let condition = match query.condition.as_deref().map(Filter::parse).transpose() {
+        Ok(condition) => condition,
+        Err(e) => return get_error(format!("Invalid where: {}", e)),
+    };
+
+    // browsers send back the id of the last received event when reconnecting
+    let resumed = match headers.get("last-event-id").map(|v| v.to_str()) {
+        Some(Ok(id)) => match PageCursor::decode(id) {
+            Some(cursor) => Some(cursor),
+            None => return get_error("Invalid Last-Event-ID".to_string()),
+        },
+        Some(Err(_)) => return get_error("Invalid Last-Event-ID".to_string()),
+        None => None,
+    };
+    let latest = get_latest_cursor(&state, &filters).await;
+    let (cursor, truncated_at) = match resumed {
+        Some(resumed) => {
+            let window = state.conf.events.max_backfill_blocks;
+            let (cursor, clamped) = resumed.clamp(latest.map(|latest| latest.key), window);
+            let truncated_at = clamped.then_some(cursor.key);
+            (Some(cursor), truncated_at)
+        }
+        None => (latest, None),
+    };
+
+    let initial = StreamState {
+        state,
+        filters,
+        condition,
+        truncated_at,
+        cursor,
+        pending: VecDeque::new(),
+    };
+    let events = stream::unfold(initial, |mut s| async move {
+        if let Some(block) = s.truncated_at.take() {
+            let event = Event::default()
+                .event("truncated")
+                .json_data(json!({ "from_block": block }))
+                .unwrap_or_default();
+            return Some((Ok::<Event, Infallible>(event), s));
+        }
+        loop {
+            if let Some((cursor, doc)) = s.pending.pop_front() {
+                let event = Event::default()
+                    .id(cursor.encode())
+                    .json_data(&doc)
+                    .unwrap_or_else(|_| Event::default().comment("unserializable event"));
+                return Some((Ok::<Event, Infallible>(event), s));
+            }
+            sleep(POLL_INTERVAL).await;
+            match fetch_events(&s.state, &s.filters, &s.cursor).await {
+                Ok(events) => {
+                    // the events filtered out are skipped for good
+                    if let Some((cursor, _)) = events.last() {
+                        s.cursor = Some(cursor.clone());
+                    }
+                    let condition = &s.condition;
+                    s.pending.extend(events.into_iter().filter(|(_, doc)| {
+                        condition.as_ref().map_or(true, |condition| {
+                            serde_json::to_value(doc).is_ok_and(|value| condition.matches(&value))
+                        })
+                    }));
+                }
+                Err(e) => s
+                    .state
+                    .logger
+                    .warning(format!("Error while streaming events: {}", e)),
+            }
+        }
+    });
+
+    Sse::new(events)
+        .keep_alive(
+            KeepAlive::new()
+                .interval(HEARTBEAT_INTERVAL)
+                .text("heartbeat"),
+        )
+        .into_response()
+}
@@ -0,0 +1,43 @@
+use crate::{
+    auth::get_api_key,
+    errors::{ApiError, ErrorCode},
+    exports::{start_job, ExportKind},
+    models::AppState,
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde_json::json;
+use std::sync::Arc;
+
+#[route(post, "/export/domains", crate::endpoints::export::domains)]
+pub async fn handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> impl IntoResponse {
+    let api_key = match get_api_key(&state, &headers).await {
+        Some(api_key) => api_key,
+        None => return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response(),
+    };
+    if state.export_storage.is_none() {
+        return ApiError::new(ErrorCode::FeatureDisabled, "Exports are not enabled")
+            .into_response();
+    }
+
+    match start_job(&state, ExportKind::Domains, api_key.name).await {
+        Ok(job) => (
+            StatusCode::ACCEPTED,
+            Json(json!({
+                "job_id": job.job_id,
+                "status": job.status,
+                "status_url": format!("/export/jobs/{}", job.job_id),
+            })),
+        )
+            .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::InternalError,
+            format!("Unable to start export: {}", e),
+        )
+        .into_response(),
+    }
+}
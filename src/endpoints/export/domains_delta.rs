@@ -0,0 +1,74 @@
+use crate::{
+    auth::get_api_key,
+    errors::{ApiError, ErrorCode},
+    exports::{find_job, parse_snapshot_date, start_job, ExportKind},
+    models::AppState,
+    utils::get_error,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct DeltaQuery {
+    from: String,
+    to: String,
+}
+
+#[route(get, "/export/domains/delta", crate::endpoints::export::domains_delta)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<DeltaQuery>,
+) -> impl IntoResponse {
+    let api_key = match get_api_key(&state, &headers).await {
+        Some(api_key) => api_key,
+        None => return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response(),
+    };
+    if state.export_storage.is_none() {
+        return ApiError::new(ErrorCode::FeatureDisabled, "Exports are not enabled")
+            .into_response();
+    }
+    match (
+        parse_snapshot_date(&query.from),
+        parse_snapshot_date(&query.to),
+    ) {
+        (Ok(from), Ok(to)) if from < to => {}
+        (Ok(_), Ok(_)) => return get_error("from must be before to".to_string()),
+        (Err(e), _) | (_, Err(e)) => return get_error(e.to_string()),
+    }
+
+    let kind = ExportKind::DomainsDelta {
+        from: query.from,
+        to: query.to,
+    };
+    // the same delta is only built once per partner
+    let job = match find_job(&state, &kind, &api_key.name).await {
+        Ok(Some(job)) => Ok(job),
+        Ok(None) => start_job(&state, kind, api_key.name).await,
+        Err(e) => Err(e),
+    };
+    match job {
+        Ok(job) => (
+            StatusCode::ACCEPTED,
+            Json(json!({
+                "job_id": job.job_id,
+                "status": job.status,
+                "status_url": format!("/export/jobs/{}", job.job_id),
+            })),
+        )
+            .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::InternalError,
+            format!("Unable to start export: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,30 @@
+use crate::{
+    auth::get_api_key,
+    errors::{ApiError, ErrorCode},
+    exports::stream_domains,
+    models::AppState,
+    streaming::stream_lines_response,
+};
+use axum::{
+    extract::State,
+    http::{header::CONTENT_DISPOSITION, HeaderMap, HeaderValue},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use futures::TryStreamExt;
+use std::sync::Arc;
+
+#[route(get, "/export/domains.jsonl", crate::endpoints::export::domains_jsonl)]
+pub async fn handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> impl IntoResponse {
+    if get_api_key(&state, &headers).await.is_none() {
+        return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response();
+    }
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        CONTENT_DISPOSITION,
+        HeaderValue::from_static("attachment; filename=\"domains.jsonl\""),
+    );
+    let rows = stream_domains(state)
+        .and_then(|row| async move { serde_json::to_string(&row).map_err(anyhow::Error::from) });
+    stream_lines_response(headers, "application/x-ndjson", None, rows)
+}
@@ -0,0 +1,71 @@
+use crate::{
+    auth::{get_api_key, is_admin},
+    errors::{ApiError, ErrorCode},
+    exports::{get_job, ExportJob, ExportStatus},
+    models::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::Serialize;
+use std::sync::Arc;
+
+#[derive(Serialize)]
+pub struct ExportJobData {
+    #[serde(flatten)]
+    job: ExportJob,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    download_url: Option<String>,
+}
+
+#[route(get, "/export/jobs/:job_id", crate::endpoints::export::get_job)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path(job_id): Path<String>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let requester = if is_admin(&state, &headers) {
+        None
+    } else {
+        match get_api_key(&state, &headers).await {
+            Some(api_key) => Some(api_key.name),
+            None => {
+                return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response()
+            }
+        }
+    };
+
+    let job = match get_job(&state, &job_id).await {
+        // partners can only follow their own exports
+        Ok(Some(job)) if requester.map_or(true, |name| name == job.requested_by) => job,
+        Ok(_) => return ApiError::new(ErrorCode::NotFound, "Export job not found").into_response(),
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+
+    // urls are signed on each request so they are always valid for the whole expiry
+    let download_url = match (&job.object_key, &state.export_storage) {
+        (Some(key), Some(storage)) if job.status == ExportStatus::Done => {
+            match storage.get_download_url(key) {
+                Ok(url) => Some(url),
+                Err(e) => {
+                    return ApiError::new(
+                        ErrorCode::InternalError,
+                        format!("Unable to sign download url: {}", e),
+                    )
+                    .into_response()
+                }
+            }
+        }
+        _ => None,
+    };
+    (StatusCode::OK, Json(ExportJobData { job, download_url })).into_response()
+}
@@ -0,0 +1,5 @@
+pub mod domains;
+pub mod domains_csv;
+pub mod domains_delta;
+pub mod domains_jsonl;
+pub mod get_job;
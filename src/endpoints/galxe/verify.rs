@@ -6,8 +6,9 @@ use axum_auto_routes::route;
 use futures::StreamExt;
 use mongodb::{bson::doc, bson::Document};
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct EmailQuery {
     email: String,
 }
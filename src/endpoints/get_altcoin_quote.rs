@@ -15,11 +15,19 @@ use starknet::core::{
     types::FieldElement,
 };
 
-use crate::{models::AppState, utils::get_error};
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    quotes::{check_quote, QUOTE_UNAVAILABLE},
+    types::starknet_addr::StarknetAddr,
+    utils::get_error,
+};
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct AddrQuery {
-    erc20_addr: FieldElement,
+    #[param(value_type = String)]
+    erc20_addr: StarknetAddr,
 }
 
 #[derive(Deserialize, Debug)]
@@ -39,11 +47,11 @@ pub async fn handler(
     Query(query): Query<AddrQuery>,
 ) -> impl IntoResponse {
     // check if erc20_addr is whitelisted
-    if !state.conf.altcoins.data.contains_key(&query.erc20_addr) {
+    if !state.conf.altcoins.data.contains_key(&*query.erc20_addr) {
         return get_error("Token not supported".to_string());
     }
 
-    let altcoin_data = state.conf.altcoins.data.get(&query.erc20_addr).unwrap();
+    let altcoin_data = state.conf.altcoins.data.get(&*query.erc20_addr).unwrap();
     // fetch quote from avnu api
     let url = format!(
         "{}/tokens/short?in=0x49d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7",
@@ -56,7 +64,7 @@ pub async fn handler(
                 Ok(res) => {
                     let result = res
                         .iter()
-                        .find(|&api_response| api_response.address == query.erc20_addr);
+                        .find(|&api_response| api_response.address == *query.erc20_addr);
                     match result {
                         Some(data) => {
                             // compute message hash
@@ -69,7 +77,26 @@ pub async fn handler(
                             if quote < altcoin_data.min_price as f64
                                 || quote > altcoin_data.max_price as f64
                             {
-                                return get_error("Quote out of range".to_string());
+                                return ApiError::new(
+                                    ErrorCode::QuoteOutOfRange,
+                                    "Quote out of range",
+                                )
+                                .into_response();
+                            }
+                            if let Err(e) =
+                                check_quote(&state.conf.altcoins, altcoin_data, quote).await
+                            {
+                                state
+                                    .logger
+                                    .warning(format!("altcoin quote refused: {}", e));
+                                return (
+                                    StatusCode::SERVICE_UNAVAILABLE,
+                                    Json(json!({
+                                        "error": QUOTE_UNAVAILABLE,
+                                        "reason": e.to_string(),
+                                    })),
+                                )
+                                    .into_response();
                             }
                             // convert current price to wei and return an integer as AVNU api can use more than 18 decimals
                             let current_price_wei =
@@ -105,25 +132,39 @@ pub async fn handler(
                                     })),
                                 )
                                     .into_response(),
-                                Err(e) => get_error(format!(
-                                    "Error while generating Starknet signature: {}",
-                                    e
-                                )),
+                                Err(e) => ApiError::new(
+                                    ErrorCode::InternalError,
+                                    format!("Error while generating Starknet signature: {}", e),
+                                )
+                                .into_response(),
                             }
                         }
-                        None => get_error("Token address not found".to_string()),
+                        None => ApiError::new(ErrorCode::NotFound, "Token address not found")
+                            .into_response(),
                     }
                 }
-                Err(e) => get_error(format!(
-                    "Failed to deserialize result from AVNU API: {} for response: {}",
-                    e, text
-                )),
+                Err(e) => ApiError::new(
+                    ErrorCode::UpstreamError,
+                    format!(
+                        "Failed to deserialize result from AVNU API: {} for response: {}",
+                        e, text
+                    ),
+                )
+                .into_response(),
             },
-            Err(e) => get_error(format!(
-                "Failed to get JSON response while fetching token quote: {}",
-                e
-            )),
+            Err(e) => ApiError::new(
+                ErrorCode::UpstreamError,
+                format!(
+                    "Failed to get JSON response while fetching token quote: {}",
+                    e
+                ),
+            )
+            .into_response(),
         },
-        Err(e) => get_error(format!("Failed to fetch quote from AVNU api: {}", e)),
+        Err(e) => ApiError::new(
+            ErrorCode::UpstreamError,
+            format!("Failed to fetch quote from AVNU api: {}", e),
+        )
+        .into_response(),
     }
 }
@@ -1,11 +1,15 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    streaming::{stream_json_response, Envelope},
+};
 use axum::{
     extract::State,
-    http::{HeaderMap, HeaderValue, StatusCode},
-    response::{IntoResponse, Json},
+    http::{HeaderMap, HeaderValue},
+    response::IntoResponse,
 };
 use axum_auto_routes::route;
-use futures::StreamExt;
+use futures::{future::ready, StreamExt};
 use mongodb::bson::{doc, Document};
 use serde::Serialize;
 use std::sync::Arc;
@@ -18,11 +22,6 @@ struct IdDetails {
     expiry: i64,
 }
 
-#[derive(Serialize)]
-pub struct ExpiringDomains {
-    ids: Vec<IdDetails>,
-}
-
 #[route(get, "/get_expiring_domains", crate::endpoints::get_expiring_domains)]
 pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
     let mut headers = HeaderMap::new();
@@ -54,34 +53,34 @@ pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
         },
     ];
 
-    let mut ids: Vec<IdDetails> = Vec::new();
-
     match collection.aggregate(pipeline, None).await {
-        Ok(mut cursor) => {
-            while let Some(doc_result) = cursor.next().await {
-                match doc_result {
-                    Ok(doc) => {
-                        if let (Ok(domain), Ok(address), Ok(id), Ok(expiry)) = (
-                            doc.get_str("domain"),
-                            doc.get_str("legacy_address"),
-                            doc.get_str("id"),
-                            doc.get_i64("expiry"),
-                        ) {
-                            ids.push(IdDetails {
-                                addr: address.to_string(),
-                                domain: domain.to_string(),
-                                id: id.to_string(),
-                                expiry,
-                            });
-                        }
-                    }
-                    Err(_) => {
-                        get_error("Error while parsing document".to_string());
-                    }
-                }
-            }
-            (StatusCode::OK, Json(ExpiringDomains { ids })).into_response()
+        Ok(cursor) => {
+            // written as the cursor is read, the week can hold many domains
+            let ids = cursor.filter_map(|doc_result| {
+                ready(match doc_result {
+                    Ok(doc) => match (
+                        doc.get_str("domain"),
+                        doc.get_str("legacy_address"),
+                        doc.get_str("id"),
+                        doc.get_i64("expiry"),
+                    ) {
+                        (Ok(domain), Ok(address), Ok(id), Ok(expiry)) => Some(Ok(IdDetails {
+                            addr: address.to_string(),
+                            domain: domain.to_string(),
+                            id: id.to_string(),
+                            expiry,
+                        })),
+                        _ => None,
+                    },
+                    Err(e) => Some(Err(e)),
+                })
+            });
+            stream_json_response(headers, Envelope::object("ids"), ids)
         }
-        Err(_) => get_error("Failed to retrive data from database".to_string()),
+        Err(_) => ApiError::new(
+            ErrorCode::DatabaseError,
+            "Failed to retrive data from database",
+        )
+        .into_response(),
     }
 }
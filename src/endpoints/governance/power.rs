@@ -0,0 +1,73 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    governance::{compute_power, get_holdings, sign_attestation, Attestation, RuleResult},
+    models::AppState,
+    types::starknet_addr::StarknetAddr,
+};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct PowerQuery {
+    #[param(value_type = String)]
+    addr: StarknetAddr,
+}
+
+#[derive(Serialize)]
+pub struct PowerResponse {
+    power: u64,
+    breakdown: Vec<RuleResult>,
+    attestation: Attestation,
+}
+
+#[route(get, "/governance/power", crate::endpoints::governance::power)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<PowerQuery>,
+) -> impl IntoResponse {
+    let conf = &state.conf.governance;
+    if conf.private_key == FieldElement::ZERO {
+        return ApiError::new(
+            ErrorCode::FeatureDisabled,
+            "Governance attestations are not configured",
+        )
+        .into_response();
+    }
+    let holdings =
+        match get_holdings(state.storage.as_ref(), &state.conf.contracts, &query.addr).await {
+            Ok(holdings) => holdings,
+            Err(e) => {
+                return ApiError::new(
+                    ErrorCode::DatabaseError,
+                    format!("Error while reading the holdings: {}", e),
+                )
+                .into_response()
+            }
+        };
+    let now = chrono::Utc::now().timestamp();
+    let power = compute_power(&conf.rules, &holdings, now);
+    match sign_attestation(conf, &query.addr, power.power, now) {
+        Ok(attestation) => (
+            StatusCode::OK,
+            Json(PowerResponse {
+                power: power.power,
+                breakdown: power.breakdown,
+                attestation,
+            }),
+        )
+            .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::InternalError,
+            format!("Error while signing the attestation: {}", e),
+        )
+        .into_response(),
+    }
+}
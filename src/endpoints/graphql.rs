@@ -0,0 +1,16 @@
+use crate::{graphql::get_schema, models::AppState};
+use async_graphql_axum::{GraphQLRequest, GraphQLResponse};
+use axum::extract::State;
+use axum_auto_routes::route;
+use std::sync::Arc;
+
+#[route(post, "/graphql", crate::endpoints::graphql)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    request: GraphQLRequest,
+) -> GraphQLResponse {
+    get_schema()
+        .execute(request.into_inner().data(state))
+        .await
+        .into()
+}
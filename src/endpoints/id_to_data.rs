@@ -1,6 +1,9 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
+    fanout::complete_identity,
+    locale::get_locale,
     models::{AppState, IdentityData},
-    utils::{get_error, to_hex},
+    utils::to_hex,
 };
 use axum::{
     extract::{Query, State},
@@ -13,19 +16,25 @@ use mongodb::bson::{doc, from_bson, Bson, Document};
 use serde::Deserialize;
 use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct IdQuery {
+    #[param(value_type = String)]
     id: FieldElement,
+    locale: Option<String>,
 }
 
 #[route(get, "/id_to_data", crate::endpoints::id_to_data)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
+    request_headers: HeaderMap,
     Query(query): Query<IdQuery>,
 ) -> impl IntoResponse {
+    let locale = get_locale(query.locale.as_deref(), &request_headers);
     let mut headers = HeaderMap::new();
     headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+    headers.insert("Vary", HeaderValue::from_static("Accept-Language"));
 
     let collection = state.starknetid_db.collection::<Document>("id_owners");
 
@@ -36,9 +45,8 @@ pub async fn handler(
         Ok(cursor) => cursor,
         Err(_) => {
             return (
-                StatusCode::INTERNAL_SERVER_ERROR,
                 headers,
-                "Failed to retrieve data".to_string(),
+                ApiError::new(ErrorCode::DatabaseError, "Failed to retrieve data"),
             )
                 .into_response();
         }
@@ -47,16 +55,23 @@ pub async fn handler(
     // The aggregation returns a single document
     return if let Some(result) = cursor.next().await {
         match result {
-            Ok(doc) => (
-                StatusCode::OK,
-                headers,
-                Json(from_bson::<IdentityData>(Bson::Document(doc)).expect("Malformed document")),
+            Ok(doc) => {
+                let mut identity =
+                    from_bson::<IdentityData>(Bson::Document(doc)).expect("Malformed document");
+                complete_identity(&state, &mut identity).await;
+                if let Some(locale) = locale {
+                    identity.set_display_fields(locale);
+                }
+                (StatusCode::OK, headers, Json(identity)).into_response()
+            }
+            Err(err) => ApiError::new(
+                ErrorCode::InternalError,
+                format!("Unexpected error: {}", err),
             )
-                .into_response(),
-            Err(err) => get_error(format!("Unexpected error: {}", err)),
+            .into_response(),
         }
     } else {
-        get_error("Identity not found".to_string())
+        ApiError::new(ErrorCode::IdentityNotFound, "Identity not found").into_response()
     };
 }
 
@@ -124,7 +139,7 @@ fn get_pipeline(id: String) -> Vec<Document> {
                         }
                     },
                     doc! {
-                        "$project": {"_id": 0, "field": 1, "data": 1, "verifier": 1}
+                        "$project": {"_id": 0, "field": 1, "data": 1, "verifier": 1, "block": "$_cursor.from"}
                     }
                 ],
                 "as": "verifier_data"
@@ -146,7 +161,7 @@ fn get_pipeline(id: String) -> Vec<Document> {
                         }
                     },
                     doc! {
-                        "$project": {"_id": 0, "field": 1, "extended_data": 1, "verifier": 1}
+                        "$project": {"_id": 0, "field": 1, "extended_data": 1, "verifier": 1, "block": "$_cursor.from"}
                     }
                 ],
                 "as": "extended_verifier_data"
@@ -0,0 +1,2 @@
+pub mod set_description;
+pub mod verifier_data;
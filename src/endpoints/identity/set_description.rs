@@ -0,0 +1,101 @@
+use crate::{
+    auth::verify_account_signature,
+    descriptions::{
+        get_description_hash, get_id_owner, validate_description, DESCRIPTIONS_COLLECTION,
+        MAX_SIGNATURE_AGE,
+    },
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    utils::{get_error, to_hex},
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::bson::doc;
+use serde::Deserialize;
+use serde_json::json;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetDescriptionQuery {
+    #[schema(value_type = String)]
+    id: FieldElement,
+    // an empty description removes it
+    description: String,
+    timestamp: i64,
+    // signature of the description hash by the owner of the identity
+    #[schema(value_type = Vec<String>)]
+    signature: Vec<FieldElement>,
+}
+
+#[route(
+    post,
+    "/identity/set_description",
+    crate::endpoints::identity::set_description
+)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<SetDescriptionQuery>,
+) -> impl IntoResponse {
+    if (chrono::Utc::now().timestamp() - query.timestamp).abs() > MAX_SIGNATURE_AGE {
+        return ApiError::new(ErrorCode::SignatureExpired, "Signature expired").into_response();
+    }
+    let description = query.description.trim();
+    if let Err(e) = validate_description(&state.profanity, description) {
+        return get_error(e);
+    }
+
+    let owner = match get_id_owner(state.storage.as_ref(), &query.id).await {
+        Ok(Some(owner)) => owner,
+        Ok(None) => {
+            return ApiError::new(ErrorCode::IdentityNotFound, "Identity not found").into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    // the client signs the description as sent
+    let message_hash = get_description_hash(&query.id, &query.description, query.timestamp);
+    if !verify_account_signature(&state, owner, message_hash, &query.signature).await {
+        return ApiError::new(ErrorCode::InvalidSignature, "Invalid signature").into_response();
+    }
+
+    let filter = doc! { "id": to_hex(&query.id) };
+    let result = if description.is_empty() {
+        state
+            .storage
+            .delete_many(DESCRIPTIONS_COLLECTION, filter)
+            .await
+            .map(|_| ())
+    } else {
+        state
+            .storage
+            .upsert_one(
+                DESCRIPTIONS_COLLECTION,
+                filter,
+                doc! {
+                    "owner": to_hex(&owner),
+                    "description": description,
+                    "updated_at": chrono::Utc::now().timestamp(),
+                },
+            )
+            .await
+    };
+    match result {
+        Ok(()) => (StatusCode::OK, Json(json!({ "description": description }))).into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while saving description: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,36 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    verifiers::get_verifier_data,
+};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+
+#[route(
+    get,
+    "/identity/:id/verifier_data",
+    crate::endpoints::identity::verifier_data
+)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<FieldElement>,
+) -> impl IntoResponse {
+    match get_verifier_data(&state, &id).await {
+        Ok(verifier_data) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+            (StatusCode::OK, headers, Json(verifier_data)).into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,40 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    rendering::card::{parse_card_file, render_card},
+    utils::get_error,
+};
+use axum::{
+    extract::{Path, State},
+    http::{header::CONTENT_TYPE, HeaderMap, HeaderValue, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use std::sync::Arc;
+
+#[route(get, "/identity_card/:file", crate::endpoints::identity_card)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path(file): Path<String>,
+) -> impl IntoResponse {
+    let (id, format) = match parse_card_file(&file) {
+        Some(card) => card,
+        None => return get_error("Expected a card file such as 12.png or 12.svg".to_string()),
+    };
+    match render_card(&state, id, format).await {
+        Ok(bytes) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=300"));
+            headers.insert(
+                CONTENT_TYPE,
+                HeaderValue::from_static(format.content_type()),
+            );
+            (StatusCode::OK, headers, bytes).into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::InternalError,
+            format!("Unable to render the card: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,80 @@
+use crate::{
+    avatars::{get_domain_id, resolve_avatar},
+    errors::{ApiError, ErrorCode},
+    images::{load_image, set_image_headers},
+    models::AppState,
+    utils::{deserialize_domain, get_error},
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct ImageQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
+    domain: String,
+    // width of the square thumbnail, the original image when missing
+    w: Option<u32>,
+}
+
+#[route(get, "/img", crate::endpoints::img)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ImageQuery>,
+) -> impl IntoResponse {
+    if let Some(width) = query.w {
+        if !state.conf.images.widths.contains(&width) {
+            return get_error(format!(
+                "Unsupported width, expected one of {:?}",
+                state.conf.images.widths
+            ));
+        }
+    }
+    let id = match get_domain_id(&state, &query.domain).await {
+        Ok(Some(id)) => id,
+        Ok(None) => {
+            return ApiError::new(ErrorCode::DomainNotFound, "Domain not found").into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let avatar = match resolve_avatar(&state, id).await {
+        Ok(Some(avatar)) => avatar,
+        Ok(None) => {
+            return ApiError::new(ErrorCode::NotFound, "No avatar set for this domain")
+                .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to resolve the avatar: {}", e),
+            )
+            .into_response()
+        }
+    };
+
+    match load_image(&state, &avatar.image, query.w).await {
+        Ok(image) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=3600"));
+            set_image_headers(&mut headers, &image);
+            (StatusCode::OK, headers, image.bytes).into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::UpstreamError,
+            format!("Unable to fetch the image: {}", e),
+        )
+        .into_response(),
+    }
+}
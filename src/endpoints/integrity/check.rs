@@ -0,0 +1,29 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    integrity::check_integrity,
+    models::AppState,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+
+#[route(get, "/integrity/:addr", crate::endpoints::integrity::check)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path(addr): Path<FieldElement>,
+) -> impl IntoResponse {
+    let now = chrono::Utc::now().timestamp();
+    match check_integrity(state.storage.as_ref(), &addr, &state.conf.grace, now).await {
+        Ok(integrity) => (StatusCode::OK, Json(integrity)).into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
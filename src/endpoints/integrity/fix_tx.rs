@@ -0,0 +1,81 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    integrity::{check_integrity, get_fix_calls, Diagnosis},
+    models::AppState,
+    simulation::{simulate, Call, SimulationFailure},
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct FixTxQuery {
+    // overrides the tx_builder.simulate setting
+    simulate: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct FixTx {
+    calls: Vec<Call>,
+    // mismatches the calls don't repair
+    manual: Vec<Diagnosis>,
+    simulated: bool,
+    // set when the simulation reverted
+    failure: Option<SimulationFailure>,
+}
+
+#[route(post, "/integrity/:addr/fix_tx", crate::endpoints::integrity::fix_tx)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path(addr): Path<FieldElement>,
+    Query(query): Query<FixTxQuery>,
+) -> impl IntoResponse {
+    let now = chrono::Utc::now().timestamp();
+    let integrity =
+        match check_integrity(state.storage.as_ref(), &addr, &state.conf.grace, now).await {
+            Ok(integrity) => integrity,
+            Err(e) => {
+                return ApiError::new(
+                    ErrorCode::DatabaseError,
+                    format!("Error while fetching from database: {}", e),
+                )
+                .into_response()
+            }
+        };
+    let (calls, manual) = get_fix_calls(&integrity, &addr, &state.conf.contracts);
+
+    let simulate_calls = query.simulate.unwrap_or(state.conf.tx_builder.simulate);
+    if calls.is_empty() || !simulate_calls {
+        let data = FixTx {
+            calls,
+            manual,
+            simulated: false,
+            failure: None,
+        };
+        return (StatusCode::OK, Json(data)).into_response();
+    }
+    let (simulated, failure) = match simulate(&state, &addr, &calls).await {
+        Ok(failure) => (true, failure),
+        // the calldata is still valid when the node can't simulate it
+        Err(e) => {
+            state
+                .logger
+                .warning(format!("integrity: unable to simulate the fix: {}", e));
+            (false, None)
+        }
+    };
+    let data = FixTx {
+        calls,
+        manual,
+        simulated,
+        failure,
+    };
+    (StatusCode::OK, Json(data)).into_response()
+}
@@ -0,0 +1,57 @@
+use crate::{
+    calendar::{get_expiry_events, render_calendar},
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    user_tokens::{get_request_token, get_token_address},
+};
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct ExpiryFeedQuery {
+    token: Option<String>,
+}
+
+#[route(get, "/me/expiry.ics", crate::endpoints::me::expiry_ics)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ExpiryFeedQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let address = match get_request_token(&headers, query.token.as_deref()) {
+        Some(token) => get_token_address(&state, token).await,
+        None => None,
+    };
+    let address = match address {
+        Some(address) => address,
+        None => return ApiError::new(ErrorCode::Unauthorized, "Invalid token").into_response(),
+    };
+
+    match get_expiry_events(state.storage.as_ref(), &address).await {
+        Ok(events) => (
+            StatusCode::OK,
+            [(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/calendar; charset=utf-8"),
+            )],
+            render_calendar(
+                &events,
+                &state.conf.calendar.reminder_days,
+                chrono::Utc::now().timestamp(),
+            ),
+        )
+            .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
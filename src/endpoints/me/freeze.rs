@@ -0,0 +1,63 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    freezes::{freeze_identities, notify_webhooks, MAX_REASON_LEN},
+    models::AppState,
+    user_tokens::{get_request_token, get_token_address},
+    utils::get_error,
+    webhooks::WebhookEvent,
+};
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct FreezeQuery {
+    // shown to the counterparties, e.g. "seed phrase leaked"
+    reason: Option<String>,
+}
+
+#[route(post, "/me/freeze", crate::endpoints::me::freeze)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(query): Json<FreezeQuery>,
+) -> impl IntoResponse {
+    let address = match get_request_token(&headers, None) {
+        Some(token) => get_token_address(&state, token).await,
+        None => None,
+    };
+    let address = match address {
+        Some(address) => address,
+        None => return ApiError::new(ErrorCode::Unauthorized, "Invalid token").into_response(),
+    };
+    let reason = query
+        .reason
+        .map(|reason| reason.trim().to_string())
+        .filter(|reason| !reason.is_empty());
+    if reason
+        .as_ref()
+        .is_some_and(|reason| reason.chars().count() > MAX_REASON_LEN)
+    {
+        return get_error(format!("Reason longer than {} characters", MAX_REASON_LEN));
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    match freeze_identities(state.storage.as_ref(), &address, reason, now).await {
+        Ok(freezes) => {
+            notify_webhooks(&state, &freezes, WebhookEvent::IdentityFrozen, now).await;
+            (StatusCode::OK, Json(json!({ "frozen": freezes }))).into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while freezing identities: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,6 @@
+pub mod expiry_ics;
+pub mod freeze;
+pub mod notifications;
+pub mod quota;
+pub mod token;
+pub mod unfreeze;
@@ -0,0 +1,39 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    notifications::get_notifications,
+    user_tokens::{get_request_token, get_token_address},
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde_json::json;
+use std::sync::Arc;
+
+#[route(get, "/me/notifications", crate::endpoints::me::notifications)]
+pub async fn handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> impl IntoResponse {
+    let address = match get_request_token(&headers, None) {
+        Some(token) => get_token_address(&state, token).await,
+        None => None,
+    };
+    let address = match address {
+        Some(address) => address,
+        None => return ApiError::new(ErrorCode::Unauthorized, "Invalid token").into_response(),
+    };
+
+    match get_notifications(state.storage.as_ref(), &address).await {
+        Ok(notifications) => (
+            StatusCode::OK,
+            Json(json!({ "notifications": notifications })),
+        )
+            .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
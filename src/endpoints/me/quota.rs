@@ -0,0 +1,58 @@
+use crate::{
+    models::AppState,
+    rate_limit::{get_caller, RATE_CLASSES},
+};
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde_json::json;
+use std::{net::SocketAddr, sync::Arc};
+
+#[route(get, "/me/quota", crate::endpoints::me::quota)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    peer: Option<ConnectInfo<SocketAddr>>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    let limits = &state.conf.rate_limits;
+    let caller = get_caller(&state, &headers, peer.map(|info| info.0)).await;
+    let now = chrono::Utc::now();
+    // this request is already counted in the light window
+    let quotas: Vec<_> = RATE_CLASSES
+        .iter()
+        .map(|class| {
+            state.rate_limiter.get(
+                &caller.name,
+                *class,
+                limits.get_limit(*class, caller.api_key.as_deref()),
+                limits.window_secs,
+                now.timestamp(),
+            )
+        })
+        .collect();
+    let burst = limits.get_burst_tier(&caller).map(|tier| {
+        json!({
+            "tier": caller.tier,
+            "capacity": tier.capacity,
+            "refill_per_sec": tier.refill_per_sec,
+            "credits": state.rate_limiter.get_credits(
+                &caller.name,
+                tier,
+                now.timestamp_millis() as f64 / 1000.0,
+            ),
+        })
+    });
+    (
+        StatusCode::OK,
+        Json(json!({
+            "caller": caller.name,
+            "enabled": limits.enabled,
+            "window_secs": limits.window_secs,
+            "quotas": quotas,
+            "burst": burst,
+        })),
+    )
+}
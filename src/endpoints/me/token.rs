@@ -0,0 +1,56 @@
+use crate::{
+    auth::verify_account_signature,
+    descriptions::MAX_SIGNATURE_AGE,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    types::starknet_addr::StarknetAddr,
+    user_tokens::{get_token_request_hash, issue_token},
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct TokenQuery {
+    #[schema(value_type = String)]
+    address: StarknetAddr,
+    timestamp: i64,
+    // signature of the token request hash by the address
+    #[schema(value_type = Vec<String>)]
+    signature: Vec<FieldElement>,
+}
+
+#[route(post, "/me/token", crate::endpoints::me::token)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<TokenQuery>,
+) -> impl IntoResponse {
+    if (chrono::Utc::now().timestamp() - query.timestamp).abs() > MAX_SIGNATURE_AGE {
+        return ApiError::new(ErrorCode::SignatureExpired, "Signature expired").into_response();
+    }
+    let message_hash = get_token_request_hash(&query.address, query.timestamp);
+    if !verify_account_signature(&state, *query.address, message_hash, &query.signature).await {
+        return ApiError::new(ErrorCode::InvalidSignature, "Invalid signature").into_response();
+    }
+
+    match issue_token(&state, &query.address).await {
+        Ok((token, expires_at)) => (
+            StatusCode::OK,
+            Json(json!({ "token": token, "expires_at": expires_at })),
+        )
+            .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while saving token: {}", e),
+        )
+        .into_response(),
+    }
+}
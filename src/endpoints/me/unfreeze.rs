@@ -0,0 +1,59 @@
+use crate::{
+    auth::verify_account_signature,
+    descriptions::MAX_SIGNATURE_AGE,
+    errors::{ApiError, ErrorCode},
+    freezes::{get_unfreeze_hash, notify_webhooks, unfreeze_identities},
+    models::AppState,
+    types::starknet_addr::StarknetAddr,
+    webhooks::WebhookEvent,
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+// a token could have leaked with the wallet, lifting a freeze takes a fresh signature
+#[derive(Deserialize, ToSchema)]
+pub struct UnfreezeQuery {
+    #[schema(value_type = String)]
+    address: StarknetAddr,
+    timestamp: i64,
+    // signature of the unfreeze hash by the address
+    #[schema(value_type = Vec<String>)]
+    signature: Vec<FieldElement>,
+}
+
+#[route(post, "/me/unfreeze", crate::endpoints::me::unfreeze)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<UnfreezeQuery>,
+) -> impl IntoResponse {
+    let now = chrono::Utc::now().timestamp();
+    if (now - query.timestamp).abs() > MAX_SIGNATURE_AGE {
+        return ApiError::new(ErrorCode::SignatureExpired, "Signature expired").into_response();
+    }
+    let message_hash = get_unfreeze_hash(&query.address, query.timestamp);
+    if !verify_account_signature(&state, *query.address, message_hash, &query.signature).await {
+        return ApiError::new(ErrorCode::InvalidSignature, "Invalid signature").into_response();
+    }
+
+    match unfreeze_identities(state.storage.as_ref(), &query.address).await {
+        Ok(freezes) => {
+            notify_webhooks(&state, &freezes, WebhookEvent::IdentityUnfrozen, now).await;
+            let ids: Vec<String> = freezes.into_iter().map(|freeze| freeze.id).collect();
+            (StatusCode::OK, Json(json!({ "unfrozen": ids }))).into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while unfreezing identities: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -1,21 +1,59 @@
 pub mod addr_has_rev;
 pub mod addr_to_available_ids;
 pub mod addr_to_domain;
+pub mod addr_to_domain_at;
 pub mod addr_to_external_domains;
 pub mod addr_to_full_ids;
 pub mod addr_to_token_id;
+pub mod addressbook;
 pub mod addrs_to_domains;
+pub mod admin;
 pub mod campaigns;
+pub mod compare;
+pub mod contracts;
 pub mod crosschain;
 pub mod data_to_ids;
+pub mod decode;
+pub mod dev;
+pub mod docs;
+pub mod domain;
 pub mod domain_to_addr;
+pub mod domain_to_addr_at;
 pub mod domain_to_data;
+pub mod domain_to_record;
+pub mod domains;
+pub mod domains_to_addrs;
+pub mod events;
+pub mod export;
 pub mod galxe;
 pub mod get_altcoin_quote;
 pub mod get_expiring_domains;
+pub mod governance;
+pub mod graphql;
 pub mod id_to_data;
+pub mod identity;
+pub mod identity_card;
+pub mod img;
+pub mod integrity;
+pub mod me;
+pub mod openapi;
+pub mod org;
+pub mod partners;
+pub mod portfolio;
+pub mod preferences;
+pub mod raffles;
 pub mod referral;
+pub mod rendering;
 pub mod renewal;
+pub mod social_to_domain;
 pub mod starkscan;
 pub mod stats;
+pub mod status;
+pub mod transparency;
+pub mod tx;
+pub mod tx_builder;
+pub mod ui;
 pub mod uri;
+pub mod uris;
+pub mod watch;
+pub mod webhooks;
@@ -0,0 +1,19 @@
+use crate::{models::AppState, openapi::get_openapi, routes::ROUTES};
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use std::sync::Arc;
+
+#[route(get, "/openapi.json", crate::endpoints::openapi)]
+pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    // the routes of the groups disabled on this tier aren't documented either
+    let routes = ROUTES
+        .iter()
+        .filter(|spec| state.conf.deployment.is_enabled(spec.group));
+    let mut headers = HeaderMap::new();
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=3600"));
+    (StatusCode::OK, headers, Json(get_openapi(routes))).into_response()
+}
@@ -0,0 +1,71 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    organizations::{OrgMember, MEMBERS_COLLECTION},
+    storage::FindSpec,
+    utils::normalize_domain,
+};
+use axum::{
+    extract::{Path, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use mongodb::bson::{doc, from_document};
+use serde::Serialize;
+use std::sync::Arc;
+
+#[derive(Serialize)]
+pub struct OrgMembersData {
+    org: String,
+    members: Vec<OrgMember>,
+}
+
+#[route(get, "/org/:domain/members", crate::endpoints::org::get_members)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path(domain): Path<String>,
+) -> impl IntoResponse {
+    let org = normalize_domain(&domain);
+    let spec = FindSpec {
+        sort: Some(doc! { "added_at": 1 }),
+        ..Default::default()
+    };
+    match state
+        .storage
+        .find(MEMBERS_COLLECTION, doc! { "org": &org }, spec)
+        .await
+    {
+        Ok(documents) => {
+            let mut results = Vec::new();
+            for document in documents {
+                match from_document::<OrgMember>(document) {
+                    Ok(member) => results.push(member),
+                    _ => {
+                        return ApiError::new(
+                            ErrorCode::InternalError,
+                            "Error while parsing members",
+                        )
+                        .into_response()
+                    }
+                }
+            }
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=60"));
+            (
+                StatusCode::OK,
+                headers,
+                Json(OrgMembersData {
+                    org,
+                    members: results,
+                }),
+            )
+                .into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
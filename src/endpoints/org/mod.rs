@@ -0,0 +1,2 @@
+pub mod get_members;
+pub mod set_member;
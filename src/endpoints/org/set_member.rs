@@ -0,0 +1,117 @@
+use crate::{
+    auth::verify_account_signature,
+    descriptions::MAX_SIGNATURE_AGE,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    organizations::{get_domain_owner, get_member_hash, OrgMember, MEMBERS_COLLECTION},
+    utils::deserialize_domain,
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::bson::{doc, to_document};
+use serde::Deserialize;
+use serde_json::json;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetMemberQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
+    org: String,
+    #[serde(deserialize_with = "deserialize_domain")]
+    member: String,
+    // an empty role removes the member
+    role: String,
+    title: Option<String>,
+    timestamp: i64,
+    // signature of the member hash by the owner of the org domain
+    #[schema(value_type = Vec<String>)]
+    signature: Vec<FieldElement>,
+}
+
+#[route(post, "/org/set_member", crate::endpoints::org::set_member)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<SetMemberQuery>,
+) -> impl IntoResponse {
+    if (chrono::Utc::now().timestamp() - query.timestamp).abs() > MAX_SIGNATURE_AGE {
+        return ApiError::new(ErrorCode::SignatureExpired, "Signature expired").into_response();
+    }
+
+    let owner = match get_domain_owner(state.storage.as_ref(), &query.org).await {
+        Ok(Some(owner)) => owner,
+        Ok(None) => {
+            return ApiError::new(ErrorCode::DomainNotFound, "Organization domain not found")
+                .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let title = query.title.filter(|title| !title.is_empty());
+    let message_hash = get_member_hash(
+        &query.org,
+        &query.member,
+        &query.role,
+        title.as_deref().unwrap_or_default(),
+        query.timestamp,
+    );
+    if !verify_account_signature(&state, owner, message_hash, &query.signature).await {
+        return ApiError::new(ErrorCode::InvalidSignature, "Invalid signature").into_response();
+    }
+
+    let filter = doc! { "org": &query.org, "member": &query.member };
+    if query.role.is_empty() {
+        return match state.storage.delete_many(MEMBERS_COLLECTION, filter).await {
+            Ok(_) => (StatusCode::OK, Json(json!({ "removed": true }))).into_response(),
+            Err(e) => ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while removing member: {}", e),
+            )
+            .into_response(),
+        };
+    }
+
+    match get_domain_owner(state.storage.as_ref(), &query.member).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            return ApiError::new(ErrorCode::DomainNotFound, "Member domain not found")
+                .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    }
+    let member = OrgMember {
+        org: query.org,
+        member: query.member,
+        role: query.role,
+        title,
+        added_at: chrono::Utc::now().timestamp(),
+    };
+    match state
+        .storage
+        .replace_one(MEMBERS_COLLECTION, filter, to_document(&member).unwrap())
+        .await
+    {
+        Ok(_) => (StatusCode::OK, Json(member)).into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while saving member: {}", e),
+        )
+        .into_response(),
+    }
+}
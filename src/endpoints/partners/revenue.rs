@@ -0,0 +1,85 @@
+use crate::{
+    auth::{get_api_key, is_admin},
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    partners::{get_revenue, to_csv, Period},
+};
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct RevenueQuery {
+    from: i64,
+    to: Option<i64>,
+    #[param(inline)]
+    period: Option<Period>,
+    // csv or json
+    format: Option<String>,
+}
+
+#[route(get, "/partners/:id/revenue", crate::endpoints::partners::revenue)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(query): Query<RevenueQuery>,
+    headers: HeaderMap,
+) -> impl IntoResponse {
+    // partners can only see their own revenue
+    if !is_admin(&state, &headers) {
+        match get_api_key(&state, &headers).await {
+            Some(api_key) if api_key.name == id => {}
+            _ => return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response(),
+        }
+    }
+    let partner = match state.conf.partners.get(&id) {
+        Some(partner) => partner,
+        None => return ApiError::new(ErrorCode::NotFound, "Partner not found").into_response(),
+    };
+    let to = query.to.unwrap_or_else(|| chrono::Utc::now().timestamp());
+    let period = query.period.unwrap_or(Period::Month);
+
+    let revenues = match get_revenue(&state, partner, query.from, to, period).await {
+        Ok(revenues) => revenues,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    if query.format.as_deref() == Some("csv") {
+        return (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, HeaderValue::from_static("text/csv")),
+                (
+                    header::CONTENT_DISPOSITION,
+                    HeaderValue::from_static("attachment; filename=\"revenue.csv\""),
+                ),
+            ],
+            to_csv(&revenues),
+        )
+            .into_response();
+    }
+    (
+        StatusCode::OK,
+        Json(json!({
+            "partner": id,
+            "share": partner.share,
+            "from": query.from,
+            "to": to,
+            "revenues": revenues,
+            "owed": revenues.iter().map(|row| row.owed).sum::<i64>(),
+        })),
+    )
+        .into_response()
+}
@@ -0,0 +1 @@
+pub mod transfer_tx;
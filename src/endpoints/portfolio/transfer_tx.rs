@@ -0,0 +1,138 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    portfolio::{check_target, get_transfer_calls, get_transfer_items, TransferItem},
+    simulation::{classify_revert, rpc_request, simulate, Call, FailureReason, SimulationFailure},
+    utils::{get_error, normalize_domain, to_hex},
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct TransferTxQuery {
+    // current owner, which sends the multicall
+    #[schema(value_type = String)]
+    from: FieldElement,
+    #[schema(value_type = String)]
+    to: FieldElement,
+    #[serde(default)]
+    #[schema(value_type = Vec<String>)]
+    ids: Vec<FieldElement>,
+    #[serde(default)]
+    domains: Vec<String>,
+    // overrides the tx_builder.simulate setting
+    simulate: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct TransferTx {
+    calls: Vec<Call>,
+    transferred: Vec<TransferItem>,
+    simulated: bool,
+    // set when the simulation reverted
+    failure: Option<SimulationFailure>,
+}
+
+#[route(
+    post,
+    "/portfolio/transfer_tx",
+    crate::endpoints::portfolio::transfer_tx
+)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<TransferTxQuery>,
+) -> impl IntoResponse {
+    let conf = &state.conf.portfolio;
+    let selected = query.ids.len() + query.domains.len();
+    if selected == 0 {
+        return get_error("Select at least one identity or domain".to_string());
+    }
+    if selected > conf.max_items {
+        return get_error(format!("At most {} items per transfer", conf.max_items));
+    }
+
+    // an address with no contract deployed has no class hash
+    let class_hash = match rpc_request(
+        &state,
+        "starknet_getClassHashAt",
+        json!({ "block_id": "latest", "contract_address": to_hex(&query.to) }),
+    )
+    .await
+    {
+        Ok(result) => result
+            .as_str()
+            .and_then(|hash| FieldElement::from_hex_be(hash).ok()),
+        Err(e) if classify_revert(&e.to_string()) == FailureReason::AccountNotDeployed => None,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to fetch the class hash of the recipient: {}", e),
+            )
+            .into_response()
+        }
+    };
+    if let Err(e) = check_target(
+        conf,
+        &state.conf.contracts,
+        &query.from,
+        &query.to,
+        class_hash.as_ref(),
+    ) {
+        return get_error(e);
+    }
+
+    let domains: Vec<String> = query.domains.iter().map(|d| normalize_domain(d)).collect();
+    let items =
+        match get_transfer_items(state.storage.as_ref(), &query.from, &query.ids, &domains).await {
+            Ok(items) => items,
+            // the other errors are domains or identities the sender can't transfer
+            Err(e) if e.downcast_ref::<mongodb::error::Error>().is_some() => {
+                return ApiError::new(
+                    ErrorCode::DatabaseError,
+                    format!("Error while fetching from database: {}", e),
+                )
+                .into_response()
+            }
+            Err(e) => return get_error(e.to_string()),
+        };
+    let calls = get_transfer_calls(
+        state.conf.contracts.starknetid,
+        query.from,
+        query.to,
+        &items,
+    );
+
+    let (simulated, failure) = if query.simulate.unwrap_or(state.conf.tx_builder.simulate) {
+        match simulate(&state, &query.from, &calls).await {
+            Ok(failure) => (true, failure),
+            // the calldata is still valid when the node can't simulate it
+            Err(e) => {
+                state
+                    .logger
+                    .warning(format!("portfolio: unable to simulate: {}", e));
+                (false, None)
+            }
+        }
+    } else {
+        (false, None)
+    };
+    (
+        StatusCode::OK,
+        Json(TransferTx {
+            calls,
+            transferred: items,
+            simulated,
+            failure,
+        }),
+    )
+        .into_response()
+}
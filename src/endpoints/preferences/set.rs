@@ -0,0 +1,70 @@
+use crate::{
+    addressbook::get_contact_hash,
+    auth::verify_account_signature,
+    descriptions::MAX_SIGNATURE_AGE,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    preferences::{get_preferences_hash, Preferences, PREFERENCES_COLLECTION},
+    types::starknet_addr::StarknetAddr,
+    utils::to_hex,
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::bson::doc;
+use serde::Deserialize;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct SetPreferencesQuery {
+    #[schema(value_type = String)]
+    address: StarknetAddr,
+    #[serde(flatten)]
+    #[schema(inline)]
+    preferences: Preferences,
+    timestamp: i64,
+    // signature of the preferences hash by the address
+    #[schema(value_type = Vec<String>)]
+    signature: Vec<FieldElement>,
+}
+
+#[route(post, "/preferences/set", crate::endpoints::preferences::set)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<SetPreferencesQuery>,
+) -> impl IntoResponse {
+    if (chrono::Utc::now().timestamp() - query.timestamp).abs() > MAX_SIGNATURE_AGE {
+        return ApiError::new(ErrorCode::SignatureExpired, "Signature expired").into_response();
+    }
+    let message_hash = get_preferences_hash(&query.address, &query.preferences, query.timestamp);
+    if !verify_account_signature(&state, *query.address, message_hash, &query.signature).await {
+        return ApiError::new(ErrorCode::InvalidSignature, "Invalid signature").into_response();
+    }
+
+    match state
+        .storage
+        .upsert_one(
+            PREFERENCES_COLLECTION,
+            doc! { "address": to_hex(&query.address) },
+            doc! {
+                "addressbook_discoverable": query.preferences.addressbook_discoverable,
+                "reverification_reminders": query.preferences.reverification_reminders,
+                "contact_hash": get_contact_hash(&query.address),
+                "updated_at": chrono::Utc::now().timestamp(),
+            },
+        )
+        .await
+    {
+        Ok(()) => (StatusCode::OK, Json(query.preferences)).into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while saving preferences: {}", e),
+        )
+        .into_response(),
+    }
+}
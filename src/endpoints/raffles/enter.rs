@@ -0,0 +1,119 @@
+use crate::{
+    auth::verify_account_signature,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    raffle::{is_eligible, Raffle},
+    types::starknet_addr::StarknetAddr,
+    utils::{get_error, to_hex},
+};
+use axum::{
+    extract::{Json, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::{
+    bson::{doc, from_document, Document},
+    options::UpdateOptions,
+};
+use serde::Deserialize;
+use starknet::{
+    core::{crypto::pedersen_hash, types::FieldElement, utils::cairo_short_string_to_felt},
+    macros::short_string,
+};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct EnterQuery {
+    id: String,
+    #[schema(value_type = String)]
+    addr: StarknetAddr,
+    // signature of pedersen(pedersen('raffle entry', id), addr) by the account
+    #[schema(value_type = Vec<String>)]
+    signature: Vec<FieldElement>,
+}
+
+#[route(post, "/raffles/enter", crate::endpoints::raffles::enter)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Json(query): Json<EnterQuery>,
+) -> impl IntoResponse {
+    let raffles = state.free_domains_db.collection::<Document>("raffles");
+    let raffle = match raffles.find_one(doc! { "id": &query.id }, None).await {
+        Ok(Some(doc)) => match from_document::<Raffle>(doc) {
+            Ok(raffle) => raffle,
+            Err(e) => {
+                return ApiError::new(ErrorCode::InternalError, format!("Malformed raffle: {}", e))
+                    .into_response()
+            }
+        },
+        Ok(None) => return ApiError::new(ErrorCode::NotFound, "Raffle not found").into_response(),
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    if chrono::Utc::now().timestamp() > raffle.entry_deadline {
+        return ApiError::new(ErrorCode::Conflict, "Raffle entries are closed").into_response();
+    }
+
+    let raffle_felt = match cairo_short_string_to_felt(&raffle.id) {
+        Ok(felt) => felt,
+        Err(_) => return get_error("Invalid raffle id".to_string()),
+    };
+    let message_hash = pedersen_hash(
+        &pedersen_hash(&short_string!("raffle entry"), &raffle_felt),
+        &query.addr,
+    );
+    if !verify_account_signature(&state, *query.addr, message_hash, &query.signature).await {
+        return ApiError::new(ErrorCode::InvalidSignature, "Invalid signature").into_response();
+    }
+
+    match is_eligible(&state, &query.addr, &raffle.rules).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return ApiError::new(
+                ErrorCode::Forbidden,
+                "Address is not eligible for this raffle",
+            )
+            .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while checking eligibility: {}", e),
+            )
+            .into_response()
+        }
+    }
+
+    let entries = state
+        .free_domains_db
+        .collection::<Document>("raffle_entries");
+    let addr = to_hex(&query.addr);
+    match entries
+        .update_one(
+            doc! { "raffle_id": &raffle.id, "addr": &addr },
+            doc! {
+                "$setOnInsert": {
+                    "raffle_id": &raffle.id,
+                    "addr": &addr,
+                    "timestamp": chrono::Utc::now().timestamp(),
+                }
+            },
+            UpdateOptions::builder().upsert(true).build(),
+        )
+        .await
+    {
+        Ok(_) => (StatusCode::OK, Json("Entry registered".to_string())).into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while updating database: {}", e),
+        )
+        .into_response(),
+    }
+}
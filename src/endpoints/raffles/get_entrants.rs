@@ -0,0 +1,130 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    pagination::{get_limit, get_sort, ENTRANT_ORDER},
+    raffle::Raffle,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, from_document, Document},
+    options::FindOptions,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct GetEntrantsQuery {
+    id: String,
+    limit: Option<i64>,
+    cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct EntrantsData {
+    entrants: Vec<String>,
+    next_cursor: Option<String>,
+}
+
+/// Sorted entrants of a closed raffle, the list its `entrants_hash` and winners are computed
+/// over, pages joined with newlines hash to the published `entrants_hash`
+#[route(get, "/raffles/get_entrants", crate::endpoints::raffles::get_entrants)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetEntrantsQuery>,
+) -> impl IntoResponse {
+    let raffles = state.free_domains_db.collection::<Document>("raffles");
+    let raffle = match raffles.find_one(doc! { "id": &query.id }, None).await {
+        Ok(Some(doc)) => match from_document::<Raffle>(doc) {
+            // entries may still change until the closing
+            Ok(raffle) if raffle.closing.is_none() => {
+                return ApiError::new(ErrorCode::Conflict, "Raffle entries not closed yet")
+                    .into_response()
+            }
+            Ok(raffle) => raffle,
+            Err(e) => {
+                return ApiError::new(ErrorCode::InternalError, format!("Malformed raffle: {}", e))
+                    .into_response()
+            }
+        },
+        Ok(None) => return ApiError::new(ErrorCode::NotFound, "Raffle not found").into_response(),
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+
+    // the entrants hashed by the closing, see `raffle::get_entrants`
+    let mut filter = doc! {
+        "raffle_id": &query.id,
+        "timestamp": { "$lte": raffle.entry_deadline },
+    };
+    if let Some(cursor) = &query.cursor {
+        filter.insert("addr", doc! { "$gt": cursor });
+    }
+    let limit = get_limit(query.limit, state.conf.pagination.max_limit);
+    // the addresses are stored as padded hex, sorted the same way by mongodb and `sort`
+    let options = FindOptions::builder()
+        .sort(get_sort(ENTRANT_ORDER))
+        .limit(limit)
+        .build();
+    let entries = state
+        .free_domains_db
+        .collection::<Document>("raffle_entries");
+    let mut entrants = Vec::new();
+    match entries.find(filter, options).await {
+        Ok(mut cursor) => {
+            while let Some(result) = cursor.next().await {
+                match result {
+                    Ok(doc) => {
+                        if let Ok(addr) = doc.get_str("addr") {
+                            entrants.push(addr.to_string());
+                        }
+                    }
+                    Err(e) => {
+                        return ApiError::new(
+                            ErrorCode::DatabaseError,
+                            format!("Error while reading entries: {}", e),
+                        )
+                        .into_response()
+                    }
+                }
+            }
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    }
+
+    // the cursor is the last entrant of a full page
+    let next_cursor = if entrants.len() as i64 == limit {
+        entrants.last().cloned()
+    } else {
+        None
+    };
+    let mut headers = HeaderMap::new();
+    // the entrants of a closed raffle never change
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=3600"));
+    (
+        StatusCode::OK,
+        headers,
+        Json(EntrantsData {
+            entrants,
+            next_cursor,
+        }),
+    )
+        .into_response()
+}
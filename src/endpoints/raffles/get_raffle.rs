@@ -0,0 +1,41 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    raffle::{Raffle, RaffleAudit},
+};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use mongodb::bson::{doc, from_document, Document};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct GetRaffleQuery {
+    id: String,
+}
+
+#[route(get, "/raffles/get_raffle", crate::endpoints::raffles::get_raffle)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetRaffleQuery>,
+) -> impl IntoResponse {
+    let raffles = state.free_domains_db.collection::<Document>("raffles");
+    match raffles.find_one(doc! { "id": &query.id }, None).await {
+        Ok(Some(doc)) => match from_document::<Raffle>(doc) {
+            Ok(raffle) => (StatusCode::OK, Json(RaffleAudit::from(raffle))).into_response(),
+            Err(e) => ApiError::new(ErrorCode::InternalError, format!("Malformed raffle: {}", e))
+                .into_response(),
+        },
+        Ok(None) => ApiError::new(ErrorCode::NotFound, "Raffle not found").into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
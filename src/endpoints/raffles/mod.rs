@@ -0,0 +1,3 @@
+pub mod enter;
+pub mod get_entrants;
+pub mod get_raffle;
@@ -1,4 +1,8 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    types::starknet_addr::StarknetAddr,
+};
 use axum::{
     extract::{Json, State},
     http::StatusCode,
@@ -11,12 +15,13 @@ use mongodb::{
     options::UpdateOptions,
 };
 use serde::Deserialize;
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::ToSchema;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, ToSchema)]
 pub struct AddClickQuery {
-    sponsor_addr: FieldElement,
+    #[schema(value_type = String)]
+    sponsor_addr: StarknetAddr,
 }
 
 #[route(post, "/referral/add_click", crate::endpoints::referral::add_click)]
@@ -35,7 +40,8 @@ pub async fn handler(
     let result = sponsor_usage
         .update_one(
             doc! {
-                "sponsor_addr": query.sponsor_addr.to_string(),
+                // stored as a decimal felt like before the padding
+                "sponsor_addr": query.sponsor_addr.0.to_string(),
                 "day": today_bson,
             },
             doc! {
@@ -52,6 +58,8 @@ pub async fn handler(
             Json("Sponsor usage updated successfully".to_string()),
         )
             .into_response(),
-        Err(_) => get_error("Error while updating database".to_string()),
+        Err(_) => {
+            ApiError::new(ErrorCode::DatabaseError, "Error while updating database").into_response()
+        }
     }
 }
@@ -1,4 +1,7 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+};
 use axum::{
     extract::{Query, State},
     http::{HeaderMap, HeaderValue, StatusCode},
@@ -10,13 +13,14 @@ use futures::StreamExt;
 use mongodb::bson::{doc, Bson, DateTime as BsonDateTime};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct Data {
     counts: Vec<i64>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct IdQuery {
     sponsor: String,
     since_day: i64,
@@ -69,7 +73,11 @@ pub async fn handler(
                 }
             }
             Err(e) => {
-                return get_error(format!("Error while fetching from database: {:?}", e));
+                return ApiError::new(
+                    ErrorCode::DatabaseError,
+                    format!("Error while fetching from database: {:?}", e),
+                )
+                .into_response();
             }
         }
 
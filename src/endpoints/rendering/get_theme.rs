@@ -0,0 +1,29 @@
+use crate::{models::AppState, rendering::theme::get_theme, utils::deserialize_domain};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct GetThemeQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
+    domain: String,
+}
+
+/// Theme the identity cards of the domain are rendered with, for the clients drawing their own
+#[route(get, "/rendering/get_theme", crate::endpoints::rendering::get_theme)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetThemeQuery>,
+) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=300"));
+
+    let theme = get_theme(&state, &query.domain).await;
+    (StatusCode::OK, headers, Json(theme)).into_response()
+}
@@ -0,0 +1,2 @@
+pub mod get_theme;
+pub mod set_theme;
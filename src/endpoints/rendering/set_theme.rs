@@ -0,0 +1,73 @@
+use crate::{
+    auth::{get_api_key, is_admin},
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    rendering::theme::Theme,
+    utils::get_error,
+};
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::{
+    bson::{doc, to_document, Document},
+    options::ReplaceOptions,
+};
+use std::sync::Arc;
+
+#[route(post, "/rendering/set_theme", crate::endpoints::rendering::set_theme)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(theme): Json<Theme>,
+) -> impl IntoResponse {
+    // admins can theme any namespace, partners only the ones attached to their key
+    if !is_admin(&state, &headers) {
+        match get_api_key(&state, &headers).await {
+            Some(api_key) if api_key.owns_namespace(&theme.namespace) => {}
+            Some(_) => {
+                return ApiError::new(
+                    ErrorCode::Forbidden,
+                    "API key not allowed for this namespace",
+                )
+                .into_response()
+            }
+            None => {
+                return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response()
+            }
+        }
+    }
+
+    if let Err(e) = theme.validate() {
+        return get_error(e);
+    }
+
+    let document = match to_document(&theme) {
+        Ok(document) => document,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::InternalError,
+                format!("Unable to serialize theme: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let themes = state.starknetid_db.collection::<Document>("themes");
+    match themes
+        .replace_one(
+            doc! { "namespace": &theme.namespace },
+            document,
+            ReplaceOptions::builder().upsert(true).build(),
+        )
+        .await
+    {
+        Ok(_) => (StatusCode::OK, Json(theme)).into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while updating database: {}", e),
+        )
+        .into_response(),
+    }
+}
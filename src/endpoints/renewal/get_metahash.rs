@@ -1,6 +1,8 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
-    utils::{get_error, to_hex},
+    types::starknet_addr::StarknetAddr,
+    utils::to_hex,
 };
 use axum::{
     extract::{Query, State},
@@ -11,8 +13,8 @@ use axum_auto_routes::route;
 use futures::TryStreamExt;
 use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct GetMetaHashData {
@@ -20,9 +22,10 @@ pub struct GetMetaHashData {
     tax_rate: f32,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct GetMetaHashQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
 }
 
 #[route(get, "/renewal/get_metahash", crate::endpoints::renewal::get_metahash)]
@@ -67,7 +70,8 @@ pub async fn handler(
         Ok(cursor) => match cursor.try_collect::<Vec<mongodb::bson::Document>>().await {
             Ok(documents) => {
                 if documents.is_empty() {
-                    return get_error("No documents found".to_string());
+                    return ApiError::new(ErrorCode::NotFound, "No documents found")
+                        .into_response();
                 }
 
                 let mut headers = HeaderMap::new();
@@ -90,10 +94,18 @@ pub async fn handler(
                         return (StatusCode::OK, headers, Json(res)).into_response();
                     }
                 }
-                get_error("No metahash found".to_string())
+                ApiError::new(ErrorCode::NotFound, "No metahash found").into_response()
             }
-            Err(_) => get_error("Error while fetching from database".to_string()),
+            Err(_) => ApiError::new(
+                ErrorCode::DatabaseError,
+                "Error while fetching from database",
+            )
+            .into_response(),
         },
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(_) => ApiError::new(
+            ErrorCode::DatabaseError,
+            "Error while fetching from database",
+        )
+        .into_response(),
     }
 }
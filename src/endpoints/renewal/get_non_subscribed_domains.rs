@@ -1,6 +1,8 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
-    utils::{get_error, to_hex},
+    types::starknet_addr::StarknetAddr,
+    utils::to_hex,
 };
 use axum::{
     extract::{Query, State},
@@ -12,12 +14,13 @@ use futures::StreamExt;
 use mongodb::{bson::doc, options::AggregateOptions};
 use regex::Regex;
 use serde::Deserialize;
-use starknet::core::types::FieldElement;
 use std::{collections::HashSet, sync::Arc};
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct StarknetIdQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
 }
 
 lazy_static::lazy_static! {
@@ -183,6 +186,10 @@ pub async fn handler(
             let results: Vec<String> = domains_set.into_iter().collect(); // Convert HashSet back to Vec to match your function's expected return type
             (StatusCode::OK, Json(results)).into_response()
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(_) => ApiError::new(
+            ErrorCode::DatabaseError,
+            "Error while fetching from database",
+        )
+        .into_response(),
     }
 }
@@ -1,6 +1,8 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
-    utils::{get_error, to_hex},
+    types::starknet_addr::StarknetAddr,
+    utils::{deserialize_domain, to_hex},
 };
 use axum::{
     extract::{Query, State},
@@ -11,17 +13,19 @@ use axum_auto_routes::route;
 use futures::TryStreamExt;
 use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct StarknetIdData {
     starknet_id: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct StarknetIdQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
+    #[serde(deserialize_with = "deserialize_domain")]
     domain: String,
 }
 
@@ -62,7 +66,11 @@ pub async fn handler(
         }
         (StatusCode::OK, headers, Json(combined_results)).into_response()
     } else {
-        get_error("Error while fetching from database or no results found".to_string())
+        ApiError::new(
+            ErrorCode::DatabaseError,
+            "Error while fetching from database or no results found",
+        )
+        .into_response()
     }
 }
 
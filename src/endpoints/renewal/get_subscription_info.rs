@@ -1,6 +1,8 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
-    utils::{get_error, to_hex},
+    types::starknet_addr::StarknetAddr,
+    utils::to_hex,
 };
 use axum::{
     extract::{Query, State},
@@ -14,10 +16,12 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::{collections::HashMap, sync::Arc};
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct StarknetIdQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
 }
 
 lazy_static::lazy_static! {
@@ -257,6 +261,10 @@ pub async fn handler(
             }
             (StatusCode::OK, Json(results)).into_response()
         }
-        Err(_) => get_error("Error while fetching from database".to_string()),
+        Err(_) => ApiError::new(
+            ErrorCode::DatabaseError,
+            "Error while fetching from database",
+        )
+        .into_response(),
     }
 }
@@ -2,3 +2,4 @@ pub mod get_metahash;
 pub mod get_non_subscribed_domains;
 pub mod get_renewal_data;
 pub mod get_subscription_info;
+pub mod status;
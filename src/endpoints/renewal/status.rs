@@ -0,0 +1,41 @@
+use crate::{
+    auto_renewal::get_renewal_status,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    utils::deserialize_domain,
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct StatusQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
+    domain: String,
+}
+
+#[route(get, "/renewal/status", crate::endpoints::renewal::status)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<StatusQuery>,
+) -> impl IntoResponse {
+    match get_renewal_status(&state, &query.domain).await {
+        Ok(Some(status)) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+            (StatusCode::OK, headers, Json(status)).into_response()
+        }
+        Ok(None) => ApiError::new(ErrorCode::DomainNotFound, "no domain found").into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::UpstreamError,
+            format!("Error while fetching the renewal status: {}", e),
+        )
+        .into_response(),
+    }
+}
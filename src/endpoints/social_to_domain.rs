@@ -0,0 +1,72 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    socials::{find_identities, normalize_handle, resolve_social_id, SocialType},
+    utils::{get_error, to_hex},
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct SocialQuery {
+    // twitter, discord or github
+    #[serde(rename = "type")]
+    #[param(value_type = String)]
+    social_type: SocialType,
+    // the username, or the numeric user id the verifiers store
+    handle: String,
+}
+
+#[route(get, "/social_to_domain", crate::endpoints::social_to_domain)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<SocialQuery>,
+) -> impl IntoResponse {
+    let handle = match normalize_handle(&query.handle) {
+        Some(handle) => handle,
+        None => return get_error("Invalid handle".to_string()),
+    };
+    let social_id = match resolve_social_id(&state.conf, query.social_type, &handle).await {
+        Ok(social_id) => social_id,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to find the account: {}", e),
+            )
+            .into_response()
+        }
+    };
+    match find_identities(
+        state.storage.as_ref(),
+        &state.conf.contracts,
+        query.social_type,
+        &social_id,
+    )
+    .await
+    {
+        Ok(identities) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=300"));
+            let body = json!({
+                "type": query.social_type,
+                "handle": handle,
+                "social_id": to_hex(&social_id),
+                "identities": identities,
+            });
+            (StatusCode::OK, headers, Json(body)).into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
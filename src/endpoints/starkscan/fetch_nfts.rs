@@ -1,6 +1,8 @@
 use crate::{
+    errors::{ApiError, ErrorCode},
     models::AppState,
-    utils::{get_error, to_hex},
+    types::starknet_addr::StarknetAddr,
+    utils::to_hex,
 };
 use axum::{
     extract::{Query, State},
@@ -11,12 +13,13 @@ use axum_auto_routes::route;
 use mongodb::bson::doc;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use starknet::core::types::FieldElement;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct FetchNftsQuery {
-    addr: FieldElement,
+    #[param(value_type = String)]
+    addr: StarknetAddr,
     cursor: Option<String>,
 }
 
@@ -70,16 +73,28 @@ pub async fn handler(
         Ok(response) => match response.text().await {
             Ok(text) => match serde_json::from_str::<StarkscanApiResult>(&text) {
                 Ok(res) => (StatusCode::OK, Json(res)).into_response(),
-                Err(e) => get_error(format!(
-                    "Failed to deserialize result from Starkscan API: {} for response: {}",
-                    e, text
-                )),
+                Err(e) => ApiError::new(
+                    ErrorCode::UpstreamError,
+                    format!(
+                        "Failed to deserialize result from Starkscan API: {} for response: {}",
+                        e, text
+                    ),
+                )
+                .into_response(),
             },
-            Err(e) => get_error(format!(
-                "Failed to get JSON response while fetching user NFT data: {}",
-                e
-            )),
+            Err(e) => ApiError::new(
+                ErrorCode::UpstreamError,
+                format!(
+                    "Failed to get JSON response while fetching user NFT data: {}",
+                    e
+                ),
+            )
+            .into_response(),
         },
-        Err(e) => get_error(format!("Failed to fetch user NFTs from API: {}", e)),
+        Err(e) => ApiError::new(
+            ErrorCode::UpstreamError,
+            format!("Failed to fetch user NFTs from API: {}", e),
+        )
+        .into_response(),
     }
 }
@@ -1,4 +1,8 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{
+    errors::{ApiError, ErrorCode},
+    locale::get_locale,
+    models::AppState,
+};
 use axum::{
     extract::{Query, State},
     http::{HeaderMap, HeaderValue, StatusCode},
@@ -10,24 +14,31 @@ use futures::StreamExt;
 use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct CountAddrsData {
     count: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count_display: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct CountAddrsQuery {
     since: i64,
+    locale: Option<String>,
 }
 
 #[route(get, "/stats/count_addrs", crate::endpoints::stats::count_addrs)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
+    request_headers: HeaderMap,
     Query(query): Query<CountAddrsQuery>,
 ) -> impl IntoResponse {
+    let locale = get_locale(query.locale.as_deref(), &request_headers);
     let mut headers = HeaderMap::new();
     headers.insert("Cache-Control", HeaderValue::from_static("max-age=60"));
+    headers.insert("Vary", HeaderValue::from_static("Accept-Language"));
 
     let domain_collection = state
         .starknetid_db
@@ -55,15 +66,26 @@ pub async fn handler(
                 match result {
                     Ok(doc_) => {
                         let count = doc_.get_i32("total").unwrap_or(0);
-                        let response_data = CountAddrsData { count };
+                        let response_data = CountAddrsData {
+                            count,
+                            count_display: locale.map(|locale| locale.format_number(count as i64)),
+                        };
                         (StatusCode::OK, headers, Json(response_data)).into_response()
                     }
-                    Err(e) => get_error(format!("Error while processing the document: {:?}", e)),
+                    Err(e) => ApiError::new(
+                        ErrorCode::InternalError,
+                        format!("Error while processing the document: {:?}", e),
+                    )
+                    .into_response(),
                 }
             } else {
-                get_error("No documents found".to_string())
+                ApiError::new(ErrorCode::NotFound, "No documents found").into_response()
             }
         }
-        Err(e) => get_error(format!("Error while fetching from database: {:?}", e)),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {:?}", e),
+        )
+        .into_response(),
     }
 }
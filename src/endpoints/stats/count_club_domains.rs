@@ -11,6 +11,7 @@ use mongodb::bson::{self, doc, Bson};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct CountClubDomainsData {
@@ -18,7 +19,7 @@ pub struct CountClubDomainsData {
     count: i32,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct CountClubDomainsQuery {
     since: i64,
 }
@@ -1,4 +1,8 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{
+    errors::{ApiError, ErrorCode},
+    locale::get_locale,
+    models::AppState,
+};
 use axum::{
     extract::{Query, State},
     http::{HeaderMap, HeaderValue, StatusCode},
@@ -9,24 +13,51 @@ use axum_auto_routes::route;
 use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct CountDomainsData {
     count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count_display: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct CountDomainsQuery {
     since: i64,
+    locale: Option<String>,
 }
 
 #[route(get, "/stats/count_domains", crate::endpoints::stats::count_domains)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
+    request_headers: HeaderMap,
     Query(query): Query<CountDomainsQuery>,
 ) -> impl IntoResponse {
+    let locale = get_locale(query.locale.as_deref(), &request_headers);
     let mut headers = HeaderMap::new();
     headers.insert("Cache-Control", HeaderValue::from_static("max-age=60"));
+    headers.insert("Vary", HeaderValue::from_static("Accept-Language"));
+
+    if let Some(analytics) = &state.analytics {
+        return match analytics
+            .count_domains(query.since, chrono::Utc::now().timestamp())
+            .await
+        {
+            Ok(count) => {
+                let response_data = CountDomainsData {
+                    count,
+                    count_display: locale.map(|locale| locale.format_number(count as i64)),
+                };
+                (StatusCode::OK, headers, Json(response_data)).into_response()
+            }
+            Err(e) => ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from analytics: {}", e),
+            )
+            .into_response(),
+        };
+    }
 
     let domain_collection = state
         .starknetid_db
@@ -44,9 +75,16 @@ pub async fn handler(
 
     match total {
         Ok(count) => {
-            let response_data = CountDomainsData { count };
+            let response_data = CountDomainsData {
+                count,
+                count_display: locale.map(|locale| locale.format_number(count as i64)),
+            };
             (StatusCode::OK, headers, Json(response_data)).into_response()
         }
-        Err(e) => get_error(format!("Error while fetching from database: {:?}", e)),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {:?}", e),
+        )
+        .into_response(),
     }
 }
@@ -1,4 +1,9 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{
+    errors::{ApiError, ErrorCode},
+    locale::get_locale,
+    models::AppState,
+    utils::get_error,
+};
 use axum::{
     extract::{Query, State},
     http::{HeaderMap, HeaderValue, StatusCode},
@@ -10,25 +15,33 @@ use futures::StreamExt;
 use mongodb::bson::{doc, Bson};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct CountRenewedData {
     from: i64,
     count: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    from_display: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count_display: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct CountRenewedQuery {
     begin: i64,
     end: i64,
     segments: i64,
+    locale: Option<String>,
 }
 
 #[route(get, "/stats/count_renewed", crate::endpoints::stats::count_renewed)]
 pub async fn handler(
     State(state): State<Arc<AppState>>,
+    request_headers: HeaderMap,
     Query(query): Query<CountRenewedQuery>,
 ) -> impl IntoResponse {
+    let locale = get_locale(query.locale.as_deref(), &request_headers);
     let begin_time = query.begin;
     let end_time = query.end;
     let delta_time = ((end_time as f64 - begin_time as f64) / query.segments as f64).round() as i64;
@@ -36,6 +49,32 @@ pub async fn handler(
     if delta_time > 3600 {
         let mut headers = HeaderMap::new();
         headers.insert("Cache-Control", HeaderValue::from_static("max-age=60"));
+        headers.insert("Vary", HeaderValue::from_static("Accept-Language"));
+
+        if let Some(analytics) = &state.analytics {
+            return match analytics
+                .count_segments("renewals", "timestamp", begin_time, end_time, delta_time)
+                .await
+            {
+                Ok(segments) => {
+                    let result: Vec<CountRenewedData> = segments
+                        .into_iter()
+                        .map(|(from, count)| CountRenewedData {
+                            from,
+                            count: count as i32,
+                            from_display: locale.and_then(|locale| locale.format_date(from)),
+                            count_display: locale.map(|locale| locale.format_number(count)),
+                        })
+                        .collect();
+                    (StatusCode::OK, headers, Json(result)).into_response()
+                }
+                Err(e) => ApiError::new(
+                    ErrorCode::DatabaseError,
+                    format!("Error while fetching from analytics: {}", e),
+                )
+                .into_response(),
+            };
+        }
 
         let domain_collection = state
             .starknetid_db
@@ -99,7 +138,12 @@ pub async fn handler(
                 let from: i64 = doc.get_i64("from").unwrap();
                 let count = doc.get_i32("count").unwrap();
 
-                CountRenewedData { from, count }
+                CountRenewedData {
+                    from,
+                    count,
+                    from_display: locale.and_then(|locale| locale.format_date(from)),
+                    count_display: locale.map(|locale| locale.format_number(count as i64)),
+                }
             })
             .collect::<Vec<_>>()
             .await;
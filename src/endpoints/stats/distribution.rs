@@ -0,0 +1,24 @@
+use crate::models::AppState;
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use std::sync::Arc;
+
+#[route(get, "/stats/distribution", crate::endpoints::stats::distribution)]
+pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match state.distribution.report() {
+        Some(report) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=600"));
+            (StatusCode::OK, headers, Json(report)).into_response()
+        }
+        None => ApiError::new(
+            ErrorCode::Unavailable,
+            "Distribution stats are being computed",
+        )
+        .into_response(),
+    }
+}
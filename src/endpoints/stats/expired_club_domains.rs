@@ -1,4 +1,7 @@
-use crate::{models::AppState, utils::get_error};
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+};
 use axum::{
     extract::State,
     http::{HeaderMap, HeaderValue, StatusCode},
@@ -105,10 +108,14 @@ pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
                 }
             }
             if output.is_empty() {
-                return get_error("No documents found".to_string());
+                return ApiError::new(ErrorCode::NotFound, "No documents found").into_response();
             }
             (StatusCode::OK, headers, Json(output)).into_response()
         }
-        Err(e) => get_error(format!("Error while fetching from database: {:?}", e)),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {:?}", e),
+        )
+        .into_response(),
     }
 }
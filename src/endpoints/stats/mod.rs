@@ -4,4 +4,6 @@ pub mod count_created;
 pub mod count_domains;
 pub mod count_ids;
 pub mod count_renewed;
+pub mod distribution;
 pub mod expired_club_domains;
+pub mod retention;
@@ -0,0 +1,70 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    retention::{Cohort, COHORTS_COLLECTION},
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use futures::TryStreamExt;
+use mongodb::{bson::doc, options::FindOptions};
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct RetentionQuery {
+    // first and last cohorts returned, e.g. 2024-01
+    from: Option<String>,
+    to: Option<String>,
+}
+
+#[route(get, "/stats/retention", crate::endpoints::stats::retention)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RetentionQuery>,
+) -> impl IntoResponse {
+    let mut month = doc! {};
+    if let Some(from) = query.from {
+        month.insert("$gte", from);
+    }
+    if let Some(to) = query.to {
+        month.insert("$lte", to);
+    }
+    let filter = if month.is_empty() {
+        doc! {}
+    } else {
+        doc! { "month": month }
+    };
+    let options = FindOptions::builder()
+        .sort(doc! { "month": 1 })
+        .projection(doc! { "_id": 0 })
+        .build();
+    let cohorts = state
+        .starknetid_db
+        .collection::<Cohort>(COHORTS_COLLECTION)
+        .find(filter, options)
+        .await;
+    match cohorts {
+        Ok(cursor) => match cursor.try_collect::<Vec<Cohort>>().await {
+            Ok(cohorts) => {
+                let mut headers = HeaderMap::new();
+                headers.insert("Cache-Control", HeaderValue::from_static("max-age=3600"));
+                (StatusCode::OK, headers, Json(cohorts)).into_response()
+            }
+            Err(e) => ApiError::new(
+                ErrorCode::InternalError,
+                format!("Error while parsing cohorts: {}", e),
+            )
+            .into_response(),
+        },
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{models::AppState, status::get_status};
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use std::sync::Arc;
+
+#[route(get, "/status", crate::endpoints::status)]
+pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    let status = get_status(&state).await;
+    // load balancers evict the replicas answering 503
+    let code = match status.healthy {
+        true => StatusCode::OK,
+        false => StatusCode::SERVICE_UNAVAILABLE,
+    };
+    (code, Json(status)).into_response()
+}
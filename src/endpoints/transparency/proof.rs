@@ -0,0 +1,87 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    transparency::{get_entry, TransparencyEntry},
+};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct ProofQuery {
+    index: u64,
+    // size of a tree head previously fetched, defaults to the current one
+    tree_size: Option<u64>,
+}
+
+#[derive(Serialize)]
+pub struct InclusionProof {
+    index: u64,
+    tree_size: u64,
+    leaf_hash: String,
+    audit_path: Vec<String>,
+    root: String,
+    entry: TransparencyEntry,
+}
+
+#[route(get, "/transparency/proof", crate::endpoints::transparency::proof)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ProofQuery>,
+) -> impl IntoResponse {
+    let size = match state.transparency.sync(&state).await {
+        Ok(size) => size as u64,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while loading the log: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let tree_size = query.tree_size.unwrap_or(size);
+    if tree_size > size {
+        return ApiError::new(ErrorCode::UnknownTreeSize, "Unknown tree size").into_response();
+    }
+
+    let (leaf, path) = match state
+        .transparency
+        .proof(query.index as usize, tree_size as usize)
+    {
+        Some(proof) => proof,
+        None => {
+            return ApiError::new(ErrorCode::IndexNotInTree, "Index is not part of this tree")
+                .into_response()
+        }
+    };
+    let entry = match get_entry(&state, query.index).await {
+        Ok(Some(entry)) => entry,
+        Ok(None) => return ApiError::new(ErrorCode::NotFound, "Entry not found").into_response(),
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let root = state.transparency.root(tree_size as usize).unwrap();
+    (
+        StatusCode::OK,
+        Json(InclusionProof {
+            index: query.index,
+            tree_size,
+            leaf_hash: hex::encode(leaf),
+            audit_path: path.iter().map(hex::encode).collect(),
+            root: hex::encode(root),
+            entry,
+        }),
+    )
+        .into_response()
+}
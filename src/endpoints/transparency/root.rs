@@ -0,0 +1,37 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    transparency::get_tree_head,
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+
+#[route(get, "/transparency/root", crate::endpoints::transparency::root)]
+pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    // the public key of a zero key is known to everyone
+    if state.conf.transparency.private_key == FieldElement::ZERO {
+        return ApiError::new(
+            ErrorCode::FeatureDisabled,
+            "Transparency tree heads are not configured",
+        )
+        .into_response();
+    }
+    match get_tree_head(&state).await {
+        Ok(tree_head) => {
+            let mut headers = HeaderMap::new();
+            headers.insert("Cache-Control", HeaderValue::from_static("max-age=10"));
+            (StatusCode::OK, headers, Json(tree_head)).into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::InternalError,
+            format!("Error while building the tree head: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1 @@
+pub mod naming_actions;
@@ -0,0 +1,42 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    naming_actions::get_naming_actions,
+    utils::get_error,
+};
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde_json::json;
+use starknet::core::types::FieldElement;
+use std::sync::Arc;
+
+#[route(get, "/tx/:hash/naming_actions", crate::endpoints::tx::naming_actions)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path(hash): Path<String>,
+) -> impl IntoResponse {
+    let hash = match FieldElement::from_hex_be(&hash) {
+        Ok(hash) => hash,
+        Err(_) => return get_error("Invalid transaction hash".to_string()),
+    };
+    match get_naming_actions(&state, &hash).await {
+        Ok((block, actions)) => (
+            StatusCode::OK,
+            Json(json!({
+                "transaction_hash": hash.to_string(),
+                "block_number": block,
+                "actions": actions,
+            })),
+        )
+            .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::UpstreamError,
+            format!("Unable to fetch the transaction: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,163 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    rpc_queue::Priority,
+    simulation::{simulate, Call, SimulationFailure},
+    types::starknet_addr::StarknetAddr,
+    utils::{get_error, normalize_domain},
+};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, BlockTag, FieldElement, FunctionCall},
+    macros::selector,
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use starknet_id::encode;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+#[derive(Deserialize, IntoParams)]
+pub struct RegisterQuery {
+    domain: String,
+    #[param(value_type = String)]
+    address: StarknetAddr,
+    days: u16,
+    // overrides the tx_builder.simulate setting
+    simulate: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct RegisterTx {
+    calls: Vec<Call>,
+    simulated: bool,
+    // set when the simulation reverted
+    failure: Option<SimulationFailure>,
+}
+
+#[route(get, "/tx_builder/register", crate::endpoints::tx_builder::register)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<RegisterQuery>,
+) -> impl IntoResponse {
+    let domain = normalize_domain(&query.domain);
+    let label = domain.strip_suffix(".stark").unwrap_or(&domain);
+    if label.is_empty() || label.contains('.') {
+        return get_error("Only root domains can be registered".to_string());
+    }
+    let encoded = match encode(label) {
+        Ok(encoded) => encoded,
+        Err(_) => return get_error("Invalid domain".to_string()),
+    };
+
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).unwrap(),
+    ));
+    let call = provider.call(
+        FunctionCall {
+            contract_address: state.conf.tx_builder.pricing,
+            entry_point_selector: selector!("compute_buy_price"),
+            calldata: vec![
+                FieldElement::from(label.chars().count()),
+                FieldElement::from(query.days),
+            ],
+        },
+        BlockId::Tag(BlockTag::Latest),
+    );
+    // erc20 address then the u256 price
+    let (erc20, price_low, price_high) = match state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_call", call)
+        .await
+    {
+        Ok(result) if result.len() >= 3 => (result[0], result[1], result[2]),
+        Ok(_) => {
+            return ApiError::new(ErrorCode::UpstreamError, "Unexpected pricing result")
+                .into_response()
+        }
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::UpstreamError,
+                format!("Unable to fetch the price: {}", e),
+            )
+            .into_response()
+        }
+    };
+
+    let contracts = &state.conf.contracts;
+    let id = FieldElement::from(rand::random::<u64>());
+    let calls = vec![
+        Call {
+            to: contracts.starknetid,
+            entrypoint: "mint",
+            selector: selector!("mint"),
+            calldata: vec![id],
+        },
+        Call {
+            to: erc20,
+            entrypoint: "approve",
+            selector: selector!("approve"),
+            calldata: vec![contracts.naming, price_low, price_high],
+        },
+        // resolver, sponsor, discount and metadata are left empty
+        Call {
+            to: contracts.naming,
+            entrypoint: "buy",
+            selector: selector!("buy"),
+            calldata: vec![
+                id,
+                encoded,
+                FieldElement::from(query.days),
+                FieldElement::ZERO,
+                FieldElement::ZERO,
+                FieldElement::ZERO,
+                FieldElement::ZERO,
+            ],
+        },
+    ];
+
+    if !query.simulate.unwrap_or(state.conf.tx_builder.simulate) {
+        return (
+            StatusCode::OK,
+            Json(RegisterTx {
+                calls,
+                simulated: false,
+                failure: None,
+            }),
+        )
+            .into_response();
+    }
+    let simulation = simulate(&state, &query.address, &calls).await;
+    match simulation {
+        Ok(failure) => (
+            StatusCode::OK,
+            Json(RegisterTx {
+                calls,
+                simulated: true,
+                failure,
+            }),
+        )
+            .into_response(),
+        // the calldata is still valid when the node can't simulate it
+        Err(e) => {
+            state
+                .logger
+                .warning(format!("tx_builder: unable to simulate: {}", e));
+            (
+                StatusCode::OK,
+                Json(RegisterTx {
+                    calls,
+                    simulated: false,
+                    failure: None,
+                }),
+            )
+                .into_response()
+        }
+    }
+}
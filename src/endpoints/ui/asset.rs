@@ -0,0 +1,32 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    ui::{get_asset, get_content_type},
+};
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use std::sync::Arc;
+
+#[route(get, "/ui/*path", crate::endpoints::ui::asset)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<String>,
+) -> impl IntoResponse {
+    let path = path.trim_start_matches('/');
+    match get_asset(path).filter(|_| state.conf.ui.enabled) {
+        Some(contents) => (
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, get_content_type(path)),
+                (header::CACHE_CONTROL, "max-age=3600"),
+            ],
+            contents,
+        )
+            .into_response(),
+        None => ApiError::new(ErrorCode::NotFound, "Asset not found").into_response(),
+    }
+}
@@ -0,0 +1,32 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    ui::{get_asset, render_index, INDEX},
+};
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{Html, IntoResponse},
+};
+use axum_auto_routes::route;
+use std::sync::Arc;
+
+#[route(get, "/ui", crate::endpoints::ui::index)]
+pub async fn handler(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    match get_asset(INDEX).filter(|_| state.conf.ui.enabled) {
+        Some(html) => (
+            StatusCode::OK,
+            [(header::CACHE_CONTROL, "no-cache")],
+            Html(render_index(
+                &String::from_utf8_lossy(html),
+                &state.conf.ui.title,
+            )),
+        )
+            .into_response(),
+        None => ApiError::new(
+            ErrorCode::FeatureDisabled,
+            "The ui is not embedded in this build",
+        )
+        .into_response(),
+    }
+}
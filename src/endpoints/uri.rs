@@ -1,4 +1,10 @@
 use crate::{
+    badges::BadgeSubject,
+    contract_claims::is_contract_verified,
+    descriptions::{get_description, get_id_owner},
+    errors::{ApiError, ErrorCode},
+    fanout::with_timeout,
+    freshness::get_social_freshness,
     models::AppState,
     utils::{fetch_img_url, to_hex, to_u256},
 };
@@ -9,11 +15,12 @@ use axum::{
 };
 use axum_auto_routes::route;
 use chrono::DateTime;
-use futures::StreamExt;
-use mongodb::bson::doc;
+use futures::stream::{self, StreamExt};
+use mongodb::bson::{doc, Document};
 use serde::{Deserialize, Serialize};
 use starknet::core::types::FieldElement;
 use std::{collections::HashMap, sync::Arc};
+use utoipa::IntoParams;
 
 #[derive(Serialize)]
 pub struct TokenURI {
@@ -30,8 +37,9 @@ pub struct Attribute {
     value: Vec<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
 pub struct TokenIdQuery {
+    #[param(value_type = String)]
     id: FieldElement,
 }
 
@@ -43,10 +51,13 @@ pub struct VerifierData {
     pub extended_data: Option<Vec<String>>,
 }
 
-const NFT_PP_CONTRACT: &'static str =
+pub const NFT_PP_CONTRACT: &'static str =
     "0x00000000000000000000000000000000006e66745f70705f636f6e7472616374";
-const NFT_PP_ID: &'static str =
+pub const NFT_PP_ID: &'static str =
     "0x00000000000000000000000000000000000000000000006e66745f70705f6964";
+const DEFAULT_DESCRIPTION: &str = "This token represents an identity on StarkNet.";
+// lookups of the profile pictures running at once
+const MAX_PP_FETCHES: usize = 16;
 
 #[route(get, "/uri", crate::endpoints::uri)]
 pub async fn handler(
@@ -56,20 +67,142 @@ pub async fn handler(
     let domains = state
         .starknetid_db
         .collection::<mongodb::bson::Document>("domains");
-    let id_verifier_data = state
-        .starknetid_db
-        .collection::<mongodb::bson::Document>("id_verifier_data");
-
-    // Query the domains collection
     let domain_filter = doc! {
         "id": to_hex(&query.id),
         "_cursor.to": null
     };
-    let domain_data = domains.find_one(domain_filter, None).await.unwrap();
 
-    // Query the id_verifier_data collection
+    // only the domain is needed, the profile picture is left out when it fails or runs late
+    let (domain_data, img_url) = tokio::join!(
+        with_timeout(&state, "domain", domains.find_one(domain_filter, None)),
+        with_timeout(&state, "pp_url", get_pp_url(&state, &query.id)),
+    );
+    let domain_data = match domain_data {
+        Some(Ok(domain_data)) => domain_data,
+        _ => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                "Error while fetching from database",
+            )
+            .into_response()
+        }
+    };
+    let token_uri = get_token_uri(&state, &query.id, domain_data, img_url.flatten()).await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+    (StatusCode::OK, headers, Json(token_uri)).into_response()
+}
+
+/// Metadata of an identity nft from its domain and profile picture, the description, badges
+/// and freshness are left out when they fail or run late
+pub async fn get_token_uri(
+    state: &AppState,
+    id: &FieldElement,
+    domain_data: Option<Document>,
+    img_url: Option<String>,
+) -> TokenURI {
+    let (description, social_freshness) = tokio::join!(
+        with_timeout(state, "description", get_uri_description(state, id)),
+        with_timeout(state, "social_freshness", get_social_freshness(state, id)),
+    );
+    let description = description
+        .flatten()
+        .unwrap_or_else(|| DEFAULT_DESCRIPTION.to_string());
+    let social_freshness = social_freshness.unwrap_or_default();
+
+    match domain_data {
+        Some(doc) => {
+            let domain = doc.get_str("domain").unwrap_or_default().to_owned();
+            let expiry = doc.get_i64("expiry").unwrap_or_default();
+            let badges = with_timeout(state, "badges", get_badge_subject(state, id, &doc))
+                .await
+                .map(|subject| state.badges.compute(&subject))
+                .unwrap_or_default();
+
+            TokenURI {
+                name: domain.clone(),
+                description,
+                image: get_image(state, id, img_url),
+                expiry: Some(expiry),
+                attributes:
+                    Some(
+                        vec![
+                            Attribute {
+                                trait_type: "Subdomain".to_string(),
+                                value: vec![
+                                    if domain.contains(".") { "yes" } else { "no" }.to_string()
+                                ],
+                            },
+                            Attribute {
+                                trait_type: "Domain expiry".to_string(),
+                                value: vec![DateTime::from_timestamp(expiry.into(), 0)
+                                    .map(|dt| dt.format("%b %d, %Y").to_string())
+                                    .unwrap_or_else(|| "Invalid date".into())],
+                            },
+                            Attribute {
+                                trait_type: "Domain expiry timestamp".to_string(),
+                                value: vec![expiry.to_string()],
+                            },
+                            Attribute {
+                                trait_type: "Badges".to_string(),
+                                value: badges.into_iter().map(|badge| badge.name).collect(),
+                            },
+                        ]
+                        .into_iter()
+                        .chain(social_freshness.iter().map(|freshness| Attribute {
+                            trait_type: format!("{} verified at", freshness.field),
+                            value: vec![freshness.verified_at.to_string()],
+                        }))
+                        .chain(std::iter::once(Attribute {
+                            trait_type: "Stale verifications".to_string(),
+                            value: social_freshness
+                                .iter()
+                                .filter(|freshness| freshness.is_stale)
+                                .map(|freshness| freshness.field.to_string())
+                                .collect(),
+                        }))
+                        .collect(),
+                    ),
+            }
+        }
+        None => TokenURI {
+            name: format!("Starknet ID: {}", id),
+            description,
+            image: get_image(state, id, None),
+            expiry: None,
+            attributes: None,
+        },
+    }
+}
+
+// the rendered cards show the profile picture, else it is the image and identicons the fallback
+fn get_image(state: &AppState, id: &FieldElement, img_url: Option<String>) -> String {
+    let cards = &state.conf.cards;
+    match img_url {
+        _ if cards.enabled => format!(
+            "{}/identity_card/{}.png",
+            cards.base_url.trim_end_matches('/'),
+            id
+        ),
+        Some(url) => url,
+        None => format!("https://identicon.starknet.id/{}", id),
+    }
+}
+
+/// Image of the nft set as profile picture of the identity
+pub async fn get_pp_url(state: &AppState, id: &FieldElement) -> Option<String> {
+    get_pp_urls(state, &[*id]).await.remove(&to_hex(id))
+}
+
+/// Images of the nfts set as profile pictures of the identities, by hex id. The verifier data
+/// is read in one lookup
+pub async fn get_pp_urls(state: &AppState, ids: &[FieldElement]) -> HashMap<String, String> {
+    let id_verifier_data = state
+        .starknetid_db
+        .collection::<mongodb::bson::Document>("id_verifier_data");
     let verifier_filter = doc! {
-        "id": to_hex(&query.id),
+        "id": { "$in": ids.iter().map(to_hex).collect::<Vec<String>>() },
         "$or": [
             { "_cursor.to": null },
             { "_cursor.to": { "$exists": false } }
@@ -82,11 +215,15 @@ pub async fn handler(
             ]
         }
     };
-    let mut verifier_data_by_field: HashMap<String, VerifierData> = HashMap::new();
+    let mut verifier_data_by_id: HashMap<String, HashMap<String, VerifierData>> = HashMap::new();
     if let Ok(mut cursor) = id_verifier_data.find(verifier_filter, None).await {
         while let Some(result) = cursor.next().await {
             if let Ok(doc) = result {
-                if let (Ok(verifier), Ok(field)) = (doc.get_str("verifier"), doc.get_str("field")) {
+                if let (Ok(id), Ok(verifier), Ok(field)) = (
+                    doc.get_str("id"),
+                    doc.get_str("verifier"),
+                    doc.get_str("field"),
+                ) {
                     let data = doc.get_str("data").ok().map(String::from);
 
                     let extended_data = doc
@@ -100,82 +237,107 @@ pub async fn handler(
                         })
                         .filter(|v: &Vec<String>| !v.is_empty());
 
-                    verifier_data_by_field.insert(
-                        field.to_string(),
-                        VerifierData {
-                            verifier: verifier.to_string(),
-                            field: field.to_string(),
-                            data,
-                            extended_data,
-                        },
-                    );
+                    verifier_data_by_id
+                        .entry(id.to_string())
+                        .or_default()
+                        .insert(
+                            field.to_string(),
+                            VerifierData {
+                                verifier: verifier.to_string(),
+                                field: field.to_string(),
+                                data,
+                                extended_data,
+                            },
+                        );
                 }
             }
         }
     }
 
-    let img_url = match (
-        verifier_data_by_field.get(NFT_PP_CONTRACT),
-        verifier_data_by_field.get(NFT_PP_ID),
-    ) {
-        (Option::Some(data_contract), Option::Some(data_id)) => {
-            let id_felts = data_id.to_owned().extended_data.as_ref().unwrap();
-            let id = to_u256(id_felts.get(0).unwrap(), id_felts.get(1).unwrap());
-            fetch_img_url(
+    let nfts = verifier_data_by_id
+        .into_iter()
+        .filter_map(|(id, verifier_data_by_field)| {
+            match (
+                verifier_data_by_field.get(NFT_PP_CONTRACT),
+                verifier_data_by_field.get(NFT_PP_ID),
+            ) {
+                (Option::Some(data_contract), Option::Some(data_id)) => {
+                    let token_id = data_id
+                        .extended_data
+                        .as_ref()
+                        .and_then(|id_felts| to_u256(id_felts.first()?, id_felts.get(1)?))?;
+                    Some((id, data_contract.data.to_owned()?, token_id.to_string()))
+                }
+                _ => None,
+            }
+        });
+    stream::iter(nfts)
+        .map(|(id, contract, token_id)| async move {
+            let url = fetch_img_url(
                 &state.conf.starkscan.api_url,
                 &state.conf.starkscan.api_key,
-                data_contract.data.to_owned().unwrap(),
-                id.to_string(),
+                contract,
+                token_id,
             )
-            .await
-        }
-        _ => None,
-    };
+            .await;
+            url.map(|url| (id, url))
+        })
+        .buffer_unordered(MAX_PP_FETCHES)
+        .filter_map(|url| async move { url })
+        .collect()
+        .await
+}
 
-    let mut headers = HeaderMap::new();
-    headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+async fn get_uri_description(state: &AppState, id: &FieldElement) -> Option<String> {
+    match get_id_owner(state.storage.as_ref(), id).await {
+        Ok(Some(owner)) => get_description(state.storage.as_ref(), id, &owner).await,
+        _ => None,
+    }
+}
 
-    match domain_data {
-        Some(doc) => {
-            let domain = doc.get_str("domain").unwrap_or_default().to_owned();
-            let expiry = doc.get_i64("expiry").unwrap_or_default();
+async fn get_badge_subject(state: &AppState, id: &FieldElement, domain: &Document) -> BadgeSubject {
+    let id_verifier_data = state
+        .starknetid_db
+        .collection::<Document>("id_verifier_data");
+    let verifiers: Vec<String> = state.conf.contracts.verifiers.iter().map(to_hex).collect();
+    let filter = doc! {
+        "id": to_hex(id),
+        "$or": [
+            { "_cursor.to": null },
+            { "_cursor.to": { "$exists": false } }
+        ],
+        "verifier": { "$in": verifiers },
+        "data": { "$ne": null }
+    };
+    let (verified_fields, contract_verified) = tokio::join!(
+        async {
+            let mut verified_fields = Vec::new();
+            if let Ok(mut cursor) = id_verifier_data.find(filter, None).await {
+                while let Some(Ok(doc)) = cursor.next().await {
+                    if let Some(field) = doc
+                        .get_str("field")
+                        .ok()
+                        .and_then(|field| FieldElement::from_hex_be(field).ok())
+                    {
+                        verified_fields.push(field);
+                    }
+                }
+            }
+            verified_fields
+        },
+        async {
+            match domain.get_str("domain") {
+                Ok(domain) => is_contract_verified(state, domain).await,
+                Err(_) => false,
+            }
+        },
+    );
 
-            let token_uri = TokenURI {
-                name: domain.clone(),
-                description: "This token represents an identity on StarkNet.".to_string(),
-                image: match img_url {
-                    Some(url) => url,
-                    None => format!("https://identicon.starknet.id/{}", &query.id),
-                },
-                expiry: Some(expiry),
-                attributes: Some(vec![
-                    Attribute {
-                        trait_type: "Subdomain".to_string(),
-                        value: vec![if domain.contains(".") { "yes" } else { "no" }.to_string()],
-                    },
-                    Attribute {
-                        trait_type: "Domain expiry".to_string(),
-                        value: vec![DateTime::from_timestamp(expiry.into(), 0)
-                            .map(|dt| dt.format("%b %d, %Y").to_string())
-                            .unwrap_or_else(|| "Invalid date".into())],
-                    },
-                    Attribute {
-                        trait_type: "Domain expiry timestamp".to_string(),
-                        value: vec![expiry.to_string()],
-                    },
-                ]),
-            };
-            (StatusCode::OK, headers, Json(token_uri)).into_response()
-        }
-        None => {
-            let token_uri = TokenURI {
-                name: format!("Starknet ID: {}", &query.id),
-                description: "This token represents an identity on StarkNet.".to_string(),
-                image: format!("https://identicon.starknet.id/{}", &query.id),
-                expiry: None,
-                attributes: None,
-            };
-            (StatusCode::OK, headers, Json(token_uri)).into_response()
-        }
+    BadgeSubject {
+        domain: domain.get_str("domain").ok().map(String::from),
+        creation_date: domain.get_i64("creation_date").ok().map(|date| date as u64),
+        expiry: domain.get_i64("expiry").ok().map(|date| date as u64),
+        verified_fields,
+        contract_verified,
     }
 }
@@ -0,0 +1,107 @@
+use crate::{
+    endpoints::uri::{get_pp_urls, get_token_uri},
+    errors::{ApiError, ErrorCode},
+    fanout::with_timeout,
+    models::AppState,
+    utils::{get_error, to_hex},
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use mongodb::bson::{doc, Document};
+use serde::Deserialize;
+use starknet::core::types::FieldElement;
+use std::{collections::HashMap, sync::Arc};
+use utoipa::IntoParams;
+
+const MAX_IDS: usize = 100;
+// metadata objects completed at once
+const MAX_TOKEN_URIS: usize = 16;
+
+#[derive(Deserialize, IntoParams)]
+pub struct TokenIdsQuery {
+    // comma separated, e.g. 1,2,3
+    ids: String,
+}
+
+/// Ids of the query, in their order and without duplicates
+pub fn parse_ids(ids: &str) -> Result<Vec<FieldElement>, String> {
+    let mut parsed: Vec<FieldElement> = vec![];
+    for id in ids.split(',').map(str::trim).filter(|id| !id.is_empty()) {
+        let id = match id.starts_with("0x") {
+            true => FieldElement::from_hex_be(id),
+            false => FieldElement::from_dec_str(id),
+        }
+        .map_err(|_| format!("Invalid id: {}", id))?;
+        if !parsed.contains(&id) {
+            parsed.push(id);
+        }
+    }
+    match parsed.len() {
+        0 => Err("No id to fetch".to_string()),
+        len if len > MAX_IDS => Err(format!("At most {} ids can be fetched", MAX_IDS)),
+        _ => Ok(parsed),
+    }
+}
+
+#[route(get, "/uris", crate::endpoints::uris)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<TokenIdsQuery>,
+) -> impl IntoResponse {
+    let ids = match parse_ids(&query.ids) {
+        Ok(ids) => ids,
+        Err(e) => return get_error(e),
+    };
+    let domains = state.starknetid_db.collection::<Document>("domains");
+    let domain_filter = doc! {
+        "id": { "$in": ids.iter().map(to_hex).collect::<Vec<String>>() },
+        "_cursor.to": null
+    };
+
+    let (domain_data, img_urls) = tokio::join!(
+        with_timeout(&state, "domain", async {
+            domains
+                .find(domain_filter, None)
+                .await?
+                .try_collect::<Vec<Document>>()
+                .await
+        }),
+        with_timeout(&state, "pp_url", get_pp_urls(&state, &ids)),
+    );
+    let mut domain_data: HashMap<String, Document> = match domain_data {
+        Some(Ok(docs)) => docs
+            .into_iter()
+            .filter_map(|doc| Some((doc.get_str("id").ok()?.to_string(), doc)))
+            .collect(),
+        _ => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                "Error while fetching from database",
+            )
+            .into_response()
+        }
+    };
+    let mut img_urls = img_urls.unwrap_or_default();
+
+    let lookups: Vec<_> = ids
+        .iter()
+        .map(|id| {
+            let hex = to_hex(id);
+            (id, domain_data.remove(&hex), img_urls.remove(&hex))
+        })
+        .collect();
+    let token_uris: Vec<_> = stream::iter(lookups)
+        .map(|(id, domain_data, img_url)| get_token_uri(&state, id, domain_data, img_url))
+        .buffered(MAX_TOKEN_URIS)
+        .collect()
+        .await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert("Cache-Control", HeaderValue::from_static("max-age=30"));
+    (StatusCode::OK, headers, Json(token_uris)).into_response()
+}
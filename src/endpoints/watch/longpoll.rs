@@ -0,0 +1,84 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    utils::deserialize_domain,
+    watch::get_domain_version,
+};
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tokio::time::{sleep, Duration, Instant};
+use utoipa::IntoParams;
+
+const MAX_TIMEOUT: u64 = 60;
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Serialize)]
+pub struct LongPollData {
+    domain: String,
+    changed: bool,
+    block: Option<i64>,
+}
+
+#[derive(Deserialize, IntoParams)]
+pub struct LongPollQuery {
+    #[serde(deserialize_with = "deserialize_domain")]
+    domain: String,
+    timeout: Option<u64>,
+    // last block seen by the client, returns right away if the domain changed since then
+    since: Option<i64>,
+}
+
+#[route(get, "/watch/longpoll", crate::endpoints::watch::longpoll)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<LongPollQuery>,
+) -> impl IntoResponse {
+    let timeout = Duration::from_secs(query.timeout.unwrap_or(30).clamp(1, MAX_TIMEOUT));
+    let deadline = Instant::now() + timeout;
+
+    let initial = match get_domain_version(&state, &query.domain).await {
+        Ok(version) => version,
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    };
+    let changed_since = match (query.since, initial.block()) {
+        (Some(since), Some(block)) => block > since,
+        _ => false,
+    };
+
+    let mut current = initial.clone();
+    while !changed_since && current == initial && Instant::now() < deadline {
+        sleep(POLL_INTERVAL.min(deadline.saturating_duration_since(Instant::now()))).await;
+        current = match get_domain_version(&state, &query.domain).await {
+            Ok(version) => version,
+            Err(e) => {
+                return ApiError::new(
+                    ErrorCode::DatabaseError,
+                    format!("Error while fetching from database: {}", e),
+                )
+                .into_response()
+            }
+        };
+    }
+
+    (
+        StatusCode::OK,
+        Json(LongPollData {
+            domain: query.domain,
+            changed: changed_since || current != initial,
+            block: current.block(),
+        }),
+    )
+        .into_response()
+}
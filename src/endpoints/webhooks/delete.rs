@@ -0,0 +1,42 @@
+use crate::{
+    auth::get_api_key,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    webhooks::delete_webhook,
+};
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct DeleteWebhookQuery {
+    id: String,
+}
+
+#[route(post, "/webhooks/delete", crate::endpoints::webhooks::delete)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(query): Json<DeleteWebhookQuery>,
+) -> impl IntoResponse {
+    let api_key = match get_api_key(&state, &headers).await {
+        Some(api_key) => api_key,
+        None => return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response(),
+    };
+    match delete_webhook(state.storage.as_ref(), &api_key.name, &query.id).await {
+        Ok(true) => (StatusCode::OK, Json(json!({ "deleted": query.id }))).into_response(),
+        Ok(false) => ApiError::new(ErrorCode::NotFound, "Webhook not found").into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while updating database: {}", e),
+        )
+        .into_response(),
+    }
+}
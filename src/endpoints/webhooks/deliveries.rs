@@ -0,0 +1,59 @@
+use crate::{
+    auth::get_api_key,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    webhooks::{delivery::get_deliveries, get_webhook},
+};
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+use utoipa::IntoParams;
+
+const MAX_DELIVERIES: i64 = 100;
+
+#[derive(Deserialize, IntoParams)]
+pub struct DeliveriesQuery {
+    id: String,
+}
+
+#[route(get, "/webhooks/deliveries", crate::endpoints::webhooks::deliveries)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<DeliveriesQuery>,
+) -> impl IntoResponse {
+    let api_key = match get_api_key(&state, &headers).await {
+        Some(api_key) => api_key,
+        None => return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response(),
+    };
+    let storage = state.storage.as_ref();
+    match get_webhook(storage, &query.id).await {
+        Ok(Some(webhook)) if webhook.owner == api_key.name => {}
+        Ok(_) => return ApiError::new(ErrorCode::NotFound, "Webhook not found").into_response(),
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    }
+    match get_deliveries(storage, &query.id, MAX_DELIVERIES).await {
+        Ok(deliveries) => (
+            StatusCode::OK,
+            Json(json!({ "id": query.id, "deliveries": deliveries })),
+        )
+            .into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
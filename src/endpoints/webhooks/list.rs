@@ -0,0 +1,33 @@
+use crate::{
+    auth::get_api_key,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    webhooks::{get_webhooks, WebhookInfo},
+};
+use axum::{
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    response::{IntoResponse, Json},
+};
+use axum_auto_routes::route;
+use serde_json::json;
+use std::sync::Arc;
+
+#[route(get, "/webhooks", crate::endpoints::webhooks::list)]
+pub async fn handler(State(state): State<Arc<AppState>>, headers: HeaderMap) -> impl IntoResponse {
+    let api_key = match get_api_key(&state, &headers).await {
+        Some(api_key) => api_key,
+        None => return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response(),
+    };
+    match get_webhooks(state.storage.as_ref(), Some(&api_key.name)).await {
+        Ok(webhooks) => {
+            let webhooks: Vec<WebhookInfo> = webhooks.into_iter().map(WebhookInfo::from).collect();
+            (StatusCode::OK, Json(json!({ "webhooks": webhooks }))).into_response()
+        }
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while fetching from database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,4 @@
+pub mod delete;
+pub mod deliveries;
+pub mod list;
+pub mod register;
@@ -0,0 +1,68 @@
+use crate::{
+    auth::get_api_key,
+    errors::{ApiError, ErrorCode},
+    models::AppState,
+    utils::get_error,
+    webhooks::{get_webhooks, insert_webhook, new_webhook, WebhookEvent},
+};
+use axum::{
+    extract::{Json, State},
+    http::{HeaderMap, StatusCode},
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use serde::Deserialize;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+#[derive(Deserialize, ToSchema)]
+pub struct RegisterWebhookQuery {
+    url: String,
+    #[schema(inline)]
+    events: Vec<WebhookEvent>,
+    // e.g. `domain.endsWith(".braavos.stark")`
+    filter: Option<String>,
+}
+
+#[route(post, "/webhooks/register", crate::endpoints::webhooks::register)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(query): Json<RegisterWebhookQuery>,
+) -> impl IntoResponse {
+    let api_key = match get_api_key(&state, &headers).await {
+        Some(api_key) => api_key,
+        None => return ApiError::new(ErrorCode::Unauthorized, "Invalid API key").into_response(),
+    };
+    let storage = state.storage.as_ref();
+    match get_webhooks(storage, Some(&api_key.name)).await {
+        Ok(webhooks) if webhooks.len() >= state.conf.webhooks.max_per_key => {
+            return get_error(format!(
+                "At most {} webhooks per API key",
+                state.conf.webhooks.max_per_key
+            ))
+        }
+        Ok(_) => {}
+        Err(e) => {
+            return ApiError::new(
+                ErrorCode::DatabaseError,
+                format!("Error while fetching from database: {}", e),
+            )
+            .into_response()
+        }
+    }
+    let now = chrono::Utc::now().timestamp();
+    let webhook = match new_webhook(&api_key.name, query.url, query.events, query.filter, now) {
+        Ok(webhook) => webhook,
+        Err(e) => return get_error(e),
+    };
+    // the secret is only shown here
+    match insert_webhook(storage, &webhook).await {
+        Ok(_) => (StatusCode::OK, Json(webhook)).into_response(),
+        Err(e) => ApiError::new(
+            ErrorCode::DatabaseError,
+            format!("Error while updating database: {}", e),
+        )
+        .into_response(),
+    }
+}
@@ -0,0 +1,127 @@
+//! Errors of the endpoints, answered as `{"error": {"code": ..., "message": ...}}` so the
+//! clients can tell a missing domain from a malformed query without matching the messages
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+use utoipa::ToSchema;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    InvalidInput,
+    InvalidAddress,
+    Unauthorized,
+    InvalidSignature,
+    SignatureExpired,
+    Forbidden,
+    NotFound,
+    DomainNotFound,
+    IdentityNotFound,
+    // the instance doesn't serve it, e.g. the exports without a bucket
+    FeatureDisabled,
+    // tree size of a proof the transparency log hasn't reached
+    UnknownTreeSize,
+    IndexNotInTree,
+    // the domain of a contract claim doesn't point to the contract
+    ContractNotResolved,
+    Conflict,
+    RateLimited,
+    DatabaseError,
+    UpstreamError,
+    // the price of the altcoin is outside of the configured bounds
+    QuoteOutOfRange,
+    Unavailable,
+    InternalError,
+}
+
+impl ErrorCode {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ErrorCode::InvalidInput | ErrorCode::InvalidAddress => StatusCode::BAD_REQUEST,
+            ErrorCode::Unauthorized | ErrorCode::InvalidSignature | ErrorCode::SignatureExpired => {
+                StatusCode::UNAUTHORIZED
+            }
+            ErrorCode::Forbidden | ErrorCode::ContractNotResolved => StatusCode::FORBIDDEN,
+            ErrorCode::NotFound
+            | ErrorCode::DomainNotFound
+            | ErrorCode::IdentityNotFound
+            | ErrorCode::FeatureDisabled
+            | ErrorCode::UnknownTreeSize
+            | ErrorCode::IndexNotInTree => StatusCode::NOT_FOUND,
+            ErrorCode::Conflict => StatusCode::CONFLICT,
+            ErrorCode::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            ErrorCode::DatabaseError | ErrorCode::InternalError => {
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            ErrorCode::UpstreamError => StatusCode::BAD_GATEWAY,
+            ErrorCode::Unavailable | ErrorCode::QuoteOutOfRange => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Code of an error only known by its status, e.g. a plain text rejection of axum
+    pub fn from_status(status: StatusCode) -> ErrorCode {
+        match status {
+            StatusCode::UNAUTHORIZED => ErrorCode::Unauthorized,
+            StatusCode::FORBIDDEN => ErrorCode::Forbidden,
+            StatusCode::NOT_FOUND => ErrorCode::NotFound,
+            StatusCode::CONFLICT => ErrorCode::Conflict,
+            StatusCode::TOO_MANY_REQUESTS => ErrorCode::RateLimited,
+            StatusCode::BAD_GATEWAY | StatusCode::GATEWAY_TIMEOUT => ErrorCode::UpstreamError,
+            StatusCode::SERVICE_UNAVAILABLE => ErrorCode::Unavailable,
+            status if status.is_server_error() => ErrorCode::InternalError,
+            _ => ErrorCode::InvalidInput,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ErrorDetails {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+/// Body of the client errors
+#[derive(Serialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct ErrorBody {
+    pub error: ErrorDetails,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        ApiError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = self.code.status();
+        if status.is_server_error() {
+            // answered by `standardize_failures`, which logs them with an incident id
+            let mut response = (status, self.message).into_response();
+            response.extensions_mut().insert(self.code);
+            return response;
+        }
+        (
+            status,
+            Json(ErrorBody {
+                error: ErrorDetails {
+                    code: self.code,
+                    message: self.message,
+                },
+            }),
+        )
+            .into_response()
+    }
+}
@@ -0,0 +1,290 @@
+use std::{collections::BTreeMap, sync::RwLock};
+
+use axum::{
+    body::Body,
+    http::{header::CONTENT_TYPE, Request},
+    Router,
+};
+use hyper::service::Service;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::time::{timeout, Duration};
+use utoipa::openapi::{
+    schema::{Schema, SchemaType},
+    RefOr, Required,
+};
+
+use crate::{
+    config::Examples,
+    openapi::{get_input, to_openapi_path, Input},
+    routes::{AuthScope, CachePolicy, Method, RateClass, RouteGroup, RouteSpec},
+};
+
+// nested objects of the bodies are sampled down to this depth
+const MAX_DEPTH: usize = 4;
+const REPLAY_TIMEOUT: Duration = Duration::from_secs(5);
+// larger responses, such as the openapi document, are summarized
+const MAX_BODY_LEN: usize = 16 * 1024;
+// replaying it would embed the examples in themselves
+const EXAMPLES_HANDLER: &str = "endpoints::docs::examples";
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExampleRequest {
+    pub method: Method,
+    // path and query string
+    pub url: String,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<Value>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExampleResponse {
+    pub status: u16,
+    pub body: Value,
+}
+
+/// Sample call of a route, with the response this instance served for it when it could be
+/// replayed safely
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Example {
+    pub handler: &'static str,
+    pub path: &'static str,
+    pub group: RouteGroup,
+    pub request: ExampleRequest,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response: Option<ExampleResponse>,
+}
+
+/// Examples rendered once the router is built, the requests alone are served until then
+#[derive(Default)]
+pub struct ExampleStore {
+    examples: RwLock<Option<Vec<Example>>>,
+}
+
+impl ExampleStore {
+    pub fn get(&self) -> Option<Vec<Example>> {
+        self.examples.read().unwrap().clone()
+    }
+
+    pub fn set(&self, examples: Vec<Example>) {
+        *self.examples.write().unwrap() = Some(examples);
+    }
+}
+
+// value of a parameter named after a fixture, e.g. `domain` or `addr`
+fn get_fixture_value(name: &str, fixture: &Examples) -> Option<String> {
+    Some(match name {
+        "domain" | "domains" | "root" | "parent" => fixture.domain.clone(),
+        "addr" | "addrs" | "address" | "addresses" | "owner" | "target" => fixture.address.clone(),
+        "id" | "ids" | "token_id" => fixture.id.clone(),
+        // e.g. /identity_card/:file
+        "file" => format!("{}.svg", fixture.id),
+        _ => return None,
+    })
+}
+
+fn sample_scalar(name: &str, schema_type: &SchemaType, fixture: &Examples) -> Value {
+    match schema_type {
+        SchemaType::Integer => json!(1),
+        SchemaType::Number => json!(1.0),
+        SchemaType::Boolean => json!(true),
+        SchemaType::Array => json!([]),
+        SchemaType::Object => json!({}),
+        _ => json!(get_fixture_value(name, fixture).unwrap_or_else(|| name.to_string())),
+    }
+}
+
+/// Value of a parameter or body field: its example or first variant when documented, else a
+/// fixture value picked by name or a placeholder of its type
+pub fn sample(name: &str, schema: &RefOr<Schema>, fixture: &Examples, depth: usize) -> Value {
+    let schema = match schema {
+        RefOr::T(schema) => schema,
+        // the shared components are out of the handler inputs
+        RefOr::Ref(_) => return json!({}),
+    };
+    match schema {
+        Schema::Object(object) => {
+            if let Some(example) = &object.example {
+                return example.clone();
+            }
+            if let Some(value) = object
+                .enum_values
+                .as_ref()
+                .and_then(|values| values.first())
+            {
+                return value.clone();
+            }
+            if object.schema_type != SchemaType::Object || object.properties.is_empty() {
+                return sample_scalar(name, &object.schema_type, fixture);
+            }
+            if depth >= MAX_DEPTH {
+                return json!({});
+            }
+            let fields = object
+                .properties
+                .iter()
+                .map(|(field, schema)| (field.clone(), sample(field, schema, fixture, depth + 1)));
+            Value::Object(fields.collect())
+        }
+        Schema::Array(array) => json!([sample(name, &array.items, fixture, depth + 1)]),
+        Schema::OneOf(one_of) => one_of
+            .items
+            .first()
+            .map_or(Value::Null, |item| sample(name, item, fixture, depth + 1)),
+        Schema::AllOf(all_of) => all_of
+            .items
+            .first()
+            .map_or(Value::Null, |item| sample(name, item, fixture, depth + 1)),
+        _ => Value::Null,
+    }
+}
+
+fn to_query_value(value: &Value) -> String {
+    match value {
+        Value::String(value) => value.clone(),
+        // lists are sent comma separated
+        Value::Array(values) => values
+            .iter()
+            .map(to_query_value)
+            .collect::<Vec<String>>()
+            .join(","),
+        value => value.to_string(),
+    }
+}
+
+/// Request of the route for the fixture identity, with its required parameters only
+pub fn get_request(spec: &RouteSpec, fixture: &Examples) -> ExampleRequest {
+    let (_, path_params) = to_openapi_path(spec.path);
+    let mut url = spec.path.to_string();
+    for name in &path_params {
+        let value = get_fixture_value(name, fixture).unwrap_or_else(|| name.to_string());
+        url = url
+            .replace(&format!(":{}", name), &value)
+            .replace(&format!("*{}", name), &value);
+    }
+
+    let mut body = None;
+    match get_input(spec.handler) {
+        Some(Input::Query(params)) => {
+            let pairs: Vec<(String, String)> = params
+                .iter()
+                .filter(|param| matches!(param.required, Required::True))
+                .map(|param| {
+                    let value = param.schema.as_ref().map_or_else(
+                        || sample_scalar(&param.name, &SchemaType::String, fixture),
+                        |schema| sample(&param.name, schema, fixture, 0),
+                    );
+                    (param.name.clone(), to_query_value(&value))
+                })
+                .collect();
+            if !pairs.is_empty() {
+                let query = serde_urlencoded::to_string(&pairs).unwrap_or_default();
+                url = format!("{}?{}", url, query);
+            }
+        }
+        Some(Input::Body(schema)) => body = Some(sample("body", &schema, fixture, 0)),
+        None => {}
+    }
+
+    let mut headers = BTreeMap::new();
+    match spec.auth {
+        AuthScope::Partner => {
+            headers.insert("x-api-key".to_string(), "<api key>".to_string());
+        }
+        AuthScope::Admin => {
+            headers.insert("x-admin-key".to_string(), "<admin key>".to_string());
+        }
+        AuthScope::Token => {
+            headers.insert("authorization".to_string(), "Bearer <token>".to_string());
+        }
+        AuthScope::Public | AuthScope::Signature => {}
+    }
+    if body.is_some() {
+        headers.insert("content-type".to_string(), "application/json".to_string());
+    }
+    ExampleRequest {
+        method: spec.method,
+        url,
+        headers,
+        body,
+    }
+}
+
+/// Public reads are replayed, the writes, authenticated routes and uncached heavy ones such
+/// as streams aren't
+pub fn is_replayed(spec: &RouteSpec) -> bool {
+    spec.method == Method::Get
+        && spec.auth == AuthScope::Public
+        && spec.handler != EXAMPLES_HANDLER
+        && (spec.rate == RateClass::Light || matches!(spec.cache, CachePolicy::MaxAge(_)))
+}
+
+fn new_example(spec: &RouteSpec, fixture: &Examples) -> Example {
+    Example {
+        handler: spec.handler,
+        path: spec.path,
+        group: spec.group,
+        request: get_request(spec, fixture),
+        response: None,
+    }
+}
+
+/// Requests of the routes, without their responses
+pub fn get_examples<'a>(
+    routes: impl IntoIterator<Item = &'a RouteSpec>,
+    fixture: &Examples,
+) -> Vec<Example> {
+    routes
+        .into_iter()
+        .map(|spec| new_example(spec, fixture))
+        .collect()
+}
+
+async fn replay(router: &Router, request: &ExampleRequest) -> Option<ExampleResponse> {
+    let req = Request::get(&request.url).body(Body::empty()).ok()?;
+    let response = timeout(REPLAY_TIMEOUT, router.clone().call(req))
+        .await
+        .ok()?
+        .ok()?;
+    let status = response.status().as_u16();
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
+    let bytes = timeout(REPLAY_TIMEOUT, hyper::body::to_bytes(response.into_body()))
+        .await
+        .ok()?
+        .ok()?;
+    let body = match serde_json::from_slice(&bytes) {
+        _ if bytes.len() > MAX_BODY_LEN => {
+            json!(format!("<{} bytes of {}>", bytes.len(), content_type))
+        }
+        Ok(body) => body,
+        Err(_) if content_type.starts_with("text/plain") => {
+            json!(String::from_utf8_lossy(&bytes))
+        }
+        Err(_) => json!(format!("<{} bytes of {}>", bytes.len(), content_type)),
+    };
+    Some(ExampleResponse { status, body })
+}
+
+/// Examples of the routes with the responses of the app to the replayed ones
+pub async fn render<'a>(
+    router: Router,
+    routes: impl IntoIterator<Item = &'a RouteSpec>,
+    fixture: &Examples,
+) -> Vec<Example> {
+    let mut examples = vec![];
+    for spec in routes {
+        let mut example = new_example(spec, fixture);
+        if is_replayed(spec) {
+            example.response = replay(&router, &example.request).await;
+        }
+        examples.push(example);
+    }
+    examples
+}
@@ -0,0 +1,134 @@
+use std::collections::{HashMap, HashSet};
+
+use anyhow::Result;
+use mongodb::bson::{doc, Document};
+use serde::Serialize;
+
+use crate::{
+    pagination::{get_sort, PageCursor, EXPIRY_ORDER},
+    storage::{FindSpec, Storage},
+};
+
+// collections of the auto renewal subscriptions, paid in eth or in altcoins
+const RENEWAL_COLLECTIONS: [&str; 2] = ["auto_renew_flows", "auto_renew_flows_altcoins"];
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ExpiringDomain {
+    pub domain: String,
+    pub id: String,
+    pub owner: Option<String>,
+    pub expiry: i64,
+    // enabled subscriptions of the current owner, an old owner's one doesn't renew
+    pub auto_renew: bool,
+    pub auto_renew_altcoin: bool,
+}
+
+pub fn get_expiring_filter(from: i64, to: i64, after: Option<&PageCursor>) -> Document {
+    let mut filter = doc! {
+        "expiry": { "$gte": from, "$lte": to },
+        "_cursor.to": null,
+    };
+    if let Some(after) = after {
+        filter.extend(after.after("expiry"));
+    }
+    filter
+}
+
+async fn get_subscribers(
+    storage: &dyn Storage,
+    collection: &str,
+    domains: &[String],
+) -> Result<HashSet<(String, String)>> {
+    Ok(storage
+        .find(
+            collection,
+            doc! { "domain": { "$in": domains }, "enabled": true, "_cursor.to": null },
+            FindSpec::default(),
+        )
+        .await?
+        .iter()
+        .filter_map(|doc| {
+            Some((
+                doc.get_str("domain").ok()?.to_string(),
+                doc.get_str("renewer_address").ok()?.to_string(),
+            ))
+        })
+        .collect())
+}
+
+/// Domains expiring between `from` and `to`, soonest first, with their owner and whether
+/// the owner subscribed to their auto renewal
+pub async fn get_expiring_domains(
+    storage: &dyn Storage,
+    from: i64,
+    to: i64,
+    after: Option<&PageCursor>,
+    limit: i64,
+) -> Result<(Vec<ExpiringDomain>, Option<PageCursor>)> {
+    let docs = storage
+        .find(
+            "domains",
+            get_expiring_filter(from, to, after),
+            FindSpec {
+                sort: Some(get_sort(EXPIRY_ORDER)),
+                skip: 0,
+                limit: Some(limit),
+            },
+        )
+        .await?;
+    // a full page means there might be more domains after it
+    let next_cursor = match docs.last() {
+        Some(last) if docs.len() as i64 == limit => PageCursor::from_document(last, "expiry"),
+        _ => None,
+    };
+
+    let ids: Vec<String> = docs
+        .iter()
+        .filter_map(|doc| doc.get_str("id").ok().map(String::from))
+        .collect();
+    let owners: HashMap<String, String> = storage
+        .find(
+            "id_owners",
+            doc! { "id": { "$in": ids }, "_cursor.to": null },
+            FindSpec::default(),
+        )
+        .await?
+        .iter()
+        .filter_map(|doc| {
+            Some((
+                doc.get_str("id").ok()?.to_string(),
+                doc.get_str("owner").ok()?.to_string(),
+            ))
+        })
+        .collect();
+    let names: Vec<String> = docs
+        .iter()
+        .filter_map(|doc| doc.get_str("domain").ok().map(String::from))
+        .collect();
+    let (eth, altcoin) = tokio::try_join!(
+        get_subscribers(storage, RENEWAL_COLLECTIONS[0], &names),
+        get_subscribers(storage, RENEWAL_COLLECTIONS[1], &names),
+    )?;
+
+    let domains = docs
+        .iter()
+        .filter_map(|doc| {
+            let domain = doc.get_str("domain").ok()?.to_string();
+            let id = doc.get_str("id").unwrap_or_default().to_string();
+            let owner = owners.get(&id).cloned();
+            let subscribed = |subscribers: &HashSet<(String, String)>| match &owner {
+                Some(owner) => subscribers.contains(&(domain.clone(), owner.clone())),
+                None => false,
+            };
+            Some(ExpiringDomain {
+                auto_renew: subscribed(&eth),
+                auto_renew_altcoin: subscribed(&altcoin),
+                domain,
+                id,
+                owner,
+                expiry: doc.get_i64("expiry").ok()?,
+            })
+        })
+        .collect();
+    Ok((domains, next_cursor))
+}
@@ -0,0 +1,589 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use futures::{stream, Stream, StreamExt, TryStreamExt};
+use mongodb::{
+    bson::{doc, to_bson, Bson, Document},
+    options::{FindOneOptions, FindOptions},
+};
+use reqwest::Url;
+use s3::{bucket::Bucket, creds::Credentials, region::Region};
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::types::{BlockId, MaybePendingBlockWithTxHashes},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use tokio::{
+    fs::{self, File},
+    io::AsyncWriteExt,
+};
+
+use crate::{
+    config::Exports,
+    models::AppState,
+    rpc_queue::Priority,
+    storage::{FindSpec, Storage},
+};
+
+pub const JOBS_COLLECTION: &str = "export_jobs";
+// domains read per query by the streamed dumps
+const DUMP_PAGE_SIZE: i64 = 1000;
+// fields compared between two snapshots of a domain
+const DOMAIN_FIELDS: [&str; 6] = [
+    "domain",
+    "id",
+    "expiry",
+    "creation_date",
+    "legacy_address",
+    "rev_address",
+];
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportStatus {
+    Pending,
+    Running,
+    Done,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "type")]
+pub enum ExportKind {
+    // every live domain
+    Domains,
+    // domains created, changed or expired between two snapshot dates (yyyy-mm-dd, utc)
+    DomainsDelta { from: String, to: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ExportJob {
+    pub job_id: String,
+    pub kind: ExportKind,
+    // name of the api key which requested the export
+    pub requested_by: String,
+    pub status: ExportStatus,
+    pub created_at: i64,
+    pub finished_at: Option<i64>,
+    pub records: u64,
+    pub object_key: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Bucket receiving the export artifacts
+pub struct ExportStorage {
+    bucket: Bucket,
+    url_expiry_secs: u32,
+}
+
+impl ExportStorage {
+    pub fn new(conf: &Exports) -> Result<Option<Self>> {
+        if conf.bucket.is_empty() {
+            return Ok(None);
+        }
+        let region = Region::Custom {
+            region: conf.region.clone(),
+            endpoint: conf.endpoint.clone(),
+        };
+        let credentials = Credentials::new(
+            Some(&conf.access_key),
+            Some(&conf.secret_key),
+            None,
+            None,
+            None,
+        )?;
+        let bucket = Bucket::new(&conf.bucket, region, credentials)?.with_path_style();
+        Ok(Some(ExportStorage {
+            bucket,
+            url_expiry_secs: conf.url_expiry_secs,
+        }))
+    }
+
+    async fn upload(&self, key: &str, path: &Path) -> Result<()> {
+        let mut file = File::open(path).await?;
+        // sent as a multipart upload so the artifact never sits in memory
+        let status = self.bucket.put_object_stream(&mut file, key).await?;
+        if !(200..300).contains(&status) {
+            return Err(anyhow!("upload of {} failed with status {}", key, status));
+        }
+        Ok(())
+    }
+
+    pub async fn put_object(&self, key: &str, bytes: &[u8], content_type: &str) -> Result<()> {
+        let response = self
+            .bucket
+            .put_object_with_content_type(key, bytes, content_type)
+            .await?;
+        if !(200..300).contains(&response.status_code()) {
+            return Err(anyhow!(
+                "upload of {} failed with status {}",
+                key,
+                response.status_code()
+            ));
+        }
+        Ok(())
+    }
+
+    pub async fn get_object(&self, key: &str) -> Result<Vec<u8>> {
+        let response = self.bucket.get_object(key).await?;
+        if response.status_code() != 200 {
+            return Err(anyhow!("{} not found", key));
+        }
+        Ok(response.bytes().to_vec())
+    }
+
+    /// Expiring url giving read access to an artifact without credentials
+    pub fn get_download_url(&self, key: &str) -> Result<String> {
+        Ok(self.bucket.presign_get(key, self.url_expiry_secs, None)?)
+    }
+}
+
+impl ExportKind {
+    fn name(&self) -> &'static str {
+        match self {
+            ExportKind::Domains => "domains",
+            ExportKind::DomainsDelta { .. } => "domains_delta",
+        }
+    }
+}
+
+/// Unix timestamp of the start of a `yyyy-mm-dd` utc date
+pub fn parse_snapshot_date(date: &str) -> Result<i64> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| anyhow!("Invalid date {}, expected yyyy-mm-dd", date))?;
+    Ok(date.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp())
+}
+
+/// How a domain changed between two snapshots, `before` and `after` being its versions
+/// live at each of them
+pub fn get_change(
+    before: Option<&Document>,
+    after: Option<&Document>,
+    from: i64,
+    to: i64,
+) -> Option<&'static str> {
+    let expired_in_range = |doc: &Document| {
+        doc.get_i64("expiry")
+            .map_or(false, |expiry| expiry > from && expiry <= to)
+    };
+    match (before, after) {
+        (None, Some(_)) => Some("created"),
+        // burnt or deleted domains are reported as expired too
+        (Some(_), None) => Some("expired"),
+        (Some(_), Some(after)) if expired_in_range(after) => Some("expired"),
+        (Some(before), Some(after))
+            if DOMAIN_FIELDS
+                .iter()
+                .any(|field| before.get(field) != after.get(field)) =>
+        {
+            Some("changed")
+        }
+        _ => None,
+    }
+}
+
+fn is_live_at(doc: &Document, block: i64) -> bool {
+    let cursor = match doc.get_document("_cursor") {
+        Ok(cursor) => cursor,
+        Err(_) => return false,
+    };
+    let from = cursor.get_i64("from").unwrap_or(i64::MAX);
+    let to = match cursor.get("to") {
+        Some(Bson::Int64(to)) => Some(*to),
+        _ => None,
+    };
+    from <= block && to.map_or(true, |to| to > block)
+}
+
+async fn get_block_timestamp(
+    state: &AppState,
+    provider: &JsonRpcClient<HttpTransport>,
+    block: u64,
+) -> Result<u64> {
+    let call = provider.get_block_with_tx_hashes(BlockId::Number(block));
+    match state
+        .rpc_queue
+        .run(Priority::Background, "starknet_getBlockWithTxHashes", call)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch block {}: {}", block, e))?
+    {
+        MaybePendingBlockWithTxHashes::Block(block) => Ok(block.timestamp),
+        MaybePendingBlockWithTxHashes::PendingBlock(block) => Ok(block.timestamp),
+    }
+}
+
+/// Binary searches the last block produced before `timestamp`
+async fn get_block_at(state: &AppState, timestamp: i64) -> Result<i64> {
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).unwrap(),
+    ));
+    let latest = state
+        .rpc_queue
+        .run(
+            Priority::Background,
+            "starknet_blockNumber",
+            provider.block_number(),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to fetch latest block number: {}", e))?;
+    let (mut low, mut high) = (0, latest);
+    if get_block_timestamp(state, &provider, 0).await? as i64 >= timestamp {
+        return Ok(-1);
+    }
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if (get_block_timestamp(state, &provider, mid).await? as i64) < timestamp {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low as i64)
+}
+
+async fn write_line(file: &mut File, doc: &Document) -> Result<()> {
+    let mut line = serde_json::to_vec(doc)?;
+    line.push(b'\n');
+    file.write_all(&line).await?;
+    Ok(())
+}
+
+fn get_domain_projection() -> Document {
+    let mut projection = doc! { "_id": 0 };
+    for field in DOMAIN_FIELDS {
+        projection.insert(field, 1);
+    }
+    projection
+}
+
+async fn update_job(state: &AppState, job_id: &str, update: Document) {
+    if let Err(e) = state
+        .starknetid_db
+        .collection::<Document>(JOBS_COLLECTION)
+        .update_one(doc! { "job_id": job_id }, doc! { "$set": update }, None)
+        .await
+    {
+        state
+            .logger
+            .severe(format!("exports: unable to update job {}: {}", job_id, e));
+    }
+}
+
+async fn write_domains(state: &AppState, file: &mut File) -> Result<u64> {
+    let options = FindOptions::builder()
+        .projection(get_domain_projection())
+        .build();
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .find(doc! { "_cursor.to": null }, options)
+        .await?;
+    let mut records = 0;
+    while let Some(doc) = cursor.next().await {
+        write_line(file, &doc?).await?;
+        records += 1;
+    }
+    Ok(records)
+}
+
+// compares the versions of each domain live at both snapshots
+async fn write_domains_delta(
+    state: &AppState,
+    file: &mut File,
+    from: &str,
+    to: &str,
+) -> Result<u64> {
+    let (from, to) = (parse_snapshot_date(from)?, parse_snapshot_date(to)?);
+    let (from_block, to_block) = (
+        get_block_at(state, from).await?,
+        get_block_at(state, to).await?,
+    );
+
+    let mut projection = get_domain_projection();
+    projection.insert("_cursor", 1);
+    let options = FindOptions::builder()
+        .projection(projection)
+        .sort(doc! { "domain": 1, "_cursor.from": 1 })
+        .build();
+    // every version live at some point between the two snapshots
+    let filter = doc! {
+        "_cursor.from": { "$lte": to_block },
+        "$or": [
+            { "_cursor.to": null },
+            { "_cursor.to": { "$gt": from_block } },
+        ],
+    };
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .find(filter, options)
+        .await?;
+
+    let mut records = 0;
+    let mut versions: Vec<Document> = Vec::new();
+    loop {
+        let next = cursor.next().await.transpose()?;
+        let same_domain = match (&next, versions.first()) {
+            (Some(doc), Some(first)) => doc.get("domain") == first.get("domain"),
+            _ => true,
+        };
+        if !same_domain || next.is_none() {
+            let before = versions.iter().find(|doc| is_live_at(doc, from_block));
+            let after = versions.iter().find(|doc| is_live_at(doc, to_block));
+            if let Some(change) = get_change(before, after, from, to) {
+                let mut record = after.or(before).unwrap().clone();
+                record.remove("_cursor");
+                record.insert("change", change);
+                write_line(file, &record).await?;
+                records += 1;
+            }
+            versions.clear();
+        }
+        match next {
+            Some(doc) => versions.push(doc),
+            None => break,
+        }
+    }
+    Ok(records)
+}
+
+// writes the records as json lines, one line per document
+async fn write_artifact(state: &AppState, kind: &ExportKind, path: &Path) -> Result<u64> {
+    let mut file = File::create(path).await?;
+    let records = match kind {
+        ExportKind::Domains => write_domains(state, &mut file).await?,
+        ExportKind::DomainsDelta { from, to } => {
+            write_domains_delta(state, &mut file, from, to).await?
+        }
+    };
+    file.flush().await?;
+    Ok(records)
+}
+
+async fn run_job(state: &AppState, storage: &ExportStorage, job: &ExportJob) -> Result<u64> {
+    let dir = PathBuf::from(&state.conf.exports.tmp_dir);
+    fs::create_dir_all(&dir).await?;
+    let path = dir.join(format!("{}.jsonl", job.job_id));
+    let result: Result<u64> = async {
+        let records = write_artifact(state, &job.kind, &path).await?;
+        storage.upload(&get_object_key(job), &path).await?;
+        Ok(records)
+    }
+    .await;
+    let _ = fs::remove_file(&path).await;
+    result
+}
+
+pub fn get_object_key(job: &ExportJob) -> String {
+    format!("{}/{}.jsonl", job.kind.name(), job.job_id)
+}
+
+/// Saves a pending job and builds its artifact in the background
+pub async fn start_job(
+    state: &Arc<AppState>,
+    kind: ExportKind,
+    requested_by: String,
+) -> Result<ExportJob> {
+    let job = ExportJob {
+        job_id: format!("{:032x}", rand::random::<u128>()),
+        kind,
+        requested_by,
+        status: ExportStatus::Pending,
+        created_at: chrono::Utc::now().timestamp(),
+        finished_at: None,
+        records: 0,
+        object_key: None,
+        error: None,
+    };
+    state
+        .starknetid_db
+        .collection::<ExportJob>(JOBS_COLLECTION)
+        .insert_one(&job, None)
+        .await?;
+
+    let task_state = state.clone();
+    let task_job = job.clone();
+    tokio::spawn(async move {
+        let state = task_state;
+        let job = task_job;
+        let storage = match &state.export_storage {
+            Some(storage) => storage,
+            None => return,
+        };
+        update_job(
+            &state,
+            &job.job_id,
+            doc! { "status": to_bson(&ExportStatus::Running).unwrap() },
+        )
+        .await;
+        let update = match run_job(&state, storage, &job).await {
+            Ok(records) => doc! {
+                "status": to_bson(&ExportStatus::Done).unwrap(),
+                "records": records as i64,
+                "object_key": get_object_key(&job),
+                "finished_at": chrono::Utc::now().timestamp(),
+            },
+            Err(e) => {
+                state
+                    .logger
+                    .warning(format!("exports: job {} failed: {}", job.job_id, e));
+                doc! {
+                    "status": to_bson(&ExportStatus::Failed).unwrap(),
+                    "error": e.to_string(),
+                    "finished_at": chrono::Utc::now().timestamp(),
+                }
+            }
+        };
+        update_job(&state, &job.job_id, update).await;
+    });
+    Ok(job)
+}
+
+/// Latest job of a partner for the same export which didn't fail
+pub async fn find_job(
+    state: &AppState,
+    kind: &ExportKind,
+    requested_by: &str,
+) -> Result<Option<ExportJob>> {
+    let options = FindOneOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .build();
+    Ok(state
+        .starknetid_db
+        .collection::<ExportJob>(JOBS_COLLECTION)
+        .find_one(
+            doc! {
+                "kind": to_bson(kind)?,
+                "requested_by": requested_by,
+                "status": { "$ne": to_bson(&ExportStatus::Failed)? },
+            },
+            options,
+        )
+        .await?)
+}
+
+pub async fn get_job(state: &AppState, job_id: &str) -> mongodb::error::Result<Option<ExportJob>> {
+    state
+        .starknetid_db
+        .collection::<ExportJob>(JOBS_COLLECTION)
+        .find_one(doc! { "job_id": job_id }, None)
+        .await
+}
+
+/// Live domain as written by the streamed dumps
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DomainRow {
+    pub domain: String,
+    pub id: String,
+    pub owner: Option<String>,
+    pub expiry: Option<i64>,
+    pub resolver: Option<String>,
+}
+
+impl DomainRow {
+    pub const CSV_HEADER: &'static str = "domain,id,owner,expiry,resolver";
+
+    pub fn to_csv(&self) -> String {
+        [
+            escape_csv(&self.domain),
+            escape_csv(&self.id),
+            self.owner.as_deref().map(escape_csv).unwrap_or_default(),
+            self.expiry
+                .map(|expiry| expiry.to_string())
+                .unwrap_or_default(),
+            self.resolver.as_deref().map(escape_csv).unwrap_or_default(),
+        ]
+        .join(",")
+    }
+}
+
+// quoted when it holds a separator, a quote or a line break
+pub fn escape_csv(value: &str) -> String {
+    match value.contains([',', '"', '\n', '\r']) {
+        true => format!("\"{}\"", value.replace('"', "\"\"")),
+        false => value.to_string(),
+    }
+}
+
+/// Live domains sorted by name after `after`, with the owners of their identities
+pub async fn get_domains_page(
+    storage: &dyn Storage,
+    after: Option<&str>,
+    limit: i64,
+) -> Result<Vec<DomainRow>> {
+    let mut filter = doc! { "_cursor.to": null };
+    if let Some(after) = after {
+        filter.insert("domain", doc! { "$gt": after });
+    }
+    let docs = storage
+        .find(
+            "domains",
+            filter,
+            FindSpec {
+                sort: Some(doc! { "domain": 1 }),
+                limit: Some(limit),
+                ..Default::default()
+            },
+        )
+        .await?;
+    let ids: Vec<&str> = docs
+        .iter()
+        .filter_map(|doc| doc.get_str("id").ok())
+        .collect();
+    let owners: HashMap<String, String> = storage
+        .find(
+            "id_owners",
+            doc! { "id": { "$in": ids }, "_cursor.to": null },
+            FindSpec::default(),
+        )
+        .await?
+        .iter()
+        .filter_map(|doc| {
+            Some((
+                doc.get_str("id").ok()?.to_string(),
+                doc.get_str("owner").ok()?.to_string(),
+            ))
+        })
+        .collect();
+    Ok(docs
+        .iter()
+        .filter_map(|doc| {
+            let id = doc.get_str("id").unwrap_or_default().to_string();
+            Some(DomainRow {
+                domain: doc.get_str("domain").ok()?.to_string(),
+                owner: owners.get(&id).cloned(),
+                id,
+                expiry: doc.get_i64("expiry").ok(),
+                resolver: doc.get_str("resolver").ok().map(String::from),
+            })
+        })
+        .collect())
+}
+
+/// Every live domain, read a page at a time while the response is written
+pub fn stream_domains(state: Arc<AppState>) -> impl Stream<Item = Result<DomainRow>> {
+    // None once the last page is read, else the domain the next page starts after
+    let start: Option<Option<String>> = Some(None);
+    stream::try_unfold((state, start), |(state, after)| async move {
+        let after = match after {
+            Some(after) => after,
+            None => return Ok(None),
+        };
+        let rows =
+            get_domains_page(state.storage.as_ref(), after.as_deref(), DUMP_PAGE_SIZE).await?;
+        let next = match rows.last() {
+            Some(last) if rows.len() as i64 == DUMP_PAGE_SIZE => Some(Some(last.domain.clone())),
+            _ => None,
+        };
+        Ok(Some((
+            stream::iter(rows.into_iter().map(Ok)),
+            (state, next),
+        )))
+    })
+    .try_flatten()
+}
@@ -0,0 +1,205 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::State,
+    http::{
+        header::{CONTENT_LENGTH, CONTENT_TYPE},
+        HeaderValue, Request, StatusCode,
+    },
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+};
+use serde::Serialize;
+
+use crate::{
+    errors::{ApiError, ErrorCode, ErrorDetails},
+    incidents::new_incident_id,
+    metrics::{labeled, Metrics},
+    models::AppState,
+    request_tracing::RequestId,
+    types::starknet_addr::ERROR_PREFIX,
+};
+use utoipa::ToSchema;
+
+// retry hint of the upstream failures, long enough for a saturated pool to drain
+const UPSTREAM_RETRY_MS: u64 = 5000;
+// the handler errors are kept in the body up to this size
+const MAX_ERROR_LEN: usize = 512;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Component {
+    Database,
+    Rpc,
+}
+
+/// Body of the throttling and server failure responses, so clients can decide when to retry
+#[derive(Serialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FailureBody {
+    pub error: ErrorDetails,
+    // null when retrying the same request isn't expected to succeed
+    pub retry_after_ms: Option<u64>,
+    pub degraded_components: Vec<Component>,
+    // reference of the logged failure to give to the support
+    pub incident_id: Option<String>,
+}
+
+/// Components currently saturated: rpc calls waiting for a slot, or requests waiting for a
+/// connection of one of the `databases` pools
+pub fn get_degraded_components(metrics: &Metrics, databases: &[&str]) -> Vec<Component> {
+    let mut components = vec![];
+    if databases
+        .iter()
+        .any(|db| metrics.get(&labeled("mongo_pool_wait_queue", "db", db)) > 0)
+    {
+        components.push(Component::Database);
+    }
+    if metrics.get(&labeled("rpc_queue_depth", "priority", "interactive")) > 0 {
+        components.push(Component::Rpc);
+    }
+    components
+}
+
+pub fn get_state_degraded_components(state: &AppState) -> Vec<Component> {
+    let databases = &state.conf.databases;
+    get_degraded_components(
+        &state.metrics,
+        &[
+            &databases.starknetid.name,
+            &databases.sales.name,
+            &databases.free_domains.name,
+        ],
+    )
+}
+
+/// Retry hint of a failure status, only the throttling and upstream ones are worth retrying
+pub fn get_retry_after_ms(status: StatusCode) -> Option<u64> {
+    match status {
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            Some(UPSTREAM_RETRY_MS)
+        }
+        _ => None,
+    }
+}
+
+/// Json response of a failure, with the matching `retry-after` header in seconds
+pub fn failure_response(status: StatusCode, body: FailureBody) -> Response {
+    let retry_after = body.retry_after_ms.map(|ms| (ms + 999) / 1000);
+    let mut response = (status, Json(body)).into_response();
+    if let Some(secs) = retry_after {
+        response
+            .headers_mut()
+            .insert("retry-after", HeaderValue::from(secs));
+    }
+    response
+}
+
+/// Error of a rejected body or query holding a malformed address, without the extractor
+/// context before it
+pub fn get_address_error(body: &str) -> Option<String> {
+    let start = body.find(ERROR_PREFIX)?;
+    Some(body[start..].chars().take(MAX_ERROR_LEN).collect())
+}
+
+// the headers set by the handler or the layers, the json ones replace the text ones
+fn copy_headers(response: &mut Response, headers: &axum::http::HeaderMap) {
+    for (name, value) in headers.iter() {
+        if name != CONTENT_TYPE && name != CONTENT_LENGTH && !response.headers().contains_key(name)
+        {
+            response.headers_mut().append(name, value.clone());
+        }
+    }
+}
+
+/// `ApiError` of a plain text client error, e.g. an extractor rejection. axum rejects the
+/// malformed json bodies with a 422, they are answered like the malformed queries
+pub fn get_rejection_error(status: StatusCode, body: &str) -> ApiError {
+    if let Some(error) = get_address_error(body) {
+        return ApiError::new(ErrorCode::InvalidAddress, error);
+    }
+    let message = match body.is_empty() {
+        true => status
+            .canonical_reason()
+            .unwrap_or("Bad request")
+            .to_string(),
+        false => body.chars().take(MAX_ERROR_LEN).collect(),
+    };
+    match status {
+        StatusCode::UNPROCESSABLE_ENTITY => ApiError::new(ErrorCode::InvalidInput, message),
+        status => ApiError::new(ErrorCode::from_status(status), message),
+    }
+}
+
+/// Wraps the plain text server failures of the handlers in a `FailureBody` and the plain text
+/// client errors in an `ErrorBody`, e.g. the extractor rejections
+pub async fn standardize_failures<B>(
+    State(state): State<Arc<AppState>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let path = req.uri().path().to_string();
+    let request_id = req
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.clone())
+        .unwrap_or_default();
+    let response = next.run(req).await;
+    let content_type = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("text/plain");
+    // the html pages and the images keep their own errors
+    if response.status().is_success()
+        || response.status().is_redirection()
+        || !content_type.starts_with("text/plain")
+    {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let bytes = hyper::body::to_bytes(body).await.unwrap_or_default();
+    if parts.status.is_client_error() {
+        let mut response =
+            get_rejection_error(parts.status, &String::from_utf8_lossy(&bytes)).into_response();
+        copy_headers(&mut response, &parts.headers);
+        return response;
+    }
+
+    // the `ApiError` server failures keep their code
+    let code = parts
+        .extensions
+        .get::<ErrorCode>()
+        .copied()
+        .unwrap_or_else(|| ErrorCode::from_status(parts.status));
+    let error = match bytes {
+        bytes if !bytes.is_empty() => String::from_utf8_lossy(&bytes)
+            .chars()
+            .take(MAX_ERROR_LEN)
+            .collect(),
+        _ => parts
+            .status
+            .canonical_reason()
+            .unwrap_or("Server error")
+            .to_string(),
+    };
+    let incident_id = new_incident_id();
+    state.logger.warning(format!(
+        "incident {}: {} {} failed (request {}): {}",
+        incident_id, parts.status, path, request_id, error
+    ));
+    let mut response = failure_response(
+        parts.status,
+        FailureBody {
+            error: ErrorDetails {
+                code,
+                message: error,
+            },
+            retry_after_ms: get_retry_after_ms(parts.status),
+            degraded_components: get_state_degraded_components(&state),
+            incident_id: Some(incident_id),
+        },
+    );
+    copy_headers(&mut response, &parts.headers);
+    response
+}
@@ -0,0 +1,98 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use anyhow::{anyhow, Result};
+use reqwest::Url;
+use starknet::{
+    core::types::{BlockId, BlockTag, FieldElement, FunctionCall},
+    macros::selector,
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+use starknet_id::encode;
+
+use crate::{metrics::labeled, models::AppState, rpc_queue::Priority};
+
+// expired results are dropped past this many cached domains
+const MAX_CACHED: usize = 10_000;
+
+/// Results of the contract calls made for the domains the indexer didn't catch up with,
+/// None when the contract doesn't resolve the domain either
+#[derive(Default)]
+pub struct FallbackCache {
+    entries: Mutex<HashMap<String, (i64, Option<FieldElement>)>>,
+}
+
+impl FallbackCache {
+    pub fn get(&self, domain: &str, now: i64) -> Option<Option<FieldElement>> {
+        match self.entries.lock().unwrap().get(domain) {
+            Some((expires_at, target)) if *expires_at > now => Some(*target),
+            _ => None,
+        }
+    }
+
+    pub fn insert(&self, domain: &str, target: Option<FieldElement>, expires_at: i64, now: i64) {
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= MAX_CACHED {
+            entries.retain(|_, (expires_at, _)| *expires_at > now);
+        }
+        entries.insert(domain.to_string(), (expires_at, target));
+    }
+}
+
+/// Calldata of `domain_to_address` for a domain without hint
+pub fn get_domain_calldata(domain: &str) -> Result<Vec<FieldElement>> {
+    let labels: Vec<&str> = domain
+        .strip_suffix(".stark")
+        .ok_or_else(|| anyhow!("Only .stark domains are resolved by the naming contract"))?
+        .split('.')
+        .collect();
+    let mut calldata = vec![FieldElement::from(labels.len())];
+    for label in labels {
+        calldata.push(encode(label).map_err(|_| anyhow!("Unable to encode {}", label))?);
+    }
+    calldata.push(FieldElement::ZERO);
+    Ok(calldata)
+}
+
+/// Target of the domain read from the naming contract, None when the fallback is disabled
+/// or the domain doesn't resolve
+pub async fn resolve_domain(state: &AppState, domain: &str) -> Result<Option<FieldElement>> {
+    let conf = &state.conf.fallback;
+    if conf.rpc_url.is_empty() {
+        return Ok(None);
+    }
+    let now = chrono::Utc::now().timestamp();
+    if let Some(target) = state.resolution_fallback.get(domain, now) {
+        return Ok(target);
+    }
+
+    let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(&conf.rpc_url)?));
+    let call = provider.call(
+        FunctionCall {
+            contract_address: state.conf.contracts.naming,
+            entry_point_selector: selector!("domain_to_address"),
+            calldata: get_domain_calldata(domain)?,
+        },
+        BlockId::Tag(BlockTag::Pending),
+    );
+    let result = state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_call", call)
+        .await?;
+    let target = result
+        .first()
+        .copied()
+        .filter(|addr| *addr != FieldElement::ZERO);
+    // the misses are retried sooner, the registration may be about to land
+    let ttl = match target {
+        Some(_) => conf.cache_secs,
+        None => conf.miss_cache_secs,
+    };
+    state
+        .resolution_fallback
+        .insert(domain, target, now + ttl, now);
+    let result = if target.is_some() { "hit" } else { "miss" };
+    state
+        .metrics
+        .add(&labeled("resolution_fallback_total", "result", result), 1);
+    Ok(target)
+}
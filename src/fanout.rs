@@ -0,0 +1,84 @@
+use std::future::Future;
+
+use tokio::time::{timeout, Duration};
+
+use crate::{
+    activity::get_address_activity,
+    badges::BadgeSubject,
+    contract_claims::is_contract_verified,
+    descriptions::get_description,
+    display_address::format_address,
+    freezes::get_freeze,
+    metrics::labeled,
+    models::{AppState, IdentityData},
+};
+
+/// Runs a branch of a handler fan-out within the configured deadline, a late branch is
+/// dropped and counted in `fanout_timeouts_total` so the response is served without it
+pub async fn with_timeout<T>(
+    state: &AppState,
+    branch: &str,
+    future: impl Future<Output = T>,
+) -> Option<T> {
+    let deadline = Duration::from_millis(state.conf.fanout.branch_timeout_ms);
+    match timeout(deadline, future).await {
+        Ok(value) => Some(value),
+        Err(_) => {
+            state
+                .metrics
+                .add(&labeled("fanout_timeouts_total", "branch", branch), 1);
+            None
+        }
+    }
+}
+
+/// Badges, verification freshness, description, activity and freeze of a profile, the lookups
+/// are independent so they run concurrently
+pub async fn complete_identity(state: &AppState, identity: &mut IdentityData) {
+    let domain = identity
+        .domain
+        .as_ref()
+        .map(|domain| domain.domain.as_str());
+    let (contract_verified, freshness, description, activity, compromised) = tokio::join!(
+        with_timeout(state, "contract_verified", async {
+            match domain {
+                Some(domain) => is_contract_verified(state, domain).await,
+                None => false,
+            }
+        }),
+        with_timeout(
+            state,
+            "verifier_freshness",
+            identity.get_verifier_freshness(state)
+        ),
+        with_timeout(
+            state,
+            "description",
+            get_description(state.storage.as_ref(), &identity.id, &identity.owner)
+        ),
+        with_timeout(
+            state,
+            "activity",
+            get_address_activity(state, &identity.owner, domain)
+        ),
+        with_timeout(
+            state,
+            "freeze",
+            get_freeze(state.storage.as_ref(), &identity.id)
+        ),
+    );
+
+    let mut subject = BadgeSubject::from_identity(state, identity);
+    subject.contract_verified = contract_verified.unwrap_or_default();
+    identity.badges = state.badges.compute(&subject);
+    identity.owner_display_address = Some(format_address(
+        &identity.owner,
+        state.conf.address_display.format,
+    ));
+    if let Some(freshness) = freshness {
+        identity.set_verifier_freshness(freshness);
+    }
+    identity.description = description.flatten();
+    identity.activity = activity.flatten();
+    identity.compromised = compromised.and_then(Result::ok).flatten();
+}
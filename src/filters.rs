@@ -0,0 +1,356 @@
+use serde_json::Value;
+
+// longer or deeper filters are refused, so a subscriber can't make the evaluation costly
+const MAX_FILTER_LEN: usize = 1024;
+const MAX_DEPTH: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Method {
+    StartsWith,
+    EndsWith,
+    // substring of a string, or element of an array
+    Contains,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(Value),
+    Ident(String),
+    Dot,
+    LParen,
+    RParen,
+    Not,
+    And,
+    Or,
+    Compare(CompareOp),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(Value),
+    // dotted path in the event, null when missing
+    Field(Vec<String>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Compare(CompareOp, Box<Expr>, Box<Expr>),
+    Call(Method, Box<Expr>, Box<Expr>),
+}
+
+fn read_string(chars: &[char], start: usize) -> Result<(String, usize), String> {
+    let quote = chars[start];
+    let mut value = String::new();
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                value.push(chars[i + 1]);
+                i += 2;
+            }
+            c if c == quote => return Ok((value, i + 1)),
+            c => {
+                value.push(c);
+                i += 1;
+            }
+        }
+    }
+    Err("Unterminated string".to_string())
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let chars: Vec<char> = source.chars().collect();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let next = chars.get(i + 1).copied();
+        let (token, len) = match (chars[i], next) {
+            (c, _) if c.is_whitespace() => {
+                i += 1;
+                continue;
+            }
+            ('"' | '\'', _) => {
+                let (value, end) = read_string(&chars, i)?;
+                tokens.push(Token::Literal(Value::from(value)));
+                i = end;
+                continue;
+            }
+            (c, _)
+                if c.is_ascii_digit() || (c == '-' && next.is_some_and(|n| n.is_ascii_digit())) =>
+            {
+                let end = (i + 1..chars.len())
+                    .find(|&j| !chars[j].is_ascii_digit() && chars[j] != '.')
+                    .unwrap_or(chars.len());
+                let number: String = chars[i..end].iter().collect();
+                let number: f64 = number
+                    .parse()
+                    .map_err(|_| format!("Invalid number: {}", number))?;
+                tokens.push(Token::Literal(Value::from(number)));
+                i = end;
+                continue;
+            }
+            (c, _) if c.is_alphabetic() || c == '_' => {
+                let end = (i + 1..chars.len())
+                    .find(|&j| !chars[j].is_alphanumeric() && chars[j] != '_')
+                    .unwrap_or(chars.len());
+                let ident: String = chars[i..end].iter().collect();
+                tokens.push(match ident.as_str() {
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "null" => Token::Literal(Value::Null),
+                    _ => Token::Ident(ident),
+                });
+                i = end;
+                continue;
+            }
+            ('&', Some('&')) => (Token::And, 2),
+            ('|', Some('|')) => (Token::Or, 2),
+            ('=', Some('=')) => (Token::Compare(CompareOp::Eq), 2),
+            ('!', Some('=')) => (Token::Compare(CompareOp::Ne), 2),
+            ('<', Some('=')) => (Token::Compare(CompareOp::Le), 2),
+            ('>', Some('=')) => (Token::Compare(CompareOp::Ge), 2),
+            ('<', _) => (Token::Compare(CompareOp::Lt), 1),
+            ('>', _) => (Token::Compare(CompareOp::Gt), 1),
+            ('!', _) => (Token::Not, 1),
+            ('.', _) => (Token::Dot, 1),
+            ('(', _) => (Token::LParen, 1),
+            (')', _) => (Token::RParen, 1),
+            (c, _) => return Err(format!("Unexpected character: {}", c)),
+        };
+        tokens.push(token);
+        i += len;
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), String> {
+        match self.next() {
+            Some(token) if token == expected => Ok(()),
+            _ => Err(format!("Expected {:?}", expected)),
+        }
+    }
+
+    fn descend(&mut self) -> Result<(), String> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(format!("Filters are nested at most {} times", MAX_DEPTH));
+        }
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        self.descend()?;
+        let mut left = self.and()?;
+        while self.peek() == Some(&Token::Or) {
+            self.next();
+            left = Expr::Or(Box::new(left), Box::new(self.and()?));
+        }
+        self.depth -= 1;
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut left = self.comparison()?;
+        while self.peek() == Some(&Token::And) {
+            self.next();
+            left = Expr::And(Box::new(left), Box::new(self.comparison()?));
+        }
+        Ok(left)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.unary()?;
+        match self.peek() {
+            Some(&Token::Compare(op)) => {
+                self.next();
+                Ok(Expr::Compare(op, Box::new(left), Box::new(self.unary()?)))
+            }
+            _ => Ok(left),
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        if self.peek() != Some(&Token::Not) {
+            return self.primary();
+        }
+        self.next();
+        self.descend()?;
+        let expr = Expr::Not(Box::new(self.unary()?));
+        self.depth -= 1;
+        Ok(expr)
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let mut expr = match self.next() {
+            Some(Token::Literal(value)) => return Ok(Expr::Literal(value)),
+            Some(Token::LParen) => {
+                let expr = self.or()?;
+                self.expect(Token::RParen)?;
+                return Ok(expr);
+            }
+            Some(Token::Ident(ident)) => Expr::Field(vec![ident]),
+            Some(token) => return Err(format!("Unexpected {:?}", token)),
+            None => return Err("Unexpected end of the filter".to_string()),
+        };
+        while self.peek() == Some(&Token::Dot) {
+            self.next();
+            let name = match self.next() {
+                Some(Token::Ident(name)) => name,
+                _ => return Err("Expected a field or a method after a dot".to_string()),
+            };
+            if self.peek() == Some(&Token::LParen) {
+                self.next();
+                let method = match name.as_str() {
+                    "startsWith" => Method::StartsWith,
+                    "endsWith" => Method::EndsWith,
+                    "contains" => Method::Contains,
+                    _ => return Err(format!("Unknown method: {}", name)),
+                };
+                let arg = self.or()?;
+                self.expect(Token::RParen)?;
+                expr = Expr::Call(method, Box::new(expr), Box::new(arg));
+                continue;
+            }
+            match &mut expr {
+                Expr::Field(path) => path.push(name),
+                _ => return Err(format!("Unexpected field after a method: {}", name)),
+            }
+        }
+        Ok(expr)
+    }
+}
+
+// `length` is the length of the first label of the domain, unless the event has its own
+fn get_field(event: &Value, path: &[String]) -> Value {
+    if let Some(value) = path.iter().try_fold(event, |value, key| value.get(key)) {
+        return value.clone();
+    }
+    match path {
+        [field] if field == "length" => event["domain"]
+            .as_str()
+            .and_then(|domain| domain.split('.').next())
+            .map(|label| Value::from(label.chars().count()))
+            .unwrap_or(Value::Null),
+        _ => Value::Null,
+    }
+}
+
+fn is_true(value: &Value) -> bool {
+    value == &Value::Bool(true)
+}
+
+// numbers are compared by value whatever their json representation
+fn equals(a: &Value, b: &Value) -> bool {
+    match (a.as_f64(), b.as_f64()) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+fn compare(op: CompareOp, a: &Value, b: &Value) -> bool {
+    let ordering = match (a, b) {
+        (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        _ => None,
+    };
+    match op {
+        CompareOp::Eq => equals(a, b),
+        CompareOp::Ne => !equals(a, b),
+        // values of different types are never ordered
+        CompareOp::Lt => ordering.is_some_and(|o| o.is_lt()),
+        CompareOp::Le => ordering.is_some_and(|o| o.is_le()),
+        CompareOp::Gt => ordering.is_some_and(|o| o.is_gt()),
+        CompareOp::Ge => ordering.is_some_and(|o| o.is_ge()),
+    }
+}
+
+fn call(method: Method, target: &Value, arg: &Value) -> bool {
+    match (method, target, arg) {
+        (Method::StartsWith, Value::String(target), Value::String(arg)) => target.starts_with(arg),
+        (Method::EndsWith, Value::String(target), Value::String(arg)) => target.ends_with(arg),
+        (Method::Contains, Value::String(target), Value::String(arg)) => target.contains(arg),
+        (Method::Contains, Value::Array(values), arg) => values.iter().any(|v| equals(v, arg)),
+        _ => false,
+    }
+}
+
+fn eval(expr: &Expr, event: &Value) -> Value {
+    let result = match expr {
+        Expr::Literal(value) => return value.clone(),
+        Expr::Field(path) => return get_field(event, path),
+        Expr::Not(expr) => !is_true(&eval(expr, event)),
+        Expr::And(a, b) => is_true(&eval(a, event)) && is_true(&eval(b, event)),
+        Expr::Or(a, b) => is_true(&eval(a, event)) || is_true(&eval(b, event)),
+        Expr::Compare(op, a, b) => compare(*op, &eval(a, event), &eval(b, event)),
+        Expr::Call(method, target, arg) => call(*method, &eval(target, event), &eval(arg, event)),
+    };
+    Value::Bool(result)
+}
+
+/// Expression the events sent to a subscriber must match, e.g.
+/// `event == "transfer" && domain.endsWith(".stark") && length <= 5`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    source: String,
+    expr: Expr,
+}
+
+impl Filter {
+    pub fn parse(source: &str) -> Result<Self, String> {
+        let source = source.trim();
+        if source.is_empty() {
+            return Err("Empty filter".to_string());
+        }
+        if source.len() > MAX_FILTER_LEN {
+            return Err(format!("Filters are at most {} bytes", MAX_FILTER_LEN));
+        }
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+            depth: 0,
+        };
+        let expr = parser.or()?;
+        if let Some(token) = parser.peek() {
+            return Err(format!("Unexpected {:?}", token));
+        }
+        Ok(Filter {
+            source: source.to_string(),
+            expr,
+        })
+    }
+
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Whether the event matches, anything but a true result is a mismatch
+    pub fn matches(&self, event: &Value) -> bool {
+        is_true(&eval(&self.expr, event))
+    }
+}
@@ -0,0 +1,269 @@
+use std::sync::{atomic::Ordering, Arc};
+
+use anyhow::{anyhow, Result};
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::FindOneOptions,
+};
+use reqwest::Url;
+use serde::Serialize;
+use starknet::{
+    core::types::{BlockId, BlockStatus, MaybePendingBlockWithTxHashes},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+
+use crate::{
+    models::AppState,
+    rpc_queue::{Priority, RpcQueue},
+};
+
+// collections written by the indexer, rolled back when it reports a reorg
+const INDEXED_COLLECTIONS: [&str; 9] = [
+    "domains",
+    "id_owners",
+    "id_user_data",
+    "id_verifier_data",
+    "custom_resolutions",
+    "offchain_resolvers",
+    "auto_renew_flows",
+    "auto_renew_flows_altcoins",
+    "renewals",
+];
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Finality {
+    AcceptedOnL1,
+    AcceptedOnL2,
+}
+
+/// Facts indexed from a block at or below the last L1 accepted block can't be reorged anymore
+pub fn get_finality(block: i64, last_l1_block: u64) -> Finality {
+    if block >= 0 && block as u64 <= last_l1_block {
+        Finality::AcceptedOnL1
+    } else {
+        Finality::AcceptedOnL2
+    }
+}
+
+impl AppState {
+    pub fn finality(&self, block: i64) -> Finality {
+        get_finality(block, self.last_l1_block.load(Ordering::Relaxed))
+    }
+}
+
+async fn is_accepted_on_l1(
+    queue: &RpcQueue,
+    provider: &JsonRpcClient<HttpTransport>,
+    block: u64,
+) -> Result<bool> {
+    let call = provider.get_block_with_tx_hashes(BlockId::Number(block));
+    match queue
+        .run(Priority::Background, "starknet_getBlockWithTxHashes", call)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch block {}: {}", block, e))?
+    {
+        MaybePendingBlockWithTxHashes::Block(block) => {
+            Ok(matches!(block.status, BlockStatus::AcceptedOnL1))
+        }
+        MaybePendingBlockWithTxHashes::PendingBlock(_) => Ok(false),
+    }
+}
+
+/// Binary searches the last block accepted on L1, starting from the previously known one
+async fn find_last_l1_block(
+    queue: &RpcQueue,
+    provider: &JsonRpcClient<HttpTransport>,
+    known: u64,
+) -> Result<u64> {
+    let latest = queue
+        .run(
+            Priority::Background,
+            "starknet_blockNumber",
+            provider.block_number(),
+        )
+        .await
+        .map_err(|e| anyhow!("Failed to fetch latest block number: {}", e))?;
+    let (mut low, mut high) = (known.min(latest), latest);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if is_accepted_on_l1(queue, provider, mid).await? {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
+
+pub async fn update_last_l1_block(state: &Arc<AppState>) {
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).unwrap(),
+    ));
+    let known = state.last_l1_block.load(Ordering::Relaxed);
+    match find_last_l1_block(&state.rpc_queue, &provider, known).await {
+        Ok(block) => state.last_l1_block.store(block, Ordering::Relaxed),
+        Err(e) => state
+            .logger
+            .warning(format!("Unable to update last L1 accepted block: {}", e)),
+    }
+}
+
+/// Only the blocks after the last L1 accepted one can be reorged, a deeper rollback would
+/// delete final data
+pub fn can_roll_back(block: i64, last_l1_block: u64) -> bool {
+    block >= 0 && block as u64 >= last_l1_block
+}
+
+/// The indexer reports a reorg with the last block it ingested before stopping, nothing may
+/// be indexed past it while the rollback runs
+pub fn is_ingestion_stopped(last_indexed_block: i64, indexer_block: i64) -> bool {
+    last_indexed_block <= indexer_block
+}
+
+/// Last block written to the indexed collections, by a new document or an invalidation
+pub async fn get_last_indexed_block(state: &AppState) -> mongodb::error::Result<i64> {
+    let event_collections = state.conf.events.collections.values().map(String::as_str);
+    let mut last = -1;
+    for name in INDEXED_COLLECTIONS.into_iter().chain(event_collections) {
+        let collection = state.starknetid_db.collection::<Document>(name);
+        for key in ["from", "to"] {
+            let field = format!("_cursor.{}", key);
+            let options = FindOneOptions::builder()
+                .sort(doc! { &field: -1 })
+                .projection(doc! { &field: 1 })
+                .build();
+            let block = collection
+                .find_one(doc! { &field: { "$ne": null } }, options)
+                .await?
+                .and_then(|doc| doc.get_document("_cursor").ok()?.get_i64(key).ok());
+            last = last.max(block.unwrap_or(-1));
+        }
+    }
+    Ok(last)
+}
+
+/// Undoes everything indexed after `block`: documents created later are deleted and
+/// documents invalidated later become live again
+pub async fn rollback_to_block(state: &AppState, block: i64) -> mongodb::error::Result<()> {
+    let event_collections = state.conf.events.collections.values().map(String::as_str);
+    for name in INDEXED_COLLECTIONS.into_iter().chain(event_collections) {
+        let collection = state.starknetid_db.collection::<Document>(name);
+        collection
+            .delete_many(doc! { "_cursor.from": { "$gt": block } }, None)
+            .await?;
+        collection
+            .update_many(
+                doc! { "_cursor.to": { "$gt": block } },
+                doc! { "$set": { "_cursor.to": null } },
+                None,
+            )
+            .await?;
+    }
+    Ok(())
+}
+
+/// Applies the reorgs reported by the indexer in the `reorgs` collection, on the instance
+/// they are enabled on
+pub async fn process_reorgs(state: &Arc<AppState>) {
+    let last_l1_block = state.last_l1_block.load(Ordering::Relaxed);
+    // the depth of the reorgs can't be checked until the last L1 block is known
+    if !state.conf.reorgs.enabled || last_l1_block == 0 {
+        return;
+    }
+    let reorgs = state.starknetid_db.collection::<Document>("reorgs");
+    let mut cursor = match reorgs
+        .find(doc! { "processed": { "$ne": true } }, None)
+        .await
+    {
+        Ok(cursor) => cursor,
+        Err(e) => {
+            state
+                .logger
+                .severe(format!("Error while fetching reorgs: {}", e));
+            return;
+        }
+    };
+
+    while let Some(Ok(reorg)) = cursor.next().await {
+        let block = match reorg.get_i64("block") {
+            Ok(block) => block,
+            Err(_) => {
+                state
+                    .logger
+                    .warning(format!("Ignoring malformed reorg: {:?}", reorg));
+                continue;
+            }
+        };
+        let indexer_block = match reorg.get_i64("indexer_block") {
+            Ok(indexer_block) => indexer_block,
+            Err(_) => {
+                state.logger.warning(format!(
+                    "Ignoring reorg without the last block of the indexer: {:?}",
+                    reorg
+                ));
+                continue;
+            }
+        };
+        if !can_roll_back(block, last_l1_block) {
+            state.logger.severe(format!(
+                "Refusing to roll back to block {}, the last L1 accepted block is {}",
+                block, last_l1_block
+            ));
+            let _ = reorgs
+                .update_one(
+                    doc! { "_id": reorg.get("_id").cloned() },
+                    doc! { "$set": { "processed": true, "refused": true } },
+                    None,
+                )
+                .await;
+            continue;
+        }
+        // the indexer must have stopped, or the rollback would race its writes
+        match get_last_indexed_block(state).await {
+            Ok(last) if is_ingestion_stopped(last, indexer_block) => {}
+            Ok(last) => {
+                state.logger.warning(format!(
+                    "Delaying the rollback to block {}, block {} was indexed after {}",
+                    block, last, indexer_block
+                ));
+                continue;
+            }
+            Err(e) => {
+                state
+                    .logger
+                    .severe(format!("Error while checking the indexer progress: {}", e));
+                continue;
+            }
+        }
+        let result = match rollback_to_block(state, block).await {
+            // nothing may have been indexed while rolling back
+            Ok(()) => match get_last_indexed_block(state).await {
+                Ok(last) if last <= block => Ok(()),
+                Ok(last) => Err(format!("block {} was indexed during the rollback", last)),
+                Err(e) => Err(e.to_string()),
+            },
+            Err(e) => Err(e.to_string()),
+        };
+        // the indexer resumes from `block` once the reorg is processed
+        match result {
+            Ok(()) => {
+                state
+                    .logger
+                    .warning(format!("Rolled back indexed data to block {}", block));
+                let _ = reorgs
+                    .update_one(
+                        doc! { "_id": reorg.get("_id").cloned() },
+                        doc! { "$set": { "processed": true, "processed_at": chrono::Utc::now().timestamp() } },
+                        None,
+                    )
+                    .await;
+            }
+            Err(e) => state.logger.severe(format!(
+                "Error while rolling back to block {}: {}",
+                block, e
+            )),
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use anyhow::Result;
+use mongodb::bson::{doc, from_document, to_document};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use starknet::{
+    core::{crypto::pedersen_hash, types::FieldElement},
+    macros::short_string,
+};
+
+use crate::{
+    models::AppState,
+    storage::{FindSpec, Storage},
+    utils::to_hex,
+    webhooks::{
+        dispatch::{get_domain_of, queue_notification, WebhookNotification},
+        WebhookEvent,
+    },
+};
+
+pub const FREEZES_COLLECTION: &str = "identity_freezes";
+pub const MAX_REASON_LEN: usize = 280;
+
+/// Identity its owner reported compromised, shown as a warning until they unfreeze it. The
+/// mark stays on the identity when it is moved out of the wallet
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Freeze {
+    pub id: String,
+    // owner of the identity when it was frozen
+    pub address: String,
+    pub reason: Option<String>,
+    pub frozen_at: i64,
+}
+
+/// Message signed by an address to lift the freezes it made
+pub fn get_unfreeze_hash(address: &FieldElement, timestamp: i64) -> FieldElement {
+    let hash = pedersen_hash(&short_string!("unfreeze identities"), address);
+    pedersen_hash(&hash, &FieldElement::from(timestamp as u64))
+}
+
+pub async fn get_freeze(storage: &dyn Storage, id: &FieldElement) -> Result<Option<Freeze>> {
+    Ok(storage
+        .find_one(FREEZES_COLLECTION, doc! { "id": to_hex(id) })
+        .await?
+        .and_then(|doc| from_document(doc).ok()))
+}
+
+/// Freezes the identities owned by the address
+pub async fn freeze_identities(
+    storage: &dyn Storage,
+    address: &FieldElement,
+    reason: Option<String>,
+    now: i64,
+) -> Result<Vec<Freeze>> {
+    let ids: Vec<String> = storage
+        .find(
+            "id_owners",
+            doc! { "owner": to_hex(address), "_cursor.to": null },
+            FindSpec::default(),
+        )
+        .await?
+        .iter()
+        .filter_map(|doc| doc.get_str("id").ok().map(String::from))
+        .collect();
+    let mut freezes = vec![];
+    for id in ids {
+        let freeze = Freeze {
+            id,
+            address: to_hex(address),
+            reason: reason.clone(),
+            frozen_at: now,
+        };
+        storage
+            .replace_one(
+                FREEZES_COLLECTION,
+                doc! { "id": &freeze.id },
+                to_document(&freeze)?,
+            )
+            .await?;
+        freezes.push(freeze);
+    }
+    Ok(freezes)
+}
+
+/// Lifts the freezes made by the address, including on the identities it no longer owns
+pub async fn unfreeze_identities(
+    storage: &dyn Storage,
+    address: &FieldElement,
+) -> Result<Vec<Freeze>> {
+    let filter = doc! { "address": to_hex(address) };
+    let freezes: Vec<Freeze> = storage
+        .find(FREEZES_COLLECTION, filter.clone(), FindSpec::default())
+        .await?
+        .into_iter()
+        .filter_map(|doc| from_document(doc).ok())
+        .collect();
+    storage.delete_many(FREEZES_COLLECTION, filter).await?;
+    Ok(freezes)
+}
+
+/// Tells the webhooks about the freezes, so the counterparties of the victim hear of them
+pub async fn notify_webhooks(state: &AppState, freezes: &[Freeze], event: WebhookEvent, now: i64) {
+    if !state.conf.webhooks.enabled {
+        return;
+    }
+    let storage = state.storage.as_ref();
+    for freeze in freezes {
+        let domain = get_domain_of(storage, &freeze.id).await.ok().flatten();
+        let notification = WebhookNotification {
+            event,
+            key: format!("{}:{}:{}", event.as_str(), freeze.id, now),
+            block: None,
+            data: json!({
+                "domain": domain,
+                "id": freeze.id,
+                "address": freeze.address,
+                "reason": freeze.reason,
+            }),
+        };
+        if let Err(e) = queue_notification(storage, &notification, now).await {
+            state.logger.warning(format!(
+                "freezes: unable to notify the webhooks of {}: {}",
+                freeze.id, e
+            ));
+        }
+    }
+}
@@ -0,0 +1,165 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use futures::{future::join_all, StreamExt};
+use mongodb::bson::{doc, Document};
+use reqwest::Url;
+use starknet::{
+    core::types::{BlockId, FieldElement, MaybePendingBlockWithTxHashes},
+    macros::short_string,
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+
+use crate::{
+    models::{AppState, IdentityData},
+    rpc_queue::Priority,
+    utils::to_hex,
+};
+
+// the cache is cleared past this size, blocks never change so it is refilled as needed
+const MAX_CACHED_BLOCKS: usize = 100_000;
+
+/// Timestamps of the blocks verifications were written at
+#[derive(Default)]
+pub struct BlockTimestamps {
+    timestamps: Mutex<HashMap<i64, i64>>,
+}
+
+impl BlockTimestamps {
+    pub fn get(&self, block: i64) -> Option<i64> {
+        self.timestamps.lock().unwrap().get(&block).copied()
+    }
+
+    pub fn insert(&self, block: i64, timestamp: i64) {
+        let mut timestamps = self.timestamps.lock().unwrap();
+        if timestamps.len() >= MAX_CACHED_BLOCKS {
+            timestamps.clear();
+        }
+        timestamps.insert(block, timestamp);
+    }
+}
+
+pub fn is_stale(verified_at: i64, now: i64, stale_after_secs: i64) -> bool {
+    now - verified_at > stale_after_secs
+}
+
+pub async fn get_block_timestamp(state: &AppState, block: i64) -> Option<i64> {
+    if let Some(timestamp) = state.block_timestamps.get(block) {
+        return Some(timestamp);
+    }
+    let provider = JsonRpcClient::new(HttpTransport::new(
+        Url::parse(&state.conf.variables.rpc_url).ok()?,
+    ));
+    let call = provider.get_block_with_tx_hashes(BlockId::Number(block as u64));
+    let timestamp = match state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_getBlockWithTxHashes", call)
+        .await
+        .ok()?
+    {
+        MaybePendingBlockWithTxHashes::Block(block) => block.timestamp,
+        // not final yet, so not cached
+        MaybePendingBlockWithTxHashes::PendingBlock(block) => return Some(block.timestamp as i64),
+    } as i64;
+    state.block_timestamps.insert(block, timestamp);
+    Some(timestamp)
+}
+
+/// Verification time and staleness of a verifier data written at `block`
+pub async fn get_freshness(state: &AppState, block: Option<i64>) -> (Option<i64>, Option<bool>) {
+    let verified_at = match block {
+        Some(block) => get_block_timestamp(state, block).await,
+        None => None,
+    };
+    let now = chrono::Utc::now().timestamp();
+    let stale_after = state.conf.verifier_freshness.stale_after_secs;
+    (
+        verified_at,
+        verified_at.map(|verified_at| is_stale(verified_at, now, stale_after)),
+    )
+}
+
+pub struct SocialFreshness {
+    pub field: &'static str,
+    pub verified_at: i64,
+    pub is_stale: bool,
+}
+
+/// Freshness of the social verifications of an identity, by the configured verifiers
+pub async fn get_social_freshness(state: &AppState, id: &FieldElement) -> Vec<SocialFreshness> {
+    let fields = [
+        ("twitter", short_string!("twitter")),
+        ("discord", short_string!("discord")),
+        ("github", short_string!("github")),
+    ];
+    let filter = doc! {
+        "id": to_hex(id),
+        "_cursor.to": null,
+        "verifier": { "$in": state.conf.contracts.verifiers.iter().map(to_hex).collect::<Vec<_>>() },
+        "field": { "$in": fields.iter().map(|(_, field)| to_hex(field)).collect::<Vec<_>>() },
+        "data": { "$ne": null },
+    };
+    let mut cursor = match state
+        .starknetid_db
+        .collection::<Document>("id_verifier_data")
+        .find(filter, None)
+        .await
+    {
+        Ok(cursor) => cursor,
+        Err(_) => return vec![],
+    };
+    let mut latest: HashMap<&'static str, (i64, bool)> = HashMap::new();
+    while let Some(Ok(doc)) = cursor.next().await {
+        let name = fields
+            .iter()
+            .find(|(_, field)| Some(to_hex(field).as_str()) == doc.get_str("field").ok())
+            .map(|(name, _)| *name);
+        let block = doc
+            .get_document("_cursor")
+            .and_then(|c| c.get_i64("from"))
+            .ok();
+        if let (Some(name), (Some(verified_at), Some(is_stale))) =
+            (name, get_freshness(state, block).await)
+        {
+            // the latest verification wins when several verifiers wrote the field
+            let entry = latest.entry(name).or_insert((verified_at, is_stale));
+            if verified_at > entry.0 {
+                *entry = (verified_at, is_stale);
+            }
+        }
+    }
+    let mut freshness: Vec<SocialFreshness> = latest
+        .into_iter()
+        .map(|(field, (verified_at, is_stale))| SocialFreshness {
+            field,
+            verified_at,
+            is_stale,
+        })
+        .collect();
+    freshness.sort_by_key(|f| f.field);
+    freshness
+}
+
+impl IdentityData {
+    /// Freshness of the verifier data then of the extended ones, looked up concurrently
+    pub async fn get_verifier_freshness(
+        &self,
+        state: &AppState,
+    ) -> Vec<(Option<i64>, Option<bool>)> {
+        let blocks = self
+            .verifier_data
+            .iter()
+            .map(|data| data.block)
+            .chain(self.extended_verifier_data.iter().map(|data| data.block));
+        join_all(blocks.map(|block| get_freshness(state, block))).await
+    }
+
+    pub fn set_verifier_freshness(&mut self, freshness: Vec<(Option<i64>, Option<bool>)>) {
+        let mut freshness = freshness.into_iter();
+        for data in &mut self.verifier_data {
+            (data.verified_at, data.is_stale) = freshness.next().unwrap_or_default();
+        }
+        for data in &mut self.extended_verifier_data {
+            (data.verified_at, data.is_stale) = freshness.next().unwrap_or_default();
+        }
+    }
+}
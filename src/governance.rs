@@ -0,0 +1,230 @@
+use anyhow::Result;
+use mongodb::bson::doc;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::{
+        crypto::pedersen_hash,
+        types::FieldElement,
+        utils::{cairo_short_string_to_felt, parse_cairo_short_string},
+    },
+    macros::short_string,
+};
+use starknet_crypto::get_public_key;
+
+use crate::{
+    config::{Contracts, Governance},
+    ecdsa_sign::non_determinist_ecdsa_sign,
+    storage::{FindSpec, Storage},
+    utils::to_hex,
+};
+
+// field written by the proof of personhood verifier
+const POP_FIELD: &str = "proof_of_personhood";
+const SECONDS_PER_DAY: i64 = 24 * 3600;
+const SECONDS_PER_YEAR: f64 = 365.0 * 24.0 * 3600.0;
+
+/// Voting power granted for the Starknet ID assets of an address
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PowerRule {
+    // holds a domain registered at least this many days ago
+    DomainHeld { min_days: i64, power: u64 },
+    // one of its identities has this field verified, eg: "twitter"
+    Verified { field: String, power: u64 },
+    // one of its identities passed the proof of personhood
+    ProofOfPersonhood { power: u64 },
+    // multiplies the power by 1 + per_year for each year of its oldest domain
+    Tenure { per_year: f64, max_multiplier: f64 },
+}
+
+/// Indexed assets of an address the rules are evaluated on
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Holdings {
+    // creation dates of the live domains of its identities
+    pub domain_creations: Vec<i64>,
+    pub verified_fields: Vec<String>,
+    pub pop_verified: bool,
+}
+
+/// Power contributed by a rule, the tenure reports its multiplier instead
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct RuleResult {
+    pub rule: PowerRule,
+    pub power: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct VotingPower {
+    pub power: u64,
+    pub breakdown: Vec<RuleResult>,
+}
+
+/// Signed statement of the power of an address, verifiable against `public_key`
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Attestation {
+    pub addr: String,
+    pub power: u64,
+    pub issued_at: i64,
+    pub expiry: i64,
+    pub message_hash: String,
+    pub public_key: String,
+    pub r: String,
+    pub s: String,
+}
+
+/// Power of an address: the sum of the rules it fulfills, times the tenure multipliers
+pub fn compute_power(rules: &[PowerRule], holdings: &Holdings, now: i64) -> VotingPower {
+    let oldest = holdings.domain_creations.iter().min().copied();
+    let mut breakdown = vec![];
+    let mut base = 0u64;
+    let mut multiplier = 1.0;
+    for rule in rules {
+        let (power, rule_multiplier) = match rule {
+            PowerRule::DomainHeld { min_days, power } => {
+                let held = oldest.is_some_and(|date| now - date >= min_days * SECONDS_PER_DAY);
+                (if held { *power } else { 0 }, None)
+            }
+            PowerRule::Verified { field, power } => (
+                if holdings.verified_fields.contains(field) {
+                    *power
+                } else {
+                    0
+                },
+                None,
+            ),
+            PowerRule::ProofOfPersonhood { power } => {
+                (if holdings.pop_verified { *power } else { 0 }, None)
+            }
+            PowerRule::Tenure {
+                per_year,
+                max_multiplier,
+            } => {
+                let years =
+                    oldest.map_or(0.0, |date| (now - date).max(0) as f64 / SECONDS_PER_YEAR);
+                let tenure = (1.0 + per_year * years).min(*max_multiplier).max(1.0);
+                multiplier *= tenure;
+                (0, Some(tenure))
+            }
+        };
+        base += power;
+        breakdown.push(RuleResult {
+            rule: rule.clone(),
+            power,
+            multiplier: rule_multiplier,
+        });
+    }
+    VotingPower {
+        power: (base as f64 * multiplier).floor() as u64,
+        breakdown,
+    }
+}
+
+/// Live domains and verifications of the identities owned by the address
+pub async fn get_holdings(
+    storage: &dyn Storage,
+    contracts: &Contracts,
+    addr: &FieldElement,
+) -> Result<Holdings> {
+    let ids: Vec<String> = storage
+        .find(
+            "id_owners",
+            doc! { "owner": to_hex(addr), "_cursor.to": null },
+            FindSpec::default(),
+        )
+        .await?
+        .iter()
+        .filter_map(|doc| doc.get_str("id").ok().map(String::from))
+        .collect();
+    if ids.is_empty() {
+        return Ok(Holdings::default());
+    }
+
+    let domain_creations = storage
+        .find(
+            "domains",
+            doc! { "id": { "$in": &ids }, "_cursor.to": null },
+            FindSpec::default(),
+        )
+        .await?
+        .iter()
+        .filter_map(|doc| doc.get_i64("creation_date").ok())
+        .collect();
+
+    let verifiers: Vec<String> = contracts.verifiers.iter().map(to_hex).collect();
+    let verified_docs = storage
+        .find(
+            "id_verifier_data",
+            doc! {
+                "id": { "$in": &ids },
+                "verifier": { "$in": verifiers },
+                "data": { "$ne": null },
+                "_cursor.to": null,
+            },
+            FindSpec::default(),
+        )
+        .await?;
+    let mut verified_fields = vec![];
+    for doc in &verified_docs {
+        let field = doc
+            .get_str("field")
+            .ok()
+            .and_then(|field| FieldElement::from_hex_be(field).ok())
+            .and_then(|field| parse_cairo_short_string(&field).ok());
+        if let Some(field) = field.filter(|field| !verified_fields.contains(field)) {
+            verified_fields.push(field);
+        }
+    }
+
+    let pop_verified = storage
+        .count(
+            "id_verifier_data",
+            doc! {
+                "id": { "$in": &ids },
+                "verifier": to_hex(&contracts.pop_verifier),
+                "field": to_hex(&cairo_short_string_to_felt(POP_FIELD)?),
+                "_cursor.to": null,
+            },
+        )
+        .await?
+        > 0;
+    Ok(Holdings {
+        domain_creations,
+        verified_fields,
+        pop_verified,
+    })
+}
+
+/// Message signed for the power of the address, valid until `expiry`
+pub fn get_attestation_hash(addr: &FieldElement, power: u64, expiry: i64) -> FieldElement {
+    pedersen_hash(
+        &pedersen_hash(
+            &pedersen_hash(&short_string!("governance power"), addr),
+            &FieldElement::from(power),
+        ),
+        &FieldElement::from(expiry.max(0) as u64),
+    )
+}
+
+/// Attestation of the power signed with the governance key, valid for a while
+pub fn sign_attestation(
+    conf: &Governance,
+    addr: &FieldElement,
+    power: u64,
+    now: i64,
+) -> Result<Attestation> {
+    let expiry = now + conf.attestation_validity_secs;
+    let message_hash = get_attestation_hash(addr, power, expiry);
+    let signature = non_determinist_ecdsa_sign(&conf.private_key, &message_hash)?;
+    Ok(Attestation {
+        addr: to_hex(addr),
+        power,
+        issued_at: now,
+        expiry,
+        message_hash: to_hex(&message_hash),
+        public_key: to_hex(&get_public_key(&conf.private_key)),
+        r: to_hex(&signature.r),
+        s: to_hex(&signature.s),
+    })
+}
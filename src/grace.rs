@@ -0,0 +1,55 @@
+use serde::Serialize;
+
+use crate::config::GracePolicy;
+
+/// Where a domain stands relative to its expiry
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExpiryStatus {
+    Active,
+    // expired since less than the grace period, only the owner can renew it
+    Grace,
+    Expired,
+}
+
+impl ExpiryStatus {
+    pub fn is_active(&self) -> bool {
+        *self == ExpiryStatus::Active
+    }
+}
+
+/// How the endpoints show a domain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DomainDisplay {
+    pub status: ExpiryStatus,
+    // false when the endpoints answer as if the domain wasn't registered
+    pub resolves: bool,
+}
+
+/// Status of a domain expiring at `expiry`, the domains without one (subdomains) don't expire
+pub fn get_expiry_status(expiry: Option<i64>, now: i64, grace_secs: i64) -> ExpiryStatus {
+    match expiry {
+        Some(expiry) if now < expiry => ExpiryStatus::Active,
+        Some(expiry) if now < expiry + grace_secs => ExpiryStatus::Grace,
+        Some(_) => ExpiryStatus::Expired,
+        None => ExpiryStatus::Active,
+    }
+}
+
+impl GracePolicy {
+    pub fn resolves(&self, status: ExpiryStatus) -> bool {
+        match status {
+            ExpiryStatus::Active => true,
+            ExpiryStatus::Grace => self.resolve_in_grace,
+            ExpiryStatus::Expired => self.resolve_expired,
+        }
+    }
+
+    pub fn get_display(&self, expiry: Option<i64>, now: i64) -> DomainDisplay {
+        let status = get_expiry_status(expiry, now, self.grace_secs);
+        DomainDisplay {
+            status,
+            resolves: self.resolves(status),
+        }
+    }
+}
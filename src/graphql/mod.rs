@@ -0,0 +1,54 @@
+//! GraphQL view over the indexed collections, integrators pick the fields they need in a
+//! single request instead of chaining the REST endpoints
+
+pub mod types;
+
+use std::sync::Arc;
+
+use async_graphql::{Context, EmptyMutation, EmptySubscription, Object, Result, Schema};
+use mongodb::bson::doc;
+
+use crate::{models::AppState, utils::normalize_domain};
+use types::{find_domain, find_identities, parse_address, parse_felt, Domain, Identity};
+
+// bounds the cost of a query, each nested relation is a database round trip
+const MAX_DEPTH: usize = 8;
+const MAX_COMPLEXITY: usize = 500;
+
+pub type ApiSchema = Schema<Query, EmptyMutation, EmptySubscription>;
+
+pub struct Query;
+
+#[Object]
+impl Query {
+    /// Live domain with this name
+    async fn domain(&self, ctx: &Context<'_>, name: String) -> Result<Option<Domain>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        find_domain(state, doc! { "domain": normalize_domain(&name) }).await
+    }
+
+    /// Identity by its token id, hex or decimal
+    async fn identity(&self, ctx: &Context<'_>, id: String) -> Result<Option<Identity>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let id = parse_felt(&id)?;
+        Ok(find_identities(state, doc! { "id": id }).await?.pop())
+    }
+
+    /// Identities owned by an address
+    async fn identities(&self, ctx: &Context<'_>, owner: String) -> Result<Vec<Identity>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        find_identities(state, doc! { "owner": parse_address(&owner)? }).await
+    }
+}
+
+lazy_static::lazy_static! {
+    static ref SCHEMA: ApiSchema = Schema::build(Query, EmptyMutation, EmptySubscription)
+        .limit_depth(MAX_DEPTH)
+        .limit_complexity(MAX_COMPLEXITY)
+        .finish();
+}
+
+/// Schema shared by the requests, the state is attached to each of them
+pub fn get_schema() -> &'static ApiSchema {
+    &SCHEMA
+}
@@ -0,0 +1,244 @@
+use std::sync::Arc;
+
+use async_graphql::{ComplexObject, Context, Result, SimpleObject};
+use futures::TryStreamExt;
+use mongodb::bson::{doc, Document};
+use starknet::core::{
+    types::FieldElement,
+    utils::{cairo_short_string_to_felt, parse_cairo_short_string},
+};
+
+use crate::{models::AppState, utils::to_hex};
+
+const RENEWAL_COLLECTIONS: [(&str, bool); 2] = [
+    ("auto_renew_flows", false),
+    ("auto_renew_flows_altcoins", true),
+];
+
+/// Hex or decimal felt, as stored in the collections
+pub fn parse_felt(value: &str) -> Result<String> {
+    let felt = if value.starts_with("0x") {
+        FieldElement::from_hex_be(value)
+    } else {
+        FieldElement::from_dec_str(value)
+    };
+    Ok(to_hex(
+        &felt.map_err(|_| format!("Invalid felt: {}", value))?,
+    ))
+}
+
+pub fn parse_address(value: &str) -> Result<String> {
+    parse_felt(value)
+}
+
+// fields can be given as their short string name, e.g. "twitter"
+pub fn parse_field(value: &str) -> Result<String> {
+    if value.starts_with("0x") {
+        return parse_felt(value);
+    }
+    cairo_short_string_to_felt(value)
+        .map(|felt| to_hex(&felt))
+        .map_err(|_| format!("Invalid field: {}", value).into())
+}
+
+fn get_field_name(field: &str) -> Option<String> {
+    FieldElement::from_hex_be(field)
+        .ok()
+        .and_then(|felt| parse_cairo_short_string(&felt).ok())
+}
+
+async fn find_live(
+    state: &AppState,
+    collection: &str,
+    mut filter: Document,
+) -> Result<Vec<Document>> {
+    filter.insert("_cursor.to", mongodb::bson::Bson::Null);
+    Ok(state
+        .starknetid_db
+        .collection::<Document>(collection)
+        .find(filter, None)
+        .await?
+        .try_collect()
+        .await?)
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Domain {
+    pub domain: String,
+    pub id: Option<String>,
+    // address the domain resolves to natively
+    pub target: Option<String>,
+    // address whose main domain this is
+    pub rev_address: Option<String>,
+    pub expiry: Option<i64>,
+    pub creation_date: Option<i64>,
+}
+
+#[derive(SimpleObject)]
+pub struct Renewal {
+    pub renewer_address: String,
+    pub enabled: bool,
+    // paid with an altcoin rather than ETH
+    pub altcoin: bool,
+}
+
+#[derive(SimpleObject)]
+#[graphql(complex)]
+pub struct Identity {
+    pub id: String,
+    pub owner: String,
+    // the domain of this identity is the main domain of its owner
+    pub main: bool,
+}
+
+#[derive(SimpleObject)]
+pub struct UserData {
+    pub field: String,
+    pub field_name: Option<String>,
+    pub data: String,
+}
+
+#[derive(SimpleObject)]
+pub struct VerifierData {
+    pub verifier: String,
+    pub field: String,
+    pub field_name: Option<String>,
+    pub data: Option<String>,
+    pub extended_data: Option<Vec<String>>,
+    // block the verification was written at
+    pub block: Option<i64>,
+}
+
+fn get_string(doc: &Document, key: &str) -> Option<String> {
+    doc.get_str(key).ok().map(|value| value.to_string())
+}
+
+fn to_domain(doc: &Document) -> Option<Domain> {
+    Some(Domain {
+        domain: get_string(doc, "domain")?,
+        id: get_string(doc, "id"),
+        target: get_string(doc, "legacy_address"),
+        rev_address: get_string(doc, "rev_address"),
+        expiry: doc.get_i64("expiry").ok(),
+        creation_date: doc.get_i64("creation_date").ok(),
+    })
+}
+
+pub async fn find_domain(state: &AppState, filter: Document) -> Result<Option<Domain>> {
+    Ok(find_live(state, "domains", filter)
+        .await?
+        .first()
+        .and_then(to_domain))
+}
+
+pub async fn find_identities(state: &AppState, filter: Document) -> Result<Vec<Identity>> {
+    Ok(find_live(state, "id_owners", filter)
+        .await?
+        .iter()
+        .filter_map(|doc| {
+            Some(Identity {
+                id: get_string(doc, "id")?,
+                owner: get_string(doc, "owner")?,
+                main: doc.get_bool("main").unwrap_or_default(),
+            })
+        })
+        .collect())
+}
+
+#[ComplexObject]
+impl Domain {
+    async fn identity(&self, ctx: &Context<'_>) -> Result<Option<Identity>> {
+        let id = match &self.id {
+            Some(id) => id,
+            None => return Ok(None),
+        };
+        let state = ctx.data::<Arc<AppState>>()?;
+        Ok(find_identities(state, doc! { "id": id }).await?.pop())
+    }
+
+    /// Auto renewal subscriptions of the domain
+    async fn renewals(&self, ctx: &Context<'_>) -> Result<Vec<Renewal>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let mut renewals = vec![];
+        for (collection, altcoin) in RENEWAL_COLLECTIONS {
+            let docs = find_live(state, collection, doc! { "domain": &self.domain }).await?;
+            renewals.extend(docs.iter().filter_map(|doc| {
+                Some(Renewal {
+                    renewer_address: get_string(doc, "renewer_address")?,
+                    enabled: doc.get_bool("enabled").unwrap_or_default(),
+                    altcoin,
+                })
+            }));
+        }
+        Ok(renewals)
+    }
+}
+
+#[ComplexObject]
+impl Identity {
+    /// Root domain of the identity
+    async fn domain(&self, ctx: &Context<'_>) -> Result<Option<Domain>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        find_domain(state, doc! { "id": &self.id }).await
+    }
+
+    async fn user_data(&self, ctx: &Context<'_>, field: Option<String>) -> Result<Vec<UserData>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let mut filter = doc! { "id": &self.id };
+        if let Some(field) = field {
+            filter.insert("field", parse_field(&field)?);
+        }
+        Ok(find_live(state, "id_user_data", filter)
+            .await?
+            .iter()
+            .filter_map(|doc| {
+                let field = get_string(doc, "field")?;
+                Some(UserData {
+                    field_name: get_field_name(&field),
+                    field,
+                    data: get_string(doc, "data")?,
+                })
+            })
+            .collect())
+    }
+
+    async fn verifier_data(
+        &self,
+        ctx: &Context<'_>,
+        verifier: Option<String>,
+        field: Option<String>,
+    ) -> Result<Vec<VerifierData>> {
+        let state = ctx.data::<Arc<AppState>>()?;
+        let mut filter = doc! { "id": &self.id };
+        if let Some(verifier) = verifier {
+            filter.insert("verifier", parse_address(&verifier)?);
+        }
+        if let Some(field) = field {
+            filter.insert("field", parse_field(&field)?);
+        }
+        Ok(find_live(state, "id_verifier_data", filter)
+            .await?
+            .iter()
+            .filter_map(|doc| {
+                let field = get_string(doc, "field")?;
+                Some(VerifierData {
+                    verifier: get_string(doc, "verifier")?,
+                    field_name: get_field_name(&field),
+                    field,
+                    data: get_string(doc, "data"),
+                    extended_data: doc.get_array("extended_data").ok().map(|values| {
+                        values
+                            .iter()
+                            .filter_map(|value| value.as_str().map(|value| value.to_string()))
+                            .collect()
+                    }),
+                    block: doc
+                        .get_document("_cursor")
+                        .and_then(|cursor| cursor.get_i64("from"))
+                        .ok(),
+                })
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,323 @@
+use anyhow::{anyhow, Result};
+use mongodb::bson::{doc, Document};
+use reqwest::Url;
+use serde::Serialize;
+use starknet::{
+    core::types::FieldElement,
+    providers::{jsonrpc::HttpTransport, JsonRpcClient},
+};
+
+use crate::{
+    integrity::get_target,
+    models::AppState,
+    storage::{FindSpec, Storage},
+    utils::{cursor_at_block, get_block_at_timestamp, to_hex},
+};
+
+const STARKNET_FIELD: &str = "0x000000000000000000000000000000000000000000000000737461726b6e6574";
+// versions read per collection to build a timeline
+const MAX_VERSIONS: i64 = 1000;
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum HistoryEvent {
+    Registered {
+        id: Option<String>,
+        expiry: Option<i64>,
+    },
+    // the domain moved to another identity
+    Transferred {
+        from_id: Option<String>,
+        to_id: Option<String>,
+    },
+    // the identity holding the domain changed hands
+    IdentityTransferred {
+        id: String,
+        from: Option<String>,
+        to: Option<String>,
+    },
+    Renewed {
+        expiry: Option<i64>,
+    },
+    ResolverChanged {
+        resolver: Option<String>,
+    },
+    SubdomainCreated {
+        subdomain: String,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct HistoryEntry {
+    pub block: i64,
+    #[serde(flatten)]
+    pub event: HistoryEvent,
+}
+
+/// Resolution of a native domain as it was indexed at a past block
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ResolutionAt {
+    pub domain: String,
+    pub addr: Option<String>,
+    pub id: Option<String>,
+    pub domain_expiry: Option<i64>,
+}
+
+async fn find_at(
+    storage: &dyn Storage,
+    collection: &str,
+    block: i64,
+    filter: Document,
+) -> Result<Option<Document>> {
+    let mut at_block = cursor_at_block(block);
+    at_block.extend(filter);
+    storage.find_one(collection, at_block).await
+}
+
+/// Block a historical lookup is made at, given either directly or as a timestamp
+pub async fn get_lookup_block(
+    state: &AppState,
+    block: Option<u64>,
+    timestamp: Option<u64>,
+) -> Result<i64> {
+    match (block, timestamp) {
+        (Some(block), None) => Ok(block as i64),
+        (None, Some(timestamp)) => {
+            if timestamp > chrono::Utc::now().timestamp() as u64 {
+                return Err(anyhow!("Timestamp is in the future"));
+            }
+            let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(
+                &state.conf.variables.rpc_url,
+            )?));
+            Ok(get_block_at_timestamp(&provider, timestamp).await? as i64)
+        }
+        _ => Err(anyhow!("Either a block or a timestamp is required")),
+    }
+}
+
+// target of the domain document at the block: its legacy address, else the starknet field
+// of its identity, else the owner of its identity
+async fn get_target_at(
+    storage: &dyn Storage,
+    domain_doc: &Document,
+    block: i64,
+) -> Result<ResolutionAt> {
+    let id = domain_doc.get_str("id").ok().map(String::from);
+    let (owner, starknet_field) = match &id {
+        Some(id) => (
+            find_at(storage, "id_owners", block, doc! { "id": id }).await?,
+            find_at(
+                storage,
+                "id_user_data",
+                block,
+                doc! { "id": id, "field": STARKNET_FIELD },
+            )
+            .await?,
+        ),
+        None => (None, None),
+    };
+    Ok(ResolutionAt {
+        domain: domain_doc.get_str("domain").unwrap_or_default().to_string(),
+        addr: get_target(
+            domain_doc.get_str("legacy_address").ok(),
+            starknet_field
+                .as_ref()
+                .and_then(|doc| doc.get_str("data").ok()),
+            owner.as_ref().and_then(|doc| doc.get_str("owner").ok()),
+        ),
+        id,
+        domain_expiry: domain_doc.get_i64("expiry").ok(),
+    })
+}
+
+/// Target of a native domain at the block, None when it wasn't registered yet
+pub async fn domain_to_addr_at(
+    storage: &dyn Storage,
+    domain: &str,
+    block: i64,
+) -> Result<Option<ResolutionAt>> {
+    match find_at(storage, "domains", block, doc! { "domain": domain }).await? {
+        Some(domain_doc) => Ok(Some(get_target_at(storage, &domain_doc, block).await?)),
+        None => Ok(None),
+    }
+}
+
+/// Domain shown for the address at the block: its reverse record when the domain resolved
+/// back to it, else the domain of its main identity
+pub async fn addr_to_domain_at(
+    storage: &dyn Storage,
+    addr: &FieldElement,
+    block: i64,
+) -> Result<Option<ResolutionAt>> {
+    let addr = to_hex(addr);
+    if let Some(domain_doc) =
+        find_at(storage, "domains", block, doc! { "rev_address": &addr }).await?
+    {
+        let resolution = get_target_at(storage, &domain_doc, block).await?;
+        if resolution.addr.as_deref() == Some(addr.as_str()) {
+            return Ok(Some(resolution));
+        }
+    }
+    let main_id = find_at(
+        storage,
+        "id_owners",
+        block,
+        doc! { "owner": &addr, "main": true },
+    )
+    .await?;
+    let id = match main_id.as_ref().and_then(|doc| doc.get_str("id").ok()) {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    match find_at(storage, "domains", block, doc! { "id": id }).await? {
+        Some(domain_doc) => Ok(Some(get_target_at(storage, &domain_doc, block).await?)),
+        None => Ok(None),
+    }
+}
+
+fn get_cursor(doc: &Document, field: &str) -> Option<i64> {
+    doc.get_document("_cursor").ok()?.get_i64(field).ok()
+}
+
+fn get_optional_str(doc: &Document, field: &str) -> Option<String> {
+    doc.get_str(field).ok().map(String::from)
+}
+
+/// Events between two successive versions of a domain document, a version which doesn't
+/// follow the previous one right away is a new registration
+pub fn get_version_events(previous: Option<&Document>, version: &Document) -> Vec<HistoryEvent> {
+    let (id, expiry) = (
+        get_optional_str(version, "id"),
+        version.get_i64("expiry").ok(),
+    );
+    let previous = match previous {
+        Some(previous) if get_cursor(previous, "to") == get_cursor(version, "from") => previous,
+        _ => return vec![HistoryEvent::Registered { id, expiry }],
+    };
+    let mut events = vec![];
+    let previous_id = get_optional_str(previous, "id");
+    if previous_id != id {
+        events.push(HistoryEvent::Transferred {
+            from_id: previous_id,
+            to_id: id,
+        });
+    }
+    if expiry > previous.get_i64("expiry").ok() {
+        events.push(HistoryEvent::Renewed { expiry });
+    }
+    let resolver = get_optional_str(version, "resolver");
+    if resolver != get_optional_str(previous, "resolver") {
+        events.push(HistoryEvent::ResolverChanged { resolver });
+    }
+    events
+}
+
+// owner changes of the identity while it held the domain, between the blocks `from` and `to`
+fn get_owner_events(
+    id: &str,
+    owners: &[Document],
+    from: i64,
+    to: Option<i64>,
+) -> Vec<HistoryEntry> {
+    owners
+        .windows(2)
+        .filter_map(|pair| {
+            let block = get_cursor(&pair[1], "from")?;
+            let owner = get_optional_str(&pair[1], "owner");
+            let previous_owner = get_optional_str(&pair[0], "owner");
+            let held = block > from && !matches!(to, Some(to) if block >= to);
+            (held && owner != previous_owner).then(|| HistoryEntry {
+                block,
+                event: HistoryEvent::IdentityTransferred {
+                    id: id.to_string(),
+                    from: previous_owner,
+                    to: owner,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Timeline of a domain, ordered by block: its registrations, transfers, renewals,
+/// resolver changes and the creation of its direct subdomains
+pub async fn get_domain_history(storage: &dyn Storage, domain: &str) -> Result<Vec<HistoryEntry>> {
+    let by_block = || FindSpec {
+        sort: Some(doc! { "_cursor.from": 1 }),
+        limit: Some(MAX_VERSIONS),
+        ..Default::default()
+    };
+    let versions = storage
+        .find("domains", doc! { "domain": domain }, by_block())
+        .await?;
+    let mut entries = vec![];
+    for (i, version) in versions.iter().enumerate() {
+        let block = match get_cursor(version, "from") {
+            Some(block) => block,
+            None => continue,
+        };
+        let previous = i.checked_sub(1).map(|i| &versions[i]);
+        entries.extend(
+            get_version_events(previous, version)
+                .into_iter()
+                .map(|event| HistoryEntry { block, event }),
+        );
+    }
+
+    // the identity owners are read over the periods each identity held the domain
+    let mut ids: Vec<&str> = versions
+        .iter()
+        .filter_map(|version| version.get_str("id").ok())
+        .collect();
+    ids.sort_unstable();
+    ids.dedup();
+    for id in ids {
+        let owners = storage
+            .find("id_owners", doc! { "id": id }, by_block())
+            .await?;
+        for version in versions
+            .iter()
+            .filter(|version| version.get_str("id").ok() == Some(id))
+        {
+            if let Some(from) = get_cursor(version, "from") {
+                entries.extend(get_owner_events(
+                    id,
+                    &owners,
+                    from,
+                    get_cursor(version, "to"),
+                ));
+            }
+        }
+    }
+
+    let subdomains = storage
+        .find(
+            "domains",
+            doc! { "domain": { "$regex": format!("^[^.]+\\.{}$", regex::escape(domain)) } },
+            FindSpec {
+                sort: Some(doc! { "domain": 1, "_cursor.from": 1 }),
+                limit: Some(MAX_VERSIONS),
+                ..Default::default()
+            },
+        )
+        .await?;
+    let mut last_subdomain = None;
+    for version in &subdomains {
+        let subdomain = version.get_str("domain").ok();
+        if subdomain == last_subdomain {
+            continue;
+        }
+        last_subdomain = subdomain;
+        if let (Some(subdomain), Some(block)) = (subdomain, get_cursor(version, "from")) {
+            entries.push(HistoryEntry {
+                block,
+                event: HistoryEvent::SubdomainCreated {
+                    subdomain: subdomain.to_string(),
+                },
+            });
+        }
+    }
+
+    // stable, so the events of a block keep the order they were found in
+    entries.sort_by_key(|entry| entry.block);
+    Ok(entries)
+}
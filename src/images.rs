@@ -0,0 +1,222 @@
+use std::{
+    io::Cursor,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{anyhow, Result};
+use axum::http::{
+    header::{CONTENT_SECURITY_POLICY, CONTENT_TYPE, X_CONTENT_TYPE_OPTIONS},
+    HeaderMap, HeaderValue,
+};
+use bytes::{Bytes, BytesMut};
+use image::{imageops::FilterType, ImageFormat};
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use tokio::{fs, net::lookup_host};
+
+use crate::{
+    avatars::get_client,
+    config::Config,
+    models::AppState,
+    webhooks::{is_public_ip, validate_url},
+};
+
+// cache objects of the exports bucket are stored under this prefix
+const BUCKET_PREFIX: &str = "images/";
+
+/// Image bytes with the content type they are served with
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedImage {
+    pub content_type: String,
+    pub bytes: Bytes,
+}
+
+/// Content type of the image bytes, svg has no magic number so it is sniffed
+pub fn get_content_type(bytes: &[u8]) -> Option<String> {
+    if let Ok(format) = image::guess_format(bytes) {
+        return Some(format.to_mime_type().to_string());
+    }
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).to_lowercase();
+    head.contains("<svg").then(|| "image/svg+xml".to_string())
+}
+
+/// Name of the cached variant of an image url, its original when `width` is None
+pub fn get_cache_key(url: &str, width: Option<u32>) -> String {
+    let hash = hex::encode(Sha256::digest(url.as_bytes()));
+    match width {
+        Some(width) => format!("{}_w{}", hash, width),
+        None => hash,
+    }
+}
+
+/// Png thumbnail fitting in a `width` square, the aspect ratio is kept and smaller
+/// images aren't upscaled
+pub fn resize(bytes: &[u8], width: u32) -> Result<Vec<u8>> {
+    let image = image::load_from_memory(bytes)?;
+    let image = if image.width() > width || image.height() > width {
+        image.resize(width, width, FilterType::Lanczos3)
+    } else {
+        image
+    };
+    let mut resized = Cursor::new(vec![]);
+    image.write_to(&mut resized, ImageFormat::Png)?;
+    Ok(resized.into_inner())
+}
+
+/// Urls of the configured gateways are trusted, the others must point to a public host so an
+/// avatar can't make the server call the internal network
+pub async fn check_fetchable(config: &Config, url: &str) -> Result<()> {
+    if config
+        .variables
+        .ipfs_gateways
+        .iter()
+        .any(|gateway| url.starts_with(gateway.as_str()))
+    {
+        return Ok(());
+    }
+    validate_url(url).map_err(|e| anyhow!(e))?;
+    // nor through a host name resolving to it
+    let parsed = Url::parse(url)?;
+    let host = parsed
+        .host_str()
+        .unwrap_or_default()
+        .trim_matches(&['[', ']'][..]);
+    let port = parsed.port_or_known_default().unwrap_or(80);
+    for addr in lookup_host((host, port)).await? {
+        if !is_public_ip(addr.ip()) {
+            return Err(anyhow!("The url must point to a public host"));
+        }
+    }
+    Ok(())
+}
+
+/// Body of the response, refused once larger than `max_size` even when it has no content
+/// length
+pub async fn read_body(mut response: reqwest::Response, max_size: usize) -> Result<Bytes> {
+    if response
+        .content_length()
+        .is_some_and(|length| length as usize > max_size)
+    {
+        return Err(anyhow!("The response is too large"));
+    }
+    let mut body = BytesMut::new();
+    while let Some(chunk) = response.chunk().await? {
+        if body.len() + chunk.len() > max_size {
+            return Err(anyhow!("The response is too large"));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body.freeze())
+}
+
+/// Image served over http, refused when it isn't an image or exceeds the avatars size cap
+pub async fn fetch_image(config: &Config, url: &str) -> Result<CachedImage> {
+    check_fetchable(config, url).await?;
+    let response = get_client(config)?
+        .get(url)
+        .send()
+        .await?
+        .error_for_status()?;
+    let bytes = read_body(response, config.avatars.max_image_size).await?;
+    // the gateways often send a generic content type, the bytes are trusted instead
+    let content_type = get_content_type(&bytes).ok_or_else(|| anyhow!("Not an image"))?;
+    Ok(CachedImage {
+        content_type,
+        bytes,
+    })
+}
+
+/// Content type and sandboxing headers of a served image, an svg can carry scripts run on the
+/// origin of the api when opened directly
+pub fn set_image_headers(headers: &mut HeaderMap, image: &CachedImage) {
+    if let Ok(content_type) = HeaderValue::from_str(&image.content_type) {
+        headers.insert(CONTENT_TYPE, content_type);
+    }
+    headers.insert(
+        CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("default-src 'none'; sandbox"),
+    );
+    headers.insert(X_CONTENT_TYPE_OPTIONS, HeaderValue::from_static("nosniff"));
+}
+
+fn get_cache_path(config: &Config, key: &str) -> PathBuf {
+    PathBuf::from(&config.images.cache_dir).join(key)
+}
+
+async fn read_cache(state: &AppState, key: &str) -> Option<Bytes> {
+    let conf = &state.conf.images;
+    if conf.use_bucket {
+        // the bucket lifecycle rules expire the objects
+        let storage = state.export_storage.as_ref()?;
+        return storage
+            .get_object(&format!("{}{}", BUCKET_PREFIX, key))
+            .await
+            .ok()
+            .map(Bytes::from);
+    }
+    let path = get_cache_path(&state.conf, key);
+    let modified = fs::metadata(&path).await.ok()?.modified().ok()?;
+    let age = SystemTime::now()
+        .duration_since(modified)
+        .unwrap_or_default();
+    if age > Duration::from_secs(conf.cache_ttl_secs) {
+        return None;
+    }
+    fs::read(&path).await.ok().map(Bytes::from)
+}
+
+async fn write_cache(state: &AppState, key: &str, image: &CachedImage) -> Result<()> {
+    if state.conf.images.use_bucket {
+        let storage = state
+            .export_storage
+            .as_ref()
+            .ok_or_else(|| anyhow!("No bucket configured"))?;
+        return storage
+            .put_object(
+                &format!("{}{}", BUCKET_PREFIX, key),
+                &image.bytes,
+                &image.content_type,
+            )
+            .await;
+    }
+    fs::create_dir_all(&state.conf.images.cache_dir).await?;
+    // written aside then renamed so a concurrent read never sees a partial file
+    let path = get_cache_path(&state.conf, key);
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, &image.bytes).await?;
+    fs::rename(&tmp_path, &path).await?;
+    Ok(())
+}
+
+/// Image at the url, resized to `width` if given, served from the cache when possible
+pub async fn load_image(state: &AppState, url: &str, width: Option<u32>) -> Result<CachedImage> {
+    let key = get_cache_key(url, width);
+    if let Some(bytes) = read_cache(state, &key).await {
+        if let Some(content_type) = get_content_type(&bytes) {
+            return Ok(CachedImage {
+                content_type,
+                bytes,
+            });
+        }
+    }
+    let original = fetch_image(&state.conf, url).await?;
+    let image = match width {
+        // svg can't be rasterized, it scales anyway
+        Some(width) if original.content_type != "image/svg+xml" => {
+            let bytes = original.bytes.clone();
+            let resized = tokio::task::spawn_blocking(move || resize(&bytes, width)).await??;
+            CachedImage {
+                content_type: "image/png".to_string(),
+                bytes: Bytes::from(resized),
+            }
+        }
+        _ => original,
+    };
+    if let Err(e) = write_cache(state, &key, &image).await {
+        state
+            .logger
+            .warning(format!("images: unable to cache {}: {}", key, e));
+    }
+    Ok(image)
+}
@@ -0,0 +1,150 @@
+use std::{backtrace::Backtrace, cell::RefCell, panic::AssertUnwindSafe, sync::Arc};
+
+use axum::{
+    extract::{MatchedPath, State},
+    http::{Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+use futures::FutureExt;
+use mongodb::bson::{doc, Document};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    errors::{ErrorCode, ErrorDetails},
+    failures::{failure_response, get_state_degraded_components, FailureBody},
+    metrics::labeled,
+    models::AppState,
+    sampling::sanitize_query,
+};
+
+pub const INCIDENTS_COLLECTION: &str = "incidents";
+
+struct PanicInfo {
+    message: String,
+    backtrace: String,
+}
+
+thread_local! {
+    // filled by the panic hook right before the unwinding reaches `catch_panics`
+    static LAST_PANIC: RefCell<Option<PanicInfo>> = RefCell::new(None);
+}
+
+/// Keeps the default panic output and records the panic details for the incident
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let message = match info.payload().downcast_ref::<&str>() {
+            Some(message) => message.to_string(),
+            None => match info.payload().downcast_ref::<String>() {
+                Some(message) => message.clone(),
+                None => "unknown panic".to_string(),
+            },
+        };
+        let location = info
+            .location()
+            .map(|location| format!(" at {}:{}", location.file(), location.line()))
+            .unwrap_or_default();
+        LAST_PANIC.with(|last| {
+            *last.borrow_mut() = Some(PanicInfo {
+                message: format!("{}{}", message, location),
+                backtrace: Backtrace::force_capture().to_string(),
+            })
+        });
+        default_hook(info);
+    }));
+}
+
+/// Groups the incidents sharing a stack, it ignores the unresolved frames whose address
+/// varies between runs
+pub fn get_backtrace_hash(backtrace: &str) -> String {
+    let mut hasher = Sha256::new();
+    for line in backtrace.lines() {
+        let line = line.trim();
+        // frames are numbered as "12: symbol"
+        let frame = match line.split_once(": ") {
+            Some((number, symbol)) if number.chars().all(|c| c.is_ascii_digit()) => symbol,
+            _ => line,
+        };
+        if !frame.is_empty() && !frame.starts_with("0x") {
+            hasher.update(frame.as_bytes());
+        }
+    }
+    hex::encode(&hasher.finalize()[..8])
+}
+
+pub fn new_incident_id() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+// turns a panicking handler into a 500 instead of dropping the connection
+pub async fn catch_panics<B>(
+    State(state): State<Arc<AppState>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let route = req
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+    let method = req.method().to_string();
+    let query = req.uri().query().map(sanitize_query).unwrap_or_default();
+
+    match AssertUnwindSafe(next.run(req)).catch_unwind().await {
+        Ok(response) => response,
+        Err(_) => {
+            let panic = LAST_PANIC.with(|last| last.borrow_mut().take());
+            let (message, backtrace) = match panic {
+                Some(panic) => (panic.message, panic.backtrace),
+                None => ("unknown panic".to_string(), String::new()),
+            };
+            let incident_id = new_incident_id();
+            let backtrace_hash = get_backtrace_hash(&backtrace);
+            state
+                .metrics
+                .add(&labeled("panics_total", "endpoint", &route), 1);
+            state.logger.severe(format!(
+                "incident {}: {} {} panicked: {}",
+                incident_id, method, route, message
+            ));
+
+            let breadcrumb = doc! {
+                "incident_id": &incident_id,
+                "route": &route,
+                "method": method,
+                "query": query,
+                "message": message,
+                "backtrace_hash": backtrace_hash,
+                "backtrace": backtrace,
+                "created_at": chrono::Utc::now().timestamp(),
+            };
+            let incident_state = state.clone();
+            tokio::spawn(async move {
+                if let Err(e) = incident_state
+                    .starknetid_db
+                    .collection::<Document>(INCIDENTS_COLLECTION)
+                    .insert_one(breadcrumb, None)
+                    .await
+                {
+                    incident_state
+                        .logger
+                        .warning(format!("incidents: unable to save breadcrumb: {}", e));
+                }
+            });
+
+            failure_response(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                FailureBody {
+                    error: ErrorDetails {
+                        code: ErrorCode::InternalError,
+                        message: "Internal server error".to_string(),
+                    },
+                    retry_after_ms: None,
+                    degraded_components: get_state_degraded_components(&state),
+                    incident_id: Some(incident_id),
+                },
+            )
+        }
+    }
+}
@@ -0,0 +1,318 @@
+use anyhow::Result;
+use mongodb::bson::{doc, Bson, Document};
+use serde::Serialize;
+use starknet::{core::types::FieldElement, macros::selector};
+use starknet_id::encode;
+
+use crate::{
+    config::{Contracts, GracePolicy},
+    grace::ExpiryStatus,
+    simulation::Call,
+    storage::Storage,
+    utils::to_hex,
+};
+
+const STARKNET_FIELD: &str = "0x000000000000000000000000000000000000000000000000737461726b6e6574";
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Transaction which fixes a mismatch, to be sent by the address unless stated otherwise
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Fix {
+    SetAddressToDomain,
+    SetDomainToAddress,
+    // the owner of the identity has to point the domain to the address
+    RequestFromOwner,
+    SetMainId,
+    Renew,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Mismatch {
+    // no domain is shown for the address
+    NoReverseRecord,
+    // the reverse record points to a domain which resolves to another address
+    TargetMismatch {
+        domain: String,
+        target: Option<String>,
+    },
+    // the identity of the mismatched domain belongs to another address, which is the only
+    // one able to fix its target
+    OwnerMismatch {
+        domain: String,
+        owner: Option<String>,
+    },
+    // the main identity of the address isn't the one of its reverse record domain
+    MainIdMismatch {
+        main_id: String,
+        domain_id: String,
+    },
+    MainIdWithoutDomain {
+        main_id: String,
+    },
+    DomainExpired {
+        domain: String,
+        expiry: i64,
+        status: ExpiryStatus,
+    },
+}
+
+impl Mismatch {
+    pub fn get_fix(&self) -> Fix {
+        match self {
+            Mismatch::NoReverseRecord | Mismatch::MainIdWithoutDomain { .. } => {
+                Fix::SetAddressToDomain
+            }
+            Mismatch::TargetMismatch { .. } => Fix::SetDomainToAddress,
+            Mismatch::OwnerMismatch { .. } => Fix::RequestFromOwner,
+            Mismatch::MainIdMismatch { .. } => Fix::SetMainId,
+            Mismatch::DomainExpired { .. } => Fix::Renew,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Diagnosis {
+    #[serde(flatten)]
+    pub mismatch: Mismatch,
+    pub fix: Fix,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Integrity {
+    pub address: String,
+    pub domain: Option<String>,
+    pub id: Option<String>,
+    // address the domain resolves to
+    pub target: Option<String>,
+    pub owner: Option<String>,
+    pub consistent: bool,
+    pub mismatches: Vec<Diagnosis>,
+}
+
+async fn find_live(
+    storage: &dyn Storage,
+    collection: &str,
+    mut filter: Document,
+) -> Result<Option<Document>> {
+    filter.insert("_cursor.to", Bson::Null);
+    storage.find_one(collection, filter).await
+}
+
+/// Target of a native domain: its legacy address, else the starknet field of its identity,
+/// else the owner of its identity
+pub fn get_target(
+    legacy_address: Option<&str>,
+    starknet_field: Option<&str>,
+    owner: Option<&str>,
+) -> Option<String> {
+    legacy_address
+        .filter(|address| *address != ZERO_ADDRESS)
+        .or(starknet_field)
+        .or(owner)
+        .map(String::from)
+}
+
+/// Checks that the reverse record of `address`, the target of its domain and the owner of
+/// the identity all agree
+pub async fn check_integrity(
+    storage: &dyn Storage,
+    address: &FieldElement,
+    grace: &GracePolicy,
+    now: i64,
+) -> Result<Integrity> {
+    let address = to_hex(address);
+    let main_id = find_live(
+        storage,
+        "id_owners",
+        doc! { "owner": &address, "main": true },
+    )
+    .await?
+    .and_then(|doc| doc.get_str("id").ok().map(String::from));
+    let mut mismatches = vec![];
+
+    // the reverse record, else the domain of the main identity
+    let domain_doc = match find_live(storage, "domains", doc! { "rev_address": &address }).await? {
+        Some(doc) => Some(doc),
+        None => match &main_id {
+            Some(id) => {
+                let doc = find_live(storage, "domains", doc! { "id": id }).await?;
+                if doc.is_none() {
+                    mismatches.push(Mismatch::MainIdWithoutDomain {
+                        main_id: id.clone(),
+                    });
+                }
+                doc
+            }
+            None => None,
+        },
+    };
+    let domain_doc = match domain_doc {
+        Some(doc) => doc,
+        None => {
+            if mismatches.is_empty() {
+                mismatches.push(Mismatch::NoReverseRecord);
+            }
+            return Ok(Integrity {
+                address,
+                domain: None,
+                id: main_id,
+                target: None,
+                owner: None,
+                consistent: false,
+                mismatches: into_diagnoses(mismatches),
+            });
+        }
+    };
+
+    let domain = domain_doc.get_str("domain").unwrap_or_default().to_string();
+    let id = domain_doc.get_str("id").ok().map(String::from);
+    let (owner, starknet_field) = match &id {
+        Some(id) => (
+            find_live(storage, "id_owners", doc! { "id": id }).await?,
+            find_live(
+                storage,
+                "id_user_data",
+                doc! { "id": id, "field": STARKNET_FIELD },
+            )
+            .await?,
+        ),
+        None => (None, None),
+    };
+    let owner = owner.and_then(|doc| doc.get_str("owner").ok().map(String::from));
+    let starknet_field = starknet_field.and_then(|doc| doc.get_str("data").ok().map(String::from));
+    let target = get_target(
+        domain_doc.get_str("legacy_address").ok(),
+        starknet_field.as_deref(),
+        owner.as_deref(),
+    );
+
+    if target.as_deref() != Some(address.as_str()) {
+        mismatches.push(Mismatch::TargetMismatch {
+            domain: domain.clone(),
+            target: target.clone(),
+        });
+        if owner.as_deref() != Some(address.as_str()) {
+            mismatches.push(Mismatch::OwnerMismatch {
+                domain: domain.clone(),
+                owner: owner.clone(),
+            });
+        }
+    }
+    if let (Some(main_id), Some(id)) = (&main_id, &id) {
+        if main_id != id {
+            mismatches.push(Mismatch::MainIdMismatch {
+                main_id: main_id.clone(),
+                domain_id: id.clone(),
+            });
+        }
+    }
+    if let Ok(expiry) = domain_doc.get_i64("expiry") {
+        let display = grace.get_display(Some(expiry), now);
+        if !display.status.is_active() {
+            mismatches.push(Mismatch::DomainExpired {
+                domain: domain.clone(),
+                expiry,
+                status: display.status,
+            });
+        }
+    }
+
+    Ok(Integrity {
+        address,
+        domain: Some(domain),
+        id,
+        target,
+        owner,
+        consistent: mismatches.is_empty(),
+        mismatches: into_diagnoses(mismatches),
+    })
+}
+
+// labels span of a domain, [2, encode("sub"), encode("ben")] for sub.ben.stark
+fn get_domain_calldata(domain: &str) -> Option<Vec<FieldElement>> {
+    let labels = domain
+        .strip_suffix(".stark")?
+        .split('.')
+        .map(|label| encode(label).ok())
+        .collect::<Option<Vec<_>>>()?;
+    let mut calldata = vec![FieldElement::from(labels.len())];
+    calldata.extend(labels);
+    Some(calldata)
+}
+
+fn parse_id(id: &str) -> Option<FieldElement> {
+    if id.starts_with("0x") {
+        FieldElement::from_hex_be(id).ok()
+    } else {
+        FieldElement::from_dec_str(id).ok()
+    }
+}
+
+/// Multicall sent by the address to repair the diagnoses of its integrity, along with the
+/// diagnoses it can't fix itself: a missing reverse record needs a domain to be picked, a
+/// foreign identity needs its owner and an expired domain a renewal
+pub fn get_fix_calls(
+    integrity: &Integrity,
+    address: &FieldElement,
+    contracts: &Contracts,
+) -> (Vec<Call>, Vec<Diagnosis>) {
+    let owns_identity = integrity.owner.as_deref() == Some(integrity.address.as_str());
+    let mut calls = vec![];
+    let mut manual = vec![];
+    for diagnosis in &integrity.mismatches {
+        let call = match &diagnosis.mismatch {
+            Mismatch::TargetMismatch { domain, .. } if owns_identity => get_domain_calldata(domain)
+                .map(|mut calldata| {
+                    calldata.push(*address);
+                    Call {
+                        to: contracts.naming,
+                        entrypoint: "set_domain_to_address",
+                        selector: selector!("set_domain_to_address"),
+                        calldata,
+                    }
+                }),
+            Mismatch::MainIdMismatch { domain_id, .. } if owns_identity => {
+                parse_id(domain_id).map(|id| Call {
+                    to: contracts.starknetid,
+                    entrypoint: "set_main_id",
+                    selector: selector!("set_main_id"),
+                    calldata: vec![id],
+                })
+            }
+            // the domain resolves to the address, it can still be set as its main domain
+            Mismatch::MainIdMismatch { .. } => integrity
+                .domain
+                .as_deref()
+                .filter(|_| integrity.target.as_deref() == Some(integrity.address.as_str()))
+                .and_then(get_domain_calldata)
+                .map(|mut calldata| {
+                    // empty hint
+                    calldata.push(FieldElement::ZERO);
+                    Call {
+                        to: contracts.naming,
+                        entrypoint: "set_address_to_domain",
+                        selector: selector!("set_address_to_domain"),
+                        calldata,
+                    }
+                }),
+            _ => None,
+        };
+        match call {
+            Some(call) => calls.push(call),
+            None => manual.push(diagnosis.clone()),
+        }
+    }
+    (calls, manual)
+}
+
+fn into_diagnoses(mismatches: Vec<Mismatch>) -> Vec<Diagnosis> {
+    mismatches
+        .into_iter()
+        .map(|mismatch| Diagnosis {
+            fix: mismatch.get_fix(),
+            mismatch,
+        })
+        .collect()
+}
@@ -0,0 +1,117 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
+use crate::{
+    config::{Config, Ipfs},
+    metrics::labeled,
+    models::AppState,
+};
+
+lazy_static::lazy_static! {
+    static ref GATEWAY_HEALTH: GatewayHealth = GatewayHealth::default();
+}
+
+#[derive(Default, Clone, Copy)]
+struct GatewayState {
+    // failed requests in a row
+    failures: u32,
+    down_until: i64,
+}
+
+/// Failures of the ipfs gateways, a gateway failing repeatedly is skipped for a while
+#[derive(Default)]
+pub struct GatewayHealth {
+    gateways: Mutex<HashMap<String, GatewayState>>,
+}
+
+impl GatewayHealth {
+    pub fn record(&self, gateway: &str, ok: bool, now: i64, conf: &Ipfs) {
+        let mut gateways = self.gateways.lock().unwrap();
+        let state = gateways.entry(gateway.to_string()).or_default();
+        if ok {
+            *state = GatewayState::default();
+            return;
+        }
+        state.failures += 1;
+        if state.failures >= conf.max_failures {
+            state.down_until = now + conf.cooldown_secs;
+        }
+    }
+
+    pub fn is_down(&self, gateway: &str, now: i64) -> bool {
+        self.gateways
+            .lock()
+            .unwrap()
+            .get(gateway)
+            .is_some_and(|state| state.down_until > now)
+    }
+
+    /// Gateways in their order of preference, the ones down are kept last as a last resort
+    pub fn order<'a>(&self, gateways: &'a [String], now: i64) -> Vec<&'a str> {
+        let (up, down): (Vec<&str>, Vec<&str>) = gateways
+            .iter()
+            .map(String::as_str)
+            .partition(|gateway| !self.is_down(gateway, now));
+        up.into_iter().chain(down).collect()
+    }
+}
+
+/// Health of the gateways shared by every lookup of the process
+pub fn get_health() -> &'static GatewayHealth {
+    &GATEWAY_HEALTH
+}
+
+/// Url of the ipfs path on the preferred gateway currently up
+pub fn get_gateway_url(config: &Config, path: &str) -> Option<String> {
+    let now = chrono::Utc::now().timestamp();
+    get_health()
+        .order(&config.variables.ipfs_gateways, now)
+        .first()
+        .map(|gateway| format!("{}{}", gateway, path))
+}
+
+async fn is_serving(client: &reqwest::Client, url: &str) -> bool {
+    client
+        .head(url)
+        .send()
+        .await
+        .is_ok_and(|response| response.status().is_success())
+}
+
+fn get_client(conf: &Ipfs) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_millis(conf.timeout_ms))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Url of the ipfs path on the first gateway actually serving it, the gateways tried
+/// before are recorded as failing
+pub async fn find_serving_url(config: &Config, path: &str) -> Option<String> {
+    let conf = &config.ipfs;
+    let client = get_client(conf);
+    let now = chrono::Utc::now().timestamp();
+    for gateway in get_health().order(&config.variables.ipfs_gateways, now) {
+        let url = format!("{}{}", gateway, path);
+        let ok = is_serving(&client, &url).await;
+        get_health().record(gateway, ok, chrono::Utc::now().timestamp(), conf);
+        if ok {
+            return Some(url);
+        }
+    }
+    None
+}
+
+/// Probes every gateway with the health check cid and publishes which are up
+pub async fn check_gateways(state: &AppState) {
+    let conf = &state.conf.ipfs;
+    let client = get_client(conf);
+    for gateway in &state.conf.variables.ipfs_gateways {
+        let ok = is_serving(&client, &format!("{}{}", gateway, conf.health_check_cid)).await;
+        let now = chrono::Utc::now().timestamp();
+        get_health().record(gateway, ok, now, conf);
+        state.metrics.set(
+            &labeled("ipfs_gateway_up", "gateway", gateway),
+            !get_health().is_down(gateway, now) as i64,
+        );
+    }
+}
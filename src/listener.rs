@@ -0,0 +1,110 @@
+use std::{
+    env, fs,
+    net::SocketAddr,
+    os::unix::io::FromRawFd,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+
+use anyhow::{bail, Result};
+use axum::Router;
+use hyper::server::accept::Accept;
+use serde::Deserialize;
+use tokio::net::{UnixListener, UnixStream};
+
+use crate::{config::Config, logger::Logger, tls};
+
+// first file descriptor passed by systemd, see sd_listen_fds(3)
+const SD_LISTEN_FDS_START: i32 = 3;
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ListenerKind {
+    #[default]
+    Tcp,
+    Unix,
+    // tcp socket opened by systemd, it stays open while the service restarts
+    Systemd,
+}
+
+struct UnixAccept {
+    listener: UnixListener,
+}
+
+impl Accept for UnixAccept {
+    type Conn = UnixStream;
+    type Error = std::io::Error;
+
+    fn poll_accept(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Conn, Self::Error>>> {
+        let (stream, _) = ready!(self.listener.poll_accept(cx))?;
+        Poll::Ready(Some(Ok(stream)))
+    }
+}
+
+fn systemd_listener() -> Result<std::net::TcpListener> {
+    let pid: u32 = env::var("LISTEN_PID")?.parse()?;
+    if pid != std::process::id() {
+        bail!("sockets were passed to another process");
+    }
+    let fds: i32 = env::var("LISTEN_FDS")?.parse()?;
+    if fds < 1 {
+        bail!("no socket passed by systemd");
+    }
+    // safety: systemd hands over this file descriptor to our process
+    let listener = unsafe { std::net::TcpListener::from_raw_fd(SD_LISTEN_FDS_START) };
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+pub async fn serve(app: Router, conf: &Config, logger: &Logger) -> Result<()> {
+    match conf.server.listener {
+        ListenerKind::Tcp => {
+            let addr = SocketAddr::from(([0, 0, 0, 0], conf.server.port));
+            if conf.tls.enabled {
+                logger.info(format!(
+                    "server: listening on https://0.0.0.0:{}",
+                    conf.server.port
+                ));
+                return tls::serve(app, addr, conf.tls.clone(), logger.clone()).await;
+            }
+            logger.info(format!(
+                "server: listening on http://0.0.0.0:{}",
+                conf.server.port
+            ));
+            axum::Server::bind(&addr)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await?;
+        }
+        ListenerKind::Unix => {
+            // the peers of a unix socket have no address, all the clients would share one
+            // rate limit bucket unless the reverse proxy in front of us tells them apart
+            if conf.rate_limits.enabled && !conf.rate_limits.trust_forwarded_for {
+                bail!("a unix listener needs rate_limits.trust_forwarded_for when rate limiting");
+            }
+            // a socket left by a previous run would prevent binding
+            let _ = fs::remove_file(&conf.server.unix_socket);
+            let listener = UnixListener::bind(&conf.server.unix_socket)?;
+            logger.info(format!(
+                "server: listening on unix:{}",
+                conf.server.unix_socket
+            ));
+            axum::Server::builder(UnixAccept { listener })
+                .serve(app.into_make_service())
+                .await?;
+        }
+        ListenerKind::Systemd => {
+            let listener = systemd_listener()?;
+            logger.info(format!(
+                "server: listening on systemd socket {}",
+                listener.local_addr()?
+            ));
+            axum::Server::from_tcp(listener)?
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await?;
+        }
+    }
+    Ok(())
+}
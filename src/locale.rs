@@ -0,0 +1,192 @@
+use axum::http::HeaderMap;
+use chrono::{DateTime, Datelike};
+
+use crate::models::IdentityData;
+
+/// Languages the human readable `*_display` fields can be formatted in, the canonical
+/// epoch fields are always returned as is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Locale {
+    En,
+    Fr,
+    De,
+    Es,
+    Pt,
+}
+
+const MONTHS: [(Locale, [&str; 12]); 5] = [
+    (
+        Locale::En,
+        [
+            "January",
+            "February",
+            "March",
+            "April",
+            "May",
+            "June",
+            "July",
+            "August",
+            "September",
+            "October",
+            "November",
+            "December",
+        ],
+    ),
+    (
+        Locale::Fr,
+        [
+            "janvier",
+            "février",
+            "mars",
+            "avril",
+            "mai",
+            "juin",
+            "juillet",
+            "août",
+            "septembre",
+            "octobre",
+            "novembre",
+            "décembre",
+        ],
+    ),
+    (
+        Locale::De,
+        [
+            "Januar",
+            "Februar",
+            "März",
+            "April",
+            "Mai",
+            "Juni",
+            "Juli",
+            "August",
+            "September",
+            "Oktober",
+            "November",
+            "Dezember",
+        ],
+    ),
+    (
+        Locale::Es,
+        [
+            "enero",
+            "febrero",
+            "marzo",
+            "abril",
+            "mayo",
+            "junio",
+            "julio",
+            "agosto",
+            "septiembre",
+            "octubre",
+            "noviembre",
+            "diciembre",
+        ],
+    ),
+    (
+        Locale::Pt,
+        [
+            "janeiro",
+            "fevereiro",
+            "março",
+            "abril",
+            "maio",
+            "junho",
+            "julho",
+            "agosto",
+            "setembro",
+            "outubro",
+            "novembro",
+            "dezembro",
+        ],
+    ),
+];
+
+impl Locale {
+    /// Parses a language tag such as `fr` or `fr-CA`, only the language is used
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.trim().split(['-', '_']).next()?.to_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "fr" => Some(Locale::Fr),
+            "de" => Some(Locale::De),
+            "es" => Some(Locale::Es),
+            "pt" => Some(Locale::Pt),
+            _ => None,
+        }
+    }
+
+    fn month(&self, month: u32) -> &'static str {
+        let (_, months) = MONTHS.iter().find(|(locale, _)| locale == self).unwrap();
+        months[month as usize - 1]
+    }
+
+    pub fn format_date(&self, timestamp: i64) -> Option<String> {
+        let date = DateTime::from_timestamp(timestamp, 0)?;
+        let (day, month, year) = (date.day(), self.month(date.month()), date.year());
+        Some(match self {
+            Locale::En => format!("{} {}, {}", month, day, year),
+            Locale::Fr => format!("{} {} {}", day, month, year),
+            Locale::De => format!("{}. {} {}", day, month, year),
+            Locale::Es | Locale::Pt => format!("{} de {} de {}", day, month, year),
+        })
+    }
+
+    pub fn format_number(&self, number: i64) -> String {
+        let separator = match self {
+            Locale::En => ",",
+            // narrow no-break space
+            Locale::Fr => "\u{202f}",
+            Locale::De | Locale::Es | Locale::Pt => ".",
+        };
+        let digits = number.unsigned_abs().to_string();
+        let mut formatted = String::new();
+        for (i, digit) in digits.chars().enumerate() {
+            if i > 0 && (digits.len() - i) % 3 == 0 {
+                formatted.push_str(separator);
+            }
+            formatted.push(digit);
+        }
+        if number < 0 {
+            formatted.insert(0, '-');
+        }
+        formatted
+    }
+}
+
+/// Locale requested with `?locale=`, or else the preferred supported one of `Accept-Language`
+pub fn get_locale(query: Option<&str>, headers: &HeaderMap) -> Option<Locale> {
+    if let Some(tag) = query {
+        return Locale::from_tag(tag);
+    }
+    let accepted = headers.get("accept-language")?.to_str().ok()?;
+    let mut candidates: Vec<(f32, Locale)> = accepted
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let locale = Locale::from_tag(parts.next()?)?;
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .map_or(Some(1.0), |q| q.parse::<f32>().ok())?;
+            Some((quality, locale))
+        })
+        .collect();
+    // stable sort, equal qualities keep the client order
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0));
+    candidates
+        .into_iter()
+        .find(|(quality, _)| *quality > 0.0)
+        .map(|(_, locale)| locale)
+}
+
+impl IdentityData {
+    pub fn set_display_fields(&mut self, locale: Locale) {
+        self.creation_date_display = locale.format_date(self.creation_date as i64);
+        if let Some(domain) = &mut self.domain {
+            domain.creation_date_display = locale.format_date(domain.creation_date as i64);
+            domain.expiry_display = domain
+                .expiry
+                .and_then(|expiry| locale.format_date(expiry as i64));
+        }
+    }
+}
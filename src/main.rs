@@ -1,25 +1,108 @@
 #![recursion_limit = "256"]
 
+mod activity;
+mod address_labels;
+mod addressbook;
+mod analytics;
+mod auth;
+mod auto_renewal;
+mod avatars;
+mod badges;
+mod cache;
+mod calendar;
+mod clients;
+mod clubs;
+mod compatibility;
 mod config;
+mod contract_claims;
+mod cors;
+mod db_pool;
+mod descriptions;
+mod display_address;
+mod distribution;
 mod ecdsa_sign;
+mod encoding;
 mod endpoints;
+mod errors;
+mod examples;
+mod expiring;
+mod exports;
+mod failures;
+mod fallback;
+mod fanout;
+mod filters;
+mod finality;
+mod freezes;
+mod freshness;
+mod governance;
+mod grace;
+mod graphql;
+mod history;
+mod images;
+mod incidents;
+mod integrity;
+mod ipfs;
+mod listener;
+mod locale;
 mod logger;
+mod maintenance;
+mod marketplaces;
+mod metrics;
 mod models;
+mod naming_actions;
+mod networks;
+mod normalization;
+mod notifications;
+mod openapi;
+mod organizations;
+mod pagination;
+mod partners;
+mod portfolio;
+mod preferences;
+mod pricing;
+mod profanity;
+mod quotes;
+mod raffle;
+mod rate_limit;
+mod records;
+mod reminders;
+mod rendering;
+mod request_tracing;
 mod resolving;
+mod retention;
+mod routes;
+mod rpc_queue;
+mod rpc_usage;
+mod sampling;
+mod search;
+mod simulation;
+mod socials;
+mod status;
+mod storage;
+mod streaming;
+mod suggestions;
 mod tax;
+mod test_vectors;
+mod tls;
+mod transparency;
+mod types;
+mod ui;
+mod user_tokens;
 mod utils;
+mod verifiers;
+mod watch;
+mod webhooks;
+mod ws;
 
-use axum::{http::StatusCode, Router};
+use axum::{http::StatusCode, middleware};
 use axum_auto_routes::route;
-use mongodb::{bson::doc, options::ClientOptions, Client};
+use mongodb::{bson::doc, Client};
 use std::collections::HashMap;
-use std::sync::Arc;
-use std::{net::SocketAddr, sync::Mutex};
+use std::sync::Mutex;
+use std::sync::{atomic::AtomicU64, Arc};
 use tokio::time::{sleep, Duration};
 use utils::WithState;
 
-use tower_http::cors::{Any, CorsLayer};
-
 use crate::resolving::update_offchain_resolvers;
 
 lazy_static::lazy_static! {
@@ -31,8 +114,28 @@ mod tests;
 
 #[tokio::main]
 async fn main() {
+    if std::env::args().any(|arg| arg == "--print-routes") {
+        println!("{}", serde_json::to_string_pretty(routes::ROUTES).unwrap());
+        return;
+    }
+
     let conf = config::load();
     let logger = logger::Logger::new(&conf.watchtower);
+    // the undeclared handlers would be refused on every request
+    if let Err(e) = routes::check_registry(ROUTE_REGISTRY.lock().unwrap().len()) {
+        logger.severe(format!("error: {}", e));
+        return;
+    }
+    for section in config::get_unset_keys(&conf) {
+        logger.warning(format!(
+            "{}: no private_key set, signatures disabled",
+            section
+        ));
+    }
+    incidents::install_panic_hook();
+    if let Err(e) = request_tracing::init(&conf.tracing) {
+        logger.warning(format!("tracing: unable to start: {}", e));
+    }
 
     // Testing logger when server started
     logger.info(format!(
@@ -40,30 +143,382 @@ async fn main() {
         env!("CARGO_PKG_VERSION")
     ));
 
+    let metrics = Arc::new(metrics::Metrics::default());
+    let analytics = match analytics::AnalyticsMirror::connect(&conf.analytics, &logger).await {
+        Ok(analytics) => analytics,
+        Err(e) => {
+            logger.severe(format!("analytics: unable to connect to postgres: {}", e));
+            None
+        }
+    };
+    let shared_state = match build_state(conf.clone(), &metrics, &logger, analytics).await {
+        Some(state) => state,
+        None => return,
+    };
+    // the other networks have their own databases, the analytics only mirror the default one
+    let mut network_states = vec![];
+    for name in conf.networks.others.keys() {
+        let network_conf = conf.for_network(name).unwrap();
+        match build_state(network_conf, &metrics, &logger, None).await {
+            Some(state) => network_states.push((name.clone(), state)),
+            None => return,
+        }
+    }
+    let all_states: Vec<Arc<models::AppState>> = std::iter::once(shared_state.clone())
+        .chain(network_states.iter().map(|(_, state)| state.clone()))
+        .collect();
+    // we will know by looking at the log number which db has an issue
+    for db in all_states
+        .iter()
+        .flat_map(|state| [&state.starknetid_db, &state.sales_db])
+    {
+        if db.run_command(doc! {"ping": 1}, None).await.is_err() {
+            logger.severe("error: unable to connect to a database".to_string());
+            return;
+        } else {
+            logger.info("database: connected".to_string());
+        }
+    }
+
+    // refuse to serve next to peers whose cached responses this instance can't share
+    let instance_id = compatibility::new_instance_id();
+    match compatibility::join_pool(&shared_state, &instance_id).await {
+        Ok(true) => {}
+        Ok(false) => {
+            logger
+                .severe("error: incompatible peers are serving, not joining the pool".to_string());
+            return;
+        }
+        Err(e) => logger.warning(format!("compatibility: unable to check the peers: {}", e)),
+    }
+    let heartbeat_state = shared_state.clone();
+    tokio::spawn(async move {
+        loop {
+            sleep(Duration::from_secs(
+                (heartbeat_state.conf.compatibility.peer_ttl_secs / 3).max(1),
+            ))
+            .await;
+            let now = chrono::Utc::now().timestamp();
+            if let Err(e) =
+                compatibility::send_heartbeat(heartbeat_state.storage.as_ref(), &instance_id, now)
+                    .await
+            {
+                heartbeat_state
+                    .logger
+                    .warning(format!("compatibility: unable to send a heartbeat: {}", e));
+            }
+        }
+    });
+
+    sampling::init(&shared_state).await;
+    analytics::start(&shared_state);
+    transparency::init(&shared_state).await;
+    search::init(&shared_state).await;
+    normalization::init(&shared_state).await;
+
+    // track L1 finality on every network, and roll back indexed data on reorgs where enabled
+    for state in &all_states {
+        let finality_state = state.clone();
+        tokio::spawn(async move {
+            loop {
+                finality::update_last_l1_block(&finality_state).await;
+                finality::process_reorgs(&finality_state).await;
+                sleep(Duration::from_millis(
+                    (finality_state.conf.variables.refresh_delay * 1000.0) as u64,
+                ))
+                .await;
+            }
+        });
+    }
+
+    // latin skeletons of the unicode domains for the searches, from the last block synced
+    let skeletons_state = shared_state.clone();
+    tokio::spawn(async move {
+        let mut since = 0;
+        loop {
+            match normalization::sync_skeletons(skeletons_state.storage.as_ref(), since).await {
+                Ok(block) => since = block,
+                Err(e) => skeletons_state
+                    .logger
+                    .warning(format!("normalization: unable to sync skeletons: {}", e)),
+            }
+            sleep(Duration::from_secs(normalization::SYNC_INTERVAL_SECS)).await;
+        }
+    });
+
+    // publish the entrants hash of the raffles once their entries close
+    let raffles_state = shared_state.clone();
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = raffle::close_expired_raffles(&raffles_state).await {
+                raffles_state
+                    .logger
+                    .warning(format!("raffles: unable to close raffles: {}", e));
+            }
+            sleep(Duration::from_secs(raffle::CLOSE_INTERVAL_SECS)).await;
+        }
+    });
+
+    // publish the rpc credits used this month and warn before the budget runs out
+    let budget_state = shared_state.clone();
+    tokio::spawn(async move {
+        loop {
+            rpc_usage::check_budget(&budget_state);
+            sleep(Duration::from_secs(
+                budget_state.conf.rpc_budget.check_interval_secs,
+            ))
+            .await;
+        }
+    });
+
+    // probes of the ipfs gateways, so the lookups skip the ones down
+    let ipfs_state = shared_state.clone();
+    tokio::spawn(async move {
+        loop {
+            ipfs::check_gateways(&ipfs_state).await;
+            sleep(Duration::from_secs(
+                ipfs_state.conf.ipfs.check_interval_secs,
+            ))
+            .await;
+        }
+    });
+
+    // webhooks of the indexed writes and expiries, then their deliveries, of the default network
+    if conf.webhooks.enabled {
+        let webhooks_state = shared_state.clone();
+        tokio::spawn(async move {
+            loop {
+                if let Err(e) = webhooks::dispatch::dispatch(&webhooks_state).await {
+                    webhooks_state
+                        .logger
+                        .warning(format!("webhooks: unable to dispatch: {}", e));
+                }
+                if let Err(e) = webhooks::delivery::deliver_due(&webhooks_state).await {
+                    webhooks_state
+                        .logger
+                        .warning(format!("webhooks: unable to deliver: {}", e));
+                }
+                sleep(Duration::from_secs(
+                    webhooks_state.conf.webhooks.interval_secs,
+                ))
+                .await;
+            }
+        });
+    }
+
+    // cleanup of the collections and caches growing without bound
+    let maintenance_state = shared_state.clone();
+    tokio::spawn(async move {
+        loop {
+            maintenance::run(&maintenance_state).await;
+            sleep(Duration::from_secs(
+                maintenance_state.conf.maintenance.interval_secs,
+            ))
+            .await;
+        }
+    });
+
+    // holder, age and length histograms of the domains
+    let distribution_state = shared_state.clone();
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = distribution::compute(&distribution_state).await {
+                distribution_state
+                    .logger
+                    .warning(format!("distribution: unable to compute stats: {}", e));
+            }
+            sleep(Duration::from_secs(
+                distribution_state.conf.distribution.interval_secs,
+            ))
+            .await;
+        }
+    });
+
+    // renewal rate of each monthly registration cohort
+    let retention_state = shared_state.clone();
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = retention::compute(&retention_state).await {
+                retention_state
+                    .logger
+                    .warning(format!("retention: unable to compute cohorts: {}", e));
+            }
+            sleep(Duration::from_secs(
+                retention_state.conf.retention.interval_secs,
+            ))
+            .await;
+        }
+    });
+
+    // reminders of the stale social verifications
+    let notifications_state = shared_state.clone();
+    tokio::spawn(async move {
+        loop {
+            match notifications::send_reverification_reminders(&notifications_state).await {
+                Ok(sent) if sent > 0 => notifications_state.logger.info(format!(
+                    "notifications: sent {} reverification reminders",
+                    sent
+                )),
+                Ok(_) => {}
+                Err(e) => notifications_state
+                    .logger
+                    .warning(format!("notifications: unable to send reminders: {}", e)),
+            }
+            sleep(Duration::from_secs(
+                notifications_state.conf.notifications.interval_secs,
+            ))
+            .await;
+        }
+    });
+
+    // renewal reminders emailed to the owners of the domains expiring soon
+    if conf.reminders.enabled {
+        let reminders_state = shared_state.clone();
+        tokio::spawn(async move {
+            loop {
+                match reminders::send_renewal_reminders(&reminders_state).await {
+                    Ok(sent) if sent > 0 => reminders_state
+                        .logger
+                        .info(format!("reminders: sent {} renewal reminders", sent)),
+                    Ok(_) => {}
+                    Err(e) => reminders_state
+                        .logger
+                        .warning(format!("reminders: unable to send reminders: {}", e)),
+                }
+                sleep(Duration::from_secs(
+                    reminders_state.conf.reminders.interval_secs,
+                ))
+                .await;
+            }
+        });
+    }
+
+    // metadata refresh of the identity cards listed on the marketplaces
+    let marketplaces_state = shared_state.clone();
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = marketplaces::push_refreshes(&marketplaces_state).await {
+                marketplaces_state
+                    .logger
+                    .warning(format!("marketplaces: unable to push refreshes: {}", e));
+            }
+            sleep(Duration::from_secs(
+                marketplaces_state.conf.marketplaces.interval_secs,
+            ))
+            .await;
+        }
+    });
+
+    // change streams feeding the websocket subscriptions and the response cache invalidation,
+    // on every network
+    for state in &all_states {
+        if conf.ws.enabled || conf.response_cache.enabled {
+            for collection in ws::feed::WATCHED_COLLECTIONS {
+                let ws_state = state.clone();
+                tokio::spawn(async move { ws::feed::run(&ws_state, collection).await });
+            }
+        }
+        if conf.response_cache.enabled {
+            let cache_state = state.clone();
+            tokio::spawn(async move { cache::run_invalidation(&cache_state).await });
+        }
+    }
+
+    // refresh offchain resolvers from indexed data, on every network
+    for state in &all_states {
+        let refresh_state = state.clone();
+        tokio::spawn(async move {
+            loop {
+                update_offchain_resolvers(&refresh_state).await;
+                sleep(Duration::from_millis(
+                    (refresh_state.conf.variables.refresh_delay * 1000.0) as u64,
+                ))
+                .await;
+            }
+        });
+    }
+
+    let cors_layer = cors::get_cors_layer(&conf.cors);
+    let network_routers = network_states
+        .into_iter()
+        .map(|(name, state)| (name, routes::build_router(state)))
+        .collect();
+    let app = networks::build_router(
+        conf.networks.default.clone(),
+        routes::build_router(shared_state.clone()),
+        network_routers,
+    )
+    .layer(cors_layer)
+    .layer(middleware::from_fn_with_state(
+        shared_state.clone(),
+        cors::set_security_headers,
+    ))
+    .layer(middleware::from_fn(request_tracing::trace_requests));
+
+    // responses of the examples, replayed on the app once it is built
+    if conf.examples.enabled {
+        let examples_state = shared_state.clone();
+        let examples_app = app.clone();
+        tokio::spawn(async move {
+            let examples =
+                examples::render(examples_app, routes::ROUTES, &examples_state.conf.examples).await;
+            examples_state.examples.set(examples);
+        });
+    }
+
+    if let Err(e) = listener::serve(app, &conf, &logger).await {
+        logger.severe(format!("error: unable to serve: {}", e));
+    }
+}
+
+/// State of the network the config serves by default, None when its data couldn't be loaded
+async fn build_state(
+    conf: config::Config,
+    metrics: &Arc<metrics::Metrics>,
+    logger: &logger::Logger,
+    analytics: Option<analytics::AnalyticsMirror>,
+) -> Option<Arc<models::AppState>> {
+    let pool = &conf.databases.pool;
     let starknetid_client_options =
-        ClientOptions::parse(&conf.databases.starknetid.connection_string)
+        db_pool::get_client_options(&conf.databases.starknetid, pool, metrics)
             .await
             .unwrap();
-
-    let sales_client_options = ClientOptions::parse(&conf.databases.sales.connection_string)
+    let sales_client_options = db_pool::get_client_options(&conf.databases.sales, pool, metrics)
         .await
         .unwrap();
     let free_domains_client_options =
-        ClientOptions::parse(&conf.databases.free_domains.connection_string)
+        db_pool::get_client_options(&conf.databases.free_domains, pool, metrics)
             .await
             .unwrap();
 
-    let states = tax::sales_tax::load_sales_tax(&logger).await;
+    let states = tax::sales_tax::load_sales_tax(logger).await;
     if states.states.is_empty() {
         logger.severe("error: unable to load sales tax".to_string());
-        return;
+        return None;
     }
 
-    let shared_state = Arc::new(models::AppState {
-        conf: conf.clone(),
-        starknetid_db: Client::with_options(starknetid_client_options)
-            .unwrap()
-            .database(&conf.databases.starknetid.name),
+    let badges = badges::load_badges(logger);
+    let address_labels = address_labels::load_address_labels(&conf.address_labels.datasets, logger);
+    let profanity = profanity::load_profanity_filter(&conf.profanity.locales, logger);
+    let suggestions = suggestions::load_dictionaries(&conf.suggestions.dictionaries, logger);
+    // the exports are disabled rather than the instance when their bucket is misconfigured
+    let export_storage = match exports::ExportStorage::new(&conf.exports) {
+        Ok(storage) => storage,
+        Err(e) => {
+            logger.severe(format!(
+                "exports: unable to open the bucket, disabled: {}",
+                e
+            ));
+            None
+        }
+    };
+
+    let starknetid_db = Client::with_options(starknetid_client_options)
+        .unwrap()
+        .database(&conf.databases.starknetid.name);
+    Some(Arc::new(models::AppState {
+        storage: Arc::new(storage::MongoStorage::new(starknetid_db.clone())),
+        starknetid_db,
         sales_db: Client::with_options(sales_client_options)
             .unwrap()
             .database(&conf.databases.sales.name),
@@ -71,51 +526,31 @@ async fn main() {
             .unwrap()
             .database(&conf.databases.free_domains.name),
         states,
+        badges,
+        address_labels,
+        profanity,
+        suggestions,
         dynamic_offchain_resolvers: Arc::new(Mutex::new(HashMap::new())),
+        last_l1_block: AtomicU64::new(0),
+        export_storage,
+        analytics,
+        transparency: transparency::TransparencyLog::default(),
+        maintenance: maintenance::MaintenanceLog::default(),
+        distribution: distribution::DistributionStats::default(),
+        block_timestamps: freshness::BlockTimestamps::default(),
+        marketplace_refresh: marketplaces::RefreshCursor::default(),
+        rate_limiter: rate_limit::RateLimiter::default(),
+        api_key_cache: auth::ApiKeyCache::default(),
+        client_usage: clients::ClientUsage::default(),
+        change_feed: ws::feed::ChangeFeed::default(),
+        response_cache: cache::ResponseCache::default(),
+        resolution_fallback: fallback::FallbackCache::default(),
+        examples: examples::ExampleStore::default(),
+        rpc_queue: rpc_queue::RpcQueue::new(&conf.rpc_limits, metrics.clone()),
+        metrics: metrics.clone(),
         logger: logger.clone(),
-    });
-    // we will know by looking at the log number which db has an issue
-    for db in [&shared_state.starknetid_db, &shared_state.sales_db] {
-        if db.run_command(doc! {"ping": 1}, None).await.is_err() {
-            logger.severe("error: unable to connect to a database".to_string());
-            return;
-        } else {
-            logger.info("database: connected".to_string());
-        }
-    }
-
-    // refresh offchain resolvers from indexed data
-    let refresh_state = shared_state.clone();
-    tokio::spawn(async move {
-        loop {
-            update_offchain_resolvers(&refresh_state).await;
-            sleep(Duration::from_millis(
-                (conf.variables.refresh_delay * 1000.0) as u64,
-            ))
-            .await;
-        }
-    });
-
-    let cors = CorsLayer::new().allow_headers(Any).allow_origin(Any);
-    let app = ROUTE_REGISTRY
-        .lock()
-        .unwrap()
-        .clone()
-        .into_iter()
-        .fold(Router::new().with_state(shared_state.clone()), |acc, r| {
-            acc.merge(r.to_router(shared_state.clone()))
-        })
-        .layer(cors);
-
-    let addr = SocketAddr::from(([0, 0, 0, 0], conf.server.port));
-    logger.info(format!(
-        "server: listening on http://0.0.0.0:{}",
-        conf.server.port
-    ));
-    axum::Server::bind(&addr)
-        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
-        .await
-        .unwrap();
+        conf,
+    }))
 }
 
 #[route(get, "/")]
@@ -0,0 +1,215 @@
+use std::{sync::Mutex, time::Instant};
+
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, Bson, Document},
+    options::FindOneOptions,
+};
+use serde::Serialize;
+
+use crate::models::AppState;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RetentionCheck {
+    pub collection: String,
+    pub retention_secs: u64,
+    pub has_ttl_index: bool,
+    // documents past their retention which were still stored
+    pub overdue: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CacheCardinality {
+    pub cache: &'static str,
+    pub keys: usize,
+    pub oversized: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Compaction {
+    pub collection: String,
+    pub capped: bool,
+    pub documents: u64,
+    pub deleted: u64,
+    pub storage_size: i64,
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MaintenanceReport {
+    pub ran_at: i64,
+    pub duration_ms: u64,
+    pub retention: Vec<RetentionCheck>,
+    pub caches: Vec<CacheCardinality>,
+    pub compactions: Vec<Compaction>,
+}
+
+/// Report of the last maintenance run, served by `/admin/maintenance`
+#[derive(Default)]
+pub struct MaintenanceLog {
+    last_report: Mutex<Option<MaintenanceReport>>,
+}
+
+impl MaintenanceLog {
+    pub fn last_report(&self) -> Option<MaintenanceReport> {
+        self.last_report.lock().unwrap().clone()
+    }
+}
+
+pub fn get_cache_cardinalities(
+    caches: &[(&'static str, usize)],
+    max_keys: usize,
+) -> Vec<CacheCardinality> {
+    caches
+        .iter()
+        .map(|(cache, keys)| CacheCardinality {
+            cache: *cache,
+            keys: *keys,
+            oversized: *keys > max_keys,
+        })
+        .collect()
+}
+
+// numbers are stored as i32 or i64 depending on the writer
+fn get_number(doc: &Document, key: &str) -> i64 {
+    match doc.get(key) {
+        Some(Bson::Int32(value)) => *value as i64,
+        Some(Bson::Int64(value)) => *value,
+        Some(Bson::Double(value)) => *value as i64,
+        _ => 0,
+    }
+}
+
+async fn check_retention(state: &AppState, name: &str, retention_secs: u64) -> RetentionCheck {
+    let collection = state.starknetid_db.collection::<Document>(name);
+    let mut check = RetentionCheck {
+        collection: name.to_string(),
+        retention_secs,
+        has_ttl_index: false,
+        overdue: 0,
+        error: None,
+    };
+    let result: mongodb::error::Result<()> = async {
+        let mut indexes = collection.list_indexes(None).await?;
+        while let Some(index) = indexes.next().await {
+            if index?
+                .options
+                .and_then(|options| options.expire_after)
+                .is_some()
+            {
+                check.has_ttl_index = true;
+            }
+        }
+        let cutoff = chrono::Utc::now().timestamp() - retention_secs as i64;
+        // ttl indexes only expire dates, epoch timestamps are cleaned here
+        let deleted = collection
+            .delete_many(doc! { "created_at": { "$lt": cutoff } }, None)
+            .await?;
+        check.overdue = deleted.deleted_count;
+        Ok(())
+    }
+    .await;
+    check.error = result.err().map(|e| e.to_string());
+    check
+}
+
+async fn compact_collection(state: &AppState, name: &str, max_documents: u64) -> Compaction {
+    let db = &state.starknetid_db;
+    let collection = db.collection::<Document>(name);
+    let mut compaction = Compaction {
+        collection: name.to_string(),
+        capped: false,
+        documents: 0,
+        deleted: 0,
+        storage_size: 0,
+        error: None,
+    };
+    let result: mongodb::error::Result<()> = async {
+        let stats = db.run_command(doc! { "collStats": name }, None).await?;
+        compaction.capped = stats.get_bool("capped").unwrap_or(false);
+        compaction.documents = get_number(&stats, "count") as u64;
+        // capped collections are bounded by size already and reject most deletes
+        if !compaction.capped && compaction.documents > max_documents {
+            let options = FindOneOptions::builder()
+                .sort(doc! { "created_at": -1 })
+                .skip(max_documents)
+                .build();
+            if let Some(first_excess) = collection.find_one(None, options).await? {
+                let cutoff = get_number(&first_excess, "created_at");
+                let deleted = collection
+                    .delete_many(doc! { "created_at": { "$lte": cutoff } }, None)
+                    .await?;
+                compaction.deleted = deleted.deleted_count;
+            }
+        }
+        if compaction.deleted > 0 || compaction.capped {
+            db.run_command(doc! { "compact": name }, None).await?;
+        }
+        let stats = db.run_command(doc! { "collStats": name }, None).await?;
+        compaction.documents = get_number(&stats, "count") as u64;
+        compaction.storage_size = get_number(&stats, "storageSize");
+        Ok(())
+    }
+    .await;
+    compaction.error = result.err().map(|e| e.to_string());
+    compaction
+}
+
+/// Cleans the expired documents, trims the oversized collections and reports the caches size
+pub async fn run(state: &AppState) -> MaintenanceReport {
+    let started = Instant::now();
+    let conf = &state.conf.maintenance;
+
+    let mut retention = vec![];
+    for (collection, retention_secs) in &conf.retention_secs {
+        retention.push(check_retention(state, collection, *retention_secs).await);
+    }
+    let mut compactions = vec![];
+    for (collection, max_documents) in &conf.max_documents {
+        compactions.push(compact_collection(state, collection, *max_documents).await);
+    }
+    let caches = get_cache_cardinalities(
+        &[
+            (
+                "offchain_resolvers",
+                state.dynamic_offchain_resolvers.lock().unwrap().len(),
+            ),
+            ("metrics", state.metrics.key_count()),
+            ("rpc_usage", state.rpc_queue.usage.key_count()),
+            ("responses", state.response_cache.len()),
+        ],
+        conf.max_cache_keys,
+    );
+
+    for check in retention.iter().filter(|check| check.overdue > 0) {
+        state.logger.warning(format!(
+            "maintenance: deleted {} expired documents from {}",
+            check.overdue, check.collection
+        ));
+    }
+    for cache in caches.iter().filter(|cache| cache.oversized) {
+        state.logger.warning(format!(
+            "maintenance: {} cache holds {} keys",
+            cache.cache, cache.keys
+        ));
+    }
+    for compaction in &compactions {
+        if let Some(e) = &compaction.error {
+            state.logger.warning(format!(
+                "maintenance: unable to compact {}: {}",
+                compaction.collection, e
+            ));
+        }
+    }
+
+    let report = MaintenanceReport {
+        ran_at: chrono::Utc::now().timestamp(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        retention,
+        caches,
+        compactions,
+    };
+    *state.maintenance.last_report.lock().unwrap() = Some(report.clone());
+    report
+}
@@ -0,0 +1,183 @@
+use std::{
+    collections::BTreeSet,
+    sync::atomic::{AtomicI64, Ordering},
+};
+
+use anyhow::Result;
+use mongodb::bson::doc;
+use starknet::core::types::FieldElement;
+
+use crate::{
+    config::MarketplaceApi,
+    endpoints::uri::{NFT_PP_CONTRACT, NFT_PP_ID},
+    models::AppState,
+    storage::{FindSpec, Storage},
+    utils::to_hex,
+};
+
+// refresh pings sent per run, the next run resumes after the last pinged block
+const MAX_REFRESHES: usize = 500;
+
+/// Last indexed block whose changes were pushed to the marketplaces, unset until the first run
+#[derive(Default)]
+pub struct RefreshCursor {
+    block: AtomicI64,
+}
+
+/// Refresh url of a token for a marketplace, `{contract}` and `{token_id}` are substituted
+pub fn get_refresh_url(
+    api: &MarketplaceApi,
+    contract: &FieldElement,
+    token_id: &FieldElement,
+) -> String {
+    api.url
+        .replace("{contract}", &to_hex(contract))
+        .replace("{token_id}", &token_id.to_string())
+}
+
+async fn get_latest_block(storage: &dyn Storage) -> Result<i64> {
+    let latest = storage
+        .find(
+            "domains",
+            doc! {},
+            FindSpec {
+                sort: Some(doc! { "_cursor.from": -1 }),
+                limit: Some(1),
+                ..Default::default()
+            },
+        )
+        .await?;
+    Ok(latest
+        .first()
+        .and_then(|doc| doc.get_document("_cursor").ok()?.get_i64("from").ok())
+        .unwrap_or_default())
+}
+
+// (block, identity) of the writes which change what the identity card shows: the domain
+// and its expiry, the main domain flag and the profile picture
+pub async fn get_changes(storage: &dyn Storage, since: i64) -> Result<Vec<(i64, String)>> {
+    let changed = doc! { "_cursor.from": { "$gt": since } };
+    let mut changes = vec![];
+    // a truncated collection may have more changes at its last block and after it
+    let mut horizon = i64::MAX;
+    for (collection, mut filter) in [
+        ("domains", changed.clone()),
+        ("id_owners", changed.clone()),
+        ("id_verifier_data", changed.clone()),
+    ] {
+        if collection == "id_verifier_data" {
+            filter.insert("field", doc! { "$in": [NFT_PP_CONTRACT, NFT_PP_ID] });
+        }
+        let docs = storage
+            .find(
+                collection,
+                filter,
+                FindSpec {
+                    sort: Some(doc! { "_cursor.from": 1 }),
+                    limit: Some(MAX_REFRESHES as i64),
+                    ..Default::default()
+                },
+            )
+            .await?;
+        let collection_changes: Vec<(i64, String)> = docs
+            .iter()
+            .filter_map(|doc| {
+                Some((
+                    doc.get_document("_cursor").ok()?.get_i64("from").ok()?,
+                    doc.get_str("id").ok()?.to_string(),
+                ))
+            })
+            .collect();
+        if docs.len() >= MAX_REFRESHES {
+            if let Some((block, _)) = collection_changes.last() {
+                horizon = horizon.min(*block);
+            }
+        }
+        changes.extend(collection_changes);
+    }
+    changes.sort();
+    if changes.iter().any(|(block, _)| *block < horizon) {
+        changes.retain(|(block, _)| *block < horizon);
+    } else {
+        // a single block with more changes than the budget is sent partially
+        changes.retain(|(block, _)| *block == horizon);
+    }
+    Ok(changes)
+}
+
+/// Keeps the changes of whole blocks under the refresh budget, so no change of a block
+/// is left behind when the cursor moves past it
+pub fn get_batch(changes: &[(i64, String)], max: usize) -> (Option<i64>, BTreeSet<String>) {
+    let mut ids = BTreeSet::new();
+    let mut last_block = None;
+    let mut i = 0;
+    while i < changes.len() {
+        let block = changes[i].0;
+        let end = changes[i..]
+            .iter()
+            .position(|(b, _)| *b != block)
+            .map_or(changes.len(), |offset| i + offset);
+        let block_ids: BTreeSet<&String> = changes[i..end].iter().map(|(_, id)| id).collect();
+        let new_ids = block_ids.iter().filter(|id| !ids.contains(**id)).count();
+        // a single block larger than the budget is still sent in one go
+        if last_block.is_some() && ids.len() + new_ids > max {
+            break;
+        }
+        ids.extend(block_ids.into_iter().cloned());
+        last_block = Some(block);
+        i = end;
+    }
+    (last_block, ids)
+}
+
+/// Pings the configured marketplaces for the identities changed since the last run
+pub async fn push_refreshes(state: &AppState) -> Result<usize> {
+    let apis = &state.conf.marketplaces.apis;
+    let cursor = &state.marketplace_refresh.block;
+    let since = cursor.load(Ordering::Relaxed);
+    if since == 0 {
+        // the history was listed by the marketplaces already
+        cursor.store(
+            get_latest_block(state.storage.as_ref()).await?,
+            Ordering::Relaxed,
+        );
+        return Ok(0);
+    }
+    if apis.is_empty() {
+        return Ok(0);
+    }
+
+    let changes = get_changes(state.storage.as_ref(), since).await?;
+    let (last_block, ids) = get_batch(&changes, MAX_REFRESHES);
+    let client = reqwest::Client::new();
+    let contract = state.conf.contracts.starknetid;
+    let mut sent = 0;
+    for id in &ids {
+        let token_id = match FieldElement::from_hex_be(id) {
+            Ok(token_id) => token_id,
+            Err(_) => continue,
+        };
+        for api in apis {
+            let mut request = client.post(get_refresh_url(api, &contract, &token_id));
+            if !api.api_key.is_empty() {
+                request = request.header(api.api_key_header.as_str(), api.api_key.as_str());
+            }
+            match request.send().await {
+                Ok(response) if response.status().is_success() => sent += 1,
+                Ok(response) => state.logger.warning(format!(
+                    "marketplaces: {} refused the refresh of {}: {}",
+                    api.name,
+                    id,
+                    response.status()
+                )),
+                Err(e) => state
+                    .logger
+                    .warning(format!("marketplaces: unable to reach {}: {}", api.name, e)),
+            }
+        }
+    }
+    if let Some(block) = last_block {
+        cursor.store(block, Ordering::Relaxed);
+    }
+    Ok(sent)
+}
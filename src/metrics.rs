@@ -0,0 +1,44 @@
+use std::{collections::BTreeMap, sync::Mutex};
+
+/// Process wide counters and gauges, rendered in the prometheus text format
+#[derive(Default)]
+pub struct Metrics {
+    values: Mutex<BTreeMap<String, i64>>,
+}
+
+/// Name of a metric with a single label, e.g. `mongo_connections{db="sales"}`
+pub fn labeled(name: &str, label: &str, value: &str) -> String {
+    format!("{}{{{}=\"{}\"}}", name, label, value)
+}
+
+impl Metrics {
+    pub fn add(&self, name: &str, delta: i64) {
+        *self
+            .values
+            .lock()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert(0) += delta;
+    }
+
+    pub fn set(&self, name: &str, value: i64) {
+        self.values.lock().unwrap().insert(name.to_string(), value);
+    }
+
+    pub fn key_count(&self) -> usize {
+        self.values.lock().unwrap().len()
+    }
+
+    pub fn get(&self, name: &str) -> i64 {
+        self.values.lock().unwrap().get(name).copied().unwrap_or(0)
+    }
+
+    pub fn render(&self) -> String {
+        self.values
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, value)| format!("{} {}\n", name, value))
+            .collect()
+    }
+}
@@ -5,24 +5,71 @@ use mongodb::{
 use starknet::core::types::FieldElement;
 
 use crate::{
+    activity::Activity,
+    address_labels::AddressLabels,
+    analytics::AnalyticsMirror,
+    auth::ApiKeyCache,
+    badges::{Badge, BadgeRules},
+    cache::ResponseCache,
+    clients::ClientUsage,
     config::{Config, OffchainResolver},
+    distribution::DistributionStats,
+    examples::ExampleStore,
+    exports::ExportStorage,
+    fallback::FallbackCache,
+    freezes::Freeze,
+    freshness::BlockTimestamps,
+    logger::Logger,
+    maintenance::MaintenanceLog,
+    marketplaces::RefreshCursor,
+    metrics::Metrics,
+    profanity::ProfanityFilter,
+    rate_limit::RateLimiter,
+    records::AddressRecord,
+    rpc_queue::RpcQueue,
+    storage::Storage,
+    suggestions::SuggestionDictionaries,
+    transparency::TransparencyLog,
     utils::to_hex,
-    logger::Logger, 
+    ws::feed::ChangeFeed,
 };
 use serde::{ser::SerializeSeq, Deserialize, Deserializer, Serialize, Serializer};
 use std::{
     collections::HashMap,
-    sync::{Arc, Mutex},
+    sync::{atomic::AtomicU64, Arc, Mutex},
 };
 
 pub struct AppState {
     pub conf: Config,
+    // document store of the starknetid database for the handlers migrated to it
+    pub storage: Arc<dyn Storage>,
     pub starknetid_db: Database,
     pub sales_db: Database,
     pub free_domains_db: Database,
     pub states: States,
+    pub badges: BadgeRules,
+    pub address_labels: AddressLabels,
+    pub profanity: ProfanityFilter,
+    pub suggestions: SuggestionDictionaries,
     pub dynamic_offchain_resolvers: Arc<Mutex<HashMap<String, OffchainResolver>>>,
-    pub logger : Logger,
+    pub last_l1_block: AtomicU64,
+    pub metrics: Arc<Metrics>,
+    pub rpc_queue: RpcQueue,
+    pub export_storage: Option<ExportStorage>,
+    pub analytics: Option<AnalyticsMirror>,
+    pub transparency: TransparencyLog,
+    pub maintenance: MaintenanceLog,
+    pub distribution: DistributionStats,
+    pub block_timestamps: BlockTimestamps,
+    pub marketplace_refresh: RefreshCursor,
+    pub rate_limiter: RateLimiter,
+    pub api_key_cache: ApiKeyCache,
+    pub client_usage: ClientUsage,
+    pub change_feed: ChangeFeed,
+    pub response_cache: ResponseCache,
+    pub resolution_fallback: FallbackCache,
+    pub examples: ExampleStore,
+    pub logger: Logger,
 }
 
 fn serialize_felt<S>(field_element: &FieldElement, serializer: S) -> Result<S::Ok, S::Error>
@@ -77,13 +124,30 @@ pub struct IdentityData {
     pub id: FieldElement,
     #[serde(serialize_with = "serialize_felt")]
     pub owner: FieldElement,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub owner_display_address: Option<String>,
     pub main: bool,
     pub creation_date: u64,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub creation_date_display: Option<String>,
+    // off-chain bio set by the owner
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<Activity>,
     #[serde(deserialize_with = "deserialize_optional_domain")]
     pub domain: Option<Domain>,
     pub user_data: Vec<UserData>,
     pub verifier_data: Vec<VerifierData>,
     pub extended_verifier_data: Vec<ExtendedVerifierData>,
+    #[serde(skip_deserializing)]
+    pub badges: Vec<Badge>,
+    // addresses of the identity on the other chains
+    #[serde(skip_deserializing, skip_serializing_if = "Vec::is_empty")]
+    pub records: Vec<AddressRecord>,
+    // reported compromised by its owner, counterparties should be wary
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub compromised: Option<Freeze>,
 }
 
 fn deserialize_optional_domain<'de, D>(deserializer: D) -> Result<Option<Domain>, D::Error>
@@ -108,7 +172,11 @@ pub struct Domain {
     pub migrated: bool,
     pub root: bool,
     pub creation_date: u64,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub creation_date_display: Option<String>,
     pub expiry: Option<u64>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub expiry_display: Option<String>,
     #[serde(serialize_with = "serialize_opt_felt")]
     pub resolver: Option<FieldElement>,
     #[serde(serialize_with = "serialize_opt_felt")]
@@ -133,6 +201,13 @@ pub struct VerifierData {
     pub field: FieldElement,
     #[serde(serialize_with = "serialize_felt")]
     pub data: FieldElement,
+    // block the verification was written at
+    #[serde(default)]
+    pub block: Option<i64>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<i64>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub is_stale: Option<bool>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -143,6 +218,13 @@ pub struct ExtendedVerifierData {
     pub field: FieldElement,
     #[serde(serialize_with = "serialize_vec_felt")]
     pub extended_data: Vec<FieldElement>,
+    // block the verification was written at
+    #[serde(default)]
+    pub block: Option<i64>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<i64>,
+    #[serde(skip_deserializing, skip_serializing_if = "Option::is_none")]
+    pub is_stale: Option<bool>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -0,0 +1,336 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use starknet::{
+    core::{types::FieldElement, utils::parse_cairo_short_string},
+    macros::selector,
+};
+use starknet_id::decode;
+
+use crate::{
+    freshness::get_block_timestamp, models::AppState, simulation::rpc_request, utils::to_hex,
+};
+
+const YEAR_SECS: i64 = 365 * 24 * 3600;
+
+/// Event emitted by a transaction, as returned in its receipt
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawEvent {
+    pub from_address: FieldElement,
+    pub keys: Vec<FieldElement>,
+    pub data: Vec<FieldElement>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum NamingAction {
+    DomainRegistered {
+        domain: String,
+        id: String,
+        expiry: i64,
+        // rounded duration, known when the block timestamp is
+        years: Option<i64>,
+    },
+    DomainRenewed {
+        domain: String,
+        expiry: i64,
+    },
+    ResolverUpdated {
+        domain: String,
+        resolver: String,
+    },
+    MainDomainSet {
+        address: String,
+        // empty when the main domain was cleared
+        domain: Option<String>,
+    },
+    DomainTransferred {
+        domain: String,
+        from_id: String,
+        to_id: String,
+    },
+    IdentityMinted {
+        id: String,
+        owner: String,
+    },
+    IdentityTransferred {
+        id: String,
+        from: String,
+        to: String,
+    },
+    UserDataUpdated {
+        id: String,
+        field: String,
+    },
+    VerifierDataUpdated {
+        id: String,
+        field: String,
+        verifier: String,
+    },
+    MainIdentitySet {
+        owner: String,
+        id: String,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DecodedAction {
+    #[serde(flatten)]
+    pub action: NamingAction,
+    pub description: String,
+}
+
+fn felt_to_i64(felt: &FieldElement) -> Option<i64> {
+    u64::try_from(*felt).ok().map(|value| value as i64)
+}
+
+fn felt_to_id(felt: &FieldElement) -> String {
+    felt.to_string()
+}
+
+fn get_field_name(field: &FieldElement) -> String {
+    parse_cairo_short_string(field).unwrap_or_else(|_| to_hex(field))
+}
+
+/// Domain of its encoded labels, the .stark root is implicit:
+/// [encode("sub"), encode("ben")] for sub.ben.stark
+pub fn decode_labels(labels: &[FieldElement]) -> String {
+    let domain = labels
+        .iter()
+        .map(|label| decode(*label))
+        .collect::<Vec<_>>()
+        .join(".");
+    format!("{}.stark", domain)
+}
+
+// span of encoded labels starting with its length
+fn decode_domain(felts: &[FieldElement]) -> Option<String> {
+    let len = felts.first().and_then(felt_to_i64)? as usize;
+    let labels = felts.get(1..1 + len)?;
+    if labels.is_empty() {
+        return None;
+    }
+    Some(decode_labels(labels))
+}
+
+/// Action of a naming or identity contract event, None for the other events
+pub fn decode_event(
+    event: &RawEvent,
+    naming: &FieldElement,
+    starknetid: &FieldElement,
+    timestamp: Option<i64>,
+) -> Option<NamingAction> {
+    let (keys, data) = (&event.keys, &event.data);
+    let selector = *keys.first()?;
+    if event.from_address == *naming {
+        if selector == selector!("DomainMint") {
+            let domain = decode_domain(&keys[1..])?;
+            let expiry = felt_to_i64(data.get(1)?)?;
+            return Some(NamingAction::DomainRegistered {
+                domain,
+                id: felt_to_id(data.first()?),
+                expiry,
+                years: timestamp.map(|timestamp| {
+                    ((expiry - timestamp) as f64 / YEAR_SECS as f64).round() as i64
+                }),
+            });
+        }
+        if selector == selector!("DomainRenewal") {
+            let domain = decode_domain(&keys[1..])?;
+            return Some(NamingAction::DomainRenewed {
+                domain,
+                expiry: felt_to_i64(data.first()?)?,
+            });
+        }
+        if selector == selector!("DomainResolverUpdate") {
+            let domain = decode_domain(&keys[1..])?;
+            return Some(NamingAction::ResolverUpdated {
+                domain,
+                resolver: to_hex(data.first()?),
+            });
+        }
+        if selector == selector!("AddressToDomainUpdate") {
+            return Some(NamingAction::MainDomainSet {
+                address: to_hex(keys.get(1)?),
+                domain: decode_domain(data),
+            });
+        }
+        if selector == selector!("DomainTransfer") {
+            let domain = decode_domain(&keys[1..])?;
+            return Some(NamingAction::DomainTransferred {
+                domain,
+                from_id: felt_to_id(data.first()?),
+                to_id: felt_to_id(data.get(1)?),
+            });
+        }
+    } else if event.from_address == *starknetid {
+        if selector == selector!("Transfer") {
+            // token_id is an u256 split in a low and a high part, identities fit in the low one
+            let (from, to, id) = (keys.get(1)?, keys.get(2)?, keys.get(3)?);
+            return Some(if *from == FieldElement::ZERO {
+                NamingAction::IdentityMinted {
+                    id: felt_to_id(id),
+                    owner: to_hex(to),
+                }
+            } else {
+                NamingAction::IdentityTransferred {
+                    id: felt_to_id(id),
+                    from: to_hex(from),
+                    to: to_hex(to),
+                }
+            });
+        }
+        if selector == selector!("UserDataUpdate") {
+            return Some(NamingAction::UserDataUpdated {
+                id: felt_to_id(keys.get(1)?),
+                field: get_field_name(data.first()?),
+            });
+        }
+        if selector == selector!("VerifierDataUpdate") {
+            return Some(NamingAction::VerifierDataUpdated {
+                id: felt_to_id(keys.get(1)?),
+                field: get_field_name(data.first()?),
+                verifier: to_hex(data.get(2)?),
+            });
+        }
+        if selector == selector!("MainIdUpdate") {
+            return Some(NamingAction::MainIdentitySet {
+                owner: to_hex(keys.get(1)?),
+                id: felt_to_id(data.first()?),
+            });
+        }
+    }
+    None
+}
+
+fn plural(count: i64, unit: &str) -> String {
+    if count == 1 {
+        format!("1 {}", unit)
+    } else {
+        format!("{} {}s", count, unit)
+    }
+}
+
+fn format_date(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+pub fn describe(action: &NamingAction) -> String {
+    match action {
+        NamingAction::DomainRegistered {
+            domain,
+            years: Some(years),
+            ..
+        } if *years > 0 => format!("registered {} for {}", domain, plural(*years, "year")),
+        NamingAction::DomainRegistered { domain, expiry, .. } => {
+            format!("registered {} until {}", domain, format_date(*expiry))
+        }
+        NamingAction::DomainRenewed { domain, expiry } => {
+            format!("renewed {} until {}", domain, format_date(*expiry))
+        }
+        NamingAction::ResolverUpdated { domain, resolver } => {
+            format!("set the resolver of {} to {}", domain, resolver)
+        }
+        NamingAction::MainDomainSet {
+            address,
+            domain: Some(domain),
+        } => format!("set {} as the main domain of {}", domain, address),
+        NamingAction::MainDomainSet { address, .. } => {
+            format!("cleared the main domain of {}", address)
+        }
+        NamingAction::DomainTransferred {
+            domain,
+            from_id,
+            to_id,
+        } => format!(
+            "moved {} from identity {} to identity {}",
+            domain, from_id, to_id
+        ),
+        NamingAction::IdentityMinted { id, owner } => {
+            format!("minted identity {} for {}", id, owner)
+        }
+        NamingAction::IdentityTransferred { id, from, to } => {
+            format!("transferred identity {} from {} to {}", id, from, to)
+        }
+        NamingAction::UserDataUpdated { id, field } => {
+            format!("updated the {} data of identity {}", field, id)
+        }
+        NamingAction::VerifierDataUpdated {
+            id,
+            field,
+            verifier,
+        } => format!(
+            "verified the {} of identity {} with verifier {}",
+            field, id, verifier
+        ),
+        NamingAction::MainIdentitySet { owner, id } => {
+            format!("set identity {} as the main identity of {}", id, owner)
+        }
+    }
+}
+
+fn parse_felts(value: &Value) -> Vec<FieldElement> {
+    value
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| FieldElement::from_hex_be(value.as_str()?).ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+pub fn parse_events(receipt: &Value) -> Vec<RawEvent> {
+    receipt["events"]
+        .as_array()
+        .map(|events| {
+            events
+                .iter()
+                .filter_map(|event| {
+                    Some(RawEvent {
+                        from_address: FieldElement::from_hex_be(event["from_address"].as_str()?)
+                            .ok()?,
+                        keys: parse_felts(&event["keys"]),
+                        data: parse_felts(&event["data"]),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Block and naming actions of a transaction, decoded from the events of its receipt
+pub async fn get_naming_actions(
+    state: &AppState,
+    hash: &FieldElement,
+) -> Result<(Option<i64>, Vec<DecodedAction>)> {
+    let receipt = rpc_request(
+        state,
+        "starknet_getTransactionReceipt",
+        json!({ "transaction_hash": to_hex(hash) }),
+    )
+    .await?;
+    if receipt.is_null() {
+        return Err(anyhow!("Transaction not found"));
+    }
+    let block = receipt["block_number"].as_i64();
+    let timestamp = match block {
+        Some(block) => get_block_timestamp(state, block).await,
+        None => None,
+    };
+    let contracts = &state.conf.contracts;
+    let actions = parse_events(&receipt)
+        .iter()
+        .filter_map(|event| {
+            decode_event(event, &contracts.naming, &contracts.starknetid, timestamp)
+        })
+        .map(|action| DecodedAction {
+            description: describe(&action),
+            action,
+        })
+        .collect();
+    Ok((block, actions))
+}
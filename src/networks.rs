@@ -0,0 +1,136 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::Body,
+    http::{Request, Uri},
+    response::{IntoResponse, Response},
+    Router,
+};
+use hyper::service::Service;
+
+use crate::{
+    errors::{ApiError, ErrorCode},
+    routes::RouteSpec,
+};
+
+pub const NETWORK_PARAM: &str = "network";
+
+/// Path prefix of the network of a request, for the redirects to stay on it
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetworkPrefix(pub String);
+
+/// Network a request is made on and its uri without the network: a path prefix, e.g.
+/// /sepolia/domain_to_addr, or else a `network` query parameter. None is the default network
+pub fn split_network(uri: &Uri, known: &[&str]) -> Result<(Option<String>, String), String> {
+    let path = uri.path();
+    let query = uri.query().unwrap_or_default();
+    let prefix = path
+        .strip_prefix('/')
+        .and_then(|path| path.split('/').next())
+        .unwrap_or_default();
+    if known.contains(&prefix) {
+        let rest = &path[prefix.len() + 1..];
+        let rest = if rest.is_empty() { "/" } else { rest };
+        let path_and_query = match query {
+            "" => rest.to_string(),
+            query => format!("{}?{}", rest, query),
+        };
+        return Ok((Some(prefix.to_string()), path_and_query));
+    }
+
+    // the other parameters are kept as they were encoded
+    let mut network = None;
+    let mut params = vec![];
+    for param in query.split('&').filter(|param| !param.is_empty()) {
+        match param.split_once('=') {
+            Some((NETWORK_PARAM, value)) => network = Some(value.to_string()),
+            _ => params.push(param),
+        }
+    }
+    let network = match network {
+        Some(network) if known.contains(&network.as_str()) => Some(network),
+        Some(network) => return Err(format!("Unknown network: {}", network)),
+        None => None,
+    };
+    let path_and_query = match params.is_empty() {
+        true => path.to_string(),
+        false => format!("{}?{}", path, params.join("&")),
+    };
+    Ok((network, path_and_query))
+}
+
+/// Network names also starting a route, whose requests would be served on the network
+pub fn get_route_collisions<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    routes: &[RouteSpec],
+) -> Vec<String> {
+    names
+        .into_iter()
+        .filter(|name| {
+            routes
+                .iter()
+                .any(|route| route.path.trim_start_matches('/').split('/').next() == Some(name))
+        })
+        .map(String::from)
+        .collect()
+}
+
+struct NetworkRouters {
+    default_name: String,
+    known: Vec<String>,
+    // only locked to route a request, the futures of the routers own what they serve it with
+    default: Mutex<Router>,
+    networks: HashMap<String, Mutex<Router>>,
+}
+
+/// Router of the default network, handing the requests of the others to their own router
+pub fn build_router(
+    default_name: String,
+    default: Router,
+    networks: HashMap<String, Router>,
+) -> Router {
+    let routers = Arc::new(NetworkRouters {
+        known: networks
+            .keys()
+            .cloned()
+            .chain(std::iter::once(default_name.clone()))
+            .collect(),
+        default_name,
+        default: Mutex::new(default),
+        networks: networks
+            .into_iter()
+            .map(|(name, router)| (name, Mutex::new(router)))
+            .collect(),
+    });
+    Router::new().fallback(move |mut req: Request<Body>| {
+        let routers = routers.clone();
+        async move {
+            let known: Vec<&str> = routers.known.iter().map(String::as_str).collect();
+            let (network, path_and_query) = match split_network(req.uri(), &known) {
+                Ok(split) => split,
+                Err(e) => return ApiError::new(ErrorCode::InvalidInput, e).into_response(),
+            };
+            // the network routers only see the path they serve
+            if let Ok(uri) = path_and_query.parse::<Uri>() {
+                *req.uri_mut() = uri;
+            }
+            let router = match network.as_deref() {
+                Some(network) if network != routers.default_name => {
+                    req.extensions_mut()
+                        .insert(NetworkPrefix(format!("/{}", network)));
+                    routers.networks.get(network)
+                }
+                _ => None,
+            };
+            let future = router.unwrap_or(&routers.default).lock().unwrap().call(req);
+            let result: Result<Response, _> = future.await;
+            match result {
+                Ok(response) => response,
+                Err(e) => match e {},
+            }
+        }
+    })
+}
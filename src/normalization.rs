@@ -0,0 +1,127 @@
+use anyhow::Result;
+use deunicode::deunicode;
+use mongodb::{
+    bson::{doc, Document},
+    options::IndexOptions,
+    IndexModel,
+};
+
+use crate::{
+    models::AppState,
+    pagination::{get_sort, DOMAIN_ORDER},
+    storage::{FindSpec, Storage},
+};
+
+pub const SKELETONS: &str = "domain_skeletons";
+pub const SYNC_INTERVAL_SECS: u64 = 60;
+// any character outside of ascii, the latin domains are found by the search directly
+const NON_ASCII: &str = "[^\\x00-\\x7f]";
+// sorts after every character a skeleton can contain
+const PREFIX_END: char = '\u{7f}';
+
+/// Latin approximation of a label, as typed on a latin keyboard: "москва" -> "moskva",
+/// "αθηνα" -> "athena", "东京" -> "dongjing"
+pub fn get_skeleton(label: &str) -> String {
+    deunicode(label)
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect()
+}
+
+/// Skeleton of each label of a domain, "москва.stark" -> "moskva.stark"
+pub fn get_domain_skeleton(domain: &str) -> String {
+    domain
+        .split('.')
+        .map(get_skeleton)
+        .collect::<Vec<String>>()
+        .join(".")
+}
+
+/// Unicode domains whose skeleton starts with `prefix`, in alphabetical order, `after`
+/// resumes past the last domain of the previous page
+pub async fn find_transliterated(
+    storage: &dyn Storage,
+    prefix: &str,
+    after: Option<&str>,
+    limit: i64,
+) -> Result<Vec<String>> {
+    let mut filter = doc! {
+        "skeleton": { "$gte": prefix, "$lt": format!("{}{}", prefix, PREFIX_END) },
+    };
+    if let Some(after) = after {
+        filter.insert("domain", doc! { "$gt": after });
+    }
+    Ok(storage
+        .find(
+            SKELETONS,
+            filter,
+            FindSpec {
+                sort: Some(get_sort(DOMAIN_ORDER)),
+                skip: 0,
+                limit: Some(limit),
+            },
+        )
+        .await?
+        .iter()
+        .filter_map(|doc| doc.get_str("domain").ok().map(String::from))
+        .collect())
+}
+
+/// Adds the skeletons of the unicode domains indexed after `since`, returns the block to
+/// resume from
+pub async fn sync_skeletons(storage: &dyn Storage, since: i64) -> Result<i64> {
+    let domains = storage
+        .find(
+            "domains",
+            doc! {
+                "domain": { "$regex": NON_ASCII },
+                "_cursor.from": { "$gt": since },
+                "_cursor.to": null,
+            },
+            FindSpec::default(),
+        )
+        .await?;
+    let mut last_block = since;
+    for doc in &domains {
+        let domain = match doc.get_str("domain") {
+            Ok(domain) => domain,
+            Err(_) => continue,
+        };
+        storage
+            .upsert_one(
+                SKELETONS,
+                doc! { "domain": domain },
+                doc! { "skeleton": get_domain_skeleton(domain) },
+            )
+            .await?;
+        if let Ok(block) = doc.get_document("_cursor").and_then(|c| c.get_i64("from")) {
+            last_block = last_block.max(block);
+        }
+    }
+    Ok(last_block)
+}
+
+/// Creates the indexes of the skeleton searches, a no-op once they exist
+pub async fn init(state: &AppState) {
+    let indexes = vec![
+        IndexModel::builder()
+            .keys(doc! { "domain": 1 })
+            .options(IndexOptions::builder().unique(true).build())
+            .build(),
+        IndexModel::builder()
+            .keys(doc! { "skeleton": 1, "domain": 1 })
+            .build(),
+    ];
+    if let Err(e) = state
+        .starknetid_db
+        .collection::<Document>(SKELETONS)
+        .create_indexes(indexes, None)
+        .await
+    {
+        state.logger.warning(format!(
+            "normalization: unable to create skeleton indexes: {}",
+            e
+        ));
+    }
+}
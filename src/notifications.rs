@@ -0,0 +1,155 @@
+use anyhow::Result;
+use mongodb::bson::{doc, from_document, to_document, Bson};
+use serde::{Deserialize, Serialize};
+use starknet::core::{types::FieldElement, utils::parse_cairo_short_string};
+
+use crate::{
+    freshness::get_freshness,
+    models::AppState,
+    preferences::PREFERENCES_COLLECTION,
+    storage::{FindSpec, Storage},
+    utils::to_hex,
+};
+
+pub const NOTIFICATIONS_COLLECTION: &str = "notifications";
+// notifications listed by /me/notifications
+pub const MAX_LISTED: i64 = 100;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NotificationKind {
+    Reverification,
+}
+
+/// Message for an address, delivered in its /me/notifications inbox
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Notification {
+    pub address: String,
+    pub kind: NotificationKind,
+    // a given key is only notified once
+    pub key: String,
+    pub message: String,
+    pub created_at: i64,
+}
+
+/// Stores the notification unless one with the same key was already sent
+pub async fn notify(storage: &dyn Storage, notification: Notification) -> Result<bool> {
+    let filter = doc! { "address": &notification.address, "key": &notification.key };
+    if storage
+        .find_one(NOTIFICATIONS_COLLECTION, filter)
+        .await?
+        .is_some()
+    {
+        return Ok(false);
+    }
+    storage
+        .insert_one(NOTIFICATIONS_COLLECTION, to_document(&notification)?)
+        .await?;
+    Ok(true)
+}
+
+pub async fn get_notifications(
+    storage: &dyn Storage,
+    address: &FieldElement,
+) -> Result<Vec<Notification>> {
+    storage
+        .find(
+            NOTIFICATIONS_COLLECTION,
+            doc! { "address": to_hex(address) },
+            FindSpec {
+                sort: Some(doc! { "created_at": -1 }),
+                limit: Some(MAX_LISTED),
+                ..Default::default()
+            },
+        )
+        .await?
+        .into_iter()
+        .map(|mut doc| {
+            doc.remove("_id");
+            Ok(from_document(doc)?)
+        })
+        .collect()
+}
+
+// key of a verification, a new verification of the same field gets its own reminder
+pub fn get_reverification_key(id: &str, field: &str, block: i64) -> String {
+    format!("reverification:{}:{}:{}", id, field, block)
+}
+
+/// Reminds the addresses which opted in when the verifications of their identities are stale
+pub async fn send_reverification_reminders(state: &AppState) -> Result<usize> {
+    let storage = state.storage.as_ref();
+    let verifiers: Vec<String> = state.conf.contracts.verifiers.iter().map(to_hex).collect();
+    let mut sent = 0;
+    let opted_in = storage
+        .find(
+            PREFERENCES_COLLECTION,
+            doc! { "reverification_reminders": true },
+            FindSpec::default(),
+        )
+        .await?;
+    for preferences in opted_in {
+        let address = match preferences.get_str("address") {
+            Ok(address) => address.to_string(),
+            Err(_) => continue,
+        };
+        let ids: Vec<Bson> = storage
+            .find(
+                "id_owners",
+                doc! { "owner": &address, "_cursor.to": null },
+                FindSpec::default(),
+            )
+            .await?
+            .iter()
+            .filter_map(|doc| doc.get("id").cloned())
+            .collect();
+        if ids.is_empty() {
+            continue;
+        }
+        let verifications = storage
+            .find(
+                "id_verifier_data",
+                doc! {
+                    "id": { "$in": ids },
+                    "verifier": { "$in": verifiers.clone() },
+                    "data": { "$ne": null },
+                    "_cursor.to": null,
+                },
+                FindSpec::default(),
+            )
+            .await?;
+        for verification in verifications {
+            let (id, field, block) = match (
+                verification.get_str("id"),
+                verification.get_str("field"),
+                verification
+                    .get_document("_cursor")
+                    .and_then(|cursor| cursor.get_i64("from")),
+            ) {
+                (Ok(id), Ok(field), Ok(block)) => (id, field, block),
+                _ => continue,
+            };
+            if get_freshness(state, Some(block)).await.1 != Some(true) {
+                continue;
+            }
+            let field_name = FieldElement::from_hex_be(field)
+                .ok()
+                .and_then(|field| parse_cairo_short_string(&field).ok())
+                .unwrap_or_else(|| field.to_string());
+            let notification = Notification {
+                address: address.clone(),
+                kind: NotificationKind::Reverification,
+                key: get_reverification_key(id, field, block),
+                message: format!(
+                    "Your {} verification of identity {} is outdated, verify it again to keep your profile trustworthy",
+                    field_name, id
+                ),
+                created_at: chrono::Utc::now().timestamp(),
+            };
+            if notify(storage, notification).await? {
+                sent += 1;
+            }
+        }
+    }
+    Ok(sent)
+}
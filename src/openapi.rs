@@ -0,0 +1,308 @@
+use std::collections::BTreeMap;
+
+use utoipa::{
+    openapi::{
+        path::{
+            OperationBuilder, Parameter, ParameterBuilder, ParameterIn, PathItemBuilder,
+            PathItemType,
+        },
+        request_body::RequestBodyBuilder,
+        schema::{ObjectBuilder, Schema, SchemaType},
+        security::{
+            ApiKey, ApiKeyValue, Http, HttpAuthScheme, SecurityRequirement, SecurityScheme,
+        },
+        ComponentsBuilder, ContentBuilder, InfoBuilder, OpenApi, OpenApiBuilder, PathsBuilder, Ref,
+        RefOr, Required, ResponseBuilder,
+    },
+    IntoParams, ToSchema,
+};
+
+use crate::{
+    endpoints::*,
+    errors::{ErrorBody, ErrorCode, ErrorDetails},
+    failures::{Component, FailureBody},
+    routes::{AuthScope, CachePolicy, Method, RouteSpec},
+};
+
+const API_KEY: &str = "api_key";
+const ADMIN_KEY: &str = "admin_key";
+const USER_TOKEN: &str = "user_token";
+
+/// Swagger UI of the document served at /openapi.json, its assets come from a cdn
+pub const DOCS_HTML: &str = r##"<!DOCTYPE html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8" />
+    <title>starknetid_server api</title>
+    <link rel="stylesheet" href="https://unpkg.com/swagger-ui-dist@5/swagger-ui.css" />
+  </head>
+  <body>
+    <div id="swagger-ui"></div>
+    <script src="https://unpkg.com/swagger-ui-dist@5/swagger-ui-bundle.js" crossorigin></script>
+    <script>
+      window.onload = () => {
+        window.ui = SwaggerUIBundle({ url: "openapi.json", dom_id: "#swagger-ui" });
+      };
+    </script>
+  </body>
+</html>
+"##;
+
+/// Parameters or body a handler reads besides its path segments
+pub enum Input {
+    Query(Vec<Parameter>),
+    Body(RefOr<Schema>),
+}
+
+fn query<T: IntoParams>() -> Input {
+    Input::Query(T::into_params(|| Some(ParameterIn::Query)))
+}
+
+fn body<'s, T: ToSchema<'s>>() -> Input {
+    Input::Body(T::schema().1)
+}
+
+/// Input of the handler, None when it only reads its path or headers
+#[rustfmt::skip]
+pub fn get_input(handler: &str) -> Option<Input> {
+    Some(match handler {
+        "endpoints::addr_has_rev" => query::<addr_has_rev::AddrHasRevQuery>(),
+        "endpoints::addr_to_available_ids" => query::<addr_to_available_ids::AddrQuery>(),
+        "endpoints::addr_to_domain" => query::<addr_to_domain::AddrToDomainQuery>(),
+        "endpoints::addr_to_domain_at" => query::<addr_to_domain_at::AddrToDomainAtQuery>(),
+        "endpoints::addr_to_external_domains" => query::<addr_to_external_domains::DomainQuery>(),
+        "endpoints::addr_to_full_ids" => query::<addr_to_full_ids::AddrQuery>(),
+        "endpoints::addr_to_token_id" => query::<addr_to_token_id::TokenIdQuery>(),
+        "endpoints::addressbook::resolve_sync" => body::<addressbook::resolve_sync::ResolveSyncQuery>(),
+        "endpoints::addrs_to_domains" => body::<addrs_to_domains::AddrToDomainsQuery>(),
+        "endpoints::admin::add_api_key" => body::<admin::add_api_key::AddApiKeyQuery>(),
+        "endpoints::admin::create_raffle" => body::<admin::create_raffle::CreateRaffleQuery>(),
+        "endpoints::admin::delete_theme" => body::<admin::delete_theme::DeleteThemeQuery>(),
+        "endpoints::admin::draw_raffle" => body::<admin::draw_raffle::DrawRaffleQuery>(),
+        "endpoints::admin::get_samples" => query::<admin::get_samples::GetSamplesQuery>(),
+        "endpoints::admin::maintenance" => query::<admin::maintenance::MaintenanceQuery>(),
+        "endpoints::campaigns::get_free_domain" => query::<campaigns::get_free_domain::FreeDomainQuery>(),
+        "endpoints::compare" => query::<compare::CompareQuery>(),
+        "endpoints::contracts::claim" => body::<contracts::claim::ClaimQuery>(),
+        "endpoints::crosschain::solana::claim" => body::<crosschain::solana::claim::SigQuery>(),
+        "endpoints::crosschain::solana::claim_ledger" => body::<crosschain::solana::claim_ledger::SigQuery>(),
+        "endpoints::data_to_ids" => query::<data_to_ids::StarknetIdQuery>(),
+        "endpoints::decode::batch" => body::<decode::batch::DecodeBatchQuery>(),
+        "endpoints::domain::availability" => query::<domain::availability::AvailabilityQuery>(),
+        "endpoints::domain::avatar" => query::<domain::avatar::AvatarQuery>(),
+        "endpoints::domain::history" => query::<domain::history::HistoryQuery>(),
+        "endpoints::domain::owner_at" => query::<domain::owner_at::OwnerAtQuery>(),
+        "endpoints::domain_to_addr" => query::<domain_to_addr::DomainQuery>(),
+        "endpoints::domain_to_addr_at" => query::<domain_to_addr_at::DomainToAddrAtQuery>(),
+        "endpoints::domain_to_data" => query::<domain_to_data::DomainQuery>(),
+        "endpoints::domain_to_record" => query::<domain_to_record::RecordQuery>(),
+        "endpoints::domains::expiring" => query::<domains::expiring::ExpiringQuery>(),
+        "endpoints::domains::search" => query::<domains::search::SearchQuery>(),
+        "endpoints::domains_to_addrs" => body::<domains_to_addrs::DomainsToAddrsQuery>(),
+        "endpoints::events::get_events" => query::<events::get_events::EventsQuery>(),
+        "endpoints::events::stream" => query::<events::stream::StreamQuery>(),
+        "endpoints::export::domains_delta" => query::<export::domains_delta::DeltaQuery>(),
+        "endpoints::galxe::verify" => body::<galxe::verify::EmailQuery>(),
+        "endpoints::get_altcoin_quote" => query::<get_altcoin_quote::AddrQuery>(),
+        "endpoints::governance::power" => query::<governance::power::PowerQuery>(),
+        "endpoints::id_to_data" => query::<id_to_data::IdQuery>(),
+        "endpoints::identity::set_description" => body::<identity::set_description::SetDescriptionQuery>(),
+        "endpoints::img" => query::<img::ImageQuery>(),
+        "endpoints::integrity::fix_tx" => query::<integrity::fix_tx::FixTxQuery>(),
+        "endpoints::me::expiry_ics" => query::<me::expiry_ics::ExpiryFeedQuery>(),
+        "endpoints::me::freeze" => body::<me::freeze::FreezeQuery>(),
+        "endpoints::me::token" => body::<me::token::TokenQuery>(),
+        "endpoints::me::unfreeze" => body::<me::unfreeze::UnfreezeQuery>(),
+        "endpoints::org::set_member" => body::<org::set_member::SetMemberQuery>(),
+        "endpoints::partners::revenue" => query::<partners::revenue::RevenueQuery>(),
+        "endpoints::portfolio::transfer_tx" => body::<portfolio::transfer_tx::TransferTxQuery>(),
+        "endpoints::preferences::set" => body::<preferences::set::SetPreferencesQuery>(),
+        "endpoints::raffles::enter" => body::<raffles::enter::EnterQuery>(),
+        "endpoints::raffles::get_entrants" => query::<raffles::get_entrants::GetEntrantsQuery>(),
+        "endpoints::raffles::get_raffle" => query::<raffles::get_raffle::GetRaffleQuery>(),
+        "endpoints::referral::add_click" => body::<referral::add_click::AddClickQuery>(),
+        "endpoints::referral::click_count" => query::<referral::click_count::IdQuery>(),
+        "endpoints::referral::revenue" => query::<referral::revenue::IdQuery>(),
+        "endpoints::referral::sales_count" => query::<referral::sales_count::IdQuery>(),
+        "endpoints::rendering::get_theme" => query::<rendering::get_theme::GetThemeQuery>(),
+        "endpoints::rendering::set_theme" => body::<crate::rendering::theme::Theme>(),
+        "endpoints::renewal::get_metahash" => query::<renewal::get_metahash::GetMetaHashQuery>(),
+        "endpoints::renewal::get_non_subscribed_domains" => query::<renewal::get_non_subscribed_domains::StarknetIdQuery>(),
+        "endpoints::renewal::get_renewal_data" => query::<renewal::get_renewal_data::StarknetIdQuery>(),
+        "endpoints::renewal::get_subscription_info" => query::<renewal::get_subscription_info::StarknetIdQuery>(),
+        "endpoints::renewal::status" => query::<renewal::status::StatusQuery>(),
+        "endpoints::social_to_domain" => query::<social_to_domain::SocialQuery>(),
+        "endpoints::starkscan::fetch_nfts" => query::<starkscan::fetch_nfts::FetchNftsQuery>(),
+        "endpoints::stats::count_addrs" => query::<stats::count_addrs::CountAddrsQuery>(),
+        "endpoints::stats::count_club_domains" => query::<stats::count_club_domains::CountClubDomainsQuery>(),
+        "endpoints::stats::count_created" => query::<stats::count_created::CountCreatedQuery>(),
+        "endpoints::stats::count_domains" => query::<stats::count_domains::CountDomainsQuery>(),
+        "endpoints::stats::count_ids" => query::<stats::count_ids::CountDomainsQuery>(),
+        "endpoints::stats::count_renewed" => query::<stats::count_renewed::CountRenewedQuery>(),
+        "endpoints::stats::retention" => query::<stats::retention::RetentionQuery>(),
+        "endpoints::transparency::proof" => query::<transparency::proof::ProofQuery>(),
+        "endpoints::tx_builder::register" => query::<tx_builder::register::RegisterQuery>(),
+        "endpoints::uri" => query::<uri::TokenIdQuery>(),
+        "endpoints::uris" => query::<uris::TokenIdsQuery>(),
+        "endpoints::watch::longpoll" => query::<watch::longpoll::LongPollQuery>(),
+        "endpoints::webhooks::delete" => body::<webhooks::delete::DeleteWebhookQuery>(),
+        "endpoints::webhooks::deliveries" => query::<webhooks::deliveries::DeliveriesQuery>(),
+        "endpoints::webhooks::register" => body::<webhooks::register::RegisterWebhookQuery>(),
+        _ => return None,
+    })
+}
+
+/// OpenAPI path of an axum route, e.g. /domain/:domain/owner_at becomes /domain/{domain}/owner_at
+pub fn to_openapi_path(path: &str) -> (String, Vec<String>) {
+    let mut params = vec![];
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| match segment.strip_prefix(&[':', '*'][..]) {
+            Some(name) => {
+                params.push(name.to_string());
+                format!("{{{}}}", name)
+            }
+            None => segment.to_string(),
+        })
+        .collect();
+    (segments.join("/"), params)
+}
+
+fn string_schema() -> RefOr<Schema> {
+    RefOr::T(Schema::Object(
+        ObjectBuilder::new().schema_type(SchemaType::String).build(),
+    ))
+}
+
+fn get_tag(spec: &RouteSpec) -> String {
+    serde_json::to_value(spec.group)
+        .ok()
+        .and_then(|value| value.as_str().map(String::from))
+        .unwrap_or_default()
+}
+
+fn get_security(auth: AuthScope) -> Vec<SecurityRequirement> {
+    let scheme = |name: &str| SecurityRequirement::new(name, Vec::<String>::new());
+    match auth {
+        // the signature is part of the body
+        AuthScope::Public | AuthScope::Signature => vec![],
+        AuthScope::Partner => vec![scheme(API_KEY), scheme(ADMIN_KEY)],
+        AuthScope::Token => vec![scheme(USER_TOKEN)],
+        AuthScope::Admin => vec![scheme(ADMIN_KEY)],
+    }
+}
+
+// `FailureBody` of the throttling and server failures, `ErrorBody` of the client errors
+fn error_response(description: &str, schema: &str) -> utoipa::openapi::Response {
+    ResponseBuilder::new()
+        .description(description)
+        .content(
+            "application/json",
+            ContentBuilder::new()
+                .schema(Ref::from_schema_name(schema))
+                .build(),
+        )
+        .build()
+}
+
+fn get_operation(spec: &RouteSpec, path_params: &[String]) -> utoipa::openapi::path::Operation {
+    let success = match spec.cache {
+        CachePolicy::NoStore => "Success, not cached".to_string(),
+        CachePolicy::MaxAge(secs) => format!("Success, cached for {}s", secs),
+    };
+    let mut operation = OperationBuilder::new()
+        .operation_id(Some(
+            spec.handler
+                .trim_start_matches("endpoints::")
+                .replace("::", "_"),
+        ))
+        .tag(get_tag(spec))
+        .response("200", ResponseBuilder::new().description(success).build())
+        .response("4XX", error_response("Client error", "ErrorBody"))
+        .response("429", error_response("Rate limit exceeded", "FailureBody"))
+        .response("5XX", error_response("Server failure", "FailureBody"));
+    for name in path_params {
+        operation = operation.parameter(
+            ParameterBuilder::new()
+                .name(name)
+                .parameter_in(ParameterIn::Path)
+                .required(Required::True)
+                .schema(Some(string_schema()))
+                .build(),
+        );
+    }
+    match get_input(spec.handler) {
+        Some(Input::Query(params)) => {
+            for param in params {
+                operation = operation.parameter(param);
+            }
+        }
+        Some(Input::Body(schema)) => {
+            operation = operation.request_body(Some(
+                RequestBodyBuilder::new()
+                    .content(
+                        "application/json",
+                        ContentBuilder::new().schema(schema).build(),
+                    )
+                    .required(Some(Required::True))
+                    .build(),
+            ))
+        }
+        None => {}
+    }
+    let security = get_security(spec.auth);
+    if !security.is_empty() {
+        operation = operation
+            .response("401", error_response("Unauthorized", "ErrorBody"))
+            .securities(Some(security));
+    }
+    operation.build()
+}
+
+/// Document of the routes, grouped by path
+pub fn get_openapi<'a>(routes: impl IntoIterator<Item = &'a RouteSpec>) -> OpenApi {
+    let mut items: BTreeMap<String, PathItemBuilder> = BTreeMap::new();
+    for spec in routes {
+        let (path, params) = to_openapi_path(spec.path);
+        let method = match spec.method {
+            Method::Get => PathItemType::Get,
+            Method::Post => PathItemType::Post,
+        };
+        let item = items.remove(&path).unwrap_or_default();
+        items.insert(path, item.operation(method, get_operation(spec, &params)));
+    }
+    let mut paths = PathsBuilder::new();
+    for (path, item) in items {
+        paths = paths.path(path, item.build());
+    }
+
+    let components = ComponentsBuilder::new()
+        .schema_from::<FailureBody>()
+        .schema_from::<ErrorBody>()
+        .schema_from::<ErrorDetails>()
+        .schema_from::<ErrorCode>()
+        .schema_from::<Component>()
+        .security_scheme(
+            API_KEY,
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("x-api-key"))),
+        )
+        .security_scheme(
+            ADMIN_KEY,
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("x-admin-key"))),
+        )
+        .security_scheme(
+            USER_TOKEN,
+            SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)),
+        )
+        .build();
+    OpenApiBuilder::new()
+        .info(
+            InfoBuilder::new()
+                .title("starknetid_server")
+                .version(env!("CARGO_PKG_VERSION"))
+                .build(),
+        )
+        .paths(paths.build())
+        .components(Some(components))
+        .build()
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use mongodb::bson::doc;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::{crypto::pedersen_hash, types::FieldElement, utils::starknet_keccak},
+    macros::short_string,
+};
+
+use crate::storage::Storage;
+
+pub const MEMBERS_COLLECTION: &str = "org_members";
+
+/// Member of an organization, declared off-chain by the owner of the org domain
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct OrgMember {
+    pub org: String,
+    pub member: String,
+    pub role: String,
+    pub title: Option<String>,
+    pub added_at: i64,
+}
+
+/// Message signed by the org owner to set (or with an empty role, remove) a member, an empty
+/// title stands for none
+pub fn get_member_hash(
+    org: &str,
+    member: &str,
+    role: &str,
+    title: &str,
+    timestamp: i64,
+) -> FieldElement {
+    let hash = pedersen_hash(
+        &short_string!("org member"),
+        &starknet_keccak(org.as_bytes()),
+    );
+    let hash = pedersen_hash(&hash, &starknet_keccak(member.as_bytes()));
+    let hash = pedersen_hash(&hash, &starknet_keccak(role.as_bytes()));
+    let hash = pedersen_hash(&hash, &starknet_keccak(title.as_bytes()));
+    pedersen_hash(&hash, &FieldElement::from(timestamp as u64))
+}
+
+/// Current owner of the identity a domain points to
+pub async fn get_domain_owner(storage: &dyn Storage, domain: &str) -> Result<Option<FieldElement>> {
+    let domain_doc = storage
+        .find_one("domains", doc! { "domain": domain, "_cursor.to": null })
+        .await?;
+    let id = match domain_doc.as_ref().and_then(|doc| doc.get_str("id").ok()) {
+        Some(id) => id,
+        None => return Ok(None),
+    };
+    let owner_doc = storage
+        .find_one("id_owners", doc! { "id": id, "_cursor.to": null })
+        .await?;
+    Ok(owner_doc
+        .as_ref()
+        .and_then(|doc| doc.get_str("owner").ok())
+        .and_then(|owner| FieldElement::from_hex_be(owner).ok()))
+}
@@ -0,0 +1,82 @@
+use mongodb::bson::{doc, oid::ObjectId, Bson, Document};
+
+/// Ascending sort keys of a paginated list, the last one is unique so the documents sharing
+/// the other keys keep the same order between two pages
+pub type Ordering = &'static [&'static str];
+
+pub const BLOCK_ORDER: Ordering = &["_cursor.from", "_id"];
+pub const EXPIRY_ORDER: Ordering = &["expiry", "_id"];
+// a domain has a single live version
+pub const DOMAIN_ORDER: Ordering = &["domain"];
+// an address enters a raffle once
+pub const ENTRANT_ORDER: Ordering = &["addr"];
+
+pub fn get_sort(ordering: Ordering) -> Document {
+    ordering
+        .iter()
+        .map(|key| (key.to_string(), Bson::Int32(1)))
+        .collect()
+}
+
+/// Opaque position in a list sorted by an integer field then by `_id`, the `_id`
+/// tie-breaker keeps the order stable when many documents share the same key
+#[derive(Debug, Clone, PartialEq)]
+pub struct PageCursor {
+    pub key: i64,
+    pub id: ObjectId,
+}
+
+impl PageCursor {
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.key, self.id.to_hex())
+    }
+
+    pub fn decode(cursor: &str) -> Option<Self> {
+        let (key, id) = cursor.split_once('_')?;
+        Some(PageCursor {
+            key: key.parse().ok()?,
+            id: ObjectId::parse_str(id).ok()?,
+        })
+    }
+
+    pub fn from_document(doc: &Document, field: &str) -> Option<Self> {
+        let key = match field.split_once('.') {
+            Some((parent, child)) => doc.get_document(parent).ok()?.get_i64(child).ok()?,
+            None => doc.get_i64(field).ok()?,
+        };
+        Some(PageCursor {
+            key,
+            id: doc.get_object_id("_id").ok()?,
+        })
+    }
+
+    /// This cursor, or the start of the block `window` blocks before `latest_key` when it is
+    /// older, with whether it was moved
+    pub fn clamp(self, latest_key: Option<i64>, window: i64) -> (Self, bool) {
+        match latest_key {
+            Some(latest) if self.key < latest - window => (
+                PageCursor {
+                    key: latest - window,
+                    id: ObjectId::from_bytes([0; 12]),
+                },
+                true,
+            ),
+            _ => (self, false),
+        }
+    }
+
+    /// Matches the documents located after this cursor when sorting by `field` then `_id`
+    pub fn after(&self, field: &str) -> Document {
+        doc! {
+            "$or": [
+                { field: { "$gt": self.key } },
+                { field: self.key, "_id": { "$gt": self.id } },
+            ]
+        }
+    }
+}
+
+/// Clamps a user supplied page size
+pub fn get_limit(limit: Option<i64>, max_limit: i64) -> i64 {
+    limit.unwrap_or(max_limit).clamp(1, max_limit)
+}
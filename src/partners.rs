@@ -0,0 +1,107 @@
+use anyhow::{anyhow, Result};
+use futures::StreamExt;
+use mongodb::bson::{doc, Bson, DateTime as BsonDateTime, Document};
+use serde::{Deserialize, Serialize};
+
+use crate::{config::Partner, models::AppState};
+use utoipa::ToSchema;
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Period {
+    Day,
+    Week,
+    Month,
+}
+
+impl Period {
+    /// `$dateToString` format of the period a sale falls in, e.g. 2024-03 or 2024-W12
+    pub fn date_format(&self) -> &'static str {
+        match self {
+            Period::Day => "%Y-%m-%d",
+            Period::Week => "%G-W%V",
+            Period::Month => "%Y-%m",
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PeriodRevenue {
+    pub period: String,
+    pub registrations: i64,
+    pub revenue: i64,
+    pub owed: i64,
+}
+
+/// Part of the revenue owed to the partner, rounded down
+pub fn get_owed(revenue: i64, share: f64) -> i64 {
+    (revenue as f64 * share).floor() as i64
+}
+
+fn get_number(doc: &Document, key: &str) -> i64 {
+    match doc.get(key) {
+        Some(Bson::Int32(n)) => *n as i64,
+        Some(Bson::Int64(n)) => *n,
+        Some(Bson::Double(n)) => *n as i64,
+        _ => 0,
+    }
+}
+
+/// Referred sales of the partner grouped by period, oldest first
+pub async fn get_revenue(
+    state: &AppState,
+    partner: &Partner,
+    from: i64,
+    to: i64,
+    period: Period,
+) -> Result<Vec<PeriodRevenue>> {
+    let pipeline = vec![
+        doc! { "$match": {
+            "sponsor_addr": { "$in": partner.sponsors.clone() },
+            "amount": { "$gt": 0 },
+            "timestamp": {
+                "$gte": BsonDateTime::from_millis(from * 1000),
+                "$lt": BsonDateTime::from_millis(to * 1000),
+            },
+            "_cursor.to": Bson::Null,
+        }},
+        doc! { "$group": {
+            "_id": { "$dateToString": { "format": period.date_format(), "date": "$timestamp" } },
+            "registrations": { "$sum": 1 },
+            "revenue": { "$sum": "$amount" },
+        }},
+        doc! { "$sort": { "_id": 1 } },
+    ];
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("referral_revenues")
+        .aggregate(pipeline, None)
+        .await?;
+    let mut revenues = vec![];
+    while let Some(doc) = cursor.next().await {
+        let doc = doc?;
+        let revenue = get_number(&doc, "revenue");
+        revenues.push(PeriodRevenue {
+            period: doc
+                .get_str("_id")
+                .map_err(|e| anyhow!("invalid period: {}", e))?
+                .to_string(),
+            registrations: get_number(&doc, "registrations"),
+            revenue,
+            owed: get_owed(revenue, partner.share),
+        });
+    }
+    Ok(revenues)
+}
+
+/// Same rows as the json report, for the spreadsheets of the reconciliation
+pub fn to_csv(revenues: &[PeriodRevenue]) -> String {
+    let mut csv = String::from("period,registrations,revenue,owed\n");
+    for row in revenues {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            row.period, row.registrations, row.revenue, row.owed
+        ));
+    }
+    csv
+}
@@ -0,0 +1,107 @@
+use anyhow::{anyhow, Result};
+use mongodb::bson::doc;
+use serde::Serialize;
+use starknet::{core::types::FieldElement, macros::selector};
+
+use crate::{
+    config::{Contracts, Portfolio},
+    simulation::Call,
+    storage::Storage,
+    utils::to_hex,
+};
+
+/// Identity moved by a portfolio transfer, with the domain it holds
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct TransferItem {
+    #[serde(serialize_with = "crate::simulation::serialize_felt")]
+    pub id: FieldElement,
+    pub domain: Option<String>,
+}
+
+/// Refuses targets which would lose the identities: the sender itself, the naming contracts,
+/// the denylisted addresses and classes, and the addresses with no account deployed
+pub fn check_target(
+    conf: &Portfolio,
+    contracts: &Contracts,
+    from: &FieldElement,
+    to: &FieldElement,
+    class_hash: Option<&FieldElement>,
+) -> Result<(), String> {
+    if to == from {
+        return Err("The target is the current owner".to_string());
+    }
+    if *to == FieldElement::ZERO
+        || *to == contracts.starknetid
+        || *to == contracts.naming
+        || conf.denied_addresses.contains(to)
+    {
+        return Err("The target can't hold identities".to_string());
+    }
+    match class_hash {
+        None => Err("No account is deployed at the target".to_string()),
+        Some(class_hash) if conf.denied_class_hashes.contains(class_hash) => {
+            Err("The target contract is denylisted".to_string())
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+/// Identities of the selection, each domain stands for the identity holding it, all of
+/// them must be owned by `owner`
+pub async fn get_transfer_items(
+    storage: &dyn Storage,
+    owner: &FieldElement,
+    ids: &[FieldElement],
+    domains: &[String],
+) -> Result<Vec<TransferItem>> {
+    let mut selected = ids.to_vec();
+    for domain in domains {
+        let id = storage
+            .find_one("domains", doc! { "domain": domain, "_cursor.to": null })
+            .await?
+            .and_then(|doc| FieldElement::from_hex_be(doc.get_str("id").ok()?).ok())
+            .ok_or_else(|| anyhow!("Domain not found: {}", domain))?;
+        selected.push(id);
+    }
+    let mut items: Vec<TransferItem> = vec![];
+    for id in selected {
+        if items.iter().any(|item| item.id == id) {
+            continue;
+        }
+        let owned = storage
+            .find_one(
+                "id_owners",
+                doc! { "id": to_hex(&id), "owner": to_hex(owner), "_cursor.to": null },
+            )
+            .await?
+            .is_some();
+        if !owned {
+            return Err(anyhow!("Identity {} isn't owned by the sender", id));
+        }
+        let domain = storage
+            .find_one("domains", doc! { "id": to_hex(&id), "_cursor.to": null })
+            .await?
+            .and_then(|doc| doc.get_str("domain").ok().map(String::from));
+        items.push(TransferItem { id, domain });
+    }
+    Ok(items)
+}
+
+/// Transfers of the identity tokens, their token id is an u256 of which identities fill
+/// the low part
+pub fn get_transfer_calls(
+    starknetid: FieldElement,
+    from: FieldElement,
+    to: FieldElement,
+    items: &[TransferItem],
+) -> Vec<Call> {
+    items
+        .iter()
+        .map(|item| Call {
+            to: starknetid,
+            entrypoint: "transfer_from",
+            selector: selector!("transfer_from"),
+            calldata: vec![from, to, item.id, FieldElement::ZERO],
+        })
+        .collect()
+}
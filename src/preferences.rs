@@ -0,0 +1,50 @@
+use anyhow::Result;
+use mongodb::bson::{doc, from_document};
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::{crypto::pedersen_hash, types::FieldElement},
+    macros::short_string,
+};
+
+use crate::{storage::Storage, utils::to_hex};
+use utoipa::ToSchema;
+
+pub const PREFERENCES_COLLECTION: &str = "preferences";
+
+/// Privacy settings of an address, everything is opt in
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, ToSchema)]
+pub struct Preferences {
+    // the address book sync can match this address to its domain
+    #[serde(default)]
+    pub addressbook_discoverable: bool,
+    // notifies the stale social verifications of the owned identities
+    #[serde(default)]
+    pub reverification_reminders: bool,
+}
+
+/// Message signed by the address to update its preferences
+pub fn get_preferences_hash(
+    address: &FieldElement,
+    preferences: &Preferences,
+    timestamp: i64,
+) -> FieldElement {
+    let hash = pedersen_hash(&short_string!("set preferences"), address);
+    let hash = pedersen_hash(
+        &hash,
+        &FieldElement::from(preferences.addressbook_discoverable as u8),
+    );
+    let hash = pedersen_hash(
+        &hash,
+        &FieldElement::from(preferences.reverification_reminders as u8),
+    );
+    pedersen_hash(&hash, &FieldElement::from(timestamp as u64))
+}
+
+pub async fn get_preferences(storage: &dyn Storage, address: &FieldElement) -> Result<Preferences> {
+    Ok(storage
+        .find_one(PREFERENCES_COLLECTION, doc! { "address": to_hex(address) })
+        .await?
+        .map(from_document::<Preferences>)
+        .transpose()?
+        .unwrap_or_default())
+}
@@ -0,0 +1,30 @@
+// wei per day by label length, from the pricing contract, 5 characters and more share the
+// last price
+const PRICES_PER_DAY: [u128; 5] = [
+    1068493150684932,
+    657534246575343,
+    200000000000000,
+    73972602739726,
+    24657534246575,
+];
+const DAYS_PER_YEAR: u64 = 365;
+
+/// Length the pricing contract charges for: the characters of the label, without the root
+pub fn get_label_length(label: &str) -> usize {
+    label.chars().count()
+}
+
+/// Price in wei of a day of registration, None for an empty label
+pub fn get_price_per_day(length: usize) -> Option<u128> {
+    let index = length.checked_sub(1)?.min(PRICES_PER_DAY.len() - 1);
+    Some(PRICES_PER_DAY[index])
+}
+
+/// Same result as `compute_buy_price` of the pricing contract, in wei
+pub fn compute_buy_price(length: usize, days: u64) -> Option<u128> {
+    Some(get_price_per_day(length)? * days as u128)
+}
+
+pub fn get_price_per_year(length: usize) -> Option<u128> {
+    compute_buy_price(length, DAYS_PER_YEAR)
+}
@@ -0,0 +1,88 @@
+use std::{collections::HashMap, fs};
+
+use crate::logger::Logger;
+
+// terms shorter than this only match whole labels, "ass" shouldn't block "assets"
+const MIN_SUBSTRING_LEN: usize = 4;
+
+/// Screens names issued through the api, on-chain registrations are never filtered
+#[derive(Debug, Clone, Default)]
+pub struct ProfanityFilter {
+    // locale -> terms
+    pub terms: HashMap<String, Vec<String>>,
+}
+
+/// Undoes common obfuscations so "sh1t" and "s-h-i-t" match "shit"
+pub fn normalize_label(label: &str) -> String {
+    label
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            '0' => Some('o'),
+            '1' | '!' => Some('i'),
+            '3' => Some('e'),
+            '4' | '@' => Some('a'),
+            '5' | '$' => Some('s'),
+            '7' => Some('t'),
+            '-' | '_' | '.' => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+impl ProfanityFilter {
+    /// Returns the locale and term matched by one of the domain labels
+    pub fn find_match(&self, domain: &str) -> Option<(&str, &str)> {
+        let domain = domain.strip_suffix(".stark").unwrap_or(domain);
+        for label in domain.split('.').map(normalize_label) {
+            for (locale, terms) in &self.terms {
+                let found = terms.iter().find(|term| {
+                    if term.chars().count() >= MIN_SUBSTRING_LEN {
+                        label.contains(term.as_str())
+                    } else {
+                        label == **term
+                    }
+                });
+                if let Some(term) = found {
+                    return Some((locale, term));
+                }
+            }
+        }
+        None
+    }
+
+    /// Same as `find_match` for each word of a free text such as a bio
+    pub fn find_text_match(&self, text: &str) -> Option<(&str, &str)> {
+        text.split(|c: char| {
+            c.is_whitespace() || (c.is_ascii_punctuation() && !"!@$-_".contains(c))
+        })
+        .filter(|word| !word.is_empty())
+        .find_map(|word| self.find_match(word))
+    }
+
+    pub fn is_allowed(&self, domain: &str) -> bool {
+        self.find_match(domain).is_none()
+    }
+}
+
+pub fn load_profanity_filter(locales: &[String], logger: &Logger) -> ProfanityFilter {
+    let mut terms = HashMap::new();
+    for locale in locales {
+        match fs::read_to_string(format!("./src/profanity/words/{}.txt", locale)) {
+            Ok(data) => {
+                let words = data
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(normalize_label)
+                    .collect();
+                terms.insert(locale.clone(), words);
+            }
+            Err(e) => logger.warning(format!(
+                "Unable to load profanity word list for {}: {}",
+                locale, e
+            )),
+        }
+    }
+    ProfanityFilter { terms }
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, Result};
+
+use crate::config::{AltcoinData, Altcoins};
+
+// error code returned instead of a quote the oracles disagree on
+pub const QUOTE_UNAVAILABLE: &str = "QUOTE_UNAVAILABLE";
+
+/// Relative difference between the primary quote and the reference one
+pub fn get_deviation(quote: f64, reference: f64) -> f64 {
+    ((quote - reference) / reference).abs()
+}
+
+/// Tokens per ETH according to the secondary api
+pub async fn get_secondary_quote(conf: &Altcoins, altcoin: &AltcoinData) -> Result<f64> {
+    let id = altcoin
+        .secondary_id
+        .as_ref()
+        .ok_or_else(|| anyhow!("no secondary price source for this token"))?;
+    let url = format!(
+        "{}/simple/price?ids={}&vs_currencies=eth",
+        conf.secondary_api, id
+    );
+    let prices = reqwest::get(&url)
+        .await?
+        .json::<HashMap<String, HashMap<String, f64>>>()
+        .await?;
+    let price = prices
+        .get(id)
+        .and_then(|currencies| currencies.get("eth"))
+        .copied()
+        .filter(|price| *price > 0.0)
+        .ok_or_else(|| anyhow!("secondary api returned no price for {}", id))?;
+    Ok(1.0 / price)
+}
+
+/// Checks the avnu quote against the secondary source, a missing reference fails the check
+pub async fn check_quote(conf: &Altcoins, altcoin: &AltcoinData, quote: f64) -> Result<()> {
+    if conf.secondary_api.is_empty() {
+        return Ok(());
+    }
+    let reference = get_secondary_quote(conf, altcoin).await?;
+    let deviation = get_deviation(quote, reference);
+    if deviation > conf.max_deviation {
+        return Err(anyhow!(
+            "quote {} deviates by {:.1}% from the reference {}",
+            quote,
+            deviation * 100.0,
+            reference
+        ));
+    }
+    Ok(())
+}
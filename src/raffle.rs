@@ -0,0 +1,263 @@
+use anyhow::{anyhow, Result};
+use ethers::utils::keccak256;
+use futures::StreamExt;
+use mongodb::bson::{doc, from_document, Document};
+use serde::{Deserialize, Serialize};
+use starknet::core::{types::FieldElement, utils::cairo_short_string_to_felt};
+
+use crate::{models::AppState, utils::to_hex};
+use utoipa::ToSchema;
+
+// how often the expired raffles are closed, and how long after the deadline, for the entries
+// sent right before it to be stored
+pub const CLOSE_INTERVAL_SECS: u64 = 30;
+pub const CLOSE_DELAY_SECS: i64 = 60;
+
+/// Condition an address must fulfill on indexed data to enter a raffle
+#[derive(Serialize, Deserialize, Debug, Clone, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EligibilityRule {
+    // owns at least this many identities holding a domain
+    MinDomains { count: i64 },
+    // owns a domain created before this timestamp
+    RegisteredBefore { timestamp: i64 },
+    // one of its identities has this field verified, eg: "twitter"
+    Verified { field: String },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RaffleClosing {
+    // keccak256 of the sorted entrants, published before the block seeding the draw exists
+    pub entrants_hash: String,
+    pub closed_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct RaffleDraw {
+    // first block after the closing, its hash is mixed with the seed
+    pub block_number: i64,
+    pub block_hash: String,
+    // keccak256 of the sorted entrants, joined with newlines, listed by /raffles/get_entrants
+    pub entrants_hash: String,
+    pub winners: Vec<String>,
+    pub drawn_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Raffle {
+    pub id: String,
+    pub name: String,
+    pub winners_count: i64,
+    pub entry_deadline: i64,
+    pub rules: Vec<EligibilityRule>,
+    // keccak256 of the seed, published when the raffle is created
+    pub commitment: String,
+    // kept secret until the draw
+    pub seed: String,
+    pub closing: Option<RaffleClosing>,
+    pub draw: Option<RaffleDraw>,
+}
+
+/// Public view of a raffle, the seed is only revealed once winners are drawn so
+/// that anyone can recompute the draw from the audit trail
+#[derive(Serialize)]
+pub struct RaffleAudit {
+    pub id: String,
+    pub name: String,
+    pub winners_count: i64,
+    pub entry_deadline: i64,
+    pub rules: Vec<EligibilityRule>,
+    pub commitment: String,
+    pub seed: Option<String>,
+    pub closing: Option<RaffleClosing>,
+    pub draw: Option<RaffleDraw>,
+}
+
+impl From<Raffle> for RaffleAudit {
+    fn from(raffle: Raffle) -> Self {
+        RaffleAudit {
+            seed: raffle.draw.as_ref().map(|_| raffle.seed.clone()),
+            id: raffle.id,
+            name: raffle.name,
+            winners_count: raffle.winners_count,
+            entry_deadline: raffle.entry_deadline,
+            rules: raffle.rules,
+            commitment: raffle.commitment,
+            closing: raffle.closing,
+            draw: raffle.draw,
+        }
+    }
+}
+
+pub fn get_commitment(seed: &[u8]) -> String {
+    format!("0x{}", hex::encode(keccak256(seed)))
+}
+
+pub fn get_entrants_hash(entrants: &[String]) -> String {
+    let mut sorted = entrants.to_vec();
+    sorted.sort();
+    format!("0x{}", hex::encode(keccak256(sorted.join("\n"))))
+}
+
+/// Entrants of a raffle, the entries stored after its deadline are left out
+pub async fn get_entrants(
+    state: &AppState,
+    raffle: &Raffle,
+) -> mongodb::error::Result<Vec<String>> {
+    let mut entrants = Vec::new();
+    let mut cursor = state
+        .free_domains_db
+        .collection::<Document>("raffle_entries")
+        .find(
+            doc! { "raffle_id": &raffle.id, "timestamp": { "$lte": raffle.entry_deadline } },
+            None,
+        )
+        .await?;
+    while let Some(doc) = cursor.next().await {
+        if let Ok(addr) = doc?.get_str("addr") {
+            entrants.push(addr.to_string());
+        }
+    }
+    Ok(entrants)
+}
+
+/// Publishes the hash of the entrants of a raffle past its deadline, only the first closing
+/// of a raffle is stored
+pub async fn close_raffle(state: &AppState, raffle: &Raffle) -> Result<RaffleClosing> {
+    let entrants = get_entrants(state, raffle).await?;
+    let closing = RaffleClosing {
+        entrants_hash: get_entrants_hash(&entrants),
+        closed_at: chrono::Utc::now().timestamp(),
+    };
+    let raffles = state.free_domains_db.collection::<Document>("raffles");
+    let result = raffles
+        .update_one(
+            doc! { "id": &raffle.id, "closing": null },
+            doc! { "$set": { "closing": {
+                "entrants_hash": &closing.entrants_hash,
+                "closed_at": closing.closed_at,
+            } } },
+            None,
+        )
+        .await?;
+    if result.modified_count == 1 {
+        return Ok(closing);
+    }
+    // closed by another instance in the meantime
+    let doc = raffles
+        .find_one(doc! { "id": &raffle.id }, None)
+        .await?
+        .ok_or_else(|| anyhow!("Raffle not found"))?;
+    from_document::<Raffle>(doc)?
+        .closing
+        .ok_or_else(|| anyhow!("Raffle not closed"))
+}
+
+/// Closes the raffles whose deadline passed, for their entrants hash to be public before
+/// the block seeding their draw
+pub async fn close_expired_raffles(state: &AppState) -> Result<usize> {
+    let deadline = chrono::Utc::now().timestamp() - CLOSE_DELAY_SECS;
+    let mut cursor = state
+        .free_domains_db
+        .collection::<Document>("raffles")
+        .find(
+            doc! { "entry_deadline": { "$lt": deadline }, "closing": null, "draw": null },
+            None,
+        )
+        .await?;
+    let mut closed = 0;
+    while let Some(doc) = cursor.next().await {
+        close_raffle(state, &from_document::<Raffle>(doc?)?).await?;
+        closed += 1;
+    }
+    Ok(closed)
+}
+
+/// Draws winners from the committed seed mixed with the hash of the first block after the
+/// entries closed, which the API could not know when committing. Each pick hashes the previous
+/// randomness again, the modulo bias is negligible for any realistic number of entrants.
+pub fn draw_winners(
+    seed: &[u8],
+    block_hash: &FieldElement,
+    entrants: &[String],
+    count: usize,
+) -> Vec<String> {
+    let mut pool = entrants.to_vec();
+    pool.sort();
+    pool.dedup();
+
+    let mut randomness = keccak256([seed, &block_hash.to_bytes_be()].concat());
+    let mut winners = Vec::new();
+    while winners.len() < count && !pool.is_empty() {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&randomness[..8]);
+        let index = (u64::from_be_bytes(bytes) % pool.len() as u64) as usize;
+        winners.push(pool.remove(index));
+        randomness = keccak256(randomness);
+    }
+    winners
+}
+
+pub async fn is_eligible(
+    state: &AppState,
+    addr: &FieldElement,
+    rules: &[EligibilityRule],
+) -> mongodb::error::Result<bool> {
+    let mut ids = Vec::new();
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("id_owners")
+        .find(doc! { "owner": to_hex(addr), "_cursor.to": null }, None)
+        .await?;
+    while let Some(doc) = cursor.next().await {
+        if let Ok(id) = doc?.get_str("id") {
+            ids.push(id.to_string());
+        }
+    }
+
+    let mut creation_dates = Vec::new();
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .find(doc! { "id": { "$in": &ids }, "_cursor.to": null }, None)
+        .await?;
+    while let Some(doc) = cursor.next().await {
+        creation_dates.push(doc?.get_i64("creation_date").unwrap_or(i64::MAX));
+    }
+
+    for rule in rules {
+        let valid = match rule {
+            EligibilityRule::MinDomains { count } => creation_dates.len() as i64 >= *count,
+            EligibilityRule::RegisteredBefore { timestamp } => {
+                creation_dates.iter().any(|date| date < timestamp)
+            }
+            EligibilityRule::Verified { field } => {
+                let field = match cairo_short_string_to_felt(field) {
+                    Ok(field) => field,
+                    Err(_) => return Ok(false),
+                };
+                let verifiers: Vec<String> =
+                    state.conf.contracts.verifiers.iter().map(to_hex).collect();
+                state
+                    .starknetid_db
+                    .collection::<Document>("id_verifier_data")
+                    .count_documents(
+                        doc! {
+                            "id": { "$in": &ids },
+                            "field": to_hex(&field),
+                            "verifier": { "$in": verifiers },
+                            "data": { "$ne": null },
+                            "_cursor.to": null,
+                        },
+                        None,
+                    )
+                    .await?
+                    > 0
+            }
+        };
+        if !valid {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,335 @@
+use std::{
+    collections::HashMap,
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+    sync::Mutex,
+};
+
+use axum::{
+    extract::{ConnectInfo, State},
+    http::{HeaderMap, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use serde::Serialize;
+
+use crate::{
+    auth::get_api_key,
+    config::{BurstTier, RateLimits},
+    errors::{ErrorCode, ErrorDetails},
+    failures::{failure_response, get_state_degraded_components, FailureBody},
+    metrics::labeled,
+    models::AppState,
+    routes::{get_matched_route, RateClass},
+};
+
+// expired windows are dropped past this many tracked callers
+const MAX_TRACKED: usize = 100_000;
+pub const RATE_CLASSES: [RateClass; 3] = [RateClass::Light, RateClass::Heavy, RateClass::Write];
+
+struct Window {
+    start: i64,
+    used: u64,
+}
+
+/// Consumption of a caller in the current window of a rate class
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Quota {
+    pub class: RateClass,
+    pub limit: u64,
+    pub used: u64,
+    pub remaining: u64,
+    // seconds until the window resets
+    pub reset: i64,
+}
+
+// token bucket refilled continuously up to the capacity of the tier
+struct Bucket {
+    credits: f64,
+    updated_at: f64,
+}
+
+/// Burst accounting of an api key since the server started
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct BurstUsage {
+    pub api_key: String,
+    pub tier: Option<String>,
+    // requests over the window limits served with a burst credit
+    pub spent: u64,
+    // requests refused once the credits ran out
+    pub throttled: u64,
+    pub credits: Option<f64>,
+}
+
+/// Request counter of the caller, the api key ones may have a burst tier
+pub struct Caller {
+    pub name: String,
+    // name of the api key, which picks its quotas
+    pub api_key: Option<String>,
+    pub tier: Option<String>,
+}
+
+/// Fixed windows of requests per caller and rate class
+#[derive(Default)]
+pub struct RateLimiter {
+    windows: Mutex<HashMap<(String, RateClass), Window>>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+    // by caller, the api key name is only displayed
+    burst_usage: Mutex<HashMap<String, BurstUsage>>,
+}
+
+impl RateLimiter {
+    fn quota(
+        &self,
+        caller: &str,
+        class: RateClass,
+        limit: u64,
+        window_secs: i64,
+        now: i64,
+        hit: bool,
+    ) -> Quota {
+        let start = now - now.rem_euclid(window_secs);
+        let mut windows = self.windows.lock().unwrap();
+        if hit && windows.len() >= MAX_TRACKED {
+            windows.retain(|_, window| window.start == start);
+        }
+        let key = (caller.to_string(), class);
+        let used = match windows.get_mut(&key) {
+            Some(window) if window.start == start => {
+                if hit {
+                    window.used += 1;
+                }
+                window.used
+            }
+            _ if hit => {
+                windows.insert(key, Window { start, used: 1 });
+                1
+            }
+            _ => 0,
+        };
+        Quota {
+            class,
+            limit,
+            used,
+            remaining: limit.saturating_sub(used),
+            reset: start + window_secs - now,
+        }
+    }
+
+    /// Counts a request of the caller
+    pub fn hit(
+        &self,
+        caller: &str,
+        class: RateClass,
+        limit: u64,
+        window_secs: i64,
+        now: i64,
+    ) -> Quota {
+        self.quota(caller, class, limit, window_secs, now, true)
+    }
+
+    pub fn get(
+        &self,
+        caller: &str,
+        class: RateClass,
+        limit: u64,
+        window_secs: i64,
+        now: i64,
+    ) -> Quota {
+        self.quota(caller, class, limit, window_secs, now, false)
+    }
+
+    /// Credits left in the bucket of the caller, a new bucket starts full
+    pub fn get_credits(&self, caller: &str, tier: &BurstTier, now: f64) -> f64 {
+        match self.buckets.lock().unwrap().get(caller) {
+            Some(bucket) => refill(bucket, tier, now),
+            None => tier.capacity,
+        }
+    }
+
+    /// Spends a burst credit of the caller, false when the bucket is empty
+    pub fn take_credit(&self, caller: &str, tier: &BurstTier, now: f64) -> bool {
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(caller.to_string()).or_insert(Bucket {
+            credits: tier.capacity,
+            updated_at: now,
+        });
+        bucket.credits = refill(bucket, tier, now);
+        bucket.updated_at = now;
+        if bucket.credits < 1.0 {
+            return false;
+        }
+        bucket.credits -= 1.0;
+        true
+    }
+
+    fn record_burst(&self, caller: &Caller, spent: bool) {
+        let api_key = match &caller.api_key {
+            Some(api_key) => api_key,
+            None => return,
+        };
+        let mut usage = self.burst_usage.lock().unwrap();
+        let entry = usage
+            .entry(caller.name.clone())
+            .or_insert_with(|| BurstUsage {
+                api_key: api_key.clone(),
+                ..Default::default()
+            });
+        entry.tier = caller.tier.clone();
+        if spent {
+            entry.spent += 1;
+        } else {
+            entry.throttled += 1;
+        }
+    }
+
+    /// Burst accounting of the api keys, with the credits they have left
+    pub fn get_burst_usage(&self, limits: &RateLimits, now: f64) -> Vec<BurstUsage> {
+        let mut usage: Vec<BurstUsage> = Vec::new();
+        for (caller, entry) in self.burst_usage.lock().unwrap().iter() {
+            let mut entry = entry.clone();
+            entry.credits = entry
+                .tier
+                .as_ref()
+                .and_then(|tier| limits.burst_tiers.get(tier))
+                .map(|tier| self.get_credits(caller, tier, now));
+            usage.push(entry);
+        }
+        usage.sort_by(|a, b| b.spent.cmp(&a.spent).then(a.api_key.cmp(&b.api_key)));
+        usage
+    }
+}
+
+fn refill(bucket: &Bucket, tier: &BurstTier, now: f64) -> f64 {
+    let elapsed = (now - bucket.updated_at).max(0.0);
+    (bucket.credits + elapsed * tier.refill_per_sec).min(tier.capacity)
+}
+
+impl RateLimits {
+    pub fn get_burst_tier(&self, caller: &Caller) -> Option<&BurstTier> {
+        self.burst_tiers.get(caller.tier.as_ref()?)
+    }
+
+    pub fn get_limit(&self, class: RateClass, api_key: Option<&str>) -> u64 {
+        let pick = |light: u64, heavy: u64, write: u64| match class {
+            RateClass::Light => light,
+            RateClass::Heavy => heavy,
+            RateClass::Write => write,
+        };
+        match api_key {
+            Some(name) => match self.api_key_quotas.get(name) {
+                Some(quota) => pick(quota.light, quota.heavy, quota.write),
+                None => pick(self.light, self.heavy, self.write) * self.api_key_multiplier,
+            },
+            None => pick(self.light, self.heavy, self.write),
+        }
+    }
+}
+
+/// Caller the requests are counted against: the id of its api key, or else its ip
+pub async fn get_caller(state: &AppState, headers: &HeaderMap, peer: Option<SocketAddr>) -> Caller {
+    if headers.contains_key("x-api-key") {
+        if let Some(api_key) = get_api_key(state, headers).await {
+            return Caller {
+                name: format!("key:{}", api_key.id.to_hex()),
+                api_key: Some(api_key.name),
+                tier: api_key.tier,
+            };
+        }
+    }
+    let forwarded = state
+        .conf
+        .rate_limits
+        .trust_forwarded_for
+        .then(|| headers.get("x-forwarded-for")?.to_str().ok())
+        .flatten()
+        .and_then(get_forwarded_ip)
+        .map(|ip| ip.to_string());
+    let ip = forwarded
+        .or_else(|| peer.map(|peer| peer.ip().to_string()))
+        .unwrap_or_else(|| "unknown".to_string());
+    Caller {
+        name: format!("ip:{}", ip),
+        api_key: None,
+        tier: None,
+    }
+}
+
+/// Client address appended by the reverse proxy: the rightmost entry, the ones before it are
+/// sent by the client and can be anything
+pub fn get_forwarded_ip(value: &str) -> Option<IpAddr> {
+    value.rsplit(',').next()?.trim().parse().ok()
+}
+
+pub fn set_headers(headers: &mut HeaderMap, quota: &Quota) {
+    for (name, value) in [
+        ("ratelimit-limit", quota.limit as i64),
+        ("ratelimit-remaining", quota.remaining as i64),
+        ("ratelimit-reset", quota.reset),
+    ] {
+        headers.insert(name, HeaderValue::from(value));
+    }
+}
+
+/// Counts the request against the limit of its route class and sends the RateLimit headers
+pub async fn limit_rate<B>(
+    State(state): State<Arc<AppState>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let limits = &state.conf.rate_limits;
+    if !limits.enabled {
+        return next.run(req).await;
+    }
+    let spec = match get_matched_route(&req) {
+        Ok(Some(spec)) => spec,
+        Ok(None) => return next.run(req).await,
+        Err(e) => return e.into_response(),
+    };
+    let peer = req
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|info| info.0);
+    let caller = get_caller(&state, req.headers(), peer).await;
+    let now = chrono::Utc::now();
+    let quota = state.rate_limiter.hit(
+        &caller.name,
+        spec.rate,
+        limits.get_limit(spec.rate, caller.api_key.as_deref()),
+        limits.window_secs,
+        now.timestamp(),
+    );
+    let mut limited = quota.used > quota.limit;
+    if limited {
+        if let Some(tier) = limits.get_burst_tier(&caller) {
+            let now = now.timestamp_millis() as f64 / 1000.0;
+            limited = !state.rate_limiter.take_credit(&caller.name, tier, now);
+            state.rate_limiter.record_burst(&caller, !limited);
+            if let Some(api_key) = caller.api_key.as_ref().filter(|_| !limited) {
+                state.metrics.add(
+                    &labeled("rate_limit_burst_credits_total", "api_key", api_key),
+                    1,
+                );
+            }
+        }
+    }
+
+    let mut response = if limited {
+        failure_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            FailureBody {
+                error: ErrorDetails {
+                    code: ErrorCode::RateLimited,
+                    message: "Rate limit exceeded".to_string(),
+                },
+                retry_after_ms: Some(quota.reset.max(0) as u64 * 1000),
+                degraded_components: get_state_degraded_components(&state),
+                incident_id: None,
+            },
+        )
+    } else {
+        next.run(req).await
+    };
+    set_headers(response.headers_mut(), &quota);
+    response
+}
@@ -0,0 +1,84 @@
+use anyhow::{anyhow, Result};
+use sha2::{Digest, Sha256};
+
+const BECH32_CHARSET: &str = "qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_CONST: u32 = 1;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+
+/// Mainnet address: a base58check P2PKH or P2SH, or a bech32 segwit whose canonical
+/// spelling is lowercase
+pub fn normalize(address: &str) -> Result<String> {
+    let lowercase = address.to_lowercase();
+    if lowercase.starts_with("bc1") {
+        check_segwit(address, &lowercase)?;
+        Ok(lowercase)
+    } else {
+        check_base58(address)?;
+        Ok(address.to_string())
+    }
+}
+
+fn check_base58(address: &str) -> Result<()> {
+    let bytes = bs58::decode(address).into_vec()?;
+    if bytes.len() != 25 || !matches!(bytes[0], 0x00 | 0x05) {
+        return Err(anyhow!("Not a mainnet bitcoin address"));
+    }
+    let (payload, checksum) = bytes.split_at(21);
+    let hash = Sha256::digest(Sha256::digest(payload));
+    if hash[..4] != *checksum {
+        return Err(anyhow!("Invalid bitcoin address checksum"));
+    }
+    Ok(())
+}
+
+fn polymod(values: &[u8]) -> u32 {
+    const GENERATORS: [u32; 5] = [
+        0x3b6a_57b2,
+        0x2650_8e6d,
+        0x1ea1_19fa,
+        0x3d42_33dd,
+        0x2a14_62b3,
+    ];
+    let mut checksum = 1_u32;
+    for value in values {
+        let top = checksum >> 25;
+        checksum = ((checksum & 0x01ff_ffff) << 5) ^ *value as u32;
+        for (i, generator) in GENERATORS.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                checksum ^= generator;
+            }
+        }
+    }
+    checksum
+}
+
+// BIP-173 checksum, BIP-350 for the witness versions above 0
+fn check_segwit(address: &str, lowercase: &str) -> Result<()> {
+    if address != lowercase && address != address.to_uppercase() {
+        return Err(anyhow!("A bech32 address has a single case"));
+    }
+    let data = lowercase
+        .strip_prefix("bc1")
+        .unwrap_or_default()
+        .chars()
+        .map(|c| BECH32_CHARSET.find(c).map(|value| value as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| anyhow!("Invalid bech32 character"))?;
+    // witness version, at least 2 bytes of program and the 6 checksum characters
+    if data.len() < 1 + 4 + 6 || lowercase.len() > 90 {
+        return Err(anyhow!("Invalid bech32 length"));
+    }
+    let mut values: Vec<u8> = "bc".bytes().map(|byte| byte >> 5).collect();
+    values.push(0);
+    values.extend("bc".bytes().map(|byte| byte & 31));
+    values.extend(&data);
+    let expected = match data[0] {
+        0 => BECH32_CONST,
+        1..=16 => BECH32M_CONST,
+        _ => return Err(anyhow!("Invalid witness version")),
+    };
+    if polymod(&values) != expected {
+        return Err(anyhow!("Invalid bitcoin address checksum"));
+    }
+    Ok(())
+}
@@ -0,0 +1,29 @@
+use anyhow::{anyhow, Result};
+use ethers::{types::H160, utils::to_checksum};
+use starknet::core::types::FieldElement;
+
+/// EIP-55 checksummed address, a mixed case address must already be checksummed
+pub fn normalize(address: &str) -> Result<String> {
+    let hex = address
+        .strip_prefix("0x")
+        .ok_or_else(|| anyhow!("An evm address starts with 0x"))?;
+    if hex.len() != 40 {
+        return Err(anyhow!("An evm address has 40 hex characters"));
+    }
+    let bytes = hex::decode(hex)?;
+    let checksummed = to_checksum(&H160::from_slice(&bytes), None);
+    let mixed_case =
+        hex.chars().any(|c| c.is_ascii_lowercase()) && hex.chars().any(|c| c.is_ascii_uppercase());
+    if mixed_case && checksummed != address {
+        return Err(anyhow!("Invalid evm address checksum"));
+    }
+    Ok(checksummed)
+}
+
+pub fn from_felt(felt: &FieldElement) -> Result<String> {
+    let bytes = felt.to_bytes_be();
+    if bytes[..12].iter().any(|byte| *byte != 0) {
+        return Err(anyhow!("An evm address fits in 20 bytes"));
+    }
+    Ok(to_checksum(&H160::from_slice(&bytes[12..]), None))
+}
@@ -0,0 +1,151 @@
+mod btc;
+mod evm;
+mod sol;
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use futures::future::join_all;
+use mongodb::bson::doc;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use starknet::{
+    core::{
+        types::{BlockId, BlockTag, FieldElement, FunctionCall},
+        utils::{cairo_short_string_to_felt, parse_cairo_short_string},
+    },
+    macros::selector,
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+
+use crate::{models::AppState, rpc_queue::Priority, utils::to_hex};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum Chain {
+    Evm,
+    Btc,
+    Sol,
+}
+
+pub const CHAINS: [Chain; 3] = [Chain::Evm, Chain::Btc, Chain::Sol];
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AddressRecord {
+    pub chain: Chain,
+    pub address: String,
+}
+
+impl Chain {
+    /// User data field of the identity holding the address
+    pub fn get_field(&self) -> &'static str {
+        match self {
+            Chain::Evm => "evm-address",
+            Chain::Btc => "btc-address",
+            Chain::Sol => "sol-address",
+        }
+    }
+
+    /// Validated address in its canonical spelling: checksummed for evm, lowercase for the
+    /// bech32 bitcoin addresses
+    pub fn normalize(&self, address: &str) -> Result<String> {
+        match self {
+            Chain::Evm => evm::normalize(address),
+            Chain::Btc => btc::normalize(address),
+            Chain::Sol => sol::normalize(address),
+        }
+    }
+
+    /// Address stored in the user data: a single felt for evm, the short strings of its
+    /// text for the addresses too long to fit in a felt
+    pub fn decode(&self, felts: &[FieldElement]) -> Result<String> {
+        match self {
+            Chain::Evm => match felts {
+                [felt] => evm::from_felt(felt),
+                _ => Err(anyhow!("An evm address is a single felt")),
+            },
+            Chain::Btc | Chain::Sol => {
+                let text = felts
+                    .iter()
+                    .map(parse_cairo_short_string)
+                    .collect::<Result<Vec<_>, _>>()?
+                    .concat();
+                self.normalize(&text)
+            }
+        }
+    }
+}
+
+/// Felts of the unbounded user data of the field, empty when it isn't set
+pub async fn get_unbounded_user_data(
+    state: &AppState,
+    id: FieldElement,
+    field: FieldElement,
+) -> Result<Vec<FieldElement>> {
+    let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(
+        &state.conf.variables.rpc_url,
+    )?));
+    let call = provider.call(
+        FunctionCall {
+            contract_address: state.conf.contracts.starknetid,
+            entry_point_selector: selector!("get_unbounded_user_data"),
+            calldata: vec![id, field, FieldElement::ZERO],
+        },
+        BlockId::Tag(BlockTag::Latest),
+    );
+    let result = state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_call", call)
+        .await?;
+    // the array starts with its length
+    Ok(result.into_iter().skip(1).collect())
+}
+
+/// Address of the chain set by the identity, None when it isn't set
+pub async fn get_record(
+    state: &AppState,
+    id: FieldElement,
+    chain: Chain,
+) -> Result<Option<AddressRecord>> {
+    let field = cairo_short_string_to_felt(chain.get_field())?;
+    let felts = match chain {
+        // fits in the indexed user data
+        Chain::Evm => state
+            .storage
+            .find_one(
+                "id_user_data",
+                doc! { "id": to_hex(&id), "field": to_hex(&field), "_cursor.to": null },
+            )
+            .await?
+            .and_then(|doc| FieldElement::from_hex_be(doc.get_str("data").ok()?).ok())
+            .filter(|data| *data != FieldElement::ZERO)
+            .into_iter()
+            .collect(),
+        Chain::Btc | Chain::Sol => get_unbounded_user_data(state, id, field).await?,
+    };
+    if felts.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(AddressRecord {
+        chain,
+        address: chain.decode(&felts)?,
+    }))
+}
+
+/// Valid address records of the identity, on every chain
+pub async fn get_records(state: &Arc<AppState>, id: FieldElement) -> Vec<AddressRecord> {
+    join_all(CHAINS.iter().map(|chain| get_record(state, id, *chain)))
+        .await
+        .into_iter()
+        .filter_map(|record| match record {
+            Ok(record) => record,
+            Err(e) => {
+                state
+                    .logger
+                    .warning(format!("records: skipping an invalid record: {}", e));
+                None
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,10 @@
+use anyhow::{anyhow, Result};
+
+/// Base58 of a 32 bytes public key, its spelling is already canonical
+pub fn normalize(address: &str) -> Result<String> {
+    let bytes = bs58::decode(address).into_vec()?;
+    if bytes.len() != 32 {
+        return Err(anyhow!("A solana address is a 32 bytes key"));
+    }
+    Ok(address.to_string())
+}
@@ -0,0 +1,85 @@
+use anyhow::{anyhow, Result};
+use lettre::{
+    message::header::ContentType, transport::smtp::authentication::Credentials, AsyncSmtpTransport,
+    AsyncTransport, Message, Tokio1Executor,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+const SENDGRID_URL: &str = "https://api.sendgrid.com/v3/mail/send";
+
+/// Service the reminders are sent through
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum EmailProvider {
+    Sendgrid {
+        api_key: String,
+    },
+    // relay over tls, e.g. smtp.mailgun.org:465
+    Smtp {
+        host: String,
+        port: u16,
+        username: String,
+        password: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Email {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+async fn send_sendgrid(api_key: &str, email: &Email) -> Result<()> {
+    let body = json!({
+        "personalizations": [{ "to": [{ "email": email.to }] }],
+        "from": { "email": email.from },
+        "subject": email.subject,
+        "content": [{ "type": "text/plain", "value": email.body }],
+    });
+    let response = reqwest::Client::new()
+        .post(SENDGRID_URL)
+        .bearer_auth(api_key)
+        .json(&body)
+        .send()
+        .await?;
+    if !response.status().is_success() {
+        return Err(anyhow!("SendGrid answered {}", response.status()));
+    }
+    Ok(())
+}
+
+async fn send_smtp(
+    host: &str,
+    port: u16,
+    username: &str,
+    password: &str,
+    email: &Email,
+) -> Result<()> {
+    let message = Message::builder()
+        .from(email.from.parse()?)
+        .to(email.to.parse()?)
+        .subject(email.subject.as_str())
+        .header(ContentType::TEXT_PLAIN)
+        .body(email.body.clone())?;
+    let transport = AsyncSmtpTransport::<Tokio1Executor>::relay(host)?
+        .port(port)
+        .credentials(Credentials::new(username.to_string(), password.to_string()))
+        .build();
+    transport.send(message).await?;
+    Ok(())
+}
+
+pub async fn send(provider: &EmailProvider, email: &Email) -> Result<()> {
+    match provider {
+        EmailProvider::Sendgrid { api_key } => send_sendgrid(api_key, email).await,
+        EmailProvider::Smtp {
+            host,
+            port,
+            username,
+            password,
+        } => send_smtp(host, *port, username, password, email).await,
+    }
+}
@@ -0,0 +1,158 @@
+//! Emails reminding the owners with a verified email address to renew their domains before
+//! they expire
+
+pub mod email;
+
+use anyhow::Result;
+use mongodb::bson::{doc, Bson, Document};
+use starknet::core::{
+    types::FieldElement,
+    utils::{cairo_short_string_to_felt, parse_cairo_short_string},
+};
+
+use crate::{
+    config::Reminders,
+    expiring::get_expiring_domains,
+    models::AppState,
+    reminders::email::{send, Email},
+    utils::to_hex,
+};
+
+pub const REMINDERS_COLLECTION: &str = "renewal_reminders";
+// field written by the email verifier
+const EMAIL_FIELD: &str = "email";
+const SECONDS_PER_DAY: i64 = 24 * 3600;
+const PAGE_SIZE: i64 = 500;
+
+/// Smallest of the reminder thresholds the expiry is within, a domain expiring in 5 days
+/// gets the 7 days reminder of [30, 7, 1] but not the 30 days one anymore
+pub fn get_threshold(days_before: &[i64], expiry: i64, now: i64) -> Option<i64> {
+    days_before
+        .iter()
+        .copied()
+        .filter(|days| expiry - now <= days * SECONDS_PER_DAY)
+        .min()
+}
+
+// a renewal moves the expiry, the next term gets its own reminders
+pub fn get_reminder_key(domain: &str, expiry: i64, days: i64) -> String {
+    format!("renewal:{}:{}:{}", domain, expiry, days)
+}
+
+fn decode_felt(value: &Bson) -> Option<String> {
+    FieldElement::from_hex_be(value.as_str()?)
+        .ok()
+        .and_then(|felt| parse_cairo_short_string(&felt).ok())
+}
+
+/// Address of an email verification, split in short strings in its extended data when it
+/// doesn't fit in a single felt
+pub fn decode_email(verification: &Document) -> Option<String> {
+    let email = match verification.get_array("extended_data") {
+        Ok(chunks) => chunks.iter().map(decode_felt).collect::<Option<String>>()?,
+        Err(_) => decode_felt(verification.get("data")?)?,
+    };
+    Some(email).filter(|email| email.contains('@'))
+}
+
+/// Subject or body of a reminder, `{domain}`, `{days}` and `{expiry}` are replaced
+pub fn render(template: &str, domain: &str, days: i64, expiry: i64) -> String {
+    let expiry = chrono::DateTime::from_timestamp(expiry, 0)
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .unwrap_or_default();
+    template
+        .replace("{domain}", domain)
+        .replace("{days}", &days.to_string())
+        .replace("{expiry}", &expiry)
+}
+
+async fn get_verified_email(
+    state: &AppState,
+    conf: &Reminders,
+    id: &str,
+) -> Result<Option<String>> {
+    Ok(state
+        .storage
+        .find_one(
+            "id_verifier_data",
+            doc! {
+                "id": id,
+                "verifier": to_hex(&conf.email_verifier),
+                "field": to_hex(&cairo_short_string_to_felt(EMAIL_FIELD)?),
+                "_cursor.to": null,
+            },
+        )
+        .await?
+        .as_ref()
+        .and_then(decode_email))
+}
+
+/// Emails the reminders due for the domains expiring soon, once per domain, term and
+/// threshold. The auto renewed domains are skipped
+pub async fn send_renewal_reminders(state: &AppState) -> Result<usize> {
+    let conf = &state.conf.reminders;
+    let storage = state.storage.as_ref();
+    let max_days = match conf.days_before.iter().max() {
+        Some(days) => *days,
+        None => return Ok(0),
+    };
+    let now = chrono::Utc::now().timestamp();
+    let mut sent = 0;
+    let mut after = None;
+    loop {
+        let (domains, next) = get_expiring_domains(
+            storage,
+            now,
+            now + max_days * SECONDS_PER_DAY,
+            after.as_ref(),
+            PAGE_SIZE,
+        )
+        .await?;
+        for domain in domains {
+            if domain.auto_renew || domain.auto_renew_altcoin {
+                continue;
+            }
+            let days = match get_threshold(&conf.days_before, domain.expiry, now) {
+                Some(days) => days,
+                None => continue,
+            };
+            let key = get_reminder_key(&domain.domain, domain.expiry, days);
+            if storage
+                .find_one(REMINDERS_COLLECTION, doc! { "_id": &key })
+                .await?
+                .is_some()
+            {
+                continue;
+            }
+            let to = match get_verified_email(state, conf, &domain.id).await? {
+                Some(to) => to,
+                None => continue,
+            };
+            let email = Email {
+                from: conf.from.clone(),
+                to,
+                subject: render(&conf.subject, &domain.domain, days, domain.expiry),
+                body: render(&conf.template, &domain.domain, days, domain.expiry),
+            };
+            if let Err(e) = send(&conf.provider, &email).await {
+                state.logger.warning(format!(
+                    "reminders: unable to email the reminder of {}: {}",
+                    domain.domain, e
+                ));
+                continue;
+            }
+            // the address isn't kept, the verification stays the source of truth
+            storage
+                .insert_one(
+                    REMINDERS_COLLECTION,
+                    doc! { "_id": &key, "domain": &domain.domain, "id": &domain.id, "sent_at": now },
+                )
+                .await?;
+            sent += 1;
+        }
+        match next {
+            Some(next) => after = Some(next),
+            None => return Ok(sent),
+        }
+    }
+}
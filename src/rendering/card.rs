@@ -0,0 +1,247 @@
+use anyhow::{anyhow, Result};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use lazy_static::lazy_static;
+use mongodb::bson::doc;
+use resvg::{
+    tiny_skia::{Pixmap, Transform},
+    usvg::{self, fontdb, TreeParsing, TreeTextToPath},
+};
+use starknet::core::types::FieldElement;
+
+use crate::{
+    avatars::resolve_avatar,
+    endpoints::uri::get_pp_url,
+    fanout::with_timeout,
+    images::{load_image, CachedImage},
+    models::AppState,
+    rendering::theme::{get_theme, Theme},
+    utils::to_hex,
+};
+
+pub const CARD_WIDTH: u32 = 600;
+pub const CARD_HEIGHT: u32 = 315;
+const PICTURE_SIZE: u32 = 160;
+const LOGO_SIZE: u32 = 48;
+const MARGIN: u32 = 40;
+
+lazy_static! {
+    // loaded once, the text of the cards is turned into paths with them
+    static ref FONTS: fontdb::Database = {
+        let mut fonts = fontdb::Database::new();
+        fonts.load_system_fonts();
+        fonts
+    };
+}
+
+/// What an identity card shows, the picture is embedded so the card renders offline
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Card {
+    pub name: String,
+    pub expiry: Option<i64>,
+    // data uri of the profile picture
+    pub picture: Option<String>,
+    // data uri of the logo of the theme
+    pub logo: Option<String>,
+}
+
+/// Image format of a card file, e.g. `12.png`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CardFormat {
+    Svg,
+    Png,
+}
+
+impl CardFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            CardFormat::Svg => "image/svg+xml",
+            CardFormat::Png => "image/png",
+        }
+    }
+}
+
+/// Id and format of a card file name, the id is decimal like in the nft metadata
+pub fn parse_card_file(file: &str) -> Option<(FieldElement, CardFormat)> {
+    let (id, format) = match file.rsplit_once('.')? {
+        (id, "svg") => (id, CardFormat::Svg),
+        (id, "png") => (id, CardFormat::Png),
+        _ => return None,
+    };
+    Some((FieldElement::from_dec_str(id).ok()?, format))
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Font size fitting the name on the width left by the picture, long names shrink
+pub fn get_font_size(name: &str) -> u32 {
+    let width = CARD_WIDTH - PICTURE_SIZE - 3 * MARGIN;
+    // a glyph is about 0.6 em wide
+    let fitting = (width as f64 / (0.6 * name.chars().count().max(1) as f64)) as u32;
+    fitting.clamp(14, 48)
+}
+
+fn get_clip(theme: &Theme, x: u32, y: u32) -> String {
+    let radius = match theme.frame.as_str() {
+        "circle" => PICTURE_SIZE / 2,
+        "rounded" => PICTURE_SIZE / 8,
+        _ => 0,
+    };
+    format!(
+        r#"<clipPath id="picture"><rect x="{}" y="{}" width="{size}" height="{size}" rx="{radius}"/></clipPath>"#,
+        x,
+        y,
+        size = PICTURE_SIZE,
+        radius = radius
+    )
+}
+
+/// Svg of the card in the colors of the theme of the domain
+pub fn render_svg(card: &Card, theme: &Theme) -> String {
+    let (x, y) = (MARGIN, (CARD_HEIGHT - PICTURE_SIZE) / 2);
+    let picture = match &card.picture {
+        Some(picture) => format!(
+            r#"{}<image x="{}" y="{}" width="{size}" height="{size}" href="{}" clip-path="url(#picture)" preserveAspectRatio="xMidYMid slice"/>"#,
+            get_clip(theme, x, y),
+            x,
+            y,
+            escape(picture),
+            size = PICTURE_SIZE
+        ),
+        None => format!(
+            r#"{}<rect x="{}" y="{}" width="{size}" height="{size}" fill="{}" clip-path="url(#picture)"/>"#,
+            get_clip(theme, x, y),
+            x,
+            y,
+            theme.accent_color,
+            size = PICTURE_SIZE
+        ),
+    };
+    // in the top right corner, it keeps its own aspect ratio
+    let logo = match &card.logo {
+        Some(logo) => format!(
+            r#"<image x="{}" y="{}" width="{size}" height="{size}" href="{}" preserveAspectRatio="xMidYMid meet"/>"#,
+            CARD_WIDTH - MARGIN - LOGO_SIZE,
+            MARGIN,
+            escape(logo),
+            size = LOGO_SIZE
+        ),
+        None => String::new(),
+    };
+    let expiry = card
+        .expiry
+        .and_then(|expiry| chrono::DateTime::from_timestamp(expiry, 0))
+        .map(|date| format!("Expires {}", date.format("%b %d, %Y")))
+        .unwrap_or_default();
+    let text_x = MARGIN * 2 + PICTURE_SIZE;
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#,
+            r#"<rect width="{width}" height="{height}" fill="{background}"/>"#,
+            r#"<rect y="{bar}" width="{width}" height="8" fill="{accent}"/>"#,
+            "{picture}",
+            "{logo}",
+            r#"<text x="{text_x}" y="{name_y}" font-family="sans-serif" font-weight="bold" font-size="{font_size}" fill="{text}">{name}</text>"#,
+            r#"<text x="{text_x}" y="{expiry_y}" font-family="sans-serif" font-size="18" fill="{text}">{expiry}</text>"#,
+            "</svg>"
+        ),
+        width = CARD_WIDTH,
+        height = CARD_HEIGHT,
+        background = theme.background_color,
+        accent = theme.accent_color,
+        bar = CARD_HEIGHT - 8,
+        picture = picture,
+        logo = logo,
+        text_x = text_x,
+        name_y = CARD_HEIGHT / 2,
+        expiry_y = CARD_HEIGHT / 2 + 36,
+        font_size = get_font_size(&card.name),
+        text = theme.text_color,
+        name = escape(&card.name),
+        expiry = expiry,
+    )
+}
+
+/// Png of an svg card, its text is drawn with the system fonts
+pub fn rasterize(svg: &str) -> Result<Vec<u8>> {
+    let mut tree = usvg::Tree::from_str(svg, &usvg::Options::default())?;
+    tree.convert_text(&FONTS);
+    let mut pixmap =
+        Pixmap::new(CARD_WIDTH, CARD_HEIGHT).ok_or_else(|| anyhow!("Invalid card size"))?;
+    resvg::render(&tree, Transform::default(), &mut pixmap.as_mut());
+    Ok(pixmap.encode_png()?)
+}
+
+fn to_data_uri(image: &CachedImage) -> String {
+    format!(
+        "data:{};base64,{}",
+        image.content_type,
+        STANDARD.encode(&image.bytes)
+    )
+}
+
+async fn get_picture(state: &AppState, id: FieldElement) -> Option<String> {
+    let url = match get_pp_url(state, &id).await {
+        Some(url) => url,
+        None => resolve_avatar(state, id).await.ok()??.image,
+    };
+    // the thumbnail keeps the card small, a picture that can't be loaded is left out
+    let image = load_image(state, &url, Some(PICTURE_SIZE * 2)).await.ok()?;
+    Some(to_data_uri(&image))
+}
+
+/// Card of an identity, named after its id when it has no domain
+pub async fn get_card(state: &AppState, id: FieldElement) -> Result<Card> {
+    let domain = state
+        .storage
+        .find_one("domains", doc! { "id": to_hex(&id), "_cursor.to": null })
+        .await?;
+    let picture = with_timeout(state, "card_picture", get_picture(state, id))
+        .await
+        .flatten();
+    Ok(match domain {
+        Some(domain) => Card {
+            name: domain.get_str("domain").unwrap_or_default().to_string(),
+            expiry: domain.get_i64("expiry").ok(),
+            picture,
+            logo: None,
+        },
+        None => Card {
+            name: format!("Starknet ID: {}", id),
+            expiry: None,
+            picture,
+            logo: None,
+        },
+    })
+}
+
+/// Card of an identity in the requested format
+pub async fn render_card(
+    state: &AppState,
+    id: FieldElement,
+    format: CardFormat,
+) -> Result<Vec<u8>> {
+    let mut card = get_card(state, id).await?;
+    let theme = get_theme(state, &card.name).await;
+    // a logo that can't be loaded is left out like the pictures
+    if let Some(url) = &theme.logo_url {
+        card.logo = with_timeout(
+            state,
+            "card_logo",
+            load_image(state, url, Some(LOGO_SIZE * 2)),
+        )
+        .await
+        .and_then(Result::ok)
+        .map(|image| to_data_uri(&image));
+    }
+    let svg = render_svg(&card, &theme);
+    match format {
+        CardFormat::Svg => Ok(svg.into_bytes()),
+        CardFormat::Png => tokio::task::spawn_blocking(move || rasterize(&svg)).await?,
+    }
+}
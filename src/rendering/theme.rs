@@ -0,0 +1,103 @@
+use futures::StreamExt;
+use lazy_static::lazy_static;
+use mongodb::bson::{doc, Document};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::models::AppState;
+use utoipa::ToSchema;
+
+lazy_static! {
+    static ref COLOR_REGEX: Regex = Regex::new(r"^#[0-9a-fA-F]{6}$").unwrap();
+}
+
+pub const FRAMES: [&str; 4] = ["none", "square", "rounded", "circle"];
+
+/// Visual customization applied to the rendered assets of every domain in a namespace
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct Theme {
+    // TLD or root domain the theme applies to, eg: "stark" or "braavos.stark"
+    pub namespace: String,
+    pub background_color: String,
+    pub text_color: String,
+    pub accent_color: String,
+    pub logo_url: Option<String>,
+    pub frame: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            namespace: "stark".to_string(),
+            background_color: "#ffffff".to_string(),
+            text_color: "#402d28".to_string(),
+            accent_color: "#6affaf".to_string(),
+            logo_url: None,
+            frame: "rounded".to_string(),
+        }
+    }
+}
+
+impl Theme {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.namespace.is_empty() || self.namespace.starts_with('.') {
+            return Err("Invalid namespace".to_string());
+        }
+        for (name, color) in [
+            ("background_color", &self.background_color),
+            ("text_color", &self.text_color),
+            ("accent_color", &self.accent_color),
+        ] {
+            if !COLOR_REGEX.is_match(color) {
+                return Err(format!("{} must be a #rrggbb color", name));
+            }
+        }
+        if let Some(logo_url) = &self.logo_url {
+            if !logo_url.starts_with("https://") {
+                return Err("logo_url must be an https url".to_string());
+            }
+        }
+        if !FRAMES.contains(&self.frame.as_str()) {
+            return Err(format!("frame must be one of {}", FRAMES.join(", ")));
+        }
+        Ok(())
+    }
+}
+
+/// Lists the namespaces a domain belongs to, from the most to the least specific,
+/// eg: "alice.braavos.stark" -> ["alice.braavos.stark", "braavos.stark", "stark"]
+pub fn get_namespaces(domain: &str) -> Vec<String> {
+    let parts: Vec<&str> = domain.split('.').collect();
+    (0..parts.len()).map(|i| parts[i..].join(".")).collect()
+}
+
+/// Picks the theme of the most specific namespace containing the domain
+pub fn select_theme<'a>(themes: &'a [Theme], domain: &str) -> Option<&'a Theme> {
+    get_namespaces(domain)
+        .iter()
+        .find_map(|namespace| themes.iter().find(|theme| &theme.namespace == namespace))
+}
+
+pub async fn get_theme(state: &AppState, domain: &str) -> Theme {
+    let themes_collection = state.starknetid_db.collection::<Document>("themes");
+    let mut themes = Vec::new();
+    match themes_collection
+        .find(
+            doc! { "namespace": { "$in": get_namespaces(domain) } },
+            None,
+        )
+        .await
+    {
+        Ok(mut cursor) => {
+            while let Some(Ok(doc)) = cursor.next().await {
+                if let Ok(theme) = mongodb::bson::from_document::<Theme>(doc) {
+                    themes.push(theme);
+                }
+            }
+        }
+        Err(e) => state
+            .logger
+            .warning(format!("Error while fetching themes: {}", e)),
+    }
+    select_theme(&themes, domain).cloned().unwrap_or_default()
+}
@@ -0,0 +1,131 @@
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use anyhow::Result;
+use axum::{
+    http::{HeaderMap, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
+use mongodb::event::command::{CommandEventHandler, CommandStartedEvent};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::WithExportConfig;
+use opentelemetry_sdk::{runtime, trace, Resource};
+use tracing::Instrument;
+use tracing_subscriber::{layer::SubscriberExt, EnvFilter};
+
+use crate::{config::Tracing, incidents::new_incident_id};
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+// longer ids sent by the clients are replaced by ours
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+/// Id of the request being served, set on the request for the layers and the handlers
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestId(pub String);
+
+tokio::task_local! {
+    static QUERY_COUNT: Arc<AtomicU64>;
+}
+
+/// Counts the mongodb commands sent by the task serving a request
+pub struct QueryCounter;
+
+impl CommandEventHandler for QueryCounter {
+    fn handle_command_started_event(&self, _event: CommandStartedEvent) {
+        record_query();
+    }
+}
+
+/// Counts a query for the request being served, if any
+pub fn record_query() {
+    let _ = QUERY_COUNT.try_with(|count| count.fetch_add(1, Ordering::Relaxed));
+}
+
+/// Output of the future with the queries it sent
+pub async fn with_query_count<F: Future>(future: F) -> (F::Output, u64) {
+    let count = Arc::new(AtomicU64::new(0));
+    let output = QUERY_COUNT.scope(count.clone(), future).await;
+    (output, count.load(Ordering::Relaxed))
+}
+
+/// Id the client or the load balancer in front of us sent, or else a new one
+pub fn get_request_id(headers: &HeaderMap) -> String {
+    headers
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| {
+            !id.is_empty()
+                && id.len() <= MAX_REQUEST_ID_LEN
+                && id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+        .map(String::from)
+        .unwrap_or_else(new_incident_id)
+}
+
+/// Json logs to stdout, and the spans to the otlp collector when one is configured
+pub fn init(conf: &Tracing) -> Result<()> {
+    let otlp = match conf.otlp_endpoint.is_empty() {
+        true => None,
+        false => {
+            let tracer = opentelemetry_otlp::new_pipeline()
+                .tracing()
+                .with_exporter(
+                    opentelemetry_otlp::new_exporter()
+                        .tonic()
+                        .with_endpoint(conf.otlp_endpoint.clone()),
+                )
+                .with_trace_config(trace::config().with_resource(Resource::new(vec![
+                    KeyValue::new("service.name", conf.service_name.clone()),
+                ])))
+                .install_batch(runtime::Tokio)?;
+            Some(tracing_opentelemetry::layer().with_tracer(tracer))
+        }
+    };
+    let subscriber = tracing_subscriber::registry()
+        .with(EnvFilter::try_new(&conf.level)?)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(false),
+        )
+        .with(otlp);
+    // not `try_init`, env_logger already is the logger of the log crate
+    tracing::subscriber::set_global_default(subscriber)?;
+    Ok(())
+}
+
+/// Serves the request in a span of its id and logs it, the id is sent back in the response
+pub async fn trace_requests<B>(mut req: Request<B>, next: Next<B>) -> Response {
+    let request_id = get_request_id(req.headers());
+    let method = req.method().to_string();
+    let path = req.uri().path().to_string();
+    req.extensions_mut().insert(RequestId(request_id.clone()));
+
+    let span =
+        tracing::info_span!("request", request_id = %request_id, method = %method, path = %path);
+    let start = Instant::now();
+    let (mut response, queries) = with_query_count(next.run(req).instrument(span.clone())).await;
+    tracing::info!(
+        parent: &span,
+        request_id = %request_id,
+        method = %method,
+        path = %path,
+        status = response.status().as_u16(),
+        latency_ms = start.elapsed().as_millis() as u64,
+        mongo_queries = queries,
+        "request served"
+    );
+    if let Ok(value) = HeaderValue::from_str(&request_id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
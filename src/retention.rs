@@ -0,0 +1,129 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Datelike};
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, to_document, Document},
+    options::{FindOptions, UpdateOptions},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::models::AppState;
+
+pub const COHORTS_COLLECTION: &str = "retention_cohorts";
+const YEAR_SECS: i64 = 365 * 86400;
+
+/// Domains registered in a calendar month and how many were renewed in their first year
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Cohort {
+    // e.g. 2024-03
+    pub month: String,
+    pub registered: u64,
+    pub renewed: u64,
+    pub rate: f64,
+    // false while some domains of the cohort can still renew within their first year
+    pub mature: bool,
+    pub computed_at: i64,
+}
+
+pub fn get_cohort_month(timestamp: i64) -> Option<String> {
+    let date = DateTime::from_timestamp(timestamp, 0)?;
+    Some(format!("{:04}-{:02}", date.year(), date.month()))
+}
+
+/// A renewal counts when made within the first year of the registration, or its grace period
+pub fn is_renewed_first_year(creation_date: i64, renewals: &[i64], grace_secs: i64) -> bool {
+    renewals.iter().any(|renewal| {
+        *renewal > creation_date && *renewal <= creation_date + YEAR_SECS + grace_secs
+    })
+}
+
+#[derive(Default)]
+struct CohortCounts {
+    registered: u64,
+    renewed: u64,
+    last_creation: i64,
+}
+
+async fn get_renewals(state: &AppState) -> mongodb::error::Result<HashMap<String, Vec<i64>>> {
+    let options = FindOptions::builder()
+        .projection(doc! { "_id": 0, "domain": 1, "timestamp": 1 })
+        .build();
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("renewals")
+        .find(doc! { "_cursor.to": null }, options)
+        .await?;
+    let mut renewals: HashMap<String, Vec<i64>> = HashMap::new();
+    while let Some(doc) = cursor.next().await {
+        let doc = doc?;
+        if let (Ok(domain), Ok(timestamp)) = (doc.get_str("domain"), doc.get_i64("timestamp")) {
+            renewals
+                .entry(domain.to_string())
+                .or_default()
+                .push(timestamp);
+        }
+    }
+    Ok(renewals)
+}
+
+/// Recomputes the cohorts and stores them in `retention_cohorts`
+pub async fn compute(state: &AppState) -> mongodb::error::Result<usize> {
+    let now = chrono::Utc::now().timestamp();
+    let grace_secs = state.conf.retention.grace_secs;
+    let renewals = get_renewals(state).await?;
+    let options = FindOptions::builder()
+        .projection(doc! { "_id": 0, "domain": 1, "creation_date": 1 })
+        .build();
+    let mut cursor = state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .find(doc! { "_cursor.to": null }, options)
+        .await?;
+
+    let mut cohorts: HashMap<String, CohortCounts> = HashMap::new();
+    while let Some(doc) = cursor.next().await {
+        let doc = doc?;
+        let (domain, creation_date) = match (doc.get_str("domain"), doc.get_i64("creation_date")) {
+            // subdomains are not renewed
+            (Ok(domain), Ok(creation_date)) if domain.matches('.').count() == 1 => {
+                (domain, creation_date)
+            }
+            _ => continue,
+        };
+        let month = match get_cohort_month(creation_date) {
+            Some(month) => month,
+            None => continue,
+        };
+        let counts = cohorts.entry(month).or_default();
+        counts.registered += 1;
+        counts.last_creation = counts.last_creation.max(creation_date);
+        let domain_renewals = renewals.get(domain).map(Vec::as_slice).unwrap_or_default();
+        if is_renewed_first_year(creation_date, domain_renewals, grace_secs) {
+            counts.renewed += 1;
+        }
+    }
+
+    let collection = state
+        .starknetid_db
+        .collection::<Document>(COHORTS_COLLECTION);
+    let count = cohorts.len();
+    for (month, counts) in cohorts {
+        let cohort = Cohort {
+            rate: counts.renewed as f64 / counts.registered as f64,
+            mature: now > counts.last_creation + YEAR_SECS + grace_secs,
+            month,
+            registered: counts.registered,
+            renewed: counts.renewed,
+            computed_at: now,
+        };
+        collection
+            .update_one(
+                doc! { "month": &cohort.month },
+                doc! { "$set": to_document(&cohort)? },
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await?;
+    }
+    Ok(count)
+}
@@ -0,0 +1,381 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{MatchedPath, State},
+    http::{self, Request},
+    middleware::{self, Next},
+    response::{IntoResponse, Redirect, Response},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache, clients,
+    errors::{ApiError, ErrorCode},
+    failures, incidents,
+    models::AppState,
+    networks::NetworkPrefix,
+    pagination::{Ordering, BLOCK_ORDER, DOMAIN_ORDER, ENTRANT_ORDER, EXPIRY_ORDER},
+    rate_limit, rpc_usage, sampling,
+    utils::{get_canonical_location, WithState},
+    ROUTE_REGISTRY,
+};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// Route groups can be disabled as a whole depending on the deployment tier
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteGroup {
+    Core,
+    Admin,
+    Partner,
+    Export,
+    Integrations,
+}
+
+/// Who is allowed to call a route
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthScope {
+    Public,
+    // the caller proves ownership of an account with a signature
+    Signature,
+    // admin key or a partner api key owning the namespace
+    Partner,
+    // user token issued by /me/token
+    Token,
+    Admin,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CachePolicy {
+    NoStore,
+    MaxAge(u64),
+}
+
+/// Cost class of a route, used to pick its rate limit
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum RateClass {
+    // single indexed document lookups
+    Light,
+    // aggregations, scans and calls to external services
+    Heavy,
+    // routes writing to the database
+    Write,
+}
+
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct RouteSpec {
+    pub path: &'static str,
+    pub method: Method,
+    pub handler: &'static str,
+    pub group: RouteGroup,
+    pub auth: AuthScope,
+    pub cache: CachePolicy,
+    pub rate: RateClass,
+    // sort keys of the paginated routes, the pages of a cursor never overlap
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ordering: Option<Ordering>,
+}
+
+const fn route(
+    method: Method,
+    path: &'static str,
+    handler: &'static str,
+    group: RouteGroup,
+    auth: AuthScope,
+    cache: CachePolicy,
+    rate: RateClass,
+) -> RouteSpec {
+    RouteSpec {
+        path,
+        method,
+        handler,
+        group,
+        auth,
+        cache,
+        rate,
+        ordering: None,
+    }
+}
+
+impl RouteSpec {
+    const fn ordered(self, ordering: Ordering) -> RouteSpec {
+        RouteSpec {
+            ordering: Some(ordering),
+            ..self
+        }
+    }
+}
+
+use AuthScope::*;
+use CachePolicy::*;
+use Method::*;
+use RateClass::*;
+
+/// Bumped on a breaking change of a response body, instances of another version don't
+/// share a pool since caches would mix both shapes
+pub const RESPONSE_SHAPE_VERSION: u32 = 1;
+
+/// Every route served by the api, a route registered with `#[route]` must be listed here
+#[rustfmt::skip]
+pub const ROUTES: &[RouteSpec] = &[
+    route(Get, "/", "root", RouteGroup::Core, Public, NoStore, Light),
+    route(Get, "/addr_has_rev", "endpoints::addr_has_rev", RouteGroup::Core, Public, NoStore, Light),
+    route(Get, "/addr_to_available_ids", "endpoints::addr_to_available_ids", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Get, "/addr_to_domain", "endpoints::addr_to_domain", RouteGroup::Core, Public, NoStore, Light),
+    route(Get, "/addr_to_domain_at", "endpoints::addr_to_domain_at", RouteGroup::Core, Public, MaxAge(3600), Heavy),
+    route(Get, "/addr_to_external_domains", "endpoints::addr_to_external_domains", RouteGroup::Core, Public, MaxAge(30), Heavy).ordered(BLOCK_ORDER),
+    route(Get, "/addr_to_full_ids", "endpoints::addr_to_full_ids", RouteGroup::Core, Public, NoStore, Heavy).ordered(BLOCK_ORDER),
+    route(Get, "/addr_to_token_id", "endpoints::addr_to_token_id", RouteGroup::Core, Public, MaxAge(30), Light),
+    route(Post, "/addressbook/resolve_sync", "endpoints::addressbook::resolve_sync", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Post, "/addrs_to_domains", "endpoints::addrs_to_domains", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Post, "/admin/add_api_key", "endpoints::admin::add_api_key", RouteGroup::Admin, Admin, NoStore, Write),
+    route(Post, "/admin/create_raffle", "endpoints::admin::create_raffle", RouteGroup::Admin, Admin, NoStore, Write),
+    route(Post, "/admin/delete_theme", "endpoints::admin::delete_theme", RouteGroup::Admin, Admin, NoStore, Write),
+    route(Post, "/admin/draw_raffle", "endpoints::admin::draw_raffle", RouteGroup::Admin, Admin, NoStore, Write),
+    route(Get, "/admin/get_samples", "endpoints::admin::get_samples", RouteGroup::Admin, Admin, NoStore, Light),
+    route(Get, "/admin/get_themes", "endpoints::admin::get_themes", RouteGroup::Admin, Admin, NoStore, Light),
+    route(Get, "/admin/maintenance", "endpoints::admin::maintenance", RouteGroup::Admin, Admin, NoStore, Heavy),
+    route(Get, "/admin/metrics", "endpoints::admin::metrics", RouteGroup::Admin, Admin, NoStore, Light),
+    route(Get, "/admin/usage/burst", "endpoints::admin::burst_usage", RouteGroup::Admin, Admin, NoStore, Light),
+    route(Get, "/admin/usage/clients", "endpoints::admin::client_usage", RouteGroup::Admin, Admin, NoStore, Light),
+    route(Get, "/admin/usage/rpc", "endpoints::admin::rpc_usage", RouteGroup::Admin, Admin, NoStore, Light),
+    route(Get, "/campaigns/get_free_domain", "endpoints::campaigns::get_free_domain", RouteGroup::Core, Public, NoStore, Write),
+    route(Get, "/compare", "endpoints::compare", RouteGroup::Core, Public, MaxAge(60), Heavy),
+    route(Post, "/contracts/claim", "endpoints::contracts::claim", RouteGroup::Core, Signature, NoStore, Write),
+    route(Post, "/crosschain/ethereum/resolve", "endpoints::crosschain::ethereum::resolve", RouteGroup::Integrations, Signature, NoStore, Heavy),
+    route(Get, "/crosschain/ethereum/resolve/:sender/:data", "endpoints::crosschain::ethereum::resolve_get", RouteGroup::Integrations, Signature, NoStore, Heavy),
+    route(Post, "/crosschain/solana/claim", "endpoints::crosschain::solana::claim", RouteGroup::Integrations, Signature, NoStore, Write),
+    route(Post, "/crosschain/solana/claim_ledger", "endpoints::crosschain::solana::claim_ledger", RouteGroup::Integrations, Signature, NoStore, Write),
+    route(Get, "/data_to_ids", "endpoints::data_to_ids", RouteGroup::Core, Public, MaxAge(30), Light),
+    route(Post, "/decode/batch", "endpoints::decode::batch", RouteGroup::Core, Public, NoStore, Light),
+    route(Get, "/dev/test_vectors", "endpoints::dev::test_vectors", RouteGroup::Core, Public, MaxAge(3600), Light),
+    route(Get, "/docs", "endpoints::docs::ui", RouteGroup::Core, Public, MaxAge(3600), Light),
+    route(Get, "/docs/examples", "endpoints::docs::examples", RouteGroup::Core, Public, MaxAge(60), Light),
+    route(Get, "/domain/:domain/owner_at", "endpoints::domain::owner_at", RouteGroup::Core, Public, MaxAge(3600), Heavy),
+    route(Get, "/domain/availability", "endpoints::domain::availability", RouteGroup::Core, Public, MaxAge(30), Light),
+    route(Get, "/domain/avatar", "endpoints::domain::avatar", RouteGroup::Core, Public, MaxAge(300), Heavy),
+    route(Get, "/domain/history", "endpoints::domain::history", RouteGroup::Core, Public, MaxAge(60), Heavy),
+    route(Get, "/domain_to_addr", "endpoints::domain_to_addr", RouteGroup::Core, Public, MaxAge(60), Light),
+    route(Get, "/domain_to_addr_at", "endpoints::domain_to_addr_at", RouteGroup::Core, Public, MaxAge(3600), Heavy),
+    route(Get, "/domain_to_data", "endpoints::domain_to_data", RouteGroup::Core, Public, MaxAge(30), Light),
+    route(Get, "/domain_to_record", "endpoints::domain_to_record", RouteGroup::Core, Public, MaxAge(60), Light),
+    route(Get, "/domains/expiring", "endpoints::domains::expiring", RouteGroup::Core, Public, MaxAge(60), Heavy).ordered(EXPIRY_ORDER),
+    route(Get, "/domains/search", "endpoints::domains::search", RouteGroup::Core, Public, MaxAge(30), Light).ordered(DOMAIN_ORDER),
+    route(Post, "/domains_to_addrs", "endpoints::domains_to_addrs", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Get, "/events", "endpoints::events::get_events", RouteGroup::Core, Public, MaxAge(30), Heavy).ordered(BLOCK_ORDER),
+    route(Get, "/events/stream", "endpoints::events::stream", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Post, "/export/domains", "endpoints::export::domains", RouteGroup::Export, Partner, NoStore, Heavy),
+    route(Get, "/export/domains.csv", "endpoints::export::domains_csv", RouteGroup::Export, Partner, NoStore, Heavy),
+    route(Get, "/export/domains.jsonl", "endpoints::export::domains_jsonl", RouteGroup::Export, Partner, NoStore, Heavy),
+    route(Get, "/export/domains/delta", "endpoints::export::domains_delta", RouteGroup::Export, Partner, NoStore, Heavy),
+    route(Get, "/export/jobs/:job_id", "endpoints::export::get_job", RouteGroup::Export, Partner, NoStore, Light),
+    route(Post, "/galxe/verify", "endpoints::galxe::verify", RouteGroup::Integrations, Public, NoStore, Light),
+    route(Get, "/get_altcoin_quote", "endpoints::get_altcoin_quote", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Get, "/get_expiring_domains", "endpoints::get_expiring_domains", RouteGroup::Core, Public, MaxAge(30), Heavy),
+    route(Get, "/governance/power", "endpoints::governance::power", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Post, "/graphql", "endpoints::graphql", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Get, "/id_to_data", "endpoints::id_to_data", RouteGroup::Core, Public, MaxAge(30), Light),
+    route(Get, "/identity/:id/verifier_data", "endpoints::identity::verifier_data", RouteGroup::Core, Public, MaxAge(30), Light),
+    route(Post, "/identity/set_description", "endpoints::identity::set_description", RouteGroup::Core, Signature, NoStore, Write),
+    route(Get, "/identity_card/:file", "endpoints::identity_card", RouteGroup::Core, Public, MaxAge(300), Heavy),
+    route(Get, "/img", "endpoints::img", RouteGroup::Core, Public, MaxAge(3600), Heavy),
+    route(Get, "/integrity/:addr", "endpoints::integrity::check", RouteGroup::Core, Public, NoStore, Light),
+    route(Post, "/integrity/:addr/fix_tx", "endpoints::integrity::fix_tx", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Post, "/raffles/enter", "endpoints::raffles::enter", RouteGroup::Core, Signature, NoStore, Write),
+    route(Get, "/me/expiry.ics", "endpoints::me::expiry_ics", RouteGroup::Core, Token, NoStore, Light),
+    route(Post, "/me/freeze", "endpoints::me::freeze", RouteGroup::Core, Token, NoStore, Write),
+    route(Get, "/me/notifications", "endpoints::me::notifications", RouteGroup::Core, Token, NoStore, Light),
+    route(Get, "/me/quota", "endpoints::me::quota", RouteGroup::Core, Public, NoStore, Light),
+    route(Post, "/me/token", "endpoints::me::token", RouteGroup::Core, Signature, NoStore, Write),
+    route(Post, "/me/unfreeze", "endpoints::me::unfreeze", RouteGroup::Core, Signature, NoStore, Write),
+    route(Get, "/openapi.json", "endpoints::openapi", RouteGroup::Core, Public, MaxAge(3600), Light),
+    route(Get, "/org/:domain/members", "endpoints::org::get_members", RouteGroup::Core, Public, MaxAge(60), Light),
+    route(Post, "/org/set_member", "endpoints::org::set_member", RouteGroup::Core, Signature, NoStore, Write),
+    route(Get, "/partners/:id/revenue", "endpoints::partners::revenue", RouteGroup::Partner, Partner, NoStore, Heavy),
+    route(Post, "/portfolio/transfer_tx", "endpoints::portfolio::transfer_tx", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Post, "/preferences/set", "endpoints::preferences::set", RouteGroup::Core, Signature, NoStore, Write),
+    route(Get, "/raffles/get_entrants", "endpoints::raffles::get_entrants", RouteGroup::Core, Public, MaxAge(3600), Light).ordered(ENTRANT_ORDER),
+    route(Get, "/raffles/get_raffle", "endpoints::raffles::get_raffle", RouteGroup::Core, Public, NoStore, Light),
+    route(Post, "/referral/add_click", "endpoints::referral::add_click", RouteGroup::Core, Public, NoStore, Write),
+    route(Get, "/referral/click_count", "endpoints::referral::click_count", RouteGroup::Core, Public, MaxAge(30), Heavy),
+    route(Get, "/referral/revenue", "endpoints::referral::revenue", RouteGroup::Core, Public, MaxAge(30), Heavy),
+    route(Get, "/referral/sales_count", "endpoints::referral::sales_count", RouteGroup::Core, Public, MaxAge(30), Heavy),
+    route(Get, "/rendering/get_theme", "endpoints::rendering::get_theme", RouteGroup::Core, Public, MaxAge(300), Light),
+    route(Post, "/rendering/set_theme", "endpoints::rendering::set_theme", RouteGroup::Partner, Partner, NoStore, Write),
+    route(Get, "/renewal/get_metahash", "endpoints::renewal::get_metahash", RouteGroup::Core, Public, MaxAge(30), Light),
+    route(Get, "/renewal/get_non_subscribed_domains", "endpoints::renewal::get_non_subscribed_domains", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Get, "/renewal/get_renewal_data", "endpoints::renewal::get_renewal_data", RouteGroup::Core, Public, MaxAge(30), Light),
+    route(Get, "/renewal/get_subscription_info", "endpoints::renewal::get_subscription_info", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Get, "/renewal/status", "endpoints::renewal::status", RouteGroup::Core, Public, MaxAge(30), Heavy),
+    route(Get, "/social_to_domain", "endpoints::social_to_domain", RouteGroup::Core, Public, MaxAge(300), Heavy),
+    route(Get, "/starkscan/fetch_nfts", "endpoints::starkscan::fetch_nfts", RouteGroup::Integrations, Public, NoStore, Heavy),
+    route(Get, "/stats/count_addrs", "endpoints::stats::count_addrs", RouteGroup::Core, Public, MaxAge(60), Heavy),
+    route(Get, "/stats/count_club_domains", "endpoints::stats::count_club_domains", RouteGroup::Core, Public, MaxAge(60), Heavy),
+    route(Get, "/stats/count_created", "endpoints::stats::count_created", RouteGroup::Core, Public, MaxAge(60), Heavy),
+    route(Get, "/stats/count_domains", "endpoints::stats::count_domains", RouteGroup::Core, Public, MaxAge(60), Heavy),
+    route(Get, "/stats/count_ids", "endpoints::stats::count_ids", RouteGroup::Core, Public, MaxAge(60), Heavy),
+    route(Get, "/stats/count_renewed", "endpoints::stats::count_renewed", RouteGroup::Core, Public, MaxAge(60), Heavy),
+    route(Get, "/stats/distribution", "endpoints::stats::distribution", RouteGroup::Core, Public, MaxAge(600), Light),
+    route(Get, "/stats/expired_club_domains", "endpoints::stats::expired_club_domains", RouteGroup::Core, Public, MaxAge(60), Heavy),
+    route(Get, "/stats/retention", "endpoints::stats::retention", RouteGroup::Core, Public, MaxAge(3600), Light),
+    route(Get, "/status", "endpoints::status", RouteGroup::Core, Public, NoStore, Light),
+    route(Get, "/transparency/proof", "endpoints::transparency::proof", RouteGroup::Core, Public, NoStore, Light),
+    route(Get, "/transparency/root", "endpoints::transparency::root", RouteGroup::Core, Public, MaxAge(10), Light),
+    route(Get, "/tx/:hash/naming_actions", "endpoints::tx::naming_actions", RouteGroup::Core, Public, MaxAge(30), Heavy),
+    route(Get, "/tx_builder/register", "endpoints::tx_builder::register", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Get, "/ui", "endpoints::ui::index", RouteGroup::Core, Public, NoStore, Light),
+    route(Get, "/ui/*path", "endpoints::ui::asset", RouteGroup::Core, Public, MaxAge(3600), Light),
+    route(Get, "/uri", "endpoints::uri", RouteGroup::Core, Public, MaxAge(30), Light),
+    route(Get, "/uris", "endpoints::uris", RouteGroup::Core, Public, MaxAge(30), Heavy),
+    route(Get, "/watch/longpoll", "endpoints::watch::longpoll", RouteGroup::Core, Public, NoStore, Heavy),
+    route(Get, "/webhooks", "endpoints::webhooks::list", RouteGroup::Partner, Partner, NoStore, Light),
+    route(Post, "/webhooks/delete", "endpoints::webhooks::delete", RouteGroup::Partner, Partner, NoStore, Write),
+    route(Get, "/webhooks/deliveries", "endpoints::webhooks::deliveries", RouteGroup::Partner, Partner, NoStore, Light),
+    route(Post, "/webhooks/register", "endpoints::webhooks::register", RouteGroup::Partner, Partner, NoStore, Write),
+    route(Get, "/ws", "ws::endpoint", RouteGroup::Core, Public, NoStore, Light),
+];
+
+pub fn get_route(method: Method, path: &str) -> Option<&'static RouteSpec> {
+    ROUTES
+        .iter()
+        .find(|spec| spec.method == method && spec.path == path)
+}
+
+/// The handlers registered with `#[route]` must all be declared in `ROUTES`
+pub fn check_registry(registered: usize) -> Result<(), String> {
+    if registered == ROUTES.len() {
+        Ok(())
+    } else {
+        Err(format!(
+            "routes: {} routes registered but {} declared in the route registry",
+            registered,
+            ROUTES.len()
+        ))
+    }
+}
+
+/// Declared route of a request, `None` when no route matched it. A matched route missing
+/// from the registry is refused since its group, auth and rate class are unknown
+pub fn get_matched_route<B>(req: &Request<B>) -> Result<Option<&'static RouteSpec>, ApiError> {
+    let path = match req.extensions().get::<MatchedPath>() {
+        Some(path) => path,
+        None => return Ok(None),
+    };
+    Method::from_http(req.method())
+        .and_then(|method| get_route(method, path.as_str()))
+        .map(Some)
+        .ok_or_else(|| {
+            ApiError::new(
+                ErrorCode::InternalError,
+                format!("{} is missing from the route registry", path.as_str()),
+            )
+        })
+}
+
+impl Method {
+    pub fn from_http(method: &http::Method) -> Option<Self> {
+        match *method {
+            // served by the get handlers
+            http::Method::GET | http::Method::HEAD => Some(Method::Get),
+            http::Method::POST => Some(Method::Post),
+            _ => None,
+        }
+    }
+}
+
+// hides the routes whose group is disabled on this deployment tier, and the undeclared ones
+async fn gate_route<B>(
+    State(state): State<Arc<AppState>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let spec = match get_matched_route(&req) {
+        Ok(spec) => spec,
+        Err(e) => return e.into_response(),
+    };
+    match spec {
+        Some(spec) if !state.conf.deployment.is_enabled(spec.group) => {
+            ApiError::new(ErrorCode::FeatureDisabled, "Route disabled on this deployment")
+                .into_response()
+        }
+        _ => next.run(req).await,
+    }
+}
+
+// sends clients to the url of the canonical domain so caches don't split on its spelling
+async fn redirect_to_canonical<B>(
+    State(state): State<Arc<AppState>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    if state.conf.server.canonical_redirect && req.method() == http::Method::GET {
+        if let Some(query) = req.uri().query() {
+            if let Some(location) = get_canonical_location(req.uri().path(), query) {
+                let prefix = req
+                    .extensions()
+                    .get::<NetworkPrefix>()
+                    .map_or("", |prefix| prefix.0.as_str());
+                return Redirect::permanent(&format!("{}{}", prefix, location)).into_response();
+            }
+        }
+    }
+    next.run(req).await
+}
+
+/// Merges all the routes registered with `#[route]` into the app router
+pub fn build_router(shared_state: Arc<AppState>) -> Router {
+    ROUTE_REGISTRY
+        .lock()
+        .unwrap()
+        .clone()
+        .into_iter()
+        .fold(Router::new().with_state(shared_state.clone()), |acc, r| {
+            acc.merge(r.to_router(shared_state.clone()))
+        })
+        .layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            redirect_to_canonical,
+        ))
+        .layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            rpc_usage::track_rpc_usage,
+        ))
+        .layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            clients::track_clients,
+        ))
+        .layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            sampling::sample_traffic,
+        ))
+        .layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            cache::cache_responses,
+        ))
+        .layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            rate_limit::limit_rate,
+        ))
+        .layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            gate_route,
+        ))
+        .layer(middleware::from_fn_with_state(
+            shared_state.clone(),
+            failures::standardize_failures,
+        ))
+        .layer(middleware::from_fn_with_state(
+            shared_state,
+            incidents::catch_panics,
+        ))
+}
@@ -0,0 +1,172 @@
+use std::{
+    collections::VecDeque,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use tokio::{sync::oneshot, time::Instant};
+
+use crate::{
+    config::RpcLimits,
+    metrics::{labeled, Metrics},
+    rpc_usage::RpcUsage,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Priority {
+    // calls made while a client waits for the response
+    Interactive,
+    Background,
+}
+
+#[derive(Default)]
+struct QueueState {
+    available: usize,
+    interactive: VecDeque<oneshot::Sender<()>>,
+    background: VecDeque<(Instant, oneshot::Sender<()>)>,
+}
+
+/// Bounds the number of concurrent RPC calls, interactive calls go first unless a
+/// background call waited for longer than `max_background_wait`
+pub struct RpcQueue {
+    state: Mutex<QueueState>,
+    max_background_wait: Duration,
+    metrics: Arc<Metrics>,
+    pub usage: RpcUsage,
+}
+
+struct Permit<'a> {
+    queue: &'a RpcQueue,
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.queue.metrics.add("rpc_calls_in_flight", -1);
+        self.queue.release();
+    }
+}
+
+// releases the permit if it was granted right when the waiting call got cancelled
+struct Pending<'a> {
+    queue: &'a RpcQueue,
+    receiver: Option<oneshot::Receiver<()>>,
+}
+
+impl Drop for Pending<'_> {
+    fn drop(&mut self) {
+        if let Some(mut receiver) = self.receiver.take() {
+            receiver.close();
+            if receiver.try_recv().is_ok() {
+                self.queue.release();
+            }
+        }
+    }
+}
+
+impl RpcQueue {
+    pub fn new(limits: &RpcLimits, metrics: Arc<Metrics>) -> Self {
+        RpcQueue {
+            state: Mutex::new(QueueState {
+                available: limits.max_concurrent,
+                ..Default::default()
+            }),
+            max_background_wait: Duration::from_millis(limits.max_background_wait_ms),
+            metrics,
+            usage: RpcUsage::default(),
+        }
+    }
+
+    fn permit(&self) -> Permit<'_> {
+        self.metrics.add("rpc_calls_in_flight", 1);
+        Permit { queue: self }
+    }
+
+    fn update_metrics(&self, state: &QueueState) {
+        self.metrics.set(
+            &labeled("rpc_queue_depth", "priority", "interactive"),
+            state.interactive.len() as i64,
+        );
+        self.metrics.set(
+            &labeled("rpc_queue_depth", "priority", "background"),
+            state.background.len() as i64,
+        );
+    }
+
+    async fn acquire(&self, priority: Priority) -> Permit<'_> {
+        let receiver = {
+            let mut state = self.state.lock().unwrap();
+            if state.available > 0 && state.interactive.is_empty() && state.background.is_empty() {
+                state.available -= 1;
+                return self.permit();
+            }
+            let (sender, receiver) = oneshot::channel();
+            match priority {
+                Priority::Interactive => state.interactive.push_back(sender),
+                Priority::Background => state.background.push_back((Instant::now(), sender)),
+            }
+            self.update_metrics(&state);
+            receiver
+        };
+
+        let mut pending = Pending {
+            queue: self,
+            receiver: Some(receiver),
+        };
+        // the sender is only dropped with the queue
+        let _ = pending.receiver.as_mut().unwrap().await;
+        pending.receiver = None;
+        self.permit()
+    }
+
+    fn release(&self) {
+        let mut state = self.state.lock().unwrap();
+        loop {
+            let starving = state.background.front().map_or(false, |(since, _)| {
+                since.elapsed() >= self.max_background_wait
+            });
+            let next = if starving || state.interactive.is_empty() {
+                state.background.pop_front().map(|(_, sender)| sender)
+            } else {
+                state.interactive.pop_front()
+            };
+            match next {
+                // the waiting call may have been cancelled, then try the next one
+                Some(sender) => {
+                    if sender.send(()).is_ok() {
+                        break;
+                    }
+                }
+                None => {
+                    state.available += 1;
+                    break;
+                }
+            }
+        }
+        self.update_metrics(&state);
+    }
+
+    /// Runs an RPC call once a slot is available for its priority, `method` is the
+    /// json-rpc method it sends and is used for credits accounting
+    pub async fn run<F: Future>(
+        &self,
+        priority: Priority,
+        method: &'static str,
+        call: F,
+    ) -> F::Output {
+        self.run_batch(priority, method, 1, call).await
+    }
+
+    /// Same as `run` for a multicall aggregating `batch_size` contract calls
+    pub async fn run_batch<F: Future>(
+        &self,
+        priority: Priority,
+        method: &'static str,
+        batch_size: u64,
+        call: F,
+    ) -> F::Output {
+        let _permit = self.acquire(priority).await;
+        self.usage.record_call(method, batch_size, &self.metrics);
+        call.await
+    }
+}
@@ -0,0 +1,278 @@
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use axum::{
+    extract::{MatchedPath, State},
+    http::Request,
+    middleware::Next,
+    response::Response,
+};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::Serialize;
+
+use crate::{
+    auth::get_api_key,
+    config::RpcBudget,
+    metrics::{labeled, Metrics},
+    models::AppState,
+};
+
+// calls made outside of a request, e.g. by the finality tracker
+const BACKGROUND: &str = "background";
+// projections are not meaningful in the first hour of the month
+const MIN_ELAPSED_SECS: i64 = 3600;
+
+/// Request an RPC call is attributed to
+pub struct RpcContext {
+    endpoint: String,
+    api_key: Option<String>,
+}
+
+tokio::task_local! {
+    static RPC_CONTEXT: RpcContext;
+}
+
+#[derive(Hash, PartialEq, Eq, Clone)]
+struct UsageKey {
+    endpoint: String,
+    api_key: Option<String>,
+    method: &'static str,
+}
+
+#[derive(Default)]
+struct CallStats {
+    calls: u64,
+    // contract calls sent through these rpc calls, multicalls batch several of them
+    batched: u64,
+    max_batch_size: u64,
+}
+
+struct UsageMonth {
+    start: DateTime<Utc>,
+    calls: HashMap<UsageKey, CallStats>,
+}
+
+impl UsageMonth {
+    fn new(now: DateTime<Utc>) -> Self {
+        UsageMonth {
+            start: get_month_start(now.year(), now.month()),
+            calls: HashMap::new(),
+        }
+    }
+}
+
+/// RPC calls accounting for the current calendar month
+pub struct RpcUsage {
+    month: Mutex<UsageMonth>,
+    alarmed: AtomicBool,
+}
+
+#[derive(Serialize)]
+pub struct EndpointUsage {
+    pub endpoint: String,
+    pub calls: u64,
+    pub credits: u64,
+    pub avg_batch_size: f64,
+    pub max_batch_size: u64,
+}
+
+#[derive(Serialize)]
+pub struct ApiKeyUsage {
+    pub api_key: String,
+    pub calls: u64,
+    pub credits: u64,
+}
+
+#[derive(Serialize)]
+pub struct UsageReport {
+    pub since: i64,
+    pub credits: u64,
+    pub projected_credits: u64,
+    pub monthly_credits: u64,
+    pub endpoints: Vec<EndpointUsage>,
+    pub api_keys: Vec<ApiKeyUsage>,
+}
+
+pub fn get_month_start(year: i32, month: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).unwrap()
+}
+
+/// Extrapolates the credits used so far to the whole month
+pub fn project_monthly(credits: u64, now: DateTime<Utc>) -> u64 {
+    let start = get_month_start(now.year(), now.month());
+    let end = if now.month() == 12 {
+        get_month_start(now.year() + 1, 1)
+    } else {
+        get_month_start(now.year(), now.month() + 1)
+    };
+    let elapsed = (now - start).num_seconds().max(MIN_ELAPSED_SECS);
+    let total = (end - start).num_seconds();
+    (credits as f64 * total as f64 / elapsed as f64) as u64
+}
+
+impl Default for RpcUsage {
+    fn default() -> Self {
+        RpcUsage {
+            month: Mutex::new(UsageMonth::new(Utc::now())),
+            alarmed: AtomicBool::new(false),
+        }
+    }
+}
+
+impl RpcUsage {
+    fn current_month(&self) -> std::sync::MutexGuard<'_, UsageMonth> {
+        let mut month = self.month.lock().unwrap();
+        let now = Utc::now();
+        if get_month_start(now.year(), now.month()) != month.start {
+            *month = UsageMonth::new(now);
+            self.alarmed.store(false, Ordering::Relaxed);
+        }
+        month
+    }
+
+    /// Attributes a call to the request being served, if any
+    pub fn record_call(&self, method: &'static str, batch_size: u64, metrics: &Metrics) {
+        let (endpoint, api_key) = RPC_CONTEXT
+            .try_with(|ctx| (ctx.endpoint.clone(), ctx.api_key.clone()))
+            .unwrap_or_else(|_| (BACKGROUND.to_string(), None));
+
+        metrics.add(&labeled("rpc_calls_total", "endpoint", &endpoint), 1);
+        metrics.add(&labeled("rpc_calls_total", "method", method), 1);
+        if let Some(api_key) = &api_key {
+            metrics.add(&labeled("rpc_calls_total", "api_key", api_key), 1);
+        }
+        let mut month = self.current_month();
+        let stats = month
+            .calls
+            .entry(UsageKey {
+                endpoint,
+                api_key,
+                method,
+            })
+            .or_default();
+        stats.calls += 1;
+        stats.batched += batch_size;
+        stats.max_batch_size = stats.max_batch_size.max(batch_size);
+    }
+
+    /// Number of distinct endpoint, api key and method combinations this month
+    pub fn key_count(&self) -> usize {
+        self.current_month().calls.len()
+    }
+
+    pub fn report(&self, budget: &RpcBudget) -> UsageReport {
+        let month = self.current_month();
+        let mut endpoints: HashMap<String, EndpointUsage> = HashMap::new();
+        let mut api_keys: HashMap<String, (u64, u64)> = HashMap::new();
+        for (key, stats) in &month.calls {
+            let credits = stats.calls * budget.credits(key.method);
+            let entry = endpoints
+                .entry(key.endpoint.clone())
+                .or_insert_with(|| EndpointUsage {
+                    endpoint: key.endpoint.clone(),
+                    calls: 0,
+                    credits: 0,
+                    avg_batch_size: 0.0,
+                    max_batch_size: 0,
+                });
+            // running sum of the batched calls until the average is computed below
+            entry.avg_batch_size += stats.batched as f64;
+            entry.calls += stats.calls;
+            entry.credits += credits;
+            entry.max_batch_size = entry.max_batch_size.max(stats.max_batch_size);
+            if let Some(api_key) = &key.api_key {
+                let entry = api_keys.entry(api_key.clone()).or_default();
+                entry.0 += stats.calls;
+                entry.1 += credits;
+            }
+        }
+        let credits = endpoints.values().map(|endpoint| endpoint.credits).sum();
+
+        let mut endpoints: Vec<EndpointUsage> = endpoints
+            .into_values()
+            .map(|mut endpoint| {
+                endpoint.avg_batch_size /= endpoint.calls.max(1) as f64;
+                endpoint
+            })
+            .collect();
+        endpoints.sort_by(|a, b| b.credits.cmp(&a.credits));
+        let mut api_keys: Vec<ApiKeyUsage> = api_keys
+            .into_iter()
+            .map(|(api_key, (calls, credits))| ApiKeyUsage {
+                api_key,
+                calls,
+                credits,
+            })
+            .collect();
+        api_keys.sort_by(|a, b| b.credits.cmp(&a.credits));
+
+        UsageReport {
+            since: month.start.timestamp(),
+            credits,
+            projected_credits: project_monthly(credits, Utc::now()),
+            monthly_credits: budget.monthly_credits,
+            endpoints,
+            api_keys,
+        }
+    }
+}
+
+// scopes the rpc calls made by a request so they are attributed to its route and api key
+pub async fn track_rpc_usage<B>(
+    State(state): State<Arc<AppState>>,
+    req: Request<B>,
+    next: Next<B>,
+) -> Response {
+    let endpoint = match req.extensions().get::<MatchedPath>() {
+        Some(path) => path.as_str().to_string(),
+        None => return next.run(req).await,
+    };
+    let api_key = if req.headers().contains_key("x-api-key") {
+        get_api_key(&state, req.headers())
+            .await
+            .map(|api_key| api_key.name)
+    } else {
+        None
+    };
+    RPC_CONTEXT
+        .scope(RpcContext { endpoint, api_key }, next.run(req))
+        .await
+}
+
+/// Publishes the monthly credits and warns once a month when the projection exceeds the budget
+pub fn check_budget(state: &AppState) {
+    let budget = &state.conf.rpc_budget;
+    let usage = &state.rpc_queue.usage;
+    let report = usage.report(budget);
+    state
+        .metrics
+        .set("rpc_credits_month", report.credits as i64);
+    state
+        .metrics
+        .set("rpc_credits_projected", report.projected_credits as i64);
+    if budget.monthly_credits == 0 {
+        return;
+    }
+
+    let over_budget =
+        report.projected_credits as f64 > budget.monthly_credits as f64 * budget.alarm_ratio;
+    state.metrics.set("rpc_budget_alarm", over_budget as i64);
+    if over_budget && !usage.alarmed.swap(true, Ordering::Relaxed) {
+        let top = report
+            .endpoints
+            .iter()
+            .take(3)
+            .map(|endpoint| format!("{} ({} credits)", endpoint.endpoint, endpoint.credits))
+            .collect::<Vec<_>>()
+            .join(", ");
+        state.logger.severe(format!(
+            "rpc budget: projected {} credits this month for a budget of {}, top endpoints: {}",
+            report.projected_credits, budget.monthly_credits, top
+        ));
+    }
+}
@@ -0,0 +1,186 @@
+use std::{sync::Arc, time::Instant};
+
+use axum::{
+    body::{boxed, Body, Full},
+    extract::State,
+    http::{HeaderMap, Request},
+    middleware::Next,
+    response::Response,
+};
+use hyper::body::HttpBody;
+use mongodb::{
+    bson::{doc, to_bson, Bson, Document},
+    options::CreateCollectionOptions,
+};
+use serde_json::Value;
+
+use crate::models::AppState;
+
+pub const SAMPLES_COLLECTION: &str = "debug_samples";
+const REDACTED: &str = "[redacted]";
+const SENSITIVE_HEADERS: [&str; 5] = [
+    "authorization",
+    "cookie",
+    "set-cookie",
+    "x-admin-key",
+    "x-api-key",
+];
+// matched anywhere in the names, e.g. `sig` also covers `sig_r` and `referrer_sig`
+const SENSITIVE_FIELDS: [&str; 6] = ["sig", "token", "key", "secret", "password", "email"];
+// too common as a part of other names, matched as the whole name
+const SENSITIVE_NAMES: [&str; 1] = ["code"];
+const MAX_VALUE_LEN: usize = 64;
+const MAX_QUERY_LEN: usize = 512;
+
+/// Creates the capped collection holding the samples, the oldest ones get overwritten
+pub async fn init(state: &AppState) {
+    let options = CreateCollectionOptions::builder()
+        .capped(true)
+        .size(state.conf.debug_sampling.collection_size)
+        .build();
+    // fails when the collection already exists
+    let _ = state
+        .starknetid_db
+        .create_collection(SAMPLES_COLLECTION, options)
+        .await;
+}
+
+pub fn scrub_headers(headers: &HeaderMap) -> Document {
+    let mut scrubbed = Document::new();
+    for (name, value) in headers {
+        let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+            REDACTED.to_string()
+        } else {
+            value.to_str().unwrap_or_default().to_string()
+        };
+        scrubbed.insert(name.as_str(), value);
+    }
+    scrubbed
+}
+
+/// Field of a body or of a query holding a secret or a personal data
+pub fn is_sensitive_field(name: &str) -> bool {
+    let name = name.to_lowercase();
+    SENSITIVE_NAMES.contains(&name.as_str())
+        || SENSITIVE_FIELDS.iter().any(|field| name.contains(field))
+}
+
+/// Query string without the secrets and with the values truncated
+pub fn sanitize_query(query: &str) -> String {
+    let sanitized = query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            if is_sensitive_field(name) {
+                format!("{}=[redacted]", name)
+            } else {
+                format!(
+                    "{}={}",
+                    name,
+                    value.chars().take(MAX_VALUE_LEN).collect::<String>()
+                )
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    sanitized.chars().take(MAX_QUERY_LEN).collect()
+}
+
+pub fn scrub_json(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_field(key) {
+                    *value = Value::String(REDACTED.to_string());
+                } else {
+                    scrub_json(value);
+                }
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(scrub_json),
+        _ => {}
+    }
+}
+
+fn scrub_body(body: &[u8]) -> Bson {
+    if body.is_empty() {
+        return Bson::Null;
+    }
+    match serde_json::from_slice::<Value>(body) {
+        Ok(mut json) => {
+            scrub_json(&mut json);
+            to_bson(&json).unwrap_or(Bson::Null)
+        }
+        Err(_) => match std::str::from_utf8(body) {
+            Ok(text) => Bson::String(text.to_string()),
+            Err(_) => Bson::String(format!("<{} bytes of binary data>", body.len())),
+        },
+    }
+}
+
+fn fits(size: Option<u64>, max: u64) -> bool {
+    size.map_or(false, |size| size <= max)
+}
+
+/// Records a small share of the requests along with their response
+pub async fn sample_traffic(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next<Body>,
+) -> Response {
+    let conf = &state.conf.debug_sampling;
+    // bodies of unknown size like streams can't be buffered
+    if rand::random::<f64>() >= conf.rate
+        || !fits(req.body().size_hint().upper(), conf.max_body_size)
+    {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let request_body = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(_) => return next.run(Request::from_parts(parts, Body::empty())).await,
+    };
+    let mut sample = doc! {
+        "timestamp": chrono::Utc::now().timestamp(),
+        "method": parts.method.as_str(),
+        "path": parts.uri.path(),
+        "query": parts.uri.query().map(sanitize_query),
+        "request": {
+            "headers": scrub_headers(&parts.headers),
+            "body": scrub_body(&request_body),
+        },
+    };
+
+    let started = Instant::now();
+    let response = next
+        .run(Request::from_parts(parts, Body::from(request_body)))
+        .await;
+    sample.insert("duration_ms", started.elapsed().as_millis() as i64);
+    sample.insert("status", response.status().as_u16() as i32);
+    if !fits(response.body().size_hint().upper(), conf.max_body_size) {
+        return response;
+    }
+
+    let (parts, body) = response.into_parts();
+    let response_body = match hyper::body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(_) => return Response::from_parts(parts, boxed(Full::default())),
+    };
+    sample.insert(
+        "response",
+        doc! {
+            "headers": scrub_headers(&parts.headers),
+            "body": scrub_body(&response_body),
+        },
+    );
+
+    let samples = state
+        .starknetid_db
+        .collection::<Document>(SAMPLES_COLLECTION);
+    tokio::spawn(async move {
+        let _ = samples.insert_one(sample, None).await;
+    });
+    Response::from_parts(parts, boxed(Full::from(response_body)))
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use mongodb::{
+    bson::{doc, Document},
+    IndexModel,
+};
+use serde::Serialize;
+
+use crate::{
+    models::AppState,
+    normalization::{find_transliterated, get_domain_skeleton},
+    pagination::{get_sort, DOMAIN_ORDER},
+    storage::{FindSpec, Storage},
+};
+
+// sorts after every character a domain can contain
+const PREFIX_END: char = '\u{10ffff}';
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DomainMatch {
+    pub domain: String,
+    pub id: String,
+    pub owner: Option<String>,
+    pub expiry: Option<i64>,
+}
+
+/// Filter of the live domains starting with `prefix`, as a range so the domain index is
+/// used, `after` resumes past the last domain of the previous page
+pub fn get_prefix_filter(prefix: &str, after: Option<&str>) -> Document {
+    let mut range = doc! { "$lt": format!("{}{}", prefix, PREFIX_END) };
+    match after {
+        Some(after) if after >= prefix => range.insert("$gt", after),
+        _ => range.insert("$gte", prefix),
+    };
+    doc! { "domain": range, "_cursor.to": null }
+}
+
+/// Domains starting with `prefix`, or whose latin skeleton does, in alphabetical order, with
+/// the owner of their identity
+pub async fn search_domains(
+    storage: &dyn Storage,
+    prefix: &str,
+    after: Option<&str>,
+    limit: i64,
+) -> Result<Vec<DomainMatch>> {
+    let mut domains = storage
+        .find(
+            "domains",
+            get_prefix_filter(prefix, after),
+            FindSpec {
+                sort: Some(get_sort(DOMAIN_ORDER)),
+                skip: 0,
+                limit: Some(limit),
+            },
+        )
+        .await?;
+    let skeleton = get_domain_skeleton(prefix);
+    if !skeleton.is_empty() {
+        let found: Vec<&str> = domains
+            .iter()
+            .filter_map(|doc| doc.get_str("domain").ok())
+            .collect();
+        let transliterated: Vec<String> = find_transliterated(storage, &skeleton, after, limit)
+            .await?
+            .into_iter()
+            .filter(|domain| !found.contains(&domain.as_str()))
+            .collect();
+        if !transliterated.is_empty() {
+            domains.extend(
+                storage
+                    .find(
+                        "domains",
+                        doc! { "domain": { "$in": transliterated }, "_cursor.to": null },
+                        FindSpec::default(),
+                    )
+                    .await?,
+            );
+            // both pages are in alphabetical order, the merged one keeps the first of them
+            domains.sort_by(|a, b| {
+                a.get_str("domain")
+                    .unwrap_or_default()
+                    .cmp(b.get_str("domain").unwrap_or_default())
+            });
+            domains.truncate(limit.max(0) as usize);
+        }
+    }
+    let ids: Vec<String> = domains
+        .iter()
+        .filter_map(|doc| doc.get_str("id").ok().map(String::from))
+        .collect();
+    let owners: HashMap<String, String> = storage
+        .find(
+            "id_owners",
+            doc! { "id": { "$in": ids }, "_cursor.to": null },
+            FindSpec::default(),
+        )
+        .await?
+        .iter()
+        .filter_map(|doc| {
+            Some((
+                doc.get_str("id").ok()?.to_string(),
+                doc.get_str("owner").ok()?.to_string(),
+            ))
+        })
+        .collect();
+    Ok(domains
+        .iter()
+        .filter_map(|doc| {
+            let id = doc.get_str("id").unwrap_or_default();
+            Some(DomainMatch {
+                domain: doc.get_str("domain").ok()?.to_string(),
+                id: id.to_string(),
+                owner: owners.get(id).cloned(),
+                expiry: doc.get_i64("expiry").ok(),
+            })
+        })
+        .collect())
+}
+
+/// Creates the domain index the prefix searches are ranges of, a no-op once it exists
+pub async fn init(state: &AppState) {
+    let index = IndexModel::builder().keys(doc! { "domain": 1 }).build();
+    if let Err(e) = state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .create_index(index, None)
+        .await
+    {
+        state
+            .logger
+            .warning(format!("search: unable to create domain index: {}", e));
+    }
+}
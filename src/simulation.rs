@@ -0,0 +1,166 @@
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use serde_json::{json, Value};
+use starknet::core::types::FieldElement;
+
+use crate::{models::AppState, rpc_queue::Priority, utils::to_hex};
+
+/// Contract call of a multicall, serialized the way wallets expect it
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Call {
+    #[serde(rename = "contractAddress", serialize_with = "serialize_felt")]
+    pub to: FieldElement,
+    pub entrypoint: &'static str,
+    #[serde(skip)]
+    pub selector: FieldElement,
+    #[serde(serialize_with = "serialize_felts")]
+    pub calldata: Vec<FieldElement>,
+}
+
+pub fn serialize_felt<S: serde::Serializer>(felt: &FieldElement, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&to_hex(felt))
+}
+
+fn serialize_felts<S: serde::Serializer>(felts: &[FieldElement], s: S) -> Result<S::Ok, S::Error> {
+    s.collect_seq(felts.iter().map(to_hex))
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FailureReason {
+    InsufficientBalance,
+    InsufficientAllowance,
+    DomainUnavailable,
+    AccountNotDeployed,
+    Unknown,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SimulationFailure {
+    pub reason: FailureReason,
+    // revert reason returned by the node
+    pub message: String,
+}
+
+/// `__execute__` calldata of a cairo 1 account for these calls
+pub fn get_execute_calldata(calls: &[Call]) -> Vec<FieldElement> {
+    let mut calldata = vec![FieldElement::from(calls.len())];
+    for call in calls {
+        calldata.push(call.to);
+        calldata.push(call.selector);
+        calldata.push(FieldElement::from(call.calldata.len()));
+        calldata.extend_from_slice(&call.calldata);
+    }
+    calldata
+}
+
+/// Maps a revert reason of the erc20 or naming contracts to an actionable reason
+pub fn classify_revert(message: &str) -> FailureReason {
+    let message = message.to_lowercase();
+    if message.contains("insufficient balance") || message.contains("u256_sub overflow") {
+        FailureReason::InsufficientBalance
+    } else if message.contains("insufficient allowance") {
+        FailureReason::InsufficientAllowance
+    } else if message.contains("unavailable")
+        || message.contains("not available")
+        || message.contains("unexpired")
+    {
+        FailureReason::DomainUnavailable
+    } else if message.contains("contract not found") {
+        FailureReason::AccountNotDeployed
+    } else {
+        FailureReason::Unknown
+    }
+}
+
+/// Raw json-rpc call through the rpc queue, for the methods the provider doesn't type
+pub async fn rpc_request(state: &AppState, method: &'static str, params: Value) -> Result<Value> {
+    let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+    let request = reqwest::Client::new()
+        .post(&state.conf.variables.rpc_url)
+        .json(&body)
+        .send();
+    let response = state
+        .rpc_queue
+        .run(Priority::Interactive, method, request)
+        .await?
+        .json::<Value>()
+        .await?;
+    if let Some(error) = response.get("error") {
+        // execution errors come with the revert reason in their data
+        let data = error
+            .get("data")
+            .map(|data| match data.get("execution_error") {
+                Some(Value::String(reason)) => reason.clone(),
+                _ => data.to_string(),
+            });
+        let message = error["message"].as_str().unwrap_or_default();
+        return Err(anyhow!(
+            "{}",
+            data.map_or(message.to_string(), |data| format!("{}: {}", message, data))
+        ));
+    }
+    Ok(response["result"].clone())
+}
+
+/// Revert reason of a simulated invoke, if its execution failed
+pub fn get_revert_reason(simulation: &Value) -> Option<String> {
+    let trace = &simulation[0]["transaction_trace"];
+    trace["execute_invocation"]["revert_reason"]
+        .as_str()
+        .or_else(|| trace["revert_reason"].as_str())
+        .map(|reason| reason.to_string())
+}
+
+/// Simulates the multicall sent by `sender` without validation nor fee charge, so the
+/// unsigned calldata can be checked for balance, allowance or availability issues
+pub async fn simulate(
+    state: &AppState,
+    sender: &FieldElement,
+    calls: &[Call],
+) -> Result<Option<SimulationFailure>> {
+    let failure = |message: String| SimulationFailure {
+        reason: classify_revert(&message),
+        message,
+    };
+    let nonce = match rpc_request(
+        state,
+        "starknet_getNonce",
+        json!({ "block_id": "latest", "contract_address": to_hex(sender) }),
+    )
+    .await
+    {
+        Ok(nonce) => nonce,
+        Err(e) if classify_revert(&e.to_string()) == FailureReason::AccountNotDeployed => {
+            return Ok(Some(failure(e.to_string())))
+        }
+        Err(e) => return Err(e),
+    };
+    let transaction = json!({
+        "type": "INVOKE",
+        "version": "0x1",
+        "sender_address": to_hex(sender),
+        "calldata": get_execute_calldata(calls).iter().map(to_hex).collect::<Vec<_>>(),
+        "max_fee": "0x0",
+        "signature": [],
+        "nonce": nonce,
+    });
+    match rpc_request(
+        state,
+        "starknet_simulateTransactions",
+        json!({
+            "block_id": "latest",
+            "transactions": [transaction],
+            "simulation_flags": ["SKIP_VALIDATE", "SKIP_FEE_CHARGE"],
+        }),
+    )
+    .await
+    {
+        Ok(simulation) => Ok(get_revert_reason(&simulation).map(failure)),
+        // nodes reject transactions reverting during the simulation with an execution error
+        Err(e) if e.to_string().to_lowercase().contains("execution") => {
+            Ok(Some(failure(e.to_string())))
+        }
+        Err(e) => Err(e),
+    }
+}
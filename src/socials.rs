@@ -0,0 +1,166 @@
+use anyhow::{anyhow, Context, Result};
+use mongodb::bson::doc;
+use reqwest::{Client, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use starknet::{core::types::FieldElement, macros::short_string};
+
+use crate::{
+    config::{Config, Contracts},
+    storage::{FindSpec, Storage},
+    utils::to_hex,
+};
+
+// identities a social id can be verified by, more would be a scraping tool
+pub const MAX_IDENTITIES: i64 = 20;
+
+/// Social network of the handles, the verifiers store the numeric user id of the account
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SocialType {
+    Twitter,
+    Discord,
+    Github,
+}
+
+impl SocialType {
+    /// Verifier data field of the network
+    pub fn field(&self) -> FieldElement {
+        match self {
+            SocialType::Twitter => short_string!("twitter"),
+            SocialType::Discord => short_string!("discord"),
+            SocialType::Github => short_string!("github"),
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct SocialIdentity {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domain: Option<String>,
+}
+
+/// Handle without the `@` it is often written with, None when it can't be one
+pub fn normalize_handle(handle: &str) -> Option<String> {
+    let handle = handle.trim().trim_start_matches('@');
+    let valid = !handle.is_empty()
+        && handle.len() <= 64
+        && handle
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    valid.then(|| handle.to_string())
+}
+
+/// Numeric user ids are what the verifiers store, they are used as they are
+pub fn parse_social_id(handle: &str) -> Option<FieldElement> {
+    match handle.chars().all(|c| c.is_ascii_digit()) {
+        true => FieldElement::from_dec_str(handle).ok(),
+        false => None,
+    }
+}
+
+async fn get_github_id(config: &Config, handle: &str) -> Result<FieldElement> {
+    let url = format!("{}/users/{}", config.variables.github_api_url, handle);
+    let response = Client::builder()
+        .user_agent("request")
+        .build()
+        .context("Failed to build HTTP client")?
+        .get(&url)
+        .send()
+        .await
+        .context("Failed to send request to GitHub")?;
+    if response.status() != StatusCode::OK {
+        anyhow::bail!("GitHub API returned non-OK status: {}", response.status());
+    }
+    let json: Value = response.json().await?;
+    json.get("id")
+        .and_then(Value::as_u64)
+        .map(FieldElement::from)
+        .ok_or_else(|| anyhow!("Failed to extract the user id"))
+}
+
+async fn get_twitter_id(config: &Config, handle: &str) -> Result<FieldElement> {
+    let response = Client::new()
+        .get(format!("{}/get-user", config.variables.twitter_api_url))
+        .header("X-RapidAPI-Key", config.variables.twitter_api_key.clone())
+        .header("X-RapidAPI-Host", "twttrapi.p.rapidapi.com")
+        .query(&[("username", handle)])
+        .send()
+        .await?;
+    if response.status() != StatusCode::OK {
+        anyhow::bail!("Twitter API returned non-OK status: {}", response.status());
+    }
+    let json: Value = response.json().await?;
+    json.get("data")
+        .and_then(|data| data.get("user_result"))
+        .and_then(|user_result| user_result.get("result"))
+        .and_then(|result| result.get("rest_id"))
+        .and_then(Value::as_str)
+        .and_then(|id| FieldElement::from_dec_str(id).ok())
+        .ok_or_else(|| anyhow!("Failed to extract the user id"))
+}
+
+/// User id of a handle, asked to the network unless it is already numeric
+pub async fn resolve_social_id(
+    config: &Config,
+    social_type: SocialType,
+    handle: &str,
+) -> Result<FieldElement> {
+    if let Some(id) = parse_social_id(handle) {
+        return Ok(id);
+    }
+    match social_type {
+        SocialType::Github => get_github_id(config, handle).await,
+        SocialType::Twitter => get_twitter_id(config, handle).await,
+        // bots can't search the users by name
+        SocialType::Discord => Err(anyhow!("Discord accounts are looked up by their user id")),
+    }
+}
+
+/// Identities the social id is verified on by the social verifiers, with their main domain
+pub async fn find_identities(
+    storage: &dyn Storage,
+    contracts: &Contracts,
+    social_type: SocialType,
+    social_id: &FieldElement,
+) -> Result<Vec<SocialIdentity>> {
+    let verifiers: Vec<String> = contracts
+        .verifiers
+        .iter()
+        .chain([&contracts.old_verifier])
+        .map(to_hex)
+        .collect();
+    let docs = storage
+        .find(
+            "id_verifier_data",
+            doc! {
+                "verifier": { "$in": verifiers },
+                "field": to_hex(&social_type.field()),
+                "data": to_hex(social_id),
+                "_cursor.to": null,
+            },
+            FindSpec {
+                sort: Some(doc! { "id": 1 }),
+                limit: Some(MAX_IDENTITIES),
+                ..Default::default()
+            },
+        )
+        .await?;
+    let mut ids: Vec<String> = docs
+        .iter()
+        .filter_map(|doc| doc.get_str("id").ok().map(String::from))
+        .collect();
+    // several verifiers can have written the same identity
+    ids.dedup();
+
+    let mut identities = vec![];
+    for id in ids {
+        let domain = storage
+            .find_one("domains", doc! { "id": &id, "_cursor.to": null })
+            .await?
+            .and_then(|doc| doc.get_str("domain").ok().map(String::from));
+        identities.push(SocialIdentity { id, domain });
+    }
+    Ok(identities)
+}
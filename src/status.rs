@@ -0,0 +1,120 @@
+use anyhow::{anyhow, Result};
+use mongodb::bson::doc;
+use reqwest::Url;
+use serde::Serialize;
+use starknet::{
+    core::types::{BlockId, BlockTag, MaybePendingBlockWithTxHashes},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
+
+use crate::{models::AppState, rpc_queue::Priority, ws::feed::get_latest_block};
+
+/// Last block the indexer wrote compared to the chain head
+#[derive(Serialize, Debug, Clone, Default, PartialEq)]
+pub struct IndexerLag {
+    pub indexed_block: Option<i64>,
+    pub head_block: Option<u64>,
+    pub lag_blocks: Option<u64>,
+    // seconds between the indexed block and the head, 0 on a quiet chain
+    pub lag_secs: Option<i64>,
+    // the head couldn't be read, the lag is unknown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rpc_error: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct CacheStats {
+    pub entries: usize,
+    pub max_entries: usize,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Status {
+    pub healthy: bool,
+    pub database: bool,
+    pub indexer: IndexerLag,
+    pub response_cache: CacheStats,
+}
+
+/// A replica is evicted when its database is down, it has nothing indexed or the indexer is
+/// too far behind. An unknown lag isn't held against it, the rpc is shared by every replica
+pub fn is_healthy(database: bool, indexer: &IndexerLag, max_lag_secs: i64) -> bool {
+    database
+        && indexer.indexed_block.is_some()
+        && indexer.lag_secs.map_or(true, |lag| lag <= max_lag_secs)
+}
+
+async fn get_block(
+    state: &AppState,
+    provider: &JsonRpcClient<HttpTransport>,
+    block: BlockId,
+) -> Result<(u64, i64)> {
+    let call = provider.get_block_with_tx_hashes(block);
+    match state
+        .rpc_queue
+        .run(Priority::Interactive, "starknet_getBlockWithTxHashes", call)
+        .await
+        .map_err(|e| anyhow!("Failed to fetch block: {}", e))?
+    {
+        MaybePendingBlockWithTxHashes::Block(block) => {
+            Ok((block.block_number, block.timestamp as i64))
+        }
+        MaybePendingBlockWithTxHashes::PendingBlock(_) => Err(anyhow!("Block is pending")),
+    }
+}
+
+async fn get_indexer_lag(state: &AppState, indexed_block: Option<i64>) -> Result<IndexerLag> {
+    let provider = JsonRpcClient::new(HttpTransport::new(Url::parse(
+        &state.conf.variables.rpc_url,
+    )?));
+    let (head_block, head_timestamp) =
+        get_block(state, &provider, BlockId::Tag(BlockTag::Latest)).await?;
+    let mut lag = IndexerLag {
+        indexed_block,
+        head_block: Some(head_block),
+        ..Default::default()
+    };
+    if let Some(indexed) = indexed_block {
+        let indexed = indexed.max(0) as u64;
+        lag.lag_blocks = Some(head_block.saturating_sub(indexed));
+        lag.lag_secs = Some(if indexed >= head_block {
+            0
+        } else {
+            let (_, timestamp) = get_block(state, &provider, BlockId::Number(indexed)).await?;
+            (head_timestamp - timestamp).max(0)
+        });
+    }
+    Ok(lag)
+}
+
+/// Database, indexer and cache state of the replica, for the load balancers
+pub async fn get_status(state: &AppState) -> Status {
+    let database = state
+        .starknetid_db
+        .run_command(doc! { "ping": 1 }, None)
+        .await
+        .is_ok();
+    let indexed_block = match database {
+        true => get_latest_block(state.storage.as_ref())
+            .await
+            .ok()
+            .flatten(),
+        false => None,
+    };
+    let indexer = get_indexer_lag(state, indexed_block)
+        .await
+        .unwrap_or_else(|e| IndexerLag {
+            indexed_block,
+            rpc_error: Some(e.to_string()),
+            ..Default::default()
+        });
+    Status {
+        healthy: is_healthy(database, &indexer, state.conf.status.max_lag_secs),
+        database,
+        indexer,
+        response_cache: CacheStats {
+            entries: state.response_cache.len(),
+            max_entries: state.conf.response_cache.max_entries,
+        },
+    }
+}
@@ -0,0 +1,239 @@
+use std::{cmp::Ordering, collections::HashMap, sync::Mutex};
+
+use anyhow::Result;
+use axum::async_trait;
+use mongodb::bson::{Bson, Document};
+use regex::Regex;
+
+use super::{FindSpec, Storage};
+
+/// Storage keeping the collections in memory, for tests
+#[derive(Default)]
+pub struct MemoryStorage {
+    collections: Mutex<HashMap<String, Vec<Document>>>,
+}
+
+// value at a dotted path such as "_cursor.to"
+fn get_path<'a>(doc: &'a Document, path: &str) -> Option<&'a Bson> {
+    let mut parts = path.split('.');
+    let mut value = doc.get(parts.next()?)?;
+    for part in parts {
+        value = value.as_document()?.get(part)?;
+    }
+    Some(value)
+}
+
+fn as_number(value: &Bson) -> Option<f64> {
+    match value {
+        Bson::Int32(value) => Some(*value as f64),
+        Bson::Int64(value) => Some(*value as f64),
+        Bson::Double(value) => Some(*value),
+        _ => None,
+    }
+}
+
+fn compare(a: &Bson, b: &Bson) -> Option<Ordering> {
+    match (as_number(a), as_number(b)) {
+        (Some(a), Some(b)) => a.partial_cmp(&b),
+        _ => match (a, b) {
+            (Bson::String(a), Bson::String(b)) => Some(a.cmp(b)),
+            (Bson::Boolean(a), Bson::Boolean(b)) => Some(a.cmp(b)),
+            (Bson::ObjectId(a), Bson::ObjectId(b)) => Some(a.cmp(b)),
+            (Bson::Null, Bson::Null) => Some(Ordering::Equal),
+            _ => None,
+        },
+    }
+}
+
+// null matches both null and missing fields, like mongo
+fn equals(value: Option<&Bson>, expected: &Bson) -> bool {
+    match (value, expected) {
+        (None, Bson::Null) => true,
+        (None, _) => false,
+        (Some(value), expected) => {
+            compare(value, expected) == Some(Ordering::Equal) || value == expected
+        }
+    }
+}
+
+fn is_operator_doc(condition: &Bson) -> bool {
+    matches!(condition, Bson::Document(doc) if !doc.is_empty() && doc.keys().all(|key| key.starts_with('$')))
+}
+
+fn matches_condition(value: Option<&Bson>, condition: &Bson) -> bool {
+    let operators = match condition {
+        Bson::Document(operators) if is_operator_doc(condition) => operators,
+        _ => return equals(value, condition),
+    };
+    operators.iter().all(|(operator, operand)| {
+        let ordering = value.and_then(|value| compare(value, operand));
+        match operator.as_str() {
+            "$eq" => equals(value, operand),
+            "$ne" => !equals(value, operand),
+            "$gt" => ordering == Some(Ordering::Greater),
+            "$gte" => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+            "$lt" => ordering == Some(Ordering::Less),
+            "$lte" => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            "$in" => operand
+                .as_array()
+                .is_some_and(|values| values.iter().any(|v| equals(value, v))),
+            "$nin" => !operand
+                .as_array()
+                .is_some_and(|values| values.iter().any(|v| equals(value, v))),
+            "$exists" => value.is_some() == operand.as_bool().unwrap_or(true),
+            "$regex" => match (value.and_then(Bson::as_str), operand.as_str()) {
+                (Some(value), Some(pattern)) => {
+                    Regex::new(pattern).is_ok_and(|regex| regex.is_match(value))
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    })
+}
+
+/// Evaluates a mongo filter against a document
+fn matches_filter(doc: &Document, filter: &Document) -> bool {
+    filter.iter().all(|(key, condition)| match key.as_str() {
+        "$or" => condition.as_array().is_some_and(|filters| {
+            filters
+                .iter()
+                .filter_map(Bson::as_document)
+                .any(|filter| matches_filter(doc, filter))
+        }),
+        "$and" => condition.as_array().is_some_and(|filters| {
+            filters
+                .iter()
+                .filter_map(Bson::as_document)
+                .all(|filter| matches_filter(doc, filter))
+        }),
+        path => matches_condition(get_path(doc, path), condition),
+    })
+}
+
+fn sort_documents(documents: &mut [Document], sort: &Document) {
+    documents.sort_by(|a, b| {
+        for (key, direction) in sort {
+            let ordering = match (get_path(a, key), get_path(b, key)) {
+                (Some(a), Some(b)) => compare(a, b).unwrap_or(Ordering::Equal),
+                (None, Some(_)) => Ordering::Less,
+                (Some(_), None) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+            let ordering = if as_number(direction).unwrap_or(1.0) < 0.0 {
+                ordering.reverse()
+            } else {
+                ordering
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        Ordering::Equal
+    });
+}
+
+impl MemoryStorage {
+    fn with_collection<T>(&self, collection: &str, f: impl FnOnce(&mut Vec<Document>) -> T) -> T {
+        let mut collections = self.collections.lock().unwrap();
+        f(collections.entry(collection.to_string()).or_default())
+    }
+}
+
+#[async_trait]
+impl Storage for MemoryStorage {
+    async fn find_one(&self, collection: &str, filter: Document) -> Result<Option<Document>> {
+        Ok(self.with_collection(collection, |documents| {
+            documents
+                .iter()
+                .find(|doc| matches_filter(doc, &filter))
+                .cloned()
+        }))
+    }
+
+    async fn find(
+        &self,
+        collection: &str,
+        filter: Document,
+        spec: FindSpec,
+    ) -> Result<Vec<Document>> {
+        let mut found: Vec<Document> = self.with_collection(collection, |documents| {
+            documents
+                .iter()
+                .filter(|doc| matches_filter(doc, &filter))
+                .cloned()
+                .collect()
+        });
+        if let Some(sort) = &spec.sort {
+            sort_documents(&mut found, sort);
+        }
+        let found = found.into_iter().skip(spec.skip as usize);
+        Ok(match spec.limit {
+            // a negative limit means the same as its absolute value for find
+            Some(limit) if limit != 0 => found.take(limit.unsigned_abs() as usize).collect(),
+            _ => found.collect(),
+        })
+    }
+
+    async fn count(&self, collection: &str, filter: Document) -> Result<u64> {
+        Ok(self.with_collection(collection, |documents| {
+            documents
+                .iter()
+                .filter(|doc| matches_filter(doc, &filter))
+                .count() as u64
+        }))
+    }
+
+    async fn insert_one(&self, collection: &str, document: Document) -> Result<()> {
+        self.with_collection(collection, |documents| documents.push(document));
+        Ok(())
+    }
+
+    async fn replace_one(
+        &self,
+        collection: &str,
+        filter: Document,
+        document: Document,
+    ) -> Result<()> {
+        self.with_collection(collection, |documents| {
+            match documents
+                .iter_mut()
+                .find(|doc| matches_filter(doc, &filter))
+            {
+                Some(existing) => *existing = document,
+                None => documents.push(document),
+            }
+        });
+        Ok(())
+    }
+
+    async fn upsert_one(&self, collection: &str, filter: Document, fields: Document) -> Result<()> {
+        self.with_collection(collection, |documents| {
+            match documents
+                .iter_mut()
+                .find(|doc| matches_filter(doc, &filter))
+            {
+                Some(existing) => existing.extend(fields),
+                None => {
+                    let mut document: Document = filter
+                        .into_iter()
+                        .filter(|(key, condition)| {
+                            !key.starts_with('$') && !is_operator_doc(condition)
+                        })
+                        .collect();
+                    document.extend(fields);
+                    documents.push(document);
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn delete_many(&self, collection: &str, filter: Document) -> Result<u64> {
+        Ok(self.with_collection(collection, |documents| {
+            let before = documents.len();
+            documents.retain(|doc| !matches_filter(doc, &filter));
+            (before - documents.len()) as u64
+        }))
+    }
+}
@@ -0,0 +1,51 @@
+mod memory;
+mod mongo;
+
+use anyhow::Result;
+use axum::async_trait;
+use mongodb::bson::Document;
+
+pub use memory::MemoryStorage;
+pub use mongo::MongoStorage;
+
+/// Sort, skip and limit applied by `Storage::find`
+#[derive(Default, Debug, Clone)]
+pub struct FindSpec {
+    // e.g. { "added_at": 1 }, keys are compared in order
+    pub sort: Option<Document>,
+    pub skip: u64,
+    pub limit: Option<i64>,
+}
+
+/// Document store operations used by the handlers. Filters use the mongo query language,
+/// the in memory implementation supports equality, dotted paths, `$or`, `$and`, `$regex` and
+/// the comparison operators.
+#[async_trait]
+pub trait Storage: Send + Sync {
+    async fn find_one(&self, collection: &str, filter: Document) -> Result<Option<Document>>;
+
+    async fn find(
+        &self,
+        collection: &str,
+        filter: Document,
+        spec: FindSpec,
+    ) -> Result<Vec<Document>>;
+
+    async fn count(&self, collection: &str, filter: Document) -> Result<u64>;
+
+    async fn insert_one(&self, collection: &str, document: Document) -> Result<()>;
+
+    /// Replaces the first matching document, or inserts it
+    async fn replace_one(
+        &self,
+        collection: &str,
+        filter: Document,
+        document: Document,
+    ) -> Result<()>;
+
+    /// `$set` of `fields` on the first matching document, which is created from the
+    /// equality conditions of the filter if there is none
+    async fn upsert_one(&self, collection: &str, filter: Document, fields: Document) -> Result<()>;
+
+    async fn delete_many(&self, collection: &str, filter: Document) -> Result<u64>;
+}
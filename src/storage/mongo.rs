@@ -0,0 +1,106 @@
+use anyhow::Result;
+use axum::async_trait;
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::{FindOptions, ReplaceOptions, UpdateOptions},
+    Database,
+};
+
+use super::{FindSpec, Storage};
+
+/// Default storage, backed by the starknetid database
+pub struct MongoStorage {
+    db: Database,
+}
+
+impl MongoStorage {
+    pub fn new(db: Database) -> Self {
+        MongoStorage { db }
+    }
+}
+
+#[async_trait]
+impl Storage for MongoStorage {
+    async fn find_one(&self, collection: &str, filter: Document) -> Result<Option<Document>> {
+        Ok(self
+            .db
+            .collection::<Document>(collection)
+            .find_one(filter, None)
+            .await?)
+    }
+
+    async fn find(
+        &self,
+        collection: &str,
+        filter: Document,
+        spec: FindSpec,
+    ) -> Result<Vec<Document>> {
+        let options = FindOptions::builder()
+            .sort(spec.sort)
+            .skip(spec.skip)
+            .limit(spec.limit)
+            .build();
+        Ok(self
+            .db
+            .collection::<Document>(collection)
+            .find(filter, options)
+            .await?
+            .try_collect()
+            .await?)
+    }
+
+    async fn count(&self, collection: &str, filter: Document) -> Result<u64> {
+        Ok(self
+            .db
+            .collection::<Document>(collection)
+            .count_documents(filter, None)
+            .await?)
+    }
+
+    async fn insert_one(&self, collection: &str, document: Document) -> Result<()> {
+        self.db
+            .collection::<Document>(collection)
+            .insert_one(document, None)
+            .await?;
+        Ok(())
+    }
+
+    async fn replace_one(
+        &self,
+        collection: &str,
+        filter: Document,
+        document: Document,
+    ) -> Result<()> {
+        self.db
+            .collection::<Document>(collection)
+            .replace_one(
+                filter,
+                document,
+                ReplaceOptions::builder().upsert(true).build(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn upsert_one(&self, collection: &str, filter: Document, fields: Document) -> Result<()> {
+        self.db
+            .collection::<Document>(collection)
+            .update_one(
+                filter,
+                doc! { "$set": fields },
+                UpdateOptions::builder().upsert(true).build(),
+            )
+            .await?;
+        Ok(())
+    }
+
+    async fn delete_many(&self, collection: &str, filter: Document) -> Result<u64> {
+        Ok(self
+            .db
+            .collection::<Document>(collection)
+            .delete_many(filter, None)
+            .await?
+            .deleted_count)
+    }
+}
@@ -0,0 +1,116 @@
+use std::{fmt::Display, io};
+
+use axum::{
+    body::{Bytes, StreamBody},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use futures::{stream, Stream, StreamExt};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Document a streamed array is written in
+pub enum Envelope {
+    Array,
+    // object holding the array under `field`, then the `extra` fields
+    Object {
+        field: &'static str,
+        extra: Map<String, Value>,
+    },
+}
+
+impl Envelope {
+    pub fn object(field: &'static str) -> Self {
+        Envelope::Object {
+            field,
+            extra: Map::new(),
+        }
+    }
+
+    fn get_bounds(&self) -> (String, String) {
+        match self {
+            Envelope::Array => ("[".to_string(), "]".to_string()),
+            Envelope::Object { field, extra } => {
+                let mut close = "]".to_string();
+                for (key, value) in extra {
+                    close.push_str(&format!(",{}:{}", Value::from(key.as_str()), value));
+                }
+                close.push('}');
+                (format!("{{{}:[", Value::from(*field)), close)
+            }
+        }
+    }
+}
+
+/// Json array serialized one element at a time while `items` yields them, so only one
+/// element is buffered. The status is sent before the first element so an error of
+/// `items` aborts the body, leaving the client with an invalid document
+pub fn stream_json_array<T, E, S>(
+    envelope: &Envelope,
+    items: S,
+) -> impl Stream<Item = io::Result<Bytes>>
+where
+    T: Serialize,
+    E: Display,
+    S: Stream<Item = Result<T, E>>,
+{
+    let (open, close) = envelope.get_bounds();
+    let elements = items.enumerate().map(|(index, item)| -> io::Result<Bytes> {
+        let item = item.map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        let mut chunk = if index == 0 { Vec::new() } else { vec![b','] };
+        serde_json::to_writer(&mut chunk, &item)?;
+        Ok(Bytes::from(chunk))
+    });
+    stream::once(async move { Ok(Bytes::from(open)) })
+        .chain(elements)
+        .chain(stream::once(async move { Ok(Bytes::from(close)) }))
+}
+
+pub fn stream_json_response<T, E, S>(
+    mut headers: HeaderMap,
+    envelope: Envelope,
+    items: S,
+) -> Response
+where
+    T: Serialize + Send + 'static,
+    E: Display + Send + 'static,
+    S: Stream<Item = Result<T, E>> + Send + 'static,
+{
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+    (
+        StatusCode::OK,
+        headers,
+        StreamBody::new(stream_json_array(&envelope, items)),
+    )
+        .into_response()
+}
+
+/// Text lines written as `lines` yields them, after the `header` line if any, e.g. csv or
+/// json lines. Like the arrays, an error of `lines` aborts the body
+pub fn stream_lines_response<E, S>(
+    mut headers: HeaderMap,
+    content_type: &'static str,
+    header: Option<&'static str>,
+    lines: S,
+) -> Response
+where
+    E: Display + Send + 'static,
+    S: Stream<Item = Result<String, E>> + Send + 'static,
+{
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    let header = stream::iter(header.map(|header| Ok(Bytes::from(format!("{}\n", header)))));
+    let lines = lines.map(|line| -> io::Result<Bytes> {
+        let mut line = line.map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
+        line.push('\n');
+        Ok(Bytes::from(line))
+    });
+    (
+        StatusCode::OK,
+        headers,
+        StreamBody::new(header.chain(lines)),
+    )
+        .into_response()
+}
@@ -0,0 +1,185 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+};
+
+use anyhow::Result;
+use mongodb::bson::doc;
+use starknet_id::encode;
+
+use crate::{
+    locale::Locale, logger::Logger, models::AppState, normalization::get_skeleton,
+    profanity::ProfanityFilter, storage::FindSpec,
+};
+
+// shorter words aren't replaced inside a label, "go" would match too many names
+const MIN_PART_LEN: usize = 3;
+// candidates checked against the registered domains for each suggestion returned
+const CANDIDATES_PER_SUGGESTION: usize = 4;
+
+/// Synonym groups of a locale, each word maps to the other words of its groups
+#[derive(Debug, Clone, Default)]
+pub struct Dictionary {
+    // sorted so the suggestions don't depend on the file order
+    synonyms: BTreeMap<String, Vec<String>>,
+}
+
+impl Dictionary {
+    /// One group of comma separated synonyms per line, `#` starts a comment. The words are
+    /// kept as their latin skeleton, "café" is suggested as "cafe"
+    pub fn parse(data: &str) -> Self {
+        let mut synonyms: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for line in data.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let words: Vec<String> = line
+                .split(',')
+                .map(|word| get_skeleton(word.trim()))
+                .filter(|word| !word.is_empty())
+                .collect();
+            for word in &words {
+                let entry = synonyms.entry(word.clone()).or_default();
+                for synonym in &words {
+                    if synonym != word && !entry.contains(synonym) {
+                        entry.push(synonym.clone());
+                    }
+                }
+            }
+        }
+        Dictionary { synonyms }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.synonyms.is_empty()
+    }
+
+    /// Alternatives of a root label: the synonyms of the whole label first, then the label
+    /// with a dictionary word it starts or ends with replaced, "coffeeshop" -> "cafeshop"
+    pub fn get_candidates(&self, label: &str, profanity: &ProfanityFilter) -> Vec<String> {
+        let skeleton = get_skeleton(label);
+        let label = skeleton.as_str();
+        let mut candidates: Vec<String> = self.synonyms.get(label).cloned().unwrap_or_default();
+        // the longest part first, "sunset" is a better match than "sun"
+        let mut parts: Vec<(&String, &Vec<String>)> = self
+            .synonyms
+            .iter()
+            .filter(|(word, _)| word.len() >= MIN_PART_LEN && word.len() < label.len())
+            .collect();
+        parts.sort_by_key(|(word, _)| std::cmp::Reverse(word.len()));
+        for (word, synonyms) in parts {
+            if let Some(rest) = label.strip_prefix(word.as_str()) {
+                candidates.extend(
+                    synonyms
+                        .iter()
+                        .map(|synonym| format!("{}{}", synonym, rest)),
+                );
+            }
+            if let Some(rest) = label.strip_suffix(word.as_str()) {
+                candidates.extend(
+                    synonyms
+                        .iter()
+                        .map(|synonym| format!("{}{}", rest, synonym)),
+                );
+            }
+        }
+        let mut suggestions: Vec<String> = vec![];
+        for candidate in candidates {
+            if candidate != label
+                && !suggestions.contains(&candidate)
+                && encode(&candidate).is_ok()
+                && profanity.is_allowed(&candidate)
+            {
+                suggestions.push(candidate);
+            }
+        }
+        suggestions
+    }
+}
+
+/// Dictionaries of the locales, the english one is used for the other locales
+#[derive(Debug, Clone, Default)]
+pub struct SuggestionDictionaries {
+    pub dictionaries: HashMap<Locale, Dictionary>,
+}
+
+impl SuggestionDictionaries {
+    pub fn get(&self, locale: Option<Locale>) -> Option<&Dictionary> {
+        locale
+            .and_then(|locale| self.dictionaries.get(&locale))
+            .or_else(|| self.dictionaries.get(&Locale::En))
+    }
+}
+
+pub fn load_dictionaries(
+    paths: &HashMap<String, String>,
+    logger: &Logger,
+) -> SuggestionDictionaries {
+    let mut dictionaries = HashMap::new();
+    for (tag, path) in paths {
+        let locale = match Locale::from_tag(tag) {
+            Some(locale) => locale,
+            None => {
+                logger.warning(format!("Unsupported suggestion dictionary locale: {}", tag));
+                continue;
+            }
+        };
+        match fs::read_to_string(path) {
+            Ok(data) => {
+                dictionaries.insert(locale, Dictionary::parse(&data));
+            }
+            Err(e) => logger.warning(format!(
+                "Unable to load suggestion dictionary {}: {}",
+                path, e
+            )),
+        }
+    }
+    SuggestionDictionaries { dictionaries }
+}
+
+/// Free root domains close in meaning to a taken label, in the language of the user
+pub async fn get_suggestions(
+    state: &AppState,
+    label: &str,
+    locale: Option<Locale>,
+) -> Result<Vec<String>> {
+    let conf = &state.conf.suggestions;
+    let candidates: Vec<String> = match state.suggestions.get(locale) {
+        Some(dictionary) => dictionary
+            .get_candidates(label, &state.profanity)
+            .into_iter()
+            .filter(|candidate| !state.conf.availability.reserved.contains(candidate))
+            .take(conf.max_suggestions * CANDIDATES_PER_SUGGESTION)
+            .map(|candidate| format!("{}.stark", candidate))
+            .collect(),
+        None => return Ok(vec![]),
+    };
+    if candidates.is_empty() {
+        return Ok(vec![]);
+    }
+    let registered = state
+        .storage
+        .find(
+            "domains",
+            doc! { "domain": { "$in": candidates.clone() }, "_cursor.to": null },
+            FindSpec::default(),
+        )
+        .await?;
+    let now = chrono::Utc::now().timestamp();
+    let taken: Vec<&str> = registered
+        .iter()
+        .filter(|doc| {
+            state
+                .conf
+                .grace
+                .get_display(doc.get_i64("expiry").ok(), now)
+                .resolves
+        })
+        .filter_map(|doc| doc.get_str("domain").ok())
+        .collect();
+    Ok(candidates
+        .into_iter()
+        .filter(|candidate| !taken.contains(&candidate.as_str()))
+        .take(conf.max_suggestions)
+        .collect())
+}
@@ -0,0 +1,169 @@
+use serde::Serialize;
+use serde_json::{json, Value};
+use starknet::core::types::FieldElement;
+use starknet_id::{decode, encode};
+
+use crate::{
+    contract_claims::get_claim_hash,
+    descriptions::get_description_hash,
+    organizations::get_member_hash,
+    profanity::normalize_label,
+    raffle::get_entrants_hash,
+    transparency::{leaf_hash, merkle_root, TransparencyEntry},
+    utils::{normalize_domain, to_hex},
+};
+
+// covers the basic and extended alphabets, trailing escapes and labels spanning several chunks
+const LABELS: [&str; 7] = [
+    "a",
+    "ben",
+    "vitalik",
+    "starknet-id",
+    "0xdeadbeef",
+    "aaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+    "thisisaveryveryverylongdomainlabel",
+];
+
+const DOMAINS: [&str; 6] = [
+    "ben.stark",
+    "  Ben.STARK. ",
+    "ben%2Estark",
+    "ben%252Estark",
+    "SUB.ben.stark",
+    "%20ben.stark%2E",
+];
+
+const PROFANITY_LABELS: [&str; 4] = ["sh1t", "s-h_i-t", "$@l0pe", "B4D_w0rd"];
+
+#[derive(Serialize)]
+pub struct EncodingVector {
+    pub label: String,
+    pub encoded: String,
+    pub decoded: String,
+}
+
+#[derive(Serialize)]
+pub struct NormalizationVector {
+    pub function: &'static str,
+    pub input: String,
+    pub output: String,
+}
+
+#[derive(Serialize)]
+pub struct HashVector {
+    pub function: &'static str,
+    pub inputs: Value,
+    pub output: String,
+}
+
+/// Vectors computed by the server implementations, SDKs must reproduce them byte for byte
+#[derive(Serialize)]
+pub struct TestVectors {
+    pub encoding: Vec<EncodingVector>,
+    pub normalization: Vec<NormalizationVector>,
+    pub hashing: Vec<HashVector>,
+}
+
+pub fn get_encoding_vectors() -> Vec<EncodingVector> {
+    LABELS
+        .iter()
+        .map(|label| {
+            let encoded = encode(label).unwrap();
+            EncodingVector {
+                label: label.to_string(),
+                encoded: to_hex(&encoded),
+                decoded: decode(encoded),
+            }
+        })
+        .collect()
+}
+
+pub fn get_normalization_vectors() -> Vec<NormalizationVector> {
+    let domains = DOMAINS.iter().map(|input| NormalizationVector {
+        function: "normalize_domain",
+        input: input.to_string(),
+        output: normalize_domain(input),
+    });
+    let labels = PROFANITY_LABELS.iter().map(|input| NormalizationVector {
+        function: "normalize_label",
+        input: input.to_string(),
+        output: normalize_label(input),
+    });
+    domains.chain(labels).collect()
+}
+
+pub fn get_hash_vectors() -> Vec<HashVector> {
+    let contract = FieldElement::from_hex_be(
+        "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7",
+    )
+    .unwrap();
+    let id = FieldElement::from(123456789_u64);
+    let entrants = vec!["0x1".to_string(), "0x3".to_string(), "0x2".to_string()];
+    let entry = TransparencyEntry {
+        kind: "free_domain".to_string(),
+        subject: "ben.stark".to_string(),
+        target: to_hex(&contract),
+        message_hash: to_hex(&id),
+        signature_r: "0x1".to_string(),
+        signature_s: "0x2".to_string(),
+        issued_at: 1700000000,
+    };
+    let leaf = leaf_hash(&entry);
+
+    vec![
+        HashVector {
+            function: "claim_hash",
+            inputs: json!({
+                "contract": to_hex(&contract),
+                "domain": "ben.stark",
+                "timestamp": 1700000000
+            }),
+            output: to_hex(&get_claim_hash(&contract, "ben.stark", 1700000000)),
+        },
+        HashVector {
+            function: "member_hash",
+            inputs: json!({
+                "org": "starknet.stark",
+                "member": "ben.stark",
+                "role": "admin",
+                "title": "",
+                "timestamp": 1700000000
+            }),
+            output: to_hex(&get_member_hash(
+                "starknet.stark",
+                "ben.stark",
+                "admin",
+                "",
+                1700000000,
+            )),
+        },
+        HashVector {
+            function: "description_hash",
+            inputs: json!({ "id": to_hex(&id), "description": "gm", "timestamp": 1700000000 }),
+            output: to_hex(&get_description_hash(&id, "gm", 1700000000)),
+        },
+        HashVector {
+            function: "entrants_hash",
+            inputs: json!({ "entrants": &entrants }),
+            output: get_entrants_hash(&entrants),
+        },
+        HashVector {
+            function: "transparency_leaf_hash",
+            inputs: json!({ "entry": entry }),
+            output: hex::encode(leaf),
+        },
+        HashVector {
+            function: "transparency_merkle_root",
+            inputs: json!({ "leaves": [hex::encode(leaf), hex::encode(leaf), hex::encode(leaf)] }),
+            output: hex::encode(merkle_root(&[leaf, leaf, leaf])),
+        },
+    ]
+}
+
+pub fn get_test_vectors() -> TestVectors {
+    TestVectors {
+        encoding: get_encoding_vectors(),
+        normalization: get_normalization_vectors(),
+        hashing: get_hash_vectors(),
+    }
+}
@@ -0,0 +1,58 @@
+use crate::activity::{get_activity, update_snapshot, Activity};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod activity {
+    use super::*;
+
+    const WINDOW: i64 = 90 * 86400;
+    const NOW: i64 = 1700000000;
+
+    #[test]
+    fn test_first_check_relies_on_renewals() {
+        let address = FieldElement::from(1_u64);
+        let snapshot = update_snapshot(None, &address, &FieldElement::from(12_u64), None, NOW);
+        assert_eq!(snapshot.nonce_changed_at, None);
+        assert_eq!(get_activity(&snapshot, NOW, WINDOW), Activity::Dormant);
+
+        let snapshot = update_snapshot(
+            None,
+            &address,
+            &FieldElement::from(12_u64),
+            Some(NOW - 86400),
+            NOW,
+        );
+        assert_eq!(get_activity(&snapshot, NOW, WINDOW), Activity::Active);
+    }
+
+    #[test]
+    fn test_nonce_changes_mark_activity() {
+        let address = FieldElement::from(1_u64);
+        let first = update_snapshot(None, &address, &FieldElement::from(12_u64), None, NOW);
+        let unchanged = update_snapshot(
+            Some(first.clone()),
+            &address,
+            &FieldElement::from(12_u64),
+            None,
+            NOW + 86400,
+        );
+        assert_eq!(unchanged.nonce_changed_at, None);
+
+        let changed = update_snapshot(
+            Some(first),
+            &address,
+            &FieldElement::from(13_u64),
+            None,
+            NOW + 86400,
+        );
+        assert_eq!(changed.nonce_changed_at, Some(NOW + 86400));
+        assert_eq!(
+            get_activity(&changed, NOW + 86400, WINDOW),
+            Activity::Active
+        );
+        assert_eq!(
+            get_activity(&changed, NOW + 86400 + WINDOW + 1, WINDOW),
+            Activity::Dormant
+        );
+    }
+}
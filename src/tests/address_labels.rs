@@ -0,0 +1,31 @@
+use crate::address_labels::{AddressLabel, AddressLabels};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod address_labels {
+    use super::*;
+
+    fn parse(json: &str) -> Vec<AddressLabel> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_later_datasets_override() {
+        let mut labels = parse(r#"[{"address": "0x1", "label": "Old", "category": "exchange"}]"#);
+        labels.extend(parse(
+            r#"[{"address": "0x01", "label": "New", "category": "exchange"}]"#,
+        ));
+        let labels = AddressLabels::new(labels);
+        assert_eq!(labels.get(&FieldElement::ONE).unwrap().label, "New");
+        assert!(labels.get(&FieldElement::TWO).is_none());
+    }
+
+    #[test]
+    fn test_address_is_not_serialized() {
+        let labels = parse(r#"[{"address": "0x1", "label": "Bridge", "category": "bridge"}]"#);
+        assert_eq!(
+            serde_json::to_string(&labels[0]).unwrap(),
+            r#"{"label":"Bridge","category":"bridge"}"#
+        );
+    }
+}
@@ -0,0 +1,58 @@
+use crate::{
+    addressbook::{get_contact_hash, resolve_contacts},
+    storage::{MemoryStorage, Storage},
+};
+use mongodb::bson::doc;
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod addressbook {
+    use super::*;
+
+    #[test]
+    fn test_contact_hash() {
+        // sha256 of 31 zero bytes followed by 0x01
+        assert_eq!(
+            get_contact_hash(&FieldElement::ONE),
+            "ec4916dd28fc4c10d78e287ca5d9cc51ee1ae73cbfde08c6b37324cbfaac8bc5"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_only_opted_in_contacts_match() {
+        let storage = MemoryStorage::default();
+        for (address, discoverable, domain) in
+            [("0x1", true, "alice.stark"), ("0x2", false, "ben.stark")]
+        {
+            let felt = FieldElement::from_hex_be(address).unwrap();
+            storage
+                .insert_one(
+                    "preferences",
+                    doc! {
+                        "address": address,
+                        "addressbook_discoverable": discoverable,
+                        "contact_hash": get_contact_hash(&felt),
+                    },
+                )
+                .await
+                .unwrap();
+            storage
+                .insert_one(
+                    "domains",
+                    doc! { "domain": domain, "rev_address": address, "_cursor": { "from": 1 } },
+                )
+                .await
+                .unwrap();
+        }
+
+        let hashes = vec![
+            get_contact_hash(&FieldElement::ONE).to_uppercase(),
+            get_contact_hash(&FieldElement::TWO),
+            get_contact_hash(&FieldElement::THREE),
+        ];
+        let matches = resolve_contacts(&storage, &hashes).await.unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].domain, "alice.stark");
+        assert_eq!(matches[0].hash, get_contact_hash(&FieldElement::ONE));
+    }
+}
@@ -0,0 +1,33 @@
+use crate::analytics::{get_create_statement, get_upsert_statement, MIRRORED_TABLES};
+
+#[cfg(test)]
+mod analytics {
+    use super::*;
+
+    #[test]
+    fn test_mirror_statements() {
+        let renewals = MIRRORED_TABLES
+            .iter()
+            .find(|table| table.name == "renewals")
+            .unwrap();
+        assert_eq!(
+            get_create_statement(renewals),
+            "CREATE TABLE IF NOT EXISTS renewals (mongo_id TEXT PRIMARY KEY, domain TEXT, timestamp BIGINT, cursor_to BIGINT)"
+        );
+        assert_eq!(
+            get_upsert_statement(renewals),
+            "INSERT INTO renewals (mongo_id, domain, timestamp, cursor_to) VALUES ($1, $2, $3, $4) \
+            ON CONFLICT (mongo_id) DO UPDATE SET domain = EXCLUDED.domain, timestamp = EXCLUDED.timestamp, cursor_to = EXCLUDED.cursor_to"
+        );
+    }
+
+    #[test]
+    fn test_tables_track_liveness() {
+        for table in MIRRORED_TABLES.iter() {
+            assert!(table
+                .columns
+                .iter()
+                .any(|(path, column, _)| *path == "_cursor.to" && *column == "cursor_to"));
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    auto_renewal::{get_flows, get_renewals_covered},
+    pricing::get_price_per_year,
+    storage::{MemoryStorage, Storage},
+};
+use mongodb::bson::doc;
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod renewals_covered {
+    use super::*;
+
+    #[test]
+    fn test_smallest_amount_is_spent() {
+        assert_eq!(get_renewals_covered(50, 25, 10), 2);
+        assert_eq!(get_renewals_covered(25, 50, 10), 2);
+        assert_eq!(get_renewals_covered(9, 50, 10), 0);
+    }
+
+    #[test]
+    fn test_unlimited_allowance() {
+        assert_eq!(get_renewals_covered(u128::MAX, 30, 10), 3);
+        assert_eq!(get_renewals_covered(u128::MAX, u128::MAX, 1), u64::MAX);
+    }
+
+    #[test]
+    fn test_free_domain() {
+        assert_eq!(get_renewals_covered(50, 50, 0), 0);
+    }
+}
+
+#[cfg(test)]
+mod flows {
+    use super::*;
+
+    fn felt(hex: &str) -> FieldElement {
+        FieldElement::from_hex_be(hex).unwrap()
+    }
+
+    async fn storage() -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        for (collection, renewer, enabled, contract) in [
+            ("auto_renew_flows", "0xa", true, None),
+            // subscribed by a previous owner
+            ("auto_renew_flows", "0xb", true, None),
+            ("auto_renew_flows_altcoins", "0xa", true, Some("0xc1")),
+            ("auto_renew_flows_altcoins", "0xa", false, Some("0xc1")),
+            // contract of a token no longer configured
+            ("auto_renew_flows_altcoins", "0xa", true, Some("0xc2")),
+        ] {
+            let mut doc =
+                doc! { "domain": "soon.stark", "renewer_address": renewer, "enabled": enabled };
+            if let Some(contract) = contract {
+                doc.insert("auto_renew_contract", contract);
+            }
+            storage.insert_one(collection, doc).await.unwrap();
+        }
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_owner_flows() {
+        let storage = storage().await;
+        let altcoins = |contract: &FieldElement| (*contract == felt("0xc1")).then(|| felt("0x57"));
+        let flows = get_flows(
+            &storage,
+            "soon.stark",
+            "0xa",
+            &felt("0xe1"),
+            &felt("0xe7"),
+            altcoins,
+        )
+        .await
+        .unwrap();
+        assert_eq!(flows.len(), 2);
+        assert_eq!(flows[0].renewer, felt("0xa"));
+        assert_eq!(flows[0].contract, felt("0xe1"));
+        assert_eq!(flows[0].token, felt("0xe7"));
+        assert_eq!(flows[0].yearly_price, get_price_per_year(4));
+        assert_eq!(flows[1].contract, felt("0xc1"));
+        assert_eq!(flows[1].token, felt("0x57"));
+        assert_eq!(flows[1].yearly_price, None);
+    }
+
+    #[tokio::test]
+    async fn test_no_subscription() {
+        let storage = storage().await;
+        let flows = get_flows(
+            &storage,
+            "soon.stark",
+            "0xd",
+            &felt("0xe1"),
+            &felt("0xe7"),
+            |_| None,
+        )
+        .await
+        .unwrap();
+        assert!(flows.is_empty());
+    }
+}
@@ -0,0 +1,114 @@
+use crate::{
+    avatars::{decode_data_json, expand_erc1155_id, get_image, split_u256, AvatarUri, NftStandard},
+    config::Config,
+};
+use ethers::types::{H160, U256};
+use serde_json::json;
+use starknet::core::types::FieldElement;
+use std::str::FromStr;
+
+#[cfg(test)]
+mod avatars {
+    use super::*;
+
+    #[test]
+    fn test_parse_eip155() {
+        let uri = "eip155:1/erc721:0xb47e3cd837dDF8e4c57F05d70Ab865de6e193BBB/2430";
+        assert_eq!(
+            AvatarUri::parse(uri).unwrap(),
+            AvatarUri::Eip155 {
+                chain_id: 1,
+                standard: NftStandard::Erc721,
+                contract: H160::from_str("0xb47e3cd837ddf8e4c57f05d70ab865de6e193bbb").unwrap(),
+                token_id: U256::from(2430),
+            }
+        );
+        let uri = "eip155:1/erc1155:0x495f947276749ce646f68ac8c248420045cb7b5e/0x10";
+        assert!(matches!(
+            AvatarUri::parse(uri).unwrap(),
+            AvatarUri::Eip155 {
+                standard: NftStandard::Erc1155,
+                token_id,
+                ..
+            } if token_id == U256::from(16)
+        ));
+        for uri in [
+            "eip155:1/erc20:0x495f947276749ce646f68ac8c248420045cb7b5e/1",
+            "eip155:1/erc721:0x495f/1",
+            "eip155:mainnet/erc721:0x495f947276749ce646f68ac8c248420045cb7b5e/1",
+            "eip155:1/erc721:0x495f947276749ce646f68ac8c248420045cb7b5e",
+        ] {
+            assert!(AvatarUri::parse(uri).is_err(), "{}", uri);
+        }
+    }
+
+    #[test]
+    fn test_parse_starknet_and_urls() {
+        assert_eq!(
+            AvatarUri::parse("nft:0x0123/42").unwrap(),
+            AvatarUri::Starknet {
+                contract: FieldElement::from(0x123_u64),
+                token_id: U256::from(42),
+            }
+        );
+        assert!(AvatarUri::parse("nft:0x0123").is_err());
+        assert!(AvatarUri::parse("nft:0x0123/forty").is_err());
+        for uri in [
+            "https://example.com/a.png",
+            "ipfs://Qm/a.png",
+            "data:image/png;base64,AA",
+        ] {
+            assert_eq!(
+                AvatarUri::parse(uri).unwrap(),
+                AvatarUri::Url(uri.to_string())
+            );
+        }
+        assert!(AvatarUri::parse("ftp://example.com/a.png").is_err());
+    }
+
+    #[test]
+    fn test_get_image() {
+        let config = Config::default();
+        assert_eq!(
+            get_image(&config, &json!({ "image": "ipfs://Qm/a.png" })),
+            Some("https://ipfs.io/ipfs/Qm/a.png".to_string())
+        );
+        assert_eq!(
+            get_image(
+                &config,
+                &json!({ "image": "", "image_url": "https://a.io/b.png" })
+            ),
+            Some("https://a.io/b.png".to_string())
+        );
+        assert_eq!(get_image(&config, &json!({ "name": "x" })), None);
+    }
+
+    #[test]
+    fn test_decode_data_json() {
+        // {"image":"https://a.io/b.png"}
+        let encoded = "data:application/json;base64,eyJpbWFnZSI6Imh0dHBzOi8vYS5pby9iLnBuZyJ9";
+        assert_eq!(
+            decode_data_json(encoded).unwrap(),
+            json!({ "image": "https://a.io/b.png" })
+        );
+        let plain = "data:application/json;utf8,{\"image\":\"https://a.io/b.png\"}";
+        assert_eq!(
+            decode_data_json(plain).unwrap(),
+            json!({ "image": "https://a.io/b.png" })
+        );
+        assert!(decode_data_json("data:application/json").is_err());
+    }
+
+    #[test]
+    fn test_token_ids() {
+        assert_eq!(
+            expand_erc1155_id("https://a.io/{id}.json", U256::from(0x4ca)),
+            format!("https://a.io/{:0>64}.json", "4ca")
+        );
+        let token_id = (U256::from(7) << 128) + U256::from(42);
+        assert_eq!(
+            split_u256(token_id),
+            (FieldElement::from(42_u64), FieldElement::from(7_u64))
+        );
+    }
+}
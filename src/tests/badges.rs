@@ -0,0 +1,148 @@
+use crate::badges::{BadgeCondition, BadgeRule, BadgeRules, BadgeSubject};
+use regex::Regex;
+use serde_json::json;
+use starknet::core::utils::cairo_short_string_to_felt;
+
+#[cfg(test)]
+mod badge_conditions {
+    use super::*;
+
+    const YEAR: u64 = 365 * 24 * 3600;
+
+    fn subject() -> BadgeSubject {
+        BadgeSubject {
+            domain: Some("ben.stark".to_string()),
+            creation_date: Some(1_650_000_000),
+            expiry: Some(1_650_000_000 + 3 * YEAR),
+            verified_fields: vec![
+                cairo_short_string_to_felt("twitter").unwrap(),
+                cairo_short_string_to_felt("discord").unwrap(),
+            ],
+            contract_verified: false,
+        }
+    }
+
+    #[test]
+    fn test_registered_before() {
+        let condition = BadgeCondition::RegisteredBefore {
+            timestamp: 1_672_531_200,
+        };
+        assert!(condition.is_met(&subject()));
+
+        let condition = BadgeCondition::RegisteredBefore {
+            timestamp: 1_600_000_000,
+        };
+        assert!(!condition.is_met(&subject()));
+    }
+
+    #[test]
+    fn test_registration_years() {
+        assert!(BadgeCondition::RegistrationYears { years: 3 }.is_met(&subject()));
+        assert!(!BadgeCondition::RegistrationYears { years: 4 }.is_met(&subject()));
+    }
+
+    #[test]
+    fn test_registration_years_without_expiry() {
+        let subject = BadgeSubject {
+            expiry: None,
+            ..subject()
+        };
+        assert!(!BadgeCondition::RegistrationYears { years: 1 }.is_met(&subject));
+    }
+
+    #[test]
+    fn test_verified_requires_every_field() {
+        let condition = BadgeCondition::Verified {
+            fields: vec!["twitter".to_string(), "discord".to_string()],
+        };
+        assert!(condition.is_met(&subject()));
+
+        let condition = BadgeCondition::Verified {
+            fields: vec![
+                "twitter".to_string(),
+                "discord".to_string(),
+                "github".to_string(),
+            ],
+        };
+        assert!(!condition.is_met(&subject()));
+    }
+
+    #[test]
+    fn test_verified_empty_fields() {
+        let condition = BadgeCondition::Verified { fields: vec![] };
+        assert!(!condition.is_met(&subject()));
+    }
+
+    #[test]
+    fn test_domain_matches() {
+        let condition = BadgeCondition::DomainMatches {
+            pattern: Regex::new(r"^.{1,4}\.stark$").unwrap(),
+        };
+        assert!(condition.is_met(&subject()));
+
+        let subject = BadgeSubject {
+            domain: Some("longname.stark".to_string()),
+            ..subject()
+        };
+        assert!(!condition.is_met(&subject));
+    }
+
+    #[test]
+    fn test_domain_matches_invalid_pattern() {
+        let condition = json!({ "type": "domain_matches", "pattern": "(" });
+        assert!(serde_json::from_value::<BadgeCondition>(condition).is_err());
+    }
+
+    #[test]
+    fn test_contract_verified() {
+        assert!(!BadgeCondition::ContractVerified.is_met(&subject()));
+        let subject = BadgeSubject {
+            contract_verified: true,
+            ..subject()
+        };
+        assert!(BadgeCondition::ContractVerified.is_met(&subject));
+    }
+
+    #[test]
+    fn test_compute_keeps_rules_order() {
+        let rules = BadgeRules {
+            badges: vec![
+                BadgeRule {
+                    id: "club".to_string(),
+                    name: "Club".to_string(),
+                    description: String::new(),
+                    condition: BadgeCondition::DomainMatches {
+                        pattern: Regex::new(r"^.{1,4}\.stark$").unwrap(),
+                    },
+                },
+                BadgeRule {
+                    id: "veteran".to_string(),
+                    name: "Veteran".to_string(),
+                    description: String::new(),
+                    condition: BadgeCondition::RegistrationYears { years: 10 },
+                },
+                BadgeRule {
+                    id: "og".to_string(),
+                    name: "OG".to_string(),
+                    description: String::new(),
+                    condition: BadgeCondition::RegisteredBefore {
+                        timestamp: 1_672_531_200,
+                    },
+                },
+            ],
+        };
+        let ids: Vec<String> = rules
+            .compute(&subject())
+            .into_iter()
+            .map(|badge| badge.id)
+            .collect();
+        assert_eq!(ids, vec!["club".to_string(), "og".to_string()]);
+    }
+
+    #[test]
+    fn test_default_rules_file_parses() {
+        let data = std::fs::read_to_string("./src/badges/badges.json").unwrap();
+        let rules: BadgeRules = serde_json::from_str(&data).unwrap();
+        assert!(!rules.badges.is_empty());
+    }
+}
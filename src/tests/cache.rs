@@ -0,0 +1,75 @@
+use crate::{
+    cache::{get_cache_key, get_event_tags, get_query_tags, CachedResponse, ResponseCache},
+    ws::feed::to_change_event,
+};
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use mongodb::bson::doc;
+use tokio::time::{Duration, Instant};
+
+#[cfg(test)]
+mod cache {
+    use super::*;
+
+    const ADDRESS: &str = "0x0000000000000000000000000000000000000000000000000000000000000123";
+
+    fn entry(tags: &[&str], expires_at: Instant) -> CachedResponse {
+        CachedResponse {
+            status: StatusCode::OK,
+            headers: HeaderMap::new(),
+            body: "{}".into(),
+            expires_at,
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_query_tags() {
+        assert_eq!(
+            get_query_tags("domain=Fricoben.stark&addr=0x123"),
+            vec![
+                "domain:fricoben.stark".to_string(),
+                format!("addr:{}", ADDRESS)
+            ]
+        );
+        assert_eq!(get_query_tags("id=291"), vec![format!("id:{}", ADDRESS)]);
+        assert!(get_query_tags("addr=invalid").is_empty());
+    }
+
+    #[test]
+    fn test_keys_vary_on_negotiated_headers() {
+        let mut headers = HeaderMap::new();
+        let plain = get_cache_key("/uri", Some("id=1"), &headers);
+        headers.insert("accept", HeaderValue::from_static("application/cbor"));
+        assert_ne!(plain, get_cache_key("/uri", Some("id=1"), &headers));
+    }
+
+    #[test]
+    fn test_expiry_and_invalidation() {
+        let cache = ResponseCache::default();
+        let now = Instant::now();
+        let later = now + Duration::from_secs(30);
+        cache.insert(
+            "a".to_string(),
+            entry(&["domain:fricoben.stark"], later),
+            10,
+            now,
+        );
+        cache.insert(
+            "b".to_string(),
+            entry(&[&format!("addr:{}", ADDRESS)], later),
+            10,
+            now,
+        );
+        assert!(cache.get("a", now).is_some());
+        assert!(cache.get("a", later).is_none());
+
+        let event = to_change_event("id_owners", &doc! { "id": "0x1", "owner": ADDRESS }).unwrap();
+        assert_eq!(cache.invalidate(&get_event_tags(&event)), 1);
+        assert!(cache.get("b", now).is_none());
+
+        // a full cache of live entries refuses new ones
+        cache.insert("c".to_string(), entry(&[], later), 1, now);
+        cache.insert("d".to_string(), entry(&[], later), 1, now);
+        assert_eq!(cache.len(), 1);
+    }
+}
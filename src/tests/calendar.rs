@@ -0,0 +1,38 @@
+use crate::calendar::{render_calendar, ExpiryEvent};
+
+#[cfg(test)]
+mod calendar {
+    use super::*;
+
+    #[test]
+    fn test_render_calendar() {
+        let events = vec![ExpiryEvent {
+            domain: "alice.stark".to_string(),
+            expiry: 1735689600,
+        }];
+        let feed = render_calendar(&events, &[30, 7], 1704067200);
+        assert!(feed.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(feed.ends_with("END:VCALENDAR\r\n"));
+        assert!(feed.contains("UID:alice.stark-1735689600@starknet.id\r\n"));
+        assert!(feed.contains("DTSTART:20250101T000000Z\r\n"));
+        assert!(feed.contains("DTSTAMP:20240101T000000Z\r\n"));
+        assert!(feed.contains("TRIGGER:-P30D\r\n"));
+        assert!(feed.contains("TRIGGER:-P7D\r\n"));
+        assert_eq!(feed.matches("BEGIN:VALARM").count(), 2);
+    }
+
+    #[test]
+    fn test_long_lines_are_folded() {
+        let domain = format!("{}.stark", "a".repeat(100));
+        let events = vec![ExpiryEvent {
+            domain: domain.clone(),
+            expiry: 1735689600,
+        }];
+        let feed = render_calendar(&events, &[], 1704067200);
+        assert!(feed.split("\r\n").all(|line| line.len() <= 75));
+        // unfolding gives back the original line
+        assert!(feed
+            .replace("\r\n ", "")
+            .contains(&format!("SUMMARY:{} expires\r\n", domain)));
+    }
+}
@@ -0,0 +1,53 @@
+use crate::clients::{classify, ClientClass, ClientUsage};
+
+#[cfg(test)]
+mod clients {
+    use super::*;
+
+    #[test]
+    fn test_classify() {
+        for (user_agent, class) in [
+            ("ArgentX/5.12.0", ClientClass::Wallet),
+            ("Braavos Mobile/3.1 (iOS)", ClientClass::Wallet),
+            ("Starkscan-Indexer/1.0", ClientClass::Explorer),
+            ("starknetid.js/3.0.1", ClientClass::Sdk),
+            ("axios/1.6.2", ClientClass::Sdk),
+            ("curl/8.4.0", ClientClass::Bot),
+            (
+                "Mozilla/5.0 (compatible; Googlebot/2.1; +http://www.google.com/bot.html)",
+                ClientClass::Bot,
+            ),
+            (
+                "Mozilla/5.0 (X11; Linux x86_64) AppleWebKit/537.36 Chrome/120.0 Safari/537.36",
+                ClientClass::Browser,
+            ),
+            ("my-script", ClientClass::Unknown),
+            ("  ", ClientClass::Unknown),
+        ] {
+            assert_eq!(classify(Some(user_agent)), class, "{}", user_agent);
+        }
+        assert_eq!(classify(None), ClientClass::Unknown);
+    }
+
+    #[test]
+    fn test_report() {
+        let usage = ClientUsage::default();
+        usage.record(ClientClass::Wallet, "/addr_to_domain", 10);
+        usage.record(ClientClass::Wallet, "/addr_to_domain", 20);
+        usage.record(ClientClass::Wallet, "/domain_to_addr", 15);
+        usage.record(ClientClass::Bot, "/domain_to_data", 12);
+
+        let report = usage.report();
+        assert_eq!(
+            report
+                .iter()
+                .map(|class| (class.class, class.requests))
+                .collect::<Vec<_>>(),
+            vec![(ClientClass::Wallet, 3), (ClientClass::Bot, 1)]
+        );
+        let wallet = &report[0].endpoints;
+        assert_eq!(wallet[0].endpoint, "/addr_to_domain");
+        assert_eq!((wallet[0].requests, wallet[0].last_seen), (2, 20));
+        assert_eq!(wallet[1].endpoint, "/domain_to_addr");
+    }
+}
@@ -0,0 +1,29 @@
+use crate::clubs::{appraise, get_club};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod clubs {
+    use super::*;
+
+    #[test]
+    fn test_get_club() {
+        assert_eq!(get_club("a.stark"), Some("single_letter"));
+        assert_eq!(get_club("42.stark"), Some("99"));
+        assert_eq!(get_club("ab.stark"), Some("two_letters"));
+        assert_eq!(get_club("420.stark"), Some("999"));
+        assert_eq!(get_club("ben.stark"), Some("three_letters"));
+        assert_eq!(get_club("4242.stark"), Some("10k"));
+        assert_eq!(get_club("fric.stark"), Some("four_letters"));
+        assert_eq!(get_club("ben.vip.stark"), Some("og"));
+        assert_eq!(get_club("ben.braavos.stark"), None);
+        assert_eq!(get_club("vitalik.stark"), None);
+    }
+
+    #[test]
+    fn test_appraise() {
+        let floors = HashMap::from([("three_letters".to_string(), 100.0)]);
+        assert_eq!(appraise(&floors, 5.0, Some("three_letters")), 100.0);
+        assert_eq!(appraise(&floors, 5.0, Some("four_letters")), 5.0);
+        assert_eq!(appraise(&floors, 5.0, None), 5.0);
+    }
+}
@@ -0,0 +1,88 @@
+use crate::{
+    compatibility::{
+        get_incompatibilities, get_live_peers, get_running_versions, send_heartbeat,
+        Incompatibility, Peer, SchemaVersions, PEERS_COLLECTION,
+    },
+    storage::{MemoryStorage, Storage},
+};
+use mongodb::bson::{doc, to_document};
+
+const NOW: i64 = 1_700_000_000;
+
+#[cfg(test)]
+mod compatibility {
+    use super::*;
+
+    fn peer(instance_id: &str, versions: SchemaVersions, heartbeat_at: i64) -> Peer {
+        Peer {
+            instance_id: instance_id.to_string(),
+            server_version: "0.1.0".to_string(),
+            versions,
+            heartbeat_at,
+        }
+    }
+
+    #[test]
+    fn test_incompatible_versions() {
+        let running = get_running_versions();
+        let same = peer("a", running, NOW);
+        let config_only = peer(
+            "b",
+            SchemaVersions {
+                config: running.config + 1,
+                ..running
+            },
+            NOW,
+        );
+        let cache_key = peer(
+            "c",
+            SchemaVersions {
+                cache_key: running.cache_key + 1,
+                ..running
+            },
+            NOW,
+        );
+        assert!(get_incompatibilities(&running, &[same, config_only]).is_empty());
+        assert_eq!(
+            get_incompatibilities(&running, &[cache_key]),
+            vec![Incompatibility {
+                instance_id: "c".to_string(),
+                component: "cache_key",
+                peer: running.cache_key + 1,
+                running: running.cache_key,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_live_peers() {
+        let storage = MemoryStorage::default();
+        let running = get_running_versions();
+        for document in [
+            peer("live", running, NOW - 10),
+            peer("gone", running, NOW - 600),
+        ] {
+            storage
+                .insert_one(PEERS_COLLECTION, to_document(&document).unwrap())
+                .await
+                .unwrap();
+        }
+        send_heartbeat(&storage, "self", NOW).await.unwrap();
+        // a heartbeat replaces the previous one
+        send_heartbeat(&storage, "self", NOW + 5).await.unwrap();
+        assert_eq!(
+            storage
+                .count(PEERS_COLLECTION, doc! { "instance_id": "self" })
+                .await
+                .unwrap(),
+            1
+        );
+
+        let peers = get_live_peers(&storage, "self", NOW + 5, 60).await.unwrap();
+        assert_eq!(peers, vec![peer("live", running, NOW - 10)]);
+        let peers = get_live_peers(&storage, "live", NOW + 5, 60).await.unwrap();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].instance_id, "self");
+        assert_eq!(peers[0].heartbeat_at, NOW + 5);
+    }
+}
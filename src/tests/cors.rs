@@ -0,0 +1,23 @@
+use crate::cors::is_allowed_origin;
+
+#[cfg(test)]
+mod cors {
+    use super::*;
+
+    #[test]
+    fn test_allowed_origins() {
+        let patterns = vec![
+            "https://app.example.com".to_string(),
+            "https://*.partner.io".to_string(),
+        ];
+        assert!(is_allowed_origin(&patterns, "https://app.example.com"));
+        assert!(!is_allowed_origin(&patterns, "http://app.example.com"));
+        assert!(!is_allowed_origin(&patterns, "https://example.com"));
+        assert!(is_allowed_origin(&patterns, "https://a.b.partner.io"));
+        // the wildcard only matches the subdomains
+        assert!(!is_allowed_origin(&patterns, "https://partner.io"));
+        assert!(!is_allowed_origin(&patterns, "https://evilpartner.io"));
+        assert!(!is_allowed_origin(&patterns, "https://partner.io.evil.com"));
+        assert!(!is_allowed_origin(&[], "https://app.example.com"));
+    }
+}
@@ -0,0 +1,46 @@
+use crate::{
+    descriptions::{get_description_hash, validate_description, MAX_DESCRIPTION_LEN},
+    profanity::ProfanityFilter,
+};
+use starknet::core::types::FieldElement;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod descriptions {
+    use super::*;
+
+    fn filter() -> ProfanityFilter {
+        ProfanityFilter {
+            terms: HashMap::from([(
+                "en".to_string(),
+                vec!["shit".to_string(), "ass".to_string()],
+            )]),
+        }
+    }
+
+    #[test]
+    fn test_accepts_plain_bio() {
+        let filter = filter();
+        assert!(validate_description(&filter, "Builder on Starknet.\nFirst class coffee.").is_ok());
+        assert!(validate_description(&filter, "").is_ok());
+    }
+
+    #[test]
+    fn test_rejects_invalid_bio() {
+        let filter = filter();
+        let long = "a".repeat(MAX_DESCRIPTION_LEN + 1);
+        assert!(validate_description(&filter, &long).is_err());
+        assert!(validate_description(&filter, "hello\u{0007}").is_err());
+        assert!(validate_description(&filter, "what a sh1t day").is_err());
+        assert!(validate_description(&filter, "kick ass today").is_err());
+    }
+
+    #[test]
+    fn test_hash_binds_timestamp() {
+        let id = FieldElement::from(1_u64);
+        assert_ne!(
+            get_description_hash(&id, "gm", 1700000000),
+            get_description_hash(&id, "gm", 1700000001)
+        );
+    }
+}
@@ -0,0 +1,33 @@
+use crate::display_address::{format_address, format_hex_address, AddressFormat};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod display_address {
+    use super::*;
+
+    #[test]
+    fn test_checksum_address() {
+        // vector of starknet.js getChecksumAddress
+        let address = FieldElement::from_hex_be(
+            "0x2fd23d9182193775423497fc0c472e156c57c69e4089a1967fb288a2d84e914",
+        )
+        .unwrap();
+        assert_eq!(
+            format_address(&address, AddressFormat::Checksum),
+            "0x02Fd23d9182193775423497fc0c472E156C57C69E4089A1967fb288A2d84e914"
+        );
+        assert_eq!(
+            format_address(&address, AddressFormat::Lowercase),
+            "0x02fd23d9182193775423497fc0c472e156c57c69e4089a1967fb288a2d84e914"
+        );
+    }
+
+    #[test]
+    fn test_format_hex_address() {
+        assert_eq!(
+            format_hex_address("0x0", AddressFormat::Lowercase).as_deref(),
+            Some("0x0000000000000000000000000000000000000000000000000000000000000000")
+        );
+        assert_eq!(format_hex_address("not hex", AddressFormat::Checksum), None);
+    }
+}
@@ -0,0 +1,37 @@
+use crate::distribution::{get_histogram, get_top_share, Bucket};
+
+#[cfg(test)]
+mod distribution {
+    use super::*;
+
+    #[test]
+    fn test_histogram_buckets() {
+        let histogram = get_histogram(vec![0, 1, 2, 4, 5, 12], &[1, 3, 10]);
+        assert_eq!(
+            histogram,
+            vec![
+                Bucket {
+                    from: 1,
+                    to: Some(3),
+                    count: 2,
+                },
+                Bucket {
+                    from: 3,
+                    to: Some(10),
+                    count: 2,
+                },
+                Bucket {
+                    from: 10,
+                    to: None,
+                    count: 1,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_top_share() {
+        assert_eq!(get_top_share(vec![], 100), 0.0);
+        assert_eq!(get_top_share(vec![1, 6, 1, 2], 1), 0.6);
+    }
+}
@@ -0,0 +1,96 @@
+use crate::{
+    history::{get_domain_history, get_version_events, HistoryEvent},
+    storage::{MemoryStorage, Storage},
+};
+use mongodb::bson::{doc, Bson, Document};
+
+#[cfg(test)]
+mod domain_history {
+    use super::*;
+
+    fn cursor(from: i64, to: Option<i64>) -> Document {
+        doc! { "from": from, "to": to.map_or(Bson::Null, Bson::Int64) }
+    }
+
+    #[test]
+    fn test_version_events() {
+        let first = doc! { "id": "0x1", "expiry": 10_i64, "_cursor": cursor(100, Some(200)) };
+        let renewed =
+            doc! { "id": "0x2", "expiry": 20_i64, "resolver": "0xr", "_cursor": cursor(200, None) };
+        assert_eq!(
+            get_version_events(Some(&first), &renewed),
+            vec![
+                HistoryEvent::Transferred {
+                    from_id: Some("0x1".to_string()),
+                    to_id: Some("0x2".to_string()),
+                },
+                HistoryEvent::Renewed { expiry: Some(20) },
+                HistoryEvent::ResolverChanged {
+                    resolver: Some("0xr".to_string()),
+                },
+            ]
+        );
+        // registered again after a gap
+        let later = doc! { "id": "0x3", "expiry": 30_i64, "_cursor": cursor(300, None) };
+        assert_eq!(
+            get_version_events(Some(&first), &later),
+            vec![HistoryEvent::Registered {
+                id: Some("0x3".to_string()),
+                expiry: Some(30),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_domain_history() {
+        let storage = MemoryStorage::default();
+        let documents = [
+            (
+                "domains",
+                doc! { "domain": "ben.stark", "id": "0x1", "expiry": 10_i64, "_cursor": cursor(100, Some(200)) },
+            ),
+            (
+                "domains",
+                doc! { "domain": "ben.stark", "id": "0x1", "expiry": 20_i64, "_cursor": cursor(200, None) },
+            ),
+            (
+                "domains",
+                doc! { "domain": "sub.ben.stark", "id": "0x5", "_cursor": cursor(250, None) },
+            ),
+            // not a direct subdomain
+            (
+                "domains",
+                doc! { "domain": "a.sub.ben.stark", "id": "0x6", "_cursor": cursor(260, None) },
+            ),
+            (
+                "id_owners",
+                doc! { "id": "0x1", "owner": "0xa", "_cursor": cursor(50, Some(150)) },
+            ),
+            (
+                "id_owners",
+                doc! { "id": "0x1", "owner": "0xb", "_cursor": cursor(150, None) },
+            ),
+        ];
+        for (collection, document) in documents {
+            storage.insert_one(collection, document).await.unwrap();
+        }
+        let history = get_domain_history(&storage, "ben.stark").await.unwrap();
+        let blocks: Vec<i64> = history.iter().map(|entry| entry.block).collect();
+        assert_eq!(blocks, vec![100, 150, 200, 250]);
+        assert_eq!(
+            history[1].event,
+            HistoryEvent::IdentityTransferred {
+                id: "0x1".to_string(),
+                from: Some("0xa".to_string()),
+                to: Some("0xb".to_string()),
+            }
+        );
+        assert_eq!(history[2].event, HistoryEvent::Renewed { expiry: Some(20) });
+        assert_eq!(
+            history[3].event,
+            HistoryEvent::SubdomainCreated {
+                subdomain: "sub.ben.stark".to_string(),
+            }
+        );
+    }
+}
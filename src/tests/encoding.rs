@@ -0,0 +1,52 @@
+use crate::encoding::Encoding;
+use axum::http::{HeaderMap, HeaderValue};
+use serde::{Deserialize, Serialize};
+
+#[cfg(test)]
+mod content_negotiation {
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Resolution {
+        addr: String,
+        domain_expiry: Option<i64>,
+    }
+
+    fn accept(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("accept", HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_from_headers() {
+        assert_eq!(Encoding::from_headers(&HeaderMap::new()), Encoding::Json);
+        assert_eq!(Encoding::from_headers(&accept("*/*")), Encoding::Json);
+        assert_eq!(
+            Encoding::from_headers(&accept("application/cbor, application/json")),
+            Encoding::Cbor
+        );
+        assert_eq!(
+            Encoding::from_headers(&accept("application/cbor;q=0, application/x-msgpack")),
+            Encoding::MessagePack
+        );
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let resolution = Resolution {
+            addr: "0x123".to_string(),
+            domain_expiry: Some(1717200000),
+        };
+        let cbor = Encoding::Cbor.encode(&resolution).unwrap();
+        assert_eq!(
+            ciborium::de::from_reader::<Resolution, _>(cbor.as_slice()).unwrap(),
+            resolution
+        );
+        let msgpack = Encoding::MessagePack.encode(&resolution).unwrap();
+        assert_eq!(
+            rmp_serde::from_slice::<Resolution>(&msgpack).unwrap(),
+            resolution
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use crate::errors::{ApiError, ErrorCode};
+use axum::{http::StatusCode, response::IntoResponse};
+use serde_json::{json, Value};
+
+#[cfg(test)]
+mod errors {
+    use super::*;
+
+    #[test]
+    fn test_codes() {
+        assert_eq!(
+            serde_json::to_value(ErrorCode::DomainNotFound).unwrap(),
+            json!("DOMAIN_NOT_FOUND")
+        );
+        assert_eq!(ErrorCode::DomainNotFound.status(), StatusCode::NOT_FOUND);
+        assert_eq!(ErrorCode::InvalidAddress.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(ErrorCode::IndexNotInTree.status(), StatusCode::NOT_FOUND);
+        assert_eq!(
+            ErrorCode::QuoteOutOfRange.status(),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            ErrorCode::from_status(StatusCode::GATEWAY_TIMEOUT),
+            ErrorCode::UpstreamError
+        );
+        assert_eq!(
+            ErrorCode::from_status(StatusCode::METHOD_NOT_ALLOWED),
+            ErrorCode::InvalidInput
+        );
+    }
+
+    #[tokio::test]
+    async fn test_client_error() {
+        let response = ApiError::new(ErrorCode::DomainNotFound, "Domain not found").into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(
+            serde_json::from_slice::<Value>(&body).unwrap(),
+            json!({ "error": { "code": "DOMAIN_NOT_FOUND", "message": "Domain not found" } })
+        );
+    }
+
+    #[test]
+    fn test_server_error() {
+        // left to the failures middleware, which adds the incident id
+        let response = ApiError::new(ErrorCode::DatabaseError, "timeout").into_response();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(
+            response.extensions().get::<ErrorCode>(),
+            Some(&ErrorCode::DatabaseError)
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    config::Config,
+    examples::{get_examples, get_request, is_replayed},
+    routes::ROUTES,
+};
+
+#[cfg(test)]
+mod examples {
+    use super::*;
+
+    fn find(handler: &str) -> &'static crate::routes::RouteSpec {
+        ROUTES.iter().find(|spec| spec.handler == handler).unwrap()
+    }
+
+    #[test]
+    fn test_every_route_has_an_example() {
+        let conf = Config::default();
+        let examples = get_examples(ROUTES, &conf.examples);
+        assert_eq!(examples.len(), ROUTES.len());
+        for example in &examples {
+            assert!(
+                !example.request.url.contains("/:"),
+                "{} has an unfilled path segment",
+                example.path
+            );
+        }
+    }
+
+    #[test]
+    fn test_fixture_values() {
+        let conf = Config::default();
+        let request = get_request(find("endpoints::domain_to_addr"), &conf.examples);
+        assert_eq!(request.url, "/domain_to_addr?domain=ben.stark");
+        assert!(request.body.is_none());
+        assert!(request.headers.is_empty());
+    }
+
+    #[test]
+    fn test_bodies_and_auth_headers() {
+        let conf = Config::default();
+        let request = get_request(find("endpoints::me::token"), &conf.examples);
+        let body = request.body.unwrap();
+        assert!(body["address"].is_string());
+        assert!(body["timestamp"].is_number());
+        assert!(body["signature"].is_array());
+        assert_eq!(request.headers["content-type"], "application/json");
+
+        let request = get_request(find("endpoints::me::notifications"), &conf.examples);
+        assert_eq!(request.headers["authorization"], "Bearer <token>");
+    }
+
+    #[test]
+    fn test_only_public_reads_are_replayed() {
+        assert!(is_replayed(find("endpoints::domain_to_addr")));
+        assert!(!is_replayed(find("endpoints::me::token")));
+        assert!(!is_replayed(find("endpoints::me::notifications")));
+        assert!(!is_replayed(find("endpoints::events::stream")));
+        assert!(!is_replayed(find("endpoints::docs::examples")));
+    }
+}
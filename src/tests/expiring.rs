@@ -0,0 +1,93 @@
+use crate::{
+    expiring::get_expiring_domains,
+    storage::{MemoryStorage, Storage},
+};
+use mongodb::bson::{doc, oid::ObjectId};
+
+const NOW: i64 = 1_700_000_000;
+const DAY: i64 = 24 * 3600;
+
+#[cfg(test)]
+mod expiring_domains {
+    use super::*;
+
+    async fn storage() -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        for (domain, id, expiry) in [
+            ("later.stark", "0x3", NOW + 20 * DAY),
+            ("soon.stark", "0x1", NOW + DAY),
+            ("same.stark", "0x2", NOW + 20 * DAY),
+            ("far.stark", "0x4", NOW + 90 * DAY),
+            ("gone.stark", "0x5", NOW - DAY),
+        ] {
+            storage
+                .insert_one(
+                    "domains",
+                    doc! { "_id": ObjectId::new(), "domain": domain, "id": id, "expiry": expiry, "_cursor": { "from": 1 } },
+                )
+                .await
+                .unwrap();
+        }
+        for (id, owner) in [("0x1", "0xa"), ("0x2", "0xb")] {
+            storage
+                .insert_one("id_owners", doc! { "id": id, "owner": owner })
+                .await
+                .unwrap();
+        }
+        for (collection, domain, renewer) in [
+            ("auto_renew_flows", "soon.stark", "0xa"),
+            // subscribed by a previous owner
+            ("auto_renew_flows", "same.stark", "0xc"),
+            ("auto_renew_flows_altcoins", "same.stark", "0xb"),
+        ] {
+            storage
+                .insert_one(
+                    collection,
+                    doc! { "domain": domain, "renewer_address": renewer, "enabled": true },
+                )
+                .await
+                .unwrap();
+        }
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_window_and_renewals() {
+        let storage = storage().await;
+        let (domains, next_cursor) = get_expiring_domains(&storage, NOW, NOW + 30 * DAY, None, 10)
+            .await
+            .unwrap();
+        assert_eq!(next_cursor, None);
+        assert_eq!(domains.len(), 3);
+        assert_eq!(domains[0].domain, "soon.stark");
+        assert_eq!(domains[0].owner.as_deref(), Some("0xa"));
+        assert!(domains[0].auto_renew && !domains[0].auto_renew_altcoin);
+        let same = domains.iter().find(|d| d.domain == "same.stark").unwrap();
+        assert!(!same.auto_renew && same.auto_renew_altcoin);
+        let later = domains.iter().find(|d| d.domain == "later.stark").unwrap();
+        assert_eq!(later.owner, None);
+        assert!(!later.auto_renew);
+    }
+
+    #[tokio::test]
+    async fn test_pages_share_an_expiry() {
+        let storage = storage().await;
+        let (first, cursor) = get_expiring_domains(&storage, NOW, NOW + 30 * DAY, None, 2)
+            .await
+            .unwrap();
+        let cursor = cursor.unwrap();
+        assert_eq!(cursor.key, NOW + 20 * DAY);
+        let (second, cursor) =
+            get_expiring_domains(&storage, NOW, NOW + 30 * DAY, Some(&cursor), 2)
+                .await
+                .unwrap();
+        assert_eq!(cursor, None);
+        let mut seen: Vec<&str> = first
+            .iter()
+            .chain(second.iter())
+            .map(|d| d.domain.as_str())
+            .collect();
+        seen.sort();
+        assert_eq!(seen, vec!["later.stark", "same.stark", "soon.stark"]);
+    }
+}
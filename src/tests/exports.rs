@@ -0,0 +1,147 @@
+use crate::{
+    exports::{
+        escape_csv, get_change, get_domains_page, get_object_key, parse_snapshot_date, DomainRow,
+        ExportJob, ExportKind, ExportStatus,
+    },
+    storage::{MemoryStorage, Storage},
+};
+use mongodb::bson::{doc, from_document, to_document};
+
+#[cfg(test)]
+mod export_jobs {
+    use super::*;
+
+    fn job() -> ExportJob {
+        ExportJob {
+            job_id: "0123abcd".to_string(),
+            kind: ExportKind::Domains,
+            requested_by: "braavos".to_string(),
+            status: ExportStatus::Pending,
+            created_at: 1717200000,
+            finished_at: None,
+            records: 0,
+            object_key: None,
+            error: None,
+        }
+    }
+
+    #[test]
+    fn test_object_key() {
+        assert_eq!(get_object_key(&job()), "domains/0123abcd.jsonl");
+    }
+
+    #[test]
+    fn test_job_roundtrip() {
+        let doc = to_document(&job()).unwrap();
+        assert_eq!(doc.get_str("status").unwrap(), "pending");
+        assert_eq!(
+            doc.get_document("kind").unwrap().get_str("type").unwrap(),
+            "domains"
+        );
+        let parsed: ExportJob = from_document(doc).unwrap();
+        assert_eq!(parsed.kind, ExportKind::Domains);
+    }
+
+    #[test]
+    fn test_parse_snapshot_date() {
+        assert_eq!(parse_snapshot_date("2024-05-01").unwrap(), 1714521600);
+        assert!(parse_snapshot_date("2024-13-01").is_err());
+        assert!(parse_snapshot_date("01/05/2024").is_err());
+    }
+
+    #[test]
+    fn test_delta_changes() {
+        let (from, to) = (1714521600, 1717200000);
+        let domain = doc! { "domain": "ben.stark", "id": "0x1", "expiry": 1800000000_i64 };
+        let transferred = doc! { "domain": "ben.stark", "id": "0x2", "expiry": 1800000000_i64 };
+        let expiring = doc! { "domain": "ben.stark", "id": "0x1", "expiry": 1715000000_i64 };
+
+        assert_eq!(get_change(None, Some(&domain), from, to), Some("created"));
+        assert_eq!(get_change(Some(&domain), None, from, to), Some("expired"));
+        assert_eq!(
+            get_change(Some(&domain), Some(&expiring), from, to),
+            Some("expired")
+        );
+        assert_eq!(
+            get_change(Some(&domain), Some(&transferred), from, to),
+            Some("changed")
+        );
+        assert_eq!(get_change(Some(&domain), Some(&domain), from, to), None);
+        assert_eq!(get_change(None, None, from, to), None);
+    }
+}
+
+#[cfg(test)]
+mod domain_dumps {
+    use super::*;
+
+    #[test]
+    fn test_escape_csv() {
+        assert_eq!(escape_csv("ben.stark"), "ben.stark");
+        assert_eq!(escape_csv("a,b"), "\"a,b\"");
+        assert_eq!(escape_csv("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_csv_row() {
+        let row = DomainRow {
+            domain: "ben.stark".to_string(),
+            id: "0x1".to_string(),
+            owner: Some("0xa".to_string()),
+            expiry: Some(1717200000),
+            resolver: None,
+        };
+        assert_eq!(row.to_csv(), "ben.stark,0x1,0xa,1717200000,");
+        assert_eq!(
+            DomainRow::CSV_HEADER.split(',').count(),
+            row.to_csv().split(',').count()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pages_follow_the_domains() {
+        let storage = MemoryStorage::default();
+        for (domain, id) in [
+            ("carl.stark", "0x3"),
+            ("ben.stark", "0x2"),
+            ("al.stark", "0x1"),
+        ] {
+            storage
+                .insert_one(
+                    "domains",
+                    doc! { "domain": domain, "id": id, "expiry": 10_i64, "resolver": "0xr" },
+                )
+                .await
+                .unwrap();
+        }
+        // expired version of a domain
+        storage
+            .insert_one(
+                "domains",
+                doc! { "domain": "old.stark", "id": "0x4", "_cursor": { "to": 5 } },
+            )
+            .await
+            .unwrap();
+        storage
+            .insert_one("id_owners", doc! { "id": "0x2", "owner": "0xb" })
+            .await
+            .unwrap();
+
+        let page = get_domains_page(&storage, None, 2).await.unwrap();
+        assert_eq!(
+            page.iter()
+                .map(|row| row.domain.as_str())
+                .collect::<Vec<_>>(),
+            vec!["al.stark", "ben.stark"]
+        );
+        assert_eq!(page[0].owner, None);
+        assert_eq!(page[1].owner.as_deref(), Some("0xb"));
+        assert_eq!(page[1].resolver.as_deref(), Some("0xr"));
+
+        let page = get_domains_page(&storage, Some("ben.stark"), 2)
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].domain, "carl.stark");
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{
+    errors::{ErrorCode, ErrorDetails},
+    failures::{
+        failure_response, get_address_error, get_degraded_components, get_rejection_error,
+        get_retry_after_ms, Component, FailureBody,
+    },
+    metrics::{labeled, Metrics},
+};
+use axum::http::StatusCode;
+
+#[cfg(test)]
+mod failures {
+    use super::*;
+
+    #[test]
+    fn test_degraded_components() {
+        let metrics = Metrics::default();
+        assert!(get_degraded_components(&metrics, &["starknetid"]).is_empty());
+        metrics.add(&labeled("mongo_pool_wait_queue", "db", "sales"), 3);
+        // only the given pools are checked
+        assert!(get_degraded_components(&metrics, &["starknetid"]).is_empty());
+        metrics.set(&labeled("rpc_queue_depth", "priority", "interactive"), 2);
+        assert_eq!(
+            get_degraded_components(&metrics, &["starknetid", "sales"]),
+            vec![Component::Database, Component::Rpc]
+        );
+    }
+
+    #[test]
+    fn test_retry_hints() {
+        assert_eq!(
+            get_retry_after_ms(StatusCode::SERVICE_UNAVAILABLE),
+            Some(5000)
+        );
+        assert_eq!(get_retry_after_ms(StatusCode::INTERNAL_SERVER_ERROR), None);
+        let response = failure_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            FailureBody {
+                error: ErrorDetails {
+                    code: ErrorCode::RateLimited,
+                    message: "Rate limit exceeded".to_string(),
+                },
+                retry_after_ms: Some(1500),
+                degraded_components: vec![],
+                incident_id: None,
+            },
+        );
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        // rounded up to the next second
+        assert_eq!(response.headers()["retry-after"], "2");
+        assert_eq!(response.headers()["content-type"], "application/json");
+    }
+
+    #[test]
+    fn test_address_errors() {
+        assert_eq!(
+            get_address_error(
+                "Failed to deserialize query string: invalid starknet address: it is empty"
+            ),
+            Some("invalid starknet address: it is empty".to_string())
+        );
+        assert_eq!(
+            get_address_error("Failed to deserialize query string: missing field `addr`"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_rejection_errors() {
+        let error = get_rejection_error(
+            StatusCode::UNPROCESSABLE_ENTITY,
+            "Failed to deserialize the JSON body into the target type: addresses[0]: invalid starknet address: it is empty",
+        );
+        assert_eq!(error.code, ErrorCode::InvalidAddress);
+        assert_eq!(error.message, "invalid starknet address: it is empty");
+        // the malformed bodies are answered like the malformed queries
+        let error = get_rejection_error(StatusCode::UNPROCESSABLE_ENTITY, "missing field `domain`");
+        assert_eq!(error.code, ErrorCode::InvalidInput);
+        assert_eq!(error.code.status(), StatusCode::BAD_REQUEST);
+        let error = get_rejection_error(StatusCode::NOT_FOUND, "");
+        assert_eq!(error.code, ErrorCode::NotFound);
+        assert_eq!(error.message, "Not Found");
+    }
+}
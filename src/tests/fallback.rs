@@ -0,0 +1,46 @@
+use crate::{
+    config::Config,
+    fallback::{get_domain_calldata, FallbackCache},
+};
+use starknet::core::types::FieldElement;
+use starknet_id::encode;
+
+#[cfg(test)]
+mod fallback {
+    use super::*;
+
+    #[test]
+    fn test_domain_calldata() {
+        let calldata = get_domain_calldata("sub.ben.stark").unwrap();
+        assert_eq!(
+            calldata,
+            vec![
+                FieldElement::from(2u32),
+                encode("sub").unwrap(),
+                encode("ben").unwrap(),
+                FieldElement::ZERO,
+            ]
+        );
+        assert!(get_domain_calldata("ben.eth").is_err());
+    }
+
+    #[test]
+    fn test_cache_expiry() {
+        let cache = FallbackCache::default();
+        let target = FieldElement::from(7u32);
+        cache.insert("ben.stark", Some(target), 160, 100);
+        cache.insert("missing.stark", None, 110, 100);
+
+        assert_eq!(cache.get("ben.stark", 150), Some(Some(target)));
+        assert_eq!(cache.get("missing.stark", 105), Some(None));
+        assert_eq!(cache.get("missing.stark", 110), None);
+        assert_eq!(cache.get("other.stark", 100), None);
+    }
+
+    #[test]
+    fn test_disabled_by_default() {
+        let conf = Config::default();
+        assert!(conf.fallback.rpc_url.is_empty());
+        assert!(conf.fallback.miss_cache_secs < conf.fallback.cache_secs);
+    }
+}
@@ -0,0 +1,72 @@
+use crate::filters::Filter;
+use serde_json::json;
+
+#[cfg(test)]
+mod filters {
+    use super::*;
+
+    fn matches(filter: &str, event: &serde_json::Value) -> bool {
+        Filter::parse(filter).unwrap().matches(event)
+    }
+
+    #[test]
+    fn test_example_filter() {
+        let filter = r#"event == "transfer" && domain.endsWith(".stark") && length <= 5"#;
+        assert!(matches(
+            filter,
+            &json!({ "event": "transfer", "domain": "ben.stark" })
+        ));
+        assert!(!matches(
+            filter,
+            &json!({ "event": "transfer", "domain": "fricoben.stark" })
+        ));
+        assert!(!matches(
+            filter,
+            &json!({ "event": "renewal", "domain": "ben.stark" })
+        ));
+        // missing fields are null, which matches nothing
+        assert!(!matches(filter, &json!({ "event": "transfer" })));
+    }
+
+    #[test]
+    fn test_operators() {
+        let event = json!({
+            "kind": "owner",
+            "block": 42,
+            "data": { "owner": "0x123" },
+            "addresses": ["0x123", "0x456"],
+        });
+        assert!(matches("block > 41 && block < 43.5", &event));
+        assert!(matches("block == 42.0 && block != 41", &event));
+        assert!(matches("!(kind == 'domain') || block >= 100", &event));
+        assert!(matches(r#"data.owner.startsWith("0x1")"#, &event));
+        assert!(matches(r#"addresses.contains("0x456")"#, &event));
+        assert!(matches("data.missing == null && !data.missing", &event));
+        // values of different types are never ordered
+        assert!(!matches("kind > 1", &event));
+        assert!(!matches(r#"block.contains("4")"#, &event));
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        for filter in [
+            "",
+            "block >",
+            "(block > 1",
+            "block > 1)",
+            "domain.startsWith(",
+            "domain.matches('x')",
+            "\"unterminated",
+            "block = 1",
+        ] {
+            assert!(
+                Filter::parse(filter).is_err(),
+                "{} should be invalid",
+                filter
+            );
+        }
+        let nested = format!("{}true{}", "(".repeat(40), ")".repeat(40));
+        assert!(Filter::parse(&nested).is_err());
+        assert!(Filter::parse(&"!".repeat(40)).is_err());
+    }
+}
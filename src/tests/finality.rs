@@ -0,0 +1,45 @@
+use crate::finality::{can_roll_back, get_finality, is_ingestion_stopped, Finality};
+
+#[cfg(test)]
+mod finality_status {
+    use super::*;
+
+    #[test]
+    fn test_blocks_up_to_l1_are_final() {
+        assert_eq!(get_finality(0, 100), Finality::AcceptedOnL1);
+        assert_eq!(get_finality(100, 100), Finality::AcceptedOnL1);
+    }
+
+    #[test]
+    fn test_blocks_after_l1_are_l2_only() {
+        assert_eq!(get_finality(101, 100), Finality::AcceptedOnL2);
+        assert_eq!(get_finality(-1, 100), Finality::AcceptedOnL2);
+    }
+
+    #[test]
+    fn test_rollbacks_stop_at_l1() {
+        assert!(can_roll_back(100, 100));
+        assert!(can_roll_back(150, 100));
+        assert!(!can_roll_back(99, 100));
+        assert!(!can_roll_back(-1, 100));
+    }
+
+    #[test]
+    fn test_rollbacks_wait_for_the_indexer() {
+        assert!(is_ingestion_stopped(120, 120));
+        assert!(is_ingestion_stopped(-1, 120));
+        assert!(!is_ingestion_stopped(121, 120));
+    }
+
+    #[test]
+    fn test_finality_serialization() {
+        assert_eq!(
+            serde_json::to_string(&Finality::AcceptedOnL1).unwrap(),
+            "\"accepted_on_l1\""
+        );
+        assert_eq!(
+            serde_json::to_string(&Finality::AcceptedOnL2).unwrap(),
+            "\"accepted_on_l2\""
+        );
+    }
+}
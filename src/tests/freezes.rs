@@ -0,0 +1,131 @@
+use crate::{
+    freezes::{freeze_identities, get_freeze, get_unfreeze_hash, unfreeze_identities},
+    storage::{MemoryStorage, Storage},
+    user_tokens::get_token_request_hash,
+    utils::to_hex,
+    webhooks::{
+        delivery::DELIVERIES_COLLECTION,
+        dispatch::{queue_notification, WebhookNotification},
+        insert_webhook, new_webhook, WebhookEvent,
+    },
+};
+use mongodb::bson::doc;
+use serde_json::json;
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod freezes {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    async fn storage(owner: &FieldElement) -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        for (id, to) in [("0x1", None), ("0x2", None), ("0x3", Some(10_i64))] {
+            storage
+                .insert_one(
+                    "id_owners",
+                    doc! { "id": id, "owner": to_hex(owner), "_cursor": { "from": 1, "to": to } },
+                )
+                .await
+                .unwrap();
+        }
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_freeze_and_unfreeze() {
+        let owner = FieldElement::from(0x123u32);
+        let storage = storage(&owner).await;
+        let freezes = freeze_identities(&storage, &owner, Some("leaked seed".to_string()), NOW)
+            .await
+            .unwrap();
+        // the identity transferred away isn't the caller's anymore
+        let ids: Vec<&str> = freezes.iter().map(|freeze| freeze.id.as_str()).collect();
+        assert_eq!(ids, vec!["0x1", "0x2"]);
+
+        let freeze = get_freeze(&storage, &FieldElement::ONE)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(freeze.address, to_hex(&owner));
+        assert_eq!(freeze.reason.as_deref(), Some("leaked seed"));
+        assert_eq!(freeze.frozen_at, NOW);
+
+        // freezing again doesn't duplicate the marks
+        freeze_identities(&storage, &owner, None, NOW + 1)
+            .await
+            .unwrap();
+        assert_eq!(storage.count("identity_freezes", doc! {}).await.unwrap(), 2);
+
+        // another address can't lift them
+        let other = FieldElement::from(0x456u32);
+        assert!(unfreeze_identities(&storage, &other)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            unfreeze_identities(&storage, &owner).await.unwrap().len(),
+            2
+        );
+        assert!(get_freeze(&storage, &FieldElement::ONE)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_unfreeze_hash_is_not_a_token_request() {
+        let address = FieldElement::from(0x123u32);
+        assert_ne!(
+            get_unfreeze_hash(&address, NOW),
+            get_token_request_hash(&address, NOW)
+        );
+        assert_ne!(
+            get_unfreeze_hash(&address, NOW),
+            get_unfreeze_hash(&address, NOW + 1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_queues_the_subscribed_webhooks() {
+        let storage = MemoryStorage::default();
+        for events in [
+            vec![WebhookEvent::IdentityFrozen],
+            vec![WebhookEvent::DomainExpired],
+        ] {
+            let webhook = new_webhook(
+                "braavos",
+                "https://hooks.example.com/starknetid".to_string(),
+                events,
+                None,
+                NOW,
+            )
+            .unwrap();
+            insert_webhook(&storage, &webhook).await.unwrap();
+        }
+        let notification = WebhookNotification {
+            event: WebhookEvent::IdentityFrozen,
+            key: "identity_frozen:0x1:1700000000".to_string(),
+            block: None,
+            data: json!({ "id": "0x1" }),
+        };
+        assert_eq!(
+            queue_notification(&storage, &notification, NOW)
+                .await
+                .unwrap(),
+            1
+        );
+        // queued once per webhook
+        assert_eq!(
+            queue_notification(&storage, &notification, NOW)
+                .await
+                .unwrap(),
+            0
+        );
+        assert_eq!(
+            storage.count(DELIVERIES_COLLECTION, doc! {}).await.unwrap(),
+            1
+        );
+    }
+}
@@ -0,0 +1,21 @@
+use crate::freshness::{is_stale, BlockTimestamps};
+
+#[cfg(test)]
+mod freshness {
+    use super::*;
+
+    #[test]
+    fn test_is_stale() {
+        let year = 31536000;
+        assert!(!is_stale(1_700_000_000, 1_700_000_000 + year, year));
+        assert!(is_stale(1_700_000_000, 1_700_000_001 + year, year));
+    }
+
+    #[test]
+    fn test_block_timestamps() {
+        let timestamps = BlockTimestamps::default();
+        assert_eq!(timestamps.get(10), None);
+        timestamps.insert(10, 1_700_000_000);
+        assert_eq!(timestamps.get(10), Some(1_700_000_000));
+    }
+}
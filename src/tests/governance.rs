@@ -0,0 +1,138 @@
+use crate::{
+    config::{get_unset_keys, Config},
+    governance::{
+        compute_power, get_attestation_hash, get_holdings, sign_attestation, Holdings, PowerRule,
+    },
+    storage::{MemoryStorage, Storage},
+    utils::to_hex,
+};
+use mongodb::bson::doc;
+use starknet::core::{types::FieldElement, utils::cairo_short_string_to_felt};
+
+#[cfg(test)]
+mod governance {
+    use super::*;
+
+    const YEAR: i64 = 365 * 24 * 3600;
+    const NOW: i64 = 1_700_000_000;
+
+    fn rules() -> Vec<PowerRule> {
+        vec![
+            PowerRule::DomainHeld {
+                min_days: 365,
+                power: 10,
+            },
+            PowerRule::Verified {
+                field: "twitter".to_string(),
+                power: 5,
+            },
+            PowerRule::ProofOfPersonhood { power: 20 },
+            PowerRule::Tenure {
+                per_year: 0.5,
+                max_multiplier: 2.0,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_compute_power() {
+        let power = compute_power(&rules(), &Holdings::default(), NOW);
+        assert_eq!(power.power, 0);
+        assert_eq!(power.breakdown.len(), 4);
+
+        // a recent domain only counts towards nothing
+        let recent = Holdings {
+            domain_creations: vec![NOW - YEAR / 2],
+            verified_fields: vec!["twitter".to_string()],
+            pop_verified: false,
+        };
+        // 5 from the verification, times 1.25 for half a year of tenure
+        assert_eq!(compute_power(&rules(), &recent, NOW).power, 6);
+
+        let veteran = Holdings {
+            domain_creations: vec![NOW - YEAR / 2, NOW - 5 * YEAR],
+            verified_fields: vec![],
+            pop_verified: true,
+        };
+        let power = compute_power(&rules(), &veteran, NOW);
+        // (10 + 20) times the multiplier capped at 2
+        assert_eq!(power.power, 60);
+        assert_eq!(power.breakdown[3].multiplier, Some(2.0));
+        assert_eq!(power.breakdown[0].power, 10);
+    }
+
+    #[tokio::test]
+    async fn test_get_holdings() {
+        let mut conf = Config::default();
+        conf.contracts.verifiers = vec![FieldElement::from(50u64)];
+        conf.contracts.pop_verifier = FieldElement::from(60u64);
+        let storage = MemoryStorage::default();
+        let owner = to_hex(&FieldElement::from(10u64));
+        let live = doc! { "to": null };
+        for doc in [
+            doc! { "id": "0x1", "owner": &owner, "_cursor": live.clone() },
+            doc! { "id": "0x2", "owner": "0xb", "_cursor": live.clone() },
+        ] {
+            storage.insert_one("id_owners", doc).await.unwrap();
+        }
+        for doc in [
+            doc! { "id": "0x1", "domain": "ben.stark", "creation_date": NOW - YEAR, "_cursor": live.clone() },
+            doc! { "id": "0x2", "domain": "other.stark", "creation_date": 1, "_cursor": live.clone() },
+        ] {
+            storage.insert_one("domains", doc).await.unwrap();
+        }
+        let field = |name: &str| to_hex(&cairo_short_string_to_felt(name).unwrap());
+        for doc in [
+            doc! { "id": "0x1", "field": field("twitter"), "verifier": to_hex(&FieldElement::from(50u64)), "data": "0x1", "_cursor": live.clone() },
+            // unknown verifier
+            doc! { "id": "0x1", "field": field("github"), "verifier": "0x99", "data": "0x1", "_cursor": live.clone() },
+            doc! { "id": "0x1", "field": field("proof_of_personhood"), "verifier": to_hex(&FieldElement::from(60u64)), "_cursor": live.clone() },
+        ] {
+            storage.insert_one("id_verifier_data", doc).await.unwrap();
+        }
+
+        let holdings = get_holdings(&storage, &conf.contracts, &FieldElement::from(10u64))
+            .await
+            .unwrap();
+        assert_eq!(holdings.domain_creations, vec![NOW - YEAR]);
+        assert_eq!(holdings.verified_fields, vec!["twitter".to_string()]);
+        assert!(holdings.pop_verified);
+
+        let none = get_holdings(&storage, &conf.contracts, &FieldElement::from(12u64))
+            .await
+            .unwrap();
+        assert_eq!(none, Holdings::default());
+    }
+
+    #[test]
+    fn test_sign_attestation() {
+        let mut conf = Config::default();
+        conf.governance.private_key = FieldElement::from(123456789u64);
+        let addr = FieldElement::from(10u64);
+        let attestation = sign_attestation(&conf.governance, &addr, 42, NOW).unwrap();
+        assert_eq!(
+            attestation.expiry,
+            NOW + conf.governance.attestation_validity_secs
+        );
+        let hash = get_attestation_hash(&addr, 42, attestation.expiry);
+        assert_eq!(attestation.message_hash, to_hex(&hash));
+        let parse = |value: &str| FieldElement::from_hex_be(value).unwrap();
+        assert!(starknet_crypto::verify(
+            &parse(&attestation.public_key),
+            &hash,
+            &parse(&attestation.r),
+            &parse(&attestation.s),
+        )
+        .unwrap());
+        // the power is part of the signed message
+        assert_ne!(hash, get_attestation_hash(&addr, 43, attestation.expiry));
+    }
+
+    #[test]
+    fn test_unset_keys() {
+        let mut conf = Config::default();
+        assert_eq!(get_unset_keys(&conf), vec!["governance", "transparency"]);
+        conf.governance.private_key = FieldElement::from(123456789u64);
+        assert_eq!(get_unset_keys(&conf), vec!["transparency"]);
+    }
+}
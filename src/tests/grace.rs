@@ -0,0 +1,116 @@
+use crate::{
+    config::GracePolicy,
+    grace::{get_expiry_status, DomainDisplay, ExpiryStatus},
+};
+
+const NOW: i64 = 1_700_000_000;
+const GRACE: i64 = 30 * 24 * 3600;
+
+fn policy(resolve_in_grace: bool, resolve_expired: bool) -> GracePolicy {
+    GracePolicy {
+        grace_secs: GRACE,
+        resolve_in_grace,
+        resolve_expired,
+    }
+}
+
+#[cfg(test)]
+mod expiry_status {
+    use super::*;
+
+    #[test]
+    fn test_boundaries() {
+        assert_eq!(
+            get_expiry_status(Some(NOW + 1), NOW, GRACE),
+            ExpiryStatus::Active
+        );
+        // expires at the second of its expiry
+        assert_eq!(
+            get_expiry_status(Some(NOW), NOW, GRACE),
+            ExpiryStatus::Grace
+        );
+        assert_eq!(
+            get_expiry_status(Some(NOW - GRACE + 1), NOW, GRACE),
+            ExpiryStatus::Grace
+        );
+        assert_eq!(
+            get_expiry_status(Some(NOW - GRACE), NOW, GRACE),
+            ExpiryStatus::Expired
+        );
+    }
+
+    #[test]
+    fn test_without_expiry() {
+        assert_eq!(get_expiry_status(None, NOW, GRACE), ExpiryStatus::Active);
+    }
+
+    #[test]
+    fn test_without_grace_period() {
+        assert_eq!(get_expiry_status(Some(NOW), NOW, 0), ExpiryStatus::Expired);
+        assert_eq!(
+            get_expiry_status(Some(NOW + 1), NOW, 0),
+            ExpiryStatus::Active
+        );
+    }
+
+    #[test]
+    fn test_serialization() {
+        assert_eq!(
+            serde_json::to_string(&ExpiryStatus::Grace).unwrap(),
+            r#""grace""#
+        );
+        assert!(ExpiryStatus::Active.is_active());
+        assert!(!ExpiryStatus::Expired.is_active());
+    }
+}
+
+#[cfg(test)]
+mod policy {
+    use super::*;
+
+    #[test]
+    fn test_all_policies() {
+        let statuses = [
+            ExpiryStatus::Active,
+            ExpiryStatus::Grace,
+            ExpiryStatus::Expired,
+        ];
+        for (resolve_in_grace, resolve_expired, expected) in [
+            (false, false, [true, false, false]),
+            (true, false, [true, true, false]),
+            (false, true, [true, false, true]),
+            (true, true, [true, true, true]),
+        ] {
+            let policy = policy(resolve_in_grace, resolve_expired);
+            for (status, resolves) in statuses.iter().zip(expected) {
+                assert_eq!(policy.resolves(*status), resolves, "{:?}", status);
+            }
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let policy = policy(true, false);
+        assert_eq!(
+            policy.get_display(Some(NOW - 1), NOW),
+            DomainDisplay {
+                status: ExpiryStatus::Grace,
+                resolves: true,
+            }
+        );
+        assert_eq!(
+            policy.get_display(Some(NOW - GRACE - 1), NOW),
+            DomainDisplay {
+                status: ExpiryStatus::Expired,
+                resolves: false,
+            }
+        );
+        assert_eq!(
+            policy.get_display(None, NOW),
+            DomainDisplay {
+                status: ExpiryStatus::Active,
+                resolves: true,
+            }
+        );
+    }
+}
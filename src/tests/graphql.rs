@@ -0,0 +1,34 @@
+use crate::graphql::{
+    get_schema,
+    types::{parse_felt, parse_field},
+};
+
+#[cfg(test)]
+mod graphql {
+    use super::*;
+
+    #[test]
+    fn test_parse_felts() {
+        let one = "0x0000000000000000000000000000000000000000000000000000000000000001";
+        assert_eq!(parse_felt("0x1").unwrap(), one);
+        assert_eq!(parse_felt("1").unwrap(), one);
+        assert!(parse_felt("0xzz").is_err());
+        assert_eq!(
+            parse_field("twitter").unwrap(),
+            "0x0000000000000000000000000000000000000000000000000074776974746572"
+        );
+        assert_eq!(parse_field("0x1").unwrap(), one);
+    }
+
+    #[test]
+    fn test_schema() {
+        let sdl = get_schema().sdl();
+        for field in [
+            "domain(name: String!)",
+            "renewals: [Renewal!]!",
+            "verifierData(",
+        ] {
+            assert!(sdl.contains(field), "missing {}", field);
+        }
+    }
+}
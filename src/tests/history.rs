@@ -0,0 +1,84 @@
+use crate::{
+    history::{addr_to_domain_at, domain_to_addr_at},
+    storage::{MemoryStorage, Storage},
+    utils::to_hex,
+};
+use mongodb::bson::{doc, Bson, Document};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod history {
+    use super::*;
+
+    fn addr(value: u64) -> String {
+        to_hex(&FieldElement::from(value))
+    }
+
+    fn cursor(from: i64, to: Option<i64>) -> Document {
+        doc! { "from": from, "to": to.map_or(Bson::Null, Bson::Int64) }
+    }
+
+    // ben.stark moved at block 200 from identity 0x1 of address 10 to identity 0x2 of address 11
+    async fn storage() -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        let documents = [
+            (
+                "domains",
+                doc! { "domain": "ben.stark", "id": "0x1", "rev_address": addr(10), "expiry": 1_i64, "_cursor": cursor(100, Some(200)) },
+            ),
+            (
+                "domains",
+                doc! { "domain": "ben.stark", "id": "0x2", "expiry": 2_i64, "_cursor": cursor(200, None) },
+            ),
+            (
+                "id_owners",
+                doc! { "id": "0x1", "owner": addr(10), "_cursor": cursor(50, None) },
+            ),
+            (
+                "id_owners",
+                doc! { "id": "0x2", "owner": addr(11), "_cursor": cursor(150, None) },
+            ),
+        ];
+        for (collection, document) in documents {
+            storage.insert_one(collection, document).await.unwrap();
+        }
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_domain_to_addr_at() {
+        let storage = storage().await;
+        assert_eq!(
+            domain_to_addr_at(&storage, "ben.stark", 99).await.unwrap(),
+            None
+        );
+        let before = domain_to_addr_at(&storage, "ben.stark", 150)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(before.addr, Some(addr(10)));
+        assert_eq!(before.domain_expiry, Some(1));
+        // the write at a block is already visible at that block
+        let after = domain_to_addr_at(&storage, "ben.stark", 200)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(after.addr, Some(addr(11)));
+        assert_eq!(after.id.as_deref(), Some("0x2"));
+    }
+
+    #[tokio::test]
+    async fn test_addr_to_domain_at() {
+        let storage = storage().await;
+        let address = FieldElement::from(10_u64);
+        let resolution = addr_to_domain_at(&storage, &address, 150)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolution.domain, "ben.stark");
+        assert_eq!(
+            addr_to_domain_at(&storage, &address, 250).await.unwrap(),
+            None
+        );
+    }
+}
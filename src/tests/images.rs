@@ -0,0 +1,95 @@
+use crate::{
+    config::Config,
+    images::{check_fetchable, get_cache_key, get_content_type, read_body, resize},
+};
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use std::io::Cursor;
+
+#[cfg(test)]
+mod images {
+    use super::*;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Cursor::new(vec![]);
+        DynamicImage::ImageRgba8(RgbaImage::new(width, height))
+            .write_to(&mut bytes, ImageFormat::Png)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    fn dimensions(bytes: &[u8]) -> (u32, u32) {
+        let image = image::load_from_memory(bytes).unwrap();
+        (image.width(), image.height())
+    }
+
+    #[test]
+    fn test_get_content_type() {
+        assert_eq!(get_content_type(&png(1, 1)).as_deref(), Some("image/png"));
+        let svg = b"<?xml version=\"1.0\"?>\n<SVG xmlns=\"http://www.w3.org/2000/svg\"></SVG>";
+        assert_eq!(get_content_type(svg).as_deref(), Some("image/svg+xml"));
+        assert_eq!(get_content_type(b"{\"image\": \"ipfs://\"}"), None);
+    }
+
+    #[test]
+    fn test_cache_keys() {
+        let url = "https://ipfs.io/ipfs/Qm/a.png";
+        assert_eq!(get_cache_key(url, None).len(), 64);
+        assert_eq!(
+            get_cache_key(url, Some(128)),
+            format!("{}_w128", get_cache_key(url, None))
+        );
+        assert_ne!(
+            get_cache_key(url, None),
+            get_cache_key("https://ipfs.io/ipfs/Qm/b.png", None)
+        );
+    }
+
+    #[test]
+    fn test_resize_keeps_the_aspect_ratio() {
+        let resized = resize(&png(300, 150), 128).unwrap();
+        assert_eq!(get_content_type(&resized).as_deref(), Some("image/png"));
+        assert_eq!(dimensions(&resized), (128, 64));
+        // not upscaled
+        assert_eq!(dimensions(&resize(&png(40, 20), 128).unwrap()), (40, 20));
+        assert!(resize(b"not an image", 128).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_read_body_is_capped() {
+        // a streamed body has no content length to refuse it upfront
+        let response =
+            |body: &'static [u8]| reqwest::Response::from(axum::http::Response::new(body));
+        assert_eq!(
+            &read_body(response(b"12345"), 5).await.unwrap()[..],
+            b"12345"
+        );
+        assert!(read_body(response(b"123456"), 5).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_check_fetchable() {
+        let mut config = Config::default();
+        config.variables.ipfs_gateways = vec!["http://127.0.0.1:8080/ipfs/".to_string()];
+        assert!(check_fetchable(&config, "http://8.8.8.8/a.png")
+            .await
+            .is_ok());
+        // the configured gateways may be local
+        assert!(
+            check_fetchable(&config, "http://127.0.0.1:8080/ipfs/Qm/a.png")
+                .await
+                .is_ok()
+        );
+        for url in [
+            "http://127.0.0.1:8081/a.png",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::ffff:10.0.0.1]/a.png",
+            "file:///etc/passwd",
+        ] {
+            assert!(
+                check_fetchable(&config, url).await.is_err(),
+                "{} should be refused",
+                url
+            );
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use crate::incidents::{get_backtrace_hash, new_incident_id};
+
+#[cfg(test)]
+mod incidents {
+    use super::*;
+
+    #[test]
+    fn test_backtrace_hash_ignores_addresses() {
+        let first = "   0: api::handler\n             at src/handler.rs:10\n   1: 0x55d1c2a1";
+        let second = "   0: api::handler\n             at src/handler.rs:10\n   1: 0x7f00aa11";
+        assert_eq!(get_backtrace_hash(first), get_backtrace_hash(second));
+        let other = "   0: api::other_handler\n             at src/handler.rs:10";
+        assert_ne!(get_backtrace_hash(first), get_backtrace_hash(other));
+    }
+
+    #[test]
+    fn test_incident_ids() {
+        let id = new_incident_id();
+        assert_eq!(id.len(), 16);
+        assert_ne!(id, new_incident_id());
+    }
+}
@@ -0,0 +1,195 @@
+use crate::{
+    config::{Config, GracePolicy},
+    integrity::{check_integrity, get_fix_calls, get_target, Fix, Mismatch},
+    storage::{MemoryStorage, Storage},
+    utils::to_hex,
+};
+use mongodb::bson::doc;
+use starknet::core::types::FieldElement;
+
+const NOW: i64 = 1_700_000_000;
+
+#[cfg(test)]
+mod integrity {
+    use super::*;
+
+    fn grace() -> GracePolicy {
+        GracePolicy {
+            grace_secs: 30 * 24 * 3600,
+            resolve_in_grace: true,
+            resolve_expired: false,
+        }
+    }
+
+    fn addr(value: u64) -> String {
+        to_hex(&FieldElement::from(value))
+    }
+
+    async fn insert(storage: &MemoryStorage, collection: &str, document: mongodb::bson::Document) {
+        storage.insert_one(collection, document).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_consistent() {
+        let storage = MemoryStorage::default();
+        insert(&storage, "domains", doc! { "domain": "ben.stark", "id": "0x1", "rev_address": addr(10), "expiry": NOW + 100 }).await;
+        insert(
+            &storage,
+            "id_owners",
+            doc! { "id": "0x1", "owner": addr(10), "main": true },
+        )
+        .await;
+        let integrity = check_integrity(&storage, &FieldElement::from(10_u64), &grace(), NOW)
+            .await
+            .unwrap();
+        assert!(integrity.consistent);
+        assert_eq!(integrity.domain.as_deref(), Some("ben.stark"));
+        assert_eq!(integrity.target, Some(addr(10)));
+    }
+
+    #[tokio::test]
+    async fn test_no_reverse_record() {
+        let storage = MemoryStorage::default();
+        let integrity = check_integrity(&storage, &FieldElement::from(10_u64), &grace(), NOW)
+            .await
+            .unwrap();
+        assert!(!integrity.consistent);
+        assert_eq!(integrity.mismatches[0].mismatch, Mismatch::NoReverseRecord);
+        assert_eq!(integrity.mismatches[0].fix, Fix::SetAddressToDomain);
+    }
+
+    #[tokio::test]
+    async fn test_target_of_another_owner() {
+        let storage = MemoryStorage::default();
+        // the starknet field of the identity points elsewhere
+        insert(&storage, "domains", doc! { "domain": "ben.stark", "id": "0x1", "rev_address": addr(10), "expiry": NOW + 100 }).await;
+        insert(
+            &storage,
+            "id_owners",
+            doc! { "id": "0x1", "owner": addr(11) },
+        )
+        .await;
+        insert(&storage, "id_user_data", doc! { "id": "0x1", "field": "0x000000000000000000000000000000000000000000000000737461726b6e6574", "data": addr(12) }).await;
+        let integrity = check_integrity(&storage, &FieldElement::from(10_u64), &grace(), NOW)
+            .await
+            .unwrap();
+        let fixes: Vec<Fix> = integrity.mismatches.iter().map(|d| d.fix).collect();
+        assert_eq!(fixes, vec![Fix::SetDomainToAddress, Fix::RequestFromOwner]);
+        assert_eq!(integrity.target, Some(addr(12)));
+    }
+
+    #[tokio::test]
+    async fn test_main_id_and_expiry() {
+        let storage = MemoryStorage::default();
+        insert(&storage, "domains", doc! { "domain": "ben.stark", "id": "0x1", "rev_address": addr(10), "expiry": NOW - 100 }).await;
+        insert(
+            &storage,
+            "id_owners",
+            doc! { "id": "0x1", "owner": addr(10) },
+        )
+        .await;
+        insert(
+            &storage,
+            "id_owners",
+            doc! { "id": "0x2", "owner": addr(10), "main": true },
+        )
+        .await;
+        let integrity = check_integrity(&storage, &FieldElement::from(10_u64), &grace(), NOW)
+            .await
+            .unwrap();
+        let fixes: Vec<Fix> = integrity.mismatches.iter().map(|d| d.fix).collect();
+        assert_eq!(fixes, vec![Fix::SetMainId, Fix::Renew]);
+    }
+
+    #[tokio::test]
+    async fn test_main_id_without_domain() {
+        let storage = MemoryStorage::default();
+        insert(
+            &storage,
+            "id_owners",
+            doc! { "id": "0x2", "owner": addr(10), "main": true },
+        )
+        .await;
+        let integrity = check_integrity(&storage, &FieldElement::from(10_u64), &grace(), NOW)
+            .await
+            .unwrap();
+        assert_eq!(
+            integrity.mismatches[0].mismatch,
+            Mismatch::MainIdWithoutDomain {
+                main_id: "0x2".to_string()
+            }
+        );
+        assert_eq!(integrity.mismatches.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_fix_calls() {
+        let storage = MemoryStorage::default();
+        // owned identity pointing elsewhere and another main identity
+        insert(&storage, "domains", doc! { "domain": "ben.stark", "id": "0x1", "rev_address": addr(10), "legacy_address": addr(12), "expiry": NOW - 100 }).await;
+        insert(
+            &storage,
+            "id_owners",
+            doc! { "id": "0x1", "owner": addr(10) },
+        )
+        .await;
+        insert(
+            &storage,
+            "id_owners",
+            doc! { "id": "0x2", "owner": addr(10), "main": true },
+        )
+        .await;
+        let address = FieldElement::from(10_u64);
+        let integrity = check_integrity(&storage, &address, &grace(), NOW)
+            .await
+            .unwrap();
+        let (calls, manual) = get_fix_calls(&integrity, &address, &Config::default().contracts);
+        let entrypoints: Vec<&str> = calls.iter().map(|call| call.entrypoint).collect();
+        assert_eq!(entrypoints, vec!["set_domain_to_address", "set_main_id"]);
+        assert_eq!(calls[0].calldata.len(), 3);
+        assert_eq!(calls[0].calldata[2], address);
+        assert_eq!(calls[1].calldata, vec![FieldElement::ONE]);
+        assert_eq!(manual.len(), 1);
+        assert_eq!(manual[0].fix, Fix::Renew);
+    }
+
+    #[tokio::test]
+    async fn test_foreign_identity_is_manual() {
+        let storage = MemoryStorage::default();
+        insert(
+            &storage,
+            "domains",
+            doc! { "domain": "ben.stark", "id": "0x1", "rev_address": addr(10) },
+        )
+        .await;
+        insert(
+            &storage,
+            "id_owners",
+            doc! { "id": "0x1", "owner": addr(11) },
+        )
+        .await;
+        let address = FieldElement::from(10_u64);
+        let integrity = check_integrity(&storage, &address, &grace(), NOW)
+            .await
+            .unwrap();
+        let (calls, manual) = get_fix_calls(&integrity, &address, &Config::default().contracts);
+        assert!(calls.is_empty());
+        let fixes: Vec<Fix> = manual.iter().map(|d| d.fix).collect();
+        assert_eq!(fixes, vec![Fix::SetDomainToAddress, Fix::RequestFromOwner]);
+    }
+
+    #[test]
+    fn test_target_precedence() {
+        let zero = "0x0000000000000000000000000000000000000000000000000000000000000000";
+        assert_eq!(
+            get_target(Some("0xa"), Some("0xb"), Some("0xc")),
+            Some("0xa".to_string())
+        );
+        assert_eq!(
+            get_target(Some(zero), Some("0xb"), Some("0xc")),
+            Some("0xb".to_string())
+        );
+        assert_eq!(get_target(None, None, Some("0xc")), Some("0xc".to_string()));
+        assert_eq!(get_target(None, None, None), None);
+    }
+}
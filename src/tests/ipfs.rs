@@ -0,0 +1,98 @@
+use crate::{
+    config::{Config, Variables},
+    ipfs::GatewayHealth,
+};
+
+#[cfg(test)]
+mod gateway_health {
+    use super::*;
+
+    fn get_gateways() -> Vec<String> {
+        vec![
+            "https://first.gateway/ipfs/".to_string(),
+            "https://second.gateway/ipfs/".to_string(),
+            "https://third.gateway/ipfs/".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_priority_order_when_up() {
+        let health = GatewayHealth::default();
+        let gateways = get_gateways();
+        assert_eq!(
+            health.order(&gateways, 0),
+            vec![
+                "https://first.gateway/ipfs/",
+                "https://second.gateway/ipfs/",
+                "https://third.gateway/ipfs/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failing_gateway_moved_last() {
+        let conf = Config::default().ipfs;
+        let health = GatewayHealth::default();
+        let gateways = get_gateways();
+        for _ in 0..conf.max_failures - 1 {
+            health.record(&gateways[0], false, 100, &conf);
+        }
+        // below the threshold the gateway is still preferred
+        assert_eq!(health.order(&gateways, 100)[0], gateways[0]);
+
+        health.record(&gateways[0], false, 100, &conf);
+        assert!(health.is_down(&gateways[0], 100));
+        assert_eq!(
+            health.order(&gateways, 100),
+            vec![
+                "https://second.gateway/ipfs/",
+                "https://third.gateway/ipfs/",
+                "https://first.gateway/ipfs/"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cooldown_and_recovery() {
+        let conf = Config::default().ipfs;
+        let health = GatewayHealth::default();
+        let gateways = get_gateways();
+        for _ in 0..conf.max_failures {
+            health.record(&gateways[0], false, 100, &conf);
+        }
+        assert!(health.is_down(&gateways[0], 100 + conf.cooldown_secs - 1));
+        assert!(!health.is_down(&gateways[0], 100 + conf.cooldown_secs));
+
+        // a success clears the failures counted so far
+        for _ in 0..conf.max_failures - 1 {
+            health.record(&gateways[1], false, 100, &conf);
+        }
+        health.record(&gateways[1], true, 100, &conf);
+        health.record(&gateways[1], false, 100, &conf);
+        assert!(!health.is_down(&gateways[1], 100));
+    }
+
+    #[test]
+    fn test_legacy_gateway_key() {
+        let variables = |gateways: &str| {
+            let fields = r#"
+                rpc_url = ""
+                refresh_delay = 1.0
+                discord_token = ""
+                discord_api_url = ""
+                twitter_api_key = ""
+                twitter_api_url = ""
+                github_api_url = ""
+            "#;
+            toml::from_str::<Variables>(&format!("{}\n{}", gateways, fields))
+                .unwrap()
+                .ipfs_gateways
+        };
+        assert_eq!(
+            variables("ipfs_gateway = \"https://first.gateway/ipfs/\""),
+            vec!["https://first.gateway/ipfs/".to_string()]
+        );
+        assert_eq!(variables("ipfs_gateways = []"), Vec::<String>::new());
+        assert_eq!(variables(""), vec!["https://ipfs.io/ipfs/".to_string()]);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::locale::{get_locale, Locale};
+use axum::http::{HeaderMap, HeaderValue};
+
+#[cfg(test)]
+mod response_locale {
+    use super::*;
+
+    fn accept_language(value: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("accept-language", HeaderValue::from_static(value));
+        headers
+    }
+
+    #[test]
+    fn test_get_locale() {
+        let headers = accept_language("ja, de-CH;q=0.8, fr;q=0.9");
+        assert_eq!(get_locale(None, &headers), Some(Locale::Fr));
+        // the query parameter wins over the header
+        assert_eq!(get_locale(Some("pt-BR"), &headers), Some(Locale::Pt));
+        assert_eq!(get_locale(Some("ja"), &headers), None);
+        assert_eq!(get_locale(None, &accept_language("en;q=0")), None);
+        assert_eq!(get_locale(None, &HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn test_format_date() {
+        // 2024-06-01
+        let timestamp = 1717200000;
+        assert_eq!(Locale::En.format_date(timestamp).unwrap(), "June 1, 2024");
+        assert_eq!(Locale::Fr.format_date(timestamp).unwrap(), "1 juin 2024");
+        assert_eq!(Locale::De.format_date(timestamp).unwrap(), "1. Juni 2024");
+        assert_eq!(
+            Locale::Es.format_date(timestamp).unwrap(),
+            "1 de junio de 2024"
+        );
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(Locale::En.format_number(1234567), "1,234,567");
+        assert_eq!(Locale::De.format_number(-1234), "-1.234");
+        assert_eq!(Locale::Fr.format_number(123), "123");
+        assert_eq!(Locale::Fr.format_number(12345), "12\u{202f}345");
+    }
+}
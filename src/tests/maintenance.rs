@@ -0,0 +1,26 @@
+use crate::maintenance::{get_cache_cardinalities, CacheCardinality};
+
+#[cfg(test)]
+mod maintenance {
+    use super::*;
+
+    #[test]
+    fn test_flags_oversized_caches() {
+        let caches = get_cache_cardinalities(&[("metrics", 120), ("rpc_usage", 12000)], 10000);
+        assert_eq!(
+            caches,
+            vec![
+                CacheCardinality {
+                    cache: "metrics",
+                    keys: 120,
+                    oversized: false,
+                },
+                CacheCardinality {
+                    cache: "rpc_usage",
+                    keys: 12000,
+                    oversized: true,
+                },
+            ]
+        );
+    }
+}
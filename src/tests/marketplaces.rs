@@ -0,0 +1,83 @@
+use crate::{
+    config::MarketplaceApi,
+    marketplaces::{get_batch, get_changes, get_refresh_url},
+    storage::{MemoryStorage, Storage},
+};
+use mongodb::bson::doc;
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod marketplaces {
+    use super::*;
+
+    fn changes(entries: &[(i64, &str)]) -> Vec<(i64, String)> {
+        entries
+            .iter()
+            .map(|(block, id)| (*block, id.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_refresh_url() {
+        let api = MarketplaceApi {
+            name: "opensea".to_string(),
+            url: "https://api.example.com/contract/{contract}/nfts/{token_id}/refresh".to_string(),
+            api_key_header: "x-api-key".to_string(),
+            api_key: String::new(),
+        };
+        let contract = FieldElement::from_hex_be("0x05dbdedc").unwrap();
+        let token_id = FieldElement::from_hex_be("0xff").unwrap();
+        assert_eq!(
+            get_refresh_url(&api, &contract, &token_id),
+            "https://api.example.com/contract/0x0000000000000000000000000000000000000000000000000000000005dbdedc/nfts/255/refresh"
+        );
+    }
+
+    #[test]
+    fn test_batch_keeps_whole_blocks() {
+        let all = changes(&[
+            (10, "0x1"),
+            (10, "0x2"),
+            (11, "0x1"),
+            (12, "0x3"),
+            (12, "0x4"),
+        ]);
+        let (last_block, ids) = get_batch(&all, 3);
+        // block 12 would go over the budget
+        assert_eq!(last_block, Some(11));
+        assert_eq!(ids.into_iter().collect::<Vec<_>>(), vec!["0x1", "0x2"]);
+
+        let (last_block, ids) = get_batch(&all, 1);
+        assert_eq!(last_block, Some(10));
+        assert_eq!(ids.len(), 2);
+
+        assert_eq!(get_batch(&[], 10), (None, Default::default()));
+    }
+
+    #[tokio::test]
+    async fn test_changes() {
+        let storage = MemoryStorage::default();
+        for (collection, id, block, field) in [
+            ("domains", "0x1", 5, None),
+            ("domains", "0x2", 12, None),
+            ("id_owners", "0x3", 11, None),
+            (
+                "id_verifier_data",
+                "0x4",
+                13,
+                Some(crate::endpoints::uri::NFT_PP_ID),
+            ),
+            ("id_verifier_data", "0x5", 14, Some("0x74776974746572")),
+        ] {
+            let mut document = doc! { "id": id, "_cursor": { "from": block as i64, "to": null } };
+            if let Some(field) = field {
+                document.insert("field", field);
+            }
+            storage.insert_one(collection, document).await.unwrap();
+        }
+        assert_eq!(
+            get_changes(&storage, 10).await.unwrap(),
+            changes(&[(11, "0x3"), (12, "0x2"), (13, "0x4")])
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use crate::metrics::{labeled, Metrics};
+
+#[cfg(test)]
+mod metrics_registry {
+    use super::*;
+
+    #[test]
+    fn test_counters_and_gauges() {
+        let metrics = Metrics::default();
+        metrics.add("requests", 2);
+        metrics.add("requests", -1);
+        metrics.set("max", 10);
+        metrics.set("max", 5);
+        assert_eq!(metrics.get("requests"), 1);
+        assert_eq!(metrics.get("max"), 5);
+        assert_eq!(metrics.get("missing"), 0);
+    }
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::default();
+        metrics.set(&labeled("mongo_pool_max_connections", "db", "sales"), 50);
+        metrics.add("b_total", 3);
+        assert_eq!(
+            metrics.render(),
+            "b_total 3\nmongo_pool_max_connections{db=\"sales\"} 50\n"
+        );
+    }
+}
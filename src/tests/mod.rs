@@ -1 +1,80 @@
+mod activity;
+mod address_labels;
+mod addressbook;
+mod analytics;
+mod auto_renewal;
+mod avatars;
+mod badges;
+mod cache;
+mod calendar;
+mod clients;
+mod clubs;
+mod compatibility;
+mod cors;
+mod descriptions;
+mod display_address;
+mod distribution;
+mod domain_history;
+mod encoding;
+mod errors;
+mod examples;
+mod expiring;
+mod exports;
+mod failures;
+mod fallback;
+mod filters;
+mod finality;
+mod freezes;
+mod freshness;
+mod governance;
+mod grace;
+mod graphql;
+mod history;
+mod images;
+mod incidents;
+mod integrity;
+mod ipfs;
+mod locale;
+mod maintenance;
+mod marketplaces;
+mod metrics;
+mod naming_actions;
+mod networks;
+mod normalization;
+mod notifications;
+mod openapi;
+mod organizations;
+mod pagination;
+mod partners;
+mod portfolio;
+mod pricing;
+mod profanity;
+mod quotes;
+mod raffle;
+mod rate_limit;
+mod records;
+mod reminders;
+mod rendering;
+mod request_tracing;
+mod retention;
+mod routes;
+mod rpc_queue;
+mod rpc_usage;
+mod sampling;
+mod search;
+mod simulation;
+mod socials;
+mod starknet_addr;
+mod status;
+mod storage;
+mod streaming;
+mod suggestions;
+mod test_vectors;
+mod transparency;
+mod ui;
+mod uris;
+mod user_tokens;
 mod utils;
+mod verifiers;
+mod webhooks;
+mod ws;
@@ -0,0 +1,114 @@
+use crate::naming_actions::{
+    decode_event, decode_labels, describe, parse_events, NamingAction, RawEvent,
+};
+use serde_json::json;
+use starknet::{core::types::FieldElement, macros::selector};
+use starknet_id::encode;
+
+#[cfg(test)]
+mod naming_actions {
+    use super::*;
+
+    fn event(
+        from_address: FieldElement,
+        keys: Vec<FieldElement>,
+        data: Vec<FieldElement>,
+    ) -> RawEvent {
+        RawEvent {
+            from_address,
+            keys,
+            data,
+        }
+    }
+
+    #[test]
+    fn test_registration() {
+        let (naming, starknetid) = (FieldElement::from(100_u64), FieldElement::from(200_u64));
+        let mint = event(
+            naming,
+            vec![
+                selector!("DomainMint"),
+                FieldElement::ONE,
+                encode("ben").unwrap(),
+            ],
+            vec![
+                FieldElement::from(42_u64),
+                FieldElement::from(1_000_000_000_u64 + 2 * 31_536_000),
+            ],
+        );
+        let action = decode_event(&mint, &naming, &starknetid, Some(1_000_000_000)).unwrap();
+        assert_eq!(
+            action,
+            NamingAction::DomainRegistered {
+                domain: "ben.stark".to_string(),
+                id: "42".to_string(),
+                expiry: 1_063_072_000,
+                years: Some(2),
+            }
+        );
+        assert_eq!(describe(&action), "registered ben.stark for 2 years");
+
+        // the same event from another contract isn't decoded
+        assert_eq!(decode_event(&mint, &starknetid, &naming, None), None);
+    }
+
+    #[test]
+    fn test_identity_events() {
+        let (naming, starknetid) = (FieldElement::from(100_u64), FieldElement::from(200_u64));
+        let mint = event(
+            starknetid,
+            vec![
+                selector!("Transfer"),
+                FieldElement::ZERO,
+                FieldElement::from(7_u64),
+                FieldElement::from(42_u64),
+                FieldElement::ZERO,
+            ],
+            vec![],
+        );
+        assert_eq!(
+            describe(&decode_event(&mint, &naming, &starknetid, None).unwrap()),
+            format!(
+                "minted identity 42 for {}",
+                "0x0000000000000000000000000000000000000000000000000000000000000007"
+            )
+        );
+
+        let user_data = event(
+            starknetid,
+            vec![selector!("UserDataUpdate"), FieldElement::from(42_u64)],
+            vec![
+                FieldElement::from_hex_be("0x74776974746572").unwrap(),
+                FieldElement::ONE,
+            ],
+        );
+        assert_eq!(
+            describe(&decode_event(&user_data, &naming, &starknetid, None).unwrap()),
+            "updated the twitter data of identity 42"
+        );
+    }
+
+    #[test]
+    fn test_labels() {
+        let labels = [encode("sub").unwrap(), encode("ben").unwrap()];
+        assert_eq!(decode_labels(&labels), "sub.ben.stark");
+    }
+
+    #[test]
+    fn test_receipt_events() {
+        let receipt = json!({
+            "events": [
+                { "from_address": "0x1", "keys": ["0x2"], "data": ["0x3", "0x4"] },
+                { "from_address": "invalid", "keys": [], "data": [] },
+            ]
+        });
+        assert_eq!(
+            parse_events(&receipt),
+            vec![event(
+                FieldElement::ONE,
+                vec![FieldElement::TWO],
+                vec![FieldElement::THREE, FieldElement::from(4_u64)]
+            )]
+        );
+    }
+}
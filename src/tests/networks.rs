@@ -0,0 +1,103 @@
+use crate::config::{Config, Database, Network};
+use crate::networks::{get_route_collisions, split_network};
+use crate::routes::ROUTES;
+use axum::http::Uri;
+
+#[cfg(test)]
+mod networks {
+    use super::*;
+
+    fn split(uri: &str) -> Result<(Option<String>, String), String> {
+        split_network(&uri.parse::<Uri>().unwrap(), &["mainnet", "sepolia"])
+    }
+
+    #[test]
+    fn test_split_network_prefix() {
+        assert_eq!(
+            split("/sepolia/domain_to_addr?domain=ben.stark"),
+            Ok((
+                Some("sepolia".to_string()),
+                "/domain_to_addr?domain=ben.stark".to_string()
+            ))
+        );
+        assert_eq!(
+            split("/sepolia"),
+            Ok((Some("sepolia".to_string()), "/".to_string()))
+        );
+        // a route starting like a network isn't one
+        assert_eq!(
+            split("/sepolia_stats"),
+            Ok((None, "/sepolia_stats".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_split_network_param() {
+        assert_eq!(
+            split("/domain_to_addr?domain=ben.stark&network=sepolia"),
+            Ok((
+                Some("sepolia".to_string()),
+                "/domain_to_addr?domain=ben.stark".to_string()
+            ))
+        );
+        assert_eq!(
+            split("/addrs_to_domains?network=mainnet"),
+            Ok((Some("mainnet".to_string()), "/addrs_to_domains".to_string()))
+        );
+        // the other parameters keep their encoding
+        assert_eq!(
+            split("/compare?domains=a.stark%2Cb.stark"),
+            Ok((None, "/compare?domains=a.stark%2Cb.stark".to_string()))
+        );
+        assert_eq!(
+            split("/uri?id=1&network=goerli"),
+            Err("Unknown network: goerli".to_string())
+        );
+    }
+
+    #[test]
+    fn test_route_collisions() {
+        assert_eq!(
+            get_route_collisions(["mainnet", "status", "sepolia", "org"], ROUTES),
+            vec!["status".to_string(), "org".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_config_for_network() {
+        let mut conf = Config::default();
+        let database = |name: &str| Database {
+            name: name.to_string(),
+            connection_string: "mongodb://sepolia".to_string(),
+        };
+        let mut contracts = conf.contracts.clone();
+        contracts.naming = starknet::core::types::FieldElement::ONE;
+        conf.networks.others.insert(
+            "sepolia".to_string(),
+            Network {
+                starknetid: database("sepolia_starknetid"),
+                sales: database("sepolia_sales"),
+                free_domains: database("sepolia_free_domains"),
+                rpc_url: "https://sepolia.example.com".to_string(),
+                contracts,
+            },
+        );
+
+        let sepolia = conf.for_network("sepolia").unwrap();
+        assert_eq!(sepolia.databases.starknetid.name, "sepolia_starknetid");
+        assert_eq!(sepolia.databases.sales.name, "sepolia_sales");
+        assert_eq!(sepolia.variables.rpc_url, "https://sepolia.example.com");
+        assert_eq!(
+            sepolia.contracts.naming,
+            starknet::core::types::FieldElement::ONE
+        );
+        assert_eq!(sepolia.networks.default, "sepolia");
+        assert!(sepolia.networks.others.is_empty());
+        // the pool and the other sections are shared
+        assert_eq!(
+            sepolia.databases.pool.max_connections,
+            conf.databases.pool.max_connections
+        );
+        assert!(conf.for_network("goerli").is_none());
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{
+    normalization::{get_domain_skeleton, get_skeleton, sync_skeletons},
+    search::search_domains,
+    storage::{MemoryStorage, Storage},
+};
+use mongodb::bson::doc;
+
+#[cfg(test)]
+mod normalization {
+    use super::*;
+
+    #[test]
+    fn test_skeletons() {
+        assert_eq!(get_skeleton("москва"), "moskva");
+        assert_eq!(get_skeleton("Café"), "cafe");
+        assert_eq!(get_skeleton("东京"), "dongjing");
+        assert_eq!(get_skeleton("ben-42"), "ben-42");
+        assert_eq!(
+            get_domain_skeleton("αθηνα.stark"),
+            get_skeleton("αθηνα") + ".stark"
+        );
+    }
+
+    async fn storage() -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        for (domain, id, block) in [
+            ("moskva.stark", "0x1", 1_i64),
+            ("москва.stark", "0x2", 2),
+            ("mozart.stark", "0x3", 3),
+        ] {
+            storage
+                .insert_one(
+                    "domains",
+                    doc! { "domain": domain, "id": id, "_cursor": { "from": block, "to": null } },
+                )
+                .await
+                .unwrap();
+        }
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_sync_only_adds_unicode_domains() {
+        let storage = storage().await;
+        assert_eq!(sync_skeletons(&storage, 0).await.unwrap(), 2);
+        let skeletons = storage
+            .find("domain_skeletons", doc! {}, Default::default())
+            .await
+            .unwrap();
+        assert_eq!(skeletons.len(), 1);
+        assert_eq!(skeletons[0].get_str("skeleton").unwrap(), "moskva.stark");
+        // nothing new past the last block synced
+        assert_eq!(sync_skeletons(&storage, 2).await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_search_finds_transliterated_domains() {
+        let storage = storage().await;
+        sync_skeletons(&storage, 0).await.unwrap();
+        let found = search_domains(&storage, "mosk", None, 10).await.unwrap();
+        let domains: Vec<&str> = found.iter().map(|m| m.domain.as_str()).collect();
+        assert_eq!(domains, vec!["moskva.stark", "москва.stark"]);
+
+        // the unicode domains sort last and are paged like the others
+        let first = search_domains(&storage, "mo", None, 2).await.unwrap();
+        let domains: Vec<&str> = first.iter().map(|m| m.domain.as_str()).collect();
+        assert_eq!(domains, vec!["moskva.stark", "mozart.stark"]);
+        let second = search_domains(&storage, "mo", Some("mozart.stark"), 2)
+            .await
+            .unwrap();
+        let domains: Vec<&str> = second.iter().map(|m| m.domain.as_str()).collect();
+        assert_eq!(domains, vec!["москва.stark"]);
+    }
+}
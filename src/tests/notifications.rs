@@ -0,0 +1,42 @@
+use crate::{
+    notifications::{
+        get_notifications, get_reverification_key, notify, Notification, NotificationKind,
+    },
+    storage::MemoryStorage,
+};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod notifications {
+    use super::*;
+
+    fn notification(key: &str, created_at: i64) -> Notification {
+        Notification {
+            address: "0x0000000000000000000000000000000000000000000000000000000000000001"
+                .to_string(),
+            kind: NotificationKind::Reverification,
+            key: key.to_string(),
+            message: "verify again".to_string(),
+            created_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_notifications_are_sent_once() {
+        let storage = MemoryStorage::default();
+        let key = get_reverification_key("0x1", "0x74776974746572", 100);
+        assert!(notify(&storage, notification(&key, 1)).await.unwrap());
+        assert!(!notify(&storage, notification(&key, 2)).await.unwrap());
+        // a new verification is reminded again
+        let key = get_reverification_key("0x1", "0x74776974746572", 200);
+        assert!(notify(&storage, notification(&key, 3)).await.unwrap());
+
+        let inbox = get_notifications(&storage, &FieldElement::ONE)
+            .await
+            .unwrap();
+        assert_eq!(inbox.len(), 2);
+        // latest first
+        assert_eq!(inbox[0].created_at, 3);
+        assert_eq!(inbox[1].created_at, 1);
+    }
+}
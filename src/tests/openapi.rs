@@ -0,0 +1,122 @@
+use crate::openapi::{get_input, get_openapi, to_openapi_path, Input};
+use crate::routes::{AuthScope, ROUTES};
+use regex::Regex;
+use serde_json::Value;
+use std::{fs, path::Path};
+
+#[cfg(test)]
+mod openapi {
+    use super::*;
+
+    fn collect_inputs(dir: &Path, re: &Regex, input: &Regex, handlers: &mut Vec<String>) {
+        for entry in fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            if path.is_dir() {
+                collect_inputs(&path, re, input, handlers);
+            } else if path.extension().map_or(false, |ext| ext == "rs") {
+                let source = fs::read_to_string(&path).unwrap();
+                if let (Some(caps), true) = (re.captures(&source), input.is_match(&source)) {
+                    handlers.push(caps[1].to_string());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_openapi_path() {
+        assert_eq!(
+            to_openapi_path("/domain/:domain/owner_at"),
+            (
+                "/domain/{domain}/owner_at".to_string(),
+                vec!["domain".to_string()]
+            )
+        );
+        assert_eq!(
+            to_openapi_path("/ui/*path"),
+            ("/ui/{path}".to_string(), vec!["path".to_string()])
+        );
+        assert_eq!(to_openapi_path("/uri"), ("/uri".to_string(), vec![]));
+    }
+
+    #[test]
+    fn test_every_route_is_documented() {
+        let document = serde_json::to_value(get_openapi(ROUTES)).unwrap();
+        for spec in ROUTES {
+            let method = serde_json::to_value(spec.method).unwrap();
+            let (path, _) = to_openapi_path(spec.path);
+            let operation = &document["paths"][&path][method.as_str().unwrap()];
+            assert!(operation.is_object(), "{} is not documented", spec.path);
+            assert!(
+                operation["responses"]["200"].is_object(),
+                "{} has no success response",
+                spec.path
+            );
+        }
+    }
+
+    #[test]
+    fn test_every_handler_input_is_documented() {
+        let re = Regex::new(r#"#\[route\(\s*\w+,\s*"[^"]*",\s*crate::([\w:]+),?\s*\)"#).unwrap();
+        let input = Regex::new(r"(Query|Json)<[A-Z]\w*>").unwrap();
+        let mut handlers = vec![];
+        collect_inputs(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src/endpoints"),
+            &re,
+            &input,
+            &mut handlers,
+        );
+        assert!(!handlers.is_empty());
+        for handler in handlers {
+            assert!(
+                get_input(&handler).is_some(),
+                "{} input is not documented",
+                handler
+            );
+        }
+    }
+
+    #[test]
+    fn test_felt_params_are_strings() {
+        let params = match get_input("endpoints::addr_to_domain") {
+            Some(Input::Query(params)) => serde_json::to_value(params).unwrap(),
+            _ => panic!("expected query parameters"),
+        };
+        assert_eq!(params[0]["name"], "addr");
+        assert_eq!(params[0]["in"], "query");
+        assert_eq!(params[0]["required"], true);
+        assert_eq!(params[0]["schema"]["type"], "string");
+
+        let params = match get_input("endpoints::domains::search") {
+            Some(Input::Query(params)) => serde_json::to_value(params).unwrap(),
+            _ => panic!("expected query parameters"),
+        };
+        let limit = params
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|param| param["name"] == "limit")
+            .unwrap();
+        assert_eq!(limit["required"], false);
+    }
+
+    #[test]
+    fn test_body_and_security() {
+        let document = serde_json::to_value(get_openapi(ROUTES)).unwrap();
+        let claim = &document["paths"]["/contracts/claim"]["post"];
+        let schema = &claim["requestBody"]["content"]["application/json"]["schema"];
+        assert_eq!(schema["properties"]["contract"]["type"], "string");
+        assert_eq!(schema["properties"]["signature"]["items"]["type"], "string");
+        assert_eq!(claim["security"], Value::Null);
+
+        let partner = ROUTES
+            .iter()
+            .find(|spec| spec.auth == AuthScope::Partner)
+            .unwrap();
+        let (path, _) = to_openapi_path(partner.path);
+        let method = serde_json::to_value(partner.method).unwrap();
+        let security = &document["paths"][&path][method.as_str().unwrap()]["security"];
+        assert_eq!(security[0]["api_key"], Value::Array(vec![]));
+        assert_eq!(security[1]["admin_key"], Value::Array(vec![]));
+        assert!(document["components"]["schemas"]["FailureBody"].is_object());
+    }
+}
@@ -0,0 +1,35 @@
+use crate::organizations::get_member_hash;
+
+#[cfg(test)]
+mod org_members {
+    use super::*;
+
+    #[test]
+    fn test_member_hash_binds_every_field() {
+        let hash = get_member_hash("dao.stark", "alice.stark", "admin", "cto", 1700000000);
+        assert_eq!(
+            hash,
+            get_member_hash("dao.stark", "alice.stark", "admin", "cto", 1700000000)
+        );
+        assert_ne!(
+            hash,
+            get_member_hash("other.stark", "alice.stark", "admin", "cto", 1700000000)
+        );
+        assert_ne!(
+            hash,
+            get_member_hash("dao.stark", "bob.stark", "admin", "cto", 1700000000)
+        );
+        assert_ne!(
+            hash,
+            get_member_hash("dao.stark", "alice.stark", "", "cto", 1700000000)
+        );
+        assert_ne!(
+            hash,
+            get_member_hash("dao.stark", "alice.stark", "admin", "", 1700000000)
+        );
+        assert_ne!(
+            hash,
+            get_member_hash("dao.stark", "alice.stark", "admin", "cto", 1700000001)
+        );
+    }
+}
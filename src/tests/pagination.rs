@@ -0,0 +1,194 @@
+use crate::endpoints::events::{get_events::get_projection, stream::parse_filters};
+use crate::pagination::{get_limit, get_sort, PageCursor, BLOCK_ORDER};
+use crate::storage::{FindSpec, MemoryStorage, Storage};
+use mongodb::bson::{doc, oid::ObjectId};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod page_cursor {
+    use super::*;
+
+    #[test]
+    fn test_encode_decode_roundtrip() {
+        let cursor = PageCursor {
+            key: 123456,
+            id: ObjectId::new(),
+        };
+        assert_eq!(PageCursor::decode(&cursor.encode()), Some(cursor));
+    }
+
+    #[test]
+    fn test_decode_invalid() {
+        assert_eq!(PageCursor::decode(""), None);
+        assert_eq!(PageCursor::decode("abc_def"), None);
+        assert_eq!(PageCursor::decode("12_nothex"), None);
+    }
+
+    #[test]
+    fn test_from_nested_field() {
+        let id = ObjectId::new();
+        let doc = doc! { "_id": id, "_cursor": { "from": 42_i64 } };
+        assert_eq!(
+            PageCursor::from_document(&doc, "_cursor.from"),
+            Some(PageCursor { key: 42, id })
+        );
+    }
+
+    #[test]
+    fn test_cursor_of_projected_block() {
+        // addr_to_full_ids projects the `_cursor.from` of its identities as `block`
+        let id = ObjectId::new();
+        let doc = doc! { "_id": id, "id": "0x1", "block": 7_i64 };
+        let cursor = PageCursor::from_document(&doc, "block").unwrap();
+        assert_eq!(
+            cursor.after("_cursor.from"),
+            doc! { "$or": [
+                { "_cursor.from": { "$gt": 7_i64 } },
+                { "_cursor.from": 7_i64, "_id": { "$gt": id } },
+            ] }
+        );
+    }
+
+    #[test]
+    fn test_from_missing_field() {
+        let doc = doc! { "_id": ObjectId::new() };
+        assert_eq!(PageCursor::from_document(&doc, "expiry"), None);
+    }
+}
+
+#[cfg(test)]
+mod limits {
+    use super::*;
+
+    #[test]
+    fn test_get_limit() {
+        assert_eq!(get_limit(None, 100), 100);
+        assert_eq!(get_limit(Some(10), 100), 10);
+        assert_eq!(get_limit(Some(1000), 100), 100);
+        assert_eq!(get_limit(Some(-5), 100), 1);
+    }
+}
+
+#[cfg(test)]
+mod ordering {
+    use super::*;
+
+    #[test]
+    fn test_sort_keeps_the_key_order() {
+        let keys: Vec<&str> = get_sort(BLOCK_ORDER).keys().map(String::as_str).collect();
+        assert_eq!(keys, BLOCK_ORDER);
+    }
+
+    async fn get_page(storage: &MemoryStorage, after: Option<&PageCursor>) -> Vec<String> {
+        let filter = after.map_or(doc! {}, |cursor| cursor.after("_cursor.from"));
+        let spec = FindSpec {
+            sort: Some(get_sort(BLOCK_ORDER)),
+            limit: Some(2),
+            ..Default::default()
+        };
+        storage
+            .find("events", filter, spec)
+            .await
+            .unwrap()
+            .iter()
+            .map(|doc| {
+                PageCursor::from_document(doc, "_cursor.from")
+                    .unwrap()
+                    .encode()
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_pages_during_writes() {
+        let storage = MemoryStorage::default();
+        let mut expected = vec![];
+        // every event of a block shares its sort key
+        for block in [5_i64, 5, 5, 6, 6] {
+            let id = ObjectId::new();
+            expected.push(PageCursor { key: block, id }.encode());
+            let event = doc! { "_id": id, "_cursor": { "from": block } };
+            storage.insert_one("events", event).await.unwrap();
+        }
+        let mut seen = vec![];
+        let mut after = None;
+        loop {
+            let page = get_page(&storage, after.as_ref()).await;
+            // a write landing between two pages is seen at the end of the list
+            let event = doc! { "_id": ObjectId::new(), "_cursor": { "from": 7_i64 } };
+            storage.insert_one("events", event).await.unwrap();
+            match page.last() {
+                Some(last) if seen.len() < expected.len() => {
+                    after = PageCursor::decode(last);
+                    seen.extend(page);
+                }
+                _ => break,
+            }
+        }
+        assert_eq!(seen[..expected.len()], expected[..]);
+    }
+}
+
+#[cfg(test)]
+mod projection {
+    use super::*;
+
+    #[test]
+    fn test_projection_fields() {
+        let projection = get_projection("domain, owner").unwrap();
+        assert_eq!(projection.get_i32("domain").unwrap(), 1);
+        assert_eq!(projection.get_i32("owner").unwrap(), 1);
+        assert_eq!(projection.get_i32("_cursor").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_projection_rejects_operators() {
+        assert!(get_projection("$where").is_err());
+        assert!(get_projection("domain,..owner").is_err());
+    }
+}
+
+#[cfg(test)]
+mod stream_filters {
+    use super::*;
+
+    fn collections() -> HashMap<String, String> {
+        HashMap::from([
+            ("naming".to_string(), "naming_events".to_string()),
+            ("identity".to_string(), "identity_events".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_empty_filter_streams_everything() {
+        let filters = parse_filters("", &collections()).unwrap();
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters["naming_events"], None);
+        assert_eq!(filters["identity_events"], None);
+    }
+
+    #[test]
+    fn test_filter_by_type() {
+        let filters =
+            parse_filters("naming:domain_mint, naming:domain_transfer", &collections()).unwrap();
+        assert_eq!(filters.len(), 1);
+        assert_eq!(
+            filters["naming_events"],
+            Some(vec![
+                "domain_mint".to_string(),
+                "domain_transfer".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_bare_contract_overrides_types() {
+        let filters = parse_filters("naming:domain_mint,naming", &collections()).unwrap();
+        assert_eq!(filters["naming_events"], None);
+    }
+
+    #[test]
+    fn test_unknown_contract() {
+        assert!(parse_filters("pricing", &collections()).is_err());
+    }
+}
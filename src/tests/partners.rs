@@ -0,0 +1,36 @@
+use crate::partners::{get_owed, to_csv, PeriodRevenue};
+
+#[cfg(test)]
+mod partners {
+    use super::*;
+
+    #[test]
+    fn test_owed() {
+        assert_eq!(get_owed(1000, 0.1), 100);
+        assert_eq!(get_owed(999, 0.1), 99);
+        assert_eq!(get_owed(1000, 0.0), 0);
+    }
+
+    #[test]
+    fn test_csv() {
+        let revenues = vec![
+            PeriodRevenue {
+                period: "2024-01".to_string(),
+                registrations: 3,
+                revenue: 1500,
+                owed: 150,
+            },
+            PeriodRevenue {
+                period: "2024-02".to_string(),
+                registrations: 1,
+                revenue: 500,
+                owed: 50,
+            },
+        ];
+        assert_eq!(
+            to_csv(&revenues),
+            "period,registrations,revenue,owed\n2024-01,3,1500,150\n2024-02,1,500,50\n"
+        );
+        assert_eq!(to_csv(&[]), "period,registrations,revenue,owed\n");
+    }
+}
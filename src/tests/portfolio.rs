@@ -0,0 +1,93 @@
+use crate::{
+    config::Config,
+    portfolio::{check_target, get_transfer_calls, get_transfer_items},
+    storage::{MemoryStorage, Storage},
+    utils::to_hex,
+};
+use mongodb::bson::doc;
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod portfolio {
+    use super::*;
+
+    fn felt(value: u64) -> FieldElement {
+        FieldElement::from(value)
+    }
+
+    #[test]
+    fn test_check_target() {
+        let mut config = Config::default();
+        config.contracts.starknetid = felt(100);
+        config.portfolio.denied_addresses = vec![felt(7)];
+        config.portfolio.denied_class_hashes = vec![felt(99)];
+        let check = |to: u64, class_hash: Option<u64>| {
+            check_target(
+                &config.portfolio,
+                &config.contracts,
+                &felt(1),
+                &felt(to),
+                class_hash.map(felt).as_ref(),
+            )
+        };
+        assert!(check(2, Some(50)).is_ok());
+        assert!(check(1, Some(50)).is_err());
+        assert!(check(100, Some(50)).is_err());
+        assert!(check(7, Some(50)).is_err());
+        assert!(check(2, Some(99)).is_err());
+        // not deployed yet
+        assert!(check(2, None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_transfer_items() {
+        let storage = MemoryStorage::default();
+        for (id, owner, domain) in [
+            (1, 10, "ben.stark"),
+            (2, 10, "fricoben.stark"),
+            (3, 11, "other.stark"),
+        ] {
+            storage
+                .insert_one("id_owners", doc! { "id": to_hex(&felt(id)), "owner": to_hex(&felt(owner)), "_cursor": { "to": null } })
+                .await
+                .unwrap();
+            storage
+                .insert_one(
+                    "domains",
+                    doc! { "domain": domain, "id": to_hex(&felt(id)), "_cursor": { "to": null } },
+                )
+                .await
+                .unwrap();
+        }
+
+        // the domain of an identity also selected is moved once
+        let items = get_transfer_items(
+            &storage,
+            &felt(10),
+            &[felt(1)],
+            &["ben.stark".to_string(), "fricoben.stark".to_string()],
+        )
+        .await
+        .unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].domain.as_deref(), Some("ben.stark"));
+        assert_eq!(items[1].id, felt(2));
+
+        let calls = get_transfer_calls(felt(100), felt(10), felt(20), &items);
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[1].to, felt(100));
+        assert_eq!(
+            calls[1].calldata,
+            vec![felt(10), felt(20), felt(2), FieldElement::ZERO]
+        );
+
+        assert!(get_transfer_items(&storage, &felt(10), &[felt(3)], &[])
+            .await
+            .is_err());
+        assert!(
+            get_transfer_items(&storage, &felt(10), &[], &["unknown.stark".to_string()])
+                .await
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{
+    endpoints::domain::availability::{get_availability, Availability},
+    grace::{DomainDisplay, ExpiryStatus},
+    pricing::{compute_buy_price, get_label_length, get_price_per_day, get_price_per_year},
+};
+
+#[cfg(test)]
+mod pricing {
+    use super::*;
+
+    #[test]
+    fn test_price_by_length() {
+        assert_eq!(get_price_per_day(0), None);
+        assert_eq!(get_price_per_day(1), Some(1068493150684932));
+        assert_eq!(get_price_per_day(3), Some(200000000000000));
+        assert_eq!(get_price_per_day(5), Some(24657534246575));
+        assert_eq!(get_price_per_day(40), get_price_per_day(5));
+    }
+
+    #[test]
+    fn test_buy_price() {
+        assert_eq!(compute_buy_price(4, 2), Some(2 * 73972602739726));
+        assert_eq!(get_price_per_year(5), Some(365 * 24657534246575));
+        assert_eq!(compute_buy_price(0, 365), None);
+    }
+
+    #[test]
+    fn test_label_length_counts_characters() {
+        assert_eq!(get_label_length("ben"), 3);
+        assert_eq!(get_label_length("这来"), 2);
+    }
+}
+
+#[cfg(test)]
+mod availability {
+    use super::*;
+
+    fn display(status: ExpiryStatus, resolves: bool) -> Option<DomainDisplay> {
+        Some(DomainDisplay { status, resolves })
+    }
+
+    #[test]
+    fn test_availability() {
+        assert_eq!(get_availability(false, None), Availability::Free);
+        assert_eq!(get_availability(true, None), Availability::Reserved);
+        assert_eq!(
+            get_availability(false, display(ExpiryStatus::Active, true)),
+            Availability::Taken
+        );
+        assert_eq!(
+            get_availability(false, display(ExpiryStatus::Grace, true)),
+            Availability::Taken
+        );
+        assert_eq!(
+            get_availability(false, display(ExpiryStatus::Expired, false)),
+            Availability::Free
+        );
+        // an expired reserved label goes back to being reserved
+        assert_eq!(
+            get_availability(true, display(ExpiryStatus::Expired, false)),
+            Availability::Reserved
+        );
+    }
+}
@@ -0,0 +1,44 @@
+use crate::profanity::{normalize_label, ProfanityFilter};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod profanity_filter {
+    use super::*;
+
+    fn filter() -> ProfanityFilter {
+        ProfanityFilter {
+            terms: HashMap::from([
+                (
+                    "en".to_string(),
+                    vec!["shit".to_string(), "ass".to_string()],
+                ),
+                ("fr".to_string(), vec!["salope".to_string()]),
+            ]),
+        }
+    }
+
+    #[test]
+    fn test_normalize_label() {
+        assert_eq!(normalize_label("Sh1t"), "shit");
+        assert_eq!(normalize_label("s-h_i-t"), "shit");
+        assert_eq!(normalize_label("$@l0pe"), "salope");
+    }
+
+    #[test]
+    fn test_blocks_obfuscated_terms() {
+        let filter = filter();
+        assert_eq!(filter.find_match("bullsh1t.stark"), Some(("en", "shit")));
+        assert_eq!(
+            filter.find_match("sub.s4lope.stark"),
+            Some(("fr", "salope"))
+        );
+    }
+
+    #[test]
+    fn test_short_terms_match_whole_labels() {
+        let filter = filter();
+        assert!(!filter.is_allowed("ass.stark"));
+        assert!(filter.is_allowed("assets.stark"));
+        assert!(filter.is_allowed("ben.stark"));
+    }
+}
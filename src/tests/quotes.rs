@@ -0,0 +1,13 @@
+use crate::quotes::get_deviation;
+
+#[cfg(test)]
+mod quotes {
+    use super::*;
+
+    #[test]
+    fn test_deviation() {
+        assert_eq!(get_deviation(1000.0, 1000.0), 0.0);
+        assert!((get_deviation(1050.0, 1000.0) - 0.05).abs() < 1e-9);
+        assert!((get_deviation(500.0, 1000.0) - 0.5).abs() < 1e-9);
+    }
+}
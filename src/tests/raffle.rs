@@ -0,0 +1,73 @@
+use crate::raffle::{draw_winners, get_commitment, get_entrants_hash};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod draw_winners {
+    use super::*;
+
+    fn entrants() -> Vec<String> {
+        (0..20).map(|i| format!("0x{:064x}", i)).collect()
+    }
+
+    #[test]
+    fn test_draw_is_deterministic() {
+        let block_hash = FieldElement::from(1234_u64);
+        let first = draw_winners(&[1; 32], &block_hash, &entrants(), 5);
+        let second = draw_winners(&[1; 32], &block_hash, &entrants(), 5);
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 5);
+    }
+
+    #[test]
+    fn test_draw_ignores_entrants_order() {
+        let block_hash = FieldElement::from(1234_u64);
+        let mut reversed = entrants();
+        reversed.reverse();
+        assert_eq!(
+            draw_winners(&[1; 32], &block_hash, &entrants(), 5),
+            draw_winners(&[1; 32], &block_hash, &reversed, 5)
+        );
+    }
+
+    #[test]
+    fn test_draw_depends_on_block_hash() {
+        let first = draw_winners(&[1; 32], &FieldElement::from(1_u64), &entrants(), 5);
+        let second = draw_winners(&[1; 32], &FieldElement::from(2_u64), &entrants(), 5);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_draw_has_unique_winners() {
+        let mut entrants = entrants();
+        entrants.extend(entrants.clone());
+        let mut winners = draw_winners(&[7; 32], &FieldElement::ONE, &entrants, 20);
+        assert_eq!(winners.len(), 20);
+        winners.sort();
+        winners.dedup();
+        assert_eq!(winners.len(), 20);
+    }
+
+    #[test]
+    fn test_draw_more_winners_than_entrants() {
+        let winners = draw_winners(&[7; 32], &FieldElement::ONE, &entrants()[..3], 10);
+        assert_eq!(winners.len(), 3);
+    }
+
+    #[test]
+    fn test_draw_no_entrants() {
+        assert!(draw_winners(&[7; 32], &FieldElement::ONE, &[], 10).is_empty());
+    }
+
+    #[test]
+    fn test_commitment_changes_with_seed() {
+        assert_ne!(get_commitment(&[1; 32]), get_commitment(&[2; 32]));
+        assert_eq!(get_commitment(&[1; 32]).len(), 66);
+    }
+
+    #[test]
+    fn test_entrants_hash_ignores_order() {
+        let mut reversed = entrants();
+        reversed.reverse();
+        assert_eq!(get_entrants_hash(&entrants()), get_entrants_hash(&reversed));
+    }
+}
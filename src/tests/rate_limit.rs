@@ -0,0 +1,114 @@
+use crate::{
+    auth::{ApiKey, ApiKeyCache},
+    config::{BurstTier, ClassLimits, RateLimits},
+    rate_limit::{get_forwarded_ip, RateLimiter},
+    routes::RateClass,
+};
+use mongodb::bson::oid::ObjectId;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod rate_limit {
+    use super::*;
+
+    #[test]
+    fn test_fixed_windows() {
+        let limiter = RateLimiter::default();
+        let quota = limiter.hit("ip:1.2.3.4", RateClass::Heavy, 2, 60, 125);
+        assert_eq!((quota.used, quota.remaining, quota.reset), (1, 1, 55));
+        limiter.hit("ip:1.2.3.4", RateClass::Heavy, 2, 60, 130);
+        let quota = limiter.hit("ip:1.2.3.4", RateClass::Heavy, 2, 60, 179);
+        assert_eq!((quota.used, quota.remaining, quota.reset), (3, 0, 1));
+
+        // other classes and callers are counted apart
+        let quota = limiter.get("ip:1.2.3.4", RateClass::Light, 2, 60, 179);
+        assert_eq!(quota.used, 0);
+        let quota = limiter.get("ip:5.6.7.8", RateClass::Heavy, 2, 60, 179);
+        assert_eq!(quota.used, 0);
+
+        // a new window starts from zero
+        let quota = limiter.get("ip:1.2.3.4", RateClass::Heavy, 2, 60, 180);
+        assert_eq!((quota.used, quota.remaining, quota.reset), (0, 2, 60));
+        let quota = limiter.hit("ip:1.2.3.4", RateClass::Heavy, 2, 60, 180);
+        assert_eq!(quota.used, 1);
+    }
+
+    #[test]
+    fn test_api_key_lookups_expire() {
+        let cache = ApiKeyCache::default();
+        let api_key = ApiKey {
+            id: ObjectId::new(),
+            key: "secret".to_string(),
+            name: "partner".to_string(),
+            namespaces: vec![],
+            enabled: true,
+            tier: None,
+        };
+        cache.insert("secret", Some(api_key), 100);
+        cache.insert("invalid", None, 100);
+        assert_eq!(
+            cache.get("secret", 110).flatten().map(|key| key.name),
+            Some("partner".to_string())
+        );
+        // the invalid keys are cached too
+        assert_eq!(
+            cache.get("invalid", 110).map(|key| key.is_none()),
+            Some(true)
+        );
+        assert!(cache.get("secret", 130).is_none());
+        assert!(cache.get("unknown", 110).is_none());
+    }
+
+    #[test]
+    fn test_api_key_limits() {
+        let limits = RateLimits {
+            enabled: true,
+            window_secs: 60,
+            light: 600,
+            heavy: 60,
+            write: 30,
+            api_key_multiplier: 10,
+            trust_forwarded_for: false,
+            burst_tiers: HashMap::new(),
+            api_key_quotas: HashMap::from([(
+                "braavos".to_string(),
+                ClassLimits {
+                    light: 20000,
+                    heavy: 2000,
+                    write: 300,
+                },
+            )]),
+        };
+        assert_eq!(limits.get_limit(RateClass::Heavy, None), 60);
+        assert_eq!(limits.get_limit(RateClass::Heavy, Some("argent")), 600);
+        assert_eq!(limits.get_limit(RateClass::Heavy, Some("braavos")), 2000);
+    }
+
+    #[test]
+    fn test_burst_credits() {
+        let limiter = RateLimiter::default();
+        let tier = BurstTier {
+            capacity: 2.0,
+            refill_per_sec: 0.5,
+        };
+        assert_eq!(limiter.get_credits("key:partner", &tier, 100.0), 2.0);
+        assert!(limiter.take_credit("key:partner", &tier, 100.0));
+        assert!(limiter.take_credit("key:partner", &tier, 100.0));
+        assert!(!limiter.take_credit("key:partner", &tier, 101.0));
+        // refilled by half a credit per second, up to the capacity
+        assert!(limiter.take_credit("key:partner", &tier, 102.0));
+        assert_eq!(limiter.get_credits("key:partner", &tier, 1000.0), 2.0);
+    }
+
+    #[test]
+    fn test_forwarded_ip() {
+        // the client can prepend anything, only the entry of the proxy counts
+        assert_eq!(
+            get_forwarded_ip("1.1.1.1, 10.0.0.1,203.0.113.7"),
+            Some("203.0.113.7".parse().unwrap())
+        );
+        assert_eq!(get_forwarded_ip("::1"), Some("::1".parse().unwrap()));
+        assert_eq!(get_forwarded_ip("1.1.1.1, forged"), None);
+        assert_eq!(get_forwarded_ip(""), None);
+    }
+}
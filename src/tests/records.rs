@@ -0,0 +1,93 @@
+use crate::records::{Chain, CHAINS};
+use starknet::core::{types::FieldElement, utils::cairo_short_string_to_felt};
+
+const EVM: &str = "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed";
+
+#[cfg(test)]
+mod records {
+    use super::*;
+
+    fn short_strings(text: &str) -> Vec<FieldElement> {
+        text.as_bytes()
+            .chunks(31)
+            .map(|chunk| cairo_short_string_to_felt(std::str::from_utf8(chunk).unwrap()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_fields() {
+        let fields: Vec<&str> = CHAINS.iter().map(|chain| chain.get_field()).collect();
+        assert_eq!(fields, vec!["evm-address", "btc-address", "sol-address"]);
+    }
+
+    #[test]
+    fn test_evm_is_checksummed() {
+        assert_eq!(Chain::Evm.normalize(&EVM.to_lowercase()).unwrap(), EVM);
+        assert_eq!(Chain::Evm.normalize(EVM).unwrap(), EVM);
+        let bad_checksum = EVM.replace("aA", "Aa");
+        assert!(Chain::Evm.normalize(&bad_checksum).is_err());
+        assert!(Chain::Evm.normalize(&EVM[2..]).is_err());
+        assert!(Chain::Evm.normalize(&EVM[..40]).is_err());
+    }
+
+    #[test]
+    fn test_evm_decodes_from_a_felt() {
+        let felt = FieldElement::from_hex_be(EVM).unwrap();
+        assert_eq!(Chain::Evm.decode(&[felt]).unwrap(), EVM);
+        // wider than 20 bytes
+        let felt = FieldElement::from_hex_be(&format!("0x01{}", &EVM[2..])).unwrap();
+        assert!(Chain::Evm.decode(&[felt]).is_err());
+        assert!(Chain::Evm.decode(&[]).is_err());
+    }
+
+    #[test]
+    fn test_btc_base58() {
+        for address in [
+            "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2",
+            "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy",
+        ] {
+            assert_eq!(Chain::Btc.normalize(address).unwrap(), address);
+        }
+        // wrong checksum
+        assert!(Chain::Btc
+            .normalize("1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN3")
+            .is_err());
+    }
+
+    #[test]
+    fn test_btc_bech32() {
+        let segwit = "bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t4";
+        assert_eq!(Chain::Btc.normalize(segwit).unwrap(), segwit);
+        assert_eq!(
+            Chain::Btc.normalize(&segwit.to_uppercase()).unwrap(),
+            segwit
+        );
+        let taproot = "bc1p5d7rjq7g6rdk2yhzks9smlaqtedr4dekq08ge8ztwac72sfr9rusxg3297";
+        assert_eq!(Chain::Btc.normalize(taproot).unwrap(), taproot);
+        // mixed case and wrong checksum
+        assert!(Chain::Btc
+            .normalize("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kV8f3t4")
+            .is_err());
+        assert!(Chain::Btc
+            .normalize("bc1qw508d6qejxtdg4y5r3zarvary0c5xw7kv8f3t5")
+            .is_err());
+    }
+
+    #[test]
+    fn test_btc_decodes_from_short_strings() {
+        let taproot = "bc1p5d7rjq7g6rdk2yhzks9smlaqtedr4dekq08ge8ztwac72sfr9rusxg3297";
+        assert_eq!(Chain::Btc.decode(&short_strings(taproot)).unwrap(), taproot);
+    }
+
+    #[test]
+    fn test_sol() {
+        let address = "11111111111111111111111111111111";
+        assert_eq!(Chain::Sol.normalize(address).unwrap(), address);
+        assert_eq!(Chain::Sol.decode(&short_strings(address)).unwrap(), address);
+        // 31 bytes
+        assert!(Chain::Sol
+            .normalize("1111111111111111111111111111111")
+            .is_err());
+        assert!(Chain::Sol.normalize("0OIl").is_err());
+    }
+}
@@ -0,0 +1,57 @@
+use crate::reminders::{decode_email, get_reminder_key, get_threshold, render};
+use mongodb::bson::doc;
+use starknet::core::utils::cairo_short_string_to_felt;
+
+#[cfg(test)]
+mod reminders {
+    use super::*;
+
+    const DAY: i64 = 24 * 3600;
+    const NOW: i64 = 1_700_000_000;
+
+    fn felt(value: &str) -> String {
+        format!("{:#x}", cairo_short_string_to_felt(value).unwrap())
+    }
+
+    #[test]
+    fn test_threshold_is_the_closest_one() {
+        let days = [30, 7, 1];
+        assert_eq!(get_threshold(&days, NOW + 20 * DAY, NOW), Some(30));
+        assert_eq!(get_threshold(&days, NOW + 5 * DAY, NOW), Some(7));
+        assert_eq!(get_threshold(&days, NOW + DAY, NOW), Some(1));
+        assert_eq!(get_threshold(&days, NOW + 31 * DAY, NOW), None);
+        // each threshold and term is sent once
+        assert_ne!(
+            get_reminder_key("ben.stark", NOW, 7),
+            get_reminder_key("ben.stark", NOW, 1)
+        );
+    }
+
+    #[test]
+    fn test_decode_email() {
+        let short = doc! { "data": felt("ben@starknet.id") };
+        assert_eq!(decode_email(&short).as_deref(), Some("ben@starknet.id"));
+        let long = doc! {
+            "extended_data": [felt("a.very.long.mailbox.name"), felt("@starknet.id")],
+        };
+        assert_eq!(
+            decode_email(&long).as_deref(),
+            Some("a.very.long.mailbox.name@starknet.id")
+        );
+        assert_eq!(decode_email(&doc! { "data": felt("not an email") }), None);
+        assert_eq!(decode_email(&doc! {}), None);
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            render(
+                "{domain} expires in {days} days, on {expiry}",
+                "ben.stark",
+                7,
+                NOW
+            ),
+            "ben.stark expires in 7 days, on 2023-11-14"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::rendering::{
+    card::{get_font_size, parse_card_file, render_svg, Card, CardFormat},
+    theme::{get_namespaces, select_theme, Theme},
+};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod theme {
+    use super::*;
+
+    fn theme(namespace: &str) -> Theme {
+        Theme {
+            namespace: namespace.to_string(),
+            ..Theme::default()
+        }
+    }
+
+    #[test]
+    fn test_get_namespaces() {
+        assert_eq!(
+            get_namespaces("alice.braavos.stark"),
+            vec!["alice.braavos.stark", "braavos.stark", "stark"]
+        );
+        assert_eq!(get_namespaces("stark"), vec!["stark"]);
+    }
+
+    #[test]
+    fn test_select_most_specific_theme() {
+        let themes = vec![theme("stark"), theme("braavos.stark")];
+        let selected = select_theme(&themes, "alice.braavos.stark").unwrap();
+        assert_eq!(selected.namespace, "braavos.stark");
+
+        let selected = select_theme(&themes, "bob.stark").unwrap();
+        assert_eq!(selected.namespace, "stark");
+    }
+
+    #[test]
+    fn test_select_no_matching_theme() {
+        let themes = vec![theme("braavos.stark")];
+        assert!(select_theme(&themes, "alice.argent.stark").is_none());
+        // a namespace must match whole labels
+        assert!(select_theme(&themes, "alicebraavos.stark").is_none());
+    }
+
+    #[test]
+    fn test_validate_default_theme() {
+        assert!(Theme::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_color() {
+        let theme = Theme {
+            accent_color: "red".to_string(),
+            ..Theme::default()
+        };
+        assert!(theme.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_insecure_logo() {
+        let theme = Theme {
+            logo_url: Some("http://example.com/logo.png".to_string()),
+            ..Theme::default()
+        };
+        assert!(theme.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_frame() {
+        let theme = Theme {
+            frame: "hexagon".to_string(),
+            ..Theme::default()
+        };
+        assert!(theme.validate().is_err());
+    }
+}
+
+#[cfg(test)]
+mod card {
+    use super::*;
+
+    #[test]
+    fn test_parse_card_file() {
+        assert_eq!(
+            parse_card_file("12.png"),
+            Some((FieldElement::from(12_u8), CardFormat::Png))
+        );
+        assert_eq!(
+            parse_card_file("12.svg"),
+            Some((FieldElement::from(12_u8), CardFormat::Svg))
+        );
+        assert_eq!(parse_card_file("12.gif"), None);
+        assert_eq!(parse_card_file("12"), None);
+        assert_eq!(parse_card_file("ben.png"), None);
+    }
+
+    #[test]
+    fn test_long_names_shrink() {
+        assert_eq!(get_font_size("ben.stark"), 48);
+        assert!(get_font_size("averyveryverylongdomainname.stark") < 48);
+        assert_eq!(get_font_size(&"a".repeat(200)), 14);
+    }
+
+    #[test]
+    fn test_render_svg() {
+        let card = Card {
+            name: "<ben>.stark".to_string(),
+            expiry: Some(1_700_000_000),
+            picture: None,
+            logo: None,
+        };
+        let svg = render_svg(&card, &Theme::default());
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains("&lt;ben&gt;.stark"));
+        assert!(svg.contains("Expires Nov 14, 2023"));
+        assert!(svg.contains(&Theme::default().background_color));
+        assert!(!svg.contains("<image"));
+
+        let card = Card {
+            picture: Some("data:image/png;base64,AAAA".to_string()),
+            ..card
+        };
+        let theme = Theme {
+            frame: "circle".to_string(),
+            ..Theme::default()
+        };
+        let svg = render_svg(&card, &theme);
+        assert!(svg.contains(r#"href="data:image/png;base64,AAAA""#));
+        assert!(svg.contains(r#"rx="80""#));
+
+        let card = Card {
+            logo: Some("data:image/png;base64,BBBB".to_string()),
+            ..card
+        };
+        assert!(render_svg(&card, &theme).contains(r#"href="data:image/png;base64,BBBB""#));
+    }
+}
@@ -0,0 +1,34 @@
+use crate::request_tracing::{get_request_id, record_query, with_query_count, REQUEST_ID_HEADER};
+use axum::http::{HeaderMap, HeaderValue};
+
+#[cfg(test)]
+mod request_tracing {
+    use super::*;
+
+    #[test]
+    fn test_request_id() {
+        let mut headers = HeaderMap::new();
+        // a new id when the client sent none
+        assert_eq!(get_request_id(&headers).len(), 16);
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("lb-4f2a.1"));
+        assert_eq!(get_request_id(&headers), "lb-4f2a.1");
+        headers.insert(REQUEST_ID_HEADER, HeaderValue::from_static("a b"));
+        assert_ne!(get_request_id(&headers), "a b");
+        let long = HeaderValue::from_str(&"a".repeat(129)).unwrap();
+        headers.insert(REQUEST_ID_HEADER, long);
+        assert_eq!(get_request_id(&headers).len(), 16);
+    }
+
+    #[tokio::test]
+    async fn test_query_count() {
+        // nothing to count outside of a request
+        record_query();
+        let (output, queries) = with_query_count(async {
+            record_query();
+            record_query();
+            "done"
+        })
+        .await;
+        assert_eq!((output, queries), ("done", 2));
+    }
+}
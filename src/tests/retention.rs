@@ -0,0 +1,38 @@
+use crate::retention::{get_cohort_month, is_renewed_first_year};
+
+#[cfg(test)]
+mod retention {
+    use super::*;
+
+    const MARCH_2024: i64 = 1709856000;
+    const DAY: i64 = 86400;
+
+    #[test]
+    fn test_cohort_month() {
+        assert_eq!(get_cohort_month(MARCH_2024), Some("2024-03".to_string()));
+        assert_eq!(get_cohort_month(0), Some("1970-01".to_string()));
+    }
+
+    #[test]
+    fn test_first_year_renewals() {
+        let grace = 30 * DAY;
+        assert!(!is_renewed_first_year(MARCH_2024, &[], grace));
+        assert!(is_renewed_first_year(
+            MARCH_2024,
+            &[MARCH_2024 + 300 * DAY],
+            grace
+        ));
+        assert!(is_renewed_first_year(
+            MARCH_2024,
+            &[MARCH_2024 + 380 * DAY],
+            grace
+        ));
+        assert!(!is_renewed_first_year(
+            MARCH_2024,
+            &[MARCH_2024 + 400 * DAY],
+            grace
+        ));
+        // the registration itself is not a renewal
+        assert!(!is_renewed_first_year(MARCH_2024, &[MARCH_2024], grace));
+    }
+}
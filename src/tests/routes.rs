@@ -0,0 +1,64 @@
+use crate::config::{Deployment, Tier};
+use crate::routes::{check_registry, RouteGroup, ROUTES};
+use crate::ROUTE_REGISTRY;
+use std::collections::{HashMap, HashSet};
+
+#[cfg(test)]
+mod route_registry {
+    use super::*;
+
+    #[test]
+    fn test_every_route_is_declared() {
+        // the #[route] handlers register themselves in the test binary as well
+        let registered = ROUTE_REGISTRY.lock().unwrap().len();
+        assert_eq!(check_registry(registered), Ok(()));
+        assert!(check_registry(registered + 1).is_err());
+    }
+
+    #[test]
+    fn test_orderings_end_with_a_unique_key() {
+        for spec in ROUTES {
+            if let Some(ordering) = spec.ordering {
+                assert!(
+                    matches!(ordering.last(), Some(&"_id" | &"domain")),
+                    "{} pages can overlap",
+                    spec.path
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_routes_are_unique() {
+        let mut seen = HashSet::new();
+        for spec in ROUTES {
+            assert!(
+                seen.insert((spec.path, spec.method as u8)),
+                "{} is declared twice",
+                spec.path
+            );
+        }
+    }
+
+    #[test]
+    fn test_groups_disabled_per_tier() {
+        let disabled_groups = HashMap::from([
+            (Tier::Public, vec![RouteGroup::Admin, RouteGroup::Export]),
+            (Tier::Internal, vec![]),
+        ]);
+        let public = Deployment {
+            tier: Tier::Public,
+            disabled_groups: disabled_groups.clone(),
+        };
+        assert!(public.is_enabled(RouteGroup::Core));
+        assert!(!public.is_enabled(RouteGroup::Admin));
+        assert!(!public.is_enabled(RouteGroup::Export));
+
+        // tiers without an entry serve everything
+        let partner = Deployment {
+            tier: Tier::Partner,
+            disabled_groups,
+        };
+        assert!(partner.is_enabled(RouteGroup::Admin));
+    }
+}
@@ -0,0 +1,75 @@
+use crate::{
+    config::RpcLimits,
+    metrics::Metrics,
+    rpc_queue::{Priority, RpcQueue},
+};
+use std::sync::{Arc, Mutex};
+use tokio::{
+    sync::oneshot,
+    time::{sleep, Duration},
+};
+
+#[cfg(test)]
+mod priority_queue {
+    use super::*;
+
+    // fills the only slot, queues a background then an interactive call and returns
+    // the order in which they ran
+    async fn run_order(max_background_wait_ms: u64) -> Vec<Priority> {
+        let limits = RpcLimits {
+            max_concurrent: 1,
+            max_background_wait_ms,
+        };
+        let queue = Arc::new(RpcQueue::new(&limits, Arc::new(Metrics::default())));
+        let order = Arc::new(Mutex::new(vec![]));
+
+        let (release, hold) = oneshot::channel::<()>();
+        let blocker = {
+            let queue = queue.clone();
+            tokio::spawn(async move {
+                queue
+                    .run(Priority::Interactive, "starknet_call", async {
+                        let _ = hold.await;
+                    })
+                    .await
+            })
+        };
+        sleep(Duration::from_millis(20)).await;
+
+        let mut calls = vec![];
+        for priority in [Priority::Background, Priority::Interactive] {
+            let (queue, order) = (queue.clone(), order.clone());
+            calls.push(tokio::spawn(async move {
+                queue
+                    .run(priority, "starknet_call", async {
+                        order.lock().unwrap().push(priority)
+                    })
+                    .await
+            }));
+            sleep(Duration::from_millis(20)).await;
+        }
+
+        release.send(()).unwrap();
+        blocker.await.unwrap();
+        for call in calls {
+            call.await.unwrap();
+        }
+        Arc::try_unwrap(order).unwrap().into_inner().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_interactive_calls_go_first() {
+        assert_eq!(
+            run_order(60_000).await,
+            vec![Priority::Interactive, Priority::Background]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_starving_background_calls_go_first() {
+        assert_eq!(
+            run_order(0).await,
+            vec![Priority::Background, Priority::Interactive]
+        );
+    }
+}
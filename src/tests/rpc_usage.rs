@@ -0,0 +1,48 @@
+use crate::{
+    config::RpcBudget,
+    metrics::Metrics,
+    rpc_usage::{project_monthly, RpcUsage},
+};
+use chrono::{TimeZone, Utc};
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod rpc_usage {
+    use super::*;
+
+    #[test]
+    fn test_project_monthly() {
+        // 10 days into a 30 days month
+        let now = Utc.with_ymd_and_hms(2024, 6, 11, 0, 0, 0).unwrap();
+        assert_eq!(project_monthly(1000, now), 3000);
+        // the first hour isn't extrapolated from a few seconds
+        let now = Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 10).unwrap();
+        assert_eq!(project_monthly(1, now), 744);
+    }
+
+    #[test]
+    fn test_report_credits() {
+        let budget = RpcBudget {
+            monthly_credits: 100,
+            alarm_ratio: 1.0,
+            check_interval_secs: 3600,
+            default_credits: 1,
+            method_credits: HashMap::from([("starknet_getBlockWithTxHashes".to_string(), 5)]),
+        };
+        let usage = RpcUsage::default();
+        let metrics = Metrics::default();
+        usage.record_call("starknet_call", 1, &metrics);
+        usage.record_call("starknet_call", 3, &metrics);
+        usage.record_call("starknet_getBlockWithTxHashes", 1, &metrics);
+
+        let report = usage.report(&budget);
+        assert_eq!(report.credits, 7);
+        assert_eq!(report.endpoints.len(), 1);
+        let background = &report.endpoints[0];
+        assert_eq!(background.endpoint, "background");
+        assert_eq!(background.calls, 3);
+        assert_eq!(background.max_batch_size, 3);
+        assert!(report.api_keys.is_empty());
+        assert_eq!(metrics.get("rpc_calls_total{endpoint=\"background\"}"), 3);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::sampling::{is_sensitive_field, sanitize_query, scrub_headers, scrub_json};
+use axum::http::{HeaderMap, HeaderValue};
+use serde_json::json;
+
+#[cfg(test)]
+mod pii_scrubbing {
+    use super::*;
+
+    #[test]
+    fn test_scrub_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-admin-key", HeaderValue::from_static("secret"));
+        headers.insert("accept", HeaderValue::from_static("application/json"));
+        let scrubbed = scrub_headers(&headers);
+        assert_eq!(scrubbed.get_str("x-admin-key").unwrap(), "[redacted]");
+        assert_eq!(scrubbed.get_str("accept").unwrap(), "application/json");
+    }
+
+    #[test]
+    fn test_sensitive_field_substrings() {
+        assert!(is_sensitive_field("referrer_sig"));
+        assert!(is_sensitive_field("accessToken"));
+        assert!(is_sensitive_field("X-Api-Key"));
+        assert!(is_sensitive_field("code"));
+        assert!(!is_sensitive_field("encoded_domain"));
+        assert!(!is_sensitive_field("domain"));
+    }
+
+    #[test]
+    fn test_sanitize_query_redacts_secrets() {
+        assert_eq!(
+            sanitize_query("domain=ben.stark&signature=0x1,0x2&api_key=abc"),
+            "domain=ben.stark&signature=[redacted]&api_key=[redacted]"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_query_truncates() {
+        let query = format!("domain={}", "a".repeat(200));
+        assert_eq!(sanitize_query(&query).len(), "domain=".len() + 64);
+        let query = (0..100)
+            .map(|i| format!("p{}=value", i))
+            .collect::<Vec<_>>()
+            .join("&");
+        assert_eq!(sanitize_query(&query).len(), 512);
+    }
+
+    #[test]
+    fn test_scrub_nested_json() {
+        let mut body = json!({
+            "addr": "0x123",
+            "Signature": ["0x1", "0x2"],
+            "entries": [{ "email": "ben@example.com", "domain": "ben.stark" }],
+        });
+        scrub_json(&mut body);
+        assert_eq!(
+            body,
+            json!({
+                "addr": "0x123",
+                "Signature": "[redacted]",
+                "entries": [{ "email": "[redacted]", "domain": "ben.stark" }],
+            })
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{
+    search::search_domains,
+    storage::{MemoryStorage, Storage},
+};
+use mongodb::bson::{doc, Bson};
+
+#[cfg(test)]
+mod prefix_search {
+    use super::*;
+
+    async fn storage() -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        for (domain, id, to) in [
+            ("ben.stark", "0x1", Bson::Null),
+            ("benjamin.stark", "0x2", Bson::Null),
+            ("bent.stark", "0x3", Bson::Int64(100)),
+            ("sub.ben.stark", "0x4", Bson::Null),
+            ("bob.stark", "0x5", Bson::Null),
+        ] {
+            storage
+                .insert_one(
+                    "domains",
+                    doc! { "domain": domain, "id": id, "expiry": 1700000000_i64, "_cursor": { "from": 1, "to": to } },
+                )
+                .await
+                .unwrap();
+        }
+        storage
+            .insert_one(
+                "id_owners",
+                doc! { "id": "0x2", "owner": "0x123", "_cursor": { "from": 1 } },
+            )
+            .await
+            .unwrap();
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_prefix_matches() {
+        let storage = storage().await;
+        let found = search_domains(&storage, "ben", None, 10).await.unwrap();
+        let domains: Vec<&str> = found.iter().map(|m| m.domain.as_str()).collect();
+        // bent.stark was rolled back and sub.ben.stark doesn't start with the prefix
+        assert_eq!(domains, vec!["ben.stark", "benjamin.stark"]);
+        assert_eq!(found[0].owner, None);
+        assert_eq!(found[1].owner.as_deref(), Some("0x123"));
+        assert_eq!(found[1].expiry, Some(1700000000));
+    }
+
+    #[tokio::test]
+    async fn test_pages() {
+        let storage = storage().await;
+        let first = search_domains(&storage, "b", None, 2).await.unwrap();
+        assert_eq!(first.len(), 2);
+        let after = first.last().map(|m| m.domain.as_str());
+        let second = search_domains(&storage, "b", after, 2).await.unwrap();
+        let domains: Vec<&str> = second.iter().map(|m| m.domain.as_str()).collect();
+        assert_eq!(domains, vec!["bob.stark"]);
+    }
+}
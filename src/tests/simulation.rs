@@ -0,0 +1,58 @@
+use crate::simulation::{
+    classify_revert, get_execute_calldata, get_revert_reason, Call, FailureReason,
+};
+use serde_json::json;
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod simulation {
+    use super::*;
+
+    #[test]
+    fn test_execute_calldata() {
+        let call = |to: u64, calldata: Vec<FieldElement>| Call {
+            to: FieldElement::from(to),
+            entrypoint: "test",
+            selector: FieldElement::from(9_u8),
+            calldata,
+        };
+        let calls = vec![call(1, vec![FieldElement::from(7_u8)]), call(2, vec![])];
+        let expected: Vec<FieldElement> = [2_u8, 1, 9, 1, 7, 2, 9, 0]
+            .into_iter()
+            .map(FieldElement::from)
+            .collect();
+        assert_eq!(get_execute_calldata(&calls), expected);
+    }
+
+    #[test]
+    fn test_classify_revert() {
+        assert_eq!(
+            classify_revert("Error in the called contract: 'ERC20: insufficient balance'"),
+            FailureReason::InsufficientBalance
+        );
+        assert_eq!(
+            classify_revert("ERC20: insufficient allowance"),
+            FailureReason::InsufficientAllowance
+        );
+        assert_eq!(
+            classify_revert("Failure reason: 'this domain is unavailable'"),
+            FailureReason::DomainUnavailable
+        );
+        assert_eq!(classify_revert("out of gas"), FailureReason::Unknown);
+    }
+
+    #[test]
+    fn test_revert_reason() {
+        let reverted = json!([{ "transaction_trace": {
+            "execute_invocation": { "revert_reason": "ERC20: insufficient balance" }
+        }}]);
+        assert_eq!(
+            get_revert_reason(&reverted).as_deref(),
+            Some("ERC20: insufficient balance")
+        );
+        let succeeded = json!([{ "transaction_trace": {
+            "execute_invocation": { "contract_address": "0x1", "result": [] }
+        }}]);
+        assert_eq!(get_revert_reason(&succeeded), None);
+    }
+}
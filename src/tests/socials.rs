@@ -0,0 +1,87 @@
+use crate::{
+    config::Config,
+    socials::{find_identities, normalize_handle, parse_social_id, SocialType},
+    storage::{MemoryStorage, Storage},
+    utils::to_hex,
+};
+use mongodb::bson::doc;
+use starknet::{core::types::FieldElement, macros::short_string};
+
+#[cfg(test)]
+mod social_handles {
+    use super::*;
+
+    #[test]
+    fn test_normalize_handle() {
+        assert_eq!(
+            normalize_handle(" @Starknet_ID "),
+            Some("Starknet_ID".to_string())
+        );
+        assert_eq!(normalize_handle("ben.eth"), Some("ben.eth".to_string()));
+        assert_eq!(normalize_handle("@"), None);
+        assert_eq!(normalize_handle("ben/../admin"), None);
+        assert_eq!(normalize_handle(&"a".repeat(65)), None);
+    }
+
+    #[test]
+    fn test_parse_social_id() {
+        assert_eq!(
+            parse_social_id("1234567890"),
+            Some(FieldElement::from(1234567890u64))
+        );
+        assert_eq!(parse_social_id("ben1"), None);
+    }
+
+    #[test]
+    fn test_type_field() {
+        assert_eq!(SocialType::Discord.field(), short_string!("discord"));
+        assert_eq!(
+            serde_json::from_str::<SocialType>("\"github\"").unwrap(),
+            SocialType::Github
+        );
+    }
+}
+
+#[cfg(test)]
+mod social_identities {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_find_identities() {
+        let mut conf = Config::default();
+        conf.contracts.verifiers = vec![FieldElement::from(50u64)];
+        conf.contracts.old_verifier = FieldElement::from(52u64);
+        let verifier = to_hex(&FieldElement::from(50u64));
+        let old_verifier = to_hex(&FieldElement::from(52u64));
+        let twitter = to_hex(&short_string!("twitter"));
+        let social_id = FieldElement::from(777u64);
+        let data = to_hex(&social_id);
+
+        let storage = MemoryStorage::default();
+        for doc in [
+            doc! { "id": "0x1", "verifier": &verifier, "field": &twitter, "data": &data },
+            doc! { "id": "0x1", "verifier": &old_verifier, "field": &twitter, "data": &data },
+            doc! { "id": "0x2", "verifier": &verifier, "field": &twitter, "data": &data },
+            // another account, another network and an untrusted verifier
+            doc! { "id": "0x3", "verifier": &verifier, "field": &twitter, "data": "0x1" },
+            doc! { "id": "0x4", "verifier": &verifier, "field": to_hex(&short_string!("github")), "data": &data },
+            doc! { "id": "0x5", "verifier": "0x99", "field": &twitter, "data": &data },
+        ] {
+            storage.insert_one("id_verifier_data", doc).await.unwrap();
+        }
+        storage
+            .insert_one("domains", doc! { "id": "0x1", "domain": "ben.stark" })
+            .await
+            .unwrap();
+
+        let identities =
+            find_identities(&storage, &conf.contracts, SocialType::Twitter, &social_id)
+                .await
+                .unwrap();
+        assert_eq!(identities.len(), 2);
+        assert_eq!(identities[0].id, "0x1");
+        assert_eq!(identities[0].domain.as_deref(), Some("ben.stark"));
+        assert_eq!(identities[1].id, "0x2");
+        assert_eq!(identities[1].domain, None);
+    }
+}
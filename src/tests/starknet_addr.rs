@@ -0,0 +1,51 @@
+use crate::types::starknet_addr::{AddrError, StarknetAddr};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod starknet_addr {
+    use super::*;
+
+    const PADDED_ONE: &str = "0x0000000000000000000000000000000000000000000000000000000000000001";
+
+    #[test]
+    fn test_parse() {
+        let addr: StarknetAddr = "0x1".parse().unwrap();
+        assert_eq!(*addr, FieldElement::ONE);
+        assert_eq!(addr.to_string(), PADDED_ONE);
+        assert_eq!(PADDED_ONE.parse::<StarknetAddr>(), Ok(addr));
+        // surrounding spaces and decimal felts
+        assert_eq!(" 0X01 ".parse::<StarknetAddr>(), Ok(addr));
+        assert_eq!("1".parse::<StarknetAddr>(), Ok(addr));
+        assert_eq!("0x0".parse::<StarknetAddr>(), Ok(StarknetAddr::default()));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!("".parse::<StarknetAddr>(), Err(AddrError::Empty));
+        assert_eq!("0x".parse::<StarknetAddr>(), Err(AddrError::NotHex));
+        assert_eq!("0xzz".parse::<StarknetAddr>(), Err(AddrError::NotHex));
+        assert_eq!("abc".parse::<StarknetAddr>(), Err(AddrError::NotHex));
+        let too_long = format!("0x1{}", "0".repeat(64));
+        assert_eq!(too_long.parse::<StarknetAddr>(), Err(AddrError::TooLong));
+        let above_prime = format!("0x{}", "f".repeat(64));
+        assert_eq!(
+            above_prime.parse::<StarknetAddr>(),
+            Err(AddrError::OutOfRange)
+        );
+        assert_eq!(
+            AddrError::NotHex.to_string(),
+            "invalid starknet address: expected 0x followed by hex digits"
+        );
+    }
+
+    #[test]
+    fn test_serde() {
+        let addr: StarknetAddr = serde_json::from_str("\"0x1\"").unwrap();
+        assert_eq!(
+            serde_json::to_string(&addr).unwrap(),
+            format!("\"{}\"", PADDED_ONE)
+        );
+        let error = serde_json::from_str::<StarknetAddr>("\"0xzz\"").unwrap_err();
+        assert!(error.to_string().starts_with("invalid starknet address"));
+    }
+}
@@ -0,0 +1,37 @@
+use crate::status::{is_healthy, IndexerLag};
+
+#[cfg(test)]
+mod status {
+    use super::*;
+
+    fn lag(indexed_block: Option<i64>, lag_secs: Option<i64>) -> IndexerLag {
+        IndexerLag {
+            indexed_block,
+            lag_secs,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_healthy_under_the_threshold() {
+        assert!(is_healthy(true, &lag(Some(100), Some(0)), 300));
+        assert!(is_healthy(true, &lag(Some(100), Some(300)), 300));
+        assert!(!is_healthy(true, &lag(Some(100), Some(301)), 300));
+    }
+
+    #[test]
+    fn test_unhealthy_without_database_or_data() {
+        assert!(!is_healthy(false, &lag(Some(100), Some(0)), 300));
+        assert!(!is_healthy(true, &lag(None, None), 300));
+    }
+
+    #[test]
+    fn test_unknown_lag_is_healthy() {
+        let indexer = IndexerLag {
+            indexed_block: Some(100),
+            rpc_error: Some("connection refused".to_string()),
+            ..Default::default()
+        };
+        assert!(is_healthy(true, &indexer, 300));
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    descriptions::get_id_owner,
+    organizations::get_domain_owner,
+    storage::{FindSpec, MemoryStorage, Storage},
+};
+use mongodb::bson::{doc, Bson};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod memory_storage {
+    use super::*;
+
+    async fn storage() -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        for (domain, id, to) in [
+            ("ben.stark", "0x1", Bson::Int64(100)),
+            ("ben.stark", "0x2", Bson::Null),
+            ("alice.stark", "0x3", Bson::Null),
+        ] {
+            storage
+                .insert_one(
+                    "domains",
+                    doc! { "domain": domain, "id": id, "_cursor": { "from": 1, "to": to } },
+                )
+                .await
+                .unwrap();
+        }
+        storage
+            .insert_one(
+                "id_owners",
+                doc! { "id": "0x2", "owner": "0x123", "_cursor": { "from": 1 } },
+            )
+            .await
+            .unwrap();
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_live_documents_filter() {
+        let storage = storage().await;
+        assert_eq!(
+            get_domain_owner(&storage, "ben.stark").await.unwrap(),
+            Some(FieldElement::from(0x123_u64))
+        );
+        assert_eq!(
+            get_domain_owner(&storage, "alice.stark").await.unwrap(),
+            None
+        );
+        assert_eq!(
+            get_id_owner(&storage, &FieldElement::from(2_u64))
+                .await
+                .unwrap(),
+            Some(FieldElement::from(0x123_u64))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_operators_and_sort() {
+        let storage = storage().await;
+        let filter = doc! {
+            "$or": [{ "domain": "alice.stark" }, { "_cursor.to": { "$gte": 50 } }],
+        };
+        assert_eq!(storage.count("domains", filter).await.unwrap(), 2);
+        let filter = doc! { "id": { "$in": ["0x1", "0x3"] }, "_cursor.to": { "$exists": true } };
+        assert_eq!(storage.count("domains", filter).await.unwrap(), 2);
+
+        let spec = FindSpec {
+            sort: Some(doc! { "id": -1 }),
+            skip: 1,
+            limit: Some(1),
+        };
+        let found = storage.find("domains", doc! {}, spec).await.unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].get_str("id").unwrap(), "0x2");
+    }
+
+    #[tokio::test]
+    async fn test_upsert_and_delete() {
+        let storage = MemoryStorage::default();
+        let filter = doc! { "id": "0x1" };
+        storage
+            .upsert_one(
+                "id_descriptions",
+                filter.clone(),
+                doc! { "description": "gm" },
+            )
+            .await
+            .unwrap();
+        storage
+            .upsert_one(
+                "id_descriptions",
+                filter.clone(),
+                doc! { "description": "gn" },
+            )
+            .await
+            .unwrap();
+        let found = storage
+            .find_one("id_descriptions", filter.clone())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(found, doc! { "id": "0x1", "description": "gn" });
+        assert_eq!(
+            storage
+                .delete_many("id_descriptions", filter)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(storage.count("id_descriptions", doc! {}).await.unwrap(), 0);
+    }
+}
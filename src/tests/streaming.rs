@@ -0,0 +1,65 @@
+use crate::streaming::{stream_json_array, Envelope};
+use futures::{stream, StreamExt};
+use serde_json::{json, Map, Value};
+use std::convert::Infallible;
+
+#[cfg(test)]
+mod json_array {
+    use super::*;
+
+    async fn collect(
+        envelope: Envelope,
+        items: Vec<Result<Value, String>>,
+    ) -> Result<String, String> {
+        let chunks: Vec<_> = stream_json_array(&envelope, stream::iter(items))
+            .collect()
+            .await;
+        let mut body = Vec::new();
+        for chunk in chunks {
+            body.extend_from_slice(&chunk.map_err(|e| e.to_string())?);
+        }
+        Ok(String::from_utf8(body).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_array() {
+        let items = vec![Ok(json!({ "domain": "ben.stark" })), Ok(json!(1))];
+        let body = collect(Envelope::Array, items).await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            json!([{ "domain": "ben.stark" }, 1])
+        );
+        assert_eq!(collect(Envelope::Array, vec![]).await.unwrap(), "[]");
+    }
+
+    #[tokio::test]
+    async fn test_object_with_extra_fields() {
+        let envelope = Envelope::Object {
+            field: "full_ids",
+            extra: Map::from_iter([("next_cursor".to_string(), json!("12_ab"))]),
+        };
+        let body = collect(envelope, vec![Ok(json!("0x1"))]).await.unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&body).unwrap(),
+            json!({ "full_ids": ["0x1"], "next_cursor": "12_ab" })
+        );
+        let body = collect(Envelope::object("ids"), vec![]).await.unwrap();
+        assert_eq!(body, r#"{"ids":[]}"#);
+    }
+
+    #[tokio::test]
+    async fn test_error_aborts_the_body() {
+        let items = vec![Ok(json!(1)), Err("cursor closed".to_string())];
+        assert_eq!(
+            collect(Envelope::Array, items).await,
+            Err("cursor closed".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_infallible_items() {
+        let items = stream::iter([1, 2, 3]).map(Ok::<_, Infallible>);
+        let chunks: Vec<_> = stream_json_array(&Envelope::Array, items).collect().await;
+        assert_eq!(chunks.len(), 5);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::{
+    locale::Locale,
+    profanity::ProfanityFilter,
+    suggestions::{Dictionary, SuggestionDictionaries},
+};
+use starknet_id::encode;
+use std::collections::HashMap;
+
+#[cfg(test)]
+mod suggestions {
+    use super::*;
+
+    fn dictionary() -> Dictionary {
+        Dictionary::parse(
+            "# comment\n\
+             coffee, cafe, brew\n\
+             \n\
+             shop, store\n\
+             market, Store\n\
+             sun, sunny, café\n",
+        )
+    }
+
+    fn profanity() -> ProfanityFilter {
+        ProfanityFilter {
+            terms: HashMap::from([("en".to_string(), vec!["brew".to_string()])]),
+        }
+    }
+
+    #[test]
+    fn test_synonyms_of_the_label() {
+        let candidates = dictionary().get_candidates("coffee", &ProfanityFilter::default());
+        assert_eq!(candidates, vec!["cafe", "brew"]);
+        // the groups of a word are merged
+        let candidates = dictionary().get_candidates("store", &ProfanityFilter::default());
+        assert_eq!(candidates, vec!["shop", "market"]);
+    }
+
+    #[test]
+    fn test_replaces_the_parts_of_the_label() {
+        let candidates = dictionary().get_candidates("coffeeshop", &ProfanityFilter::default());
+        assert_eq!(candidates, vec!["cafeshop", "brewshop", "coffeestore"]);
+    }
+
+    #[test]
+    fn test_filters_profane_and_invalid_candidates() {
+        let candidates = dictionary().get_candidates("coffee", &profanity());
+        assert_eq!(candidates, vec!["cafe"]);
+        // "café" is kept as its skeleton
+        let candidates = dictionary().get_candidates("sun", &ProfanityFilter::default());
+        assert_eq!(candidates, vec!["sunny", "cafe"]);
+        assert!(dictionary()
+            .get_candidates("unknown", &ProfanityFilter::default())
+            .is_empty());
+    }
+
+    #[test]
+    fn test_falls_back_to_english() {
+        let dictionaries = SuggestionDictionaries {
+            dictionaries: HashMap::from([
+                (Locale::En, Dictionary::parse("coffee, brew")),
+                (Locale::Fr, Dictionary::parse("cafe, expresso")),
+            ]),
+        };
+        let french = dictionaries.get(Some(Locale::Fr)).unwrap();
+        assert_eq!(
+            french.get_candidates("cafe", &ProfanityFilter::default()),
+            vec!["expresso"]
+        );
+        for locale in [Some(Locale::De), None] {
+            let english = dictionaries.get(locale).unwrap();
+            assert_eq!(
+                english.get_candidates("coffee", &ProfanityFilter::default()),
+                vec!["brew"]
+            );
+        }
+        assert!(SuggestionDictionaries::default().get(None).is_none());
+    }
+
+    #[test]
+    fn test_shipped_dictionaries_are_valid_labels() {
+        for locale in ["en", "fr", "es"] {
+            let data =
+                std::fs::read_to_string(format!("./src/suggestions/dictionaries/{}.txt", locale))
+                    .unwrap();
+            let dictionary = Dictionary::parse(&data);
+            assert!(!dictionary.is_empty());
+            for word in data
+                .lines()
+                .filter(|line| !line.starts_with('#'))
+                .flat_map(|line| line.split(','))
+            {
+                assert!(encode(word.trim()).is_ok(), "{} in {}", word, locale);
+            }
+        }
+    }
+}
@@ -0,0 +1,34 @@
+use crate::test_vectors::{get_encoding_vectors, get_normalization_vectors, get_test_vectors};
+
+#[cfg(test)]
+mod test_vectors {
+    use super::*;
+
+    #[test]
+    fn test_encoding_round_trips() {
+        let vectors = get_encoding_vectors();
+        assert!(!vectors.is_empty());
+        for vector in vectors {
+            assert_eq!(vector.decoded, vector.label);
+        }
+    }
+
+    #[test]
+    fn test_normalization_vectors() {
+        let vectors = get_normalization_vectors();
+        let domains: Vec<_> = vectors
+            .iter()
+            .filter(|vector| vector.function == "normalize_domain")
+            .collect();
+        assert!(domains
+            .iter()
+            .all(|vector| vector.output.ends_with("ben.stark")));
+    }
+
+    #[test]
+    fn test_vectors_are_stable() {
+        let first = serde_json::to_string(&get_test_vectors()).unwrap();
+        let second = serde_json::to_string(&get_test_vectors()).unwrap();
+        assert_eq!(first, second);
+    }
+}
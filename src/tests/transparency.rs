@@ -0,0 +1,85 @@
+use crate::transparency::{
+    inclusion_proof, leaf_hash, merkle_root, verify_inclusion, Hash, SubtreeCache,
+    TransparencyEntry,
+};
+
+#[cfg(test)]
+mod transparency_log {
+    use super::*;
+
+    fn leaves(count: usize) -> Vec<Hash> {
+        (0..count)
+            .map(|i| {
+                leaf_hash(&TransparencyEntry {
+                    kind: "sol_subdomain".to_string(),
+                    subject: format!("{}.sol", i),
+                    target: "0x123".to_string(),
+                    message_hash: "0x456".to_string(),
+                    signature_r: "0x1".to_string(),
+                    signature_s: "0x2".to_string(),
+                    issued_at: 1717200000 + i as i64,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_root() {
+        // sha256 of the empty string (RFC 6962)
+        assert_eq!(
+            hex::encode(merkle_root(&[])),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+    }
+
+    #[test]
+    fn test_inclusion_proofs() {
+        for size in 1..=13 {
+            let leaves = leaves(size);
+            let root = merkle_root(&leaves);
+            for index in 0..size {
+                let path = inclusion_proof(index, &leaves);
+                assert!(verify_inclusion(
+                    index as u64,
+                    size as u64,
+                    &leaves[index],
+                    &path,
+                    &root
+                ));
+                // the proof doesn't hold for another leaf
+                let other = leaves[(index + 1) % size];
+                assert_eq!(
+                    verify_inclusion(index as u64, size as u64, &other, &path, &root),
+                    size == 1 && other == leaves[index]
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_cached_subtrees_match_the_tree() {
+        let leaves = leaves(13);
+        let mut cache = SubtreeCache::default();
+        assert_eq!(cache.root(0), Some(merkle_root(&[])));
+        for leaf in &leaves {
+            cache.push(*leaf);
+        }
+        for size in 1..=13 {
+            assert_eq!(cache.root(size), Some(merkle_root(&leaves[..size])));
+            for index in 0..size {
+                assert_eq!(
+                    cache.proof(index, size),
+                    Some((leaves[index], inclusion_proof(index, &leaves[..size])))
+                );
+            }
+        }
+        assert_eq!(cache.root(14), None);
+        assert_eq!(cache.proof(13, 13), None);
+    }
+
+    #[test]
+    fn test_root_changes_on_append() {
+        let leaves = leaves(6);
+        assert_ne!(merkle_root(&leaves[..5]), merkle_root(&leaves));
+    }
+}
@@ -0,0 +1,21 @@
+use crate::ui::{get_content_type, render_index};
+
+#[cfg(test)]
+mod ui {
+    use super::*;
+
+    #[test]
+    fn test_content_types() {
+        assert_eq!(get_content_type("index.html"), "text/html; charset=utf-8");
+        assert_eq!(get_content_type("app.js"), "text/javascript; charset=utf-8");
+        assert_eq!(get_content_type("LICENSE"), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_title_is_escaped() {
+        assert_eq!(
+            render_index("<title>{{title}}</title>", "Names <by> \"us\""),
+            "<title>Names &lt;by&gt; &quot;us&quot;</title>"
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use crate::endpoints::uris::parse_ids;
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod parse_token_ids {
+    use super::*;
+
+    #[test]
+    fn test_decimal_and_hex_ids() {
+        assert_eq!(
+            parse_ids("1, 0x2,3,").unwrap(),
+            vec![
+                FieldElement::from(1_u8),
+                FieldElement::from(2_u8),
+                FieldElement::from(3_u8)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicates_are_fetched_once() {
+        assert_eq!(
+            parse_ids("2,1,0x2").unwrap(),
+            vec![FieldElement::from(2_u8), FieldElement::from(1_u8)]
+        );
+    }
+
+    #[test]
+    fn test_invalid_ids() {
+        assert!(parse_ids("").is_err());
+        assert!(parse_ids("1,ben").is_err());
+        let ids: Vec<String> = (1..=101).map(|id| id.to_string()).collect();
+        assert!(parse_ids(&ids.join(",")).is_err());
+        assert_eq!(parse_ids(&ids[..100].join(",")).unwrap().len(), 100);
+    }
+}
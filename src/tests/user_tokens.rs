@@ -0,0 +1,26 @@
+use crate::user_tokens::{get_request_token, hash_token};
+use axum::http::{HeaderMap, HeaderValue};
+
+#[cfg(test)]
+mod user_tokens {
+    use super::*;
+
+    #[test]
+    fn test_request_token() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(get_request_token(&headers, Some("abc")), Some("abc"));
+        assert_eq!(get_request_token(&headers, Some("")), None);
+        assert_eq!(get_request_token(&headers, None), None);
+
+        headers.insert("authorization", HeaderValue::from_static("Bearer xyz"));
+        // the header wins over the query
+        assert_eq!(get_request_token(&headers, Some("abc")), Some("xyz"));
+    }
+
+    #[test]
+    fn test_hash_token() {
+        assert_eq!(hash_token("abc"), hash_token("abc"));
+        assert_ne!(hash_token("abc"), hash_token("abd"));
+        assert_eq!(hash_token("abc").len(), 64);
+    }
+}
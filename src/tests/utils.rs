@@ -1,4 +1,7 @@
-use crate::utils::{clean_string, extract_prefix_and_root, parse_image_url, to_u256};
+use crate::utils::{
+    clean_string, extract_prefix_and_root, get_canonical_location, normalize_domain,
+    parse_image_url, to_u256,
+};
 use ark_ff::{biginteger::BigInteger256, BigInteger};
 
 #[cfg(test)]
@@ -78,7 +81,7 @@ mod to_u256 {
         let low = "0x00000000000000000000000000000001";
         let high = "0x00000000000000000000000000000000";
 
-        let result = to_u256(low, high);
+        let result = to_u256(low, high).unwrap();
 
         // Check if the result is within the valid range
         let min_value = BigInteger256::from_bits_be(&[false; 256][..]);
@@ -93,9 +96,8 @@ mod to_u256 {
         let low = "invalid hex";
         let high = "0x00000000000000000000000000000000";
 
-        let result = std::panic::catch_unwind(|| to_u256(low, high));
-
-        assert!(result.is_err());
+        assert!(to_u256(low, high).is_none());
+        assert!(to_u256("0x1", "").is_none());
     }
 
     #[test]
@@ -103,7 +105,7 @@ mod to_u256 {
         let low = "0x0000000000000000";
         let high = "0x0000000000000001";
 
-        let result = to_u256(low, high);
+        let result = to_u256(low, high).unwrap();
 
         assert_eq!(result, BigInteger256::from_bits_be(&[false; 32][..]));
     }
@@ -113,7 +115,7 @@ mod to_u256 {
         let low = "0x0000000000000000";
         let high = "0x0000000000000000";
 
-        let result = to_u256(low, high);
+        let result = to_u256(low, high).unwrap();
 
         assert_eq!(result, BigInteger256::from_bits_be(&[false; 32][..]));
     }
@@ -195,7 +197,10 @@ mod parse_image_url {
     #[test]
     fn test_parse_image_url_custom_ipfs_gateway() {
         let mut config = Config::default();
-        config.variables.ipfs_gateway = "https://custom-ipfs.gateway/".to_string();
+        config.variables.ipfs_gateways = vec![
+            "https://custom-ipfs.gateway/".to_string(),
+            "https://ipfs.io/ipfs/".to_string(),
+        ];
 
         let input_url = "ipfs://examplehash";
         let expected_output = "https://custom-ipfs.gateway/examplehash";
@@ -212,3 +217,39 @@ mod parse_image_url {
         assert_eq!(result, expected_output);
     }
 }
+
+#[cfg(test)]
+mod normalize_domain {
+    use super::*;
+
+    #[test]
+    fn test_case_and_trailing_dot() {
+        assert_eq!(normalize_domain("Ben.STARK."), "ben.stark");
+        assert_eq!(normalize_domain(" ben.stark "), "ben.stark");
+    }
+
+    #[test]
+    fn test_percent_encoded_unicode() {
+        assert_eq!(normalize_domain("caf%C3%A9.stark"), "café.stark");
+        // encoded twice
+        assert_eq!(normalize_domain("caf%25C3%25A9.stark"), "café.stark");
+        assert_eq!(normalize_domain("CAFÉ.stark"), "café.stark");
+    }
+
+    #[test]
+    fn test_invalid_escape_is_kept() {
+        assert_eq!(normalize_domain("ben%zz.stark"), "ben%zz.stark");
+    }
+
+    #[test]
+    fn test_canonical_location() {
+        assert_eq!(
+            get_canonical_location("/domain_to_addr", "domain=Ben.stark."),
+            Some("/domain_to_addr?domain=ben.stark".to_string())
+        );
+        assert_eq!(
+            get_canonical_location("/domain_to_addr", "domain=ben.stark"),
+            None
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use crate::{
+    config::{Config, Contracts},
+    utils::to_hex,
+    verifiers::{get_verifier_kind, group_verifier_data},
+};
+use mongodb::bson::doc;
+use starknet::{core::types::FieldElement, macros::short_string};
+
+#[cfg(test)]
+mod verifier_data {
+    use super::*;
+
+    fn contracts() -> Contracts {
+        let mut conf = Config::default();
+        conf.contracts.verifiers = vec![FieldElement::from(50u64), FieldElement::from(51u64)];
+        conf.contracts.old_verifier = FieldElement::from(52u64);
+        conf.contracts.pop_verifier = FieldElement::from(60u64);
+        conf.contracts.pp_verifier = FieldElement::from(70u64);
+        conf.contracts
+    }
+
+    #[test]
+    fn test_get_verifier_kind() {
+        let contracts = contracts();
+        assert_eq!(
+            get_verifier_kind(&contracts, &FieldElement::from(51u64)),
+            Some("social")
+        );
+        assert_eq!(
+            get_verifier_kind(&contracts, &FieldElement::from(60u64)),
+            Some("proof_of_personhood")
+        );
+        assert_eq!(
+            get_verifier_kind(&contracts, &FieldElement::from(99u64)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_group_by_field() {
+        let twitter = to_hex(&short_string!("twitter"));
+        let docs = vec![
+            doc! { "verifier": "0x32", "field": &twitter, "data": "0x7", "_cursor": { "from": 10 } },
+            doc! { "verifier": "0x33", "field": &twitter, "data": "0x8", "_cursor": { "from": 20 } },
+            doc! { "verifier": "0x46", "field": to_hex(&short_string!("nft_pp_id")), "extended_data": ["0x1", "0x0"], "_cursor": { "from": 5 } },
+            // written by a contract that isn't configured
+            doc! { "verifier": "0x63", "field": to_hex(&short_string!("github")), "data": "0x9" },
+            doc! { "verifier": "0x3c", "field": "0x1", "data": null },
+        ];
+        let fields = group_verifier_data(&contracts(), &docs);
+        assert_eq!(
+            fields.keys().collect::<Vec<_>>(),
+            vec!["nft_pp_id", "twitter"]
+        );
+
+        // the latest verification first
+        let twitter = &fields["twitter"];
+        assert_eq!(twitter.len(), 2);
+        assert_eq!(twitter[0].data.as_deref(), Some("0x8"));
+        assert_eq!(twitter[0].block, Some(20));
+        assert_eq!(twitter[1].kind, "social");
+
+        let pp = &fields["nft_pp_id"][0];
+        assert_eq!(pp.kind, "profile_picture");
+        assert_eq!(pp.data, None);
+        assert_eq!(
+            pp.extended_data,
+            Some(vec!["0x1".to_string(), "0x0".to_string()])
+        );
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{
+    config::Config,
+    pagination::PageCursor,
+    storage::{MemoryStorage, Storage},
+    webhooks::{
+        delivery::{get_retry_delay, record_attempt, DeliveryStatus},
+        dispatch::{get_deliveries, get_write_notifications, is_subscribed},
+        new_webhook,
+        signing::{hmac_sha256, sign},
+        validate_url, Webhook, WebhookEvent,
+    },
+    ws::feed::Write,
+};
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use serde_json::json;
+
+#[cfg(test)]
+mod webhooks {
+    use super::*;
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn webhook(events: Vec<WebhookEvent>, filter: Option<&str>) -> Webhook {
+        new_webhook(
+            "braavos",
+            "https://hooks.example.com/starknetid".to_string(),
+            events,
+            filter.map(String::from),
+            NOW,
+        )
+        .unwrap()
+    }
+
+    fn write(collection: &'static str, block: i64, n: u8, doc: Document) -> Write {
+        let mut bytes = [0; 12];
+        bytes[11] = n;
+        Write {
+            cursor: PageCursor {
+                key: block,
+                id: ObjectId::from_bytes(bytes),
+            },
+            collection,
+            doc,
+        }
+    }
+
+    #[test]
+    fn test_hmac_vectors() {
+        // RFC 4231 test cases 1, 2 and 6
+        assert_eq!(
+            hex::encode(hmac_sha256(&[0x0b; 20], b"Hi There")),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        assert_eq!(
+            hex::encode(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            hex::encode(hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+        let signature = sign("secret", NOW, "{}");
+        assert_eq!(
+            signature,
+            format!(
+                "sha256={}",
+                hex::encode(hmac_sha256(b"secret", format!("{}.{{}}", NOW).as_bytes()))
+            )
+        );
+    }
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://hooks.example.com/a?b=c").is_ok());
+        assert!(validate_url("http://8.8.8.8/hook").is_ok());
+        for url in [
+            "ftp://hooks.example.com",
+            "not a url",
+            "http://localhost:8080/hook",
+            "http://127.0.0.1/hook",
+            "http://10.0.0.12/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]/hook",
+            "http://[::ffff:192.168.1.1]/hook",
+            "http://[fd00::1]/hook",
+            "http://[fe80::1]/hook",
+            "http://metadata.google.internal/",
+        ] {
+            assert!(validate_url(url).is_err(), "{} should be refused", url);
+        }
+    }
+
+    #[test]
+    fn test_new_webhook() {
+        let webhook = webhook(vec![WebhookEvent::DomainRegistered], None);
+        assert_eq!(webhook.secret.len(), 64);
+        assert_ne!(
+            webhook.id,
+            self::webhook(vec![WebhookEvent::DomainRegistered], None).id
+        );
+        let no_events = new_webhook(
+            "braavos",
+            "https://a.example.com".to_string(),
+            vec![],
+            None,
+            NOW,
+        );
+        assert!(no_events.is_err());
+        let bad_filter = new_webhook(
+            "braavos",
+            "https://a.example.com".to_string(),
+            vec![WebhookEvent::DomainExpired],
+            Some("length <=".to_string()),
+            NOW,
+        );
+        assert!(bad_filter.is_err());
+    }
+
+    #[test]
+    fn test_retries() {
+        let conf = Config::default().webhooks;
+        assert_eq!(get_retry_delay(&conf, 1), conf.retry_base_secs);
+        assert_eq!(get_retry_delay(&conf, 3), conf.retry_base_secs * 4);
+        assert_eq!(get_retry_delay(&conf, 60), conf.retry_max_secs);
+
+        let webhook = webhook(vec![WebhookEvent::DomainExpired], None);
+        let notification = crate::webhooks::dispatch::WebhookNotification {
+            event: WebhookEvent::DomainExpired,
+            key: "expired:ben.stark:1".to_string(),
+            block: None,
+            data: json!({ "domain": "ben.stark" }),
+        };
+        let mut delivery = get_deliveries(&[webhook.clone()], &notification, NOW).remove(0);
+        assert_eq!(delivery.id, format!("{}:expired:ben.stark:1", webhook.id));
+        record_attempt(&mut delivery, Err("Status 500".to_string()), NOW, &conf);
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.next_attempt_at, NOW + conf.retry_base_secs);
+        for _ in 1..conf.max_attempts {
+            record_attempt(&mut delivery, Err("Status 500".to_string()), NOW, &conf);
+        }
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+        assert_eq!(delivery.attempts, conf.max_attempts);
+    }
+
+    #[tokio::test]
+    async fn test_write_notifications() {
+        let storage = MemoryStorage::default();
+        storage
+            .insert_one(
+                "domains",
+                doc! { "domain": "ben.stark", "id": "0x1", "expiry": NOW, "_cursor": { "from": 10_i64, "to": 20_i64 } },
+            )
+            .await
+            .unwrap();
+        storage
+            .insert_one(
+                "id_owners",
+                doc! { "id": "0x2", "owner": "0xa", "_cursor": { "from": 5_i64, "to": 30_i64 } },
+            )
+            .await
+            .unwrap();
+
+        let registered = write(
+            "domains",
+            10,
+            1,
+            doc! { "domain": "ben.stark", "id": "0x1", "expiry": NOW, "_cursor": { "from": 10_i64, "to": 20_i64 } },
+        );
+        let notifications = get_write_notifications(&storage, &registered)
+            .await
+            .unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].event, WebhookEvent::DomainRegistered);
+        assert_eq!(notifications[0].data["domain"], "ben.stark");
+
+        // the version closed at block 20 held the domain with identity 0x1
+        let transferred = write(
+            "domains",
+            20,
+            2,
+            doc! { "domain": "ben.stark", "id": "0x2", "expiry": NOW, "_cursor": { "from": 20_i64, "to": null } },
+        );
+        let notifications = get_write_notifications(&storage, &transferred)
+            .await
+            .unwrap();
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].event, WebhookEvent::DomainTransferred);
+        assert_eq!(notifications[0].data["from_id"], "0x1");
+        assert_eq!(notifications[0].data["to_id"], "0x2");
+
+        let identity = write(
+            "id_owners",
+            30,
+            3,
+            doc! { "id": "0x2", "owner": "0xb", "_cursor": { "from": 30_i64, "to": null } },
+        );
+        let notifications = get_write_notifications(&storage, &identity).await.unwrap();
+        assert_eq!(notifications[0].event, WebhookEvent::IdentityTransferred);
+        assert_eq!(notifications[0].data["from"], "0xa");
+        assert_eq!(notifications[0].data["to"], "0xb");
+
+        // a mint isn't a transfer
+        let minted = write(
+            "id_owners",
+            31,
+            4,
+            doc! { "id": "0x3", "owner": "0xc", "_cursor": { "from": 31_i64, "to": null } },
+        );
+        assert!(get_write_notifications(&storage, &minted)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let short = webhook(vec![WebhookEvent::DomainTransferred], Some("length <= 3"));
+        let long = webhook(vec![WebhookEvent::DomainTransferred], Some("length > 3"));
+        let other_event = webhook(vec![WebhookEvent::DomainRegistered], None);
+        let notifications = get_write_notifications(&storage, &transferred)
+            .await
+            .unwrap();
+        assert!(is_subscribed(&short, &notifications[0]));
+        assert!(!is_subscribed(&long, &notifications[0]));
+        assert!(!is_subscribed(&other_event, &notifications[0]));
+    }
+}
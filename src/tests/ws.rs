@@ -0,0 +1,173 @@
+use crate::{
+    config::Config,
+    filters::Filter,
+    pagination::PageCursor,
+    storage::{MemoryStorage, Storage},
+    ws::{
+        feed::{get_backfill, to_change_event, ChangeKind},
+        subscriptions::{ClientMessage, Subscriptions},
+    },
+};
+use mongodb::bson::{doc, oid::ObjectId};
+use starknet::core::types::FieldElement;
+
+#[cfg(test)]
+mod ws {
+    use super::*;
+
+    const ADDRESS: &str = "0x0000000000000000000000000000000000000000000000000000000000000123";
+
+    #[test]
+    fn test_change_events() {
+        let event = to_change_event(
+            "domains",
+            &doc! {
+                "domain": "fricoben.stark",
+                "id": "0x1",
+                "legacy_address": ADDRESS,
+                "rev_address": ADDRESS,
+                "_cursor": { "from": 42_i64, "to": null },
+            },
+        )
+        .unwrap();
+        assert_eq!(event.kind, ChangeKind::Domain);
+        assert_eq!(event.domain.as_deref(), Some("fricoben.stark"));
+        assert_eq!(event.addresses, vec![ADDRESS.to_string()]);
+        assert_eq!(event.block, Some(42));
+
+        // only the starknet field of the user data changes a resolution
+        let other_field = doc! { "id": "0x1", "field": "0x1234", "data": ADDRESS };
+        assert_eq!(to_change_event("id_user_data", &other_field), None);
+        assert_eq!(to_change_event("auto_renew_flows", &doc! {}), None);
+    }
+
+    #[test]
+    fn test_subscriptions() {
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.add_domain("fricoben.stark".to_string(), Some("0x1".to_string()));
+        subscriptions.add_address(&FieldElement::from_hex_be("0x123").unwrap());
+
+        let verifier = to_change_event("id_verifier_data", &doc! { "id": "0x1" }).unwrap();
+        assert!(subscriptions.matches(&verifier));
+        let owner = to_change_event("id_owners", &doc! { "id": "0x9", "owner": ADDRESS }).unwrap();
+        assert!(subscriptions.matches(&owner));
+
+        // the domain moved to another identity, whose writes are followed from now on
+        let moved =
+            to_change_event("domains", &doc! { "domain": "fricoben.stark", "id": "0x2" }).unwrap();
+        assert!(subscriptions.matches(&moved));
+        assert!(!subscriptions.matches(&verifier));
+        let verifier = to_change_event("id_verifier_data", &doc! { "id": "0x2" }).unwrap();
+        assert!(subscriptions.matches(&verifier));
+    }
+
+    #[test]
+    fn test_filter_subscriptions() {
+        let mut subscriptions = Subscriptions::default();
+        subscriptions.add_filter(
+            Filter::parse(r#"event == "domain" && domain.endsWith(".stark") && length <= 5"#)
+                .unwrap(),
+        );
+        let short =
+            to_change_event("domains", &doc! { "domain": "ben.stark", "id": "0x1" }).unwrap();
+        assert!(subscriptions.matches(&short));
+        let long =
+            to_change_event("domains", &doc! { "domain": "fricoben.stark", "id": "0x1" }).unwrap();
+        assert!(!subscriptions.matches(&long));
+
+        subscriptions
+            .remove_filter(r#"event == "domain" && domain.endsWith(".stark") && length <= 5"#);
+        assert!(subscriptions.is_empty());
+    }
+
+    fn write_id(n: u8) -> ObjectId {
+        let mut bytes = [0; 12];
+        bytes[11] = n;
+        ObjectId::from_bytes(bytes)
+    }
+
+    async fn get_storage() -> MemoryStorage {
+        let storage = MemoryStorage::default();
+        for (collection, block, document) in [
+            ("domains", 10, doc! { "domain": "ben.stark", "id": "0x1" }),
+            ("id_owners", 11, doc! { "id": "0x1", "owner": ADDRESS }),
+            // not a write clients see, it still moves the cursor
+            ("id_user_data", 12, doc! { "id": "0x1", "field": "0x1234" }),
+            ("domains", 13, doc! { "domain": "ben.stark", "id": "0x2" }),
+        ] {
+            let mut document = document;
+            document.insert("_id", write_id(block as u8));
+            document.insert("_cursor", doc! { "from": block as i64, "to": null });
+            storage.insert_one(collection, document).await.unwrap();
+        }
+        storage
+    }
+
+    #[tokio::test]
+    async fn test_backfill() {
+        let storage = get_storage().await;
+        let conf = Config::default().ws;
+        let after = PageCursor {
+            key: 10,
+            id: write_id(10),
+        };
+        let backfill = get_backfill(&storage, &conf, after).await.unwrap();
+        let blocks: Vec<_> = backfill.events.iter().map(|event| event.block).collect();
+        assert_eq!(blocks, vec![Some(11), Some(13)]);
+        assert_eq!(
+            backfill.events[0].cursor,
+            Some(
+                PageCursor {
+                    key: 11,
+                    id: write_id(11)
+                }
+                .encode()
+            )
+        );
+        assert_eq!(backfill.until.map(|until| until.key), Some(13));
+        assert!(backfill.complete);
+    }
+
+    #[tokio::test]
+    async fn test_backfill_bounds() {
+        let storage = get_storage().await;
+        let after = || PageCursor {
+            key: 10,
+            id: write_id(10),
+        };
+
+        // the writes past the first one of each collection may be missing, they are left out
+        let mut conf = Config::default().ws;
+        conf.max_backfill_events = 1;
+        let backfill = get_backfill(&storage, &conf, after()).await.unwrap();
+        assert_eq!(backfill.events.len(), 1);
+        assert_eq!(backfill.events[0].kind, ChangeKind::Owner);
+        assert!(!backfill.complete);
+
+        // a cursor older than the window restarts at its start
+        let mut conf = Config::default().ws;
+        conf.max_backfill_blocks = 1;
+        let backfill = get_backfill(&storage, &conf, after()).await.unwrap();
+        let blocks: Vec<_> = backfill.events.iter().map(|event| event.block).collect();
+        assert_eq!(blocks, vec![Some(13)]);
+        assert!(!backfill.complete);
+    }
+
+    #[test]
+    fn test_client_messages() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"action":"subscribe","domain":"fricoben.stark"}"#).unwrap();
+        assert_eq!(
+            message,
+            ClientMessage::Subscribe {
+                domain: Some("fricoben.stark".to_string()),
+                address: None,
+                filter: None
+            }
+        );
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"action":"resume","cursor":"42_000000000000000000000001"}"#)
+                .unwrap();
+        assert!(matches!(message, ClientMessage::Resume { .. }));
+    }
+}
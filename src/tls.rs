@@ -0,0 +1,214 @@
+use std::{
+    collections::HashMap,
+    fs,
+    net::SocketAddr,
+    path::Path,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
+
+use anyhow::{anyhow, bail, Result};
+use axum::Router;
+use axum_server::tls_rustls::RustlsConfig;
+use instant_acme::{
+    Account, AuthorizationStatus, ChallengeType, Identifier, LetsEncrypt, NewAccount, NewOrder,
+    OrderStatus,
+};
+use rcgen::{Certificate, CertificateParams, CustomExtension, DistinguishedName};
+use rustls::{
+    server::{ClientHello, ResolvesServerCert},
+    sign::{any_supported_type, CertifiedKey},
+    PrivateKey, ServerConfig,
+};
+use tokio::time::sleep;
+
+use crate::{config::Tls, logger::Logger};
+
+const ACME_TLS_ALPN: &[u8] = b"acme-tls/1";
+const RENEWAL_CHECK_INTERVAL: Duration = Duration::from_secs(12 * 3600);
+
+/// Serves the current certificate, or the tls-alpn-01 challenge certificate when the
+/// ACME server validates a domain
+#[derive(Default)]
+struct CertResolver {
+    certificate: RwLock<Option<Arc<CertifiedKey>>>,
+    challenges: RwLock<HashMap<String, Arc<CertifiedKey>>>,
+}
+
+impl ResolvesServerCert for CertResolver {
+    fn resolve(&self, client_hello: ClientHello) -> Option<Arc<CertifiedKey>> {
+        let is_challenge = client_hello
+            .alpn()
+            .map_or(false, |mut protocols| protocols.any(|p| p == ACME_TLS_ALPN));
+        if is_challenge {
+            let domain = client_hello.server_name()?;
+            return self.challenges.read().unwrap().get(domain).cloned();
+        }
+        self.certificate.read().unwrap().clone()
+    }
+}
+
+fn certified_key(chain_pem: &str, key_pem: &str) -> Result<CertifiedKey> {
+    let chain = rustls_pemfile::certs(&mut chain_pem.as_bytes())?
+        .into_iter()
+        .map(rustls::Certificate)
+        .collect();
+    let key = rustls_pemfile::pkcs8_private_keys(&mut key_pem.as_bytes())?
+        .pop()
+        .ok_or_else(|| anyhow!("No private key found"))?;
+    let key = any_supported_type(&PrivateKey(key)).map_err(|e| anyhow!("Invalid key: {}", e))?;
+    Ok(CertifiedKey::new(chain, key))
+}
+
+async fn order_certificate(conf: &Tls, resolver: &CertResolver) -> Result<(String, String)> {
+    let url = if conf.staging {
+        LetsEncrypt::Staging.url()
+    } else {
+        LetsEncrypt::Production.url()
+    };
+    let contact = format!("mailto:{}", conf.contact_email);
+    let (account, _) = Account::create(
+        &NewAccount {
+            contact: &[&contact],
+            terms_of_service_agreed: true,
+            only_return_existing: false,
+        },
+        url,
+        None,
+    )
+    .await?;
+
+    let identifiers: Vec<Identifier> = conf
+        .domains
+        .iter()
+        .map(|domain| Identifier::Dns(domain.clone()))
+        .collect();
+    let mut order = account
+        .new_order(&NewOrder {
+            identifiers: &identifiers,
+        })
+        .await?;
+
+    for authorization in order.authorizations().await? {
+        if authorization.status == AuthorizationStatus::Valid {
+            continue;
+        }
+        let Identifier::Dns(domain) = &authorization.identifier;
+        let challenge = authorization
+            .challenges
+            .iter()
+            .find(|c| c.r#type == ChallengeType::TlsAlpn01)
+            .ok_or_else(|| anyhow!("No tls-alpn-01 challenge offered for {}", domain))?;
+
+        let key_authorization = order.key_authorization(challenge);
+        let mut params = CertificateParams::new(vec![domain.clone()]);
+        params.custom_extensions = vec![CustomExtension::new_acme_identifier(
+            key_authorization.digest().as_ref(),
+        )];
+        let challenge_cert = Certificate::from_params(params)?;
+        let challenge_key = certified_key(
+            &challenge_cert.serialize_pem()?,
+            &challenge_cert.serialize_private_key_pem(),
+        )?;
+        resolver
+            .challenges
+            .write()
+            .unwrap()
+            .insert(domain.clone(), Arc::new(challenge_key));
+        order.set_challenge_ready(&challenge.url).await?;
+    }
+
+    let mut attempts = 0;
+    loop {
+        sleep(Duration::from_secs(2)).await;
+        match order.refresh().await?.status {
+            OrderStatus::Ready => break,
+            OrderStatus::Invalid => bail!("ACME order is invalid"),
+            _ if attempts >= 30 => bail!("ACME order not ready after {} attempts", attempts),
+            _ => attempts += 1,
+        }
+    }
+    resolver.challenges.write().unwrap().clear();
+
+    let mut params = CertificateParams::new(conf.domains.clone());
+    params.distinguished_name = DistinguishedName::new();
+    let cert = Certificate::from_params(params)?;
+    order.finalize(&cert.serialize_request_der()?).await?;
+    let chain = loop {
+        match order.certificate().await? {
+            Some(chain) => break chain,
+            None => sleep(Duration::from_secs(1)).await,
+        }
+    };
+    Ok((chain, cert.serialize_private_key_pem()))
+}
+
+// the cached certificate is reused until it's old enough to be renewed
+fn load_cached(conf: &Tls) -> Option<(String, String)> {
+    let cert_path = Path::new(&conf.cache_dir).join("cert.pem");
+    let age = fs::metadata(&cert_path)
+        .ok()?
+        .modified()
+        .ok()?
+        .elapsed()
+        .ok()?;
+    if age > Duration::from_secs(conf.renew_after_days * 24 * 3600) {
+        return None;
+    }
+    let chain = fs::read_to_string(cert_path).ok()?;
+    let key = fs::read_to_string(Path::new(&conf.cache_dir).join("key.pem")).ok()?;
+    Some((chain, key))
+}
+
+fn save_cached(conf: &Tls, chain: &str, key: &str) -> Result<()> {
+    fs::create_dir_all(&conf.cache_dir)?;
+    fs::write(Path::new(&conf.cache_dir).join("key.pem"), key)?;
+    fs::write(Path::new(&conf.cache_dir).join("cert.pem"), chain)?;
+    Ok(())
+}
+
+async fn refresh_certificate(conf: &Tls, resolver: &CertResolver, logger: &Logger) -> Result<()> {
+    let is_set = resolver.certificate.read().unwrap().is_some();
+    let (chain, key) = match load_cached(conf) {
+        Some(cached) if !is_set => cached,
+        Some(_) => return Ok(()),
+        None => {
+            logger.info(format!("tls: ordering certificate for {:?}", conf.domains));
+            let (chain, key) = order_certificate(conf, resolver).await?;
+            save_cached(conf, &chain, &key)?;
+            (chain, key)
+        }
+    };
+    *resolver.certificate.write().unwrap() = Some(Arc::new(certified_key(&chain, &key)?));
+    Ok(())
+}
+
+/// Serves the app over HTTPS with certificates obtained from Let's Encrypt, the
+/// tls-alpn-01 challenge is answered by the server itself so it must listen on port 443
+pub async fn serve(app: Router, addr: SocketAddr, conf: Tls, logger: Logger) -> Result<()> {
+    if conf.domains.iter().any(|domain| domain.starts_with('*')) {
+        bail!("wildcard domains can't be validated with tls-alpn-01");
+    }
+
+    let resolver = Arc::new(CertResolver::default());
+    let mut server_config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_cert_resolver(resolver.clone());
+    server_config.alpn_protocols = vec![b"http/1.1".to_vec(), ACME_TLS_ALPN.to_vec()];
+
+    // the listener must be up before ordering so the challenge can be answered
+    tokio::spawn(async move {
+        loop {
+            if let Err(e) = refresh_certificate(&conf, &resolver, &logger).await {
+                logger.severe(format!("tls: unable to refresh certificate: {}", e));
+            }
+            sleep(RENEWAL_CHECK_INTERVAL).await;
+        }
+    });
+
+    axum_server::bind_rustls(addr, RustlsConfig::from_config(Arc::new(server_config)))
+        .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+        .await?;
+    Ok(())
+}
@@ -0,0 +1,390 @@
+use std::sync::RwLock;
+
+use anyhow::{anyhow, Result};
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, from_document, to_document, Document},
+    error::{ErrorKind, WriteFailure},
+    options::{FindOneOptions, FindOptions, IndexOptions},
+    IndexModel,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use starknet::core::{
+    crypto::{ecdsa_sign, ExtendedSignature},
+    types::FieldElement,
+    utils::starknet_keccak,
+};
+use starknet_crypto::get_public_key;
+
+use crate::{models::AppState, utils::to_hex};
+
+pub const LOG_COLLECTION: &str = "transparency_log";
+// concurrent appends from other instances may take the same index
+const APPEND_ATTEMPTS: usize = 5;
+
+pub type Hash = [u8; 32];
+
+/// Off-chain authority exercised by the API, e.g. a signature allowing a subdomain mint
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TransparencyEntry {
+    // sol_subdomain, free_domain...
+    pub kind: String,
+    // domain or address the issuance is about
+    pub subject: String,
+    pub target: String,
+    pub message_hash: String,
+    pub signature_r: String,
+    pub signature_s: String,
+    pub issued_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct LogRecord {
+    index: i64,
+    leaf_hash: String,
+    #[serde(flatten)]
+    entry: TransparencyEntry,
+}
+
+#[derive(Serialize)]
+pub struct TreeHead {
+    pub tree_size: u64,
+    pub root: String,
+    pub timestamp: i64,
+    pub public_key: String,
+    pub signature_r: String,
+    pub signature_s: String,
+}
+
+/// Append-only merkle tree (RFC 6962) of the off-chain issuances, the subtree hashes are
+/// cached in memory and the entries persisted in the `transparency_log` collection
+#[derive(Default)]
+pub struct TransparencyLog {
+    subtrees: RwLock<SubtreeCache>,
+}
+
+/// Hashes of the complete subtrees, `levels[h][i]` covers the leaves from `i << h` to
+/// `(i + 1) << h`, the roots and proofs of any tree size only hash its incomplete right edge
+#[derive(Default)]
+pub struct SubtreeCache {
+    levels: Vec<Vec<Hash>>,
+}
+
+fn sha256(parts: &[&[u8]]) -> Hash {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
+
+pub fn leaf_hash(entry: &TransparencyEntry) -> Hash {
+    let data = serde_json::to_vec(entry).unwrap();
+    sha256(&[&[0], &data])
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    sha256(&[&[1], left, right])
+}
+
+// largest power of two smaller than n
+fn split_point(n: usize) -> usize {
+    let mut k = 1;
+    while k * 2 < n {
+        k *= 2;
+    }
+    k
+}
+
+pub fn merkle_root(leaves: &[Hash]) -> Hash {
+    match leaves.len() {
+        0 => sha256(&[]),
+        1 => leaves[0],
+        n => {
+            let k = split_point(n);
+            node_hash(&merkle_root(&leaves[..k]), &merkle_root(&leaves[k..]))
+        }
+    }
+}
+
+/// Audit path of the leaf at `index` in the tree made of `leaves`, the reference the cached
+/// proofs are tested against
+#[allow(dead_code)]
+pub fn inclusion_proof(index: usize, leaves: &[Hash]) -> Vec<Hash> {
+    if leaves.len() <= 1 {
+        return vec![];
+    }
+    let k = split_point(leaves.len());
+    let (mut path, sibling) = if index < k {
+        (
+            inclusion_proof(index, &leaves[..k]),
+            merkle_root(&leaves[k..]),
+        )
+    } else {
+        (
+            inclusion_proof(index - k, &leaves[k..]),
+            merkle_root(&leaves[..k]),
+        )
+    };
+    path.push(sibling);
+    path
+}
+
+impl SubtreeCache {
+    pub fn push(&mut self, leaf: Hash) {
+        let (mut hash, mut level) = (leaf, 0);
+        loop {
+            if self.levels.len() == level {
+                self.levels.push(vec![]);
+            }
+            let nodes = &mut self.levels[level];
+            nodes.push(hash);
+            if nodes.len() % 2 == 1 {
+                return;
+            }
+            hash = node_hash(&nodes[nodes.len() - 2], &nodes[nodes.len() - 1]);
+            level += 1;
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.levels.first().map_or(0, Vec::len)
+    }
+
+    // root of the `size` leaves from `start`, the left subtrees of the split are complete
+    fn subtree_root(&self, start: usize, size: usize) -> Hash {
+        if size.is_power_of_two() && start % size == 0 {
+            return self.levels[size.trailing_zeros() as usize][start / size];
+        }
+        let k = split_point(size);
+        node_hash(
+            &self.subtree_root(start, k),
+            &self.subtree_root(start + k, size - k),
+        )
+    }
+
+    fn subtree_proof(&self, index: usize, start: usize, size: usize) -> Vec<Hash> {
+        if size <= 1 {
+            return vec![];
+        }
+        let k = split_point(size);
+        let (mut path, sibling) = if index < start + k {
+            (
+                self.subtree_proof(index, start, k),
+                self.subtree_root(start + k, size - k),
+            )
+        } else {
+            (
+                self.subtree_proof(index, start + k, size - k),
+                self.subtree_root(start, k),
+            )
+        };
+        path.push(sibling);
+        path
+    }
+
+    /// Same as `merkle_root` over the first `tree_size` leaves
+    pub fn root(&self, tree_size: usize) -> Option<Hash> {
+        match tree_size {
+            0 => Some(sha256(&[])),
+            n if n <= self.len() => Some(self.subtree_root(0, n)),
+            _ => None,
+        }
+    }
+
+    /// Same as `inclusion_proof` over the first `tree_size` leaves, with the leaf
+    pub fn proof(&self, index: usize, tree_size: usize) -> Option<(Hash, Vec<Hash>)> {
+        if index >= tree_size || tree_size > self.len() {
+            return None;
+        }
+        Some((
+            self.levels[0][index],
+            self.subtree_proof(index, 0, tree_size),
+        ))
+    }
+}
+
+/// Checks an audit path the way relying parties do (RFC 9162, 2.1.3.2)
+#[allow(dead_code)]
+pub fn verify_inclusion(
+    index: u64,
+    tree_size: u64,
+    leaf: &Hash,
+    path: &[Hash],
+    root: &Hash,
+) -> bool {
+    if index >= tree_size {
+        return false;
+    }
+    let (mut fn_, mut sn) = (index, tree_size - 1);
+    let mut result = *leaf;
+    for sibling in path {
+        if sn == 0 {
+            return false;
+        }
+        if fn_ & 1 == 1 || fn_ == sn {
+            result = node_hash(sibling, &result);
+            while fn_ & 1 == 0 && fn_ != 0 {
+                fn_ >>= 1;
+                sn >>= 1;
+            }
+        } else {
+            result = node_hash(&result, sibling);
+        }
+        fn_ >>= 1;
+        sn >>= 1;
+    }
+    sn == 0 && result == *root
+}
+
+pub fn get_tree_head_hash(tree_size: u64, root: &Hash, timestamp: i64) -> FieldElement {
+    starknet_keccak(format!("{}:{}:{}", tree_size, hex::encode(root), timestamp).as_bytes())
+}
+
+/// Creates the unique index preventing two entries from sharing a position
+pub async fn init(state: &AppState) {
+    let index = IndexModel::builder()
+        .keys(doc! { "index": 1 })
+        .options(IndexOptions::builder().unique(true).build())
+        .build();
+    if let Err(e) = state
+        .starknetid_db
+        .collection::<Document>(LOG_COLLECTION)
+        .create_index(index, None)
+        .await
+    {
+        state
+            .logger
+            .severe(format!("transparency: unable to create log index: {}", e));
+    }
+}
+
+impl TransparencyLog {
+    /// Loads the entries appended since the last sync, by this or another instance
+    pub async fn sync(&self, state: &AppState) -> Result<usize> {
+        let known = self.subtrees.read().unwrap().len();
+        let options = FindOptions::builder()
+            .sort(doc! { "index": 1 })
+            .projection(doc! { "_id": 0, "index": 1, "leaf_hash": 1 })
+            .build();
+        let mut cursor = state
+            .starknetid_db
+            .collection::<Document>(LOG_COLLECTION)
+            .find(doc! { "index": { "$gte": known as i64 } }, options)
+            .await?;
+        let mut new_leaves = vec![];
+        while let Some(doc) = cursor.next().await {
+            let doc = doc?;
+            if doc.get_i64("index")? != (known + new_leaves.len()) as i64 {
+                // an append in progress elsewhere, the rest is loaded on the next sync
+                break;
+            }
+            let mut leaf = Hash::default();
+            hex::decode_to_slice(doc.get_str("leaf_hash")?, &mut leaf)?;
+            new_leaves.push(leaf);
+        }
+
+        let mut subtrees = self.subtrees.write().unwrap();
+        // another sync may have run meanwhile
+        if subtrees.len() == known {
+            for leaf in new_leaves {
+                subtrees.push(leaf);
+            }
+        }
+        Ok(subtrees.len())
+    }
+
+    pub fn root(&self, tree_size: usize) -> Option<Hash> {
+        self.subtrees.read().unwrap().root(tree_size)
+    }
+
+    pub fn proof(&self, index: usize, tree_size: usize) -> Option<(Hash, Vec<Hash>)> {
+        self.subtrees.read().unwrap().proof(index, tree_size)
+    }
+}
+
+/// Records an issuance, it must succeed before the signature is handed out
+pub async fn append_entry(
+    state: &AppState,
+    kind: &str,
+    subject: String,
+    target: &FieldElement,
+    message_hash: &FieldElement,
+    signature: &ExtendedSignature,
+) -> Result<u64> {
+    let entry = TransparencyEntry {
+        kind: kind.to_string(),
+        subject,
+        target: to_hex(target),
+        message_hash: to_hex(message_hash),
+        signature_r: to_hex(&signature.r),
+        signature_s: to_hex(&signature.s),
+        issued_at: chrono::Utc::now().timestamp(),
+    };
+    let leaf = hex::encode(leaf_hash(&entry));
+    let collection = state.starknetid_db.collection::<Document>(LOG_COLLECTION);
+    for _ in 0..APPEND_ATTEMPTS {
+        let last = collection
+            .find_one(
+                None,
+                FindOneOptions::builder().sort(doc! { "index": -1 }).build(),
+            )
+            .await?;
+        let index = last.map_or(Ok(0), |doc| doc.get_i64("index").map(|index| index + 1))?;
+        let record = LogRecord {
+            index,
+            leaf_hash: leaf.clone(),
+            entry: entry.clone(),
+        };
+        match collection.insert_one(to_document(&record)?, None).await {
+            Ok(_) => {
+                let _ = state.transparency.sync(state).await;
+                return Ok(index as u64);
+            }
+            // duplicate key, another instance took this index first
+            Err(e)
+                if matches!(
+                    *e.kind,
+                    ErrorKind::Write(WriteFailure::WriteError(ref error)) if error.code == 11000
+                ) =>
+            {
+                continue
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(anyhow!("unable to find a free position in the log"))
+}
+
+pub async fn get_entry(state: &AppState, index: u64) -> Result<Option<TransparencyEntry>> {
+    Ok(state
+        .starknetid_db
+        .collection::<Document>(LOG_COLLECTION)
+        .find_one(doc! { "index": index as i64 }, None)
+        .await?
+        .map(from_document::<LogRecord>)
+        .transpose()?
+        .map(|record| record.entry))
+}
+
+/// Latest tree head, signed with the transparency key
+pub async fn get_tree_head(state: &AppState) -> Result<TreeHead> {
+    let tree_size = state.transparency.sync(state).await?;
+    let root = state
+        .transparency
+        .root(tree_size)
+        .ok_or_else(|| anyhow!("tree size {} is not loaded", tree_size))?;
+    let timestamp = chrono::Utc::now().timestamp();
+    let hash = get_tree_head_hash(tree_size as u64, &root, timestamp);
+    let signature = ecdsa_sign(&state.conf.transparency.private_key, &hash)
+        .map_err(|e| anyhow!("unable to sign tree head: {}", e))?;
+    Ok(TreeHead {
+        tree_size: tree_size as u64,
+        root: hex::encode(root),
+        timestamp,
+        public_key: to_hex(&get_public_key(&state.conf.transparency.private_key)),
+        signature_r: to_hex(&signature.r),
+        signature_s: to_hex(&signature.s),
+    })
+}
@@ -0,0 +1,120 @@
+use std::{fmt, ops::Deref, str::FromStr};
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use starknet::core::types::FieldElement;
+
+use crate::utils::to_hex;
+
+/// Start of the error of a malformed address, the rejections holding it are answered with a
+/// json failure
+pub const ERROR_PREFIX: &str = "invalid starknet address";
+// 0x followed by the 64 hex digits of a felt
+const MAX_HEX_DIGITS: usize = 64;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AddrError {
+    Empty,
+    NotHex,
+    TooLong,
+    // at least the field prime
+    OutOfRange,
+}
+
+impl fmt::Display for AddrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            AddrError::Empty => "it is empty",
+            AddrError::NotHex => "expected 0x followed by hex digits",
+            AddrError::TooLong => "more than 64 hex digits",
+            AddrError::OutOfRange => "it exceeds the field prime",
+        };
+        write!(f, "{}: {}", ERROR_PREFIX, reason)
+    }
+}
+
+impl std::error::Error for AddrError {}
+
+/// Address of a starknet contract or account, written as a zero padded 0x hex felt. A short
+/// hex such as 0x1 is accepted, decimal ones too for the clients sending felts as numbers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct StarknetAddr(pub FieldElement);
+
+impl StarknetAddr {
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.0)
+    }
+}
+
+impl FromStr for StarknetAddr {
+    type Err = AddrError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Err(AddrError::Empty);
+        }
+        let felt = match value
+            .strip_prefix("0x")
+            .or_else(|| value.strip_prefix("0X"))
+        {
+            Some(hex) => {
+                if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                    return Err(AddrError::NotHex);
+                }
+                // the padding zeros don't count, 0x0 is a valid felt
+                let hex = match hex.trim_start_matches('0') {
+                    "" => "0",
+                    hex => hex,
+                };
+                if hex.len() > MAX_HEX_DIGITS {
+                    return Err(AddrError::TooLong);
+                }
+                FieldElement::from_hex_be(hex).map_err(|_| AddrError::OutOfRange)?
+            }
+            None if value.chars().all(|c| c.is_ascii_digit()) => {
+                FieldElement::from_dec_str(value).map_err(|_| AddrError::OutOfRange)?
+            }
+            None => return Err(AddrError::NotHex),
+        };
+        Ok(StarknetAddr(felt))
+    }
+}
+
+impl fmt::Display for StarknetAddr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&to_hex(&self.0))
+    }
+}
+
+impl Deref for StarknetAddr {
+    type Target = FieldElement;
+
+    fn deref(&self) -> &FieldElement {
+        &self.0
+    }
+}
+
+impl From<FieldElement> for StarknetAddr {
+    fn from(felt: FieldElement) -> Self {
+        StarknetAddr(felt)
+    }
+}
+
+impl From<StarknetAddr> for FieldElement {
+    fn from(addr: StarknetAddr) -> Self {
+        addr.0
+    }
+}
+
+impl Serialize for StarknetAddr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&to_hex(&self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for StarknetAddr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        value.parse().map_err(de::Error::custom)
+    }
+}
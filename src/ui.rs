@@ -0,0 +1,39 @@
+// assets of the lookup page, built in with the `embedded-ui` feature
+#[cfg(feature = "embedded-ui")]
+static ASSETS: include_dir::Dir = include_dir::include_dir!("$CARGO_MANIFEST_DIR/ui");
+
+pub const INDEX: &str = "index.html";
+
+#[cfg(feature = "embedded-ui")]
+pub fn get_asset(path: &str) -> Option<&'static [u8]> {
+    ASSETS.get_file(path).map(|file| file.contents())
+}
+
+#[cfg(not(feature = "embedded-ui"))]
+pub fn get_asset(_path: &str) -> Option<&'static [u8]> {
+    None
+}
+
+pub fn get_content_type(path: &str) -> &'static str {
+    match path.rsplit('.').next() {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("ico") => "image/x-icon",
+        _ => "application/octet-stream",
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Index page with the title of the deployment
+pub fn render_index(html: &str, title: &str) -> String {
+    html.replace("{{title}}", &escape_html(title))
+}
@@ -0,0 +1,77 @@
+use anyhow::Result;
+use axum::http::HeaderMap;
+use mongodb::bson::doc;
+use rand::{distributions::Alphanumeric, Rng};
+use sha2::{Digest, Sha256};
+use starknet::{
+    core::{crypto::pedersen_hash, types::FieldElement},
+    macros::short_string,
+};
+
+use crate::{models::AppState, utils::to_hex};
+
+pub const TOKENS_COLLECTION: &str = "user_tokens";
+const TOKEN_LEN: usize = 40;
+
+/// Message signed by an address to get a token for the `/me` endpoints
+pub fn get_token_request_hash(address: &FieldElement, timestamp: i64) -> FieldElement {
+    let hash = pedersen_hash(&short_string!("get user token"), address);
+    pedersen_hash(&hash, &FieldElement::from(timestamp as u64))
+}
+
+// only the hash is stored so a database dump doesn't leak usable tokens
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Creates a token for `address`, returned once to the caller
+pub async fn issue_token(state: &AppState, address: &FieldElement) -> Result<(String, i64)> {
+    let token: String = rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(TOKEN_LEN)
+        .map(char::from)
+        .collect();
+    let now = chrono::Utc::now().timestamp();
+    let expires_at = now + state.conf.user_tokens.ttl_secs;
+    state
+        .storage
+        .insert_one(
+            TOKENS_COLLECTION,
+            doc! {
+                "token_hash": hash_token(&token),
+                "address": to_hex(address),
+                "created_at": now,
+                "expires_at": expires_at,
+            },
+        )
+        .await?;
+    Ok((token, expires_at))
+}
+
+/// Token of the `Authorization: Bearer` header, or of the `token` query parameter for the
+/// clients which can't set headers such as calendar apps
+pub fn get_request_token<'a>(headers: &'a HeaderMap, query: Option<&'a str>) -> Option<&'a str> {
+    headers
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .or(query)
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+/// Address a valid token was issued to
+pub async fn get_token_address(state: &AppState, token: &str) -> Option<FieldElement> {
+    let doc = state
+        .storage
+        .find_one(
+            TOKENS_COLLECTION,
+            doc! {
+                "token_hash": hash_token(token),
+                "expires_at": { "$gt": chrono::Utc::now().timestamp() },
+            },
+        )
+        .await
+        .ok()??;
+    FieldElement::from_hex_be(doc.get_str("address").ok()?).ok()
+}
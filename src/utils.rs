@@ -1,25 +1,95 @@
+use anyhow::{anyhow, Result};
 use ark_ff::{biginteger::BigInteger256, BigInteger};
 use axum::{
     body::Body,
-    http::StatusCode,
     response::{IntoResponse, Response},
     Router,
 };
 use base64::{engine::general_purpose::STANDARD, Engine};
-use serde::Serialize;
+use mongodb::bson::{doc, Document};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
-use starknet::core::types::FieldElement;
+use starknet::{
+    core::types::{BlockId, FieldElement, MaybePendingBlockWithTxHashes},
+    providers::{jsonrpc::HttpTransport, JsonRpcClient, Provider},
+};
 use std::{fmt::Write, str, sync::Arc};
 
-use crate::{config::Config, models::AppState};
+use crate::{
+    config::Config,
+    errors::{ApiError, ErrorCode},
+    ipfs::{find_serving_url, get_gateway_url},
+    models::AppState,
+};
 
 #[derive(Serialize)]
 pub struct ErrorMessage {
     error: String,
 }
 
+/// Error of a malformed input, the other errors are answered with their own `ApiError` code
 pub fn get_error(error: String) -> Response {
-    (StatusCode::BAD_REQUEST, error).into_response()
+    ApiError::new(ErrorCode::InvalidInput, error).into_response()
+}
+
+fn percent_decode(input: &str) -> Option<String> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let byte = u8::from_str_radix(str::from_utf8(&bytes[i + 1..i + 3]).ok()?, 16).ok()?;
+            decoded.push(byte);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Canonical form of a domain: percent-decoded, lowercase and without trailing dot
+pub fn normalize_domain(domain: &str) -> String {
+    let mut domain = domain.trim().to_string();
+    // clients encoding the query twice leave escaped sequences behind
+    while domain.contains('%') {
+        match percent_decode(&domain) {
+            Some(decoded) if decoded != domain => domain = decoded,
+            _ => break,
+        }
+    }
+    domain.trim().trim_end_matches('.').to_lowercase()
+}
+
+pub fn deserialize_domain<'de, D>(deserializer: D) -> std::result::Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer).map(|domain| normalize_domain(&domain))
+}
+
+/// Url to redirect to when the `domain` query parameter isn't canonical
+pub fn get_canonical_location(path: &str, query: &str) -> Option<String> {
+    let mut pairs: Vec<(String, String)> = serde_urlencoded::from_str(query).ok()?;
+    let mut changed = false;
+    for (key, value) in pairs.iter_mut() {
+        if key == "domain" {
+            let canonical = normalize_domain(value);
+            if canonical != *value {
+                *value = canonical;
+                changed = true;
+            }
+        }
+    }
+    if !changed {
+        return None;
+    }
+    Some(format!(
+        "{}?{}",
+        path,
+        serde_urlencoded::to_string(&pairs).ok()?
+    ))
 }
 
 pub fn extract_prefix_and_root(domain: String) -> (String, String) {
@@ -73,7 +143,8 @@ pub fn to_hex(felt: &FieldElement) -> String {
 ///
 /// # Returns
 ///
-/// * `BigInteger256` - A 256-bit integer combining both low and high components
+/// * `Option<BigInteger256>` - A 256-bit integer combining both low and high components,
+///   None if a component is not a valid hex string
 ///
 /// # Example
 ///
@@ -81,7 +152,7 @@ pub fn to_hex(felt: &FieldElement) -> String {
 /// let low = "0x000000000000000000000000000000001";
 /// let high = "0x000000000000000000000000000000000";
 /// let result = to_u256(low, high);
-pub fn to_u256(low: &str, high: &str) -> BigInteger256 {
+pub fn to_u256(low: &str, high: &str) -> Option<BigInteger256> {
     /// Helper function that converts a byte slice into a BigInteger256
     /// Uses a bit-by-bit conversion approach for precise control
     ///
@@ -117,15 +188,15 @@ pub fn to_u256(low: &str, high: &str) -> BigInteger256 {
 
     // Convert the hexadecimal strings to BigInteger256 values
     // Strip "0x" prefix before conversion
-    let mut output = from_byte_slice(&hex::decode(&(low)[2..]).unwrap()).unwrap();
-    let mut _high = from_byte_slice(&hex::decode(&(high)[2..]).unwrap()).unwrap();
+    let mut output = from_byte_slice(&hex::decode(low.strip_prefix("0x")?).ok()?)?;
+    let mut _high = from_byte_slice(&hex::decode(high.strip_prefix("0x")?).ok()?)?;
 
     // Shift high bits left by 128 positions
     _high.muln(128);
 
     // Combine high and low bits using addition
     let _ = output.add_with_carry(&_high);
-    output
+    Some(output)
 }
 
 pub async fn fetch_img_url(
@@ -192,16 +263,28 @@ pub fn parse_base64_image(metadata: &str) -> String {
     v["image"].as_str().unwrap_or("").to_string()
 }
 
+/// Http url of an image, `ipfs://` urls go through the preferred gateway currently up
 pub fn parse_image_url(config: &Config, url: &str) -> String {
-    if url.starts_with("ipfs://") {
-        url.replace("ipfs://", config.variables.ipfs_gateway.as_str())
-    } else {
-        url.to_string()
+    match url.strip_prefix("ipfs://") {
+        Some(path) => get_gateway_url(config, path).unwrap_or_else(|| url.to_string()),
+        None => url.to_string(),
+    }
+}
+
+/// Same as `parse_image_url`, but the gateway is checked to serve the cid and the next
+/// ones are tried when it doesn't
+pub async fn resolve_image_url(config: &Config, url: &str) -> String {
+    match url.strip_prefix("ipfs://") {
+        Some(path) => match find_serving_url(config, path).await {
+            Some(url) => url,
+            None => parse_image_url(config, url),
+        },
+        None => url.to_string(),
     }
 }
 
 pub async fn fetch_image_url(config: &Config, url: &str) -> String {
-    let parsed_url = parse_image_url(config, url);
+    let parsed_url = resolve_image_url(config, url).await;
     match reqwest::get(&parsed_url).await {
         Ok(resp) => match resp.json::<Value>().await {
             Ok(data) => parse_image_url(config, data["image"].as_str().unwrap_or("")),
@@ -210,3 +293,53 @@ pub async fn fetch_image_url(config: &Config, url: &str) -> String {
         Err(_) => "Error fetching data".to_string(),
     }
 }
+
+// historical lookups utils
+/// Returns a filter matching indexed documents that were live at the given block,
+/// i.e. written at or before it and not yet invalidated by a later write
+pub fn cursor_at_block(block: i64) -> Document {
+    doc! {
+        "_cursor.from": { "$lte": block },
+        "$or": [
+            { "_cursor.to": null },
+            { "_cursor.to": { "$exists": false } },
+            { "_cursor.to": { "$gt": block } },
+        ],
+    }
+}
+
+async fn get_block_timestamp(provider: &JsonRpcClient<HttpTransport>, block: u64) -> Result<u64> {
+    match provider
+        .get_block_with_tx_hashes(BlockId::Number(block))
+        .await
+        .map_err(|e| anyhow!("Failed to fetch block {}: {}", block, e))?
+    {
+        MaybePendingBlockWithTxHashes::Block(block) => Ok(block.timestamp),
+        MaybePendingBlockWithTxHashes::PendingBlock(block) => Ok(block.timestamp),
+    }
+}
+
+/// Finds the last block produced at or before `timestamp` by binary searching block headers
+pub async fn get_block_at_timestamp(
+    provider: &JsonRpcClient<HttpTransport>,
+    timestamp: u64,
+) -> Result<u64> {
+    let latest = provider
+        .block_number()
+        .await
+        .map_err(|e| anyhow!("Failed to fetch latest block number: {}", e))?;
+    if get_block_timestamp(provider, 0).await? > timestamp {
+        return Err(anyhow!("Timestamp is before the first block"));
+    }
+
+    let (mut low, mut high) = (0, latest);
+    while low < high {
+        let mid = low + (high - low + 1) / 2;
+        if get_block_timestamp(provider, mid).await? <= timestamp {
+            low = mid;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(low)
+}
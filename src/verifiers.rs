@@ -0,0 +1,161 @@
+use std::collections::BTreeMap;
+
+use anyhow::Result;
+use futures::future::join_all;
+use mongodb::bson::{doc, Document};
+use serde::Serialize;
+use starknet::core::{types::FieldElement, utils::parse_cairo_short_string};
+
+use crate::{
+    config::Contracts, freshness::get_freshness, models::AppState, storage::FindSpec, utils::to_hex,
+};
+
+/// Verification of a field by one of the configured verifier contracts
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct VerifiedField {
+    pub verifier: String,
+    // e.g. social or proof_of_personhood
+    pub kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extended_data: Option<Vec<String>>,
+    pub block: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verified_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_stale: Option<bool>,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct IdentityVerifierData {
+    pub id: String,
+    // by field name, e.g. twitter, then from the latest verification
+    pub fields: BTreeMap<String, Vec<VerifiedField>>,
+}
+
+/// Kind of a configured verifier, None for the contracts the instance doesn't trust
+pub fn get_verifier_kind(contracts: &Contracts, verifier: &FieldElement) -> Option<&'static str> {
+    if contracts.verifiers.contains(verifier) {
+        Some("social")
+    } else if *verifier == contracts.old_verifier {
+        Some("social_legacy")
+    } else if *verifier == contracts.pop_verifier {
+        Some("proof_of_personhood")
+    } else if *verifier == contracts.pp_verifier {
+        Some("profile_picture")
+    } else {
+        None
+    }
+}
+
+// short string of the field, e.g. twitter, the hex felt when it isn't printable
+fn get_field_name(field: &FieldElement) -> String {
+    match parse_cairo_short_string(field) {
+        Ok(name) if !name.is_empty() && name.chars().all(|c| c.is_ascii_graphic()) => name,
+        _ => to_hex(field),
+    }
+}
+
+fn to_strings(doc: &Document, key: &str) -> Option<Vec<String>> {
+    let values: Vec<String> = doc
+        .get_array(key)
+        .ok()?
+        .iter()
+        .filter_map(|value| value.as_str().map(String::from))
+        .collect();
+    Some(values).filter(|values| !values.is_empty())
+}
+
+/// Verifier data documents grouped by field, the ones of unknown verifiers and the empty ones
+/// are left out
+pub fn group_verifier_data(
+    contracts: &Contracts,
+    docs: &[Document],
+) -> BTreeMap<String, Vec<VerifiedField>> {
+    let mut fields: BTreeMap<String, Vec<VerifiedField>> = BTreeMap::new();
+    for doc in docs {
+        let (verifier, field) = match (
+            doc.get_str("verifier")
+                .ok()
+                .and_then(|verifier| FieldElement::from_hex_be(verifier).ok()),
+            doc.get_str("field")
+                .ok()
+                .and_then(|field| FieldElement::from_hex_be(field).ok()),
+        ) {
+            (Some(verifier), Some(field)) => (verifier, field),
+            _ => continue,
+        };
+        let kind = match get_verifier_kind(contracts, &verifier) {
+            Some(kind) => kind,
+            None => continue,
+        };
+        let data = doc.get_str("data").ok().map(String::from);
+        let extended_data = to_strings(doc, "extended_data");
+        if data.is_none() && extended_data.is_none() {
+            continue;
+        }
+        fields
+            .entry(get_field_name(&field))
+            .or_default()
+            .push(VerifiedField {
+                verifier: to_hex(&verifier),
+                kind,
+                data,
+                extended_data,
+                block: doc
+                    .get_document("_cursor")
+                    .and_then(|cursor| cursor.get_i64("from"))
+                    .ok(),
+                verified_at: None,
+                is_stale: None,
+            });
+    }
+    for verifications in fields.values_mut() {
+        verifications.sort_by(|a, b| b.block.cmp(&a.block));
+    }
+    fields
+}
+
+/// Every field of the identity verified by the configured verifiers, with their freshness
+pub async fn get_verifier_data(
+    state: &AppState,
+    id: &FieldElement,
+) -> Result<IdentityVerifierData> {
+    let contracts = &state.conf.contracts;
+    let verifiers: Vec<String> = contracts
+        .verifiers
+        .iter()
+        .chain([
+            &contracts.old_verifier,
+            &contracts.pop_verifier,
+            &contracts.pp_verifier,
+        ])
+        .map(to_hex)
+        .collect();
+    let docs = state
+        .storage
+        .find(
+            "id_verifier_data",
+            doc! { "id": to_hex(id), "_cursor.to": null, "verifier": { "$in": verifiers } },
+            FindSpec::default(),
+        )
+        .await?;
+    let mut fields = group_verifier_data(contracts, &docs);
+    for verifications in fields.values_mut() {
+        let freshness = join_all(
+            verifications
+                .iter()
+                .map(|field| get_freshness(state, field.block)),
+        )
+        .await;
+        for (field, (verified_at, is_stale)) in verifications.iter_mut().zip(freshness) {
+            field.verified_at = verified_at;
+            field.is_stale = is_stale;
+        }
+    }
+    Ok(IdentityVerifierData {
+        id: to_hex(id),
+        fields,
+    })
+}
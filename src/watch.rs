@@ -0,0 +1,78 @@
+use futures::TryStreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    options::FindOptions,
+};
+
+use crate::models::AppState;
+
+// collections holding data of the identity a domain points to
+const IDENTITY_COLLECTIONS: [&str; 3] = ["id_owners", "id_user_data", "id_verifier_data"];
+
+/// Snapshot of the indexed data behind a domain, any write to it changes the snapshot
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct DomainVersion {
+    // blocks at which the live documents were indexed
+    pub blocks: Vec<i64>,
+}
+
+impl DomainVersion {
+    /// Latest block which modified the domain
+    pub fn block(&self) -> Option<i64> {
+        self.blocks.iter().max().copied()
+    }
+}
+
+async fn get_live_blocks(
+    state: &AppState,
+    collection: &str,
+    filter: Document,
+) -> mongodb::error::Result<Vec<i64>> {
+    let options = FindOptions::builder()
+        .projection(doc! { "_cursor.from": 1 })
+        .build();
+    let docs: Vec<Document> = state
+        .starknetid_db
+        .collection::<Document>(collection)
+        .find(filter, options)
+        .await?
+        .try_collect()
+        .await?;
+    Ok(docs
+        .iter()
+        .filter_map(|doc| doc.get_document("_cursor").ok()?.get_i64("from").ok())
+        .collect())
+}
+
+pub async fn get_domain_version(
+    state: &AppState,
+    domain: &str,
+) -> mongodb::error::Result<DomainVersion> {
+    let domain_doc = state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .find_one(doc! { "domain": domain, "_cursor.to": null }, None)
+        .await?;
+    let domain_doc = match domain_doc {
+        Some(doc) => doc,
+        // not registered (anymore)
+        None => return Ok(DomainVersion::default()),
+    };
+
+    let mut blocks = Vec::new();
+    if let Ok(from) = domain_doc
+        .get_document("_cursor")
+        .and_then(|cursor| cursor.get_i64("from"))
+    {
+        blocks.push(from);
+    }
+    if let Ok(id) = domain_doc.get_str("id") {
+        for collection in IDENTITY_COLLECTIONS {
+            blocks.extend(
+                get_live_blocks(state, collection, doc! { "id": id, "_cursor.to": null }).await?,
+            );
+        }
+    }
+    blocks.sort_unstable();
+    Ok(DomainVersion { blocks })
+}
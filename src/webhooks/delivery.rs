@@ -0,0 +1,205 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use mongodb::bson::{doc, from_document, to_document};
+use reqwest::header::CONTENT_TYPE;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Webhooks,
+    metrics::labeled,
+    models::AppState,
+    storage::{FindSpec, Storage},
+    webhooks::{
+        get_webhook,
+        signing::{sign, SIGNATURE_HEADER, TIMESTAMP_HEADER},
+        Webhook, WebhookEvent,
+    },
+};
+
+pub const DELIVERIES_COLLECTION: &str = "webhook_deliveries";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    // out of attempts, or its webhook was deleted
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+/// Notification to send to a webhook, retried until it is acknowledged with a 2xx
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Delivery {
+    // `{webhook id}:{notification key}`, so a notification is queued once per webhook
+    #[serde(rename = "_id")]
+    pub id: String,
+    pub webhook_id: String,
+    pub event: WebhookEvent,
+    // json body, signed as sent
+    pub body: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+}
+
+/// Seconds before the next attempt, doubling from `retry_base_secs` up to `retry_max_secs`
+pub fn get_retry_delay(conf: &Webhooks, attempts: u32) -> i64 {
+    let factor = 1_i64 << attempts.saturating_sub(1).min(30);
+    conf.retry_base_secs
+        .saturating_mul(factor)
+        .min(conf.retry_max_secs)
+}
+
+/// Outcome of an attempt, a delivery failing `max_attempts` times is given up
+pub fn record_attempt(
+    delivery: &mut Delivery,
+    result: Result<(), String>,
+    now: i64,
+    conf: &Webhooks,
+) {
+    delivery.attempts += 1;
+    match result {
+        Ok(()) => {
+            delivery.status = DeliveryStatus::Delivered;
+            delivery.last_error = None;
+        }
+        Err(e) => {
+            delivery.last_error = Some(e);
+            if delivery.attempts >= conf.max_attempts {
+                delivery.status = DeliveryStatus::Failed;
+            } else {
+                delivery.next_attempt_at = now + get_retry_delay(conf, delivery.attempts);
+            }
+        }
+    }
+}
+
+/// Queues a delivery, false when it was queued already
+pub async fn enqueue(storage: &dyn Storage, delivery: &Delivery) -> Result<bool> {
+    if storage
+        .find_one(DELIVERIES_COLLECTION, doc! { "_id": &delivery.id })
+        .await?
+        .is_some()
+    {
+        return Ok(false);
+    }
+    storage
+        .insert_one(DELIVERIES_COLLECTION, to_document(delivery)?)
+        .await?;
+    Ok(true)
+}
+
+/// Last deliveries of a webhook, newest first
+pub async fn get_deliveries(
+    storage: &dyn Storage,
+    webhook_id: &str,
+    limit: i64,
+) -> Result<Vec<Delivery>> {
+    let spec = FindSpec {
+        sort: Some(doc! { "created_at": -1 }),
+        limit: Some(limit),
+        ..Default::default()
+    };
+    Ok(storage
+        .find(
+            DELIVERIES_COLLECTION,
+            doc! { "webhook_id": webhook_id },
+            spec,
+        )
+        .await?
+        .into_iter()
+        .filter_map(|doc| from_document(doc).ok())
+        .collect())
+}
+
+async fn send(
+    client: &reqwest::Client,
+    webhook: &Webhook,
+    body: &str,
+    now: i64,
+) -> Result<(), String> {
+    let response = client
+        .post(&webhook.url)
+        .header(CONTENT_TYPE, "application/json")
+        .header(TIMESTAMP_HEADER, now)
+        .header(SIGNATURE_HEADER, sign(&webhook.secret, now, body))
+        .body(body.to_string())
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+    if !response.status().is_success() {
+        return Err(format!("Status {}", response.status()));
+    }
+    Ok(())
+}
+
+/// Attempts the deliveries due, then drops the finished ones past their retention
+pub async fn deliver_due(state: &AppState) -> Result<usize> {
+    let storage = state.storage.as_ref();
+    let conf = &state.conf.webhooks;
+    let now = chrono::Utc::now().timestamp();
+    // redirects are refused, they could point back to the internal network
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(conf.timeout_ms))
+        .redirect(reqwest::redirect::Policy::none())
+        .build()?;
+    let due = storage
+        .find(
+            DELIVERIES_COLLECTION,
+            doc! { "status": "pending", "next_attempt_at": { "$lte": now } },
+            FindSpec {
+                sort: Some(doc! { "next_attempt_at": 1 }),
+                limit: Some(conf.batch_size),
+                ..Default::default()
+            },
+        )
+        .await?;
+    let attempted = due.len();
+    for doc in due {
+        let mut delivery: Delivery = from_document(doc)?;
+        match get_webhook(storage, &delivery.webhook_id).await? {
+            Some(webhook) => {
+                let result = send(&client, &webhook, &delivery.body, now).await;
+                record_attempt(&mut delivery, result, now, conf);
+            }
+            None => {
+                delivery.status = DeliveryStatus::Failed;
+                delivery.last_error = Some("The webhook was deleted".to_string());
+            }
+        }
+        state.metrics.add(
+            &labeled("webhook_attempts_total", "status", delivery.status.as_str()),
+            1,
+        );
+        storage
+            .replace_one(
+                DELIVERIES_COLLECTION,
+                doc! { "_id": &delivery.id },
+                to_document(&delivery)?,
+            )
+            .await?;
+    }
+    storage
+        .delete_many(
+            DELIVERIES_COLLECTION,
+            doc! {
+                "status": { "$in": ["delivered", "failed"] },
+                "created_at": { "$lt": now - conf.retention_secs },
+            },
+        )
+        .await?;
+    Ok(attempted)
+}
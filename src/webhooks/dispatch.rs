@@ -0,0 +1,288 @@
+use anyhow::Result;
+use mongodb::bson::{doc, oid::ObjectId, Document};
+use serde_json::{json, Value};
+
+use crate::{
+    expiring::get_expiring_domains,
+    filters::Filter,
+    history::{get_version_events, HistoryEvent},
+    models::AppState,
+    pagination::PageCursor,
+    storage::Storage,
+    webhooks::{
+        delivery::{enqueue, Delivery, DeliveryStatus},
+        get_webhooks, Webhook, WebhookEvent,
+    },
+    ws::feed::{get_latest_block, get_writes_after, Write},
+};
+
+const STATE_COLLECTION: &str = "webhook_state";
+const DISPATCHER_ID: &str = "dispatcher";
+
+/// Event of the indexed data a webhook may be called for
+#[derive(Debug, Clone, PartialEq)]
+pub struct WebhookNotification {
+    pub event: WebhookEvent,
+    // unique per write or expiry, so a notification isn't queued twice
+    pub key: String,
+    pub block: Option<i64>,
+    pub data: Value,
+}
+
+impl WebhookNotification {
+    /// Fields the filters of the webhooks are evaluated on
+    pub fn to_filter_value(&self) -> Value {
+        let mut value = self.data.clone();
+        value["event"] = Value::from(self.event.as_str());
+        value["block"] = Value::from(self.block);
+        value
+    }
+}
+
+/// Whether the webhook subscribed to the notification, a filter failing to parse matches nothing
+pub fn is_subscribed(webhook: &Webhook, notification: &WebhookNotification) -> bool {
+    if !webhook.events.contains(&notification.event) {
+        return false;
+    }
+    match &webhook.filter {
+        Some(filter) => Filter::parse(filter)
+            .is_ok_and(|filter| filter.matches(&notification.to_filter_value())),
+        None => true,
+    }
+}
+
+// previous version of an indexed document, closed by the write
+async fn get_previous(storage: &dyn Storage, write: &Write, key: &str) -> Result<Option<Document>> {
+    let value = match write.doc.get_str(key) {
+        Ok(value) => value,
+        Err(_) => return Ok(None),
+    };
+    storage
+        .find_one(
+            write.collection,
+            doc! { key: value, "_cursor.to": write.cursor.key },
+        )
+        .await
+}
+
+pub async fn get_domain_of(storage: &dyn Storage, id: &str) -> Result<Option<String>> {
+    Ok(storage
+        .find_one("domains", doc! { "id": id, "_cursor.to": null })
+        .await?
+        .and_then(|doc| doc.get_str("domain").ok().map(String::from)))
+}
+
+/// Notifications of an indexed write: new and transferred domains, identities changing
+/// hands and verifier data updates
+pub async fn get_write_notifications(
+    storage: &dyn Storage,
+    write: &Write,
+) -> Result<Vec<WebhookNotification>> {
+    let get = |key: &str| write.doc.get_str(key).ok().map(String::from);
+    let notification = |event: WebhookEvent, data: Value| WebhookNotification {
+        event,
+        key: format!("{}:{}", write.cursor.encode(), event.as_str()),
+        block: Some(write.cursor.key),
+        data,
+    };
+    let mut notifications = vec![];
+    match write.collection {
+        "domains" => {
+            let previous = get_previous(storage, write, "domain").await?;
+            for event in get_version_events(previous.as_ref(), &write.doc) {
+                match event {
+                    HistoryEvent::Registered { id, expiry } => notifications.push(notification(
+                        WebhookEvent::DomainRegistered,
+                        json!({ "domain": get("domain"), "id": id, "expiry": expiry }),
+                    )),
+                    HistoryEvent::Transferred { from_id, to_id } => {
+                        notifications.push(notification(
+                            WebhookEvent::DomainTransferred,
+                            json!({ "domain": get("domain"), "from_id": from_id, "to_id": to_id }),
+                        ))
+                    }
+                    _ => {}
+                }
+            }
+        }
+        "id_owners" => {
+            let previous = get_previous(storage, write, "id").await?;
+            let from = previous.and_then(|doc| doc.get_str("owner").ok().map(String::from));
+            let (id, to) = (get("id"), get("owner"));
+            // a mint has no previous owner
+            if let (Some(id), Some(from)) = (id, from) {
+                if Some(&from) != to.as_ref() {
+                    let domain = get_domain_of(storage, &id).await?;
+                    notifications.push(notification(
+                        WebhookEvent::IdentityTransferred,
+                        json!({
+                            "domain": domain,
+                            "id": id,
+                            "from": from,
+                            "to": to,
+                        }),
+                    ));
+                }
+            }
+        }
+        "id_verifier_data" => {
+            let domain = match get("id") {
+                Some(id) => get_domain_of(storage, &id).await?,
+                None => None,
+            };
+            notifications.push(notification(
+                WebhookEvent::VerifierDataUpdated,
+                json!({
+                    "domain": domain,
+                    "id": get("id"),
+                    "field": get("field"),
+                    "verifier": get("verifier"),
+                    "data": get("data"),
+                }),
+            ));
+        }
+        _ => {}
+    }
+    Ok(notifications)
+}
+
+/// Notifications of the domains which expired between `from` and `to`
+pub async fn get_expiry_notifications(
+    storage: &dyn Storage,
+    from: i64,
+    to: i64,
+    page_size: i64,
+) -> Result<Vec<WebhookNotification>> {
+    let mut notifications = vec![];
+    let mut after = None;
+    loop {
+        let (domains, next) =
+            get_expiring_domains(storage, from, to, after.as_ref(), page_size).await?;
+        notifications.extend(domains.into_iter().map(|domain| WebhookNotification {
+            event: WebhookEvent::DomainExpired,
+            key: format!("expired:{}:{}", domain.domain, domain.expiry),
+            block: None,
+            data: json!({
+                "domain": domain.domain,
+                "id": domain.id,
+                "owner": domain.owner,
+                "expiry": domain.expiry,
+            }),
+        }));
+        match next {
+            Some(next) => after = Some(next),
+            None => return Ok(notifications),
+        }
+    }
+}
+
+/// Deliveries of the notification to the webhooks subscribed to it
+pub fn get_deliveries(
+    webhooks: &[Webhook],
+    notification: &WebhookNotification,
+    now: i64,
+) -> Vec<Delivery> {
+    webhooks
+        .iter()
+        .filter(|webhook| is_subscribed(webhook, notification))
+        .map(|webhook| {
+            let id = format!("{}:{}", webhook.id, notification.key);
+            let body = json!({
+                "id": id,
+                "event": notification.event,
+                "block": notification.block,
+                "created_at": now,
+                "data": notification.data,
+            });
+            Delivery {
+                webhook_id: webhook.id.clone(),
+                event: notification.event,
+                body: body.to_string(),
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                next_attempt_at: now,
+                last_error: None,
+                created_at: now,
+                id,
+            }
+        })
+        .collect()
+}
+
+async fn enqueue_all(
+    storage: &dyn Storage,
+    webhooks: &[Webhook],
+    notification: &WebhookNotification,
+    now: i64,
+) -> Result<usize> {
+    let mut queued = 0;
+    for delivery in get_deliveries(webhooks, notification, now) {
+        if enqueue(storage, &delivery).await? {
+            queued += 1;
+        }
+    }
+    Ok(queued)
+}
+
+/// Queues a notification which isn't read from the indexed data, such as a freeze
+pub async fn queue_notification(
+    storage: &dyn Storage,
+    notification: &WebhookNotification,
+    now: i64,
+) -> Result<usize> {
+    let webhooks = get_webhooks(storage, None).await?;
+    enqueue_all(storage, &webhooks, notification, now).await
+}
+
+/// Queues the deliveries of the writes and expiries since the last run, the data indexed
+/// before the first run isn't notified
+pub async fn dispatch(state: &AppState) -> Result<usize> {
+    let storage = state.storage.as_ref();
+    let conf = &state.conf.webhooks;
+    let now = chrono::Utc::now().timestamp();
+    let saved = storage
+        .find_one(STATE_COLLECTION, doc! { "_id": DISPATCHER_ID })
+        .await?;
+    let cursor = match saved
+        .as_ref()
+        .and_then(|doc| doc.get_str("cursor").ok())
+        .and_then(PageCursor::decode)
+    {
+        Some(cursor) => cursor,
+        // after every write of the newest block
+        None => PageCursor {
+            key: get_latest_block(storage).await?.unwrap_or_default(),
+            id: ObjectId::from_bytes([0xff; 12]),
+        },
+    };
+    let expiry_checked_at = saved
+        .as_ref()
+        .and_then(|doc| doc.get_i64("expiry_checked_at").ok())
+        .unwrap_or(now);
+
+    let (writes, _) = get_writes_after(storage, &cursor, conf.batch_size).await?;
+    let mut notifications = vec![];
+    for write in &writes {
+        notifications.extend(get_write_notifications(storage, write).await?);
+    }
+    if now > expiry_checked_at {
+        notifications.extend(
+            get_expiry_notifications(storage, expiry_checked_at + 1, now, conf.batch_size).await?,
+        );
+    }
+
+    let webhooks = get_webhooks(storage, None).await?;
+    let mut queued = 0;
+    for notification in &notifications {
+        queued += enqueue_all(storage, &webhooks, notification, now).await?;
+    }
+    let cursor = writes.last().map_or(cursor, |write| write.cursor.clone());
+    storage
+        .upsert_one(
+            STATE_COLLECTION,
+            doc! { "_id": DISPATCHER_ID },
+            doc! { "cursor": cursor.encode(), "expiry_checked_at": now },
+        )
+        .await?;
+    Ok(queued)
+}
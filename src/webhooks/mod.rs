@@ -0,0 +1,207 @@
+//! Callbacks registered by the api keys, called with signed payloads when their domains are
+//! registered, transferred, expire, get new verifier data or are reported compromised
+
+pub mod delivery;
+pub mod dispatch;
+pub mod signing;
+
+use std::net::IpAddr;
+
+use anyhow::Result;
+use mongodb::bson::{doc, from_document, to_document};
+use rand::Rng;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    filters::Filter,
+    storage::{FindSpec, Storage},
+};
+use utoipa::ToSchema;
+
+pub const WEBHOOKS_COLLECTION: &str = "webhooks";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    DomainRegistered,
+    // the domain moved to another identity
+    DomainTransferred,
+    // the identity holding the domain changed hands
+    IdentityTransferred,
+    DomainExpired,
+    VerifierDataUpdated,
+    // its owner reported the identity compromised, or lifted the report
+    IdentityFrozen,
+    IdentityUnfrozen,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::DomainRegistered => "domain_registered",
+            WebhookEvent::DomainTransferred => "domain_transferred",
+            WebhookEvent::IdentityTransferred => "identity_transferred",
+            WebhookEvent::DomainExpired => "domain_expired",
+            WebhookEvent::VerifierDataUpdated => "verifier_data_updated",
+            WebhookEvent::IdentityFrozen => "identity_frozen",
+            WebhookEvent::IdentityUnfrozen => "identity_unfrozen",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Webhook {
+    pub id: String,
+    // name of the api key which registered it
+    pub owner: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    // expression the notifications must match, see `Filter`
+    #[serde(default)]
+    pub filter: Option<String>,
+    // key of the payload signatures, only shown when registering
+    pub secret: String,
+    pub created_at: i64,
+}
+
+/// Webhook as listed to its owner, without its secret
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct WebhookInfo {
+    pub id: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    pub filter: Option<String>,
+    pub created_at: i64,
+}
+
+impl From<Webhook> for WebhookInfo {
+    fn from(webhook: Webhook) -> Self {
+        WebhookInfo {
+            id: webhook.id,
+            url: webhook.url,
+            events: webhook.events,
+            filter: webhook.filter,
+            created_at: webhook.created_at,
+        }
+    }
+}
+
+pub fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast())
+        }
+        // ipv4 mapped addresses are checked as such, fc00::/7 and fe80::/10 are local
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.segments()[0] & 0xfe00 == 0xfc00
+                    || ip.segments()[0] & 0xffc0 == 0xfe80)
+            }
+        },
+    }
+}
+
+/// Callbacks are http urls of public hosts, so a key can't make the server call the
+/// internal network
+pub fn validate_url(url: &str) -> Result<(), String> {
+    let parsed = Url::parse(url).map_err(|e| format!("Invalid url: {}", e))?;
+    if !matches!(parsed.scheme(), "https" | "http") {
+        return Err("The url must use http or https".to_string());
+    }
+    let host = parsed.host_str().unwrap_or_default();
+    let public = match host.trim_matches(&['[', ']'][..]).parse::<IpAddr>() {
+        Ok(ip) => is_public_ip(ip),
+        Err(_) => {
+            !host.is_empty()
+                && host != "localhost"
+                && !host.ends_with(".localhost")
+                && !host.ends_with(".internal")
+        }
+    };
+    if !public {
+        return Err("The url must point to a public host".to_string());
+    }
+    Ok(())
+}
+
+/// Webhook of the api key, after checking its url, events and filter
+pub fn new_webhook(
+    owner: &str,
+    url: String,
+    events: Vec<WebhookEvent>,
+    filter: Option<String>,
+    now: i64,
+) -> Result<Webhook, String> {
+    validate_url(&url)?;
+    if events.is_empty() {
+        return Err("At least one event is required".to_string());
+    }
+    if let Some(filter) = &filter {
+        Filter::parse(filter).map_err(|e| format!("Invalid filter: {}", e))?;
+    }
+    let mut rng = rand::thread_rng();
+    Ok(Webhook {
+        id: hex::encode(rng.gen::<[u8; 12]>()),
+        owner: owner.to_string(),
+        url,
+        events,
+        filter,
+        secret: hex::encode(rng.gen::<[u8; 32]>()),
+        created_at: now,
+    })
+}
+
+pub async fn insert_webhook(storage: &dyn Storage, webhook: &Webhook) -> Result<()> {
+    storage
+        .insert_one(WEBHOOKS_COLLECTION, to_document(webhook)?)
+        .await
+}
+
+/// Webhooks of an api key, or all of them
+pub async fn get_webhooks(storage: &dyn Storage, owner: Option<&str>) -> Result<Vec<Webhook>> {
+    let filter = match owner {
+        Some(owner) => doc! { "owner": owner },
+        None => doc! {},
+    };
+    let spec = FindSpec {
+        sort: Some(doc! { "created_at": 1 }),
+        ..Default::default()
+    };
+    Ok(storage
+        .find(WEBHOOKS_COLLECTION, filter, spec)
+        .await?
+        .into_iter()
+        .filter_map(|doc| from_document(doc).ok())
+        .collect())
+}
+
+pub async fn get_webhook(storage: &dyn Storage, id: &str) -> Result<Option<Webhook>> {
+    Ok(storage
+        .find_one(WEBHOOKS_COLLECTION, doc! { "id": id })
+        .await?
+        .and_then(|doc| from_document(doc).ok()))
+}
+
+/// Deletes a webhook of the api key and its pending deliveries, false when it has none
+pub async fn delete_webhook(storage: &dyn Storage, owner: &str, id: &str) -> Result<bool> {
+    let deleted = storage
+        .delete_many(WEBHOOKS_COLLECTION, doc! { "id": id, "owner": owner })
+        .await?;
+    if deleted > 0 {
+        storage
+            .delete_many(
+                delivery::DELIVERIES_COLLECTION,
+                doc! { "webhook_id": id, "status": "pending" },
+            )
+            .await?;
+    }
+    Ok(deleted > 0)
+}
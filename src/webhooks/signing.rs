@@ -0,0 +1,33 @@
+use sha2::{Digest, Sha256};
+
+pub const SIGNATURE_HEADER: &str = "x-webhook-signature";
+pub const TIMESTAMP_HEADER: &str = "x-webhook-timestamp";
+const BLOCK_SIZE: usize = 64;
+
+/// HMAC-SHA256 of the message, RFC 2104
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut block = [0_u8; BLOCK_SIZE];
+    // longer keys are hashed first
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+    let mut inner = Sha256::new();
+    inner.update(block.map(|byte| byte ^ 0x36));
+    inner.update(message);
+    let mut outer = Sha256::new();
+    outer.update(block.map(|byte| byte ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+/// Signature header of a body sent at `timestamp`, receivers recompute it over
+/// `{timestamp}.{body}` with their secret and refuse stale timestamps to prevent replays
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    let message = format!("{}.{}", timestamp, body);
+    format!(
+        "sha256={}",
+        hex::encode(hmac_sha256(secret.as_bytes(), message.as_bytes()))
+    )
+}
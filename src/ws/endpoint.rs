@@ -0,0 +1,211 @@
+use crate::{
+    errors::{ApiError, ErrorCode},
+    filters::Filter,
+    models::AppState,
+    pagination::PageCursor,
+    utils::normalize_domain,
+    ws::{
+        feed::{get_backfill, ChangeEvent},
+        subscriptions::{ClientMessage, ServerMessage, Subscriptions},
+    },
+};
+use axum::{
+    extract::{
+        ws::{Message, WebSocket, WebSocketUpgrade},
+        State,
+    },
+    response::IntoResponse,
+};
+use axum_auto_routes::route;
+use mongodb::bson::{doc, Document};
+use std::sync::Arc;
+use tokio::sync::broadcast::error::RecvError;
+
+async fn send(socket: &mut WebSocket, message: &ServerMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).unwrap_or_default();
+    socket.send(Message::Text(text)).await
+}
+
+async fn get_domain_id(state: &AppState, domain: &str) -> Option<String> {
+    state
+        .starknetid_db
+        .collection::<Document>("domains")
+        .find_one(doc! { "domain": domain, "_cursor.to": null }, None)
+        .await
+        .ok()
+        .flatten()
+        .and_then(|doc| doc.get_str("id").ok().map(|id| id.to_string()))
+}
+
+async fn handle_message(
+    state: &AppState,
+    subscriptions: &mut Subscriptions,
+    replayed_until: &mut Option<PageCursor>,
+    text: &str,
+) -> Vec<ServerMessage> {
+    let message = match serde_json::from_str::<ClientMessage>(text) {
+        Ok(message) => message,
+        Err(e) => {
+            return vec![ServerMessage::Error {
+                message: format!("Invalid message: {}", e),
+            }]
+        }
+    };
+    match message {
+        ClientMessage::Subscribe {
+            domain,
+            address,
+            filter,
+        } => {
+            let filter = match filter.as_deref().map(Filter::parse).transpose() {
+                Ok(filter) => filter,
+                Err(e) => {
+                    return vec![ServerMessage::Error {
+                        message: format!("Invalid filter: {}", e),
+                    }]
+                }
+            };
+            let added =
+                domain.is_some() as usize + address.is_some() as usize + filter.is_some() as usize;
+            if subscriptions.len() + added > state.conf.ws.max_subscriptions {
+                return vec![ServerMessage::Error {
+                    message: format!(
+                        "At most {} subscriptions per connection",
+                        state.conf.ws.max_subscriptions
+                    ),
+                }];
+            }
+            if let Some(domain) = domain {
+                let domain = normalize_domain(&domain);
+                let id = get_domain_id(state, &domain).await;
+                subscriptions.add_domain(domain, id);
+            }
+            if let Some(address) = address {
+                subscriptions.add_address(&address);
+            }
+            if let Some(filter) = filter {
+                subscriptions.add_filter(filter);
+            }
+        }
+        ClientMessage::Unsubscribe {
+            domain,
+            address,
+            filter,
+        } => {
+            if let Some(domain) = domain {
+                subscriptions.remove_domain(&normalize_domain(&domain));
+            }
+            if let Some(address) = address {
+                subscriptions.remove_address(&address);
+            }
+            if let Some(filter) = filter {
+                subscriptions.remove_filter(&filter);
+            }
+        }
+        ClientMessage::Resume { cursor } => {
+            return resume(state, subscriptions, replayed_until, &cursor).await
+        }
+    }
+    vec![subscriptions.to_message()]
+}
+
+// the missed writes matching the subscriptions, then the end of the replay
+async fn resume(
+    state: &AppState,
+    subscriptions: &mut Subscriptions,
+    replayed_until: &mut Option<PageCursor>,
+    cursor: &str,
+) -> Vec<ServerMessage> {
+    let cursor = match PageCursor::decode(cursor) {
+        Some(cursor) => cursor,
+        None => {
+            return vec![ServerMessage::Error {
+                message: "Invalid cursor".to_string(),
+            }]
+        }
+    };
+    let backfill = match get_backfill(state.storage.as_ref(), &state.conf.ws, cursor).await {
+        Ok(backfill) => backfill,
+        Err(e) => {
+            return vec![ServerMessage::Error {
+                message: format!("Unable to replay the missed writes: {}", e),
+            }]
+        }
+    };
+    let mut messages: Vec<ServerMessage> = backfill
+        .events
+        .into_iter()
+        .filter(|event| subscriptions.matches(event))
+        .map(|event| ServerMessage::Change { event })
+        .collect();
+    messages.push(ServerMessage::Resumed {
+        replayed: messages.len(),
+        complete: backfill.complete,
+    });
+    if backfill.until.is_some() {
+        *replayed_until = backfill.until;
+    }
+    messages
+}
+
+// live events sent already by a replay
+fn is_replayed(event: &ChangeEvent, replayed_until: &Option<PageCursor>) -> bool {
+    match (
+        event.cursor.as_deref().and_then(PageCursor::decode),
+        replayed_until,
+    ) {
+        (Some(cursor), Some(until)) => (cursor.key, cursor.id) <= (until.key, until.id),
+        _ => false,
+    }
+}
+
+async fn handle_socket(mut socket: WebSocket, state: Arc<AppState>) {
+    let mut events = state.change_feed.subscribe();
+    let mut subscriptions = Subscriptions::default();
+    let mut replayed_until = None;
+    'connection: loop {
+        tokio::select! {
+            message = socket.recv() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let replies =
+                        handle_message(&state, &mut subscriptions, &mut replayed_until, &text).await;
+                    for reply in &replies {
+                        if send(&mut socket, reply).await.is_err() {
+                            break 'connection;
+                        }
+                    }
+                }
+                // pings are answered by the websocket layer
+                Some(Ok(Message::Ping(_) | Message::Pong(_) | Message::Binary(_))) => {}
+                Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+            },
+            event = events.recv() => {
+                let message = match event {
+                    Ok(event) if is_replayed(&event, &replayed_until) => continue,
+                    Ok(event) if subscriptions.matches(&event) => ServerMessage::Change { event },
+                    Ok(_) => continue,
+                    Err(RecvError::Lagged(missed)) => ServerMessage::Lagged { missed },
+                    Err(RecvError::Closed) => break,
+                };
+                if send(&mut socket, &message).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+#[route(get, "/ws", crate::ws::endpoint)]
+pub async fn handler(
+    State(state): State<Arc<AppState>>,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    if !state.conf.ws.enabled {
+        return ApiError::new(
+            ErrorCode::FeatureDisabled,
+            "Websocket subscriptions are disabled",
+        )
+        .into_response();
+    }
+    upgrade.on_upgrade(move |socket| handle_socket(socket, state))
+}
@@ -0,0 +1,254 @@
+use anyhow::Result;
+use futures::StreamExt;
+use mongodb::{
+    bson::{doc, Document},
+    change_stream::event::OperationType,
+};
+use serde::Serialize;
+use tokio::{
+    sync::broadcast,
+    time::{sleep, Duration},
+};
+
+use crate::{
+    config::Ws,
+    models::AppState,
+    pagination::PageCursor,
+    storage::{FindSpec, Storage},
+};
+
+// events buffered for the slowest client before it is told it lagged behind
+const CHANNEL_CAPACITY: usize = 1024;
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+pub const WATCHED_COLLECTIONS: [&str; 4] =
+    ["domains", "id_owners", "id_user_data", "id_verifier_data"];
+const STARKNET_FIELD: &str = "0x000000000000000000000000000000000000000000000000737461726b6e6574";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    // target address or expiry of a domain
+    Domain,
+    Owner,
+    // starknet address written in the user data of an identity
+    Resolution,
+    VerifierData,
+}
+
+/// Indexed write to the collection of `kind`
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    pub kind: ChangeKind,
+    pub id: Option<String>,
+    pub domain: Option<String>,
+    // addresses the write points to or comes from
+    pub addresses: Vec<String>,
+    pub block: Option<i64>,
+    // position a reconnecting client resumes after
+    pub cursor: Option<String>,
+}
+
+pub struct ChangeFeed {
+    sender: broadcast::Sender<ChangeEvent>,
+}
+
+impl Default for ChangeFeed {
+    fn default() -> Self {
+        Self {
+            sender: broadcast::channel(CHANNEL_CAPACITY).0,
+        }
+    }
+}
+
+impl ChangeFeed {
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn publish(&self, event: ChangeEvent) {
+        // fails when no client is connected
+        let _ = self.sender.send(event);
+    }
+}
+
+/// Event of a document inserted by the indexer, None for the writes clients don't see
+pub fn to_change_event(collection: &str, doc: &Document) -> Option<ChangeEvent> {
+    let get = |key: &str| doc.get_str(key).ok().map(|value| value.to_string());
+    let (kind, domain, mut addresses): (_, _, Vec<String>) = match collection {
+        "domains" => (
+            ChangeKind::Domain,
+            get("domain"),
+            [get("legacy_address"), get("rev_address")]
+                .into_iter()
+                .flatten()
+                .collect(),
+        ),
+        "id_owners" => (ChangeKind::Owner, None, get("owner").into_iter().collect()),
+        "id_user_data" if doc.get_str("field").ok() == Some(STARKNET_FIELD) => (
+            ChangeKind::Resolution,
+            None,
+            get("data").into_iter().collect(),
+        ),
+        "id_verifier_data" => (ChangeKind::VerifierData, None, vec![]),
+        _ => return None,
+    };
+    // a domain usually points to the address it is the main domain of
+    addresses.dedup();
+    Some(ChangeEvent {
+        kind,
+        id: get("id"),
+        domain,
+        addresses,
+        block: doc
+            .get_document("_cursor")
+            .and_then(|cursor| cursor.get_i64("from"))
+            .ok(),
+        cursor: PageCursor::from_document(doc, "_cursor.from").map(|cursor| cursor.encode()),
+    })
+}
+
+/// Writes replayed to a resuming client
+#[derive(Debug, Default)]
+pub struct Backfill {
+    pub events: Vec<ChangeEvent>,
+    // last write read, the live events up to it were replayed already
+    pub until: Option<PageCursor>,
+    // false when writes were too old or too many to be replayed
+    pub complete: bool,
+}
+
+fn by_block() -> Option<Document> {
+    Some(doc! { "_cursor.from": 1, "_id": 1 })
+}
+
+/// Newest block written to the watched collections
+pub async fn get_latest_block(storage: &dyn Storage) -> Result<Option<i64>> {
+    let mut latest = None;
+    for collection in WATCHED_COLLECTIONS {
+        let newest = FindSpec {
+            sort: Some(doc! { "_cursor.from": -1 }),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let docs = storage.find(collection, doc! {}, newest).await?;
+        let block = docs
+            .first()
+            .and_then(|doc| PageCursor::from_document(doc, "_cursor.from"))
+            .map(|cursor| cursor.key);
+        latest = latest.max(block);
+    }
+    Ok(latest)
+}
+
+/// Indexed document of a watched collection
+#[derive(Debug, Clone)]
+pub struct Write {
+    pub cursor: PageCursor,
+    pub collection: &'static str,
+    pub doc: Document,
+}
+
+/// Writes to the watched collections after the cursor, in order, and whether all of them
+/// were read
+pub async fn get_writes_after(
+    storage: &dyn Storage,
+    after: &PageCursor,
+    limit: i64,
+) -> Result<(Vec<Write>, bool)> {
+    let mut writes = vec![];
+    // writes past the last one read from a collection reaching the limit are left for later
+    let mut until: Option<PageCursor> = None;
+    for collection in WATCHED_COLLECTIONS {
+        let spec = FindSpec {
+            sort: by_block(),
+            limit: Some(limit),
+            ..Default::default()
+        };
+        let docs = storage
+            .find(collection, after.after("_cursor.from"), spec)
+            .await?;
+        if docs.len() as i64 >= limit {
+            let last = docs
+                .last()
+                .and_then(|doc| PageCursor::from_document(doc, "_cursor.from"));
+            until = match (until, last) {
+                (Some(until), Some(last)) => Some(if (last.key, last.id) < (until.key, until.id) {
+                    last
+                } else {
+                    until
+                }),
+                (until, last) => until.or(last),
+            };
+        }
+        for doc in docs {
+            if let Some(cursor) = PageCursor::from_document(&doc, "_cursor.from") {
+                writes.push(Write {
+                    cursor,
+                    collection,
+                    doc,
+                });
+            }
+        }
+    }
+    writes.sort_by_key(|write| (write.cursor.key, write.cursor.id));
+    let mut complete = until.is_none();
+    if let Some(until) = until {
+        writes.retain(|write| (write.cursor.key, write.cursor.id) <= (until.key, until.id));
+    }
+    if writes.len() as i64 > limit {
+        writes.truncate(limit as usize);
+        complete = false;
+    }
+    Ok((writes, complete))
+}
+
+/// Writes to the watched collections after the cursor, in order, within the backfill window
+pub async fn get_backfill(storage: &dyn Storage, conf: &Ws, after: PageCursor) -> Result<Backfill> {
+    let latest = get_latest_block(storage).await?;
+    let (after, clamped) = after.clamp(latest, conf.max_backfill_blocks);
+    let (writes, complete) = get_writes_after(storage, &after, conf.max_backfill_events).await?;
+    Ok(Backfill {
+        until: writes.last().map(|write| write.cursor.clone()),
+        events: writes
+            .iter()
+            .filter_map(|write| to_change_event(write.collection, &write.doc))
+            .collect(),
+        complete: complete && !clamped,
+    })
+}
+
+async fn watch_collection(state: &AppState, collection: &str) -> mongodb::error::Result<()> {
+    // the indexer inserts a new document for each write and closes the previous one
+    let pipeline = [doc! { "$match": { "operationType": "insert" } }];
+    let mut stream = state
+        .starknetid_db
+        .collection::<Document>(collection)
+        .watch(pipeline, None)
+        .await?;
+    while let Some(change) = stream.next().await {
+        let change = change?;
+        if change.operation_type != OperationType::Insert {
+            continue;
+        }
+        if let Some(event) = change
+            .full_document
+            .as_ref()
+            .and_then(|doc| to_change_event(collection, doc))
+        {
+            state.change_feed.publish(event);
+        }
+    }
+    Ok(())
+}
+
+/// Publishes the writes of one collection to the feed, the stream is reopened when it fails
+pub async fn run(state: &AppState, collection: &'static str) {
+    loop {
+        if let Err(e) = watch_collection(state, collection).await {
+            state
+                .logger
+                .warning(format!("ws: change stream on {} failed: {}", collection, e));
+        }
+        sleep(RETRY_DELAY).await;
+    }
+}
@@ -0,0 +1,6 @@
+//! Push notifications of the indexed writes to the domains and addresses websocket clients
+//! subscribed to, fed by change streams on the identity collections
+
+pub mod endpoint;
+pub mod feed;
+pub mod subscriptions;
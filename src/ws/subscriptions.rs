@@ -0,0 +1,152 @@
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use starknet::core::types::FieldElement;
+
+use crate::{filters::Filter, utils::to_hex, ws::feed::ChangeEvent};
+
+#[derive(Deserialize, Debug, PartialEq)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe {
+        domain: Option<String>,
+        address: Option<FieldElement>,
+        // expression the events are matched against, see `Filter`
+        filter: Option<String>,
+    },
+    Unsubscribe {
+        domain: Option<String>,
+        address: Option<FieldElement>,
+        filter: Option<String>,
+    },
+    // replays the writes matching the subscriptions since the last cursor received
+    Resume {
+        cursor: String,
+    },
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Subscribed {
+        domains: Vec<String>,
+        addresses: Vec<String>,
+        filters: Vec<String>,
+    },
+    Change {
+        event: ChangeEvent,
+    },
+    // events were dropped, the client should resume from its last cursor
+    Lagged {
+        missed: u64,
+    },
+    // end of the replayed writes, when not complete the client should fetch the state of its
+    // subscriptions again
+    Resumed {
+        replayed: usize,
+        complete: bool,
+    },
+    Error {
+        message: String,
+    },
+}
+
+/// Fields of the event the filters are evaluated on, its kind is also its `event`
+pub fn to_filter_value(event: &ChangeEvent) -> Value {
+    let mut value = serde_json::to_value(event).unwrap_or_default();
+    value["event"] = value["kind"].clone();
+    value
+}
+
+/// Domains, addresses and filters watched by a connection
+#[derive(Default, Debug)]
+pub struct Subscriptions {
+    // identity each domain pointed to when last seen
+    domains: HashMap<String, Option<String>>,
+    addresses: HashSet<String>,
+    filters: Vec<Filter>,
+}
+
+impl Subscriptions {
+    pub fn len(&self) -> usize {
+        self.domains.len() + self.addresses.len() + self.filters.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn add_domain(&mut self, domain: String, id: Option<String>) {
+        self.domains.insert(domain, id);
+    }
+
+    pub fn add_address(&mut self, address: &FieldElement) {
+        self.addresses.insert(to_hex(address));
+    }
+
+    pub fn remove_domain(&mut self, domain: &str) {
+        self.domains.remove(domain);
+    }
+
+    pub fn remove_address(&mut self, address: &FieldElement) {
+        self.addresses.remove(&to_hex(address));
+    }
+
+    pub fn add_filter(&mut self, filter: Filter) {
+        if !self.filters.contains(&filter) {
+            self.filters.push(filter);
+        }
+    }
+
+    pub fn remove_filter(&mut self, source: &str) {
+        self.filters
+            .retain(|filter| filter.source() != source.trim());
+    }
+
+    /// Whether the event concerns a subscription, a domain moved to another identity is followed
+    pub fn matches(&mut self, event: &ChangeEvent) -> bool {
+        if let Some(domain) = &event.domain {
+            if let Some(id) = self.domains.get_mut(domain) {
+                *id = event.id.clone();
+                return true;
+            }
+        }
+        let id_matches = match &event.id {
+            Some(event_id) => self
+                .domains
+                .values()
+                .any(|id| id.as_deref() == Some(event_id.as_str())),
+            None => false,
+        };
+        if id_matches
+            || event
+                .addresses
+                .iter()
+                .any(|address| self.addresses.contains(address))
+        {
+            return true;
+        }
+        if self.filters.is_empty() {
+            return false;
+        }
+        let value = to_filter_value(event);
+        self.filters.iter().any(|filter| filter.matches(&value))
+    }
+
+    pub fn to_message(&self) -> ServerMessage {
+        let mut domains: Vec<String> = self.domains.keys().cloned().collect();
+        let mut addresses: Vec<String> = self.addresses.iter().cloned().collect();
+        domains.sort();
+        addresses.sort();
+        ServerMessage::Subscribed {
+            domains,
+            addresses,
+            filters: self
+                .filters
+                .iter()
+                .map(|filter| filter.source().to_string())
+                .collect(),
+        }
+    }
+}
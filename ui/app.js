@@ -0,0 +1,37 @@
+// lookup page of the embedded ui, it only talks to the api serving it
+const status = document.getElementById("status");
+const result = document.getElementById("result");
+
+fetch("/")
+  .then((response) => response.text())
+  .then((text) => {
+    status.textContent = text;
+    status.className = "status ok";
+  })
+  .catch(() => {
+    status.textContent = "The server is unreachable";
+    status.className = "status error";
+  });
+
+document.getElementById("lookup").addEventListener("submit", async (event) => {
+  event.preventDefault();
+  const query = document.getElementById("query").value.trim();
+  // addresses are resolved to their main domain, anything else is read as a domain
+  const url = /^0x[0-9a-fA-F]+$/.test(query)
+    ? `/addr_to_domain?addr=${encodeURIComponent(query)}`
+    : `/domain_to_addr?domain=${encodeURIComponent(query)}`;
+  try {
+    const response = await fetch(url);
+    const text = await response.text();
+    let body = text;
+    try {
+      body = JSON.stringify(JSON.parse(text), null, 2);
+    } catch (_) {}
+    result.textContent = body;
+    result.className = response.ok ? "" : "error";
+  } catch (e) {
+    result.textContent = `Request failed: ${e}`;
+    result.className = "error";
+  }
+  result.hidden = false;
+});